@@ -0,0 +1,334 @@
+use crate::app::CurrentView;
+use crate::common::export::ExportFormat;
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// A user intent, decoupled from the key that triggered it.
+///
+/// Keys are translated with [`keymap`] and applied with `App::dispatch`, which
+/// routes each action to the reducer for the current view.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Action {
+    Quit,
+    /// Leave the current view or close its overlay
+    Back,
+    NextRow,
+    PrevRow,
+    NextTab,
+    PrevTab,
+    /// Open the selected row (Enter)
+    Select,
+    /// Toggle or activate the selected row (Space)
+    ToggleSelect,
+    Edit,
+    Refresh,
+    OpenQuickActions,
+    ToggleDeviceVariables,
+    /// Apply a variable template to the site
+    ApplyTemplate,
+    /// Copy the marked (or all) site variables to another site
+    CopyVariables,
+    /// Delete the selected row (a site or account variable)
+    Delete,
+    OpenSearch,
+    OpenProfileSwitcher,
+    OpenLogViewer,
+    OpenErrorHistory,
+    /// Show the account's components
+    OpenComponents,
+    /// Open the account-wide variables view
+    OpenAccountVariables,
+    /// List the devices of the account by tag
+    OpenTags,
+    /// Edit the open device's tags
+    EditTags,
+    /// Show the saved device collections
+    OpenCollections,
+    /// Add the selected (or marked) devices to a collection
+    AddToCollection,
+    /// Show the recently viewed devices
+    OpenRecentDevices,
+    /// Show the antivirus scans started this session
+    OpenScans,
+    /// Open the selected site or device in the Datto RMM web console
+    OpenInWeb,
+    /// Show the alerts and incidents new since the last session
+    OpenNotifications,
+    /// Read the site's notes in full
+    ViewNotes,
+    /// Start or stop watching the open device's online status
+    ToggleWatch,
+    /// Run a configured remote access command against the open device
+    Launch,
+    /// Scroll the focused pane of a view down a few lines (or up)
+    ScrollDown,
+    ScrollUp,
+    /// Move the focus to the view's other scrollable pane
+    SwitchPane,
+    /// Show the configuration and auth state of each integration
+    OpenIntegrations,
+    /// Authenticate the selected integration again
+    Reauthenticate,
+    /// Authenticate every configured integration again, e.g. after rotating keys
+    ReauthenticateAll,
+    /// Open the next device of the parent site's device list
+    NextDevice,
+    /// Open the previous device of the parent site's device list
+    PrevDevice,
+    /// Move to the next column of a grid (the site UDF matrix)
+    NextColumn,
+    PrevColumn,
+    /// Pick which columns a grid shows
+    ChooseColumns,
+    /// Sort the current table by its next column
+    CycleSort,
+    /// Flip the direction of the current table's sort
+    ReverseSort,
+    /// Start typing a filter for the current table
+    OpenFilter,
+    /// Hide or show sites without devices
+    ToggleHideEmptySites,
+    /// Pin the selected site to the top of the site list (or unpin it)
+    TogglePin,
+    /// Show the sites of the other accounts in the site list too (or stop)
+    ToggleMergedSites,
+    OpenReports,
+    /// Jump straight to the offline servers watchlist
+    OpenWatchlist,
+    /// Mark the selected alert as seen (or clear the mark)
+    Acknowledge,
+    /// Keep fetching the selected job output while the job runs (or stop)
+    FollowOutput,
+    /// Run the open job's component again on the same device
+    Rerun,
+    /// Cancel the selected scheduled job before it runs
+    CancelJob,
+    /// Delete the open site once it has no devices
+    DeleteSite,
+    /// Select the n-th (0-based) entry of a numbered list, e.g. a job component
+    JumpTo(usize),
+    /// Write the current report to a file
+    Export(ExportFormat),
+    /// Widen the current report's range (e.g. warranty days)
+    Increase,
+    /// Narrow the current report's range
+    Decrease,
+}
+
+/// Maps a key to an action for `view`. Popups and text inputs handle their own keys
+/// before this is consulted.
+pub fn keymap(view: CurrentView, key: KeyEvent) -> Option<Action> {
+    let global = match key.code {
+        KeyCode::Char('L') => Some(Action::OpenLogViewer),
+        KeyCode::Char('E') => Some(Action::OpenErrorHistory),
+        KeyCode::Char('A') => Some(Action::ReauthenticateAll),
+        KeyCode::Char('h') => Some(Action::OpenRecentDevices),
+        KeyCode::Char('V') => Some(Action::OpenScans),
+        KeyCode::Char('b') => Some(Action::OpenNotifications),
+        KeyCode::Char('W') => Some(Action::OpenInWeb),
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::NextRow),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::PrevRow),
+        KeyCode::Enter => Some(Action::Select),
+        KeyCode::Char('s') => Some(Action::CycleSort),
+        KeyCode::Char('S') => Some(Action::ReverseSort),
+        _ => None,
+    };
+    if global.is_some() {
+        return global;
+    }
+
+    match view {
+        CurrentView::List => match key.code {
+            KeyCode::Char('q') => Some(Action::Quit),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            // 'p' pins sites here; the profile switcher moves to 'P'
+            KeyCode::Char('p') => Some(Action::TogglePin),
+            KeyCode::Char('P') => Some(Action::OpenProfileSwitcher),
+            KeyCode::Char('M') => Some(Action::ToggleMergedSites),
+            // '/' filters the site list here; device search moves to 'd'
+            KeyCode::Char('/') => Some(Action::OpenFilter),
+            KeyCode::Char('d') => Some(Action::OpenSearch),
+            KeyCode::Char('H') => Some(Action::ToggleHideEmptySites),
+            KeyCode::Char('R') => Some(Action::OpenReports),
+            KeyCode::Char('o') => Some(Action::OpenWatchlist),
+            KeyCode::Char('c') => Some(Action::OpenComponents),
+            KeyCode::Char('I') => Some(Action::OpenIntegrations),
+            KeyCode::Char('v') => Some(Action::OpenAccountVariables),
+            KeyCode::Char('T') => Some(Action::OpenTags),
+            KeyCode::Char('C') => Some(Action::OpenCollections),
+            _ => None,
+        },
+        CurrentView::Tags => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::Back),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            KeyCode::Tab | KeyCode::BackTab => Some(Action::SwitchPane),
+            KeyCode::Char('+') => Some(Action::AddToCollection),
+            _ => None,
+        },
+        CurrentView::Collections => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::Back),
+            KeyCode::Tab | KeyCode::BackTab => Some(Action::SwitchPane),
+            KeyCode::Char('r') => Some(Action::OpenQuickActions),
+            KeyCode::Char('x') => Some(Action::Delete),
+            _ => None,
+        },
+        CurrentView::AccountVariables => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::Back),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            KeyCode::Char('x') => Some(Action::Delete),
+            _ => None,
+        },
+        CurrentView::Integrations => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::Back),
+            KeyCode::Char('a') => Some(Action::Reauthenticate),
+            _ => None,
+        },
+        CurrentView::Components => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::Back),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            KeyCode::Char('/') => Some(Action::OpenFilter),
+            // Stars here, as pinning does on the site list
+            KeyCode::Char('f') => Some(Action::TogglePin),
+            _ => None,
+        },
+        CurrentView::Report => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Some(Action::Back),
+            KeyCode::Tab => Some(Action::NextTab),
+            KeyCode::BackTab => Some(Action::PrevTab),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            KeyCode::Char('x') => Some(Action::Export(ExportFormat::Csv)),
+            KeyCode::Char('X') => Some(Action::Export(ExportFormat::Json)),
+            KeyCode::Char('+') | KeyCode::Char('=') => Some(Action::Increase),
+            KeyCode::Char('-') => Some(Action::Decrease),
+            _ => None,
+        },
+        CurrentView::Detail | CurrentView::DeviceDetail | CurrentView::ActivityDetail => {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => Some(Action::Back),
+                KeyCode::Char('/') => Some(Action::OpenSearch),
+                KeyCode::Tab => Some(Action::NextTab),
+                KeyCode::BackTab => Some(Action::PrevTab),
+                KeyCode::Char(' ') => Some(Action::ToggleSelect),
+                KeyCode::Char('e') => Some(Action::Edit),
+                KeyCode::Char('r') => Some(Action::OpenQuickActions),
+                KeyCode::Char('v') => Some(Action::ToggleDeviceVariables),
+                KeyCode::Char('f') => Some(Action::OpenFilter),
+                KeyCode::Char('a') => Some(Action::Acknowledge),
+                KeyCode::Char('n') if view == CurrentView::DeviceDetail => Some(Action::NextDevice),
+                KeyCode::Char('N') if view == CurrentView::DeviceDetail => Some(Action::PrevDevice),
+                KeyCode::Char('w') if view == CurrentView::DeviceDetail => Some(Action::ToggleWatch),
+                KeyCode::Char('l') if view == CurrentView::DeviceDetail => Some(Action::Launch),
+                KeyCode::Char('t') if view == CurrentView::DeviceDetail => Some(Action::EditTags),
+                KeyCode::Char('+') if view != CurrentView::ActivityDetail => {
+                    Some(Action::AddToCollection)
+                }
+                KeyCode::PageDown if view == CurrentView::DeviceDetail => Some(Action::ScrollDown),
+                KeyCode::PageUp if view == CurrentView::DeviceDetail => Some(Action::ScrollUp),
+                KeyCode::Char('p') if view == CurrentView::DeviceDetail => Some(Action::SwitchPane),
+                KeyCode::Right if view == CurrentView::Detail => Some(Action::NextColumn),
+                KeyCode::Left if view == CurrentView::Detail => Some(Action::PrevColumn),
+                KeyCode::Char('c') if view == CurrentView::Detail => Some(Action::ChooseColumns),
+                KeyCode::Char('t') if view == CurrentView::Detail => Some(Action::ApplyTemplate),
+                KeyCode::Char('C') if view == CurrentView::Detail => Some(Action::CopyVariables),
+                KeyCode::Char('x') if view == CurrentView::Detail => Some(Action::Delete),
+                KeyCode::Char('o') if view == CurrentView::Detail => Some(Action::ViewNotes),
+                KeyCode::Char('X') if view == CurrentView::Detail => Some(Action::DeleteSite),
+                KeyCode::Char('F') if view == CurrentView::ActivityDetail => Some(Action::FollowOutput),
+                KeyCode::Char('R') if view == CurrentView::ActivityDetail => Some(Action::Rerun),
+                KeyCode::Char('c') if view != CurrentView::Detail => Some(Action::CancelJob),
+                KeyCode::Char(c @ '1'..='9') if view == CurrentView::ActivityDetail => {
+                    Some(Action::JumpTo(c as usize - '1' as usize))
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Checks each (view, key, action) binding, naming the view and key of
+    /// the first one that differs.
+    fn assert_bindings(bindings: &[(CurrentView, KeyCode, Option<Action>)]) {
+        for (view, code, expected) in bindings {
+            assert_eq!(keymap(*view, key(*code)), *expected, "{:?} in {:?}", code, view);
+        }
+    }
+
+    #[test]
+    fn test_keys_shared_by_views() {
+        assert_bindings(&[
+            (CurrentView::List, KeyCode::Char('q'), Some(Action::Quit)),
+            (CurrentView::Detail, KeyCode::Char('q'), Some(Action::Back)),
+            (CurrentView::List, KeyCode::Char('r'), Some(Action::Refresh)),
+            (CurrentView::List, KeyCode::Down, Some(Action::NextRow)),
+            (CurrentView::List, KeyCode::Esc, None),
+            (CurrentView::Report, KeyCode::Char('h'), Some(Action::OpenRecentDevices)),
+            (CurrentView::Report, KeyCode::Char('W'), Some(Action::OpenInWeb)),
+            (CurrentView::Components, KeyCode::Char('b'), Some(Action::OpenNotifications)),
+            (CurrentView::DeviceDetail, KeyCode::Char('A'), Some(Action::ReauthenticateAll)),
+        ]);
+    }
+
+    #[test]
+    fn test_site_list_keys() {
+        assert_bindings(&[
+            (CurrentView::List, KeyCode::Char('/'), Some(Action::OpenFilter)),
+            (CurrentView::List, KeyCode::Char('M'), Some(Action::ToggleMergedSites)),
+            (CurrentView::List, KeyCode::Char('v'), Some(Action::OpenAccountVariables)),
+            (CurrentView::List, KeyCode::Char('T'), Some(Action::OpenTags)),
+            (CurrentView::List, KeyCode::Char('C'), Some(Action::OpenCollections)),
+        ]);
+    }
+
+    #[test]
+    fn test_site_detail_keys() {
+        assert_bindings(&[
+            (CurrentView::Detail, KeyCode::Char('/'), Some(Action::OpenSearch)),
+            (CurrentView::Detail, KeyCode::Char('x'), Some(Action::Delete)),
+            (CurrentView::Detail, KeyCode::Char('X'), Some(Action::DeleteSite)),
+            (CurrentView::Detail, KeyCode::Char('t'), Some(Action::ApplyTemplate)),
+            (CurrentView::Detail, KeyCode::Char('o'), Some(Action::ViewNotes)),
+            (CurrentView::Detail, KeyCode::PageDown, None),
+        ]);
+    }
+
+    #[test]
+    fn test_device_detail_keys() {
+        assert_bindings(&[
+            (CurrentView::DeviceDetail, KeyCode::Char('r'), Some(Action::OpenQuickActions)),
+            (CurrentView::DeviceDetail, KeyCode::Char('V'), Some(Action::OpenScans)),
+            (CurrentView::DeviceDetail, KeyCode::Char('x'), None),
+            (CurrentView::DeviceDetail, KeyCode::Char('t'), Some(Action::EditTags)),
+            (CurrentView::DeviceDetail, KeyCode::Char('+'), Some(Action::AddToCollection)),
+            (CurrentView::DeviceDetail, KeyCode::Char('w'), Some(Action::ToggleWatch)),
+            (CurrentView::DeviceDetail, KeyCode::Char('3'), None),
+            (CurrentView::DeviceDetail, KeyCode::Char('c'), Some(Action::CancelJob)),
+            (CurrentView::DeviceDetail, KeyCode::PageDown, Some(Action::ScrollDown)),
+        ]);
+    }
+
+    #[test]
+    fn test_activity_detail_keys() {
+        assert_bindings(&[
+            (CurrentView::ActivityDetail, KeyCode::Char('3'), Some(Action::JumpTo(2))),
+            (CurrentView::ActivityDetail, KeyCode::Char('R'), Some(Action::Rerun)),
+            (CurrentView::ActivityDetail, KeyCode::Char('+'), None),
+        ]);
+    }
+
+    #[test]
+    fn test_variables_tags_and_collections_keys() {
+        assert_bindings(&[
+            (CurrentView::AccountVariables, KeyCode::Char('x'), Some(Action::Delete)),
+            (CurrentView::Tags, KeyCode::Tab, Some(Action::SwitchPane)),
+            (CurrentView::Collections, KeyCode::Char('r'), Some(Action::OpenQuickActions)),
+        ]);
+    }
+}
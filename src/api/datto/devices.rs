@@ -1,205 +1,123 @@
 use super::DattoClient;
-use crate::api::datto::types::{DevicesResponse, SoftwareResponse, Udf};
+use crate::api::datto::types::{
+    Device, DevicesResponse, OpenAlertsResponse, SoftwareResponse, UdfUpdate, UpdateDeviceRequest,
+};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Method;
 
-pub(crate) trait DevicesApi {
+#[async_trait]
+pub trait DevicesApi: Send + Sync {
     async fn get_devices(&self, site_uid: &str, page: i32, max: i32) -> Result<DevicesResponse>;
-    async fn search_devices(&self, hostname: &str) -> Result<DevicesResponse>;
-    async fn update_device_udf(&self, device_uid: &str, udf: &Udf) -> Result<()>;
+    /// One page of the devices whose hostname contains `hostname`.
+    async fn search_devices(&self, hostname: &str, page: i32, max: i32) -> Result<DevicesResponse>;
+    /// One page of every device in the account, across all sites.
+    async fn get_account_devices(&self, page: i32, max: i32) -> Result<DevicesResponse>;
+    async fn get_device(&self, device_uid: &str) -> Result<Device>;
+    /// Sets the UDFs in `update`; the device's other UDFs keep their values.
+    async fn update_device_udf(&self, device_uid: &str, update: &UdfUpdate) -> Result<()>;
     async fn move_device(&self, device_uid: &str, site_uid: &str) -> Result<()>;
+    /// Sets the warranty end date ("YYYY-MM-DD"); `None` writes null, clearing it.
     async fn update_device_warranty(&self, device_uid: &str, date: Option<String>) -> Result<()>;
+    /// Sets the description and/or friendly name of the device.
+    async fn update_device(&self, device_uid: &str, req: &UpdateDeviceRequest) -> Result<()>;
+    /// Removes the device from RMM; the agent stops reporting to the account.
+    async fn delete_device(&self, device_uid: &str) -> Result<()>;
     async fn get_device_software(&self, device_uid: &str, page: i32, max: i32) -> Result<SoftwareResponse>;
+    async fn get_device_open_alerts(&self, device_uid: &str, page: i32, max: i32) -> Result<OpenAlertsResponse>;
+    /// The device's resolved alerts, most recent first.
+    async fn get_device_resolved_alerts(
+        &self,
+        device_uid: &str,
+        page: i32,
+        max: i32,
+    ) -> Result<OpenAlertsResponse>;
 }
 
+#[async_trait]
 impl DevicesApi for DattoClient {
     async fn get_devices(&self, site_uid: &str, page: i32, max: i32) -> Result<DevicesResponse> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let url = format!(
-            "{}/api/v2/site/{}/devices?page={}&max={}",
-            self.config.api_url, site_uid, page, max
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
-
-        let text = response
-            .text()
-            .await
-            .context("Failed to get response text")?;
-
-        let devices_response = serde_json::from_str(&text).context("Failed to parse JSON")?;
-        Ok(devices_response)
+        let path = format!("site/{}/devices?page={}&max={}", site_uid, page, max);
+        self.get_json(&path, "devices").await
     }
 
-    async fn search_devices(&self, hostname: &str) -> Result<DevicesResponse> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let url = format!("{}/api/v2/account/devices", self.config.api_url);
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .query(&[("hostname", hostname), ("max", "5")])
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-        let text = response
-            .text()
-            .await
-            .context("Failed to get response text")?;
-
-        // Debug Log
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(
-                    f,
-                    "Search Devices Query: hostname={} | Status: {} | Response: {}",
-                    hostname, status, text
-                )
-                .unwrap();
-            });
-
-        if !status.is_success() {
-            anyhow::bail!("API search request failed with status: {} - {}", status, text);
-        }
-
-        let devices_response = serde_json::from_str(&text).context("Failed to parse JSON")?;
-        Ok(devices_response)
-    }
-
-    async fn update_device_udf(&self, device_uid: &str, udf: &Udf) -> Result<()> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
+    async fn search_devices(&self, hostname: &str, page: i32, max: i32) -> Result<DevicesResponse> {
+        let request = self.request(Method::GET, "account/devices")?.query(&[
+            ("hostname", hostname.to_string()),
+            ("page", page.to_string()),
+            ("max", max.to_string()),
+        ]);
+        let text = self.send(request, "device search").await?;
 
-        let url = format!("{}/api/v2/device/{}/udf", self.config.api_url, device_uid);
+        tracing::debug!(%hostname, page, body = %text, "search devices");
 
-        let response = self
-            .client
-            .post(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .json(udf)
-            .send()
-            .await
-            .context("Failed to send UDF update request")?;
+        serde_json::from_str(&text).context("Failed to parse JSON")
+    }
 
-        let status = response.status();
+    async fn get_account_devices(&self, page: i32, max: i32) -> Result<DevicesResponse> {
+        let path = format!("account/devices?page={}&max={}", page, max);
+        self.get_json(&path, "account devices").await
+    }
 
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API UDF update failed with status: {} - {}", status, text);
-        }
+    async fn get_device(&self, device_uid: &str) -> Result<Device> {
+        self.get_json(&format!("device/{}", device_uid), "device").await
+    }
 
+    async fn update_device_udf(&self, device_uid: &str, update: &UdfUpdate) -> Result<()> {
+        let path = format!("device/{}/udf", device_uid);
+        let request = self.request(Method::POST, &path)?.json(update);
+        self.send(request, "UDF update").await?;
         Ok(())
     }
 
     async fn move_device(&self, device_uid: &str, site_uid: &str) -> Result<()> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let url = format!(
-            "{}/api/v2/device/{}/site/{}",
-            self.config.api_url, device_uid, site_uid
-        );
-
-        let response = self
-            .client
-            .put(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send move device request")?;
-
-        let status = response.status();
-
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API move device failed with status: {} - {}", status, text);
-        }
-
+        let path = format!("device/{}/site/{}", device_uid, site_uid);
+        self.send(self.request(Method::PUT, &path)?, "move device").await?;
         Ok(())
     }
 
     async fn update_device_warranty(&self, device_uid: &str, date: Option<String>) -> Result<()> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let url = format!("{}/api/v2/device/{}/warranty", self.config.api_url, device_uid);
-
         let body = serde_json::json!({
             "warrantyDate": date
         });
+        let path = format!("device/{}/warranty", device_uid);
+        self.send(self.request(Method::POST, &path)?.json(&body), "warranty update").await?;
+        Ok(())
+    }
 
-        let response = self
-            .client
-            .post(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .context("Failed to send warranty update request")?;
+    async fn update_device(&self, device_uid: &str, req: &UpdateDeviceRequest) -> Result<()> {
+        tracing::debug!(%device_uid, payload = ?req, "update device");
 
-        let status = response.status();
+        let request = self.request(Method::POST, &format!("device/{}", device_uid))?.json(req);
+        self.send(request, "device update").await?;
+        Ok(())
+    }
 
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API warranty update failed with status: {} - {}", status, text);
-        }
+    async fn delete_device(&self, device_uid: &str) -> Result<()> {
+        tracing::debug!(%device_uid, "delete device");
 
+        let request = self.request(Method::DELETE, &format!("device/{}", device_uid))?;
+        self.send(request, "device delete").await?;
         Ok(())
     }
 
     async fn get_device_software(&self, device_uid: &str, page: i32, max: i32) -> Result<SoftwareResponse> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let url = format!(
-            "{}/api/v2/audit/device/{}/software?page={}&max={}",
-            self.config.api_url, device_uid, page, max
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send software request")?;
-
-        let status = response.status();
-
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API software request failed with status: {} - {}", status, text);
-        }
-
-        let text = response
-            .text()
-            .await
-            .context("Failed to get response text")?;
-
-        let software_response = serde_json::from_str(&text).context("Failed to parse software JSON")?;
-        Ok(software_response)
+        let path = format!("audit/device/{}/software?page={}&max={}", device_uid, page, max);
+        self.get_json(&path, "software").await
+    }
+
+    async fn get_device_open_alerts(&self, device_uid: &str, page: i32, max: i32) -> Result<OpenAlertsResponse> {
+        let path = format!("device/{}/alerts/open?page={}&max={}", device_uid, page, max);
+        self.get_json(&path, "device alerts").await
+    }
+
+    async fn get_device_resolved_alerts(
+        &self,
+        device_uid: &str,
+        page: i32,
+        max: i32,
+    ) -> Result<OpenAlertsResponse> {
+        let path = format!("device/{}/alerts/resolved?page={}&max={}", device_uid, page, max);
+        self.get_json(&path, "resolved device alerts").await
     }
 }
@@ -1,128 +1,149 @@
 use super::DattoClient;
 use crate::api::datto::types::{
-    ComponentsResponse, JobResult, JobStdOutput, QuickJobRequest, QuickJobResponse,
+    ComponentsResponse, JobComponent, JobComponentsResponse, JobResult, JobStdOutput,
+    QuickJobComponent, QuickJobRequest, QuickJobResponse, QuickJobVariable,
 };
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone};
+use reqwest::Method;
+
+/// Built-in Datto RMM component that reboots a device, now or at a set time.
+pub const REBOOT_COMPONENT_UID: &str = "8e6c9295-871e-41f1-8060-ca6899965b82";
+
+/// `rebootString` format understood by the reboot component (YYMMDDHHmm).
+const REBOOT_STRING_FORMAT: &str = "%y%m%d%H%M";
+
+/// Parses a reboot time typed as YY, MM, DD, HH and mm in `now`'s timezone.
+///
+/// Rejects dates that don't exist, times skipped by a DST change and times
+/// that aren't in the future. A time repeated by a DST change resolves to its
+/// first occurrence.
+pub fn parse_reboot_time<Tz: TimeZone>(
+    segments: &[String; 5],
+    now: &DateTime<Tz>,
+) -> Result<DateTime<Tz>, String> {
+    let naive = NaiveDateTime::parse_from_str(&segments.join(""), REBOOT_STRING_FORMAT)
+        .map_err(|_| "Invalid date/time".to_string())?;
+    let at = match now.timezone().from_local_datetime(&naive) {
+        LocalResult::Single(at) => at,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        LocalResult::None => {
+            return Err("That time is skipped by a daylight saving change".to_string());
+        }
+    };
+    if at <= *now {
+        return Err("Reboot time must be in the future".to_string());
+    }
+    Ok(at)
+}
 
-pub(crate) trait JobsApi {
+/// Quick job request for the reboot component; `at` of `None` reboots now.
+fn reboot_request(at: Option<NaiveDateTime>) -> QuickJobRequest {
+    QuickJobRequest {
+        job_name: "Schedule Reboot".to_string(),
+        job_component: QuickJobComponent {
+            component_uid: REBOOT_COMPONENT_UID.to_string(),
+            variables: vec![
+                QuickJobVariable {
+                    name: "rebootNow".to_string(),
+                    value: at.is_none().to_string(),
+                },
+                QuickJobVariable {
+                    name: "rebootString".to_string(),
+                    value: at
+                        .map(|at| at.format(REBOOT_STRING_FORMAT).to_string())
+                        .unwrap_or_default(),
+                },
+            ],
+        },
+    }
+}
+
+/// Quick job request that runs `component` again with the variable values
+/// it was run with; `None` if the job didn't report the component's UID.
+pub fn rerun_request(job_name: &str, component: &JobComponent) -> Option<QuickJobRequest> {
+    Some(QuickJobRequest {
+        job_name: job_name.to_string(),
+        job_component: QuickJobComponent {
+            component_uid: component.uid.clone()?,
+            variables: component
+                .variables
+                .iter()
+                .flatten()
+                .filter_map(|v| {
+                    Some(QuickJobVariable {
+                        name: v.name.clone()?,
+                        value: v.value.clone().unwrap_or_default(),
+                    })
+                })
+                .collect(),
+        },
+    })
+}
+
+#[async_trait]
+pub trait JobsApi: Send + Sync {
     async fn get_job_result(&self, job_uid: &str, device_uid: &str) -> Result<JobResult>;
     async fn get_job_stdout(&self, job_uid: &str, device_uid: &str) -> Result<Vec<JobStdOutput>>;
     async fn get_job_stderr(&self, job_uid: &str, device_uid: &str) -> Result<Vec<JobStdOutput>>;
+    /// The components of a job, with the variable values they ran with
+    async fn get_job_components(&self, job_uid: &str) -> Result<Vec<JobComponent>>;
     async fn get_components(&self, page: Option<i32>) -> Result<ComponentsResponse>;
     async fn run_quick_job(&self, device_uid: &str, req: QuickJobRequest) -> Result<QuickJobResponse>;
+    /// Deletes a job that hasn't run yet, so it never does
+    async fn cancel_job(&self, job_uid: &str) -> Result<()>;
+
+    /// Submits a reboot quick job and returns the created job's UID. `at` is
+    /// the wall-clock time of the reboot (see [`parse_reboot_time`]); `None`
+    /// reboots immediately.
+    async fn schedule_reboot(&self, device_uid: &str, at: Option<NaiveDateTime>) -> Result<String> {
+        let response = self.run_quick_job(device_uid, reboot_request(at)).await?;
+        response
+            .job
+            .and_then(|job| job.uid)
+            .context("Reboot job was submitted but no job UID was returned")
+    }
 }
 
+#[async_trait]
 impl JobsApi for DattoClient {
     async fn get_components(&self, page: Option<i32>) -> Result<ComponentsResponse> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let mut url = format!("{}/api/v2/account/components", self.config.api_url);
-        
+        let mut path = "account/components".to_string();
         if let Some(p) = page {
-            url.push_str(&format!("?page={}", p));
+            path.push_str(&format!("?page={}", p));
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send components request")?;
-
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-
-        // DEBUG LOG
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "COMPONENTS RESPONSE: {}", text).unwrap();
-            });
-
-        if !status.is_success() {
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+        let text = self.send(self.request(Method::GET, &path)?, "components").await?;
+
+        tracing::debug!(body = %text, "get components");
 
-        let components = serde_json::from_str::<ComponentsResponse>(&text)
-            .context("Failed to parse components JSON")?;
-        Ok(components)
+        serde_json::from_str::<ComponentsResponse>(&text).context("Failed to parse components JSON")
     }
 
     async fn run_quick_job(&self, device_uid: &str, req: QuickJobRequest) -> Result<QuickJobResponse> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!("{}/api/v2/device/{}/quickjob", self.config.api_url, device_uid);
-
-        let response = self
-            .client
-            .put(&url)
-            .bearer_auth(access_token)
-            .json(&req)
-            .send()
-            .await
-            .context("Failed to send quick job request")?;
-
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-
-        // DEBUG LOG
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "QUICK JOB RESPONSE: {}", text).unwrap();
-            });
-
-        if !status.is_success() {
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+        let path = format!("device/{}/quickjob", device_uid);
+        let text = self.send(self.request(Method::PUT, &path)?.json(&req), "quick job").await?;
 
-        let job_response = serde_json::from_str::<QuickJobResponse>(&text)
-            .context("Failed to parse quick job response")?;
-        
-        Ok(job_response)
+        tracing::debug!(body = %text, "run quick job");
+
+        serde_json::from_str::<QuickJobResponse>(&text)
+            .context("Failed to parse quick job response")
     }
 
-    async fn get_job_result(&self, job_uid: &str, device_uid: &str) -> Result<JobResult> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let url = format!(
-            "{}/api/v2/job/{}/results/{}",
-            self.config.api_url, job_uid, device_uid
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+    async fn cancel_job(&self, job_uid: &str) -> Result<()> {
+        tracing::debug!(%job_uid, "cancel job");
+
+        let request = self.request(Method::DELETE, &format!("job/{}", job_uid))?;
+        self.send(request, "job cancel").await?;
+        Ok(())
+    }
 
-        let text = response.text().await.context("Failed to get response text")?;
+    async fn get_job_result(&self, job_uid: &str, device_uid: &str) -> Result<JobResult> {
+        let path = format!("job/{}/results/{}", job_uid, device_uid);
+        let text = self.send(self.request(Method::GET, &path)?, "job result").await?;
 
-        // DEBUG LOG
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "JOB RESULT JSON: {}", text).unwrap();
-            });
+        tracing::debug!(body = %text, "get job result");
 
         // Try to parse as single object first
         match serde_json::from_str::<JobResult>(&text) {
@@ -136,60 +157,97 @@ impl JobsApi for DattoClient {
     }
 
     async fn get_job_stdout(&self, job_uid: &str, device_uid: &str) -> Result<Vec<JobStdOutput>> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!(
-            "{}/api/v2/job/{}/results/{}/stdout",
-            self.config.api_url, job_uid, device_uid
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send stdout request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
-
-        let output = response
-            .json::<Vec<JobStdOutput>>()
-            .await
-            .context("Failed to parse stdout JSON")?;
-        Ok(output)
+        let path = format!("job/{}/results/{}/stdout", job_uid, device_uid);
+        self.get_json(&path, "stdout").await
     }
 
     async fn get_job_stderr(&self, job_uid: &str, device_uid: &str) -> Result<Vec<JobStdOutput>> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!(
-            "{}/api/v2/job/{}/results/{}/stderr",
-            self.config.api_url, job_uid, device_uid
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send stderr request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+        let path = format!("job/{}/results/{}/stderr", job_uid, device_uid);
+        self.get_json(&path, "stderr").await
+    }
+
+    async fn get_job_components(&self, job_uid: &str) -> Result<Vec<JobComponent>> {
+        let path = format!("job/{}/components", job_uid);
+        let text = self.send(self.request(Method::GET, &path)?, "job components").await?;
+
+        tracing::debug!(body = %text, "get job components");
+
+        let components = serde_json::from_str::<JobComponentsResponse>(&text)
+            .context("Failed to parse job components JSON")?;
+        Ok(components.job_components)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::time::TimeZoneSetting;
+    use chrono::FixedOffset;
+
+    fn segments(s: [&str; 5]) -> [String; 5] {
+        s.map(str::to_string)
+    }
+
+    #[test]
+    fn test_parse_reboot_time() {
+        let tz = FixedOffset::east_opt(-5 * 3600).unwrap();
+        let now = tz.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+
+        let at = parse_reboot_time(&segments(["26", "01", "15", "22", "30"]), &now).unwrap();
+        assert_eq!(at.to_rfc3339(), "2026-01-15T22:30:00-05:00");
+
+        assert!(parse_reboot_time(&segments(["26", "01", "15", "11", "59"]), &now).is_err());
+        assert!(parse_reboot_time(&segments(["26", "02", "30", "10", "00"]), &now).is_err());
+
+        let request = reboot_request(Some(at.naive_local()));
+        let values: Vec<&str> = request
+            .job_component
+            .variables
+            .iter()
+            .map(|v| v.value.as_str())
+            .collect();
+        assert_eq!(values, vec!["false", "2601152230"]);
+    }
+
+    #[test]
+    fn test_parse_reboot_time_in_configured_zone() {
+        // A UTC host with `timezone = "America/Chicago"`: 22:30 means 22:30 in
+        // Chicago, and 20:00 is already past there though not in UTC
+        let zone = TimeZoneSetting::parse("America/Chicago").unwrap();
+        let utc_now = DateTime::parse_from_rfc3339("2026-01-16T03:00:00Z").unwrap().to_utc();
+        let now = zone.at(utc_now);
+
+        let at = parse_reboot_time(&segments(["26", "01", "15", "22", "30"]), &now).unwrap();
+        assert_eq!(at.to_rfc3339(), "2026-01-15T22:30:00-06:00");
+        assert_eq!(at.to_utc().to_rfc3339(), "2026-01-16T04:30:00+00:00");
+        assert_eq!(at.naive_local().format(REBOOT_STRING_FORMAT).to_string(), "2601152230");
+
+        assert!(parse_reboot_time(&segments(["26", "01", "15", "20", "00"]), &now).is_err());
+    }
 
-        let output = response
-            .json::<Vec<JobStdOutput>>()
-            .await
-            .context("Failed to parse stderr JSON")?;
-        Ok(output)
+    #[test]
+    fn test_rerun_request_keeps_the_variables() {
+        let component: JobComponent = serde_json::from_value(serde_json::json!({
+            "uid": "c1",
+            "name": "Clear Temp",
+            "variables": [
+                { "name": "path", "value": "C:\\Temp" },
+                { "name": "dryRun" },
+                { "value": "orphan" },
+            ],
+        }))
+        .unwrap();
+        let request = rerun_request("Clear Temp", &component).unwrap();
+        assert_eq!(request.job_component.component_uid, "c1");
+        let variables: Vec<(&str, &str)> = request
+            .job_component
+            .variables
+            .iter()
+            .map(|v| (v.name.as_str(), v.value.as_str()))
+            .collect();
+        assert_eq!(variables, vec![("path", "C:\\Temp"), ("dryRun", "")]);
+
+        let component = JobComponent { uid: None, ..component };
+        assert!(rerun_request("Clear Temp", &component).is_none());
     }
 }
@@ -1,101 +1,81 @@
 pub mod activity;
 pub mod devices;
 pub mod jobs;
+pub mod rate_limit;
+mod request;
 pub mod sites;
 pub mod types;
 pub mod variables;
 
-use crate::config::DattoConfig;
-use anyhow::{Context, Result};
-use reqwest::Client;
-use std::time::Duration;
-use types::TokenResponse;
+use crate::config::{DattoConfig, DEFAULT_ACTIVITY_PAGE_SIZE, DEFAULT_PAGE_SIZE};
+use activity::ActivityApi;
+use devices::DevicesApi;
+use jobs::JobsApi;
+use rate_limit::Quota;
+use sites::SitesApi;
+use variables::VariablesApi;
+use anyhow::Result;
+use crate::api::http::HttpClient;
 
-#[derive(Clone, Debug)]
-pub struct DattoClient {
-    pub(crate) client: Client,
-    pub(crate) config: DattoConfig,
-    pub(crate) access_token: Option<String>,
+/// Datto RMM operations used by the UI and headless commands; implemented by
+/// `DattoClient` and `api::mock::MockRmm`.
+pub trait RmmApi:
+    SitesApi + DevicesApi + VariablesApi + JobsApi + ActivityApi + LimitsApi + std::fmt::Debug
+{
 }
 
-impl DattoClient {
-    pub fn new(config: DattoConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .context("Failed to build HTTP client")?;
-        Ok(Self {
-            client,
-            config,
-            access_token: None,
-        })
-    }
-
-    pub async fn authenticate(&mut self) -> Result<()> {
-        let url = format!("{}/auth/oauth/token", self.config.api_url);
-
-        let params = [
-            ("grant_type", "password"),
-            ("username", &self.config.api_key),
-            ("password", &self.config.secret_key),
-        ];
+impl<T> RmmApi for T where
+    T: SitesApi + DevicesApi + VariablesApi + JobsApi + ActivityApi + LimitsApi + std::fmt::Debug
+{
+}
 
-        let response = self
-            .client
-            .post(&url)
-            .basic_auth("public-client", Some("public"))
-            .form(&params)
-            .send()
-            .await
-            .context("Failed to send auth request")?;
+/// Request limits of the client: the API key's remaining quota, for the
+/// status bar, and the configured page sizes.
+pub trait LimitsApi: Send + Sync {
+    /// None until the API has reported one
+    fn quota(&self) -> Option<Quota> {
+        None
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Authentication failed: {} - {}", status, text);
-        }
+    /// Items per page of list requests
+    fn page_size(&self) -> i32 {
+        DEFAULT_PAGE_SIZE
+    }
 
-        let token_response = response
-            .json::<TokenResponse>()
-            .await
-            .context("Failed to parse token")?;
-        self.access_token = Some(token_response.access_token);
+    /// Entries in the device activity log query
+    fn activity_page_size(&self) -> i32 {
+        DEFAULT_ACTIVITY_PAGE_SIZE
+    }
+}
 
-        Ok(())
+impl LimitsApi for DattoClient {
+    fn quota(&self) -> Option<Quota> {
+        self.http.quota()
     }
 
-    pub async fn get_device_open_alerts(&self, device_uid: &str, page: i32, max: i32) -> Result<types::OpenAlertsResponse> {
-        // Use /api/v2/ to match other endpoints pattern
-        let url = format!("{}/api/v2/device/{}/alerts/open?page={}&max={}", self.config.api_url, device_uid, page, max);
-        
-        // Log the URL
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "Fetching Alerts URL: {}", url).unwrap();
-            });
+    fn page_size(&self) -> i32 {
+        self.config.http.page_size
+    }
 
-        let resp = self
-            .client
-            .get(&url)
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.access_token.as_ref().unwrap()),
-            )
-            .send()
-            .await?;
+    fn activity_page_size(&self) -> i32 {
+        self.config.activity_page_size
+    }
+}
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            anyhow::bail!("Failed to fetch open alerts: {} - {}", status, text);
-        }
+#[derive(Clone, Debug)]
+pub struct DattoClient {
+    /// Shared by clones so every request counts against the same quota
+    pub(crate) http: HttpClient,
+    pub(crate) config: DattoConfig,
+    pub(crate) access_token: Option<String>,
+}
 
-        let text = resp.text().await?;
-        let alerts_response: types::OpenAlertsResponse = serde_json::from_str(&text)?;
-        Ok(alerts_response)
+impl DattoClient {
+    pub fn new(config: DattoConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new("Datto RMM", &config.http)?.with_rate_limit(),
+            access_token: None,
+            config,
+        })
     }
 }
@@ -0,0 +1,150 @@
+use crate::api::throttle::{Limiter, SendThrottled};
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Window assumed when the API doesn't say when the quota resets.
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Requests kept in reserve: below this many remaining, new requests wait for
+/// the window to reset instead of risking the lockout.
+fn reserve(limit: u32) -> u32 {
+    (limit / 20).max(5)
+}
+
+/// Request quota reported by the Datto RMM API.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_at: Instant,
+}
+
+impl Quota {
+    /// True when requests should be held back until `reset_at`.
+    pub fn is_low(&self) -> bool {
+        self.remaining <= reserve(self.limit)
+    }
+}
+
+/// Reads the quota from the `X-RateLimit-*` headers (or their `RateLimit-*`
+/// equivalents). The reset is either seconds from now or a unix timestamp.
+pub fn quota_from_headers(headers: &HeaderMap, now: Instant) -> Option<Quota> {
+    let number = |name: &str| -> Option<u64> {
+        [format!("x-ratelimit-{}", name), format!("ratelimit-{}", name)]
+            .iter()
+            .find_map(|h| headers.get(h.as_str()))?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let limit = number("limit")?;
+    let remaining = number("remaining")?;
+    let reset_in = match number("reset") {
+        Some(epoch) if epoch > 1_000_000_000 => {
+            let now_secs = chrono::Utc::now().timestamp().max(0) as u64;
+            Duration::from_secs(epoch.saturating_sub(now_secs))
+        }
+        Some(secs) => Duration::from_secs(secs),
+        None => DEFAULT_WINDOW,
+    };
+    Some(Quota {
+        limit: limit as u32,
+        remaining: remaining as u32,
+        reset_at: now + reset_in.min(DEFAULT_WINDOW * 2),
+    })
+}
+
+/// Remaining request quota of a Datto RMM API key, shared by the clones of a
+/// `DattoClient`.
+#[derive(Debug, Default)]
+pub struct RateLimit {
+    quota: Mutex<Option<Quota>>,
+}
+
+impl RateLimit {
+    pub fn quota(&self) -> Option<Quota> {
+        let mut quota = self.quota.lock().unwrap();
+        // A quota past its reset no longer says anything
+        if quota.is_some_and(|q| q.reset_at <= Instant::now()) {
+            *quota = None;
+        }
+        *quota
+    }
+
+    /// Waits while the quota is low, then counts the request against it so
+    /// concurrent requests don't all spend the same reserve.
+    async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut quota = self.quota.lock().unwrap();
+                match quota.as_mut() {
+                    Some(q) if q.reset_at <= Instant::now() => {
+                        *quota = None;
+                        return;
+                    }
+                    Some(q) if q.is_low() => q.reset_at - Instant::now(),
+                    Some(q) => {
+                        q.remaining = q.remaining.saturating_sub(1);
+                        return;
+                    }
+                    None => return,
+                }
+            };
+            tracing::warn!(wait_ms = wait.as_millis() as u64, "Datto RMM quota low, holding request");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn record(&self, response: &Response) {
+        if let Some(quota) = quota_from_headers(response.headers(), Instant::now()) {
+            *self.quota.lock().unwrap() = Some(quota);
+        }
+    }
+}
+
+/// Sends Datto RMM requests within the API key's quota.
+pub(crate) trait SendLimited {
+    async fn send_limited(self, limit: &RateLimit, limiter: &Limiter)
+    -> reqwest::Result<Response>;
+}
+
+impl SendLimited for RequestBuilder {
+    async fn send_limited(
+        self,
+        limit: &RateLimit,
+        limiter: &Limiter,
+    ) -> reqwest::Result<Response> {
+        limit.acquire().await;
+        let response = self.send_throttled(limiter).await?;
+        limit.record(&response);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_from_headers() {
+        let now = Instant::now();
+        let mut headers = HeaderMap::new();
+        assert_eq!(quota_from_headers(&headers, now), None);
+
+        headers.insert("X-RateLimit-Limit", "600".parse().unwrap());
+        headers.insert("X-RateLimit-Remaining", "12".parse().unwrap());
+        headers.insert("X-RateLimit-Reset", "30".parse().unwrap());
+        let quota = quota_from_headers(&headers, now).unwrap();
+        assert_eq!(quota.remaining, 12);
+        assert_eq!(quota.reset_at, now + Duration::from_secs(30));
+        // 600 / 20 = 30 held in reserve
+        assert!(quota.is_low());
+
+        headers.insert("X-RateLimit-Remaining", "300".parse().unwrap());
+        assert!(!quota_from_headers(&headers, now).unwrap().is_low());
+    }
+}
@@ -0,0 +1,88 @@
+use super::types::TokenResponse;
+use super::DattoClient;
+use crate::api::error::ApiError;
+use crate::api::http::Auth;
+use anyhow::{Context, Result};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+
+/// Authentication and the request helpers the endpoint modules build on.
+impl DattoClient {
+    /// Checks that `api_url` is a reachable Datto RMM API server before any
+    /// credentials are sent, so a wrong platform or URL gets its own error.
+    pub async fn ping(&self) -> Result<()> {
+        let url = format!("{}/api/v2/system/status", self.config.api_url);
+        let response = self
+            .http
+            .execute(self.http.request(Method::GET, &url, None))
+            .await
+            .with_context(|| format!("Can't reach the Datto RMM API at {}", self.config.api_url))?;
+
+        // Unauthenticated requests may be refused, but only by a Datto server
+        let status = response.status();
+        if !status.is_success()
+            && status != StatusCode::UNAUTHORIZED
+            && status != StatusCode::FORBIDDEN
+        {
+            anyhow::bail!(
+                "{} doesn't look like a Datto RMM API ({}); check datto.platform or datto.api_url",
+                self.config.api_url,
+                status
+            );
+        }
+        Ok(())
+    }
+
+    pub async fn authenticate(&mut self) -> Result<()> {
+        let url = format!("{}/auth/oauth/token", self.config.api_url);
+
+        let params = [
+            ("grant_type", "password"),
+            ("username", &self.config.api_key),
+            ("password", &self.config.secret_key),
+        ];
+
+        let request = self
+            .http
+            .request(Method::POST, &url, None)
+            .basic_auth("public-client", Some("public"))
+            .form(&params);
+        let response = self
+            .http
+            .execute(request)
+            .await
+            .context("Failed to send auth request")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let message = format!("Authentication failed: {} - {}", status, text);
+            return Err(ApiError::Auth(message).into());
+        }
+
+        let token_response = response
+            .json::<TokenResponse>()
+            .await
+            .context("Failed to parse token")?;
+        self.access_token = Some(token_response.access_token);
+
+        Ok(())
+    }
+
+    /// A request to `path`, relative to `/api/v2/`, with the bearer token.
+    pub(crate) fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        let access_token = self.access_token.as_ref().ok_or_else(ApiError::not_authenticated)?;
+        let url = format!("{}/api/v2/{}", self.config.api_url, path);
+        Ok(self.http.request(method, &url, Some(Auth::Bearer(access_token))))
+    }
+
+    /// Sends `request`; see `HttpClient::send`.
+    pub(crate) async fn send(&self, request: RequestBuilder, what: &str) -> Result<String> {
+        self.http.send(request, what).await
+    }
+
+    /// GETs `path` and parses the JSON response.
+    pub(crate) async fn get_json<T: DeserializeOwned>(&self, path: &str, what: &str) -> Result<T> {
+        self.http.send_json(self.request(Method::GET, path)?, what).await
+    }
+}
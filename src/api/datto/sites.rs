@@ -1,8 +1,11 @@
 use super::DattoClient;
-use crate::api::datto::types::{self, SitesResponse, UpdateSiteRequest};
+use crate::api::datto::types::{self, ProxySettings, SitesResponse, UpdateSiteRequest};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Method;
 
-pub(crate) trait SitesApi {
+#[async_trait]
+pub trait SitesApi: Send + Sync {
     async fn get_sites(
         &self,
         page: i32,
@@ -14,8 +17,24 @@ pub(crate) trait SitesApi {
 
     async fn get_site(&self, site_uid: &str) -> Result<types::Site>;
     async fn get_site_open_alerts(&self, site_uid: &str, page: i32, max: i32) -> Result<types::OpenAlertsResponse>;
+    /// Open alerts of every site in the account
+    async fn get_account_open_alerts(
+        &self,
+        page: i32,
+        max: i32,
+    ) -> Result<types::OpenAlertsResponse>;
+    /// Marks the alert resolved; it leaves the open alert lists.
+    async fn resolve_alert(&self, alert_uid: &str) -> Result<()>;
+    /// Sets the proxy the site's agents connect through
+    async fn update_site_proxy(&self, site_uid: &str, proxy: &ProxySettings) -> Result<()>;
+    /// Removes the site's proxy; its agents connect directly
+    async fn delete_site_proxy(&self, site_uid: &str) -> Result<()>;
+    /// Deletes the site from the account. The API refuses sites that still
+    /// have devices.
+    async fn delete_site(&self, site_uid: &str) -> Result<()>;
 }
 
+#[async_trait]
 impl SitesApi for DattoClient {
     async fn get_sites(
         &self,
@@ -23,38 +42,13 @@ impl SitesApi for DattoClient {
         max: i32,
         site_name: Option<String>,
     ) -> Result<SitesResponse> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let mut url = format!(
-            "{}/api/v2/account/sites?page={}&max={}",
-            self.config.api_url, page, max
-        );
+        let mut path = format!("account/sites?page={}&max={}", page, max);
 
         if let Some(name) = site_name {
-            url.push_str(&format!("&siteName={}", name));
-        }
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
+            path.push_str(&format!("&siteName={}", name));
         }
 
-        let sites_response = response
-            .json::<SitesResponse>()
-            .await
-            .context("Failed to parse JSON")?;
-        Ok(sites_response)
+        self.get_json(&path, "sites").await
     }
 
     async fn update_site(
@@ -62,96 +56,64 @@ impl SitesApi for DattoClient {
         site_uid: &str,
         req: types::UpdateSiteRequest,
     ) -> Result<types::Site> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!("{}/api/v2/site/{}", self.config.api_url, site_uid);
-
-        // DEBUG LOG
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "API UPDATE SITE: URL={}", url).unwrap();
-                writeln!(f, "Payload: {:?}", req).unwrap();
-            });
-
-        let response = self
-            .client
-            .post(&url)
-            .bearer_auth(access_token)
-            .json(&req)
-            .send()
-            .await
-            .context("Failed to send update site request")?;
-
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-
-        // DEBUG LOG RESPONSE
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "API RESPONSE Status: {}", status).unwrap();
-                writeln!(f, "API RESPONSE Body: {}", text).unwrap();
-            });
-
-        if !status.is_success() {
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+        tracing::debug!(%site_uid, payload = ?req, "update site");
+
+        let request = self.request(Method::POST, &format!("site/{}", site_uid))?.json(&req);
+        let text = self.send(request, "update site").await?;
 
-        let site =
-            serde_json::from_str::<types::Site>(&text).context("Failed to parse response")?;
-        Ok(site)
+        tracing::debug!(body = %text, "update site response");
+
+        serde_json::from_str::<types::Site>(&text).context("Failed to parse response")
     }
 
     async fn get_site(&self, site_uid: &str) -> Result<types::Site> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!("{}/api/v2/site/{}", self.config.api_url, site_uid);
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .send()
-            .await
-            .context("Failed to send get site request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
-
-        let site = response.json::<types::Site>().await.context("Failed to parse site response")?;
-        Ok(site)
+        self.get_json(&format!("site/{}", site_uid), "get site").await
     }
 
     async fn get_site_open_alerts(&self, site_uid: &str, page: i32, max: i32) -> Result<types::OpenAlertsResponse> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!("{}/api/v2/site/{}/alerts/open?page={}&max={}", self.config.api_url, site_uid, page, max);
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .send()
-            .await
-            .context("Failed to send site alerts request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+        let path = format!("site/{}/alerts/open?page={}&max={}", site_uid, page, max);
+        self.get_json(&path, "site alerts").await
+    }
+
+    async fn get_account_open_alerts(
+        &self,
+        page: i32,
+        max: i32,
+    ) -> Result<types::OpenAlertsResponse> {
+        let path = format!("account/alerts/open?page={}&max={}", page, max);
+        self.get_json(&path, "account alerts").await
+    }
+
+    async fn resolve_alert(&self, alert_uid: &str) -> Result<()> {
+        tracing::debug!(%alert_uid, "resolve alert");
+
+        let request = self.request(Method::POST, &format!("alert/{}/resolve", alert_uid))?;
+        self.send(request, "resolve alert").await?;
+        Ok(())
+    }
+
+    async fn update_site_proxy(&self, site_uid: &str, proxy: &ProxySettings) -> Result<()> {
+        // No payload in the log; it holds the proxy password
+        tracing::debug!(%site_uid, "update site proxy");
+
+        let path = format!("site/{}/settings/proxy", site_uid);
+        self.send(self.request(Method::POST, &path)?.json(proxy), "site proxy").await?;
+        Ok(())
+    }
+
+    async fn delete_site_proxy(&self, site_uid: &str) -> Result<()> {
+        tracing::debug!(%site_uid, "delete site proxy");
+
+        let path = format!("site/{}/settings/proxy", site_uid);
+        self.send(self.request(Method::DELETE, &path)?, "site proxy delete").await?;
+        Ok(())
+    }
+
+    async fn delete_site(&self, site_uid: &str) -> Result<()> {
+        tracing::debug!(%site_uid, "delete site");
 
-        let alerts_response = response
-            .json::<types::OpenAlertsResponse>()
-            .await
-            .context("Failed to parse site alerts response")?;
-        Ok(alerts_response)
+        let request = self.request(Method::DELETE, &format!("site/{}", site_uid))?;
+        self.send(request, "site delete").await?;
+        Ok(())
     }
 }
@@ -1,4 +1,8 @@
+use crate::api::lenient::Lenient;
+use crate::common::time;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TokenResponse {
@@ -16,12 +20,13 @@ pub struct PageDetails {
     pub next_page_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ProxySettings {
     pub host: Option<String>,
     pub password: Option<String>,
     pub port: Option<i32>,
+    #[serde(rename = "type")]
     pub type_field: Option<String>, // "type" is a reserved keyword
     pub username: Option<String>,
 }
@@ -52,6 +57,10 @@ pub struct Site {
     pub portal_url: Option<String>,
     #[serde(skip, default)]
     pub variables: Option<Vec<SiteVariable>>,
+    /// Profile of the account the site belongs to, for sites of other accounts
+    /// in the merged site list (`None` for the active profile's sites)
+    #[serde(skip, default)]
+    pub account: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -93,6 +102,22 @@ pub struct UpdateSiteRequest {
     pub notes: Option<String>,
     pub on_demand: Option<bool>,
     pub splashtop_auto_install: Option<bool>,
+    /// PSA (Autotask) company the site is mapped to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autotask_company_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autotask_company_name: Option<String>,
+}
+
+/// Device fields that can be changed; fields left `None` aren't sent and
+/// keep their value.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDeviceRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -148,6 +173,123 @@ pub struct Udf {
     pub udf30: Option<String>,
 }
 
+impl Udf {
+    /// Value of UDF `number` (1-30).
+    pub fn get(&self, number: usize) -> Option<&str> {
+        let fields = [
+            &self.udf1,
+            &self.udf2,
+            &self.udf3,
+            &self.udf4,
+            &self.udf5,
+            &self.udf6,
+            &self.udf7,
+            &self.udf8,
+            &self.udf9,
+            &self.udf10,
+            &self.udf11,
+            &self.udf12,
+            &self.udf13,
+            &self.udf14,
+            &self.udf15,
+            &self.udf16,
+            &self.udf17,
+            &self.udf18,
+            &self.udf19,
+            &self.udf20,
+            &self.udf21,
+            &self.udf22,
+            &self.udf23,
+            &self.udf24,
+            &self.udf25,
+            &self.udf26,
+            &self.udf27,
+            &self.udf28,
+            &self.udf29,
+            &self.udf30,
+        ];
+        fields.get(number.checked_sub(1)?)?.as_deref()
+    }
+
+    /// Sets UDF `number` (1-30); other numbers are ignored.
+    pub fn set(&mut self, number: usize, value: Option<String>) {
+        if let Some(field) = self.field(number) {
+            *field = value;
+        }
+    }
+
+    fn field(&mut self, number: usize) -> Option<&mut Option<String>> {
+        let fields = [
+            &mut self.udf1,
+            &mut self.udf2,
+            &mut self.udf3,
+            &mut self.udf4,
+            &mut self.udf5,
+            &mut self.udf6,
+            &mut self.udf7,
+            &mut self.udf8,
+            &mut self.udf9,
+            &mut self.udf10,
+            &mut self.udf11,
+            &mut self.udf12,
+            &mut self.udf13,
+            &mut self.udf14,
+            &mut self.udf15,
+            &mut self.udf16,
+            &mut self.udf17,
+            &mut self.udf18,
+            &mut self.udf19,
+            &mut self.udf20,
+            &mut self.udf21,
+            &mut self.udf22,
+            &mut self.udf23,
+            &mut self.udf24,
+            &mut self.udf25,
+            &mut self.udf26,
+            &mut self.udf27,
+            &mut self.udf28,
+            &mut self.udf29,
+            &mut self.udf30,
+        ];
+        fields.into_iter().nth(number.checked_sub(1)?)
+    }
+}
+
+/// Changes to some UDFs of a device, by UDF number. It serializes to just
+/// the changed fields (`{"udf30": "..."}`), which Datto RMM sets while
+/// keeping the others, so a write doesn't null the rest or overwrite
+/// another user's edits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UdfUpdate(BTreeMap<usize, String>);
+
+impl Serialize for UdfUpdate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (number, value) in &self.0 {
+            map.serialize_entry(&format!("udf{}", number), value)?;
+        }
+        map.end()
+    }
+}
+
+impl UdfUpdate {
+    /// Sets UDF `number` (1-30); an empty value clears it.
+    pub fn set(mut self, number: usize, value: impl Into<String>) -> Self {
+        if (1..=30).contains(&number) {
+            self.0.insert(number, value.into());
+        }
+        self
+    }
+
+    /// Writes the changes into `udf`, leaving the other fields as they are.
+    pub fn apply(&self, udf: &mut Udf) {
+        for (&number, value) in &self.0 {
+            udf.set(number, Some(value.clone()));
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Antivirus {
@@ -173,6 +315,9 @@ pub struct Device {
     pub site_name: Option<String>,
     pub hostname: String,
     pub description: Option<String>,
+    /// Friendly name set in Datto RMM; the hostname itself comes from the agent
+    #[serde(default)]
+    pub display_name: Option<String>,
     pub online: bool,
     #[serde(rename = "lastSeen")]
     // Note: User provided example string "2026-01-17T19:38:38.330Z" but also mentioned "number it gives right now (example Last Seen: 1768448871000 )"
@@ -219,11 +364,47 @@ pub struct Device {
     pub onboarded_via_network_monitor: Option<bool>,
 }
 
+impl Device {
+    /// When the agent last checked in; the API sends either epoch
+    /// milliseconds or an ISO string.
+    pub fn last_seen_at(&self) -> Option<DateTime<Utc>> {
+        self.last_seen.as_ref().and_then(time::parse_timestamp)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", from = "LenientDevicesResponse")]
 pub struct DevicesResponse {
     pub page_details: PageDetails,
     pub devices: Vec<Device>,
+    /// Devices of the page left out because they didn't parse
+    #[serde(skip)]
+    pub parse_errors: usize,
+}
+
+impl DevicesResponse {
+    /// Devices the page held, parsed or not, for telling the last page.
+    pub fn page_len(&self) -> usize {
+        self.devices.len() + self.parse_errors
+    }
+}
+
+/// A device list skips devices that don't parse rather than failing whole.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LenientDevicesResponse {
+    page_details: PageDetails,
+    devices: Lenient<Device>,
+}
+
+impl From<LenientDevicesResponse> for DevicesResponse {
+    fn from(raw: LenientDevicesResponse) -> Self {
+        Self {
+            page_details: raw.page_details,
+            devices: raw.devices.items,
+            parse_errors: raw.devices.errors,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -266,6 +447,12 @@ pub struct ActivityLog {
     pub has_std_err: Option<bool>,
 }
 
+impl ActivityLog {
+    pub fn at(&self) -> Option<DateTime<Utc>> {
+        self.date.and_then(time::from_epoch)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ActivityLogsResponse {
@@ -295,6 +482,28 @@ pub struct JobResult {
     pub component_results: Option<Vec<ComponentResult>>,
 }
 
+impl JobResult {
+    pub fn ran_at(&self) -> Option<DateTime<Utc>> {
+        self.ran_on.as_ref().and_then(time::parse_timestamp)
+    }
+}
+
+/// A component of a job with the variable values it was run with.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobComponent {
+    pub uid: Option<String>,
+    pub name: Option<String>,
+    pub variables: Option<Vec<QuickJobResponseVariable>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobComponentsResponse {
+    pub page_details: Option<PageDetails>,
+    pub job_components: Vec<JobComponent>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct JobStdOutput {
@@ -319,6 +528,9 @@ pub struct AlertContext {
     pub action_type: Option<String>,
     pub prev_version: Option<String>,
     pub version: Option<String>,
+    /// The monitor-specific fields, which vary by `class`
+    #[serde(flatten)]
+    pub details: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -359,6 +571,18 @@ pub struct Alert {
     pub autoresolve_mins: Option<i32>,
 }
 
+impl Alert {
+    /// When the alert was raised.
+    pub fn raised_at(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.as_ref().and_then(time::parse_timestamp)
+    }
+
+    /// When the alert was resolved, by a tech or automatically.
+    pub fn resolved_at(&self) -> Option<DateTime<Utc>> {
+        self.resolved_on.as_ref().and_then(time::parse_timestamp)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenAlertsResponse {
@@ -468,3 +692,82 @@ pub struct SoftwareResponse {
     pub page_details: PageDetails,
     pub software: Vec<Software>,
 }
+
+/// Devices for tests, built from the JSON the API sends: an online device
+/// with uid "d1" in site "s1", plus whichever fields a test sets.
+#[cfg(test)]
+pub struct DeviceBuilder(serde_json::Value);
+
+#[cfg(test)]
+impl DeviceBuilder {
+    pub fn new(hostname: &str) -> Self {
+        Self(serde_json::json!({
+            "id": 1,
+            "uid": "d1",
+            "hostname": hostname,
+            "siteId": 1,
+            "siteUid": "s1",
+            "online": true,
+        }))
+    }
+
+    /// Sets the API field `key` (e.g. "intIpAddress") to `value`.
+    pub fn with(mut self, key: &str, value: impl Serialize) -> Self {
+        self.0[key] = serde_json::to_value(value).unwrap();
+        self
+    }
+
+    pub fn uid(self, uid: &str) -> Self {
+        self.with("uid", uid)
+    }
+
+    pub fn site(self, site_uid: &str) -> Self {
+        self.with("siteUid", site_uid)
+    }
+
+    pub fn online(self, online: bool) -> Self {
+        self.with("online", online)
+    }
+
+    pub fn patch_status(self, status: &str) -> Self {
+        self.with("patchManagement", serde_json::json!({ "patchStatus": status }))
+    }
+
+    pub fn antivirus(self, product: Option<&str>, status: Option<&str>) -> Self {
+        self.with(
+            "antivirus",
+            serde_json::json!({ "antivirusProduct": product, "antivirusStatus": status }),
+        )
+    }
+
+    /// The device as the API would send it.
+    pub fn json(self) -> serde_json::Value {
+        self.0
+    }
+
+    pub fn build(self) -> Device {
+        serde_json::from_value(self.0).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_udf_update_sends_only_changed_fields() {
+        let update = UdfUpdate::default().set(30, "agent-1").set(2, "").set(31, "ignored");
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            serde_json::json!({ "udf2": "", "udf30": "agent-1" })
+        );
+
+        let mut udf = Udf::default();
+        udf.set(1, Some("Asset 17".to_string()));
+        update.apply(&mut udf);
+        assert_eq!(
+            (udf.get(1), udf.get(2), udf.get(30)),
+            (Some("Asset 17"), Some(""), Some("agent-1"))
+        );
+    }
+}
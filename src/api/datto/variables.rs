@@ -1,8 +1,11 @@
 use super::DattoClient;
 use crate::api::datto::types::{self, CreateVariableRequest, SiteVariable, UpdateVariableRequest};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Method;
 
-pub(crate) trait VariablesApi {
+#[async_trait]
+pub trait VariablesApi: Send + Sync {
     async fn get_site_variables(&self, site_uid: &str) -> Result<Vec<SiteVariable>>;
     async fn create_site_variable(
         &self,
@@ -15,35 +18,23 @@ pub(crate) trait VariablesApi {
         variable_id: i32,
         req: UpdateVariableRequest,
     ) -> Result<SiteVariable>;
+    async fn delete_site_variable(&self, site_uid: &str, variable_id: i32) -> Result<()>;
+
+    /// Variables shared by every site of the account
+    async fn get_account_variables(&self) -> Result<Vec<SiteVariable>>;
+    async fn create_account_variable(&self, req: CreateVariableRequest) -> Result<SiteVariable>;
+    async fn update_account_variable(
+        &self,
+        variable_id: i32,
+        req: UpdateVariableRequest,
+    ) -> Result<SiteVariable>;
+    async fn delete_account_variable(&self, variable_id: i32) -> Result<()>;
 }
 
+#[async_trait]
 impl VariablesApi for DattoClient {
     async fn get_site_variables(&self, site_uid: &str) -> Result<Vec<SiteVariable>> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let url = format!("{}/api/v2/site/{}/variables", self.config.api_url, site_uid);
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(access_token)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
-
-        let resp_json = response
-            .json::<types::SiteVariablesResponse>()
-            .await
-            .context("Failed to parse JSON")?;
-
-        Ok(resp_json.variables)
+        self.get_variables(&format!("site/{}", site_uid)).await
     }
 
     async fn create_site_variable(
@@ -51,35 +42,61 @@ impl VariablesApi for DattoClient {
         site_uid: &str,
         req: CreateVariableRequest,
     ) -> Result<SiteVariable> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!("{}/api/v2/site/{}/variable", self.config.api_url, site_uid);
-
-        let response = self
-            .client
-            .put(&url)
-            .bearer_auth(access_token)
-            .json(&req)
-            .send()
-            .await
-            .context("Failed to send create variable request")?;
-
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-
-        // DEBUG LOG
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "CREATE VARIABLE RESPONSE Status: {}", status).unwrap();
-                writeln!(f, "CREATE VARIABLE RESPONSE Body: {}", text).unwrap();
-            });
-
-        if !status.is_success() {
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+        self.create_variable(&format!("site/{}", site_uid), req).await
+    }
+
+    async fn update_site_variable(
+        &self,
+        site_uid: &str,
+        variable_id: i32,
+        req: UpdateVariableRequest,
+    ) -> Result<SiteVariable> {
+        self.update_variable(&format!("site/{}", site_uid), variable_id, req).await
+    }
+
+    async fn delete_site_variable(&self, site_uid: &str, variable_id: i32) -> Result<()> {
+        self.delete_variable(&format!("site/{}", site_uid), variable_id).await
+    }
+
+    async fn get_account_variables(&self) -> Result<Vec<SiteVariable>> {
+        self.get_variables("account").await
+    }
+
+    async fn create_account_variable(&self, req: CreateVariableRequest) -> Result<SiteVariable> {
+        self.create_variable("account", req).await
+    }
+
+    async fn update_account_variable(
+        &self,
+        variable_id: i32,
+        req: UpdateVariableRequest,
+    ) -> Result<SiteVariable> {
+        self.update_variable("account", variable_id, req).await
+    }
+
+    async fn delete_account_variable(&self, variable_id: i32) -> Result<()> {
+        self.delete_variable("account", variable_id).await
+    }
+}
+
+/// Site and account variables share their endpoints below `scope`, which is
+/// `site/{uid}` or `account`.
+impl DattoClient {
+    async fn get_variables(&self, scope: &str) -> Result<Vec<SiteVariable>> {
+        let response: types::SiteVariablesResponse =
+            self.get_json(&format!("{}/variables", scope), "variables").await?;
+        Ok(response.variables)
+    }
+
+    async fn create_variable(
+        &self,
+        scope: &str,
+        req: CreateVariableRequest,
+    ) -> Result<SiteVariable> {
+        let request = self.request(Method::PUT, &format!("{}/variable", scope))?.json(&req);
+        let text = self.send(request, "create variable").await?;
+
+        tracing::debug!(body = %text, "create variable response");
 
         // Handle empty response by returning a dummy variable
         if text.trim().is_empty() || text == "null" {
@@ -96,44 +113,17 @@ impl VariablesApi for DattoClient {
         }
     }
 
-    async fn update_site_variable(
+    async fn update_variable(
         &self,
-        site_uid: &str,
+        scope: &str,
         variable_id: i32,
         req: UpdateVariableRequest,
     ) -> Result<SiteVariable> {
-        let access_token = self.access_token.as_ref().context("Not authenticated")?;
-        let url = format!(
-            "{}/api/v2/site/{}/variable/{}",
-            self.config.api_url, site_uid, variable_id
-        );
-
-        let response = self
-            .client
-            .post(&url)
-            .bearer_auth(access_token)
-            .json(&req)
-            .send()
-            .await
-            .context("Failed to send update variable request")?;
-
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-
-        // DEBUG LOG
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "UPDATE VARIABLE RESPONSE Status: {}", status).unwrap();
-                writeln!(f, "UPDATE VARIABLE RESPONSE Body: {}", text).unwrap();
-            });
-
-        if !status.is_success() {
-            anyhow::bail!("API request failed with status: {} - {}", status, text);
-        }
+        let path = format!("{}/variable/{}", scope, variable_id);
+        let request = self.request(Method::POST, &path)?.json(&req);
+        let text = self.send(request, "update variable").await?;
+
+        tracing::debug!(body = %text, "update variable response");
 
         // Handle empty response by constructing the variable locally
         if text.trim().is_empty() || text == "null" {
@@ -149,4 +139,10 @@ impl VariablesApi for DattoClient {
             Ok(variable)
         }
     }
+
+    async fn delete_variable(&self, scope: &str, variable_id: i32) -> Result<()> {
+        let path = format!("{}/variable/{}", scope, variable_id);
+        self.send(self.request(Method::DELETE, &path)?, "delete variable").await?;
+        Ok(())
+    }
 }
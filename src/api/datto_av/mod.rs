@@ -1,30 +1,53 @@
 pub mod types;
 
+use crate::api::http::{Auth, HttpClient};
 use crate::config::DattoAvConfig;
-use anyhow::{Context, Result};
-use reqwest::Client;
-use std::time::Duration;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder};
 use types::AgentDetail;
 
 #[derive(Clone, Debug)]
 pub struct DattoAvClient {
-    pub(crate) client: Client,
+    pub(crate) http: HttpClient,
     pub(crate) config: DattoAvConfig,
 }
 
+/// Datto AV (EDR) operations used by the UI; implemented by `DattoAvClient`
+/// and `api::mock::MockAv`.
+#[async_trait]
+pub trait AvApi: Send + Sync + std::fmt::Debug {
+    async fn get_agent_details(&self, hostname: &str) -> Result<Vec<AgentDetail>>;
+    async fn get_agent_detail(&self, id: &str) -> Result<AgentDetail>;
+    async fn scan_agent(&self, id: &str) -> Result<()>;
+    /// Removes the agent from the Datto AV console.
+    async fn delete_agent(&self, id: &str) -> Result<()>;
+    async fn get_agent_alerts(&self, agent_id: &str) -> Result<Vec<types::Alert>>;
+    async fn get_agent_policies(&self, agent_id: &str) -> Result<serde_json::Value>;
+}
+
 impl DattoAvClient {
     pub fn new(config: DattoAvConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .context("Failed to build HTTP client")?;
-        Ok(Self { client, config })
+        Ok(Self {
+            http: HttpClient::new("Datto AV", &config.http)?,
+            config,
+        })
     }
 
-    /// Fetch agent details by hostname using a filter
-    pub async fn get_agent_details(&self, hostname: &str) -> Result<Vec<AgentDetail>> {
-        let url = format!("{}/api/AgentDetails", self.config.url);
+    /// A request to `path` below `/api/`, with the secret as the
+    /// `Authorization` header.
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!("{}/api/{}", self.config.url, path);
+        self.http
+            .request(method, &url, Some(Auth::Key(&self.config.secret)))
+            .header("Accept", "application/json")
+    }
+}
 
+#[async_trait]
+impl AvApi for DattoAvClient {
+    /// Fetch agent details by hostname using a filter
+    async fn get_agent_details(&self, hostname: &str) -> Result<Vec<AgentDetail>> {
         // Filter: {"where":{"hostname":"[INSERT HOSTNAME HERE]"}}
         // Loopback filter often passed as "filter" query param
         let filter_json = serde_json::json!({
@@ -36,87 +59,35 @@ impl DattoAvClient {
         // Pass as "filter" query parameter
         let params = [("filter", filter_json.to_string())];
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("{}", self.config.secret))
-            .header("Accept", "application/json")
-            .query(&params)
-            .send()
-            .await
-            .context("Failed to send get_agent_details request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get agent details failed: {} - {}", status, text);
-        }
-
-        let agents = response
-            .json::<Vec<AgentDetail>>()
-            .await
-            .context("Failed to parse agent details response")?;
-
-        Ok(agents)
+        let request = self.request(Method::GET, "AgentDetails").query(&params);
+        self.http.send_json(request, "agent details").await
     }
 
     /// Fetch single agent detail by ID
-    pub async fn get_agent_detail(&self, id: &str) -> Result<AgentDetail> {
-        let url = format!("{}/api/AgentDetails/{}", self.config.url, id);
-
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("{}", self.config.secret))
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .context("Failed to send get_agent_detail request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get agent detail failed: {} - {}", status, text);
-        }
-
-        let agent = response
-            .json::<AgentDetail>()
-            .await
-            .context("Failed to parse agent detail response")?;
-
-        Ok(agent)
+    async fn get_agent_detail(&self, id: &str) -> Result<AgentDetail> {
+        let request = self.request(Method::GET, &format!("AgentDetails/{}", id));
+        self.http.send_json(request, "agent detail").await
     }
 
     /// Trigger a scan for an agent
-    pub async fn scan_agent(&self, id: &str) -> Result<()> {
-        let url = format!("{}/api/Agents/scan", self.config.url);
-
+    async fn scan_agent(&self, id: &str) -> Result<()> {
         let body = serde_json::json!({
             "id": id
         });
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("{}", self.config.secret))
-            .header("Accept", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .context("Failed to send scan_agent request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Scan agent failed: {} - {}", status, text);
-        }
-
+        let request = self.request(Method::POST, "Agents/scan").json(&body);
+        self.http.send(request, "scan agent").await?;
         Ok(())
     }
 
-    pub async fn get_agent_alerts(&self, agent_id: &str) -> Result<Vec<types::Alert>> {
-        let url = format!("{}/api/Alerts", self.config.url);
+    /// Remove an agent from the console
+    async fn delete_agent(&self, id: &str) -> Result<()> {
+        let request = self.request(Method::DELETE, &format!("Agents/{}", id));
+        self.http.send(request, "delete agent").await?;
+        Ok(())
+    }
 
+    async fn get_agent_alerts(&self, agent_id: &str) -> Result<Vec<types::Alert>> {
         // Filter by agentId and sort by createdOn DESC, limit 5
         let filter = serde_json::json!({
             "where": {
@@ -128,56 +99,12 @@ impl DattoAvClient {
 
         let query = [("filter", filter.to_string())];
 
-        // Note: The example filter string provided by user was JSON encoded string.
-        // reqwest can handle query params, but we need to ensure it's passed as 'filter={json}'
-
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("{}", self.config.secret))
-            .header("Accept", "application/json")
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to fetch alerts")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Failed to fetch alerts: {} - {}", status, text);
-        }
-
-        let alerts: Vec<types::Alert> = response
-            .json()
-            .await
-            .context("Failed to parse alerts response")?;
-
-        Ok(alerts)
+        let request = self.request(Method::GET, "Alerts").query(&query);
+        self.http.send_json(request, "alerts").await
     }
 
-    pub async fn get_agent_policies(&self, agent_id: &str) -> Result<serde_json::Value> {
-        let url = format!("{}/api/Agents/{}/getAgentPolicies", self.config.url, agent_id);
-
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("{}", self.config.secret))
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .context("Failed to send get_agent_policies request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get agent policies failed: {} - {}", status, text);
-        }
-
-        let policies = response
-            .json::<serde_json::Value>()
-            .await
-            .context("Failed to parse agent policies response")?;
-
-        Ok(policies)
+    async fn get_agent_policies(&self, agent_id: &str) -> Result<serde_json::Value> {
+        let path = format!("Agents/{}/getAgentPolicies", agent_id);
+        self.http.send_json(self.request(Method::GET, &path), "agent policies").await
     }
 }
@@ -0,0 +1,149 @@
+pub mod types;
+
+use crate::api::error::ApiError;
+use crate::api::http::{Auth, HttpClient};
+use crate::config::DefenderConfig;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder};
+use serde::Deserialize;
+use types::{Alert, ListResponse, Machine, MachineAction};
+
+/// Defender for Business shares the Defender for Endpoint API.
+const API_URL: &str = "https://api.securitycenter.microsoft.com/api";
+const SCOPE: &str = "https://api.securitycenter.microsoft.com/.default";
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct DefenderClient {
+    pub(crate) http: HttpClient,
+    pub(crate) config: DefenderConfig,
+    pub(crate) access_token: Option<String>,
+}
+
+/// Microsoft Defender for Business operations used by the UI; implemented by
+/// `DefenderClient` and `api::mock::MockDefender`.
+#[async_trait]
+pub trait DefenderApi: Send + Sync + std::fmt::Debug {
+    /// Machines whose DNS name is `hostname`, ignoring case.
+    async fn get_machines(&self, hostname: &str) -> Result<Vec<Machine>>;
+    /// The machine's alerts that aren't resolved, newest first.
+    async fn get_machine_alerts(&self, machine_id: &str) -> Result<Vec<Alert>>;
+    /// Starts a quick antivirus scan, with `comment` recorded in the
+    /// action center; the returned action follows the scan.
+    async fn run_av_scan(&self, machine_id: &str, comment: &str) -> Result<MachineAction>;
+    async fn get_machine_action(&self, action_id: &str) -> Result<MachineAction>;
+}
+
+impl DefenderClient {
+    pub fn new(config: DefenderConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new("Microsoft Defender", &config.http)?,
+            config,
+            access_token: None,
+        })
+    }
+
+    /// Gets an app-only token for the Azure app registration.
+    pub async fn authenticate(&mut self) -> Result<()> {
+        let url = format!(
+            "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+            self.config.tenant_id
+        );
+        let params = [
+            ("grant_type", "client_credentials"),
+            ("client_id", &self.config.client_id),
+            ("client_secret", &self.config.client_secret),
+            ("scope", SCOPE),
+        ];
+
+        let request = self.http.request(Method::POST, &url, None).form(&params);
+        let response = self.http.execute(request).await.context("Failed to send auth request")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let message = format!("Authentication failed: {} - {}", status, text);
+            return Err(ApiError::Auth(message).into());
+        }
+
+        let token = response.json::<TokenResponse>().await.context("Failed to parse token")?;
+        self.access_token = Some(token.access_token);
+        Ok(())
+    }
+
+    /// A request to `path` below `/api/` with the access token.
+    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        let token = self.access_token.as_ref().ok_or_else(ApiError::not_authenticated)?;
+        let url = format!("{}/{}", API_URL, path);
+        Ok(self.http.request(method, &url, Some(Auth::Bearer(token))))
+    }
+}
+
+#[async_trait]
+impl DefenderApi for DefenderClient {
+    async fn get_machines(&self, hostname: &str) -> Result<Vec<Machine>> {
+        // DNS names are stored lowercased and often fully qualified; OData
+        // escapes quotes by doubling them
+        let name = hostname.to_lowercase().replace('\'', "''");
+        let filter = format!(
+            "computerDnsName eq '{}' or startswith(computerDnsName,'{}.')",
+            name, name
+        );
+        let request = self.request(Method::GET, "machines")?.query(&[("$filter", filter)]);
+        let response: ListResponse<Machine> = self.http.send_json(request, "machines").await?;
+        Ok(response.value)
+    }
+
+    async fn get_machine_alerts(&self, machine_id: &str) -> Result<Vec<Alert>> {
+        let path = format!("machines/{}/alerts", machine_id);
+        let response: ListResponse<Alert> =
+            self.http.send_json(self.request(Method::GET, &path)?, "machine alerts").await?;
+        Ok(active_alerts(response.value))
+    }
+
+    async fn run_av_scan(&self, machine_id: &str, comment: &str) -> Result<MachineAction> {
+        let path = format!("machines/{}/runAntiVirusScan", machine_id);
+        let body = serde_json::json!({ "Comment": comment, "ScanType": "Quick" });
+        let request = self.request(Method::POST, &path)?.json(&body);
+        self.http.send_json(request, "antivirus scan").await
+    }
+
+    async fn get_machine_action(&self, action_id: &str) -> Result<MachineAction> {
+        let path = format!("machineactions/{}", action_id);
+        self.http.send_json(self.request(Method::GET, &path)?, "machine action").await
+    }
+}
+
+/// The alerts still open, newest first.
+fn active_alerts(mut alerts: Vec<Alert>) -> Vec<Alert> {
+    alerts.retain(Alert::is_active);
+    // RFC 3339 timestamps sort chronologically as strings
+    alerts.sort_by(|a, b| b.alert_creation_time.cmp(&a.alert_creation_time));
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_alerts_drop_resolved_and_sort_newest_first() {
+        let response: ListResponse<Alert> = serde_json::from_value(serde_json::json!({
+            "@odata.context": "https://api.securitycenter.microsoft.com/api/$metadata#Alerts",
+            "value": [
+                { "id": "a1", "status": "New", "alertCreationTime": "2026-03-01T10:00:00Z" },
+                { "id": "a2", "status": "Resolved", "alertCreationTime": "2026-03-03T10:00:00Z" },
+                { "id": "a3", "status": "InProgress", "alertCreationTime": "2026-03-02T10:00:00Z" },
+            ]
+        }))
+        .unwrap();
+
+        let ids: Vec<_> = active_alerts(response.value).into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, ["a3", "a1"]);
+    }
+}
@@ -0,0 +1,54 @@
+use serde::Deserialize;
+
+/// A device onboarded to Defender, from `/api/machines`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Machine {
+    pub id: String,
+    pub computer_dns_name: Option<String>,
+    /// "Active", "Inactive", "ImpairedCommunication", "NoSensorData", ...
+    pub health_status: Option<String>,
+    /// "None", "Informational", "Low", "Medium" or "High"
+    pub risk_score: Option<String>,
+    pub exposure_level: Option<String>,
+    pub onboarding_status: Option<String>,
+    pub os_platform: Option<String>,
+    pub last_seen: Option<String>,
+}
+
+/// A Defender alert, from `/api/machines/{id}/alerts`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub id: String,
+    pub title: Option<String>,
+    pub severity: Option<String>,
+    /// "New", "InProgress" or "Resolved"
+    pub status: Option<String>,
+    pub category: Option<String>,
+    pub alert_creation_time: Option<String>,
+}
+
+impl Alert {
+    /// Whether the alert still needs attention.
+    pub fn is_active(&self) -> bool {
+        self.status.as_deref() != Some("Resolved")
+    }
+}
+
+/// A response action taken on a machine, e.g. an antivirus scan, from
+/// `/api/machineactions`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineAction {
+    pub id: String,
+    /// "Pending", "InProgress", "Succeeded", "Failed", "TimeOut" or "Cancelled"
+    pub status: Option<String>,
+    pub last_update_date_time_utc: Option<String>,
+}
+
+/// The OData envelope list endpoints wrap their results in.
+#[derive(Deserialize, Debug)]
+pub(crate) struct ListResponse<T> {
+    pub value: Vec<T>,
+}
@@ -0,0 +1,180 @@
+use crate::api::read_only::READ_ONLY_MESSAGE;
+use reqwest::StatusCode;
+use std::fmt;
+use std::time::Duration;
+
+/// Wait before retrying a failure that didn't say how long to wait.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// What went wrong with an API call, carried by the app's events. Clients
+/// return `anyhow` errors with an `ApiError` at the root (see `api::http`);
+/// `From<anyhow::Error>` finds it again, keeping the full message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// Not authenticated yet, or the credentials were refused (401/403)
+    Auth(String),
+    /// The API's request quota is used up (429)
+    RateLimited { retry_after: Option<Duration>, message: String },
+    /// The item is gone, e.g. deleted in the web portal (404)
+    NotFound(String),
+    /// The response didn't have the expected shape
+    Deserialize(String),
+    /// The server couldn't be reached or didn't answer in time
+    Network(String),
+    /// Any other error status
+    Status { status: StatusCode, message: String },
+    /// Refused by read-only mode without being sent
+    ReadOnly,
+    /// Failures that aren't about the API itself
+    Other(String),
+}
+
+/// The result carried by events of API calls.
+pub type ApiResult<T> = Result<T, ApiError>;
+
+impl ApiError {
+    /// The error for an error status, with the body the API sent.
+    pub fn from_status(
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        message: String,
+    ) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ApiError::Auth(message),
+            StatusCode::NOT_FOUND => ApiError::NotFound(message),
+            StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited { retry_after, message },
+            _ => ApiError::Status { status, message },
+        }
+    }
+
+    /// A request made before the client authenticated.
+    pub fn not_authenticated() -> Self {
+        ApiError::Auth("Not authenticated".to_string())
+    }
+
+    /// How long to wait before trying again; `None` when retrying wouldn't help.
+    pub fn retry_delay(&self) -> Option<Duration> {
+        match self {
+            ApiError::RateLimited { retry_after, .. } => {
+                Some(retry_after.unwrap_or(DEFAULT_RETRY_DELAY))
+            }
+            ApiError::Network(_) => Some(DEFAULT_RETRY_DELAY),
+            ApiError::Status { status, .. } if status.is_server_error() => {
+                Some(DEFAULT_RETRY_DELAY)
+            }
+            _ => None,
+        }
+    }
+
+    /// What the user can do about the error, shown after its message.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ApiError::Auth(_) => Some("check the profile's API keys"),
+            ApiError::RateLimited { .. } => Some("the API's request quota is used up"),
+            ApiError::NotFound(_) => Some("it may have been deleted"),
+            ApiError::Network(_) => Some("check the connection or proxy settings"),
+            _ => None,
+        }
+    }
+
+    /// The message followed by the hint, for error banners.
+    pub fn describe(&self) -> String {
+        match self.hint() {
+            Some(hint) => format!("{} ({})", self.message(), hint),
+            None => self.message().to_string(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::Auth(message)
+            | ApiError::NotFound(message)
+            | ApiError::Deserialize(message)
+            | ApiError::Network(message)
+            | ApiError::Other(message)
+            | ApiError::RateLimited { message, .. }
+            | ApiError::Status { message, .. } => message,
+            ApiError::ReadOnly => READ_ONLY_MESSAGE,
+        }
+    }
+
+    /// The same kind of error with another message.
+    fn with_message(&self, message: String) -> Self {
+        match self {
+            ApiError::Auth(_) => ApiError::Auth(message),
+            ApiError::NotFound(_) => ApiError::NotFound(message),
+            ApiError::Deserialize(_) => ApiError::Deserialize(message),
+            ApiError::Network(_) => ApiError::Network(message),
+            ApiError::Other(_) => ApiError::Other(message),
+            ApiError::RateLimited { retry_after, .. } => {
+                ApiError::RateLimited { retry_after: *retry_after, message }
+            }
+            ApiError::Status { status, .. } => ApiError::Status { status: *status, message },
+            ApiError::ReadOnly => ApiError::ReadOnly,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<anyhow::Error> for ApiError {
+    /// Keeps the whole context chain as the message. Errors raised without an
+    /// `ApiError` are classified by their cause.
+    fn from(error: anyhow::Error) -> Self {
+        let message = format!("{:#}", error);
+        if let Some(api) = error.chain().find_map(|e| e.downcast_ref::<ApiError>()) {
+            return api.with_message(message);
+        }
+        if error.chain().any(|e| e.is::<reqwest::Error>()) {
+            ApiError::Network(message)
+        } else if error.chain().any(|e| e.is::<serde_json::Error>()) {
+            ApiError::Deserialize(message)
+        } else {
+            ApiError::Other(message)
+        }
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError::Other(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_kind_survives_context_and_decides_retries() {
+        let limited =
+            ApiError::from_status(StatusCode::TOO_MANY_REQUESTS, None, "slow down".to_string());
+        let error = ApiError::from(
+            Err::<(), _>(anyhow::Error::new(limited))
+                .context("Failed to fetch sites")
+                .unwrap_err(),
+        );
+        assert_eq!(error.to_string(), "Failed to fetch sites: slow down");
+        assert_eq!(error.retry_delay(), Some(DEFAULT_RETRY_DELAY));
+
+        let gone = ApiError::from_status(StatusCode::NOT_FOUND, None, String::new());
+        assert!(matches!(gone, ApiError::NotFound(_)));
+        assert_eq!(gone.retry_delay(), None);
+        let refused = ApiError::from_status(StatusCode::FORBIDDEN, None, String::new());
+        assert!(matches!(refused, ApiError::Auth(_)));
+        let outage =
+            ApiError::from_status(StatusCode::BAD_GATEWAY, None, "upstream down".to_string());
+        assert!(outage.retry_delay().is_some());
+
+        let parse = serde_json::from_str::<i32>("{").context("Failed to parse JSON").unwrap_err();
+        assert!(matches!(ApiError::from(parse), ApiError::Deserialize(_)));
+        assert_eq!(ApiError::from(anyhow::anyhow!("oops")), ApiError::Other("oops".to_string()));
+    }
+}
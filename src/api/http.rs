@@ -0,0 +1,191 @@
+use crate::api::datto::rate_limit::{Quota, RateLimit, SendLimited};
+use crate::api::error::ApiError;
+use crate::api::throttle::{self, Limiter, SendThrottled};
+use crate::config::{HttpSettings, NetworkSettings};
+use anyhow::{Context, Result};
+use reqwest::{Certificate, Client, Method, NoProxy, Proxy, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+/// Sent with every request so API owners can tell the tool's traffic apart.
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// The proxy and extra root certificates of `NetworkSettings`, ready for
+/// the client builder.
+#[derive(Clone)]
+struct Network {
+    proxy: Option<Proxy>,
+    certificates: Vec<Certificate>,
+}
+
+static NETWORK: RwLock<Network> = RwLock::new(Network { proxy: None, certificates: Vec::new() });
+
+/// Applies `settings` to the clients built from now on. Fails on a proxy URL
+/// that doesn't parse or a CA bundle that can't be read or holds no
+/// certificates.
+pub fn configure(settings: &NetworkSettings) -> Result<()> {
+    let proxy = match &settings.proxy {
+        Some(url) => {
+            let proxy = Proxy::all(url).with_context(|| format!("Invalid proxy '{}'", url))?;
+            Some(proxy.no_proxy(settings.no_proxy.as_deref().and_then(NoProxy::from_string)))
+        }
+        None => None,
+    };
+    let mut certificates = Vec::new();
+    for path in &settings.ca_bundles {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+        let bundle = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Failed to parse CA bundle {}", path.display()))?;
+        if bundle.is_empty() {
+            anyhow::bail!("No certificates in CA bundle {}", path.display());
+        }
+        certificates.extend(bundle);
+    }
+    *NETWORK.write().unwrap_or_else(|e| e.into_inner()) = Network { proxy, certificates };
+    Ok(())
+}
+
+/// How a request authenticates.
+#[derive(Debug, Clone, Copy)]
+pub enum Auth<'a> {
+    /// `Authorization: Bearer <token>`
+    Bearer(&'a str),
+    /// The `Authorization` header as is, for APIs that take a bare key
+    Key(&'a str),
+}
+
+/// The HTTP side of an API client, shared by its clones. Every request goes
+/// through the client's concurrency limit and is retried on transient
+/// failures (see `throttle`); Datto RMM requests also stay within the API
+/// key's quota.
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    client: Client,
+    limiter: Limiter,
+    rate_limit: Option<Arc<RateLimit>>,
+    /// Names the API in logs and errors, e.g. "Sophos Central"
+    service: &'static str,
+}
+
+impl HttpClient {
+    pub fn new(service: &'static str, settings: &HttpSettings) -> Result<Self> {
+        let network = NETWORK.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut builder = Client::builder().timeout(settings.timeout).user_agent(USER_AGENT);
+        if let Some(proxy) = network.proxy {
+            builder = builder.proxy(proxy);
+        }
+        if !network.certificates.is_empty() {
+            builder = builder.tls_certs_merge(network.certificates);
+        }
+        let client = builder.build().context("Failed to build HTTP client")?;
+        Ok(Self {
+            client,
+            limiter: Limiter::new(settings.max_concurrent_requests),
+            rate_limit: None,
+            service,
+        })
+    }
+
+    /// Tracks the quota the API reports in its `X-RateLimit-*` headers and
+    /// holds requests back when it runs low.
+    pub fn with_rate_limit(mut self) -> Self {
+        self.rate_limit = Some(Arc::default());
+        self
+    }
+
+    /// The remaining quota, with `with_rate_limit` once the API reported one
+    pub fn quota(&self) -> Option<Quota> {
+        self.rate_limit.as_ref().and_then(|limit| limit.quota())
+    }
+
+    /// A request to `url`, authenticated with `auth` if given.
+    pub fn request(&self, method: Method, url: &str, auth: Option<Auth>) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match auth {
+            Some(Auth::Bearer(token)) => request.bearer_auth(token),
+            Some(Auth::Key(key)) => request.header(reqwest::header::AUTHORIZATION, key),
+            None => request,
+        }
+    }
+
+    /// Sends `request` within the limits, whatever status it's answered with.
+    pub async fn execute(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let started = Instant::now();
+        let response = match &self.rate_limit {
+            Some(limit) => request.send_limited(limit, &self.limiter).await?,
+            None => request.send_throttled(&self.limiter).await?,
+        };
+        tracing::debug!(
+            service = self.service,
+            url = %response.url(),
+            status = %response.status(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "request"
+        );
+        Ok(response)
+    }
+
+    /// Sends `request` and returns the response body; an error status fails
+    /// with the body the API sent, as the `ApiError` for the status. `what`
+    /// names the request in errors.
+    pub async fn send(&self, request: RequestBuilder, what: &str) -> Result<String> {
+        let response = self
+            .execute(request)
+            .await
+            .with_context(|| format!("Failed to send {} request", what))?;
+
+        let status = response.status();
+        let retry_after = throttle::retry_after(&response);
+        let text = response.text().await.context("Failed to get response text")?;
+        if !status.is_success() {
+            let message =
+                format!("{} request failed with status: {} - {}", self.service, status, text);
+            return Err(ApiError::from_status(status, retry_after, message).into());
+        }
+        Ok(text)
+    }
+
+    /// `send` for requests answered with JSON.
+    pub async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        what: &str,
+    ) -> Result<T> {
+        let text = self.send(request, what).await?;
+        serde_json::from_str(&text).with_context(|| format!("Failed to parse {} response", what))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_carry_their_auth() -> Result<()> {
+        let http = HttpClient::new("Test", &HttpSettings::default())?;
+        let authorization = |auth: Option<Auth>| -> Result<Option<String>> {
+            let request = http.request(Method::GET, "https://example.com/api", auth).build()?;
+            let header = request.headers().get(reqwest::header::AUTHORIZATION);
+            Ok(header.map(|value| value.to_str().unwrap().to_string()))
+        };
+        assert_eq!(authorization(Some(Auth::Bearer("t0k3n")))?.as_deref(), Some("Bearer t0k3n"));
+        assert_eq!(authorization(Some(Auth::Key("s3cret")))?.as_deref(), Some("s3cret"));
+        assert_eq!(authorization(None)?, None);
+        assert!(http.quota().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_broken_network_settings_are_refused() {
+        let proxy = NetworkSettings { proxy: Some("not a url".to_string()), ..Default::default() };
+        assert!(configure(&proxy).is_err());
+        let missing = NetworkSettings {
+            ca_bundles: vec!["/nonexistent/corp-root.pem".into()],
+            ..Default::default()
+        };
+        let err = configure(&missing).unwrap_err();
+        assert!(format!("{:#}", err).contains("corp-root.pem"));
+    }
+}
@@ -0,0 +1,89 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+/// Characters of a skipped item kept in the log.
+const SNIPPET_LEN: usize = 400;
+
+/// A JSON list parsed item by item: an item that doesn't fit `T` (e.g. an
+/// unexpected null) is logged and counted instead of failing the response.
+#[derive(Debug, Clone)]
+pub struct Lenient<T> {
+    pub items: Vec<T>,
+    /// Items that didn't parse
+    pub errors: usize,
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Lenient<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+        let mut items = Vec::with_capacity(values.len());
+        let mut errors = 0;
+        for (index, value) in values.iter().enumerate() {
+            match T::deserialize(value) {
+                Ok(item) => items.push(item),
+                Err(e) => {
+                    errors += 1;
+                    tracing::warn!(
+                        item = std::any::type_name::<T>(),
+                        index,
+                        error = %e,
+                        snippet = %snippet(value),
+                        "skipped a list item that didn't parse"
+                    );
+                }
+            }
+        }
+        Ok(Self { items, errors })
+    }
+}
+
+fn snippet(value: &serde_json::Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(SNIPPET_LEN) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// "38 of 40 devices loaded (2 parse errors)" when items were skipped.
+pub fn partial_load_message(what: &str, loaded: usize, errors: usize) -> Option<String> {
+    (errors > 0).then(|| {
+        let plural = if errors == 1 { "" } else { "s" };
+        let total = loaded + errors;
+        format!("{} of {} {} loaded ({} parse error{})", loaded, total, what, errors, plural)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+    use crate::api::datto::types::DevicesResponse;
+
+    #[test]
+    fn test_bad_items_are_skipped_and_counted() {
+        let device = |uid: &str, hostname: serde_json::Value| {
+            DeviceBuilder::new("").uid(uid).with("hostname", hostname).json()
+        };
+        let response: DevicesResponse = serde_json::from_value(serde_json::json!({
+            "pageDetails": { "count": 3 },
+            "devices": [
+                device("d1", "DC01".into()),
+                device("d2", serde_json::Value::Null),
+                device("d3", "FS01".into()),
+            ],
+        }))
+        .unwrap();
+        let uids: Vec<_> = response.devices.iter().map(|d| d.uid.as_str()).collect();
+        assert_eq!(uids, ["d1", "d3"]);
+        assert_eq!(response.parse_errors, 1);
+        assert_eq!(response.page_len(), 3);
+
+        assert_eq!(
+            partial_load_message("devices", 38, 2).as_deref(),
+            Some("38 of 40 devices loaded (2 parse errors)")
+        );
+        assert_eq!(partial_load_message("devices", 40, 0), None);
+        assert!(serde_json::from_str::<Lenient<i32>>("{}").is_err());
+    }
+}
@@ -0,0 +1,986 @@
+//! In-memory implementations of the API traits, used by `--demo` and in tests.
+//!
+//! Data is fixed so screens render the same way every run. Writes (variables,
+//! UDFs, jobs) succeed; site and account variables and UDFs are kept in memory
+//! so edits show up on the next fetch.
+
+use crate::api::datto::LimitsApi;
+use crate::api::datto::activity::ActivityApi;
+use crate::api::datto::devices::DevicesApi;
+use crate::api::datto::jobs::JobsApi;
+use crate::api::datto::sites::SitesApi;
+use crate::api::datto::types::{
+    ActivityLogsResponse, ComponentsResponse, CreateVariableRequest, Device, DevicesResponse,
+    JobComponent, JobResult, JobStdOutput, OpenAlertsResponse, ProxySettings, QuickJobRequest,
+    QuickJobResponse, Site, SiteVariable, SitesResponse, SoftwareResponse, Udf, UdfUpdate,
+    UpdateDeviceRequest, UpdateSiteRequest, UpdateVariableRequest,
+};
+use crate::api::datto::variables::VariablesApi;
+use crate::api::datto_av::AvApi;
+use crate::api::datto_av::types::{AgentDetail, Alert as AvAlert};
+use crate::api::defender::DefenderApi;
+use crate::api::defender::types::{Alert as DefenderAlert, Machine, MachineAction};
+use crate::api::rocket_cyber::agents::AgentsApi;
+use crate::api::rocket_cyber::incidents::IncidentsApi;
+use crate::api::rocket_cyber::types::{Agent, Incident};
+use crate::api::sentinelone::SentinelOneApi;
+use crate::api::sentinelone::types::{Agent as S1Agent, Threat};
+use crate::api::sophos::{Case, Endpoint, MdrApi, Tenant};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Fixed "now" for generated timestamps (2026-01-15T12:00:00Z, in ms).
+const NOW_MS: i64 = 1_768_478_400_000;
+const HOUR_MS: i64 = 3_600_000;
+
+/// (site uid, site name, hostname prefix)
+const SITES: [(&str, &str, &str); 3] = [
+    ("demo-site-acme", "Acme Corp", "ACME"),
+    ("demo-site-globex", "Globex", "GLOBEX"),
+    ("demo-site-initech", "Initech", "INITECH"),
+];
+
+/// (hostname suffix, operating system, online, patch status, hours since last seen)
+const DEVICES: [(&str, &str, bool, &str, i64); 4] = [
+    ("DC01", "Microsoft Windows Server 2022 Standard", true, "FullyPatched", 0),
+    ("FS01", "Microsoft Windows Server 2019 Standard", false, "RebootRequired", 30),
+    ("WS01", "Microsoft Windows 11 Pro", true, "ApprovedPending", 1),
+    ("LT02", "Microsoft Windows 11 Pro", false, "NoData", 72),
+];
+
+/// Warranty end date per entry in `DEVICES` (one expired, one missing).
+const WARRANTY_DATES: [Option<&str>; 4] = [Some("2028-06-30"), Some("2025-03-31"), Some("2026-11-01"), None];
+
+/// UDF the demo devices keep their tags in (`tags_udf` in the config).
+pub const TAGS_UDF: usize = 10;
+
+/// Comma-separated tags per entry in `DEVICES`.
+const DEVICE_TAGS: [&str; 4] = ["domain-controller", "file-server,backup", "pos-terminal", ""];
+
+/// Antivirus product and status for a demo device. Most run Datto AV, with a
+/// few on Defender and SentinelOne; a few gaps exist so the AV coverage
+/// report has something to show.
+fn antivirus_json(site_index: usize, device_index: usize) -> Value {
+    match (site_index, device_index) {
+        (1, 3) => json!({ "antivirusProduct": "Datto AV", "antivirusStatus": "NotRunning" }),
+        (2, 2) => json!({ "antivirusProduct": null, "antivirusStatus": "NotDetected" }),
+        (0, 2) => {
+            json!({ "antivirusProduct": "Sentinel Agent", "antivirusStatus": "RunningAndUpToDate" })
+        }
+        (_, 1) => json!({ "antivirusProduct": "Windows Defender", "antivirusStatus": "RunningAndNotUpToDate" }),
+        _ => json!({ "antivirusProduct": "Datto AV", "antivirusStatus": "RunningAndUpToDate" }),
+    }
+}
+
+fn from_json<T: DeserializeOwned>(value: Value) -> T {
+    serde_json::from_value(value).expect("mock data matches API types")
+}
+
+fn page_details(count: usize) -> Value {
+    json!({ "count": count, "totalCount": count, "prevPageUrl": null, "nextPageUrl": null })
+}
+
+fn site_json(index: usize) -> Value {
+    let (uid, name, _) = SITES[index];
+    let online = DEVICES.iter().filter(|d| d.2).count();
+    json!({
+        "id": 1000 + index,
+        "uid": uid,
+        "name": name,
+        "description": format!("{} (demo data)", name),
+        "notes": null,
+        "onDemand": false,
+        "splashtopAutoInstall": true,
+        "devicesStatus": {
+            "numberOfDevices": DEVICES.len(),
+            "numberOfOnlineDevices": online,
+            "numberOfOfflineDevices": DEVICES.len() - online,
+        },
+        "portalUrl": format!("https://demo.invalid/site/{}", uid),
+    })
+}
+
+fn device_json(site_index: usize, device_index: usize) -> Value {
+    let (site_uid, site_name, prefix) = SITES[site_index];
+    let (suffix, os, online, patch_status, hours) = DEVICES[device_index];
+    let id = 2000 + site_index * 100 + device_index;
+    json!({
+        "id": id,
+        "uid": format!("demo-device-{}", id),
+        "siteId": 1000 + site_index,
+        "siteUid": site_uid,
+        "siteName": site_name,
+        "hostname": format!("{}-{}", prefix, suffix),
+        "description": null,
+        "online": online,
+        "lastSeen": NOW_MS - hours * HOUR_MS,
+        "operatingSystem": os,
+        "patchManagement": {
+            "patchStatus": patch_status,
+            "patchesApprovedPending": if patch_status == "ApprovedPending" { 3 } else { 0 },
+            "patchesNotApproved": 0,
+            "patchesInstalled": 42,
+        },
+        "deviceType": {
+            "category": if suffix.starts_with("DC") || suffix.starts_with("FS") { "Server" } else { "Desktop" },
+            "type": "Windows",
+        },
+        "intIpAddress": format!("10.{}.0.{}", site_index + 1, device_index + 10),
+        "extIpAddress": "203.0.113.10",
+        "lastLoggedInUser": format!("{}\\demo.user", prefix),
+        "domain": format!("{}.local", prefix.to_lowercase()),
+        "a64Bit": true,
+        "rebootRequired": patch_status == "RebootRequired",
+        "lastReboot": NOW_MS - 24 * 7 * HOUR_MS,
+        "warrantyDate": WARRANTY_DATES[device_index],
+        "udf": { format!("udf{}", TAGS_UDF): DEVICE_TAGS[device_index] },
+        "antivirus": antivirus_json(site_index, device_index),
+        "snmpEnabled": false,
+        "deviceClass": "device",
+        "portalUrl": format!("https://demo.invalid/device/{}", id),
+    })
+}
+
+/// Key of the account variables in `MockRmm::variables`; no site has it as UID.
+const ACCOUNT_VARIABLES: &str = "account";
+
+/// Demo Datto RMM account with three sites of four devices each.
+#[derive(Debug)]
+pub struct MockRmm {
+    /// By site UID, and account variables under `ACCOUNT_VARIABLES`
+    variables: Mutex<HashMap<String, Vec<SiteVariable>>>,
+    /// UDFs written, by device UID
+    udfs: Mutex<HashMap<String, Udf>>,
+}
+
+impl Default for MockRmm {
+    fn default() -> Self {
+        let mut variables = HashMap::new();
+        variables.insert(
+            SITES[0].0.to_string(),
+            from_json(json!([
+                { "id": 1, "name": "tuiMdrProvider", "value": "Sophos", "masked": false },
+                { "id": 2, "name": "tuiMdrId", "value": "demo-tenant-acme", "masked": false },
+                { "id": 3, "name": "tuiMdrRegion", "value": "us01", "masked": false },
+                { "id": 4, "name": "WifiPassword", "value": "********", "masked": true },
+            ])),
+        );
+        variables.insert(
+            ACCOUNT_VARIABLES.to_string(),
+            from_json(json!([
+                { "id": 1, "name": "PsaUrl", "value": "https://psa.demo.invalid", "masked": false },
+                { "id": 2, "name": "LocalAdminPassword", "value": "********", "masked": true },
+            ])),
+        );
+        Self {
+            variables: Mutex::new(variables),
+            udfs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl MockRmm {
+    fn sites(&self) -> Vec<Site> {
+        (0..SITES.len()).map(|i| from_json(site_json(i))).collect()
+    }
+
+    fn devices(&self) -> Vec<Device> {
+        let udfs = self.udfs.lock().unwrap();
+        (0..SITES.len())
+            .flat_map(|s| (0..DEVICES.len()).map(move |d| from_json(device_json(s, d))))
+            .map(|mut device: Device| {
+                if let Some(udf) = udfs.get(&device.uid) {
+                    device.udf = Some(udf.clone());
+                }
+                device
+            })
+            .collect()
+    }
+
+    fn alerts(&self, device: Option<&str>, site: Option<&str>) -> OpenAlertsResponse {
+        let alerts: Vec<Value> = self
+            .devices()
+            .iter()
+            .filter(|d| !d.online || d.reboot_required == Some(true))
+            .filter(|d| device.is_none_or(|uid| d.uid == uid))
+            .filter(|d| site.is_none_or(|uid| d.site_uid == uid))
+            .map(|d| {
+                let (priority, diagnostics) = if d.online {
+                    ("Moderate", "A reboot is required to finish installing patches")
+                } else {
+                    ("High", "Device has been offline for more than 24 hours")
+                };
+                json!({
+                    "alertUid": format!("demo-alert-{}", d.id),
+                    "priority": priority,
+                    "diagnostics": diagnostics,
+                    "resolved": false,
+                    "muted": false,
+                    "timestamp": NOW_MS - 2 * HOUR_MS,
+                    "alertSourceInfo": {
+                        "deviceUid": d.uid,
+                        "deviceName": d.hostname,
+                        "siteUid": d.site_uid,
+                        "siteName": d.site_name,
+                    },
+                })
+            })
+            .collect();
+        from_json(json!({ "pageDetails": page_details(alerts.len()), "alerts": alerts }))
+    }
+}
+
+#[async_trait]
+impl SitesApi for MockRmm {
+    async fn get_sites(&self, page: i32, _max: i32, site_name: Option<String>) -> Result<SitesResponse> {
+        let sites: Vec<Site> = if page > 0 {
+            Vec::new()
+        } else {
+            self.sites()
+                .into_iter()
+                .filter(|s| {
+                    site_name
+                        .as_ref()
+                        .is_none_or(|n| s.name.to_lowercase().contains(&n.to_lowercase()))
+                })
+                .collect()
+        };
+        Ok(SitesResponse {
+            page_details: from_json(page_details(sites.len())),
+            sites,
+        })
+    }
+
+    async fn update_site(&self, site_uid: &str, req: UpdateSiteRequest) -> Result<Site> {
+        let mut site = self.get_site(site_uid).await?;
+        site.name = req.name;
+        site.description = req.description;
+        site.notes = req.notes;
+        site.on_demand = req.on_demand;
+        site.splashtop_auto_install = req.splashtop_auto_install;
+        Ok(site)
+    }
+
+    async fn get_site(&self, site_uid: &str) -> Result<Site> {
+        self.sites()
+            .into_iter()
+            .find(|s| s.uid == site_uid)
+            .context("Site not found")
+    }
+
+    async fn get_site_open_alerts(&self, site_uid: &str, page: i32, _max: i32) -> Result<OpenAlertsResponse> {
+        if page > 0 {
+            return Ok(from_json(json!({ "pageDetails": page_details(0), "alerts": [] })));
+        }
+        Ok(self.alerts(None, Some(site_uid)))
+    }
+
+    async fn get_account_open_alerts(&self, page: i32, _max: i32) -> Result<OpenAlertsResponse> {
+        if page > 0 {
+            return Ok(from_json(json!({ "pageDetails": page_details(0), "alerts": [] })));
+        }
+        Ok(self.alerts(None, None))
+    }
+
+    async fn resolve_alert(&self, _alert_uid: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn update_site_proxy(&self, _site_uid: &str, _proxy: &ProxySettings) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_site_proxy(&self, _site_uid: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_site(&self, _site_uid: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DevicesApi for MockRmm {
+    async fn get_devices(&self, site_uid: &str, page: i32, _max: i32) -> Result<DevicesResponse> {
+        let devices: Vec<Device> = if page > 0 {
+            Vec::new()
+        } else {
+            self.devices()
+                .into_iter()
+                .filter(|d| d.site_uid == site_uid)
+                .collect()
+        };
+        Ok(DevicesResponse {
+            page_details: from_json(page_details(devices.len())),
+            devices,
+            parse_errors: 0,
+        })
+    }
+
+    async fn search_devices(&self, hostname: &str, page: i32, max: i32) -> Result<DevicesResponse> {
+        let query = hostname.to_lowercase();
+        let matches: Vec<Device> = self
+            .devices()
+            .into_iter()
+            .filter(|d| d.hostname.to_lowercase().contains(&query))
+            .collect();
+        let total = matches.len();
+        let max = max.max(1) as usize;
+        let start = page.max(0) as usize * max;
+        let devices: Vec<Device> = matches.into_iter().skip(start).take(max).collect();
+        let next = (start + devices.len() < total)
+            .then(|| format!("mock://devices?page={}", page + 1));
+        Ok(DevicesResponse {
+            page_details: from_json(json!({
+                "count": devices.len(),
+                "totalCount": total,
+                "prevPageUrl": null,
+                "nextPageUrl": next,
+            })),
+            devices,
+            parse_errors: 0,
+        })
+    }
+
+    async fn get_account_devices(&self, page: i32, _max: i32) -> Result<DevicesResponse> {
+        let devices = if page > 0 { Vec::new() } else { self.devices() };
+        Ok(DevicesResponse {
+            page_details: from_json(page_details(devices.len())),
+            devices,
+            parse_errors: 0,
+        })
+    }
+
+    async fn get_device(&self, device_uid: &str) -> Result<Device> {
+        self.devices().into_iter().find(|d| d.uid == device_uid).context("Device not found")
+    }
+
+    async fn update_device_udf(&self, device_uid: &str, update: &UdfUpdate) -> Result<()> {
+        let mut udf = self.get_device(device_uid).await?.udf.unwrap_or_default();
+        update.apply(&mut udf);
+        self.udfs.lock().unwrap().insert(device_uid.to_string(), udf);
+        Ok(())
+    }
+
+    async fn move_device(&self, _device_uid: &str, _site_uid: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn update_device_warranty(&self, _device_uid: &str, _date: Option<String>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn update_device(&self, _device_uid: &str, _req: &UpdateDeviceRequest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_device(&self, _device_uid: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_device_software(&self, _device_uid: &str, page: i32, _max: i32) -> Result<SoftwareResponse> {
+        let software = if page > 0 {
+            json!([])
+        } else {
+            json!([
+                { "name": "Google Chrome", "version": "131.0.6778.86" },
+                { "name": "Microsoft Edge", "version": "131.0.2903.70" },
+                { "name": "Microsoft 365 Apps for enterprise", "version": "16.0.18129.20116" },
+                { "name": "7-Zip 24.08 (x64)", "version": "24.08" },
+            ])
+        };
+        let count = software.as_array().map_or(0, |s| s.len());
+        Ok(from_json(json!({ "pageDetails": page_details(count), "software": software })))
+    }
+
+    async fn get_device_open_alerts(&self, device_uid: &str, page: i32, _max: i32) -> Result<OpenAlertsResponse> {
+        if page > 0 {
+            return Ok(from_json(json!({ "pageDetails": page_details(0), "alerts": [] })));
+        }
+        Ok(self.alerts(Some(device_uid), None))
+    }
+
+    async fn get_device_resolved_alerts(
+        &self,
+        device_uid: &str,
+        page: i32,
+        _max: i32,
+    ) -> Result<OpenAlertsResponse> {
+        if page > 0 {
+            return Ok(from_json(json!({ "pageDetails": page_details(0), "alerts": [] })));
+        }
+        // Relative to the real clock, so the history falls in the lookback window
+        let now = chrono::Utc::now().timestamp_millis();
+        let history = [
+            ("Moderate", "Disk C: above 90% used", 5, 4),
+            ("High", "Device has been offline for more than 24 hours", 50, 26),
+            ("Low", "Windows Update service stopped", 24 * 6, 24 * 6 - 1),
+            ("Critical", "CPU above 95% for 15 minutes", 24 * 40, 24 * 40 - 1),
+        ];
+        let alerts: Vec<Value> = history
+            .iter()
+            .enumerate()
+            .map(|(i, (priority, diagnostics, raised_hours, resolved_hours))| {
+                json!({
+                    "alertUid": format!("demo-resolved-{}-{}", device_uid, i),
+                    "priority": priority,
+                    "diagnostics": diagnostics,
+                    "resolved": true,
+                    "resolvedBy": if i == 1 { "demo.tech" } else { "Auto-resolved" },
+                    "resolvedOn": now - resolved_hours * HOUR_MS,
+                    "muted": i == 2,
+                    "timestamp": now - raised_hours * HOUR_MS,
+                })
+            })
+            .collect();
+        Ok(from_json(json!({ "pageDetails": page_details(alerts.len()), "alerts": alerts })))
+    }
+}
+
+#[async_trait]
+impl VariablesApi for MockRmm {
+    async fn get_site_variables(&self, site_uid: &str) -> Result<Vec<SiteVariable>> {
+        let variables = self.variables.lock().unwrap();
+        Ok(variables.get(site_uid).cloned().unwrap_or_default())
+    }
+
+    async fn create_site_variable(&self, site_uid: &str, req: CreateVariableRequest) -> Result<SiteVariable> {
+        let mut variables = self.variables.lock().unwrap();
+        let list = variables.entry(site_uid.to_string()).or_default();
+        let variable = SiteVariable {
+            id: list.iter().map(|v| v.id).max().unwrap_or(0) + 1,
+            name: req.name,
+            value: req.value,
+            masked: req.masked,
+        };
+        list.push(variable.clone());
+        Ok(variable)
+    }
+
+    async fn update_site_variable(
+        &self,
+        site_uid: &str,
+        variable_id: i32,
+        req: UpdateVariableRequest,
+    ) -> Result<SiteVariable> {
+        let mut variables = self.variables.lock().unwrap();
+        let variable = variables
+            .get_mut(site_uid)
+            .and_then(|list| list.iter_mut().find(|v| v.id == variable_id))
+            .context("Variable not found")?;
+        variable.name = req.name;
+        variable.value = req.value;
+        Ok(variable.clone())
+    }
+
+    async fn delete_site_variable(&self, site_uid: &str, variable_id: i32) -> Result<()> {
+        let mut variables = self.variables.lock().unwrap();
+        let list = variables.get_mut(site_uid).context("Variable not found")?;
+        let index = list
+            .iter()
+            .position(|v| v.id == variable_id)
+            .context("Variable not found")?;
+        list.remove(index);
+        Ok(())
+    }
+
+    async fn get_account_variables(&self) -> Result<Vec<SiteVariable>> {
+        self.get_site_variables(ACCOUNT_VARIABLES).await
+    }
+
+    async fn create_account_variable(&self, req: CreateVariableRequest) -> Result<SiteVariable> {
+        self.create_site_variable(ACCOUNT_VARIABLES, req).await
+    }
+
+    async fn update_account_variable(
+        &self,
+        variable_id: i32,
+        req: UpdateVariableRequest,
+    ) -> Result<SiteVariable> {
+        self.update_site_variable(ACCOUNT_VARIABLES, variable_id, req).await
+    }
+
+    async fn delete_account_variable(&self, variable_id: i32) -> Result<()> {
+        self.delete_site_variable(ACCOUNT_VARIABLES, variable_id).await
+    }
+}
+
+#[async_trait]
+impl JobsApi for MockRmm {
+    async fn get_job_result(&self, job_uid: &str, device_uid: &str) -> Result<JobResult> {
+        Ok(from_json(json!({
+            "jobUid": job_uid,
+            "deviceUid": device_uid,
+            "ranOn": NOW_MS,
+            "jobDeploymentStatus": "Success",
+            "componentResults": [{
+                "componentUid": "demo-component-1",
+                "componentName": "Restart Print Spooler",
+                "componentStatus": "Success",
+                "numberOfWarnings": 0,
+                "hasStdOut": true,
+                "hasStdErr": false,
+            }],
+        })))
+    }
+
+    async fn get_job_stdout(&self, _job_uid: &str, _device_uid: &str) -> Result<Vec<JobStdOutput>> {
+        Ok(from_json(json!([{
+            "componentUid": "demo-component-1",
+            "componentName": "Restart Print Spooler",
+            "stdData": "Stopping Spooler...\nStarting Spooler...\nDone.",
+        }])))
+    }
+
+    async fn get_job_stderr(&self, _job_uid: &str, _device_uid: &str) -> Result<Vec<JobStdOutput>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_job_components(&self, _job_uid: &str) -> Result<Vec<JobComponent>> {
+        Ok(from_json(json!([{
+            "uid": "demo-component-1",
+            "name": "Restart Print Spooler",
+            "variables": [],
+        }])))
+    }
+
+    async fn get_components(&self, page: Option<i32>) -> Result<ComponentsResponse> {
+        let components = if page.unwrap_or(0) > 0 {
+            json!([])
+        } else {
+            json!([
+                {
+                    "id": 1,
+                    "uid": "demo-component-1",
+                    "name": "Restart Print Spooler",
+                    "description": "Restarts the Windows print spooler service",
+                    "categoryCode": "Scripts",
+                    "variables": [],
+                },
+                {
+                    "id": 2,
+                    "uid": "demo-component-2",
+                    "name": "Schedule Reboot",
+                    "description": "Reboots the device after a delay",
+                    "categoryCode": "Scripts",
+                    "variables": [
+                        { "name": "delayMinutes", "defaultVal": "5", "type": "String", "direction": false },
+                    ],
+                },
+            ])
+        };
+        let count = components.as_array().map_or(0, |c| c.len());
+        Ok(from_json(json!({ "pageDetails": page_details(count), "components": components })))
+    }
+
+    async fn run_quick_job(&self, _device_uid: &str, req: QuickJobRequest) -> Result<QuickJobResponse> {
+        Ok(from_json(json!({
+            "job": {
+                "id": 1,
+                "dateCreated": NOW_MS,
+                "name": req.job_name,
+                "uid": "demo-job-1",
+                "status": "active",
+            },
+            "jobComponents": [],
+        })))
+    }
+
+    async fn cancel_job(&self, _job_uid: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl LimitsApi for MockRmm {}
+
+#[async_trait]
+impl ActivityApi for MockRmm {
+    async fn get_activity_logs(
+        &self,
+        page: Option<String>,
+        _size: i32,
+        _order: Option<String>,
+        _from: Option<String>,
+        _until: Option<String>,
+        _entities: Option<Vec<String>>,
+        _categories: Option<Vec<String>>,
+        _actions: Option<Vec<String>>,
+        site_ids: Option<Vec<i32>>,
+        _user_ids: Option<Vec<i32>>,
+    ) -> Result<ActivityLogsResponse> {
+        if page.is_some() {
+            return Ok(from_json(json!({ "pageDetails": page_details(0), "activities": [] })));
+        }
+        let activities: Vec<Value> = self
+            .devices()
+            .iter()
+            .filter(|d| site_ids.as_ref().is_none_or(|ids| ids.contains(&d.site_id)))
+            .enumerate()
+            .map(|(i, d)| {
+                json!({
+                    "id": format!("demo-activity-{}", i),
+                    "entity": "DEVICE",
+                    "category": "job",
+                    "action": "deployment",
+                    "date": ((NOW_MS - i as i64 * HOUR_MS) / 1000) as f64,
+                    "site": { "id": d.site_id, "name": d.site_name },
+                    "deviceId": d.id,
+                    "hostname": d.hostname,
+                    "user": { "id": 1, "userName": "demo", "firstName": "Demo", "lastName": "User" },
+                    "details": "{\"job.name\":\"Restart Print Spooler\"}",
+                    "hasStdOut": true,
+                    "hasStdErr": false,
+                })
+            })
+            .collect();
+        Ok(from_json(json!({
+            "pageDetails": page_details(activities.len()),
+            "activities": activities,
+        })))
+    }
+}
+
+/// Demo RocketCyber account with incidents for the demo sites.
+#[derive(Debug, Default)]
+pub struct MockSoc;
+
+#[async_trait]
+impl IncidentsApi for MockSoc {
+    async fn get_incidents(&self) -> Result<Vec<Incident>> {
+        Ok(from_json(json!([
+            {
+                "id": 1, "title": "Suspicious PowerShell execution", "status": "open",
+                "accountId": 501, "accountName": "Acme Corp",
+                "createdAt": "2026-01-15T09:12:00Z", "resolvedAt": null,
+            },
+            {
+                "id": 2, "title": "Brute force login attempts", "status": "open",
+                "accountId": 502, "accountName": "Globex",
+                "createdAt": "2026-01-14T22:40:00Z", "resolvedAt": null,
+            },
+            {
+                "id": 3, "title": "Malicious domain lookup", "status": "resolved",
+                "accountId": 501, "accountName": "Acme Corp",
+                "createdAt": "2026-01-10T13:05:00Z", "resolvedAt": "2026-01-10T15:30:00Z",
+            },
+        ])))
+    }
+}
+
+#[async_trait]
+impl AgentsApi for MockSoc {
+    async fn get_agents(&self, hostname: &str) -> Result<Vec<Agent>> {
+        Ok(from_json(json!([{
+            "id": format!("demo-rc-{}", hostname.to_lowercase()),
+            "customerId": 501,
+            "customerName": "Demo Customer",
+            "hostname": hostname,
+            "ipv4Address": "10.1.0.10",
+            "macAddress": "00:00:5E:00:53:01",
+            "createdAt": "2025-03-01T00:00:00Z",
+            "platform": "windows",
+            "family": "Windows",
+            "version": "10.0",
+            "edition": "Pro",
+            "architecture": "x64",
+            "build": "22631",
+            "release": "23H2",
+            "operatingSystem": "Windows 11 Pro",
+            "accountPath": "Demo/Demo Customer",
+            "agentVersion": "2.1.0",
+            "connectivity": "online",
+            "lastConnectedAt": "2026-01-15T11:58:00Z",
+        }])))
+    }
+}
+
+/// Demo Sophos Central partner with a single tenant.
+#[derive(Debug, Default)]
+pub struct MockMdr;
+
+#[async_trait]
+impl MdrApi for MockMdr {
+    async fn get_tenant(&self, tenant_id: &str) -> Result<Tenant> {
+        Ok(Tenant {
+            id: tenant_id.to_string(),
+            name: "Acme Corp".to_string(),
+            data_region: "us01".to_string(),
+        })
+    }
+
+    async fn get_cases(&self, _tenant_id: &str, _data_region: &str) -> Result<Vec<Case>> {
+        Ok(from_json(json!([
+            {
+                "id": "demo-case-1", "description": "Credential dumping detected",
+                "severity": "high", "status": "investigating",
+                "createdAt": "2026-01-15T08:00:00Z", "type": "incident",
+            },
+            {
+                "id": "demo-case-2", "description": "Unwanted application removed",
+                "severity": "low", "status": "resolved",
+                "createdAt": "2026-01-12T10:30:00Z", "type": "incident",
+            },
+        ])))
+    }
+
+    async fn get_endpoints(
+        &self,
+        _tenant_id: &str,
+        _data_region: &str,
+        hostname_contains: &str,
+    ) -> Result<Vec<Endpoint>> {
+        Ok(from_json(json!([{
+            "id": format!("demo-endpoint-{}", hostname_contains.to_lowercase()),
+            "hostname": hostname_contains,
+            "health": { "overall": "good" },
+            "isolation": { "isIsolated": false },
+        }])))
+    }
+
+    async fn start_scan(&self, _tenant_id: &str, _data_region: &str, _endpoint_id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn isolate_endpoint(
+        &self,
+        _tenant_id: &str,
+        _data_region: &str,
+        _endpoint_id: &str,
+        _comment: &str,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_endpoint(
+        &self,
+        _tenant_id: &str,
+        _data_region: &str,
+        _endpoint_id: &str,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Demo Datto AV tenant where every hostname has a healthy agent.
+#[derive(Debug, Default)]
+pub struct MockAv;
+
+impl MockAv {
+    fn agent(&self, id: &str, hostname: &str) -> AgentDetail {
+        from_json(json!({
+            "id": id,
+            "hostname": hostname,
+            "name": hostname,
+            "status": "Protected",
+            "version": "4.2.1",
+            "os": "Windows",
+            "osWindows": true,
+            "active": true,
+            "authorized": true,
+            "isolated": false,
+            "heartbeat": "2026-01-15T11:59:00Z",
+            "organizationName": "Demo Organization",
+            "alertCount": "1",
+        }))
+    }
+}
+
+#[async_trait]
+impl AvApi for MockAv {
+    async fn get_agent_details(&self, hostname: &str) -> Result<Vec<AgentDetail>> {
+        let id = format!("demo-av-{}", hostname.to_lowercase());
+        Ok(vec![self.agent(&id, hostname)])
+    }
+
+    async fn get_agent_detail(&self, id: &str) -> Result<AgentDetail> {
+        let hostname = id.trim_start_matches("demo-av-").to_uppercase();
+        Ok(self.agent(id, &hostname))
+    }
+
+    async fn scan_agent(&self, _id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_agent(&self, _id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_agent_alerts(&self, agent_id: &str) -> Result<Vec<AvAlert>> {
+        Ok(from_json(json!([{
+            "id": format!("{}-alert-1", agent_id),
+            "name": "PUA.Win32.Toolbar",
+            "description": "Potentially unwanted application quarantined",
+            "severity": "low",
+            "agentId": agent_id,
+            "createdOn": "2026-01-14T16:20:00Z",
+            "archived": false,
+        }])))
+    }
+
+    async fn get_agent_policies(&self, _agent_id: &str) -> Result<Value> {
+        Ok(json!([{ "name": "Default Workstation Policy", "enabled": true }]))
+    }
+}
+
+/// Demo Defender tenant where every hostname is an onboarded machine with a
+/// medium risk score and one open alert.
+#[derive(Debug, Default)]
+pub struct MockDefender;
+
+#[async_trait]
+impl DefenderApi for MockDefender {
+    async fn get_machines(&self, hostname: &str) -> Result<Vec<Machine>> {
+        Ok(from_json(json!([{
+            "id": format!("demo-mde-{}", hostname.to_lowercase()),
+            "computerDnsName": format!("{}.demo.local", hostname.to_lowercase()),
+            "healthStatus": "Active",
+            "riskScore": "Medium",
+            "exposureLevel": "Low",
+            "onboardingStatus": "Onboarded",
+            "osPlatform": "Windows11",
+            "lastSeen": "2026-01-15T11:58:00Z",
+        }])))
+    }
+
+    async fn get_machine_alerts(&self, machine_id: &str) -> Result<Vec<DefenderAlert>> {
+        Ok(from_json(json!([{
+            "id": format!("{}-alert-1", machine_id),
+            "title": "Suspicious PowerShell command line",
+            "severity": "Medium",
+            "status": "New",
+            "category": "Execution",
+            "alertCreationTime": "2026-01-15T09:12:00Z",
+        }])))
+    }
+
+    async fn run_av_scan(&self, machine_id: &str, _comment: &str) -> Result<MachineAction> {
+        Ok(from_json(json!({ "id": format!("{}-scan", machine_id), "status": "Pending" })))
+    }
+
+    /// Scans finish by the first check.
+    async fn get_machine_action(&self, action_id: &str) -> Result<MachineAction> {
+        Ok(from_json(json!({
+            "id": action_id,
+            "status": "Succeeded",
+            "lastUpdateDateTimeUtc": chrono::Utc::now().to_rfc3339(),
+        })))
+    }
+}
+
+/// Demo SentinelOne console where every hostname has a connected agent with
+/// one unmitigated threat.
+#[derive(Debug, Default)]
+pub struct MockSentinelOne;
+
+impl MockSentinelOne {
+    fn agent(&self, hostname: &str) -> S1Agent {
+        let name = hostname.to_lowercase();
+        from_json(json!({
+            "id": format!("demo-s1-{}", name),
+            "uuid": format!("demo-uuid-{}", name),
+            "computerName": hostname,
+            "agentVersion": "23.4.2.14",
+            "isActive": true,
+            "infected": true,
+            "networkStatus": "connected",
+            "activeThreats": 1,
+            "siteName": "Demo Site",
+            "lastActiveDate": "2026-01-15T11:59:00Z",
+            // Scans finish by the first check
+            "scanStatus": "finished",
+            "scanFinishedAt": chrono::Utc::now().to_rfc3339(),
+        }))
+    }
+}
+
+#[async_trait]
+impl SentinelOneApi for MockSentinelOne {
+    async fn get_agents(&self, hostname: &str) -> Result<Vec<S1Agent>> {
+        Ok(vec![self.agent(hostname)])
+    }
+
+    async fn get_agent_by_uuid(&self, uuid: &str) -> Result<Option<S1Agent>> {
+        let hostname = uuid.trim_start_matches("demo-uuid-").to_uppercase();
+        Ok(Some(self.agent(&hostname)))
+    }
+
+    async fn get_threats(&self, agent_id: &str) -> Result<Vec<Threat>> {
+        Ok(from_json(json!([{
+            "id": format!("{}-threat-1", agent_id),
+            "threatInfo": {
+                "threatName": "invoice_2026.pdf.exe",
+                "confidenceLevel": "malicious",
+                "mitigationStatus": "not_mitigated",
+                "createdAt": "2026-01-15T08:41:00Z",
+            },
+        }])))
+    }
+
+    async fn initiate_scan(&self, _agent_id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn disconnect_from_network(&self, _agent_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_rmm_data_is_consistent() -> Result<()> {
+        let rmm = MockRmm::default();
+        let sites = rmm.get_sites(0, 250, None).await?.sites;
+        assert_eq!(sites.len(), SITES.len());
+
+        for site in &sites {
+            let devices = rmm.get_devices(&site.uid, 0, 250).await?.devices;
+            let status = site.devices_status.as_ref().unwrap();
+            assert_eq!(devices.len() as i32, status.number_of_devices);
+            assert_eq!(
+                devices.iter().filter(|d| d.online).count() as i32,
+                status.number_of_online_devices
+            );
+        }
+
+        let created = rmm
+            .create_site_variable(
+                &sites[1].uid,
+                CreateVariableRequest {
+                    name: "Demo".to_string(),
+                    value: "1".to_string(),
+                    masked: false,
+                },
+            )
+            .await?;
+        assert_eq!(rmm.get_site_variables(&sites[1].uid).await?[0].id, created.id);
+        // Account variables are kept apart from every site's
+        rmm.delete_account_variable(created.id).await?;
+        assert_eq!(rmm.get_account_variables().await?.len(), 1);
+        assert_eq!(rmm.get_site_variables(&sites[1].uid).await?.len(), 1);
+
+        // A UDF write keeps the device's other UDFs
+        let device = rmm.get_devices(&sites[0].uid, 0, 250).await?.devices.remove(0);
+        rmm.update_device_udf(&device.uid, &UdfUpdate::default().set(1, "Asset 17")).await?;
+        rmm.update_device_udf(&device.uid, &UdfUpdate::default().set(30, "abc")).await?;
+        let udf = rmm.get_device(&device.uid).await?.udf.unwrap();
+        assert_eq!((udf.get(1), udf.get(30)), (Some("Asset 17"), Some("abc")));
+        let listed = rmm.get_devices(&sites[0].uid, 0, 250).await?.devices.remove(0);
+        assert_eq!(listed.udf.unwrap().get(30), Some("abc"));
+
+        // Searches come a page at a time, with the total of all pages
+        let all = rmm.get_account_devices(0, 250).await?.devices.len();
+        let first = rmm.search_devices("", 0, 3).await?;
+        assert_eq!(first.page_details.total_count, Some(all as i32));
+        assert!(first.page_details.next_page_url.is_some());
+        let last = rmm.search_devices("", (all as i32 - 1) / 3, 3).await?;
+        assert!(last.page_details.next_page_url.is_none());
+        Ok(())
+    }
+}
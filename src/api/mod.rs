@@ -1,4 +1,12 @@
 pub mod datto;
 pub mod datto_av;
+pub mod defender;
+pub mod error;
+pub mod http;
+pub mod lenient;
+pub mod mock;
+pub mod read_only;
 pub mod rocket_cyber;
+pub mod sentinelone;
 pub mod sophos;
+pub mod throttle;
@@ -0,0 +1,461 @@
+//! Wrappers that pass reads through to a client and refuse every write, for
+//! the `read_only` config flag.
+//!
+//! Guarding the clients rather than each action keeps new write paths from
+//! slipping past the flag: anything that isn't a plain read here fails with
+//! [`READ_ONLY_MESSAGE`].
+
+use crate::api::datto::activity::ActivityApi;
+use crate::api::datto::devices::DevicesApi;
+use crate::api::datto::jobs::JobsApi;
+use crate::api::datto::rate_limit::Quota;
+use crate::api::datto::sites::SitesApi;
+use crate::api::datto::types::{
+    ActivityLogsResponse, ComponentsResponse, CreateVariableRequest, Device, DevicesResponse,
+    JobComponent, JobResult, JobStdOutput, OpenAlertsResponse, ProxySettings, QuickJobRequest,
+    QuickJobResponse, Site, SiteVariable, SitesResponse, SoftwareResponse, UdfUpdate,
+    UpdateDeviceRequest, UpdateSiteRequest, UpdateVariableRequest,
+};
+use crate::api::datto::variables::VariablesApi;
+use crate::api::datto::{LimitsApi, RmmApi};
+use crate::api::datto_av::AvApi;
+use crate::api::datto_av::types::{AgentDetail, Alert as AvAlert};
+use crate::api::defender::DefenderApi;
+use crate::api::defender::types::{Alert as DefenderAlert, Machine, MachineAction};
+use crate::api::error::ApiError;
+use crate::api::sentinelone::SentinelOneApi;
+use crate::api::sentinelone::types::{Agent as S1Agent, Threat};
+use crate::api::sophos::{Case, Endpoint, MdrApi, Tenant};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Error of every refused write; the app shows it as a toast.
+pub const READ_ONLY_MESSAGE: &str = "Blocked in read-only mode";
+
+fn blocked<T>() -> Result<T> {
+    Err(ApiError::ReadOnly.into())
+}
+
+/// Datto RMM client that refuses site, site proxy, variable, UDF and device
+/// updates, variable deletion, quick jobs (including reboots) and job
+/// cancellation.
+#[derive(Debug)]
+pub struct ReadOnlyRmm(pub Arc<dyn RmmApi>);
+
+#[async_trait]
+impl SitesApi for ReadOnlyRmm {
+    async fn get_sites(
+        &self,
+        page: i32,
+        max: i32,
+        site_name: Option<String>,
+    ) -> Result<SitesResponse> {
+        self.0.get_sites(page, max, site_name).await
+    }
+
+    async fn update_site(&self, _site_uid: &str, _req: UpdateSiteRequest) -> Result<Site> {
+        blocked()
+    }
+
+    async fn get_site(&self, site_uid: &str) -> Result<Site> {
+        self.0.get_site(site_uid).await
+    }
+
+    async fn get_site_open_alerts(
+        &self,
+        site_uid: &str,
+        page: i32,
+        max: i32,
+    ) -> Result<OpenAlertsResponse> {
+        self.0.get_site_open_alerts(site_uid, page, max).await
+    }
+
+    async fn get_account_open_alerts(&self, page: i32, max: i32) -> Result<OpenAlertsResponse> {
+        self.0.get_account_open_alerts(page, max).await
+    }
+
+    async fn resolve_alert(&self, _alert_uid: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn update_site_proxy(&self, _site_uid: &str, _proxy: &ProxySettings) -> Result<()> {
+        blocked()
+    }
+
+    async fn delete_site_proxy(&self, _site_uid: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn delete_site(&self, _site_uid: &str) -> Result<()> {
+        blocked()
+    }
+}
+
+#[async_trait]
+impl DevicesApi for ReadOnlyRmm {
+    async fn get_devices(&self, site_uid: &str, page: i32, max: i32) -> Result<DevicesResponse> {
+        self.0.get_devices(site_uid, page, max).await
+    }
+
+    async fn search_devices(&self, hostname: &str, page: i32, max: i32) -> Result<DevicesResponse> {
+        self.0.search_devices(hostname, page, max).await
+    }
+
+    async fn get_account_devices(&self, page: i32, max: i32) -> Result<DevicesResponse> {
+        self.0.get_account_devices(page, max).await
+    }
+
+    async fn get_device(&self, device_uid: &str) -> Result<Device> {
+        self.0.get_device(device_uid).await
+    }
+
+    async fn update_device_udf(&self, _device_uid: &str, _update: &UdfUpdate) -> Result<()> {
+        blocked()
+    }
+
+    async fn move_device(&self, _device_uid: &str, _site_uid: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn update_device_warranty(&self, _device_uid: &str, _date: Option<String>) -> Result<()> {
+        blocked()
+    }
+
+    async fn update_device(&self, _device_uid: &str, _req: &UpdateDeviceRequest) -> Result<()> {
+        blocked()
+    }
+
+    async fn delete_device(&self, _device_uid: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn get_device_software(
+        &self,
+        device_uid: &str,
+        page: i32,
+        max: i32,
+    ) -> Result<SoftwareResponse> {
+        self.0.get_device_software(device_uid, page, max).await
+    }
+
+    async fn get_device_open_alerts(
+        &self,
+        device_uid: &str,
+        page: i32,
+        max: i32,
+    ) -> Result<OpenAlertsResponse> {
+        self.0.get_device_open_alerts(device_uid, page, max).await
+    }
+
+    async fn get_device_resolved_alerts(
+        &self,
+        device_uid: &str,
+        page: i32,
+        max: i32,
+    ) -> Result<OpenAlertsResponse> {
+        self.0.get_device_resolved_alerts(device_uid, page, max).await
+    }
+}
+
+#[async_trait]
+impl VariablesApi for ReadOnlyRmm {
+    async fn get_site_variables(&self, site_uid: &str) -> Result<Vec<SiteVariable>> {
+        self.0.get_site_variables(site_uid).await
+    }
+
+    async fn create_site_variable(
+        &self,
+        _site_uid: &str,
+        _req: CreateVariableRequest,
+    ) -> Result<SiteVariable> {
+        blocked()
+    }
+
+    async fn update_site_variable(
+        &self,
+        _site_uid: &str,
+        _variable_id: i32,
+        _req: UpdateVariableRequest,
+    ) -> Result<SiteVariable> {
+        blocked()
+    }
+    async fn delete_site_variable(&self, _site_uid: &str, _variable_id: i32) -> Result<()> {
+        blocked()
+    }
+
+    async fn get_account_variables(&self) -> Result<Vec<SiteVariable>> {
+        self.0.get_account_variables().await
+    }
+
+    async fn create_account_variable(&self, _req: CreateVariableRequest) -> Result<SiteVariable> {
+        blocked()
+    }
+
+    async fn update_account_variable(
+        &self,
+        _variable_id: i32,
+        _req: UpdateVariableRequest,
+    ) -> Result<SiteVariable> {
+        blocked()
+    }
+
+    async fn delete_account_variable(&self, _variable_id: i32) -> Result<()> {
+        blocked()
+    }
+}
+
+#[async_trait]
+impl JobsApi for ReadOnlyRmm {
+    async fn get_job_result(&self, job_uid: &str, device_uid: &str) -> Result<JobResult> {
+        self.0.get_job_result(job_uid, device_uid).await
+    }
+
+    async fn get_job_stdout(&self, job_uid: &str, device_uid: &str) -> Result<Vec<JobStdOutput>> {
+        self.0.get_job_stdout(job_uid, device_uid).await
+    }
+
+    async fn get_job_stderr(&self, job_uid: &str, device_uid: &str) -> Result<Vec<JobStdOutput>> {
+        self.0.get_job_stderr(job_uid, device_uid).await
+    }
+
+    async fn get_job_components(&self, job_uid: &str) -> Result<Vec<JobComponent>> {
+        self.0.get_job_components(job_uid).await
+    }
+
+    async fn get_components(&self, page: Option<i32>) -> Result<ComponentsResponse> {
+        self.0.get_components(page).await
+    }
+
+    async fn run_quick_job(
+        &self,
+        _device_uid: &str,
+        _req: QuickJobRequest,
+    ) -> Result<QuickJobResponse> {
+        blocked()
+    }
+
+    async fn cancel_job(&self, _job_uid: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn schedule_reboot(
+        &self,
+        _device_uid: &str,
+        _at: Option<NaiveDateTime>,
+    ) -> Result<String> {
+        blocked()
+    }
+}
+
+#[async_trait]
+impl ActivityApi for ReadOnlyRmm {
+    async fn get_activity_logs(
+        &self,
+        page: Option<String>,
+        size: i32,
+        order: Option<String>,
+        from: Option<String>,
+        until: Option<String>,
+        entities: Option<Vec<String>>,
+        categories: Option<Vec<String>>,
+        actions: Option<Vec<String>>,
+        site_ids: Option<Vec<i32>>,
+        user_ids: Option<Vec<i32>>,
+    ) -> Result<ActivityLogsResponse> {
+        self.0
+            .get_activity_logs(
+                page, size, order, from, until, entities, categories, actions, site_ids, user_ids,
+            )
+            .await
+    }
+}
+
+impl LimitsApi for ReadOnlyRmm {
+    fn quota(&self) -> Option<Quota> {
+        self.0.quota()
+    }
+
+    fn page_size(&self) -> i32 {
+        self.0.page_size()
+    }
+
+    fn activity_page_size(&self) -> i32 {
+        self.0.activity_page_size()
+    }
+}
+
+/// Sophos client that refuses scans, isolation and endpoint deletion.
+#[derive(Debug)]
+pub struct ReadOnlyMdr(pub Arc<dyn MdrApi>);
+
+#[async_trait]
+impl MdrApi for ReadOnlyMdr {
+    async fn get_tenant(&self, tenant_id: &str) -> Result<Tenant> {
+        self.0.get_tenant(tenant_id).await
+    }
+
+    async fn get_cases(&self, tenant_id: &str, data_region: &str) -> Result<Vec<Case>> {
+        self.0.get_cases(tenant_id, data_region).await
+    }
+
+    async fn get_endpoints(
+        &self,
+        tenant_id: &str,
+        data_region: &str,
+        hostname_contains: &str,
+    ) -> Result<Vec<Endpoint>> {
+        self.0
+            .get_endpoints(tenant_id, data_region, hostname_contains)
+            .await
+    }
+
+    async fn start_scan(
+        &self,
+        _tenant_id: &str,
+        _data_region: &str,
+        _endpoint_id: &str,
+    ) -> Result<()> {
+        blocked()
+    }
+
+    async fn isolate_endpoint(
+        &self,
+        _tenant_id: &str,
+        _data_region: &str,
+        _endpoint_id: &str,
+        _comment: &str,
+    ) -> Result<()> {
+        blocked()
+    }
+
+    async fn delete_endpoint(
+        &self,
+        _tenant_id: &str,
+        _data_region: &str,
+        _endpoint_id: &str,
+    ) -> Result<()> {
+        blocked()
+    }
+}
+
+/// Datto AV client that refuses scans.
+#[derive(Debug)]
+pub struct ReadOnlyAv(pub Arc<dyn AvApi>);
+
+#[async_trait]
+impl AvApi for ReadOnlyAv {
+    async fn get_agent_details(&self, hostname: &str) -> Result<Vec<AgentDetail>> {
+        self.0.get_agent_details(hostname).await
+    }
+
+    async fn get_agent_detail(&self, id: &str) -> Result<AgentDetail> {
+        self.0.get_agent_detail(id).await
+    }
+
+    async fn scan_agent(&self, _id: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn delete_agent(&self, _id: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn get_agent_alerts(&self, agent_id: &str) -> Result<Vec<AvAlert>> {
+        self.0.get_agent_alerts(agent_id).await
+    }
+
+    async fn get_agent_policies(&self, agent_id: &str) -> Result<Value> {
+        self.0.get_agent_policies(agent_id).await
+    }
+}
+
+/// Defender client that refuses scans.
+#[derive(Debug)]
+pub struct ReadOnlyDefender(pub Arc<dyn DefenderApi>);
+
+#[async_trait]
+impl DefenderApi for ReadOnlyDefender {
+    async fn get_machines(&self, hostname: &str) -> Result<Vec<Machine>> {
+        self.0.get_machines(hostname).await
+    }
+
+    async fn get_machine_alerts(&self, machine_id: &str) -> Result<Vec<DefenderAlert>> {
+        self.0.get_machine_alerts(machine_id).await
+    }
+
+    async fn run_av_scan(&self, _machine_id: &str, _comment: &str) -> Result<MachineAction> {
+        blocked()
+    }
+
+    async fn get_machine_action(&self, action_id: &str) -> Result<MachineAction> {
+        self.0.get_machine_action(action_id).await
+    }
+}
+
+/// SentinelOne client that refuses scans and network disconnects.
+#[derive(Debug)]
+pub struct ReadOnlySentinelOne(pub Arc<dyn SentinelOneApi>);
+
+#[async_trait]
+impl SentinelOneApi for ReadOnlySentinelOne {
+    async fn get_agents(&self, hostname: &str) -> Result<Vec<S1Agent>> {
+        self.0.get_agents(hostname).await
+    }
+
+    async fn get_agent_by_uuid(&self, uuid: &str) -> Result<Option<S1Agent>> {
+        self.0.get_agent_by_uuid(uuid).await
+    }
+
+    async fn get_threats(&self, agent_id: &str) -> Result<Vec<Threat>> {
+        self.0.get_threats(agent_id).await
+    }
+
+    async fn initiate_scan(&self, _agent_id: &str) -> Result<()> {
+        blocked()
+    }
+
+    async fn disconnect_from_network(&self, _agent_id: &str) -> Result<()> {
+        blocked()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock::{MockAv, MockRmm, MockSentinelOne};
+
+    #[tokio::test]
+    async fn test_reads_pass_and_writes_are_blocked() -> Result<()> {
+        let rmm = ReadOnlyRmm(Arc::new(MockRmm::default()));
+        let sites = rmm.get_sites(0, 250, None).await?;
+        assert!(!sites.sites.is_empty());
+
+        let error = rmm
+            .update_device_udf("device", &UdfUpdate::default().set(1, "x"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), READ_ONLY_MESSAGE);
+        assert_eq!(ApiError::from(error), ApiError::ReadOnly);
+        assert!(rmm.schedule_reboot("device", None).await.is_err());
+        assert!(rmm.delete_device("device").await.is_err());
+        assert!(rmm.resolve_alert("alert").await.is_err());
+        assert!(rmm.cancel_job("job").await.is_err());
+        assert!(rmm.delete_site_proxy("site").await.is_err());
+        assert!(rmm.delete_site("site").await.is_err());
+        assert!(!rmm.get_account_variables().await?.is_empty());
+        assert!(rmm.delete_account_variable(1).await.is_err());
+
+        let av = ReadOnlyAv(Arc::new(MockAv));
+        assert!(av.scan_agent("agent").await.is_err());
+        assert!(av.delete_agent("agent").await.is_err());
+
+        let s1 = ReadOnlySentinelOne(Arc::new(MockSentinelOne));
+        assert_eq!(s1.get_agents("PC01").await?.len(), 1);
+        assert!(s1.initiate_scan("agent").await.is_err());
+        assert!(s1.disconnect_from_network("agent").await.is_err());
+        Ok(())
+    }
+}
@@ -1,47 +1,20 @@
 use super::RocketCyberClient;
 use crate::api::rocket_cyber::types;
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 
-pub(crate) trait AgentsApi {
+#[async_trait]
+pub trait AgentsApi: Send + Sync {
     async fn get_agents(&self, hostname: &str) -> Result<Vec<types::Agent>>;
 }
 
+#[async_trait]
 impl AgentsApi for RocketCyberClient {
     async fn get_agents(&self, hostname: &str) -> Result<Vec<types::Agent>> {
-        let base_url = self.config.api_url.trim_end_matches('/').trim_end_matches("/v3");
-        let url = format!("{}/v3/agents", base_url);
+        let request = self.get("agents").query(&[("hostname", hostname)]);
+        let text = self.http.send(request, "agents").await?;
 
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(&self.config.api_key)
-            .header("Content-Type", "application/json")
-            .query(&[("hostname", hostname)])
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-        let text = response.text().await.context("Failed to get response text")?;
-
-        // Debug Log
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(
-                    f,
-                    "RocketCyber Agents Search: hostname={} | URL: {} | Status: {} | Response: {}",
-                    hostname, url, status, text
-                )
-                .unwrap();
-            });
-
-        if !status.is_success() {
-            anyhow::bail!("RocketCyber API failed: {} - {}", status, text);
-        }
+        tracing::debug!(%hostname, body = %text, "rocketcyber agents search");
 
         let parsed: types::AgentsResponse =
             serde_json::from_str(&text).context("Failed to parse response")?;
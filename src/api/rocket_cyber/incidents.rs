@@ -1,34 +1,18 @@
 use super::RocketCyberClient;
 use crate::api::rocket_cyber::types;
-use anyhow::{Context, Result};
+use anyhow::Result;
+use async_trait::async_trait;
 
-pub(crate) trait IncidentsApi {
+#[async_trait]
+pub trait IncidentsApi: Send + Sync {
     async fn get_incidents(&self) -> Result<Vec<types::Incident>>;
 }
 
+#[async_trait]
 impl IncidentsApi for RocketCyberClient {
     async fn get_incidents(&self) -> Result<Vec<types::Incident>> {
-        let base_url = self.config.api_url.trim_end_matches('/').trim_end_matches("/v3");
-        let url = format!("{}/v3/incidents?pageSize=100", base_url);
-
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(&self.config.api_key)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("RocketCyber API failed: {} - {}", status, text);
-        }
-
-        let parsed: types::IncidentsResponse =
-            response.json().await.context("Failed to parse response")?;
+        let request = self.get(&format!("incidents?pageSize={}", self.config.http.page_size));
+        let parsed: types::IncidentsResponse = self.http.send_json(request, "incidents").await?;
         Ok(parsed.data)
     }
 }
@@ -2,23 +2,38 @@ pub mod agents;
 pub mod incidents;
 pub mod types;
 
+use crate::api::http::{Auth, HttpClient};
 use crate::config::RocketCyberConfig;
-use anyhow::{Context, Result};
-use reqwest::Client;
-use std::time::Duration;
+use agents::AgentsApi;
+use incidents::IncidentsApi;
+use anyhow::Result;
+use reqwest::{Method, RequestBuilder};
+
+/// RocketCyber (SOC) operations used by the UI; implemented by
+/// `RocketCyberClient` and `api::mock::MockSoc`.
+pub trait SocApi: IncidentsApi + AgentsApi + std::fmt::Debug {}
+
+impl<T: IncidentsApi + AgentsApi + std::fmt::Debug> SocApi for T {}
 
 #[derive(Clone, Debug)]
 pub struct RocketCyberClient {
-    pub(crate) client: Client,
+    pub(crate) http: HttpClient,
     pub(crate) config: RocketCyberConfig,
 }
 
 impl RocketCyberClient {
     pub fn new(config: RocketCyberConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .context("Failed to build HTTP client")?;
-        Ok(Self { client, config })
+        Ok(Self {
+            http: HttpClient::new("RocketCyber", &config.http)?,
+            config,
+        })
+    }
+
+    /// A GET of `path` below the v3 API, with the API key. `api_url` may be
+    /// given with or without the `/v3` suffix.
+    pub(crate) fn get(&self, path: &str) -> RequestBuilder {
+        let base_url = self.config.api_url.trim_end_matches('/').trim_end_matches("/v3");
+        let url = format!("{}/v3/{}", base_url, path);
+        self.http.request(Method::GET, &url, Some(Auth::Bearer(&self.config.api_key)))
     }
 }
@@ -0,0 +1,90 @@
+pub mod types;
+
+use crate::api::http::{Auth, HttpClient};
+use crate::config::SentinelOneConfig;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder};
+use types::{Agent, ListResponse, Threat};
+
+#[derive(Clone, Debug)]
+pub struct SentinelOneClient {
+    pub(crate) http: HttpClient,
+    pub(crate) config: SentinelOneConfig,
+}
+
+/// SentinelOne operations used by the UI; implemented by `SentinelOneClient`
+/// and `api::mock::MockSentinelOne`.
+#[async_trait]
+pub trait SentinelOneApi: Send + Sync + std::fmt::Debug {
+    /// Agents whose computer name is `hostname`, ignoring case.
+    async fn get_agents(&self, hostname: &str) -> Result<Vec<Agent>>;
+    async fn get_agent_by_uuid(&self, uuid: &str) -> Result<Option<Agent>>;
+    /// The agent's threats that aren't resolved.
+    async fn get_threats(&self, agent_id: &str) -> Result<Vec<Threat>>;
+    async fn initiate_scan(&self, agent_id: &str) -> Result<()>;
+    /// Cuts the agent off the network except for the management console.
+    async fn disconnect_from_network(&self, agent_id: &str) -> Result<()>;
+}
+
+impl SentinelOneClient {
+    pub fn new(config: SentinelOneConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new("SentinelOne", &config.http)?,
+            config,
+        })
+    }
+
+    /// A request to `path` below `/web/api/v2.1/`, with the API token.
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!("{}/web/api/v2.1/{}", self.config.url.trim_end_matches('/'), path);
+        let auth = format!("ApiToken {}", self.config.api_token);
+        self.http
+            .request(method, &url, Some(Auth::Key(&auth)))
+            .header("Accept", "application/json")
+    }
+
+    /// Runs the agent action `action` on the one agent `agent_id`.
+    async fn agent_action(&self, action: &str, agent_id: &str, what: &str) -> Result<()> {
+        let body = serde_json::json!({ "filter": { "ids": [agent_id] }, "data": {} });
+        let path = format!("agents/actions/{}", action);
+        self.http.send(self.request(Method::POST, &path).json(&body), what).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SentinelOneApi for SentinelOneClient {
+    async fn get_agents(&self, hostname: &str) -> Result<Vec<Agent>> {
+        let params = [("computerName", hostname)];
+        let request = self.request(Method::GET, "agents").query(&params);
+        let response: ListResponse<Agent> = self.http.send_json(request, "agents").await?;
+        // The console's name filter is looser than the hostname match we want
+        let mut agents = response.data;
+        agents.retain(|a| {
+            a.computer_name.as_deref().is_some_and(|name| name.eq_ignore_ascii_case(hostname))
+        });
+        Ok(agents)
+    }
+
+    async fn get_agent_by_uuid(&self, uuid: &str) -> Result<Option<Agent>> {
+        let request = self.request(Method::GET, "agents").query(&[("uuids", uuid)]);
+        let response: ListResponse<Agent> = self.http.send_json(request, "agent").await?;
+        Ok(response.data.into_iter().next())
+    }
+
+    async fn get_threats(&self, agent_id: &str) -> Result<Vec<Threat>> {
+        let params = [("agentIds", agent_id), ("resolved", "false"), ("sortOrder", "desc")];
+        let request = self.request(Method::GET, "threats").query(&params);
+        let response: ListResponse<Threat> = self.http.send_json(request, "threats").await?;
+        Ok(response.data)
+    }
+
+    async fn initiate_scan(&self, agent_id: &str) -> Result<()> {
+        self.agent_action("initiate-scan", agent_id, "initiate scan").await
+    }
+
+    async fn disconnect_from_network(&self, agent_id: &str) -> Result<()> {
+        self.agent_action("disconnect", agent_id, "disconnect from network").await
+    }
+}
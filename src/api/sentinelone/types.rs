@@ -0,0 +1,54 @@
+use serde::Deserialize;
+
+/// A SentinelOne agent, from `/agents`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Agent {
+    pub id: String,
+    pub uuid: String,
+    pub computer_name: Option<String>,
+    pub agent_version: Option<String>,
+    pub is_active: Option<bool>,
+    pub infected: Option<bool>,
+    /// "connected", "disconnected", "connecting" or "disconnecting"
+    pub network_status: Option<String>,
+    pub active_threats: Option<u32>,
+    pub site_name: Option<String>,
+    pub last_active_date: Option<String>,
+    /// Of the latest full disk scan: "none", "started", "finished" or "aborted"
+    pub scan_status: Option<String>,
+    pub scan_started_at: Option<String>,
+    pub scan_finished_at: Option<String>,
+}
+
+impl Agent {
+    /// Whether the agent is cut off from the network (or being cut off).
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self.network_status.as_deref(), Some("disconnected" | "disconnecting"))
+    }
+}
+
+/// A threat detected on an agent, from `/threats`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Threat {
+    pub id: String,
+    pub threat_info: ThreatInfo,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatInfo {
+    pub threat_name: Option<String>,
+    /// "malicious" or "suspicious"
+    pub confidence_level: Option<String>,
+    /// "not_mitigated", "mitigated", "marked_as_benign", ...
+    pub mitigation_status: Option<String>,
+    pub created_at: Option<String>,
+}
+
+/// The envelope every response wraps its results in.
+#[derive(Deserialize, Debug)]
+pub(crate) struct ListResponse<T> {
+    pub data: Vec<T>,
+}
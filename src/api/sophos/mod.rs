@@ -1,8 +1,10 @@
+use crate::api::error::ApiError;
+use crate::api::http::{Auth, HttpClient};
 use crate::config::SophosConfig;
 use anyhow::{Context, Result};
-use reqwest::Client;
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder};
 use serde::Deserialize;
-use std::time::Duration;
 
 #[derive(Deserialize, Debug)]
 struct TokenResponse {
@@ -29,19 +31,45 @@ struct ApiHosts {
 
 #[derive(Clone, Debug)]
 pub struct SophosClient {
-    pub(crate) client: Client,
+    pub(crate) http: HttpClient,
     pub(crate) config: SophosConfig,
     pub(crate) access_token: Option<String>,
 }
 
+/// Sophos Central (MDR) operations used by the UI; implemented by
+/// `SophosClient` and `api::mock::MockMdr`.
+#[async_trait]
+pub trait MdrApi: Send + Sync + std::fmt::Debug {
+    async fn get_tenant(&self, tenant_id: &str) -> Result<Tenant>;
+    async fn get_cases(&self, tenant_id: &str, data_region: &str) -> Result<Vec<Case>>;
+    async fn get_endpoints(
+        &self,
+        tenant_id: &str,
+        data_region: &str,
+        hostname_contains: &str,
+    ) -> Result<Vec<Endpoint>>;
+    async fn start_scan(&self, tenant_id: &str, data_region: &str, endpoint_id: &str) -> Result<()>;
+    /// Cuts the endpoint off the network except for Sophos Central, with
+    /// `comment` recorded in its event log.
+    async fn isolate_endpoint(
+        &self,
+        tenant_id: &str,
+        data_region: &str,
+        endpoint_id: &str,
+        comment: &str,
+    ) -> Result<()>;
+    async fn delete_endpoint(
+        &self,
+        tenant_id: &str,
+        data_region: &str,
+        endpoint_id: &str,
+    ) -> Result<()>;
+}
+
 impl SophosClient {
     pub fn new(config: SophosConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .context("Failed to build HTTP client")?;
         Ok(Self {
-            client,
+            http: HttpClient::new("Sophos Central", &config.http)?,
             config,
             access_token: None,
         })
@@ -57,18 +85,14 @@ impl SophosClient {
             ("scope", "token"),
         ];
 
-        let response = self
-            .client
-            .post(url)
-            .form(&params)
-            .send()
-            .await
-            .context("Failed to send auth request")?;
+        let request = self.http.request(Method::POST, url, None).form(&params);
+        let response = self.http.execute(request).await.context("Failed to send auth request")?;
 
         let status = response.status();
         if !status.is_success() {
             let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Authentication failed: {} - {}", status, text);
+            let message = format!("Authentication failed: {} - {}", status, text);
+            return Err(ApiError::Auth(message).into());
         }
 
         let token_response = response
@@ -80,123 +104,62 @@ impl SophosClient {
         Ok(())
     }
 
-    pub async fn whoami(&self) -> Result<String> {
-        let url = "https://api.central.sophos.com/whoami/v1";
-
-        // Ensure we have a token
-        let token = self.access_token.as_ref().context("Not authenticated")?;
+    /// A request to `url` with the access token.
+    fn request(&self, method: Method, url: &str) -> Result<RequestBuilder> {
+        let token = self.access_token.as_ref().ok_or_else(ApiError::not_authenticated)?;
+        Ok(self.http.request(method, url, Some(Auth::Bearer(token))))
+    }
 
-        let response = self
-            .client
-            .get(url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await
-            .context("Failed to send whoami request")?;
+    /// A request on behalf of the partner, for the partner API.
+    fn partner_request(&self, method: Method, url: &str) -> Result<RequestBuilder> {
+        Ok(self.request(method, url)?.header("X-Partner-ID", &self.config.partner_id))
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Whoami failed: {} - {}", status, text);
-        }
+    /// A request against one tenant's data, for the regional APIs.
+    fn tenant_request(&self, method: Method, url: &str, tenant_id: &str) -> Result<RequestBuilder> {
+        Ok(self.request(method, url)?.header("X-Tenant-ID", tenant_id))
+    }
 
-        let whoami_response = response
-            .json::<WhoAmIResponse>()
-            .await
-            .context("Failed to parse whoami response")?;
+    pub async fn whoami(&self) -> Result<String> {
+        let url = "https://api.central.sophos.com/whoami/v1";
+        let whoami_response: WhoAmIResponse =
+            self.http.send_json(self.request(Method::GET, url)?, "whoami").await?;
         println!("Whoami response: {:#?}", whoami_response);
 
         Ok(whoami_response.id)
     }
 
-    pub async fn get_tenant(&self, tenant_id: &str) -> Result<Tenant> {
-        let url = format!(
-            "https://api.central.sophos.com/partner/v1/tenants/{}",
-            tenant_id
-        );
-        let token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("X-Partner-ID", &self.config.partner_id)
-            .send()
-            .await
-            .context("Failed to send get_tenant request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get tenant failed: {} - {}", status, text);
-        }
-
-        let tenant = response
-            .json::<Tenant>()
-            .await
-            .context("Failed to parse tenant response")?;
-
-        Ok(tenant)
-    }
-
     pub async fn get_tenants(&self) -> Result<Vec<Tenant>> {
         let url = "https://api.central.sophos.com/partner/v1/tenants";
-        let token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let response = self
-            .client
-            .get(url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("X-Partner-ID", &self.config.partner_id)
-            .send()
-            .await
-            .context("Failed to send get_tenants request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get tenants failed: {} - {}", status, text);
-        }
-
-        let response_json = response
-            .json::<TenantsResponse>()
-            .await
-            .context("Failed to parse tenants response")?;
+        let request = self.partner_request(Method::GET, url)?;
+        let response_json: TenantsResponse = self.http.send_json(request, "tenants").await?;
 
         Ok(response_json.items)
     }
+}
 
-    pub async fn get_cases(&self, tenant_id: &str, data_region: &str) -> Result<Vec<Case>> {
+#[async_trait]
+impl MdrApi for SophosClient {
+    async fn get_tenant(&self, tenant_id: &str) -> Result<Tenant> {
+        let url = format!(
+            "https://api.central.sophos.com/partner/v1/tenants/{}",
+            tenant_id
+        );
+        self.http.send_json(self.partner_request(Method::GET, &url)?, "tenant").await
+    }
+
+    async fn get_cases(&self, tenant_id: &str, data_region: &str) -> Result<Vec<Case>> {
         let url = format!(
             "https://api-{}.central.sophos.com/cases/v1/cases",
             data_region
         );
-        let token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("X-Tenant-ID", tenant_id)
-            .send()
-            .await
-            .context("Failed to send get_cases request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get cases failed: {} - {}", status, text);
-        }
-
-        let response_json = response
-            .json::<CasesResponse>()
-            .await
-            .context("Failed to parse cases response")?;
+        let request = self.tenant_request(Method::GET, &url, tenant_id)?;
+        let response_json: CasesResponse = self.http.send_json(request, "cases").await?;
 
         Ok(response_json.items)
     }
 
-    pub async fn get_endpoints(
+    async fn get_endpoints(
         &self,
         tenant_id: &str,
         data_region: &str,
@@ -206,34 +169,15 @@ impl SophosClient {
             "https://api-{}.central.sophos.com/endpoint/v1/endpoints",
             data_region
         );
-        let token = self.access_token.as_ref().context("Not authenticated")?;
-
         let params = [("hostnameContains", hostname_contains)];
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("X-Tenant-ID", tenant_id)
-            .query(&params)
-            .send()
-            .await
-            .context("Failed to send get_endpoints request")?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get endpoints failed: {} - {}", status, text);
-        }
-
-        let response_json = response
-            .json::<EndpointsResponse>()
-            .await
-            .context("Failed to parse endpoints response")?;
+        let request = self.tenant_request(Method::GET, &url, tenant_id)?.query(&params);
+        let response_json: EndpointsResponse = self.http.send_json(request, "endpoints").await?;
 
         Ok(response_json.items)
     }
-    pub async fn start_scan(
+
+    async fn start_scan(
         &self,
         tenant_id: &str,
         data_region: &str,
@@ -243,23 +187,40 @@ impl SophosClient {
             "https://api-{}.central.sophos.com/endpoint/v1/endpoints/{}/scans",
             data_region, endpoint_id
         );
-        let token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("X-Tenant-ID", tenant_id)
-            .send()
-            .await
-            .context("Failed to send start_scan request")?;
+        let request = self.tenant_request(Method::POST, &url, tenant_id)?;
+        self.http.send(request, "start scan").await?;
+        Ok(())
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Start scan failed: {} - {}", status, text);
-        }
+    async fn isolate_endpoint(
+        &self,
+        tenant_id: &str,
+        data_region: &str,
+        endpoint_id: &str,
+        comment: &str,
+    ) -> Result<()> {
+        let url = format!(
+            "https://api-{}.central.sophos.com/endpoint/v1/endpoints/{}/isolation",
+            data_region, endpoint_id
+        );
+        let body = serde_json::json!({ "enabled": true, "comment": comment });
+        let request = self.tenant_request(Method::POST, &url, tenant_id)?.json(&body);
+        self.http.send(request, "isolate endpoint").await?;
+        Ok(())
+    }
 
+    async fn delete_endpoint(
+        &self,
+        tenant_id: &str,
+        data_region: &str,
+        endpoint_id: &str,
+    ) -> Result<()> {
+        let url = format!(
+            "https://api-{}.central.sophos.com/endpoint/v1/endpoints/{}",
+            data_region, endpoint_id
+        );
+        let request = self.tenant_request(Method::DELETE, &url, tenant_id)?;
+        self.http.send(request, "delete endpoint").await?;
         Ok(())
     }
 }
@@ -328,8 +289,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_sophos_auth_and_whoami() -> Result<()> {
-        let config = Config::from_env()?;
-        let mut client = SophosClient::new(config.sophos)?;
+        let config = Config::load(None)?;
+        let sophos = config.sophos.configured().context("Sophos is not configured")?;
+        let mut client = SophosClient::new(sophos)?;
 
         client
             .authenticate()
@@ -0,0 +1,158 @@
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::hash::{BuildHasher, RandomState};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+const MAX_RETRIES: u32 = 4;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+static RETRYING: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of requests currently waiting to be retried, for loading indicators.
+pub fn retrying() -> usize {
+    RETRYING.load(Ordering::Relaxed)
+}
+
+/// Caps the requests a client has in flight (`max_concurrent_requests`);
+/// shared by the clones of a client.
+#[derive(Clone, Debug)]
+pub struct Limiter(Arc<Semaphore>);
+
+impl Limiter {
+    pub fn new(max_concurrent_requests: usize) -> Self {
+        Self(Arc::new(Semaphore::new(max_concurrent_requests.max(1))))
+    }
+}
+
+/// Sends requests through the client's concurrency limit, retrying transient
+/// failures with jittered exponential backoff.
+///
+/// 429 is always retried since the request was never processed. Server errors,
+/// timeouts and connection failures are only retried for idempotent methods,
+/// so e.g. a quick job is never created twice.
+pub(crate) trait SendThrottled {
+    async fn send_throttled(self, limiter: &Limiter) -> reqwest::Result<Response>;
+}
+
+impl SendThrottled for RequestBuilder {
+    async fn send_throttled(self, limiter: &Limiter) -> reqwest::Result<Response> {
+        let idempotent = self
+            .try_clone()
+            .and_then(|b| b.build().ok())
+            .is_some_and(|r| r.method().is_idempotent());
+        let mut attempt = 0;
+        let mut builder = self;
+        loop {
+            // Keep a copy for the retry; requests with streaming bodies can't be cloned and are sent once
+            let retry = builder.try_clone();
+            let result = {
+                let _permit = limiter.0.acquire().await.expect("request limiter closed");
+                builder.send().await
+            };
+
+            let Some(next) = retry else {
+                return result;
+            };
+            if attempt >= MAX_RETRIES {
+                return result;
+            }
+            let (delay, reason) = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => (
+                    retry_after(response).unwrap_or_else(|| jitter(backoff(attempt))),
+                    "rate limited",
+                ),
+                Ok(response) if idempotent && is_transient(response.status()) => {
+                    (jitter(backoff(attempt)), "server error")
+                }
+                Err(e) if idempotent && (e.is_timeout() || e.is_connect()) => {
+                    (jitter(backoff(attempt)), "request failed")
+                }
+                _ => return result,
+            };
+
+            match &result {
+                Ok(response) => tracing::warn!(
+                    url = %response.url(),
+                    status = %response.status(),
+                    attempt = attempt + 1,
+                    delay_ms = delay.as_millis() as u64,
+                    "{}, backing off", reason
+                ),
+                Err(e) => tracing::warn!(
+                    error = %e,
+                    attempt = attempt + 1,
+                    delay_ms = delay.as_millis() as u64,
+                    "{}, backing off", reason
+                ),
+            }
+            RETRYING.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(delay).await;
+            RETRYING.fetch_sub(1, Ordering::Relaxed);
+            attempt += 1;
+            builder = next;
+        }
+    }
+}
+
+/// Statuses worth another try: the server or a gateway failed, not the request.
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Spreads `delay` over its upper half so clients backing off together don't
+/// retry in lockstep.
+fn jitter(delay: Duration) -> Duration {
+    let half = delay / 2;
+    let random = RandomState::new().hash_one(0u8);
+    half + Duration::from_nanos(random % (half.as_nanos() as u64 + 1))
+}
+
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    let secs = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_BACKOFF))
+}
+
+fn backoff(attempt: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(backoff(0), Duration::from_millis(500));
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_jitter_and_transient_statuses() {
+        for _ in 0..20 {
+            let delay = jitter(Duration::from_secs(4));
+            assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+        }
+        assert!(is_transient(StatusCode::BAD_GATEWAY));
+        assert!(!is_transient(StatusCode::NOT_FOUND));
+    }
+}
@@ -1,29 +1,449 @@
-use crate::api::datto::DattoClient;
-use crate::common::jobs::generate_job_rows;
-use crate::api::datto::activity::ActivityApi;
-use crate::api::datto::devices::DevicesApi;
-use crate::api::datto::jobs::JobsApi;
-use crate::api::datto::sites::SitesApi;
+mod account_variables;
+mod alert_detail;
+mod collections;
+mod components;
+mod confirm;
+mod decommission;
+mod device_detail;
+mod device_filter;
+mod device_search;
+mod device_watch;
+mod fetches;
+mod integrations;
+mod job_poll;
+mod mdr_mapping;
+mod net_tools;
+mod notifications;
+mod policies;
+mod popup;
+mod posture;
+mod redraw;
+mod reducer;
+mod report;
+mod retry;
+mod scans;
+mod security;
+mod session;
+mod site_deletion;
+mod site_detail;
+mod site_history;
+mod site_list;
+mod site_settings;
+mod sort;
+mod table_view;
+mod tags;
+mod tasks;
+mod templates;
+mod variable_copy;
+
+pub use account_variables::AccountVariablesState;
+pub use alert_detail::{AlertDetail, diagnostic_lines};
+pub use collections::{CollectionAction, CollectionRun, CollectionsState, DeviceCollection};
+pub use components::{
+    ComponentRun, ComponentsState, VariableChoice, VariableKind, check_variable_value, invalid_variable,
+    variable_choices, variable_kind,
+};
+pub use device_search::{SavedSearch, SearchHistory, split_search_query};
+pub use device_watch::{DeviceWatch, WATCH_INTERVAL};
+pub use device_detail::{
+    ACTIVITY_COLUMNS, DeviceDetailState, DevicePane, OPEN_ALERT_COLUMNS, RESOLVED_ALERT_COLUMNS,
+    activity_user, resolved_since, udf_label,
+};
+pub use confirm::{
+    Confirmation, FieldChange, PendingAction, device_changes, proxy_changes, site_changes,
+};
+pub use decommission::{AvEndpoint, Decommission};
+pub use fetches::Fetches;
+pub use integrations::{ConfigState, IntegrationStatus, IntegrationsState};
+pub use job_poll::{JobFollow, JobPoll, append_new_output, is_finished, is_scheduled};
+pub use mdr_mapping::{MappingSource, MdrDefaults, MdrMapping};
+pub use net_tools::{NetResult, NetToolsState};
+pub use notifications::{INCIDENT_SEVERITY, Notification, NotificationsState, SeenItems};
+pub use popup::{Popup, PopupState};
+pub use posture::{FAIR_SCORE, GOOD_SCORE};
+pub use redraw::Redraw;
+pub use report::{
+    AV_GAP_COLUMNS, AV_PRODUCT_COLUMNS, OFFLINE_SERVER_COLUMNS, PATCH_COLUMNS, PATCH_DEVICE_COLUMNS,
+    ReportKind, ReportState, WARRANTY_COLUMNS, is_server,
+};
+pub use scans::{ScanJob, ScanJobs, ScanState};
+pub use security::SecurityState;
+pub use site_history::{HISTORY_DAYS, SiteHistory};
+pub use site_deletion::SiteDeletion;
+pub use site_settings::{ProxyType, SettingKind, SiteEditField, SiteEditState};
+pub use session::Session;
+pub use site_detail::{
+    DEVICE_COLUMNS, SITE_ALERT_COLUMNS, SiteDetailState, alert_device_name, device_av,
+    device_type_label, last_seen_age, parse_udf_columns, patch_status_label,
+};
+pub use site_list::{SITE_COLUMNS, SiteListState, SiteRow, SiteSection, incident_key};
+pub use sort::SortState;
+pub use table_view::TableView;
+use table_view::{select_next, select_prev};
+pub use tags::{TagsState, device_tags, format_tags, parse_tags};
+pub use tasks::{Supervisor, current_task};
+pub use templates::{VariableChange, template_changes};
+pub use variable_copy::{VariableCopy, copy_changes};
+
+use crate::action;
+use crate::api::datto::{DattoClient, RmmApi};
+use crate::api::datto::jobs::{parse_reboot_time, rerun_request};
+use crate::common::jobs::{activity_job_summary, activity_job_uid};
+use crate::common::launcher::{self, launcher_args};
+use crate::common::net_tools::{self as net, NetTool};
+use crate::common::notify::desktop_notification;
+use crate::common::site_rules::SiteRule;
+use crate::common::warranty::compose_warranty_date;
 use crate::api::datto::types::{
-    ActivityLog, Component, CreateVariableRequest, Device, DevicesResponse, JobResult, QuickJobComponent,
-    QuickJobRequest, QuickJobResponse, QuickJobVariable, Site, SitesResponse, UpdateSiteRequest,
-    UpdateVariableRequest,
+    Alert, Component, ComponentResult, ComponentVariable, CreateVariableRequest, Device,
+    DevicesResponse, JobComponent, JobStdOutput, ProxySettings, QuickJobComponent,
+    QuickJobRequest, QuickJobVariable, Site, SiteVariable, SitesResponse, Udf, UdfUpdate,
+    UpdateDeviceRequest, UpdateSiteRequest, UpdateVariableRequest,
 };
-use crate::api::datto::variables::VariablesApi;
-use crate::event::{Event, EventHandler, ScanStatus};
+use crate::event::{Event, EventHandler};
 use crate::tui::Tui;
 use crate::ui;
 use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::widgets::TableState;
-
-use crate::api::datto_av::DattoAvClient;
-use crate::api::datto_av::types::AgentDetail;
-use crate::api::rocket_cyber::RocketCyberClient;
-use crate::api::rocket_cyber::incidents::IncidentsApi;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+
+use crate::api::datto_av::{AvApi, DattoAvClient};
+use crate::api::defender::{DefenderApi, DefenderClient};
+use crate::api::sentinelone::{SentinelOneApi, SentinelOneClient};
+use crate::api::lenient::partial_load_message;
+use crate::api::error::ApiError;
+use crate::api::read_only::{
+    READ_ONLY_MESSAGE, ReadOnlyAv, ReadOnlyDefender, ReadOnlyMdr, ReadOnlyRmm, ReadOnlySentinelOne,
+};
 use crate::api::rocket_cyber::agents::AgentsApi;
-use crate::api::sophos::{Endpoint, SophosClient};
-use std::collections::{HashMap, HashSet};
+use crate::api::rocket_cyber::{RocketCyberClient, SocApi};
+use crate::api::sophos::{MdrApi, SophosClient};
+use crate::cache::{self, Acknowledgement, Cache};
+use crate::config::{
+    Config, ConfirmKind, CustomQuickAction, DEFAULT_RESOLVED_ALERT_DAYS, Integration, Launcher,
+    ProfileSettings, Runbook, VariableTemplate,
+};
+use crate::wizard::{PROBE_HOSTNAME, Provider};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Devices requested per page of the device search; PageDown loads the next.
+const SEARCH_PAGE_SIZE: i32 = 50;
+
+/// Time between live fetches of the open device's activities.
+const ACTIVITY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How far each live activity fetch reaches back before the previous one ended.
+const ACTIVITY_POLL_OVERLAP: chrono::Duration = chrono::Duration::minutes(2);
+
+/// Activities of one device from `from` to `until`, newest first.
+async fn device_activity(
+    client: Arc<dyn RmmApi>,
+    device_id: i32,
+    site_id: i32,
+    from: chrono::DateTime<chrono::Utc>,
+    until: chrono::DateTime<chrono::Utc>,
+) -> Result<crate::api::datto::types::ActivityLogsResponse, ApiError> {
+    // Since we cannot filter by device UID directly in the API for this endpoint (based on
+    // error message), we filter by site_id and "device" entity type, then filter in memory
+    // for the specific device ID.
+    client
+        .get_activity_logs(
+            None,                                                 // Page (None = empty/first)
+            client.activity_page_size(),                          // Size
+            Some("desc".to_string()),                             // Order
+            Some(from.format("%Y-%m-%dT%H:%M:%SZ").to_string()),  // From
+            Some(until.format("%Y-%m-%dT%H:%M:%SZ").to_string()), // Until
+            Some(vec!["device".to_string()]),                     // Entities: "device" literal
+            None,                                                 // Categories
+            None,                                                 // Actions
+            Some(vec![site_id]),                                  // SiteIds
+            None,                                                 // UserIds
+        )
+        .await
+        .map(|mut response| {
+            // Client-side filtering for the specific device
+            response.activities.retain(|log| log.device_id == Some(device_id));
+            response
+        })
+        .map_err(ApiError::from)
+}
+
+/// How long a toast stays up.
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(4);
+
+/// Devices kept in the recent devices popup (`h`).
+const RECENT_DEVICES_LIMIT: usize = 20;
+
+/// How many reported errors the error history keeps.
+const MAX_ERROR_HISTORY: usize = 500;
+
+/// API clients built from a single config profile, swapped in when switching profiles.
+#[derive(Debug, Clone)]
+pub struct ProfileClients {
+    pub datto: Arc<dyn RmmApi>,
+    pub rocket: Option<Arc<dyn SocApi>>,
+    pub sophos: Option<Arc<dyn MdrApi>>,
+    pub datto_av: Option<Arc<dyn AvApi>>,
+    pub defender: Option<Arc<dyn DefenderApi>>,
+    pub sentinelone: Option<Arc<dyn SentinelOneApi>>,
+    /// UDF holding SentinelOne agent UUIDs (see `SentinelOneConfig`)
+    pub sentinelone_uuid_udf: Option<usize>,
+    /// The profile's UDF labels, which belong to the account like the clients do
+    pub udf_labels: BTreeMap<usize, String>,
+    /// UDF holding the device tags (`tags_udf` in the config)
+    pub tags_udf: Option<usize>,
+    /// Configuration and authentication state of each integration
+    pub integrations: Vec<IntegrationStatus>,
+    /// The clients refuse writes
+    pub read_only: bool,
+}
+
+impl ProfileClients {
+    /// Builds all clients for `config` and authenticates Datto, Sophos and
+    /// Defender.
+    pub async fn connect(config: Config) -> Result<Self> {
+        let mut datto = DattoClient::new(config.datto.clone())?;
+        datto.ping().await?;
+        datto.authenticate().await?;
+        Ok(Self::with_datto(Arc::new(datto), Ok(()), config).await)
+    }
+
+    /// Builds the optional clients around an already created Datto client and
+    /// authenticates Sophos and Defender. An optional integration that is misconfigured or
+    /// fails to authenticate doesn't fail the profile; it is noted in
+    /// `integrations` for the Integrations view instead.
+    pub async fn with_datto(
+        datto: Arc<dyn RmmApi>,
+        datto_auth: Result<(), String>,
+        config: Config,
+    ) -> Self {
+        let mut datto_status = IntegrationStatus::new(Provider::Datto, ConfigState::Configured);
+        datto_status.record_auth(datto_auth);
+
+        let (rocket, rocket_status) =
+            build_client(Provider::RocketCyber, config.rocket, RocketCyberClient::new);
+
+        let (mut sophos, mut sophos_status) =
+            build_client(Provider::Sophos, config.sophos, SophosClient::new);
+        if let Some(client) = &mut sophos {
+            sophos_status.record_auth(client.authenticate().await.map_err(|e| format!("{:#}", e)));
+        }
+
+        let (datto_av, datto_av_status) =
+            build_client(Provider::DattoAv, config.datto_av, DattoAvClient::new);
+
+        let (mut defender, mut defender_status) =
+            build_client(Provider::Defender, config.defender, DefenderClient::new);
+        if let Some(client) = &mut defender {
+            let auth = client.authenticate().await.map_err(|e| format!("{:#}", e));
+            defender_status.record_auth(auth);
+        }
+
+        let sentinelone_uuid_udf = match &config.sentinelone {
+            Integration::Configured(c) => c.agent_uuid_udf,
+            _ => None,
+        };
+        let (sentinelone, sentinelone_status) =
+            build_client(Provider::SentinelOne, config.sentinelone, SentinelOneClient::new);
+
+        let mut sophos = sophos.map(|c| Arc::new(c) as Arc<dyn MdrApi>);
+        let mut datto_av = datto_av.map(|c| Arc::new(c) as Arc<dyn AvApi>);
+        let mut defender = defender.map(|c| Arc::new(c) as Arc<dyn DefenderApi>);
+        let mut sentinelone = sentinelone.map(|c| Arc::new(c) as Arc<dyn SentinelOneApi>);
+        let mut datto = datto;
+        if config.read_only {
+            datto = Arc::new(ReadOnlyRmm(datto));
+            sophos = sophos.map(|c| Arc::new(ReadOnlyMdr(c)) as Arc<dyn MdrApi>);
+            datto_av = datto_av.map(|c| Arc::new(ReadOnlyAv(c)) as Arc<dyn AvApi>);
+            defender = defender.map(|c| Arc::new(ReadOnlyDefender(c)) as Arc<dyn DefenderApi>);
+            sentinelone = sentinelone
+                .map(|c| Arc::new(ReadOnlySentinelOne(c)) as Arc<dyn SentinelOneApi>);
+        }
+
+        Self {
+            datto,
+            rocket: rocket.map(|c| Arc::new(c) as Arc<dyn SocApi>),
+            sophos,
+            datto_av,
+            defender,
+            sentinelone,
+            sentinelone_uuid_udf,
+            udf_labels: config.udf_labels,
+            tags_udf: config.tags_udf,
+            integrations: vec![
+                datto_status,
+                rocket_status,
+                sophos_status,
+                datto_av_status,
+                defender_status,
+                sentinelone_status,
+            ],
+            read_only: config.read_only,
+        }
+    }
+}
+
+/// Builds the client of an optional integration, with a status noting its
+/// config state (or the failure to build the client).
+fn build_client<C, T>(
+    provider: Provider,
+    config: Integration<C>,
+    new: impl FnOnce(C) -> Result<T>,
+) -> (Option<T>, IntegrationStatus) {
+    let mut status = IntegrationStatus::new(provider, ConfigState::from(&config));
+    let client = match config.configured().map(new) {
+        Some(Ok(client)) => Some(client),
+        Some(Err(e)) => {
+            status.config = ConfigState::Invalid(format!("{:#}", e));
+            None
+        }
+        None => None,
+    };
+    (client, status)
+}
+
+/// A freshly authenticated client of one provider, from a re-authentication
+/// in the Integrations view.
+#[derive(Debug, Clone)]
+pub enum ProviderClient {
+    Datto(Arc<dyn RmmApi>),
+    RocketCyber(Arc<dyn SocApi>),
+    Sophos(Arc<dyn MdrApi>),
+    DattoAv(Arc<dyn AvApi>),
+    Defender(Arc<dyn DefenderApi>),
+    SentinelOne(Arc<dyn SentinelOneApi>),
+}
+
+impl ProviderClient {
+    /// Wraps the client so it refuses writes (RocketCyber has none).
+    fn read_only(self) -> Self {
+        match self {
+            ProviderClient::Datto(c) => ProviderClient::Datto(Arc::new(ReadOnlyRmm(c))),
+            ProviderClient::Sophos(c) => ProviderClient::Sophos(Arc::new(ReadOnlyMdr(c))),
+            ProviderClient::DattoAv(c) => ProviderClient::DattoAv(Arc::new(ReadOnlyAv(c))),
+            ProviderClient::Defender(c) => {
+                ProviderClient::Defender(Arc::new(ReadOnlyDefender(c)))
+            }
+            ProviderClient::SentinelOne(c) => {
+                ProviderClient::SentinelOne(Arc::new(ReadOnlySentinelOne(c)))
+            }
+            client @ ProviderClient::RocketCyber(_) => client,
+        }
+    }
+}
+
+/// Config state of `provider` in `config`; Datto RMM is required, so a loaded
+/// config always has it.
+fn provider_config_state(provider: Provider, config: &Config) -> ConfigState {
+    match provider {
+        Provider::Datto => ConfigState::Configured,
+        Provider::RocketCyber => ConfigState::from(&config.rocket),
+        Provider::Sophos => ConfigState::from(&config.sophos),
+        Provider::DattoAv => ConfigState::from(&config.datto_av),
+        Provider::Defender => ConfigState::from(&config.defender),
+        Provider::SentinelOne => ConfigState::from(&config.sentinelone),
+    }
+}
+
+/// Builds a client for `provider` from `config` and authenticates it. The
+/// key-only APIs (RocketCyber, Datto AV, SentinelOne) are checked with a
+/// lookup instead.
+async fn authenticate_provider(provider: Provider, config: Config) -> Result<ProviderClient> {
+    let not_configured = || anyhow::anyhow!("{} is not configured", provider.title());
+    let invalid = |e: String| anyhow::anyhow!(e);
+    let read_only = config.read_only;
+    let client = match provider {
+        Provider::Datto => {
+            let mut client = DattoClient::new(config.datto)?;
+            client.ping().await?;
+            client.authenticate().await?;
+            ProviderClient::Datto(Arc::new(client))
+        }
+        Provider::RocketCyber => {
+            let config = match config.rocket {
+                Integration::Configured(config) => config,
+                Integration::Invalid(e) => return Err(invalid(e)),
+                Integration::NotConfigured => return Err(not_configured()),
+            };
+            let client = RocketCyberClient::new(config)?;
+            client.get_agents(PROBE_HOSTNAME).await?;
+            ProviderClient::RocketCyber(Arc::new(client))
+        }
+        Provider::Sophos => {
+            let config = match config.sophos {
+                Integration::Configured(config) => config,
+                Integration::Invalid(e) => return Err(invalid(e)),
+                Integration::NotConfigured => return Err(not_configured()),
+            };
+            let mut client = SophosClient::new(config)?;
+            client.authenticate().await?;
+            ProviderClient::Sophos(Arc::new(client))
+        }
+        Provider::DattoAv => {
+            let config = match config.datto_av {
+                Integration::Configured(config) => config,
+                Integration::Invalid(e) => return Err(invalid(e)),
+                Integration::NotConfigured => return Err(not_configured()),
+            };
+            let client = DattoAvClient::new(config)?;
+            client.get_agent_details(PROBE_HOSTNAME).await?;
+            ProviderClient::DattoAv(Arc::new(client))
+        }
+        Provider::Defender => {
+            let config = match config.defender {
+                Integration::Configured(config) => config,
+                Integration::Invalid(e) => return Err(invalid(e)),
+                Integration::NotConfigured => return Err(not_configured()),
+            };
+            let mut client = DefenderClient::new(config)?;
+            client.authenticate().await?;
+            ProviderClient::Defender(Arc::new(client))
+        }
+        Provider::SentinelOne => {
+            let config = match config.sentinelone {
+                Integration::Configured(config) => config,
+                Integration::Invalid(e) => return Err(invalid(e)),
+                Integration::NotConfigured => return Err(not_configured()),
+            };
+            let client = SentinelOneClient::new(config)?;
+            client.get_agents(PROBE_HOSTNAME).await?;
+            ProviderClient::SentinelOne(Arc::new(client))
+        }
+    };
+    Ok(if read_only { client.read_only() } else { client })
+}
+
+/// Every site of the Datto RMM account of `profile`, for the merged site list.
+/// Uses a client of its own; the active profile's clients are left alone.
+async fn account_sites(profile: &str) -> Result<Vec<Site>> {
+    let config = Config::load(Some(profile))?;
+    let mut client = DattoClient::new(config.datto)?;
+    client.authenticate().await?;
+    let client: Arc<dyn RmmApi> = Arc::new(client);
+    let page_size = client.page_size();
+    let mut sites = Vec::new();
+    for page in 0.. {
+        let response = client.get_sites(page, page_size, None).await?;
+        let count = response.sites.len();
+        sites.extend(response.sites);
+        if count < page_size as usize || response.page_details.next_page_url.is_none() {
+            break;
+        }
+    }
+    Ok(sites)
+}
+
+/// A reported error, kept in the history popup and shown as a banner until acknowledged.
+#[derive(Debug, Clone)]
+pub struct ErrorEntry {
+    pub timestamp: chrono::DateTime<chrono::Local>,
+    pub source: &'static str,
+    pub message: String,
+    /// View the banner is shown on; `None` shows it everywhere
+    pub view: Option<CurrentView>,
+    pub seen: bool,
+}
 
 #[derive(Debug, Default, Clone)]
 pub struct IncidentStats {
@@ -31,59 +451,38 @@ pub struct IncidentStats {
     pub resolved: i32,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum CurrentView {
     List,
     Detail,
     DeviceDetail,
     ActivityDetail,
+    Report,
+    Components,
+    Integrations,
+    AccountVariables,
+    Tags,
+    Collections,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum SiteDetailTab {
+    #[default]
     Devices,
     Alerts,
     Variables,
+    Udfs,
     Settings,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum DeviceDetailTab {
+    #[default]
     OpenAlerts,
+    ResolvedAlerts,
     Activities,
     Software,
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub enum SiteEditField {
-    Name,
-    Description,
-    Notes,
-}
-
-#[derive(Debug)]
-pub struct SiteEditState {
-    pub name: String,
-    pub description: String,
-    pub notes: String,
-    pub on_demand: bool,
-    pub splashtop_auto_install: bool,
-    pub active_field: SiteEditField,
-    pub is_editing: bool, // Track if we are in "edit mode" for settings (or just viewing) - simplification: settings is always editable input fields
-}
-
-impl Default for SiteEditState {
-    fn default() -> Self {
-        Self {
-            name: String::new(),
-            description: String::new(),
-            notes: String::new(),
-            on_demand: false,
-            splashtop_auto_install: false,
-            active_field: SiteEditField::Name,
-            is_editing: false,
-        }
-    }
+    Policies,
 }
 
 #[derive(Debug, PartialEq)]
@@ -100,6 +499,8 @@ pub enum InputField {
     SiteName,
     SiteDescription,
     SiteNotes,
+    /// Any other typed site setting; `editing_setting` says which
+    SiteSetting,
 }
 
 #[derive(Debug)]
@@ -112,6 +513,8 @@ pub struct InputState {
     pub editing_variable_id: Option<i32>,
     // Add context for what we are editing if not a variable
     pub editing_setting: Option<SiteEditField>,
+    /// (kind, item id) being acknowledged; the buffer holds the note
+    pub acknowledging: Option<(&'static str, String)>,
 }
 
 impl Default for InputState {
@@ -124,17 +527,29 @@ impl Default for InputState {
             is_creating: true,
             editing_variable_id: None,
             editing_setting: None,
+            acknowledging: None,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum JobViewRow {
     ComponentHeader(usize), // Component Index
     StdOutLink(usize),      // Component Index
     StdErrLink(usize),      // Component Index
 }
 
+impl JobViewRow {
+    /// Index of the component the row belongs to
+    pub fn component(&self) -> usize {
+        match self {
+            JobViewRow::ComponentHeader(i)
+            | JobViewRow::StdOutLink(i)
+            | JobViewRow::StdErrLink(i) => *i,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RunComponentStep {
     Search,
@@ -148,13 +563,30 @@ pub enum QuickAction {
     ScheduleReboot,
     RunComponent,
     RunAvScan,
+    /// Cut the device off the network in its antivirus console
+    IsolateDevice,
     OpenWebRemote,
     ReloadData,
     MoveToSite,
     UpdateWarranty,
     ClearWarranty,
+    /// Change the description or friendly name
+    EditDevice,
+    /// Remove the device from RMM (and optionally its AV console)
+    Decommission,
+    /// Ping, port check or DNS lookup of the device from this machine
+    NetworkTools,
+    /// Index into `App::custom_quick_actions`
+    Custom(usize),
+    /// Index into `App::launchers`
+    Launcher(usize),
+    /// Run a component on every device of the selected collection
+    CollectionRunComponent,
+    CollectionScan,
+    CollectionReboot,
 }
 
+/// Focused segment of a YYYY-MM-DD input (warranty popup, date variables).
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum WarrantyFocus {
     Year,
@@ -162,6 +594,78 @@ pub enum WarrantyFocus {
     Day,
 }
 
+impl WarrantyFocus {
+    pub fn index(self) -> usize {
+        match self {
+            WarrantyFocus::Year => 0,
+            WarrantyFocus::Month => 1,
+            WarrantyFocus::Day => 2,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            WarrantyFocus::Year => WarrantyFocus::Month,
+            WarrantyFocus::Month => WarrantyFocus::Day,
+            WarrantyFocus::Day => WarrantyFocus::Year,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            WarrantyFocus::Year => WarrantyFocus::Day,
+            WarrantyFocus::Month => WarrantyFocus::Year,
+            WarrantyFocus::Day => WarrantyFocus::Month,
+        }
+    }
+}
+
+/// Steps the focused date segment up or down, wrapping around.
+fn adjust_date_segment(segments: &mut [String; 3], focus: WarrantyFocus, delta: i32) {
+    let idx = focus.index();
+    let mut val: i32 = segments[idx].parse().unwrap_or(0);
+    val += delta;
+
+    match focus {
+        WarrantyFocus::Year => { if val < 0 { val = 9999; } if val > 9999 { val = 0; } },
+        WarrantyFocus::Month => { if val < 1 { val = 12; } if val > 12 { val = 1; } },
+        WarrantyFocus::Day => { if val < 1 { val = 31; } if val > 31 { val = 1; } },
+    }
+
+    if focus == WarrantyFocus::Year {
+        segments[idx] = format!("{:04}", val);
+    } else {
+        segments[idx] = format!("{:02}", val);
+    }
+}
+
+/// Types a digit into the focused date segment, moving on once it is full.
+fn push_date_digit(segments: &mut [String; 3], focus: &mut WarrantyFocus, c: char) {
+    let idx = focus.index();
+    let limit = if *focus == WarrantyFocus::Year { 4 } else { 2 };
+    let mut s = segments[idx].clone();
+    s.push(c);
+    if s.len() > limit {
+        s.remove(0);
+    }
+    segments[idx] = s;
+
+    // Auto-advance
+    if segments[idx].len() == limit && *focus != WarrantyFocus::Day {
+        *focus = focus.next();
+    }
+}
+
+/// Splits a YYYY-MM-DD value into date segments, empty if it isn't one.
+fn date_segments(value: &str) -> [String; 3] {
+    let parts: Vec<&str> = value.split('-').collect();
+    if parts.len() == 3 {
+        [parts[0].to_string(), parts[1].to_string(), parts[2].to_string()]
+    } else {
+        Default::default()
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RebootFocus {
     RebootNow,
@@ -176,150 +680,84 @@ pub enum RebootFocus {
 pub struct App {
     pub should_quit: bool,
     pub counter: u8,
-    // Sites
-    pub sites: Vec<Site>,
-    // RocketCyber Incidents
-    pub incidents: Vec<crate::api::rocket_cyber::types::Incident>,
-    // Aggregated Stats: Key is lowercased account name
-    pub incident_stats: HashMap<String, IncidentStats>,
-
-    pub is_loading: bool,
-    pub error: Option<String>,
-    pub client: Option<DattoClient>,
-    pub rocket_client: Option<RocketCyberClient>,
-    pub sophos_client: Option<SophosClient>,
-    pub datto_av_client: Option<DattoAvClient>,
+    /// Oldest first, capped at `MAX_ERROR_HISTORY`
+    pub errors: VecDeque<ErrorEntry>,
+    pub client: Option<Arc<dyn RmmApi>>,
+    pub rocket_client: Option<Arc<dyn SocApi>>,
+    pub sophos_client: Option<Arc<dyn MdrApi>>,
+    pub datto_av_client: Option<Arc<dyn AvApi>>,
+    pub defender_client: Option<Arc<dyn DefenderApi>>,
+    pub sentinelone_client: Option<Arc<dyn SentinelOneApi>>,
+    /// UDF holding SentinelOne agent UUIDs, if the profile sets one
+    pub sentinelone_uuid_udf: Option<usize>,
     pub current_view: CurrentView,
 
-    // Navigation & Pagination (Sites)
-    pub table_state: TableState,
-    pub current_page: i32,
-    pub total_pages: i32,
-    pub total_count: i32,
-
-    // Devices
-    pub devices: Vec<Device>,
-    pub devices_loading: bool,
-    pub devices_error: Option<String>,
-    pub devices_table_state: TableState,
-    pub detail_tab: SiteDetailTab,
-    pub selected_device: Option<Device>,
-    pub selected_device_uids: HashSet<String>,
-    pub device_detail_tab: DeviceDetailTab,
-
-    // Activity Logs
-    pub activity_logs: Vec<ActivityLog>,
-    pub activity_logs_loading: bool,
-    pub activity_logs_error: Option<String>,
-    pub activity_logs_table_state: TableState,
-
-    // Open Alerts
-    pub open_alerts: Vec<crate::api::datto::types::Alert>,
-    pub open_alerts_loading: bool,
-    pub open_alerts_error: Option<String>,
-    pub open_alerts_table_state: TableState,
-
-    // Device Software
-    pub device_software: Vec<crate::api::datto::types::Software>,
-    pub filtered_software: Vec<crate::api::datto::types::Software>,
-    pub software_search_query: String,
-    pub is_software_searching: bool,
-    pub device_software_loading: bool,
-    pub device_software_error: Option<String>,
-    pub device_software_table_state: TableState,
-
-    // Site Open Alerts (for detail view)
-    pub site_open_alerts: Vec<crate::api::datto::types::Alert>,
-    pub site_open_alerts_loading: bool,
-    pub site_open_alerts_error: Option<String>,
-    pub site_open_alerts_table_state: TableState,
-
-    // Job Results
-    pub selected_activity_log: Option<ActivityLog>,
-    pub selected_job_result: Option<JobResult>,
-    pub job_result_loading: bool,
-    pub job_result_error: Option<String>,
-    pub selected_job_row_index: usize,
-
-    // Site & Device Editing State
-    pub variables_table_state: TableState,
-    pub udf_table_state: TableState,
-    pub editing_udf_index: Option<usize>,
-    pub site_edit_state: SiteEditState,
-    pub settings_table_state: TableState,
+    // Per-view state, exposed through the accessors below
+    site_list: SiteListState,
+    site_detail: SiteDetailState,
+    device_detail: DeviceDetailState,
+    security: SecurityState,
+    popups: PopupState,
+    report: ReportState,
+    components: ComponentsState,
+    account_variables: AccountVariablesState,
+    tags: TagsState,
+    collections: CollectionsState,
+    integrations: IntegrationsState,
+    notifications: NotificationsState,
+    /// Daily per-site counts behind the site detail sparklines
+    site_history: SiteHistory,
+    device_watch: DeviceWatch,
+    /// Background tasks; see `Supervisor`
+    tasks: Supervisor,
+    redraw: Redraw,
+
+    // Variable / setting / UDF edit modal
     pub input_state: InputState,
 
-    pub sophos_endpoints: HashMap<String, Endpoint>,
-    pub sophos_loading: HashMap<String, bool>,
-
-    pub rocket_agents: HashMap<String, crate::api::rocket_cyber::types::Agent>,
-    pub rocket_loading: HashMap<String, bool>,
-
-    pub datto_av_agents: HashMap<String, AgentDetail>,
-    pub datto_av_loading: HashMap<String, bool>,
-    // Store alerts/policies per hostname
-    pub datto_av_alerts: HashMap<String, Vec<crate::api::datto_av::types::Alert>>,
-    pub datto_av_policies: HashMap<String, serde_json::Value>,
-
-    pub scan_status: HashMap<String, crate::event::ScanStatus>,
-
-    // Job Output Popup
-    pub show_popup: bool,
-    pub popup_title: String,
-    pub popup_content: String,
-    pub popup_loading: bool,
-
-    // Device Search Popup
-    pub show_device_search: bool,
-    pub device_search_query: String,
-    pub device_search_results: Vec<Device>,
-    pub device_search_loading: bool,
-    pub device_search_error: Option<String>,
-    pub device_search_table_state: TableState,
-    pub last_search_input: Option<std::time::Instant>,
-    pub last_searched_query: String,
-
-    // Device Variables Popup
-    pub show_device_variables: bool,
-
-    // Run Component Popup
-    pub show_run_component: bool,
-    pub run_component_step: RunComponentStep,
-    pub components: Vec<Component>,
-    pub filtered_components: Vec<Component>,
-    pub component_search_query: String,
-    pub component_list_state: TableState,
-    pub selected_component: Option<Component>,
-    pub component_variables: Vec<QuickJobVariable>,
-    pub component_variable_index: usize,
-    pub component_variable_input: String,
-    pub last_job_response: Option<QuickJobResponse>,
-    pub component_error: Option<String>,
-    pub components_loading: bool,
-
-    // Quick Actions Menu
-    pub show_quick_actions: bool,
-    pub quick_action_list_state: TableState,
-    pub quick_actions: Vec<QuickAction>,
-
-    // Reboot Popup
-    pub show_reboot_popup: bool,
-    pub reboot_now: bool,
-    pub reboot_segments: [String; 5], // YY, MM, DD, HH, mm
-    pub reboot_focus: RebootFocus,
-    pub reboot_error: Option<String>,
-
-    // Move Site
-    pub show_site_move: bool,
-    pub site_move_table_state: TableState,
-    pub site_move_query: String,
-    pub filtered_sites: Vec<crate::api::datto::types::Site>,
-
-    // Warranty Update
-    pub show_warranty_popup: bool,
-    pub warranty_segments: [String; 3], // YYYY, MM, DD
-    pub warranty_focus: WarrantyFocus,
-    pub warranty_error: Option<String>,
+    // Profiles
+    pub active_profile: String,
+    pub profiles: Vec<String>,
+    /// Component shortcuts from the config file, offered in the device quick actions
+    pub custom_quick_actions: Vec<CustomQuickAction>,
+    /// Remote access commands from the config file, run against the open device
+    pub launchers: Vec<Launcher>,
+    /// Alert fixes from the config file, offered in the alert detail
+    pub runbooks: Vec<Runbook>,
+    /// Command of a launcher that takes over the terminal, run by `run` once
+    /// the key that started it is handled
+    pending_launch: Option<Vec<String>>,
+    /// Site variable templates from the config file
+    pub variable_templates: Vec<VariableTemplate>,
+    /// Site list color/badge rules from the config file
+    pub site_rules: Vec<SiteRule>,
+    /// Profiles whose sites the merged site list adds (`merged_profiles` in the config)
+    pub merged_profiles: Vec<String>,
+    /// Site of another account to open once the switch to its profile has loaded the sites
+    open_site_after_switch: Option<String>,
+    /// Actions that ask before they run (`confirm_actions` in the config file)
+    pub confirm_actions: Vec<ConfirmKind>,
+    /// Days back the device resolved alerts tab goes (`resolved_alert_days` in the config)
+    pub resolved_alert_days: u32,
+    /// Friendly UDF names from the profile config, by UDF number
+    pub udf_labels: BTreeMap<usize, String>,
+    /// UDF the device tags are kept in, comma-separated (`tags_udf` in the config)
+    pub tags_udf: Option<usize>,
+    /// Writes are refused by the clients (`read_only` in the config); shown in the header
+    pub read_only: bool,
+    /// Short-lived message shown over the bottom right corner, with when it was shown
+    pub toast: Option<(String, std::time::Instant)>,
+    /// Recently opened devices, newest first (at most `RECENT_DEVICES_LIMIT`)
+    pub recent_devices: Vec<Device>,
+    /// Saved and recent device searches, offered while the search is empty
+    pub search_history: SearchHistory,
+    /// JSON of the last session written to the cache, to skip unchanged writes
+    saved_session: Option<String>,
+
+    // On-disk cache
+    pub cache: Option<Cache>,
+    /// Local alert/incident acknowledgements, keyed by (kind, item id)
+    acknowledgements: HashMap<(String, String), Acknowledgement>,
 }
 
 impl Default for App {
@@ -327,167 +765,219 @@ impl Default for App {
         Self {
             should_quit: false,
             counter: 0,
-            sites: Vec::new(),
-            incidents: Vec::new(),
-            incident_stats: HashMap::new(),
-            is_loading: false,
-            error: None,
+            errors: VecDeque::new(),
             client: None,
             rocket_client: None,
             sophos_client: None,
             datto_av_client: None,
+            defender_client: None,
+            sentinelone_client: None,
+            sentinelone_uuid_udf: None,
             current_view: CurrentView::List,
 
-            table_state: TableState::default(),
-            current_page: 0,
-            total_pages: 0,
-            total_count: 0,
-
-            devices: Vec::new(),
-            devices_loading: false,
-            devices_error: None,
-            devices_table_state: TableState::default(),
-            detail_tab: SiteDetailTab::Devices,
-            selected_device: None,
-            selected_device_uids: HashSet::new(),
-            device_detail_tab: DeviceDetailTab::OpenAlerts,
-            // Removed duplicates
-            // variables_table_state: TableState::default(),
-            // udf_table_state: TableState::default(),
-            // editing_udf_index: None,
-
-            activity_logs: Vec::new(),
-            activity_logs_loading: false,
-            activity_logs_error: None,
-            activity_logs_table_state: TableState::default(),
-
-            open_alerts: Vec::new(),
-            open_alerts_loading: false,
-            open_alerts_error: None,
-            open_alerts_table_state: TableState::default(),
-
-            device_software: Vec::new(),
-            filtered_software: Vec::new(),
-            software_search_query: String::new(),
-            is_software_searching: false,
-            device_software_loading: false,
-            device_software_error: None,
-            device_software_table_state: TableState::default(),
-
-            site_open_alerts: Vec::new(),
-            site_open_alerts_loading: false,
-            site_open_alerts_error: None,
-            site_open_alerts_table_state: TableState::default(),
-
-            selected_activity_log: None,
-            selected_job_result: None,
-            job_result_loading: false,
-            job_result_error: None,
-            selected_job_row_index: 0,
-
-            variables_table_state: TableState::default(),
-            udf_table_state: TableState::default(),
-            editing_udf_index: None,
-            site_edit_state: SiteEditState::default(),
-            settings_table_state: TableState::default(),
-            input_state: InputState::default(),
-
-            sophos_endpoints: HashMap::new(),
-            sophos_loading: HashMap::new(),
-
-            rocket_agents: HashMap::new(),
-            rocket_loading: HashMap::new(),
-
-            datto_av_agents: HashMap::new(),
-            datto_av_loading: HashMap::new(),
-            datto_av_alerts: HashMap::new(),
-            datto_av_policies: HashMap::new(),
-
-            scan_status: HashMap::new(),
-
-            show_popup: false,
-            popup_title: String::new(),
-            popup_content: String::new(),
-            popup_loading: false,
-
-            // Device Search Popup
-            show_device_search: false,
-            device_search_query: String::new(),
-            device_search_results: Vec::new(),
-            device_search_loading: false,
-            device_search_error: None,
-            device_search_table_state: TableState::default(),
-            last_search_input: None,
-            last_searched_query: String::new(),
-
-            show_device_variables: false,
-
-            show_run_component: false,
-            run_component_step: RunComponentStep::Search,
-            components: Vec::new(),
-            filtered_components: Vec::new(),
-            component_search_query: String::new(),
-            component_list_state: TableState::default(),
-            selected_component: None,
-            component_variables: Vec::new(),
-            component_variable_index: 0,
-            component_variable_input: String::new(),
-            last_job_response: None,
-            component_error: None,
-            components_loading: false,
-
-            // Quick Actions
-            show_quick_actions: false,
-            quick_action_list_state: TableState::default(),
-            quick_actions: Vec::new(),
-
-            // Reboot
-            show_reboot_popup: false,
-            reboot_now: true,
-            reboot_segments: [
-                String::new(), // YY
-                String::new(), // MM
-                String::new(), // DD
-                String::new(), // HH
-                String::new(), // mm
-            ],
-            reboot_focus: RebootFocus::RebootNow,
-            reboot_error: None,
+            site_list: SiteListState::default(),
+            site_detail: SiteDetailState::default(),
+            device_detail: DeviceDetailState::default(),
+            security: SecurityState::default(),
+            popups: PopupState::default(),
+            report: ReportState::default(),
+            components: ComponentsState::default(),
+            account_variables: AccountVariablesState::default(),
+            tags: TagsState::default(),
+            collections: CollectionsState::default(),
+            integrations: IntegrationsState::default(),
+            notifications: NotificationsState::default(),
+            site_history: SiteHistory::default(),
+            device_watch: DeviceWatch::default(),
+            tasks: Supervisor::default(),
+            redraw: Redraw::default(),
 
-            show_site_move: false,
-            site_move_table_state: TableState::default(),
-            site_move_query: String::new(),
-            filtered_sites: Vec::new(),
+            input_state: InputState::default(),
 
-            show_warranty_popup: false,
-            warranty_segments: [String::new(), String::new(), String::new()],
-            warranty_focus: WarrantyFocus::Year,
-            warranty_error: None,
+            active_profile: String::new(),
+            profiles: Vec::new(),
+            custom_quick_actions: Vec::new(),
+            launchers: Vec::new(),
+            runbooks: Vec::new(),
+            pending_launch: None,
+            confirm_actions: ConfirmKind::ALL.to_vec(),
+            resolved_alert_days: DEFAULT_RESOLVED_ALERT_DAYS,
+            variable_templates: Vec::new(),
+            site_rules: Vec::new(),
+            merged_profiles: Vec::new(),
+            open_site_after_switch: None,
+            udf_labels: BTreeMap::new(),
+            tags_udf: None,
+            read_only: false,
+            toast: None,
+            recent_devices: Vec::new(),
+            search_history: SearchHistory::default(),
+            saved_session: None,
+
+            cache: None,
+            acknowledgements: HashMap::new(),
         }
     }
 }
 
 impl App {
-    pub fn new(
-        client: Option<DattoClient>,
-        rocket_client: Option<RocketCyberClient>,
-        sophos_client: Option<SophosClient>,
-        datto_av_client: Option<DattoAvClient>,
-    ) -> Self {
+    /// An app on the clients and settings of a profile.
+    pub fn new(clients: ProfileClients, settings: ProfileSettings) -> Self {
         let mut app = Self::default();
-        app.client = client;
-        app.rocket_client = rocket_client;
-        app.sophos_client = sophos_client;
-        app.datto_av_client = datto_av_client;
+        app.client = Some(clients.datto);
+        app.rocket_client = clients.rocket;
+        app.sophos_client = clients.sophos;
+        app.datto_av_client = clients.datto_av;
+        app.defender_client = clients.defender;
+        app.sentinelone_client = clients.sentinelone;
+        app.sentinelone_uuid_udf = clients.sentinelone_uuid_udf;
+        app.udf_labels = clients.udf_labels;
+        app.tags_udf = clients.tags_udf;
+        app.read_only = clients.read_only;
+        app.set_integrations(clients.integrations);
+
+        app.report.refresh_interval = settings.watch_interval;
+        app.report.notify_offline = settings.notify_offline_servers;
+        app.resolved_alert_days = settings.resolved_alert_days;
+        app.confirm_actions = settings.confirm_actions;
+        app.custom_quick_actions = settings.quick_actions;
+        app.variable_templates = settings.variable_templates;
+        app.launchers = settings.launchers;
+        app.runbooks = settings.runbooks;
+        app.site_rules = settings.site_rules;
+        app.site_list.groups = settings.site_groups;
+        app.merged_profiles = settings.merged_profiles;
         app
     }
 
+    pub fn site_list(&self) -> &SiteListState {
+        &self.site_list
+    }
+
+    pub fn site_list_mut(&mut self) -> &mut SiteListState {
+        &mut self.site_list
+    }
+
+    pub fn site_detail(&self) -> &SiteDetailState {
+        &self.site_detail
+    }
+
+    pub fn site_detail_mut(&mut self) -> &mut SiteDetailState {
+        &mut self.site_detail
+    }
+
+    pub fn device_detail(&self) -> &DeviceDetailState {
+        &self.device_detail
+    }
+
+    pub fn device_detail_mut(&mut self) -> &mut DeviceDetailState {
+        &mut self.device_detail
+    }
+
+    pub fn security(&self) -> &SecurityState {
+        &self.security
+    }
+
+    pub fn security_mut(&mut self) -> &mut SecurityState {
+        &mut self.security
+    }
+
+    pub fn popups(&self) -> &PopupState {
+        &self.popups
+    }
+
+    pub fn popups_mut(&mut self) -> &mut PopupState {
+        &mut self.popups
+    }
+
+    pub fn report(&self) -> &ReportState {
+        &self.report
+    }
+
+    pub fn report_mut(&mut self) -> &mut ReportState {
+        &mut self.report
+    }
+
+    pub fn components(&self) -> &ComponentsState {
+        &self.components
+    }
+
+    pub fn components_mut(&mut self) -> &mut ComponentsState {
+        &mut self.components
+    }
+
+    pub fn account_variables(&self) -> &AccountVariablesState {
+        &self.account_variables
+    }
+
+    pub fn account_variables_mut(&mut self) -> &mut AccountVariablesState {
+        &mut self.account_variables
+    }
+
+    pub fn tags(&self) -> &TagsState {
+        &self.tags
+    }
+
+    pub fn tags_mut(&mut self) -> &mut TagsState {
+        &mut self.tags
+    }
+
+    pub fn collections(&self) -> &CollectionsState {
+        &self.collections
+    }
+
+    pub fn collections_mut(&mut self) -> &mut CollectionsState {
+        &mut self.collections
+    }
+
+    pub fn integrations(&self) -> &IntegrationsState {
+        &self.integrations
+    }
+
+    pub fn integrations_mut(&mut self) -> &mut IntegrationsState {
+        &mut self.integrations
+    }
+
+    pub fn notifications(&self) -> &NotificationsState {
+        &self.notifications
+    }
+
+    pub fn notifications_mut(&mut self) -> &mut NotificationsState {
+        &mut self.notifications
+    }
+
+    pub fn site_history(&self) -> &SiteHistory {
+        &self.site_history
+    }
+
+    pub fn device_watch(&self) -> &DeviceWatch {
+        &self.device_watch
+    }
+
+    pub fn tasks(&self) -> &Supervisor {
+        &self.tasks
+    }
+
+    /// Number of variables on the selected site (excluding the "Create +" row).
+    fn selected_site_variable_count(&self) -> usize {
+        self.site_list
+            .selected_site()
+            .and_then(|s| s.variables.as_ref())
+            .map_or(0, |v| v.len())
+    }
+
     pub async fn run(&mut self, tui: &mut Tui, events: &mut EventHandler) -> Result<()> {
-        // Initial fetch
+        self.tasks.attach(events.sender());
+        // Render cached data immediately while fresh data loads
+        self.load_cached_sites();
+        self.offer_session_restore();
+
+        // Initial fetch; account variables hold the sites' default MDR mapping
         if self.client.is_some() {
             self.fetch_sites(events.sender());
+            self.fetch_account_variables(events.sender());
         } else {
-            self.error = Some("API Client not initialized. Check .env config.".to_string());
+            self.report_error("Datto", None, "API Client not initialized. Check your config.".to_string());
         }
 
         // Fetch incidents
@@ -495,20 +985,30 @@ impl App {
             self.fetch_rocket_incidents(events.sender());
         }
 
-        // Authenticate Sophos if present
-        if let Some(client) = &mut self.sophos_client {
-            if let Err(e) = client.authenticate().await {
-                self.error = Some(format!("Sophos Auth Failed: {}", e));
-            }
-        }
-
         while !self.should_quit {
-            tui.draw(|f| {
-                ui::render(self, f);
-            })?;
+            if self.redraw.is_due() {
+                // Event results can open or close popups too (a confirmation,
+                // the alert detail)
+                self.sync_popups();
+                tui.draw(|f| {
+                    ui::render(self, f);
+                })?;
+                self.redraw.drawn();
+            }
 
-            match events.next().await? {
-                Event::Key(key) => self.handle_key_event(key, events.sender()),
+            let event = events.next().await?;
+            // Ticks request a frame themselves when time changed something
+            if !matches!(event, Event::Tick) {
+                self.redraw.request();
+            }
+            match event {
+                Event::Key(key) => {
+                    self.handle_key_event(key, events.sender());
+                    self.persist_session();
+                    if let Some(args) = self.pending_launch.take() {
+                        self.run_in_terminal(tui, events, args).await?;
+                    }
+                }
                 Event::Mouse(_) => {}
                 Event::Resize(_, _) => {}
                 event => self.handle_event(event, events.sender()).await?,
@@ -517,119 +1017,369 @@ impl App {
         Ok(())
     }
 
+    /// The Datto RMM web console page of what is selected, as (name, URL).
+    /// Alerts and jobs have no page of their own in the API, so they open
+    /// their device's page, where both are listed.
+    fn portal_link(&self) -> Option<(String, String)> {
+        let device_link =
+            |d: &Device| d.portal_url.clone().map(|url| (d.hostname.clone(), url));
+        let site_link = || {
+            let site = self.site_list.selected_site()?;
+            site.portal_url.clone().map(|url| (site.name.clone(), url))
+        };
+        match self.current_view {
+            CurrentView::List => site_link(),
+            CurrentView::Detail => match self.site_detail.detail_tab {
+                SiteDetailTab::Devices => self.site_detail.selected_device().and_then(device_link),
+                SiteDetailTab::Alerts => {
+                    let alert = self.site_detail.site_open_alerts.selected()?;
+                    let uid = alert.alert_source_info.as_ref()?.device_uid.as_ref()?;
+                    self.site_detail
+                        .devices
+                        .items()
+                        .iter()
+                        .find(|d| &d.uid == uid)
+                        .and_then(device_link)
+                }
+                _ => None,
+            }
+            .or_else(site_link),
+            CurrentView::DeviceDetail | CurrentView::ActivityDetail => {
+                self.device_detail.selected_device.as_ref().and_then(device_link)
+            }
+            CurrentView::Report => self.report.selected_device().and_then(device_link),
+            CurrentView::Tags => self.tags.selected_device().and_then(device_link),
+            CurrentView::Collections => self.collections.selected_device().and_then(device_link),
+            CurrentView::Components | CurrentView::Integrations | CurrentView::AccountVariables => {
+                None
+            }
+        }
+    }
+
+    fn open_portal_link(&mut self) {
+        match self.portal_link() {
+            Some((name, url)) => {
+                tracing::debug!(%url, "opening Datto RMM web console");
+                crate::common::utils::open_browser(&url);
+                self.show_toast(format!("Opened {} in Datto RMM", name));
+            }
+            None => self.show_toast("Nothing selected with a Datto RMM page".to_string()),
+        }
+    }
+
+    /// Hands the terminal to a launcher's command until it exits. The event
+    /// reader is paused meanwhile so the command gets every key press.
+    async fn run_in_terminal(
+        &mut self,
+        tui: &mut Tui,
+        events: &mut EventHandler,
+        args: Vec<String>,
+    ) -> Result<()> {
+        tracing::info!(?args, "running launcher in the terminal");
+        events.pause();
+        crate::tui::restore()?;
+        let result = launcher::run_foreground(&args).await;
+        crate::tui::resume(tui)?;
+        events.resume();
+        if let Err(e) = result {
+            self.report_error("Launcher", Some(CurrentView::DeviceDetail), e);
+        }
+        Ok(())
+    }
+
     async fn handle_event(
         &mut self,
         event: Event,
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) -> Result<()> {
+        if let Some((provider, result)) = event.api_outcome() {
+            if result == Err(&ApiError::ReadOnly) {
+                self.show_toast(READ_ONLY_MESSAGE.to_string());
+            }
+            self.integrations.status_mut(provider).record_call(result);
+        }
+
         match event {
             Event::Tick => {
+                if crate::ui::loading::tick() {
+                    self.redraw.request();
+                }
+                if self.toast.as_ref().is_some_and(|(_, at)| at.elapsed() >= TOAST_DURATION) {
+                    self.toast = None;
+                    self.redraw.request();
+                }
+                if self.report.refresh_due() {
+                    tracing::debug!("background refresh of account devices");
+                    self.fetch_account_devices(tx.clone());
+                    self.redraw.request();
+                }
+
+                self.retry_failed_loads(tx.clone());
+                self.poll_watched_devices(tx.clone());
+                self.poll_job_status(tx.clone());
+                self.poll_scans(&tx);
+                self.follow_job_output(tx.clone());
+                self.poll_activity_logs(tx.clone());
+
                 // Handle Device Search Debounce
-                if self.show_device_search {
-                    if let Some(last_input) = self.last_search_input {
+                if self.popups.is_open(Popup::DeviceSearch) {
+                    if let Some(last_input) = self.popups.last_search_input {
                         if last_input.elapsed() >= std::time::Duration::from_millis(500) {
-                             // Log debounce check
-                             let _ = std::fs::OpenOptions::new()
-                                .create(true)
-                                .append(true)
-                                .open("debug.log")
-                                .map(|mut f| {
-                                     use std::io::Write;
-                                     writeln!(f, "Tick: Checking search. Query='{}', Last='{}'", self.device_search_query, self.last_searched_query).unwrap();
-                                });
-
-                            if self.device_search_query.len() >= 3
-                                && self.device_search_query != self.last_searched_query
+                            tracing::trace!(query = %self.popups.device_search_query, last = %self.popups.last_searched_query, "debounced search check");
+
+                            if split_search_query(&self.popups.device_search_query).0.len() >= 3
+                                && self.popups.device_search_query != self.popups.last_searched_query
                             {
-                                self.last_searched_query = self.device_search_query.clone();
-                                self.search_devices(self.device_search_query.clone(), tx.clone());
+                                self.popups.last_searched_query = self.popups.device_search_query.clone();
+                                self.search_devices(self.popups.device_search_query.clone(), tx.clone());
+                                self.redraw.request();
                             }
                         }
                     }
                 }
             }
-            Event::Key(_) | Event::Mouse(_) | Event::Resize(_, _) => {}
-            Event::DeviceSearchResultsFetched(result) => {
-                self.device_search_loading = false;
+            Event::Key(_) | Event::Mouse(_) | Event::Resize(_, _) | Event::SetupValidated(_, _) => {}
+            // A later page of a search that has since been replaced
+            Event::DeviceSearchResultsFetched(page, _)
+                if page > self.popups.device_search_pages => {}
+            Event::DeviceSearchResultsFetched(page, result) => {
+                self.popups.device_search_loading = false;
                 match result {
-                    Ok(response) => {
-                        self.device_search_results = response.devices;
-                        if !self.device_search_results.is_empty() {
-                            self.device_search_table_state.select(Some(0));
+                    Ok(mut response) => {
+                        let count = response.page_len();
+                        self.popups.device_search_loaded += count;
+                        response.devices.retain(|d| self.popups.device_search_filter.matches(d));
+                        self.popups.device_search_pages = page + 1;
+                        self.popups.device_search_total =
+                            response.page_details.total_count.map(|t| t.max(0) as usize);
+                        self.popups.device_search_has_more = count >= SEARCH_PAGE_SIZE as usize
+                            && response.page_details.next_page_url.is_some();
+                        if page == 0 {
+                            self.popups.device_search_results.set_items(response.devices);
+                            self.popups.rank_device_search_results();
+                            self.popups.device_search_results.select_first();
                         } else {
-                            self.device_search_table_state.select(None);
+                            // Ranked in with the earlier pages, keeping the selected device
+                            self.popups.device_search_results.append(response.devices);
+                            self.popups.rank_device_search_results();
+                        }
+                    }
+                    Err(e) => {
+                        self.popups.device_search_error = Some(e.to_string());
+                    }
+                }
+            }
+            Event::ProfileSwitched(name, result) => {
+                self.popups.profile_switching = false;
+                match result {
+                    Ok((clients, settings)) => {
+                        // Start from a clean slate so nothing cached from the old account leaks through
+                        let profiles = std::mem::take(&mut self.profiles);
+                        let show_merged = self.site_list.show_merged;
+                        let open_site = self.open_site_after_switch.take();
+                        *self = App::new(clients, settings);
+                        self.profiles = profiles;
+                        self.site_list.show_merged = show_merged;
+                        self.open_site_after_switch = open_site;
+                        self.active_profile = name;
+                        self.open_cache();
+                        self.load_cached_sites();
+                        self.fetch_sites(tx.clone());
+                        self.fetch_account_variables(tx.clone());
+                        if show_merged {
+                            self.fetch_merged_sites(tx.clone());
                         }
+                        self.fetch_rocket_incidents(tx);
                     }
                     Err(e) => {
-                        self.device_search_error = Some(e);
+                        self.popups.close(Popup::ProfileSwitcher);
+                        self.open_site_after_switch = None;
+                        self.report_error(
+                            "Profile",
+                            None,
+                            format!("Failed to switch to profile '{}': {}", name, e),
+                        );
+                    }
+                }
+            }
+            Event::IntegrationAuthenticated(provider, config, result) => {
+                let status = self.integrations.status_mut(provider);
+                if let Some(config) = config {
+                    status.config = config;
+                }
+                status.record_auth(result.as_ref().map(|_| ()).map_err(String::clone));
+                match result {
+                    // Reload what failed to load with the old credentials
+                    Ok(ProviderClient::Datto(client)) => {
+                        self.client = Some(client);
+                        self.fetch_sites(tx);
+                    }
+                    Ok(ProviderClient::RocketCyber(client)) => {
+                        self.rocket_client = Some(client);
+                        self.fetch_rocket_incidents(tx);
                     }
+                    Ok(ProviderClient::Sophos(client)) => self.sophos_client = Some(client),
+                    Ok(ProviderClient::DattoAv(client)) => self.datto_av_client = Some(client),
+                    Ok(ProviderClient::Defender(client)) => self.defender_client = Some(client),
+                    Ok(ProviderClient::SentinelOne(client)) => {
+                        self.sentinelone_client = Some(client)
+                    }
+                    Err(e) => self.report_error(
+                        provider.title(),
+                        Some(CurrentView::Integrations),
+                        format!("Re-authentication failed: {}", e),
+                    ),
                 }
             }
+            Event::TaskFailed(task, message) => self.report_error(
+                "Task",
+                None,
+                format!("Background task {} failed: {}", task, message),
+            ),
             Event::SitesFetched(result) => {
-                self.is_loading = false;
+                self.site_list.is_loading = false;
                 match result {
                     Ok(mut response) => {
+                        self.site_list.retry.reset();
                         // Sort sites alphabetically by name
                         response
                             .sites
                             .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                        self.sites = response.sites;
+                        self.site_list.set_sites(response.sites);
+                        self.site_list.sites_cached_at = None;
+                        self.sort_sites();
+                        let own_sites: Vec<&Site> = self.site_list.own_sites().collect();
+                        self.cache_put(cache::SITES_KEY, &own_sites);
+                        self.record_site_history();
+                        self.apply_cached_variables();
 
                         // Update pagination info
-                        self.total_count = response.page_details.total_count.unwrap_or(0);
+                        self.site_list.total_count = response.page_details.total_count.unwrap_or(0);
                         // Calculate total pages (assuming max=50)
-                        if self.total_count > 0 {
-                            self.total_pages = (self.total_count as f64 / 50.0).ceil() as i32;
+                        if self.site_list.total_count > 0 {
+                            self.site_list.total_pages = (self.site_list.total_count as f64 / 50.0).ceil() as i32;
                         } else {
-                            self.total_pages = 1;
+                            self.site_list.total_pages = 1;
                         }
 
-                        if !self.sites.is_empty() {
-                            self.table_state.select(Some(0));
-                            // Fetch variables for all sites on this page
-                            for site in &self.sites {
-                                self.fetch_site_variables(site.uid.clone(), tx.clone());
+                        // Fetch variables for all sites on this page (queued behind the shared request limit in api::throttle)
+                        for site in self.site_list.own_sites() {
+                            self.fetch_site_variables(site.uid.clone(), tx.clone());
+                        }
+
+                        // Opening a site of another account switched to it
+                        if let Some(uid) = self.open_site_after_switch.take()
+                            && self.site_list.select_site(&uid)
+                        {
+                            self.navigate_to_site_detail(uid, tx.clone());
+                        }
+                    }
+                    Err(e) => {
+                        if let Some(delay) = self.site_list.retry.schedule((), &e) {
+                            let secs = delay.as_secs();
+                            self.show_toast(format!("Loading sites failed; retrying in {}s", secs));
+                        }
+                        self.report_error(
+                            "Datto",
+                            Some(CurrentView::List),
+                            format!("Failed to fetch sites: {}", e.describe()),
+                        );
+                    }
+                }
+            }
+            Event::DevicesProgress(generation, site_uid, loaded, total) => {
+                if self.site_detail.fetches.is_current(generation)
+                    && self.site_detail.devices_loading
+                    && self.site_list.selected_site().is_some_and(|s| s.uid == site_uid)
+                {
+                    self.site_detail.devices_progress = Some((loaded, total));
+                }
+            }
+            Event::AccountDevicesProgress(loaded, total) => {
+                if self.report.loading {
+                    self.report.progress = Some((loaded, total));
+                }
+            }
+            Event::AccountDevicesFetched(result) => {
+                self.report.loading = false;
+                self.report.progress = None;
+                match result {
+                    Ok(response) => {
+                        let devices = response.devices;
+                        tracing::debug!(count = devices.len(), "fetched account devices for reports");
+                        if let Some(message) =
+                            partial_load_message("devices", devices.len(), response.parse_errors)
+                        {
+                            self.show_toast(message);
+                        }
+                        self.site_list.set_account_devices(&devices);
+                        self.report.set_devices(devices);
+                        self.rebuild_tags();
+                        self.sort_sites();
+                        for server in self.report.newly_offline_servers() {
+                            tracing::info!(hostname = %server.hostname, site = %server.site, "server went offline");
+                            if self.report.notify_offline {
+                                desktop_notification(
+                                    "Server offline",
+                                    &format!("{} ({}) went offline", server.hostname, server.site),
+                                );
                             }
-                        } else {
-                            self.table_state.select(None);
                         }
                     }
                     Err(e) => {
-                        self.error = Some(e.to_string());
+                        self.report_api_error(
+                            "Datto",
+                            Some(CurrentView::Report),
+                            "Failed to fetch devices for report",
+                            &e,
+                        );
                     }
                 }
             }
-            Event::DevicesFetched(site_uid, result) => {
+            // Drop responses for a site we've since moved away from
+            Event::DevicesFetched(generation, ..)
+                if !self.site_detail.fetches.is_current(generation) => {}
+            Event::DevicesFetched(_, site_uid, result) => {
                 // Ensure the result corresponds to the currently selected site
-                let is_current_site = if let Some(idx) = self.table_state.selected() {
-                    self.sites.get(idx).map(|s| s.uid == site_uid).unwrap_or(false)
-                } else {
-                    false
-                };
+                let is_current_site = self.site_list.selected_site().is_some_and(|s| s.uid == site_uid);
 
                 if is_current_site {
-                    self.devices_loading = false;
+                    self.site_detail.devices_loading = false;
+                    self.site_detail.devices_progress = None;
                     match result {
                         Ok(response) => {
-                            self.devices = response.devices;
-                            if !self.devices.is_empty() {
-                                self.devices_table_state.select(Some(0));
-                            } else {
-                                self.devices_table_state.select(None);
+                            self.site_detail.devices_retry.reset();
+                            let loaded = response.devices.len();
+                            if let Some(message) =
+                                partial_load_message("devices", loaded, response.parse_errors)
+                            {
+                                self.show_toast(message);
                             }
+                            self.site_detail.set_devices(response.devices);
+                            self.site_detail.devices_cached_at = None;
+                            let devices = self.site_detail.devices.items();
+                            self.cache_put(&cache::devices_key(&site_uid), &devices);
                         }
                         Err(e) => {
-                            self.devices_error = Some(e.to_string());
+                            let retry = &mut self.site_detail.devices_retry;
+                            if let Some(delay) = retry.schedule(site_uid, &e) {
+                                let secs = delay.as_secs();
+                                let message = format!("Loading devices failed; retrying in {}s", secs);
+                                self.show_toast(message);
+                            }
+                            self.site_detail.devices_error = Some(e.describe());
                         }
                     }
                 }
             }
             Event::IncidentsFetched(result) => match result {
                 Ok(incidents) => {
-                    self.incidents = incidents;
+                    self.notifications.observe_incidents(&incidents);
+                    self.save_seen_items();
+                    self.security.incidents = incidents;
                     // Aggregate stats
-                    self.incident_stats.clear();
-                    for incident in &self.incidents {
+                    self.security.incident_stats.clear();
+                    for incident in &self.security.incidents {
                         // Normalize name for matching: lowercase and trim
                         let account_name = incident.account_name.to_lowercase();
                         // This is a naive match key. In reality we might need a better mapping.
@@ -637,7 +1387,7 @@ impl App {
                         // For now we will use the lowercase name from RocketCyber as the key.
                         // When looking up from Datto Site, we will also lowercase that name.
 
-                        let entry = self
+                        let entry = self.security
                             .incident_stats
                             .entry(account_name)
                             .or_insert(IncidentStats::default());
@@ -652,7 +1402,7 @@ impl App {
 
                         // Also index by Account ID for variable mapping
                         let account_id = incident.account_id.to_string();
-                        let entry_id = self
+                        let entry_id = self.security
                             .incident_stats
                             .entry(account_id)
                             .or_insert(IncidentStats::default());
@@ -663,60 +1413,81 @@ impl App {
                             entry_id.active += 1;
                         }
                     }
+                    self.sort_sites();
                 }
                 Err(e) => {
-                    self.error = Some(format!("Failed to fetch incidents: {}", e));
+                    self.report_api_error(
+                        "RocketCyber",
+                        Some(CurrentView::List),
+                        "Failed to fetch incidents",
+                        &e,
+                    );
                 }
             },
             Event::SiteVariablesFetched(site_uid, result) => match result {
                 Ok(variables) => {
-                    if let Some(site) = self.sites.iter_mut().find(|s| s.uid == site_uid) {
-                        site.variables = Some(variables.clone());
-
-                        // Check for Sophos MDR
-                        for var in &variables {
-                            if var.name == "tuiMdrProvider" && var.value == "Sophos" {
-                                // Find tuiMdrId
-                                if let Some(id_var) =
-                                    variables.iter().find(|v| v.name == "tuiMdrId")
-                                {
-                                    // Check for tuiMdrRegion to skip tenant call
-                                    let region = variables
-                                        .iter()
-                                        .find(|v| v.name == "tuiMdrRegion")
-                                        .map(|v| v.value.clone());
-
-                                    self.fetch_sophos_cases(
-                                        id_var.value.clone(),
-                                        region,
-                                        tx.clone(),
-                                    );
-                                }
-                            }
+                    self.site_detail.variables_cached_at.remove(&site_uid);
+                    self.cache_put(&cache::variables_key(&site_uid), &variables);
+                    self.diff_variable_copy(&site_uid, &variables);
+                    if let Some(site) = self.site_list.sites.iter_mut().find(|s| s.uid == site_uid) {
+                        site.variables = Some(variables);
+                        // Variable-based site groups may move the site to another section
+                        if !self.site_list.groups.is_empty() {
+                            self.site_list.refresh_visible();
+                        }
+                        let sites = &self.site_list.sites;
+                        if let Some(site) = sites.iter().find(|s| s.uid == site_uid) {
+                            self.fetch_site_mdr_cases(site, tx.clone());
                         }
                     }
                 }
-                Err(_e) => {
-                    // Log error or ignore? For now, maybe just print to stderr if debug
-                    // self.error = Some(format!("Failed to fetch variables for {}: {}", site_uid, e));
+                Err(e) => {
+                    self.report_api_error(
+                        "Datto",
+                        Some(CurrentView::List),
+                        &format!("Failed to fetch variables for {}", site_uid),
+                        &e,
+                    );
                 }
             },
             Event::VariableCreated(site_uid, result) => {
-                self.is_loading = false;
+                self.site_list.is_loading = false;
                 match result {
                     Ok(_) => {
                         // Refresh variables
                         self.fetch_site_variables(site_uid, tx.clone());
                     }
-                    Err(e) => self.error = Some(e),
+                    Err(e) => self.report_api_error(
+                        "Datto",
+                        Some(CurrentView::Detail),
+                        "Failed to create variable",
+                        &e,
+                    ),
                 }
             }
+            Event::VariablesWritten(site_uid, result) => {
+                self.popups.template_applying = false;
+                self.popups.close(Popup::VariableTemplates);
+                if self.popups.variable_copy.take().is_some() {
+                    self.site_detail.selected_variable_ids.clear();
+                }
+                if let Err(e) = result {
+                    self.report_api_error(
+                        "Datto",
+                        Some(CurrentView::Detail),
+                        "Failed to write variables",
+                        &e,
+                    );
+                }
+                // Refresh even after a failure, since earlier variables may have been written
+                self.fetch_site_variables(site_uid, tx.clone());
+            }
             Event::VariableUpdated(site_uid, result) => {
-                self.is_loading = false;
+                self.site_list.is_loading = false;
                 match result {
                     Ok(updated_var) => {
                         // Update local state in place
-                        if let Some(site) = self.sites.iter_mut().find(|s| s.uid == site_uid) {
+                        if let Some(site) = self.site_list.sites.iter_mut().find(|s| s.uid == site_uid) {
                             if let Some(vars) = &mut site.variables {
                                 if let Some(var) = vars.iter_mut().find(|v| v.id == updated_var.id)
                                 {
@@ -726,52 +1497,158 @@ impl App {
                         }
                         // Note: No need to re-fetch variables, providing immediate feedback!
                     }
-                    Err(e) => self.error = Some(e),
+                    Err(e) => self.report_api_error(
+                        "Datto",
+                        Some(CurrentView::Detail),
+                        "Failed to update variable",
+                        &e,
+                    ),
                 }
             }
 
+            Event::VariableDeleted(site_uid, result) => match result {
+                Ok(variable_id) => {
+                    if let Some(vars) = self
+                        .site_list
+                        .sites
+                        .iter_mut()
+                        .find(|s| s.uid == site_uid)
+                        .and_then(|s| s.variables.as_mut())
+                    {
+                        vars.retain(|v| v.id != variable_id);
+                    }
+                    self.site_detail.selected_variable_ids.remove(&variable_id);
+                    let count = self.selected_site_variable_count();
+                    let table_state = &mut self.site_detail.variables_table_state;
+                    if table_state.selected().is_some_and(|i| i > count) {
+                        table_state.select(Some(count));
+                    }
+                }
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::Detail),
+                    "Failed to delete variable",
+                    &e,
+                ),
+            },
+            Event::AccountVariablesFetched(result) => {
+                self.account_variables.loading = false;
+                match result {
+                    Ok(variables) => {
+                        let defaults = MdrDefaults::from_variables(&variables);
+                        self.account_variables.set_variables(variables);
+                        if defaults != self.security.mdr_defaults {
+                            self.security.mdr_defaults = defaults;
+                            // Sites inheriting the mapping may now point elsewhere
+                            let defaults = &self.security.mdr_defaults;
+                            for site in &self.site_list.sites {
+                                if MdrMapping::resolve(site, defaults).inherits() {
+                                    self.fetch_site_mdr_cases(site, tx.clone());
+                                }
+                            }
+                            self.sort_sites();
+                        }
+                    }
+                    Err(e) => {
+                        self.account_variables.error = Some(e.to_string());
+                        self.report_api_error(
+                            "Datto",
+                            Some(CurrentView::AccountVariables),
+                            "Failed to fetch account variables",
+                            &e,
+                        );
+                    }
+                }
+            }
+            Event::AccountVariableCreated(result) => match result {
+                Ok(_) => self.fetch_account_variables(tx.clone()),
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::AccountVariables),
+                    "Failed to create account variable",
+                    &e,
+                ),
+            },
+            Event::AccountVariableUpdated(result) => match result {
+                Ok(variable) => self.account_variables.replace(variable),
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::AccountVariables),
+                    "Failed to update account variable",
+                    &e,
+                ),
+            },
+            Event::AccountVariableDeleted(result) => match result {
+                Ok(variable_id) => self.account_variables.remove(variable_id),
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::AccountVariables),
+                    "Failed to delete account variable",
+                    &e,
+                ),
+            },
             Event::SiteUpdated(result) => {
-                self.is_loading = false;
+                self.site_list.is_loading = false;
                 match result {
                     Ok(updated_site) => {
                         // Find and update the site in the local list
                         if let Some(index) =
-                            self.sites.iter().position(|s| s.uid == updated_site.uid)
+                            self.site_list.sites.iter().position(|s| s.uid == updated_site.uid)
                         {
                             // Preserve fields that might be missing in some API responses (like variables or status)
-                            let old_vars = self.sites[index].variables.clone();
-                            let old_status = self.sites[index].devices_status.clone();
+                            let old_vars = self.site_list.sites[index].variables.clone();
+                            let old_status = self.site_list.sites[index].devices_status.clone();
                             
-                            self.sites[index] = updated_site;
+                            self.site_list.sites[index] = updated_site;
                             
                             // Only restore if the new response is missing them
-                            if self.sites[index].variables.is_none() {
-                                self.sites[index].variables = old_vars;
+                            if self.site_list.sites[index].variables.is_none() {
+                                self.site_list.sites[index].variables = old_vars;
                             }
-                            if self.sites[index].devices_status.is_none() {
-                                self.sites[index].devices_status = old_status;
+                            if self.site_list.sites[index].devices_status.is_none() {
+                                self.site_list.sites[index].devices_status = old_status;
                             }
 
                             // If this is the currently selected site, update the edit state to reflect changes in UI
-                            if let Some(selected_idx) = self.table_state.selected() {
-                                if selected_idx == index {
-                                    self.populate_site_edit_state();
-                                }
+                            if self.site_list.selected_site().is_some_and(|s| s.uid == self.site_list.sites[index].uid) {
+                                self.populate_site_edit_state();
                             }
                         } else {
                             // Site not in current list (e.g. from search), add it so it can be displayed
-                            self.sites.push(updated_site);
-                            self.table_state.select(Some(self.sites.len() - 1));
+                            self.site_list.push_site(updated_site);
                             self.populate_site_edit_state();
                         }
                     }
-                    Err(e) => self.error = Some(e),
+                    Err(e) => self.report_api_error(
+                        "Datto",
+                        Some(CurrentView::Detail),
+                        "Failed to update site",
+                        &e,
+                    ),
                 }
             }
+            Event::SiteProxyUpdated(site_uid, result) => match result {
+                Ok(proxy) => {
+                    let sites = &mut self.site_list.sites;
+                    if let Some(site) = sites.iter_mut().find(|s| s.uid == site_uid) {
+                        site.proxy_settings = proxy;
+                    }
+                    if self.site_list.selected_site().is_some_and(|s| s.uid == site_uid) {
+                        self.populate_site_edit_state();
+                    }
+                    self.show_toast("Proxy settings saved".to_string());
+                }
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::Detail),
+                    "Failed to update the proxy settings",
+                    &e,
+                ),
+            },
             Event::SophosCasesFetched(tenant_id, result) => match result {
                 Ok(cases) => {
                     // Update stats
-                    let entry = self
+                    let entry = self.security
                         .incident_stats
                         .entry(tenant_id.clone())
                         .or_insert(IncidentStats::default());
@@ -789,30 +1666,23 @@ impl App {
                             entry.active += 1;
                         }
                     }
+                    self.sort_sites();
                 }
                 Err(e) => {
-                    let _ = std::fs::OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open("debug.log")
-                        .map(|mut f| {
-                            use std::io::Write;
-                            writeln!(f, "Error fetching Sophos cases for {}: {}", tenant_id, e).unwrap();
-                        });
+                    tracing::warn!(%tenant_id, error = %e, "failed to fetch Sophos cases");
                 }
             },
             Event::SophosEndpointsFetched(hostname, result) => {
-                self.sophos_loading.insert(hostname.clone(), false);
+                self.security.sophos_loading.insert(hostname.clone(), false);
                 match result {
                     Ok(endpoints) => {
                         if let Some(endpoint) = endpoints.first() {
-                            self.sophos_endpoints
+                            self.security.sophos_endpoints
                                 .insert(hostname.clone(), endpoint.clone());
 
                             // Cache Endpoint ID in UDF 30 if different
-                            if let Some(device) =
-                                self.devices.iter().find(|d| d.hostname == hostname)
-                            {
+                            let devices = self.site_detail.devices.items();
+                            if let Some(device) = devices.iter().find(|d| d.hostname == hostname) {
                                 let current_udf30 = device
                                     .udf
                                     .as_ref()
@@ -825,42 +1695,11 @@ impl App {
                                         let device_uid = device.uid.clone();
                                         let endpoint_id = endpoint.id.clone();
                                         let client = client.clone();
-                                        tokio::spawn(async move {
-                                            let udf = crate::api::datto::types::Udf {
-                                                udf30: Some(endpoint_id),
-                                                udf1: None,
-                                                udf2: None,
-                                                udf3: None,
-                                                udf4: None,
-                                                udf5: None,
-                                                udf6: None,
-                                                udf7: None,
-                                                udf8: None,
-                                                udf9: None,
-                                                udf10: None,
-                                                udf11: None,
-                                                udf12: None,
-                                                udf13: None,
-                                                udf14: None,
-                                                udf15: None,
-                                                udf16: None,
-                                                udf17: None,
-                                                udf18: None,
-                                                udf19: None,
-                                                udf20: None,
-                                                udf21: None,
-                                                udf22: None,
-                                                udf23: None,
-                                                udf24: None,
-                                                udf25: None,
-                                                udf26: None,
-                                                udf27: None,
-                                                udf28: None,
-                                                udf29: None,
-                                            };
-
-                                            let _ =
-                                                client.update_device_udf(&device_uid, &udf).await;
+                                        self.tasks.spawn("store_sophos_endpoint_id", async move {
+                                            let update = UdfUpdate::default().set(30, endpoint_id);
+                                            let _ = client
+                                                .update_device_udf(&device_uid, &update)
+                                                .await;
                                         });
                                     }
                                 }
@@ -868,55 +1707,44 @@ impl App {
                         }
                     }
                     Err(e) => {
-                        let _ = std::fs::OpenOptions::new()
-                            .create(true)
-                            .append(true)
-                            .open("debug.log")
-                            .map(|mut f| {
-                                use std::io::Write;
-                                writeln!(f, "Error fetching Sophos endpoint for {}: {}", hostname, e).unwrap();
-                            });
+                        tracing::warn!(%hostname, error = %e, "failed to fetch Sophos endpoint");
                     }
                 }
+                self.run_queued_scan(&hostname, &tx);
             }
-            Event::SophosScanStarted(hostname, result) => {
-                match result {
-                    Ok(_) => {
-                        // Scan started logic: wait 2 seconds then update status
-                        let h = hostname.clone();
-                        let tx_clone = tx.clone();
-                        tokio::spawn(async move {
-                            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                            tx_clone
-                                .send(Event::ScanStatusChanged(
-                                    h,
-                                    crate::event::ScanStatus::Started,
-                                ))
-                                .unwrap();
-                        });
-                    }
-                    Err(e) => {
-                        self.scan_status.remove(&hostname);
-                        self.error = Some(format!("Failed to start scan for {}: {}", hostname, e));
+            Event::SophosScanStarted(id, result) => self.scan_started(id, result.map(|()| None)),
+            Event::SophosEndpointIsolated(hostname, result) => match result {
+                Ok(()) => {
+                    self.show_toast(format!("{} isolated in Sophos", hostname));
+                    // Reload the endpoint so the security panel shows the isolation
+                    if let Some(device) = self.device_detail.selected_device.clone()
+                        && device.hostname == hostname
+                    {
+                        self.fetch_device_security(&device, &tx);
                     }
                 }
-            }
+                Err(e) => self.report_api_error(
+                    "Sophos",
+                    Some(CurrentView::DeviceDetail),
+                    &format!("Failed to isolate {}", hostname),
+                    &e,
+                ),
+            },
             Event::DattoAvAgentFetched(hostname, result) => {
-                self.datto_av_loading.insert(hostname.clone(), false);
+                self.security.datto_av_loading.insert(hostname.clone(), false);
                 match result {
                     Ok(agent) => {
-                        self.datto_av_agents.insert(hostname.clone(), agent.clone());
+                        self.security.datto_av_agents.insert(hostname.clone(), agent.clone());
 
                         // Check/Update UDF 30 if needed
                         // We only update if we found it via hostname (implying we might not have had ID)
                         // OR just check if UDF 30 matches.
                         // Check/Update UDF 30 if needed
                         // First, find the index of the device to update to avoid borrow issues
-                        if let Some(dev_idx) =
-                            self.devices.iter().position(|d| d.hostname == hostname)
-                        {
-                            let device_uid = self.devices[dev_idx].uid.clone();
-                            let current_udf30 = self.devices[dev_idx]
+                        let devices = self.site_detail.devices.items();
+                        if let Some(dev_idx) = devices.iter().position(|d| d.hostname == hostname) {
+                            let device_uid = self.site_detail.devices.items()[dev_idx].uid.clone();
+                            let current_udf30 = self.site_detail.devices.items()[dev_idx]
                                 .udf
                                 .as_ref()
                                 .and_then(|u| u.udf30.as_ref())
@@ -927,16 +1755,17 @@ impl App {
                             if current_udf30 != agent.id {
                                 // Update UDF 30
                                 // Update local state immediately for responsiveness
-                                if let Some(udfs) = &mut self.devices[dev_idx].udf {
+                                let device = &mut self.site_detail.devices.items_mut()[dev_idx];
+                                if let Some(udfs) = &mut device.udf {
                                     udfs.udf30 = Some(agent.id.clone());
                                 } else {
                                     let mut new_udf = crate::api::datto::types::Udf::default();
                                     new_udf.udf30 = Some(agent.id.clone());
-                                    self.devices[dev_idx].udf = Some(new_udf);
+                                    device.udf = Some(new_udf);
                                 }
 
                                 // Also update selected device if it matches
-                                if let Some(sel) = &mut self.selected_device {
+                                if let Some(sel) = &mut self.device_detail.selected_device {
                                     if sel.uid == device_uid {
                                         if let Some(udfs) = &mut sel.udf {
                                             udfs.udf30 = Some(agent.id.clone());
@@ -952,40 +1781,10 @@ impl App {
                                 if let Some(client) = &self.client {
                                     let agent_id = agent.id.clone();
                                     let client = client.clone();
-                                    tokio::spawn(async move {
-                                        let udf = crate::api::datto::types::Udf {
-                                            udf30: Some(agent_id),
-                                            udf1: None,
-                                            udf2: None,
-                                            udf3: None,
-                                            udf4: None,
-                                            udf5: None,
-                                            udf6: None,
-                                            udf7: None,
-                                            udf8: None,
-                                            udf9: None,
-                                            udf10: None,
-                                            udf11: None,
-                                            udf12: None,
-                                            udf13: None,
-                                            udf14: None,
-                                            udf15: None,
-                                            udf16: None,
-                                            udf17: None,
-                                            udf18: None,
-                                            udf19: None,
-                                            udf20: None,
-                                            udf21: None,
-                                            udf22: None,
-                                            udf23: None,
-                                            udf24: None,
-                                            udf25: None,
-                                            udf26: None,
-                                            udf27: None,
-                                            udf28: None,
-                                            udf29: None,
-                                        };
-                                        let _ = client.update_device_udf(&device_uid, &udf).await;
+                                    self.tasks.spawn("store_datto_av_agent_id", async move {
+                                        let update = UdfUpdate::default().set(30, agent_id);
+                                        let _ =
+                                            client.update_device_udf(&device_uid, &update).await;
                                     });
                                 }
                             }
@@ -994,351 +1793,604 @@ impl App {
                         // Fetch alerts for this agent
                         self.fetch_datto_av_alerts(agent.id.clone(), hostname.clone(), tx.clone());
                         // Fetch policies for this agent
-                        self.fetch_datto_av_policies(agent.id.clone(), hostname, tx.clone());
+                        let agent_id = agent.id.clone();
+                        self.fetch_datto_av_policies(agent_id, hostname.clone(), tx.clone());
                     }
                     Err(e) => {
-                        let _ = std::fs::OpenOptions::new()
-                            .create(true)
-                            .append(true)
-                            .open("debug.log")
-                            .map(|mut f| {
-                                use std::io::Write;
-                                writeln!(f, "Error fetching Datto AV agent for {}: {}", hostname, e).unwrap();
-                            });
+                        tracing::warn!(%hostname, error = %e, "failed to fetch Datto AV agent");
                     }
                 }
+                self.run_queued_scan(&hostname, &tx);
             }
-            Event::DattoAvScanStarted(hostname, result) => {
+            Event::DattoAvScanStarted(id, result) => self.scan_started(id, result.map(|()| None)),
+            Event::DattoAvAlertsFetched(hostname, result) => match result {
+                Ok(alerts) => {
+                    self.security.datto_av_alerts.insert(hostname, alerts);
+                }
+                Err(_e) => {
+                    // Ignore error for now, or log it
+                }
+            },
+            Event::DattoAvPoliciesFetched(hostname, result) => match result {
+                Ok(policies) => {
+                    tracing::debug!(%hostname, ?policies, "fetched Datto AV policies");
+                    self.security.datto_av_policies.insert(hostname, policies);
+                }
+                Err(e) => {
+                    tracing::warn!(%hostname, error = %e, "failed to fetch Datto AV policies");
+                }
+            },
+            Event::DefenderMachinesFetched(hostname, result) => {
+                self.security.defender_loading.insert(hostname.clone(), false);
                 match result {
-                    Ok(_) => {
-                        // Scan started logic: wait 2 seconds then update status
-                        let h = hostname.clone();
-                        let tx_clone = tx.clone();
-                        tokio::spawn(async move {
-                            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                            tx_clone
-                                .send(Event::ScanStatusChanged(
-                                    h,
-                                    crate::event::ScanStatus::Started,
-                                ))
-                                .unwrap();
-                        });
+                    Ok(machines) => {
+                        if let Some(machine) = machines.into_iter().next() {
+                            let machine_id = machine.id.clone();
+                            self.security.defender_machines.insert(hostname.clone(), machine);
+                            self.fetch_defender_alerts(machine_id, hostname.clone(), tx.clone());
+                        }
                     }
                     Err(e) => {
-                        self.scan_status.remove(&hostname);
-                        self.error = Some(format!(
-                            "Failed to start Datto AV scan for {}: {}",
-                            hostname, e
-                        ));
+                        tracing::warn!(%hostname, error = %e, "failed to fetch Defender machine");
                     }
                 }
+                self.run_queued_scan(&hostname, &tx);
             }
-            Event::ScanStatusChanged(hostname, status) => {
-                self.scan_status.insert(hostname, status);
-            }
-            Event::DattoAvAlertsFetched(hostname, result) => match result {
+            Event::DefenderAlertsFetched(hostname, result) => match result {
                 Ok(alerts) => {
-                    self.datto_av_alerts.insert(hostname, alerts);
+                    self.security.defender_alerts.insert(hostname, alerts);
                 }
-                Err(_e) => {
-                    // Ignore error for now, or log it
+                Err(e) => {
+                    tracing::warn!(%hostname, error = %e, "failed to fetch Defender alerts");
                 }
             },
-            Event::DattoAvPoliciesFetched(hostname, result) => match result {
-                Ok(policies) => {
-                    // Log to debug.log
-                    let _ = std::fs::OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open("debug.log")
-                        .map(|mut f| {
-                            use std::io::Write;
-                            writeln!(f, "Policies for {}: {:#?}", hostname, policies).unwrap();
-                        });
-                    self.datto_av_policies.insert(hostname, policies);
+            Event::DefenderScanStarted(id, result) => {
+                self.scan_started(id, result.map(|action| Some(action.id)));
+            }
+            Event::DefenderScanPolled(id, result) | Event::SentinelOneScanPolled(id, result) => {
+                self.scan_polled(id, result, &tx);
+            }
+            Event::SentinelOneAgentFetched(hostname, result) => {
+                self.security.sentinelone_loading.insert(hostname.clone(), false);
+                match result {
+                    Ok(agent) => {
+                        let agent_id = agent.id.clone();
+                        self.security.sentinelone_agents.insert(hostname.clone(), agent);
+                        self.fetch_sentinelone_threats(agent_id, hostname.clone(), tx.clone());
+                    }
+                    Err(e) => {
+                        tracing::warn!(%hostname, error = %e, "failed to fetch SentinelOne agent");
+                    }
+                }
+                self.run_queued_scan(&hostname, &tx);
+            }
+            Event::SentinelOneThreatsFetched(hostname, result) => match result {
+                Ok(threats) => {
+                    self.security.sentinelone_threats.insert(hostname, threats);
                 }
                 Err(e) => {
-                    let _ = std::fs::OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open("debug.log")
-                        .map(|mut f| {
-                            use std::io::Write;
-                            writeln!(f, "Failed to fetch policies for {}: {}", hostname, e).unwrap();
-                        });
+                    tracing::warn!(%hostname, error = %e, "failed to fetch SentinelOne threats");
+                }
+            },
+            Event::SentinelOneScanStarted(id, result) => self.scan_started(id, result.map(Some)),
+            Event::SentinelOneAgentDisconnected(hostname, result) => match result {
+                Ok(()) => {
+                    self.show_toast(format!("{} disconnected in SentinelOne", hostname));
+                    // Reload the agent so the security panel shows its network status
+                    if let Some(device) = self.device_detail.selected_device.clone()
+                        && device.hostname == hostname
+                    {
+                        self.fetch_device_security(&device, &tx);
+                    }
                 }
+                Err(e) => self.report_api_error(
+                    "SentinelOne",
+                    Some(CurrentView::DeviceDetail),
+                    &format!("Failed to disconnect {}", hostname),
+                    &e,
+                ),
             },
-            Event::ActivityLogsFetched(result) => {
-                self.activity_logs_loading = false;
+            // Drop responses for a device we've since moved away from
+            Event::ActivityLogsFetched(generation, device_uid, _)
+                if !self.device_detail.fetches.is_current(generation)
+                    || !self.device_detail.is_selected(&device_uid) => {}
+            Event::ActivityLogsFetched(_, _, result) => {
+                self.device_detail.activity_logs_loading = false;
                 match result {
                     Ok(response) => {
-                        self.activity_logs = response.activities;
-                        if !self.activity_logs.is_empty() {
-                            self.activity_logs_table_state.select(Some(0));
-                        } else {
-                            self.activity_logs_table_state.select(None);
-                        }
+                        self.device_detail.activity_logs.set_items(response.activities);
+                        self.device_detail.sort_activity_logs();
+                        self.device_detail.activity_logs.select_first();
                     }
                     Err(e) => {
-                        self.activity_logs_error = Some(e);
+                        self.device_detail.activity_logs_error = Some(e.to_string());
+                    }
+                }
+            }
+            Event::ActivityLogsPolled(generation, device_uid, ..)
+                if !self.device_detail.fetches.is_current(generation)
+                    || !self.device_detail.is_selected(&device_uid) => {}
+            Event::ActivityLogsPolled(_, device_uid, until, result) => {
+                self.device_detail.activity_polling = false;
+                match result {
+                    Ok(response) => {
+                        self.device_detail.activity_synced_at = Some(until);
+                        let added = self.device_detail.merge_new_activities(response.activities);
+                        if added > 0 {
+                            tracing::debug!(%device_uid, added, "new activities");
+                        }
                     }
+                    // The next poll covers the same range again
+                    Err(e) => tracing::warn!(%device_uid, error = %e, "live activity fetch failed"),
                 }
             }
-            Event::OpenAlertsFetched(device_uid, result) => {
+            Event::OpenAlertsFetched(generation, device_uid, result) => {
+                if let Ok(alerts) = &result {
+                    self.notifications.observe_alerts(alerts, false);
+                    self.save_seen_items();
+                }
                 // Ensure the result corresponds to the currently selected device
-                if let Some(device) = &self.selected_device {
-                    if device.uid == device_uid {
-                        self.open_alerts_loading = false;
+                if let Some(device) = &self.device_detail.selected_device {
+                    if device.uid == device_uid
+                        && self.device_detail.fetches.is_current(generation)
+                    {
+                        self.device_detail.open_alerts_loading = false;
                         match result {
                             Ok(alerts) => {
-                                // Debug log
-                                let _ = std::fs::OpenOptions::new()
-                                    .create(true)
-                                    .append(true)
-                                    .open("debug.log")
-                                    .map(|mut f| {
-                                        use std::io::Write;
-                                        writeln!(f, "Fetched {} alerts for device {}", alerts.len(), device_uid).unwrap();
-                                        writeln!(f, "Alerts Data: {:#?}", alerts).unwrap();
-                                    });
+                                tracing::debug!(%device_uid, count = alerts.len(), "fetched open alerts");
 
-                                self.open_alerts = alerts;
-                                if !self.open_alerts.is_empty() {
-                                    self.open_alerts_table_state.select(Some(0));
-                                } else {
-                                    self.open_alerts_table_state.select(None);
-                                }
+                                self.device_detail.open_alerts.set_items(alerts);
+                                self.device_detail.sort_open_alerts();
+                                self.device_detail.open_alerts.select_first();
                             }
                             Err(e) => {
-                                // Debug log error
-                                let _ = std::fs::OpenOptions::new()
-                                    .create(true)
-                                    .append(true)
-                                    .open("debug.log")
-                                    .map(|mut f| {
-                                        use std::io::Write;
-                                        writeln!(f, "Error fetching alerts for {}: {}", device_uid, e).unwrap();
-                                    });
-                                self.open_alerts_error = Some(e);
+                                tracing::warn!(%device_uid, error = %e, "failed to fetch open alerts");
+                                self.device_detail.open_alerts_error = Some(e.to_string());
                             }
                         }
                     }
                 }
             }
-            Event::SiteOpenAlertsFetched(site_uid, result) => {
-                if let Some(idx) = self.table_state.selected() {
-                    if let Some(site) = self.sites.get(idx) {
-                        if site.uid == site_uid {
-                            self.site_open_alerts_loading = false;
-                            match result {
-                                Ok(alerts) => {
-                                    self.site_open_alerts = alerts;
-                                    if !self.site_open_alerts.is_empty() {
-                                        self.site_open_alerts_table_state.select(Some(0));
-                                    } else {
-                                        self.site_open_alerts_table_state.select(None);
-                                    }
-                                }
-                                Err(e) => {
-                                    self.site_open_alerts_error = Some(e);
-                                }
-                            }
+            Event::ResolvedAlertsFetched(generation, device_uid, result) => {
+                if self.device_detail.is_selected(&device_uid)
+                    && self.device_detail.fetches.is_current(generation)
+                {
+                    self.device_detail.resolved_alerts_loading = false;
+                    match result {
+                        Ok(alerts) => {
+                            let count = alerts.len();
+                            tracing::debug!(%device_uid, count, "fetched resolved alerts");
+                            self.device_detail.resolved_alerts.set_items(alerts);
+                            self.device_detail.sort_resolved_alerts();
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                %device_uid, error = %e, "failed to fetch resolved alerts"
+                            );
+                            self.device_detail.resolved_alerts_error = Some(e.to_string());
                         }
                     }
                 }
             }
-            Event::JobResultFetched(result) => {
-                self.job_result_loading = false;
-                match result {
-                    Ok(job_result) => {
-                        self.selected_job_result = Some(job_result);
-                    }
-                    Err(e) => {
-                        self.job_result_error = Some(e);
-                    }
+            Event::SiteOpenAlertsFetched(generation, site_uid, result) => {
+                if let Ok(alerts) = &result {
+                    self.notifications.observe_alerts(alerts, false);
+                    self.save_seen_items();
+                    self.site_list.set_site_open_alerts(&site_uid, alerts);
                 }
-            }
-            Event::JobStdOutFetched(result) => {
-                self.popup_loading = false;
-                match result {
-                    Ok(outputs) => {
-                        // Find the output for the selected component (derived from selected row)
-                        if let Some(job_result) = &self.selected_job_result {
-                            let rows = generate_job_rows(job_result);
-                            if let Some(row) = rows.get(self.selected_job_row_index) {
-                                let comp_idx = match row {
-                                    JobViewRow::ComponentHeader(i)
-                                    | JobViewRow::StdOutLink(i)
-                                    | JobViewRow::StdErrLink(i) => *i,
-                                };
-
-                                if let Some(components) = &job_result.component_results {
-                                    if let Some(selected_comp) = components.get(comp_idx) {
-                                        if let Some(comp_uid) = &selected_comp.component_uid {
-                                            if let Some(output) = outputs
-                                                .iter()
-                                                .find(|o| o.component_uid.as_ref() == Some(comp_uid))
-                                            {
-                                                self.popup_content = output
-                                                    .std_data
-                                                    .clone()
-                                                    .unwrap_or_else(|| "No StdOut data".to_string());
-                                            } else {
-                                                self.popup_content =
-                                                    "No StdOut found for this component".to_string();
-                                            }
-                                        } else {
-                                            self.popup_content = "Component UID missing".to_string();
-                                        }
-                                    }
-                                }
+                if let Some(site) = self.site_list.selected_site() {
+                    if site.uid == site_uid && self.site_detail.fetches.is_current(generation) {
+                        self.site_detail.site_open_alerts_loading = false;
+                        match result {
+                            Ok(alerts) => {
+                                self.site_detail.site_open_alerts.set_items(alerts);
+                                self.site_detail.sort_site_alerts();
+                                self.site_detail.site_open_alerts.select_first();
+                            }
+                            Err(e) => {
+                                self.site_detail.site_open_alerts_error = Some(e.to_string());
                             }
                         }
                     }
-                    Err(e) => {
-                        self.popup_content = format!("Error: {}", e);
-                    }
                 }
             }
-            Event::JobStdErrFetched(result) => {
-                self.popup_loading = false;
+            Event::MergedSitesFetched(profile, result) => match result {
+                Ok(sites) => {
+                    tracing::debug!(%profile, count = sites.len(), "fetched merged account sites");
+                    self.site_list.set_account_sites(&profile, sites);
+                    self.sort_sites();
+                }
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::List),
+                    &format!("Failed to fetch sites of account '{}'", profile),
+                    &e,
+                ),
+            },
+            Event::AccountAlertsFetched(result) => match result {
+                Ok(alerts) => {
+                    tracing::debug!(count = alerts.len(), "fetched account open alerts");
+                    self.notifications.observe_alerts(&alerts, true);
+                    self.save_seen_items();
+                    self.site_list.set_open_alerts(&alerts);
+                    self.sort_sites();
+                    self.record_site_history();
+                }
+                // Shown on the Integrations view; the bell just stays as it was
+                Err(e) => tracing::warn!(error = %e, "failed to fetch account open alerts"),
+            },
+            Event::JobResultFetched(generation, _)
+                if !self.device_detail.fetches.is_current(generation) => {}
+            Event::JobResultFetched(_, result) => {
+                self.device_detail.job_result_loading = false;
                 match result {
-                    Ok(outputs) => {
-                        if let Some(job_result) = &self.selected_job_result {
-                            let rows = generate_job_rows(job_result);
-                            if let Some(row) = rows.get(self.selected_job_row_index) {
-                                let comp_idx = match row {
-                                    JobViewRow::ComponentHeader(i)
-                                    | JobViewRow::StdOutLink(i)
-                                    | JobViewRow::StdErrLink(i) => *i,
-                                };
-
-                                if let Some(components) = &job_result.component_results {
-                                    if let Some(selected_comp) = components.get(comp_idx) {
-                                        if let Some(comp_uid) = &selected_comp.component_uid {
-                                            if let Some(output) = outputs
-                                                .iter()
-                                                .find(|o| o.component_uid.as_ref() == Some(comp_uid))
-                                            {
-                                                self.popup_content = output
-                                                    .std_data
-                                                    .clone()
-                                                    .unwrap_or_else(|| "No StdErr data".to_string());
-                                            } else {
-                                                self.popup_content =
-                                                    "No StdErr found for this component".to_string();
-                                            }
-                                        } else {
-                                            self.popup_content = "Component UID missing".to_string();
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                    Ok(job_result) => {
+                        self.device_detail.selected_job_result = Some(job_result);
                     }
                     Err(e) => {
-                        self.popup_content = format!("Error: {}", e);
+                        self.device_detail.job_result_error = Some(e.to_string());
                     }
                 }
             }
+            Event::JobStdOutFetched(component_uid, result) => {
+                self.show_job_output(&component_uid, result, "StdOut");
+            }
+            Event::JobStdErrFetched(component_uid, result) => {
+                self.show_job_output(&component_uid, result, "StdErr");
+            }
+            Event::JobCancelled(job_uid, result) => match result {
+                Ok(()) => {
+                    self.show_toast("Job cancelled".to_string());
+                    self.refresh_cancelled_job(job_uid, tx);
+                }
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::ActivityDetail),
+                    "Failed to cancel the job",
+                    &e,
+                ),
+            },
+            Event::JobComponentsFetched(job_uid, result) => match result {
+                Ok(components) => self.confirm_job_rerun(&job_uid, components, tx),
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::ActivityDetail),
+                    "Failed to fetch the job's components",
+                    &e,
+                ),
+            },
             Event::ComponentsFetched(result) => {
-                self.components_loading = false;
+                self.popups.components_loading = false;
+                self.components.loading = false;
                 match result {
                     Ok(response) => {
-                        self.components = response.components;
+                        self.components.set_components(response.components.clone());
+                        self.popups.components = response.components;
                         // Sort by name
-                        self.components
+                        self.popups.components
                             .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                        
-                        // Initial filter (all)
-                        self.filtered_components = self.components.clone();
-                        
-                        if !self.filtered_components.is_empty() {
-                            self.component_list_state.select(Some(0));
-                        } else {
-                            self.component_list_state.select(None);
-                        }
+
+                        // Initial filter (all), starred and recent first
+                        self.filter_components();
                     }
                     Err(e) => {
-                        self.component_error = Some(e);
+                        self.components.error = Some(e.to_string());
+                        self.popups.component_error = Some(e.to_string());
                     }
                 }
             }
             Event::QuickJobExecuted(result) => {
-                self.popup_loading = false;
+                self.popups.popup_loading = false;
+                self.popups.components_loading = false;
                 match result {
                     Ok(resp) => {
-                        self.last_job_response = Some(resp);
-                        self.run_component_step = RunComponentStep::Result;
+                        // Follow the new job until it finishes
+                        let job_uid = resp.job.as_ref().and_then(|j| j.uid.clone());
+                        if let (Some(job_uid), Some(device)) = (job_uid, &self.device_detail.selected_device) {
+                            self.popups.job_poll = Some(JobPoll::new(job_uid, device.uid.clone()));
+                        }
+                        self.popups.last_job_response = Some(resp);
+                        self.popups.run_component_step = RunComponentStep::Result;
                     }
                     Err(e) => {
-                        self.component_error = Some(e);
+                        self.popups.component_error = Some(e.to_string());
                     }
                 }
             }
-            Event::WarrantyUpdated(result) => {
-                self.is_loading = false;
-                match result {
-                    Ok(_) => {
-                        // Refresh device data
-                        if let Some(mut device) = self.selected_device.clone() {
-                            let site_uid = device.site_uid.clone();
-                            let year = &self.warranty_segments[0];
-                            let month = &self.warranty_segments[1];
-                            let day = &self.warranty_segments[2];
-                            if year.is_empty() && month.is_empty() && day.is_empty() {
-                                device.warranty_date = None;
-                            } else {
-                                device.warranty_date = Some(format!("{}-{}-{}", year, month, day));
+            Event::JobStatusPolled(job_uid, result) => {
+                if let Some(poll) = self.popups.job_poll.as_mut()
+                    && poll.job_uid == job_uid
+                {
+                    poll.update(result);
+                }
+            }
+            Event::JobOutputFollowed(job_uid, result) => {
+                if let Some(follow) = self.popups.job_follow.as_mut()
+                    && follow.poll.job_uid == job_uid
+                {
+                    self.popups.popup_loading = false;
+                    match result {
+                        Ok((job_result, outputs)) => {
+                            let output = outputs
+                                .iter()
+                                .find(|o| o.component_uid.as_deref() == Some(follow.component_uid.as_str()))
+                                .and_then(|o| o.std_data.as_deref());
+                            match output {
+                                Some(data) => append_new_output(&mut self.popups.popup_content, data),
+                                None if self.popups.popup_content == "Loading..." => {
+                                    self.popups.popup_content = "Waiting for output...".to_string();
+                                }
+                                None => {}
                             }
-                            self.selected_device = Some(device);
-                            self.fetch_devices(site_uid, tx.clone());
+                            follow.poll.update(Ok(job_result.clone()));
+                            // Keep the activity's component statuses current too
+                            if self.device_detail.selected_job_result.as_ref().and_then(|r| r.job_uid.as_deref())
+                                == Some(job_uid.as_str())
+                            {
+                                self.device_detail.selected_job_result = Some(job_result);
+                            }
+                        }
+                        Err(e) => follow.poll.update(Err(e)),
+                    }
+                }
+            }
+            Event::DeviceUdfUpdated(hostname, result) => {
+                if let Err(e) = result {
+                    self.report_api_error(
+                        "Datto",
+                        None,
+                        &format!("Failed to update UDF on {}", hostname),
+                        &e,
+                    );
+                }
+            }
+            Event::RebootScheduled(result) => {
+                self.popups.reboot_submitting = false;
+                match result {
+                    Ok(job_uid) => {
+                        tracing::info!(%job_uid, "reboot job created");
+                        self.popups.reboot_confirmation = Some(format!("Reboot job created: {}", job_uid));
+                    }
+                    Err(e) => self.popups.reboot_error = Some(format!("Failed to schedule reboot: {}", e)),
+                }
+            }
+            Event::CollectionActionDone(id, hostname, result) => {
+                self.collection_result(id, &hostname, result.map_err(|e| e.to_string()));
+            }
+            Event::WarrantyUpdated(device_uid, result) => {
+                self.site_list.is_loading = false;
+                match result {
+                    Ok(date) => {
+                        let devices = self
+                            .device_detail
+                            .selected_device
+                            .iter_mut()
+                            .chain(self.site_detail.devices.items_mut().iter_mut())
+                            .chain(self.report.devices.iter_mut());
+                        for device in devices.filter(|d| d.uid == device_uid) {
+                            device.warranty_date = date.clone();
                         }
+                        // The warranty report lists devices by their date
+                        self.report.rebuild();
+                        self.show_toast(match date {
+                            Some(date) => format!("Warranty set to {}", date),
+                            None => "Warranty cleared".to_string(),
+                        });
                     }
                     Err(e) => {
-                        self.error = Some(format!("Failed to update warranty: {}", e));
+                        self.report_api_error(
+                            "Datto",
+                            Some(CurrentView::DeviceDetail),
+                            "Failed to update warranty",
+                            &e,
+                        );
+                    }
+                }
+            }
+            Event::DeviceUpdated(device_uid, result) => match result {
+                Ok(req) => {
+                    let devices = self
+                        .device_detail
+                        .selected_device
+                        .iter_mut()
+                        .chain(self.site_detail.devices.items_mut().iter_mut());
+                    for device in devices.filter(|d| d.uid == device_uid) {
+                        if let Some(description) = &req.description {
+                            device.description = Some(description.clone());
+                        }
+                        if let Some(name) = &req.display_name {
+                            device.display_name = Some(name.clone());
+                        }
+                    }
+                    self.show_toast("Device updated".to_string());
+                }
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::DeviceDetail),
+                    "Failed to update device",
+                    &e,
+                ),
+            },
+            Event::AlertResolved(alert_uid, result) => match result {
+                Ok(()) => {
+                    let is_resolved =
+                        |a: &Alert| a.alert_uid.as_deref() == Some(alert_uid.as_str());
+                    self.device_detail.open_alerts.retain(|a| !is_resolved(a));
+                    self.site_detail.site_open_alerts.retain(|a| !is_resolved(a));
+                    if self.popups.alert_detail.as_ref().is_some_and(|d| is_resolved(&d.alert)) {
+                        self.popups.alert_detail = None;
+                    }
+                    self.show_toast("Alert resolved".to_string());
+                }
+                Err(e) => self.report_api_error(
+                    "Datto",
+                    Some(CurrentView::DeviceDetail),
+                    "Failed to resolve alert",
+                    &e,
+                ),
+            },
+            Event::DeviceDecommissioned(device_uid, result) => match result {
+                Ok(()) => {
+                    let hostname = self
+                        .popups
+                        .decommission
+                        .take()
+                        .map(|d| d.hostname)
+                        .unwrap_or_default();
+                    self.site_detail.devices.retain(|d| d.uid != device_uid);
+                    let open = self.device_detail.selected_device.as_ref();
+                    if self.current_view == CurrentView::DeviceDetail
+                        && open.is_some_and(|d| d.uid == device_uid)
+                    {
+                        self.dispatch(action::Action::Back, tx.clone());
+                    }
+                    self.show_toast(format!("{} removed from RMM", hostname));
+                }
+                Err(e) => {
+                    if let Some(decommission) = &mut self.popups.decommission {
+                        decommission.running = false;
+                    }
+                    self.report_api_error(
+                        "Datto",
+                        Some(CurrentView::DeviceDetail),
+                        "Failed to decommission device",
+                        &e,
+                    );
+                }
+            },
+            Event::SiteDeleted(site_uid, result) => match result {
+                Ok(()) => {
+                    let name = self
+                        .popups
+                        .site_deletion
+                        .take()
+                        .map(|d| d.name)
+                        .unwrap_or_default();
+                    if self.site_list.remove_site(&site_uid) {
+                        self.cache_put(cache::PINNED_SITES_KEY, &self.site_list.pinned);
+                    }
+                    if self.current_view == CurrentView::Detail {
+                        self.current_view = CurrentView::List;
+                    }
+                    self.show_toast(format!("{} deleted", name));
+                }
+                Err(e) => {
+                    if let Some(deletion) = &mut self.popups.site_deletion {
+                        deletion.running = false;
+                    }
+                    self.report_api_error(
+                        "Datto",
+                        Some(CurrentView::Detail),
+                        "Failed to delete site",
+                        &e,
+                    );
+                }
+            },
+            Event::NetToolFinished(label, outcome) => {
+                // Results of a closed popup are dropped
+                if let Some(state) = &mut self.popups.net_tools {
+                    state.running = state.running.saturating_sub(1);
+                    state.results.push(NetResult { label, outcome });
+                }
+            }
+            Event::WatchedDevicePolled(device_uid, result) => {
+                let online = result.as_ref().ok().map(|d| d.online);
+                if let Err(e) = &result {
+                    tracing::warn!(%device_uid, error = %e, "failed to poll watched device");
+                }
+                if let Some(device) = self.device_watch.record(&device_uid, online) {
+                    let status = if device.online { "back online" } else { "offline" };
+                    let message = format!("{} is {}", device.hostname, status);
+                    tracing::info!(
+                        hostname = %device.hostname,
+                        online = device.online,
+                        "watched device changed state"
+                    );
+                    desktop_notification("Watched device", &message);
+                    self.show_toast(message);
+                }
+                if let Ok(polled) = result {
+                    let devices = self
+                        .device_detail
+                        .selected_device
+                        .iter_mut()
+                        .chain(self.site_detail.devices.items_mut().iter_mut());
+                    for device in devices.filter(|d| d.uid == device_uid) {
+                        device.online = polled.online;
+                        device.last_seen = polled.last_seen.clone();
                     }
                 }
             }
+            Event::SophosEndpointDeleted(hostname, result) => {
+                self.security.sophos_endpoints.remove(&hostname);
+                match result {
+                    Ok(()) => self.show_toast(format!("{} removed from Sophos", hostname)),
+                    Err(e) => self.report_api_error(
+                        "Sophos",
+                        None,
+                        &format!("Failed to remove {} from Sophos", hostname),
+                        &e,
+                    ),
+                }
+            }
+            Event::DattoAvAgentDeleted(hostname, result) => {
+                self.security.datto_av_agents.remove(&hostname);
+                match result {
+                    Ok(()) => self.show_toast(format!("{} removed from Datto AV", hostname)),
+                    Err(e) => self.report_api_error(
+                        "Datto AV",
+                        None,
+                        &format!("Failed to remove {} from Datto AV", hostname),
+                        &e,
+                    ),
+                }
+            }
             Event::DeviceMoved(result) => {
-                self.is_loading = false;
+                self.site_list.is_loading = false;
                 match result {
                     Ok(_) => {
                         // Refresh data
-                        if let Some(device) = self.selected_device.clone() {
+                        if let Some(device) = self.device_detail.selected_device.clone() {
                             let site_uid = device.site_uid.clone();
                             self.fetch_devices(site_uid, tx.clone());
                         }
                     }
                     Err(e) => {
-                        self.error = Some(format!("Failed to move device: {}", e));
+                        self.report_api_error(
+                            "Datto",
+                            Some(CurrentView::DeviceDetail),
+                            "Failed to move device",
+                            &e,
+                        );
                     }
                 }
             }
             Event::RocketCyberAgentFetched(hostname, result) => {
 
-                self.rocket_loading.insert(hostname.clone(), false);
+                self.security.rocket_loading.insert(hostname.clone(), false);
                 match result {
                     Ok(Some(agent)) => {
-                        self.rocket_agents.insert(hostname, agent);
+                        self.security.rocket_agents.insert(hostname, agent);
                     }
                     Ok(None) => {}
                     Err(_) => {}
                 }
             }
-            Event::DeviceSoftwareFetched(device_uid, result) => {
-                if let Some(device) = &self.selected_device {
-                    if device.uid == device_uid {
-                        self.device_software_loading = false;
+            Event::DeviceSoftwareFetched(generation, device_uid, result) => {
+                if let Some(device) = &self.device_detail.selected_device {
+                    if device.uid == device_uid
+                        && self.device_detail.fetches.is_current(generation)
+                    {
+                        self.device_detail.device_software_loading = false;
                         match result {
                             Ok(mut software) => {
                                 // Sort by name
                                 software.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                                self.device_software = software;
-                                self.filter_software();
+                                self.device_detail.device_software.set_items(software);
+                                self.device_detail.filter_software();
                             }
                             Err(e) => {
-                                self.device_software_error = Some(e);
+                                self.device_detail.device_software_error = Some(e.to_string());
                             }
                         }
                     }
@@ -1350,190 +2402,388 @@ impl App {
 
     fn fetch_components(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
         if let Some(client) = &self.client {
-            self.components_loading = true;
+            self.popups.components_loading = true;
+            self.components.loading = true;
             let client = client.clone();
-            tokio::spawn(async move {
-                let result = client.get_components(Some(0)).await.map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::ComponentsFetched(result)).unwrap();
+            let on_panic = |e| Event::ComponentsFetched(Err(e));
+            self.tasks.spawn_fetch("fetch_components", on_panic, async move {
+                let result = client.get_components(Some(0)).await.map_err(ApiError::from);
+                let _ = tx.send(Event::ComponentsFetched(result));
             });
         }
     }
 
-    fn run_component_job(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(client) = &self.client {
-            if let Some(device) = &self.selected_device {
-                if let Some(component) = &self.selected_component {
-                    self.components_loading = true;
-                    self.component_error = None;
-                    
-                    let client = client.clone();
-                    let device_uid = device.uid.clone();
-                    let req = QuickJobRequest {
-                        job_name: format!("Run Component: {}", component.name),
-                        job_component: QuickJobComponent {
-                            component_uid: component.uid.clone(),
-                            variables: self.component_variables.clone(),
-                        },
-                    };
+    /// The quick job and history entry for the component and variables chosen
+    /// in the Run Component popup.
+    fn component_job(&self) -> Option<(QuickJobRequest, ComponentRun)> {
+        let component = self.popups.selected_component.as_ref()?;
+        let variables = self.popups.component_variables.clone();
+        let run = ComponentRun {
+            uid: component.uid.clone(),
+            name: component.name.clone(),
+            variables: variables.clone(),
+            ran_at: chrono::Utc::now().timestamp(),
+        };
+        let req = QuickJobRequest {
+            job_name: format!("Run Component: {}", component.name),
+            job_component: QuickJobComponent { component_uid: component.uid.clone(), variables },
+        };
+        Some((req, run))
+    }
 
-                    tokio::spawn(async move {
-                        let result = client.run_quick_job(&device_uid, req).await.map_err(|e| format!("{:#}", e));
-                        tx.send(Event::QuickJobExecuted(result)).unwrap();
-                    });
-                }
+    /// Runs the chosen component on the open device, or asks to run it on
+    /// the collection the popup was opened for.
+    fn run_component_job(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some((req, run)) = self.component_job() else {
+            return;
+        };
+        let device_uid = self.device_detail.selected_device.as_ref().map(|d| d.uid.clone());
+        match (self.popups.collection_target.clone(), self.client.clone(), device_uid) {
+            (Some(collection), _, _) => {
+                self.confirm_collection_run(collection, CollectionAction::RunComponent(req), tx);
+            }
+            (None, Some(client), Some(device_uid)) => {
+                self.popups.components_loading = true;
+                self.popups.component_error = None;
+                self.popups.job_poll = None;
+
+                self.tasks.spawn("run_component_job", async move {
+                    let result = client.run_quick_job(&device_uid, req).await;
+                    let _ = tx.send(Event::QuickJobExecuted(result.map_err(ApiError::from)));
+                });
             }
+            _ => return,
         }
+        self.components.record_run(run);
+        self.cache_put(cache::COMPONENT_HISTORY_KEY, &self.components.history);
     }
 
     fn filter_components(&mut self) {
-        if self.component_search_query.is_empty() {
-            self.filtered_components = self.components.clone();
+        if self.popups.component_search_query.is_empty() {
+            self.popups.filtered_components = self.popups.components.clone();
         } else {
-            let query = self.component_search_query.to_lowercase();
-            self.filtered_components = self.components
+            let query = self.popups.component_search_query.to_lowercase();
+            self.popups.filtered_components = self.popups.components
                 .iter()
                 .filter(|c| c.name.to_lowercase().contains(&query))
                 .cloned()
                 .collect();
         }
-        
+        self.components.rank(&mut self.popups.filtered_components);
+
         // Reset selection
-        if !self.filtered_components.is_empty() {
-            self.component_list_state.select(Some(0));
+        if !self.popups.filtered_components.is_empty() {
+            self.popups.component_list_state.select(Some(0));
         } else {
-            self.component_list_state.select(None);
+            self.popups.component_list_state.select(None);
         }
     }
 
-    fn filter_software(&mut self) {
-        if self.software_search_query.is_empty() {
-            self.filtered_software = self.device_software.clone();
+    fn selected_popup_component(&self) -> Option<Component> {
+        self.popups
+            .component_list_state
+            .selected()
+            .and_then(|i| self.popups.filtered_components.get(i))
+            .cloned()
+    }
+
+    fn toggle_component_star(&mut self, uid: &str) {
+        self.components.toggle_star(uid);
+        self.cache_put(cache::STARRED_COMPONENTS_KEY, &self.components.starred);
+    }
+
+    /// Moves the Run Component popup past the search step with `comp` chosen.
+    fn select_component(&mut self, comp: Component) {
+        // Prepare variables
+        self.popups.component_variables.clear();
+
+        if let Some(vars) = &comp.variables {
+            // Sort by variablesIdx if possible
+            let mut sorted_vars = vars.clone();
+            sorted_vars.sort_by_key(|v| v.variables_idx.unwrap_or(0));
+
+            // Values from the last run of this component win over the defaults
+            let last_run = self.components.last_run(&comp.uid);
+            for var in sorted_vars {
+                let previous = last_run
+                    .and_then(|run| run.variables.iter().find(|v| v.name == var.name))
+                    .map(|v| v.value.clone());
+                self.popups.component_variables.push(QuickJobVariable {
+                    name: var.name.clone(),
+                    value: previous.or(var.default_val.clone()).unwrap_or_default(),
+                });
+            }
+        }
+        self.popups.selected_component = Some(comp);
+
+        if self.popups.component_variables.is_empty() {
+            self.popups.run_component_step = RunComponentStep::Review;
         } else {
-            let query = self.software_search_query.to_lowercase();
-            self.filtered_software = self.device_software
-                .iter()
-                .filter(|s| {
-                    s.name.to_lowercase().contains(&query) || 
-                    s.version.to_lowercase().contains(&query)
-                })
-                .cloned()
-                .collect();
+            self.popups.run_component_step = RunComponentStep::FillVariables;
+            self.load_component_variable(0);
         }
-        
-        // Reset selection
-        if !self.filtered_software.is_empty() {
-            self.device_software_table_state.select(Some(0));
+    }
+
+    /// Definition of the variable shown in the FillVariables step.
+    fn current_variable_definition(&self) -> Option<&ComponentVariable> {
+        let var = self
+            .popups
+            .component_variables
+            .get(self.popups.component_variable_index)?;
+        self.popups
+            .selected_component
+            .as_ref()
+            .and_then(|c| c.variables.as_ref())
+            .and_then(|defs| defs.iter().find(|d| d.name == var.name))
+    }
+
+    /// Allowed values of the variable shown in the FillVariables step, empty
+    /// unless it is a selection variable.
+    pub fn current_variable_choices(&self) -> Vec<VariableChoice> {
+        self.current_variable_definition()
+            .map(variable_choices)
+            .unwrap_or_default()
+    }
+
+    pub fn current_variable_kind(&self) -> VariableKind {
+        self.current_variable_definition()
+            .map_or(VariableKind::Text, variable_kind)
+    }
+
+    /// Shows variable `index` in the FillVariables step, setting up the input
+    /// for its type: the cursor of a selection goes to the current value (or
+    /// the first allowed one), booleans become true/false and dates are split
+    /// into segments.
+    fn load_component_variable(&mut self, index: usize) {
+        self.popups.component_variable_index = index;
+        self.popups.component_variable_error = None;
+        let value = self
+            .popups
+            .component_variables
+            .get(index)
+            .map(|v| v.value.clone())
+            .unwrap_or_default();
+        let choices = self.current_variable_choices();
+        let position = choices.iter().position(|c| c.value == value);
+        self.popups.component_variable_input = match self.current_variable_kind() {
+            VariableKind::Selection => match (position, choices.first()) {
+                (None, Some(first)) => first.value.clone(),
+                _ => value,
+            },
+            VariableKind::Boolean => {
+                let on = matches!(value.to_lowercase().as_str(), "true" | "1" | "yes");
+                on.to_string()
+            }
+            VariableKind::Date => {
+                self.popups.component_date_segments = date_segments(&value);
+                self.popups.component_date_focus = WarrantyFocus::Year;
+                value
+            }
+            VariableKind::Text | VariableKind::Integer => value,
+        };
+        self.popups
+            .component_choice_state
+            .select(if choices.is_empty() { None } else { Some(position.unwrap_or(0)) });
+    }
+
+    /// Rebuilds the value of a date variable from its segments.
+    fn sync_component_date(&mut self) {
+        let segments = &self.popups.component_date_segments;
+        self.popups.component_variable_input = if segments.iter().all(String::is_empty) {
+            String::new()
         } else {
-            self.device_software_table_state.select(None);
+            segments.join("-")
+        };
+    }
+
+    /// Moves on to the Review step, or back to the first variable whose value
+    /// isn't allowed.
+    fn review_component_run(&mut self) {
+        let definitions = self
+            .popups
+            .selected_component
+            .as_ref()
+            .and_then(|c| c.variables.clone())
+            .unwrap_or_default();
+        match invalid_variable(&definitions, &self.popups.component_variables) {
+            Some((index, message)) => {
+                self.popups.run_component_step = RunComponentStep::FillVariables;
+                self.load_component_variable(index);
+                self.popups.component_variable_error = Some(message);
+            }
+            None => self.popups.run_component_step = RunComponentStep::Review,
         }
     }
 
+    /// Opens the Run Component popup on the open device with `comp` already
+    /// chosen, as when started from the components view.
+    fn start_component_run(&mut self, comp: Component) {
+        self.popups.open(Popup::RunComponent);
+        self.popups.component_error = None;
+        self.popups.component_search_query.clear();
+        // Going back from the variables lands on the full list
+        self.popups.components = self.components.components.clone();
+        self.filter_components();
+        self.popups.component_list_state.select(
+            self.popups.filtered_components.iter().position(|c| c.uid == comp.uid),
+        );
+        self.select_component(comp);
+    }
+
     fn handle_run_component_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        match self.run_component_step {
+        match self.popups.run_component_step {
             RunComponentStep::Search => {
                 match key.code {
                     KeyCode::Esc => {
-                        self.show_run_component = false;
+                        self.popups.close(Popup::RunComponent);
                     }
                     KeyCode::Down | KeyCode::Char('j') => {
-                        if let Some(i) = self.component_list_state.selected() {
-                            let next = if i >= self.filtered_components.len().saturating_sub(1) {
+                        if let Some(i) = self.popups.component_list_state.selected() {
+                            let next = if i >= self.popups.filtered_components.len().saturating_sub(1) {
                                 0
                             } else {
                                 i + 1
                             };
-                            self.component_list_state.select(Some(next));
+                            self.popups.component_list_state.select(Some(next));
                         }
                     }
                     KeyCode::Up | KeyCode::Char('k') => {
-                        if let Some(i) = self.component_list_state.selected() {
+                        if let Some(i) = self.popups.component_list_state.selected() {
                             let next = if i == 0 {
-                                self.filtered_components.len().saturating_sub(1)
+                                self.popups.filtered_components.len().saturating_sub(1)
                             } else {
                                 i - 1
                             };
-                            self.component_list_state.select(Some(next));
+                            self.popups.component_list_state.select(Some(next));
                         }
                     }
                     KeyCode::Enter => {
-                        if let Some(i) = self.component_list_state.selected() {
-                            if let Some(comp) = self.filtered_components.get(i) {
-                                self.selected_component = Some(comp.clone());
-                                // Prepare variables
-                                self.component_variables.clear();
-                                
-                                if let Some(vars) = &comp.variables {
-                                    // Sort by variablesIdx if possible
-                                    let mut sorted_vars = vars.clone();
-                                    sorted_vars.sort_by_key(|v| v.variables_idx.unwrap_or(0));
-                                    
-                                    for var in sorted_vars {
-                                        self.component_variables.push(QuickJobVariable {
-                                            name: var.name.clone(),
-                                            value: var.default_val.clone().unwrap_or_default(),
-                                        });
-                                    }
-                                }
-
-                                if self.component_variables.is_empty() {
-                                    self.run_component_step = RunComponentStep::Review;
-                                } else {
-                                    self.run_component_step = RunComponentStep::FillVariables;
-                                    self.component_variable_index = 0;
-                                    // Initialize input buffer with first variable's default
-                                    self.component_variable_input = self.component_variables[0].value.clone();
-                                }
-                            }
+                        if let Some(comp) = self.selected_popup_component() {
+                            self.select_component(comp);
+                        }
+                    }
+                    // Re-run with the previous inputs, straight to the review
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        if let Some(comp) = self.selected_popup_component()
+                            && self.components.last_run(&comp.uid).is_some()
+                        {
+                            self.select_component(comp);
+                            self.review_component_run();
+                        }
+                    }
+                    KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        if let Some(comp) = self.selected_popup_component() {
+                            self.toggle_component_star(&comp.uid);
+                            self.filter_components();
+                            let position = self.popups.filtered_components.iter().position(|c| c.uid == comp.uid);
+                            self.popups.component_list_state.select(position);
                         }
                     }
                     KeyCode::Char(c) => {
-                        self.component_search_query.push(c);
+                        self.popups.component_search_query.push(c);
                         self.filter_components();
                     }
                     KeyCode::Backspace => {
-                        self.component_search_query.pop();
+                        self.popups.component_search_query.pop();
                         self.filter_components();
                     }
                     _ => {}
                 }
             }
             RunComponentStep::FillVariables => {
+                let kind = self.current_variable_kind();
+                let choices = self.current_variable_choices();
                 match key.code {
                     KeyCode::Esc => {
-                        self.run_component_step = RunComponentStep::Search;
+                        self.popups.run_component_step = RunComponentStep::Search;
                     }
                     KeyCode::Enter => {
+                        if let Some(def) = self.current_variable_definition()
+                            && let Err(e) = check_variable_value(def, &self.popups.component_variable_input)
+                        {
+                            self.popups.component_variable_error = Some(e);
+                            return;
+                        }
                         // Save current input to variable
-                        if let Some(var) = self.component_variables.get_mut(self.component_variable_index) {
-                            var.value = self.component_variable_input.clone();
+                        if let Some(var) = self.popups.component_variables.get_mut(self.popups.component_variable_index) {
+                            var.value = self.popups.component_variable_input.clone();
                         }
 
                         // Move to next variable or Review
-                        if self.component_variable_index < self.component_variables.len() - 1 {
-                            self.component_variable_index += 1;
-                            // Load next variable value into buffer
-                            self.component_variable_input = self.component_variables[self.component_variable_index].value.clone();
+                        if self.popups.component_variable_index < self.popups.component_variables.len() - 1 {
+                            self.load_component_variable(self.popups.component_variable_index + 1);
                         } else {
-                            self.run_component_step = RunComponentStep::Review;
+                            self.review_component_run();
+                        }
+                    }
+                    // Selection variables pick from their allowed values
+                    KeyCode::Down | KeyCode::Char('j') if kind == VariableKind::Selection => {
+                        select_next(&mut self.popups.component_choice_state, choices.len());
+                        if let Some(choice) = self.popups.component_choice_state.selected().and_then(|i| choices.get(i)) {
+                            self.popups.component_variable_input = choice.value.clone();
+                        }
+                    }
+                    KeyCode::Up | KeyCode::Char('k') if kind == VariableKind::Selection => {
+                        select_prev(&mut self.popups.component_choice_state, choices.len());
+                        if let Some(choice) = self.popups.component_choice_state.selected().and_then(|i| choices.get(i)) {
+                            self.popups.component_variable_input = choice.value.clone();
                         }
                     }
-                    KeyCode::Up => {
+                    // Booleans are a checkbox
+                    KeyCode::Char(' ') if kind == VariableKind::Boolean => {
+                        let on = self.popups.component_variable_input == "true";
+                        self.popups.component_variable_input = (!on).to_string();
+                    }
+                    KeyCode::Char(c @ ('y' | 't' | 'n' | 'f')) if kind == VariableKind::Boolean => {
+                        self.popups.component_variable_input = matches!(c, 'y' | 't').to_string();
+                    }
+                    // Dates are YYYY-MM-DD segments, as in the warranty popup
+                    KeyCode::Tab | KeyCode::Right if kind == VariableKind::Date => {
+                        self.popups.component_date_focus = self.popups.component_date_focus.next();
+                    }
+                    KeyCode::Left if kind == VariableKind::Date => {
+                        self.popups.component_date_focus = self.popups.component_date_focus.prev();
+                    }
+                    KeyCode::Up | KeyCode::Down if kind == VariableKind::Date => {
+                        let delta = if key.code == KeyCode::Up { 1 } else { -1 };
+                        adjust_date_segment(&mut self.popups.component_date_segments, self.popups.component_date_focus, delta);
+                        self.sync_component_date();
+                    }
+                    KeyCode::Char(c) if kind == VariableKind::Date && c.is_ascii_digit() => {
+                        push_date_digit(&mut self.popups.component_date_segments, &mut self.popups.component_date_focus, c);
+                        self.sync_component_date();
+                    }
+                    KeyCode::Char('x') if kind == VariableKind::Date => {
+                        self.popups.component_date_segments = Default::default();
+                        self.sync_component_date();
+                    }
+                    KeyCode::Backspace if kind == VariableKind::Date => {
+                        self.popups.component_date_segments[self.popups.component_date_focus.index()].pop();
+                        self.sync_component_date();
+                    }
+                    KeyCode::Up | KeyCode::BackTab => {
                         // Go back to previous variable
-                        if self.component_variable_index > 0 {
+                        if self.popups.component_variable_index > 0 {
                             // Save current (optional, but good UX)
-                            if let Some(var) = self.component_variables.get_mut(self.component_variable_index) {
-                                var.value = self.component_variable_input.clone();
+                            if let Some(var) = self.popups.component_variables.get_mut(self.popups.component_variable_index) {
+                                var.value = self.popups.component_variable_input.clone();
                             }
-                            
-                            self.component_variable_index -= 1;
-                            self.component_variable_input = self.component_variables[self.component_variable_index].value.clone();
+
+                            self.load_component_variable(self.popups.component_variable_index - 1);
                         }
                     }
-                    KeyCode::Char(c) => {
-                        self.component_variable_input.push(c);
+                    KeyCode::Char(c) if kind == VariableKind::Text => {
+                        self.popups.component_variable_input.push(c);
                     }
-                    KeyCode::Backspace => {
-                        self.component_variable_input.pop();
+                    // Numbers take digits and a leading minus
+                    KeyCode::Char(c)
+                        if kind == VariableKind::Integer
+                            && (c.is_ascii_digit() || (c == '-' && self.popups.component_variable_input.is_empty())) =>
+                    {
+                        self.popups.component_variable_input.push(c);
+                    }
+                    KeyCode::Backspace if matches!(kind, VariableKind::Text | VariableKind::Integer) => {
+                        self.popups.component_variable_input.pop();
                     }
                     _ => {}
                 }
@@ -1541,13 +2791,12 @@ impl App {
             RunComponentStep::Review => {
                 match key.code {
                     KeyCode::Esc => {
-                        if self.component_variables.is_empty() {
-                            self.run_component_step = RunComponentStep::Search;
+                        if self.popups.component_variables.is_empty() {
+                            self.popups.run_component_step = RunComponentStep::Search;
                         } else {
-                            self.run_component_step = RunComponentStep::FillVariables;
+                            self.popups.run_component_step = RunComponentStep::FillVariables;
                             // Go to last variable
-                            self.component_variable_index = self.component_variables.len() - 1;
-                            self.component_variable_input = self.component_variables[self.component_variable_index].value.clone();
+                            self.load_component_variable(self.popups.component_variables.len() - 1);
                         }
                     }
                     KeyCode::Enter => {
@@ -1560,8 +2809,10 @@ impl App {
             RunComponentStep::Result => {
                 match key.code {
                     KeyCode::Enter | KeyCode::Esc => {
-                        self.show_run_component = false;
-                        self.run_component_step = RunComponentStep::Search;
+                        self.popups.close(Popup::RunComponent);
+                        self.popups.run_component_step = RunComponentStep::Search;
+                        // Closing stops the status polling
+                        self.popups.job_poll = None;
                     }
                     _ => {}
                 }
@@ -1572,136 +2823,132 @@ impl App {
     fn handle_quick_action_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
         match key.code {
             KeyCode::Esc => {
-                self.show_quick_actions = false;
+                self.popups.close(Popup::QuickActions);
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                let next = match self.quick_action_list_state.selected() {
-                    Some(i) => if i >= self.quick_actions.len().saturating_sub(1) { 0 } else { i + 1 },
+                let next = match self.popups.quick_action_list_state.selected() {
+                    Some(i) => if i >= self.popups.quick_actions.len().saturating_sub(1) { 0 } else { i + 1 },
                     None => 0,
                 };
-                self.quick_action_list_state.select(Some(next));
+                self.popups.quick_action_list_state.select(Some(next));
             }
             KeyCode::Up | KeyCode::Char('k') => {
-                let next = match self.quick_action_list_state.selected() {
-                    Some(i) => if i == 0 { self.quick_actions.len().saturating_sub(1) } else { i - 1 },
+                let next = match self.popups.quick_action_list_state.selected() {
+                    Some(i) => if i == 0 { self.popups.quick_actions.len().saturating_sub(1) } else { i - 1 },
                     None => 0,
                 };
-                self.quick_action_list_state.select(Some(next));
+                self.popups.quick_action_list_state.select(Some(next));
             }
             KeyCode::Enter => {
-                if let Some(i) = self.quick_action_list_state.selected() {
-                    if let Some(action) = self.quick_actions.get(i) {
+                if let Some(i) = self.popups.quick_action_list_state.selected() {
+                    if let Some(action) = self.popups.quick_actions.get(i) {
                         match action {
                             QuickAction::ReloadData => {
-                                self.show_quick_actions = false;
-                                if let Some(idx) = self.table_state.selected() {
-                                    self.navigate_to_site_detail(idx, tx);
+                                self.popups.close(Popup::QuickActions);
+                                if let Some(site) = self.site_list.selected_site() {
+                                    self.navigate_to_site_detail(site.uid.clone(), tx);
                                 }
                             }
                             QuickAction::ScheduleReboot => {
-                                self.show_quick_actions = false;
-                                self.show_reboot_popup = true;
-                                self.reboot_now = true;
-                                
-                                let now = chrono::Local::now();
-                                self.reboot_segments = [
-                                    now.format("%y").to_string(),
-                                    now.format("%m").to_string(),
-                                    now.format("%d").to_string(),
-                                    now.format("%H").to_string(),
-                                    now.format("%M").to_string(),
-                                ];
-                                
-                                self.reboot_focus = RebootFocus::RebootNow;
-                                self.reboot_error = None;
+                                self.popups.close(Popup::QuickActions);
+                                self.open_reboot_popup();
                             }
                             QuickAction::RunComponent => {
-                                self.show_quick_actions = false;
-                                self.show_run_component = true;
-                                self.run_component_step = RunComponentStep::Search;
-                                self.component_search_query.clear();
-                                self.fetch_components(tx);
+                                self.popups.close(Popup::QuickActions);
+                                self.open_run_component_popup(tx);
+                            }
+                            QuickAction::CollectionReboot => {
+                                self.popups.close(Popup::QuickActions);
+                                self.open_reboot_popup();
+                                self.popups.collection_target = self.selected_collection_name();
+                            }
+                            QuickAction::CollectionRunComponent => {
+                                self.popups.close(Popup::QuickActions);
+                                self.open_run_component_popup(tx);
+                                self.popups.collection_target = self.selected_collection_name();
+                            }
+                            QuickAction::CollectionScan => {
+                                self.popups.close(Popup::QuickActions);
+                                if let Some(collection) = self.selected_collection_name() {
+                                    self.confirm_collection_run(
+                                        collection,
+                                        CollectionAction::Scan,
+                                        tx,
+                                    );
+                                }
                             }
                             QuickAction::RunAvScan => {
-                                self.show_quick_actions = false;
-                                if let Some(device) = self.selected_device.clone() {
-                                    let is_sophos = device.antivirus.as_ref()
-                                        .and_then(|av| av.antivirus_product.as_ref())
-                                        .map(|prod| prod.to_lowercase().contains("sophos"))
-                                        .unwrap_or(false);
-                                    let is_datto = device.antivirus.as_ref()
-                                        .and_then(|av| av.antivirus_product.as_ref())
-                                        .map(|prod| {
-                                            let p = prod.to_lowercase();
-                                            p.contains("datto av") || p.contains("datto edr")
-                                        })
-                                        .unwrap_or(false);
-
-                                    if is_sophos {
-                                        // Find site variables for Sophos
-                                        let sophos_params = if let Some(site) = self.sites.iter().find(|s| s.uid == device.site_uid) {
-                                            if let Some(vars) = &site.variables {
-                                                vars.iter().find(|v| v.name == "tuiMdrId").map(|id_var| {
-                                                    let region = vars.iter().find(|v| v.name == "tuiMdrRegion").map(|v| v.value.clone());
-                                                    (id_var.value.clone(), region)
-                                                })
-                                            } else { None }
-                                        } else { None };
-
-                                        if let Some((t_id, region)) = sophos_params {
-                                            self.fetch_sophos_endpoint(t_id.clone(), region.clone(), device.hostname.clone(), tx.clone());
-                                            
-                                            // Start Scan if we have endpoint ID
-                                            if let Some(endpoint) = self.sophos_endpoints.get(&device.hostname) {
-                                                if let Some(client) = &self.sophos_client {
-                                                    let client = client.clone();
-                                                    let e_id = endpoint.id.clone();
-                                                    let region = region.unwrap_or_else(|| "us01".to_string());
-                                                    let h_name = device.hostname.clone();
-                                                    let tx_clone = tx.clone();
-                                                    self.scan_status.insert(h_name.clone(), ScanStatus::Starting);
-                                                    tokio::spawn(async move {
-                                                        let result = client.start_scan(&t_id, &region, &e_id).await.map_err(|e: anyhow::Error| e.to_string());
-                                                        tx_clone.send(Event::SophosScanStarted(h_name, result)).unwrap();
-                                                    });
-                                                }
-                                            }
-                                        }
-                                    } else if is_datto {
-                                        if let Some(agent) = self.datto_av_agents.get(&device.hostname) {
-                                            if let Some(client) = &self.datto_av_client {
-                                                let client = client.clone();
-                                                let a_id = agent.id.clone();
-                                                let h_name = device.hostname.clone();
-                                                let tx_clone = tx.clone();
-                                                self.scan_status.insert(h_name.clone(), ScanStatus::Starting);
-                                                tokio::spawn(async move {
-                                                    let result = client.scan_agent(&a_id).await.map_err(|e: anyhow::Error| e.to_string());
-                                                    tx_clone.send(Event::DattoAvScanStarted(h_name, result)).unwrap();
-                                                });
-                                            }
-                                        }
-                                    }
+                                self.popups.close(Popup::QuickActions);
+                                if let Some(device) = &self.device_detail.selected_device {
+                                    let confirmation = Confirmation::new(
+                                        ConfirmKind::Scan,
+                                        "Start Scan",
+                                        format!("Start an antivirus scan on {}?", device.hostname),
+                                        PendingAction::AvScan,
+                                    );
+                                    self.request_confirmation(confirmation, tx);
+                                }
+                            }
+                            QuickAction::IsolateDevice => {
+                                self.popups.close(Popup::QuickActions);
+                                if let Some(device) = &self.device_detail.selected_device
+                                    && let Some(provider) = security::security_provider(device)
+                                {
+                                    let confirmation = Confirmation::new(
+                                        ConfirmKind::Isolate,
+                                        "Isolate Device",
+                                        format!(
+                                            "Isolate {} from the network? Only {} can reach it \
+                                             until it is released there.",
+                                            device.hostname,
+                                            provider.name()
+                                        ),
+                                        PendingAction::Isolate,
+                                    );
+                                    self.request_confirmation(confirmation, tx);
                                 }
                             }
+                            QuickAction::Custom(index) => {
+                                let index = *index;
+                                self.popups.close(Popup::QuickActions);
+                                self.run_custom_quick_action(index, tx);
+                            }
+                            QuickAction::Launcher(index) => {
+                                let index = *index;
+                                self.popups.close(Popup::QuickActions);
+                                self.start_launcher(index);
+                            }
                             QuickAction::ClearWarranty => {
-                                self.show_quick_actions = false;
-                                self.warranty_segments = [String::new(), String::new(), String::new()];
-                                self.submit_warranty_update(tx);
+                                self.popups.close(Popup::QuickActions);
+                                self.send_warranty_update(None, tx);
                             }
                             QuickAction::UpdateWarranty => {
-                                self.show_quick_actions = false;
+                                self.popups.close(Popup::QuickActions);
                                 self.open_warranty_popup();
                             }
+                            QuickAction::EditDevice => {
+                                self.popups.close(Popup::QuickActions);
+                                self.open_device_edit_popup();
+                            }
+                            QuickAction::Decommission => {
+                                self.popups.close(Popup::QuickActions);
+                                self.open_decommission_popup();
+                            }
+                            QuickAction::NetworkTools => {
+                                self.popups.close(Popup::QuickActions);
+                                if let Some(device) = &self.device_detail.selected_device {
+                                    self.popups.net_tools = Some(NetToolsState::new(device));
+                                }
+                            }
                             QuickAction::MoveToSite => {
-                                self.show_quick_actions = false;
-                                self.show_site_move = true;
-                                self.site_move_query.clear();
+                                self.popups.close(Popup::QuickActions);
+                                self.popups.open(Popup::SiteMove);
+                                self.popups.site_move_query.clear();
                                 self.filter_sites_for_move();
                             }
                             QuickAction::OpenWebRemote => {
-                                self.show_quick_actions = false;
-                                if let Some(device) = &self.selected_device {
+                                self.popups.close(Popup::QuickActions);
+                                if let Some(device) = &self.device_detail.selected_device {
                                     if let Some(url) = &device.web_remote_url {
                                         crate::common::utils::open_browser(url);
                                     }
@@ -1715,14 +2962,54 @@ impl App {
         }
     }
 
+    /// Opens the Reboot popup set to reboot now, with the time fields on the
+    /// current time.
+    fn open_reboot_popup(&mut self) {
+        self.popups.open(Popup::Reboot);
+        self.popups.reboot_now = true;
+
+        let now = chrono::Local::now();
+        self.popups.reboot_segments = [
+            now.format("%y").to_string(),
+            now.format("%m").to_string(),
+            now.format("%d").to_string(),
+            now.format("%H").to_string(),
+            now.format("%M").to_string(),
+        ];
+
+        self.popups.reboot_focus = RebootFocus::RebootNow;
+        self.popups.reboot_error = None;
+        self.popups.reboot_submitting = false;
+        self.popups.reboot_confirmation = None;
+    }
+
+    fn open_run_component_popup(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        self.popups.open(Popup::RunComponent);
+        self.popups.run_component_step = RunComponentStep::Search;
+        self.popups.component_search_query.clear();
+        self.fetch_components(tx);
+    }
+
     fn handle_reboot_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        // Once submitted the popup only waits for the result, then closes
+        if self.popups.reboot_confirmation.is_some() {
+            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                self.popups.close(Popup::Reboot);
+                self.popups.reboot_confirmation = None;
+            }
+            return;
+        }
+        if self.popups.reboot_submitting {
+            return;
+        }
+
         match key.code {
             KeyCode::Esc => {
-                self.show_reboot_popup = false;
-                self.show_quick_actions = true;
+                self.popups.close(Popup::Reboot);
+                self.popups.open(Popup::QuickActions);
             }
             KeyCode::Tab => {
-                self.reboot_focus = match self.reboot_focus {
+                self.popups.reboot_focus = match self.popups.reboot_focus {
                     RebootFocus::RebootNow => RebootFocus::Year,
                     RebootFocus::Year => RebootFocus::Month,
                     RebootFocus::Month => RebootFocus::Day,
@@ -1732,7 +3019,7 @@ impl App {
                 };
             }
             KeyCode::BackTab => {
-                self.reboot_focus = match self.reboot_focus {
+                self.popups.reboot_focus = match self.popups.reboot_focus {
                     RebootFocus::RebootNow => RebootFocus::Minute,
                     RebootFocus::Year => RebootFocus::RebootNow,
                     RebootFocus::Month => RebootFocus::Year,
@@ -1742,47 +3029,47 @@ impl App {
                 };
             }
             KeyCode::Up => {
-                if self.reboot_focus == RebootFocus::RebootNow {
-                    self.reboot_focus = RebootFocus::Minute;
+                if self.popups.reboot_focus == RebootFocus::RebootNow {
+                    self.popups.reboot_focus = RebootFocus::Minute;
                 } else {
                     self.adjust_reboot_segment(1);
                 }
             }
             KeyCode::Down => {
-                if self.reboot_focus == RebootFocus::RebootNow {
-                    self.reboot_focus = RebootFocus::Year;
+                if self.popups.reboot_focus == RebootFocus::RebootNow {
+                    self.popups.reboot_focus = RebootFocus::Year;
                 } else {
                     self.adjust_reboot_segment(-1);
                 }
             }
             KeyCode::Left => {
-                self.reboot_focus = match self.reboot_focus {
+                self.popups.reboot_focus = match self.popups.reboot_focus {
                     RebootFocus::Year => RebootFocus::RebootNow,
                     RebootFocus::Month => RebootFocus::Year,
                     RebootFocus::Day => RebootFocus::Month,
                     RebootFocus::Hour => RebootFocus::Day,
                     RebootFocus::Minute => RebootFocus::Hour,
-                    _ => self.reboot_focus,
+                    _ => self.popups.reboot_focus,
                 };
             }
             KeyCode::Right => {
-                self.reboot_focus = match self.reboot_focus {
+                self.popups.reboot_focus = match self.popups.reboot_focus {
                     RebootFocus::RebootNow => RebootFocus::Year,
                     RebootFocus::Year => RebootFocus::Month,
                     RebootFocus::Month => RebootFocus::Day,
                     RebootFocus::Day => RebootFocus::Hour,
                     RebootFocus::Hour => RebootFocus::Minute,
-                    _ => self.reboot_focus,
+                    _ => self.popups.reboot_focus,
                 };
             }
-            KeyCode::Char(' ') if self.reboot_focus == RebootFocus::RebootNow => {
-                self.reboot_now = !self.reboot_now;
+            KeyCode::Char(' ') if self.popups.reboot_focus == RebootFocus::RebootNow => {
+                self.popups.reboot_now = !self.popups.reboot_now;
             }
             KeyCode::Char('x') => {
-                self.warranty_segments = [String::new(), String::new(), String::new()];
+                self.popups.warranty_segments = [String::new(), String::new(), String::new()];
             }
             KeyCode::Char(c) if c.is_digit(10) => {
-                if self.reboot_now && self.reboot_focus != RebootFocus::RebootNow {
+                if self.popups.reboot_now && self.popups.reboot_focus != RebootFocus::RebootNow {
                     // If reboot now is checked, don't allow typing in time segments?
                     // Or automatically uncheck it? 
                     // User said "if that box is unchecked allow the user to select a date and time"
@@ -1791,8 +3078,8 @@ impl App {
                     // "if that box is unchecked" implies it must be unchecked first.
                 }
                 
-                if !self.reboot_now {
-                    let idx = match self.reboot_focus {
+                if !self.popups.reboot_now {
+                    let idx = match self.popups.reboot_focus {
                         RebootFocus::Year => Some(0),
                         RebootFocus::Month => Some(1),
                         RebootFocus::Day => Some(2),
@@ -1804,34 +3091,200 @@ impl App {
                     if let Some(i) = idx {
                         // Override logic: if we just entered or just want to replace
                         // Simplest: push and keep last 2
-                        let mut s = self.reboot_segments[i].clone();
+                        let mut s = self.popups.reboot_segments[i].clone();
                         s.push(c);
                         if s.len() > 2 {
                             s.remove(0);
                         }
-                        self.reboot_segments[i] = s;
+                        self.popups.reboot_segments[i] = s;
                     }
                 }
             }
             KeyCode::Enter => {
                 // Validation
-                if !self.reboot_now {
-                    let date_str = self.reboot_segments.join("");
-                    if chrono::NaiveDateTime::parse_from_str(&date_str, "%y%m%d%H%M").is_err() {
-                        self.reboot_error = Some("Invalid Date/Time".to_string());
-                        return;
+                let at = if self.popups.reboot_now {
+                    None
+                } else {
+                    // Typed in the configured zone, not the host's
+                    let now = crate::common::time::in_display_zone(chrono::Utc::now());
+                    match parse_reboot_time(&self.popups.reboot_segments, &now) {
+                        Ok(at) => Some(at.naive_local()),
+                        Err(e) => {
+                            self.popups.reboot_error = Some(e);
+                            return;
+                        }
                     }
+                };
+                if let Some(collection) = self.popups.collection_target.clone() {
+                    self.confirm_collection_run(collection, CollectionAction::Reboot(at), tx);
+                    return;
+                }
+                let when = at.map_or_else(
+                    || "now".to_string(),
+                    |at| format!("at {}", at.format("%Y-%m-%d %H:%M")),
+                );
+                let hostname = self
+                    .device_detail
+                    .selected_device
+                    .as_ref()
+                    .map(|d| d.hostname.clone())
+                    .unwrap_or_default();
+                let confirmation = Confirmation::new(
+                    ConfirmKind::Reboot,
+                    "Reboot Device",
+                    format!("Reboot {} {}?", hostname, when),
+                    PendingAction::Reboot(at),
+                );
+                self.request_confirmation(confirmation, tx);
+            }
+            _ => {}
+        }
+    }
+
+    /// Shows the confirmation popup for `confirmation`, or runs its action
+    /// straight away when the config doesn't ask for this kind of action.
+    fn request_confirmation(
+        &mut self,
+        confirmation: Confirmation,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        if self.confirm_actions.contains(&confirmation.kind) {
+            self.popups.confirmation = Some(confirmation);
+        } else {
+            self.run_confirmed(confirmation.action, tx);
+        }
+    }
+
+    fn handle_confirmation_input(
+        &mut self,
+        key: KeyEvent,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(confirmation) = self.popups.confirmation.take() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => self.run_confirmed(confirmation.action, tx),
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {
+                // Toggles and edits were applied to the edit state already
+                if matches!(
+                    confirmation.action,
+                    PendingAction::SiteUpdate { .. } | PendingAction::SiteProxyUpdate { .. }
+                ) {
+                    self.populate_site_edit_state();
+                }
+            }
+            _ => self.popups.confirmation = Some(confirmation),
+        }
+    }
+
+    fn run_confirmed(
+        &mut self,
+        action: PendingAction,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        match action {
+            PendingAction::SiteUpdate { site_uid, req } => self.send_site_update(site_uid, req, tx),
+            PendingAction::SiteProxyUpdate { site_uid, proxy } => {
+                self.send_site_proxy_update(site_uid, proxy, tx)
+            }
+            PendingAction::DeviceUpdate { device_uid, req } => {
+                self.send_device_update(device_uid, req, tx)
+            }
+            PendingAction::Udf { device_uid, number, value } => {
+                self.apply_udf(&device_uid, number, value, tx)
+            }
+            PendingAction::DeleteVariable { site_uid, variable_id } => {
+                self.delete_site_variable(site_uid, variable_id, tx)
+            }
+            PendingAction::DeleteAccountVariable { variable_id } => {
+                self.delete_account_variable(variable_id, tx)
+            }
+            PendingAction::AvScan => self.start_av_scan(tx),
+            PendingAction::Isolate => self.isolate_device(tx),
+            PendingAction::Reboot(at) => self.run_reboot_job(at, tx),
+            PendingAction::ResolveAlert { alert_uid } => self.resolve_alert(alert_uid, tx),
+            PendingAction::RerunJob { device_uid, req } => {
+                self.submit_quick_job(device_uid, req, tx)
+            }
+            PendingAction::CancelJob { job_uid } => self.cancel_job(job_uid, tx),
+            PendingAction::CollectionRun { collection, action } => {
+                self.run_collection_action(collection, action, &tx)
+            }
+        }
+    }
+
+    /// Shows the selected open alert of the device in the Alert Detail popup.
+    fn open_alert_detail(&mut self) {
+        let alert = self.device_detail.open_alerts.selected().cloned();
+        self.popups.alert_detail = alert.map(|alert| AlertDetail::new(alert, &self.runbooks));
+    }
+
+    fn confirm_alert_resolve(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(alert) = self.popups.alert_detail.as_ref().map(|d| &d.alert) else {
+            return;
+        };
+        let Some(alert_uid) = alert.alert_uid.clone() else {
+            return;
+        };
+        let device = alert
+            .alert_source_info
+            .as_ref()
+            .and_then(|s| s.device_name.clone())
+            .unwrap_or_else(|| "the device".to_string());
+        self.request_confirmation(
+            Confirmation::new(
+                ConfirmKind::AlertResolve,
+                "Resolve Alert",
+                format!("Resolve this alert on {}? It leaves the open alerts.", device),
+                PendingAction::ResolveAlert { alert_uid },
+            ),
+            tx,
+        );
+    }
+
+    fn resolve_alert(&mut self, alert_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.tasks.spawn("resolve_alert", async move {
+            let result = client
+                .resolve_alert(&alert_uid)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::AlertResolved(alert_uid, result));
+        });
+    }
+
+    fn handle_alert_detail_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(detail) = self.popups.alert_detail.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => self.popups.alert_detail = None,
+            KeyCode::Down | KeyCode::Char('j') => detail.scroll = detail.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => detail.scroll = detail.scroll.saturating_sub(1),
+            KeyCode::PageDown => detail.scroll = detail.scroll.saturating_add(10),
+            KeyCode::PageUp => detail.scroll = detail.scroll.saturating_sub(10),
+            KeyCode::Char('r') => self.confirm_alert_resolve(tx),
+            KeyCode::Char('x') => self.run_runbook_component(tx),
+            KeyCode::Char('o') => match detail.runbook.as_ref().and_then(|r| r.url.as_deref()) {
+                Some(url) => crate::common::utils::open_browser(url),
+                None => self.show_toast("No runbook page for this alert".to_string()),
+            },
+            KeyCode::Char('a') => {
+                if let Some(uid) = detail.alert.alert_uid.clone() {
+                    self.toggle_alert_acknowledgement(uid);
                 }
-                self.run_reboot_job(tx);
             }
             _ => {}
         }
     }
 
     fn adjust_reboot_segment(&mut self, delta: i32) {
-        if self.reboot_now { return; }
+        if self.popups.reboot_now { return; }
         
-        let idx = match self.reboot_focus {
+        let idx = match self.popups.reboot_focus {
             RebootFocus::Year => 0,
             RebootFocus::Month => 1,
             RebootFocus::Day => 2,
@@ -1840,10 +3293,10 @@ impl App {
             _ => return,
         };
         
-        let mut val: i32 = self.reboot_segments[idx].parse().unwrap_or(0);
+        let mut val: i32 = self.popups.reboot_segments[idx].parse().unwrap_or(0);
         val += delta;
         
-        match self.reboot_focus {
+        match self.popups.reboot_focus {
             RebootFocus::Year => { if val < 0 { val = 99; } if val > 99 { val = 0; } },
             RebootFocus::Month => { if val < 1 { val = 12; } if val > 12 { val = 1; } },
             RebootFocus::Day => { if val < 1 { val = 31; } if val > 31 { val = 1; } },
@@ -1852,105 +3305,310 @@ impl App {
             _ => {}
         }
         
-        self.reboot_segments[idx] = format!("{:02}", val);
+        self.popups.reboot_segments[idx] = format!("{:02}", val);
     }
 
-    fn run_reboot_job(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(client) = &self.client {
-            if let Some(device) = &self.selected_device {
-                self.show_reboot_popup = false;
-                self.show_run_component = true;
-                self.run_component_step = RunComponentStep::Result;
-                self.components_loading = true;
-                self.component_error = None;
+    /// Fetches the status of the job shown in the Run Component result when
+    /// the next poll is due.
+    fn poll_job_status(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let (Some(client), Some(poll)) = (&self.client, self.popups.job_poll.as_mut()) else {
+            return;
+        };
+        if !poll.tick() {
+            return;
+        }
+        let client = client.clone();
+        let (job_uid, device_uid) = (poll.job_uid.clone(), poll.device_uid.clone());
+        self.tasks.spawn("poll_job_status", async move {
+            let result = client
+                .get_job_result(&job_uid, &device_uid)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::JobStatusPolled(job_uid, result));
+        });
+    }
 
-                let client = client.clone();
-                let device_uid = device.uid.clone();
-                let req = QuickJobRequest {
-                    job_name: "Schedule Reboot".to_string(),
-                    job_component: QuickJobComponent {
-                        component_uid: "8e6c9295-871e-41f1-8060-ca6899965b82".to_string(),
-                        variables: vec![
-                            QuickJobVariable {
-                                name: "rebootNow".to_string(),
-                                value: self.reboot_now.to_string(),
-                            },
-                            QuickJobVariable {
-                                name: "rebootString".to_string(),
-                                value: self.reboot_segments.join(""),
-                            },
-                        ],
-                    },
-                };
+    /// Runs a config-defined component shortcut on the selected device and shows
+    /// the result in the run component popup.
+    fn run_custom_quick_action(&mut self, index: usize, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(action) = self.custom_quick_actions.get(index).cloned() else {
+            return;
+        };
+        self.run_preset_component(&action.name, &action.component_uid, &action.variables, tx);
+    }
 
-                tokio::spawn(async move {
-                    let result = client.run_quick_job(&device_uid, req).await.map_err(|e| format!("{:#}", e));
-                    tx.send(Event::QuickJobExecuted(result)).unwrap();
-                });
-            }
-        }
+    /// Runs the runbook's component on the open device, from the alert detail.
+    fn run_runbook_component(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(runbook) = self.popups.alert_detail.as_ref().and_then(|d| d.runbook.clone()) else {
+            return;
+        };
+        let Some(component_uid) = &runbook.component_uid else {
+            self.show_toast(format!("Runbook '{}' has no component_uid", runbook.name));
+            return;
+        };
+        self.popups.alert_detail = None;
+        self.run_preset_component(&runbook.name, component_uid, &runbook.variables, tx);
     }
 
-    fn navigate_to_device_detail(
+    /// Runs a component from the config on the open device with preset
+    /// variables, showing the job in the Run Component result step.
+    fn run_preset_component(
         &mut self,
-        device: Device,
+        name: &str,
+        component_uid: &str,
+        variables: &BTreeMap<String, String>,
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
-        self.selected_device = Some(device.clone());
-        self.current_view = CurrentView::DeviceDetail;
+        let Some(device) = &self.device_detail.selected_device else {
+            return;
+        };
+        let device_uid = device.uid.clone();
+        let req = QuickJobRequest {
+            job_name: name.to_string(),
+            job_component: QuickJobComponent {
+                component_uid: component_uid.to_string(),
+                variables: variables
+                    .iter()
+                    .map(|(name, value)| QuickJobVariable {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+            },
+        };
+        tracing::info!(%device_uid, action = %name, "running preset component");
+        self.submit_quick_job(device_uid, req, tx);
+    }
 
-        // Reset software search
-        self.software_search_query.clear();
-        self.is_software_searching = false;
-        self.device_software.clear();
-        self.filtered_software.clear();
+    /// Submits a quick job, showing it in the Run Component result step.
+    fn submit_quick_job(
+        &mut self,
+        device_uid: String,
+        req: QuickJobRequest,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.popups.open(Popup::RunComponent);
+        self.popups.run_component_step = RunComponentStep::Result;
+        self.popups.components_loading = true;
+        self.popups.component_error = None;
+        self.popups.last_job_response = None;
+        self.popups.job_poll = None;
+
+        self.tasks.spawn("submit_quick_job", async move {
+            let result = client.run_quick_job(&device_uid, req).await.map_err(ApiError::from);
+            let _ = tx.send(Event::QuickJobExecuted(result));
+        });
+    }
 
-        // Auto-load Security Data
-        let is_sophos = device
-            .antivirus
-            .as_ref()
-            .and_then(|av| av.antivirus_product.as_ref())
-            .map(|prod| prod.to_lowercase().contains("sophos"))
-            .unwrap_or(false);
+    /// Fetches the components of the open job, to re-run one of them.
+    fn fetch_job_components(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let Some(job_uid) =
+            self.device_detail.selected_job_result.as_ref().and_then(|j| j.job_uid.clone())
+        else {
+            self.show_toast("No job to re-run".to_string());
+            return;
+        };
+        let on_panic = {
+            let job_uid = job_uid.clone();
+            move |e| Event::JobComponentsFetched(job_uid, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_job_components", on_panic, async move {
+            let result = client
+                .get_job_components(&job_uid)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::JobComponentsFetched(job_uid, result));
+        });
+    }
 
-        let is_datto = device
-            .antivirus
+    /// Asks to run the selected component of the open job again with the
+    /// variable values it ran with, on the same device. Quick jobs run a
+    /// single component, so a job of several re-runs the selected one.
+    fn confirm_job_rerun(
+        &mut self,
+        job_uid: &str,
+        components: Vec<JobComponent>,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let detail = &self.device_detail;
+        let Some(job_result) = detail
+            .selected_job_result
             .as_ref()
-            .and_then(|av| av.antivirus_product.as_ref())
-            .map(|prod| {
-                let p = prod.to_lowercase();
-                p.contains("datto av") || p.contains("datto edr")
-            })
-            .unwrap_or(false);
-
-        if is_sophos {
-            // Find site variables for tuiMdrId
-            let sophos_params = if let Some(site) = self.sites.iter().find(|s| s.uid == device.site_uid) {
-                if let Some(vars) = &site.variables {
-                    if let Some(id_var) = vars.iter().find(|v| v.name == "tuiMdrId") {
-                        let region = vars
-                            .iter()
-                            .find(|v| v.name == "tuiMdrRegion")
-                            .map(|v| v.value.clone());
-                        Some((id_var.value.clone(), region))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            .filter(|j| j.job_uid.as_deref() == Some(job_uid))
+        else {
+            return;
+        };
+        let Some(device_uid) = job_result.device_uid.clone() else {
+            return;
+        };
+        let selected_uid = detail.selected_job_row().and_then(|row| {
+            job_result.component_results.as_ref()?.get(row.component())?.component_uid.clone()
+        });
+        let Some(component) = components
+            .iter()
+            .find(|c| selected_uid.is_some() && c.uid == selected_uid)
+            .or(components.first())
+        else {
+            self.show_toast("The job has no components to re-run".to_string());
+            return;
+        };
+        let component_name = component.name.clone().unwrap_or_else(|| "component".to_string());
+        let job_name = detail
+            .selected_activity_log
+            .as_ref()
+            .map(|log| activity_job_summary(log).0)
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| component_name.clone());
+        let Some(req) = rerun_request(&job_name, component) else {
+            self.show_toast("The job didn't report its component's UID".to_string());
+            return;
+        };
+        let hostname = detail
+            .selected_device
+            .as_ref()
+            .filter(|d| d.uid == device_uid)
+            .map_or_else(|| "the device".to_string(), |d| d.hostname.clone());
+        let variables = req.job_component.variables.len();
+        self.request_confirmation(
+            Confirmation::new(
+                ConfirmKind::JobRerun,
+                "Re-run Job",
+                format!(
+                    "Run {} again on {} with the {} variable value(s) it ran with?",
+                    component_name, hostname, variables
+                ),
+                PendingAction::RerunJob { device_uid, req },
+            ),
+            tx,
+        );
+    }
 
-            if let Some((id, region)) = sophos_params {
-                self.fetch_sophos_endpoint(id, region, device.hostname.clone(), tx.clone());
-            }
+    /// Asks to cancel the job of the open activity, or of the activity
+    /// selected on the Activities tab, if it's still scheduled.
+    fn confirm_job_cancel(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let detail = &self.device_detail;
+        let log = if self.current_view == CurrentView::ActivityDetail {
+            detail.selected_activity_log.as_ref()
+        } else {
+            detail.activity_logs.selected()
+        };
+        let Some(log) = log else {
+            return;
+        };
+        let Some(job_uid) = activity_job_uid(log) else {
+            self.show_toast("The activity isn't a job's".to_string());
+            return;
+        };
+        let (name, mut status) = activity_job_summary(log);
+        // The job result is newer than the activity when it's open
+        if let Some(deployment) = detail
+            .selected_job_result
+            .as_ref()
+            .filter(|j| j.job_uid.as_deref() == Some(job_uid.as_str()))
+            .and_then(|j| j.job_deployment_status.clone())
+        {
+            status = deployment;
+        }
+        if !is_scheduled(&status) {
+            self.show_toast("Only scheduled jobs can be cancelled".to_string());
+            return;
         }
+        let hostname = detail
+            .selected_device
+            .as_ref()
+            .map_or_else(|| "the device".to_string(), |d| d.hostname.clone());
+        self.request_confirmation(
+            Confirmation::new(
+                ConfirmKind::JobCancel,
+                "Cancel Job",
+                format!("Cancel {} before it runs on {}?", name, hostname),
+                PendingAction::CancelJob { job_uid },
+            ),
+            tx,
+        );
+    }
+
+    fn cancel_job(&mut self, job_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        tracing::info!(%job_uid, "cancelling job");
+        self.tasks.spawn("cancel_job", async move {
+            let result = client
+                .cancel_job(&job_uid)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::JobCancelled(job_uid, result));
+        });
+    }
 
-        if is_datto {
-            self.fetch_datto_av_agent(device.hostname.clone(), device.udf.clone(), tx.clone());
+    /// Fetches the activities of the open device again after a job was
+    /// cancelled, and the job's result if it's open.
+    fn refresh_cancelled_job(
+        &mut self,
+        job_uid: String,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(device) = self.device_detail.selected_device.clone() else {
+            return;
+        };
+        let job_open = self.current_view == CurrentView::ActivityDetail
+            && self
+                .device_detail
+                .selected_activity_log
+                .as_ref()
+                .and_then(activity_job_uid)
+                .is_some_and(|uid| uid == job_uid);
+        if job_open {
+            self.fetch_job_result(job_uid, device.uid.clone(), tx.clone());
         }
+        self.fetch_activity_logs(device.uid, device.id, device.site_id, tx);
+    }
+
+    fn run_reboot_job(&mut self, at: Option<chrono::NaiveDateTime>, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let (Some(client), Some(device)) = (&self.client, &self.device_detail.selected_device) else {
+            return;
+        };
+        self.popups.reboot_submitting = true;
+        self.popups.reboot_error = None;
+
+        let client = client.clone();
+        let device_uid = device.uid.clone();
+        tracing::info!(%device_uid, ?at, "scheduling reboot");
+        self.tasks.spawn("run_reboot_job", async move {
+            let result = client
+                .schedule_reboot(&device_uid, at)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::RebootScheduled(result));
+        });
+    }
+
+    fn navigate_to_device_detail(
+        &mut self,
+        device: Device,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        self.device_detail.fetches.cancel();
+        self.device_detail.selected_device = Some(device.clone());
+        self.device_detail.opened_from = None;
+        self.device_detail.reset_scroll();
+        self.current_view = CurrentView::DeviceDetail;
+        self.record_recent_device(&device);
+
+        // Reset software search
+        self.device_detail.software_search_query.clear();
+        self.device_detail.is_software_searching = false;
+        self.device_detail.device_software.clear();
+
+        // Auto-load Security Data
+        self.fetch_device_security(&device, &tx);
 
         // Fetch Rocket Cyber agent
         if self.rocket_client.is_some() {
@@ -1965,20 +3623,14 @@ impl App {
             tx.clone(),
         );
 
-        // Fetch open alerts
+        // Fetch open alerts and the recently resolved ones
         self.fetch_open_alerts(device.uid.clone(), tx.clone());
+        self.fetch_resolved_alerts(device.uid.clone(), tx.clone());
 
         // Fetch software if supported
         let is_software_supported = device.device_class.as_ref().map(|s| s.trim().to_lowercase()).as_deref() == Some("device");
         
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "Device UID: {}, Class: {:?}, Software Supported: {}", device.uid, device.device_class, is_software_supported).unwrap();
-            });
+        tracing::debug!(device_uid = %device.uid, class = ?device.device_class, is_software_supported, "opened device detail");
 
         if is_software_supported {
             self.fetch_device_software(device.uid.clone(), tx.clone());
@@ -1991,14 +3643,18 @@ impl App {
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
         if let Some(client) = self.client.clone() {
-            self.device_software_loading = true;
-            self.device_software_error = None;
-            self.device_software.clear();
-
-            tokio::spawn(async move {
+            self.device_detail.device_software_loading = true;
+            self.device_detail.device_software_error = None;
+            self.device_detail.device_software.clear();
+
+            let generation = self.device_detail.fetches.generation();
+            let uid = device_uid.clone();
+            let on_panic = move |e| Event::DeviceSoftwareFetched(generation, uid, Err(e));
+            let tasks = &self.tasks;
+            self.device_detail.fetches.spawn(tasks, "fetch_device_software", on_panic, async move {
                 let mut all_software = Vec::new();
                 let mut current_page = 0;
-                let page_size = 250;
+                let page_size = client.page_size();
 
                 loop {
                     match client
@@ -2010,15 +3666,13 @@ impl App {
                             all_software.extend(response.software);
 
                             if count < page_size as usize || response.page_details.next_page_url.is_none() {
-                                tx.send(Event::DeviceSoftwareFetched(device_uid, Ok(all_software)))
-                                    .unwrap();
+                                let _ = tx.send(Event::DeviceSoftwareFetched(generation, device_uid, Ok(all_software)));
                                 break;
                             }
                             current_page += 1;
                         }
                         Err(e) => {
-                            tx.send(Event::DeviceSoftwareFetched(device_uid, Err(e.to_string())))
-                                .unwrap();
+                            let _ = tx.send(Event::DeviceSoftwareFetched(generation, device_uid, Err(e.into())));
                             break;
                         }
                     }
@@ -2027,18 +3681,21 @@ impl App {
         }
     }
 
-    fn navigate_to_site_detail(&mut self, site_idx: usize, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(site) = self.sites.get(site_idx).cloned() {
-            self.table_state.select(Some(site_idx));
+    fn navigate_to_site_detail(&mut self, site_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if self.site_list.select_site(&site_uid)
+            && let Some(site) = self.site_list.selected_site().cloned()
+        {
             self.current_view = CurrentView::Detail;
+            self.site_detail.fetches.cancel();
             let site_uid = site.uid.clone();
-            self.selected_device_uids.clear();
+            self.site_detail.selected_device_uids.clear();
+            self.site_detail.selected_variable_ids.clear();
             
             // Refresh site data
             self.fetch_devices(site_uid.clone(), tx.clone());
             self.fetch_site_variables(site_uid.clone(), tx.clone());
             self.fetch_site_open_alerts(site_uid.clone(), tx.clone());
-            self.site_open_alerts_table_state.select(Some(0));
+            self.site_detail.site_open_alerts.select_first();
             
             // Call fetch_site to get latest data (including counts)
             self.fetch_site(site_uid.clone(), tx.clone());
@@ -2051,11 +3708,13 @@ impl App {
                 notes: site.notes.clone(),
                 on_demand: site.on_demand,
                 splashtop_auto_install: site.splashtop_auto_install,
+                autotask_company_id: None,
+                autotask_company_name: None,
             };
             
-            tokio::spawn(async move {
-                let result = client.update_site(&site_uid, req).await.map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::SiteUpdated(result)).unwrap();
+            self.tasks.spawn("navigate_to_site_detail", async move {
+                let result = client.update_site(&site_uid, req).await.map_err(ApiError::from);
+                let _ = tx.send(Event::SiteUpdated(result));
             });
         }
     }
@@ -2064,41 +3723,70 @@ impl App {
     fn fetch_rocket_incidents(&self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
         if let Some(client) = &self.rocket_client {
             let client = client.clone();
-            tokio::spawn(async move {
-                let result = client.get_incidents().await.map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::IncidentsFetched(result)).unwrap();
+            let on_panic = |e| Event::IncidentsFetched(Err(e));
+            self.tasks.spawn_fetch("fetch_rocket_incidents", on_panic, async move {
+                let result = client.get_incidents().await.map_err(ApiError::from);
+                let _ = tx.send(Event::IncidentsFetched(result));
             });
         }
     }
 
     fn fetch_rocket_cyber_agent(&mut self, hostname: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
         if let Some(client) = &self.rocket_client {
-            self.rocket_loading.insert(hostname.clone(), true);
+            self.security.rocket_loading.insert(hostname.clone(), true);
             let client = client.clone();
-            tokio::spawn(async move {
+            let on_panic = {
+                let hostname = hostname.clone();
+                move |e| Event::RocketCyberAgentFetched(hostname, Err(e))
+            };
+            self.tasks.spawn_fetch("fetch_rocket_cyber_agent", on_panic, async move {
                 let result = client.get_agents(&hostname).await;
                 match result {
                     Ok(agents) => {
                         let agent = agents.into_iter().next();
-                        tx.send(Event::RocketCyberAgentFetched(hostname, Ok(agent))).unwrap();
+                        let _ = tx.send(Event::RocketCyberAgentFetched(hostname, Ok(agent)));
                     }
                     Err(e) => {
-                        tx.send(Event::RocketCyberAgentFetched(hostname, Err(e.to_string()))).unwrap();
+                        let _ = tx.send(Event::RocketCyberAgentFetched(hostname, Err(e.into())));
                     }
                 }
             });
         }
     }
 
+    /// Loads the site list or the open site's devices again once a retry
+    /// scheduled after a retryable error is due.
+    fn retry_failed_loads(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if self.site_list.retry.take_due().is_some() && !self.site_list.is_loading {
+            tracing::info!("retrying the site list");
+            self.fetch_sites(tx.clone());
+            self.redraw.request();
+        }
+        if let Some(site_uid) = self.site_detail.devices_retry.take_due()
+            && self.current_view == CurrentView::Detail
+            && self.site_list.selected_site().is_some_and(|s| s.uid == site_uid)
+        {
+            tracing::info!(%site_uid, "retrying the device list");
+            self.fetch_devices(site_uid, tx);
+            self.redraw.request();
+        }
+    }
+
     fn fetch_sites(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
         if let Some(client) = &self.client {
-            self.is_loading = true;
-            self.error = None;
             let client = client.clone();
-            tokio::spawn(async move {
+            self.site_list.is_loading = true;
+            self.acknowledge_errors(Some(CurrentView::List));
+            self.fetch_account_alerts(tx.clone());
+            // The posture column scores sites from the account devices
+            if self.report.devices.is_empty() && !self.report.loading {
+                self.fetch_account_devices(tx.clone());
+            }
+            let on_panic = |e| Event::SitesFetched(Err(e));
+            self.tasks.spawn_fetch("fetch_sites", on_panic, async move {
                 let mut all_sites = Vec::new();
                 let mut current_page = 0;
-                let page_size = 250;
+                let page_size = client.page_size();
 
                 loop {
                     match client.get_sites(current_page, page_size, None).await {
@@ -2107,16 +3795,16 @@ impl App {
                             all_sites.extend(response.sites);
 
                             if count < page_size as usize || response.page_details.next_page_url.is_none() {
-                                tx.send(Event::SitesFetched(Ok(SitesResponse {
+                                let _ = tx.send(Event::SitesFetched(Ok(SitesResponse {
                                     page_details: response.page_details,
                                     sites: all_sites,
-                                }))).unwrap();
+                                })));
                                 break;
                             }
                             current_page += 1;
                         }
                         Err(e) => {
-                            tx.send(Event::SitesFetched(Err(e.to_string()))).unwrap();
+                            let _ = tx.send(Event::SitesFetched(Err(e.into())));
                             break;
                         }
                     }
@@ -2125,45 +3813,154 @@ impl App {
         }
     }
 
-    fn fetch_site(&mut self, site_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(client) = &self.client {
-            let client = client.clone();
-            tokio::spawn(async move {
-                let result = client.get_site(&site_uid).await.map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::SiteUpdated(result)).unwrap();
+    /// Fetches the open alerts of every site, to find the ones new since the last session.
+    fn fetch_account_alerts(&self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let on_panic = |e| Event::AccountAlertsFetched(Err(e));
+        self.tasks.spawn_fetch("fetch_account_alerts", on_panic, async move {
+            let mut all_alerts = Vec::new();
+            let mut current_page = 0;
+            let page_size = client.page_size();
+            loop {
+                match client.get_account_open_alerts(current_page, page_size).await {
+                    Ok(response) => {
+                        let count = response.alerts.len();
+                        all_alerts.extend(response.alerts);
+                        if count < page_size as usize
+                            || response.page_details.next_page_url.is_none()
+                        {
+                            let _ = tx.send(Event::AccountAlertsFetched(Ok(all_alerts)));
+                            break;
+                        }
+                        current_page += 1;
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Event::AccountAlertsFetched(Err(e.into())));
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    fn fetch_site(&mut self, site_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if let Some(client) = &self.client {
+            let client = client.clone();
+            let on_panic = |e| Event::SiteUpdated(Err(e));
+            self.tasks.spawn_fetch("fetch_site", on_panic, async move {
+                let result = client.get_site(&site_uid).await.map_err(ApiError::from);
+                let _ = tx.send(Event::SiteUpdated(result));
             });
         }
     }
 
     fn fetch_devices(&mut self, site_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
         if let Some(client) = &self.client {
-            self.devices_loading = true;
-            self.devices_error = None;
-            self.devices = Vec::new(); // Clear previous
             let client = client.clone();
-            tokio::spawn(async move {
+            self.site_detail.devices_loading = true;
+            self.site_detail.devices_progress = None;
+            self.site_detail.devices_error = None;
+            self.site_detail.set_devices(Vec::new()); // Clear previous
+            self.site_detail.devices_cached_at = None;
+            if let Some(cached) = self
+                .cache
+                .as_ref()
+                .and_then(|c| c.get::<Vec<Device>>(&cache::devices_key(&site_uid)))
+            {
+                self.site_detail.set_devices(cached.value);
+                self.site_detail.devices_cached_at = Some(cached.fetched_at);
+            }
+            // Known device count, used for progress until the API reports a total
+            let expected = self
+                .site_list
+                .selected_site()
+                .filter(|s| s.uid == site_uid)
+                .and_then(|s| s.devices_status.as_ref())
+                .map(|s| s.number_of_devices.max(0) as usize);
+            let generation = self.site_detail.fetches.generation();
+            let uid = site_uid.clone();
+            let on_panic = move |e| Event::DevicesFetched(generation, uid, Err(e));
+            self.site_detail.fetches.spawn(&self.tasks, "fetch_devices", on_panic, async move {
                 let mut all_devices = Vec::new();
+                let mut parse_errors = 0;
                 let mut current_page = 0;
-                let page_size = 250;
+                // Sites with more devices than a page show a progress gauge
+                // (`Event::DevicesProgress`)
+                let page_size = client.page_size();
 
                 loop {
                     match client.get_devices(&site_uid, current_page, page_size).await {
                         Ok(response) => {
-                            let count = response.devices.len();
+                            let count = response.page_len();
+                            parse_errors += response.parse_errors;
                             all_devices.extend(response.devices);
-                            
+
                             // If we got fewer devices than requested, or next_page_url is None, we're done
                             if count < page_size as usize || response.page_details.next_page_url.is_none() {
-                                tx.send(Event::DevicesFetched(site_uid.clone(), Ok(DevicesResponse {
+                                let _ = tx.send(Event::DevicesFetched(generation, site_uid.clone(), Ok(DevicesResponse {
+                                    page_details: response.page_details,
+                                    devices: all_devices,
+                                    parse_errors,
+                                })));
+                                break;
+                            }
+
+                            let total = response
+                                .page_details
+                                .total_count
+                                .map(|t| t.max(0) as usize)
+                                .or(expected);
+                            let _ = tx.send(Event::DevicesProgress(generation, site_uid.clone(), all_devices.len(), total));
+                            current_page += 1;
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Event::DevicesFetched(generation, site_uid.clone(), Err(e.into())));
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    /// Fetches every device in the account for the reports view, page by page.
+    fn fetch_account_devices(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if let Some(client) = &self.client {
+            let client = client.clone();
+            self.report.loading = true;
+            self.report.progress = None;
+            self.report.last_fetch = Some(std::time::Instant::now());
+            let on_panic = |e| Event::AccountDevicesFetched(Err(e));
+            self.tasks.spawn_fetch("fetch_account_devices", on_panic, async move {
+                let mut all_devices = Vec::new();
+                let mut parse_errors = 0;
+                let mut current_page = 0;
+                let page_size = client.page_size();
+
+                loop {
+                    match client.get_account_devices(current_page, page_size).await {
+                        Ok(response) => {
+                            let count = response.page_len();
+                            parse_errors += response.parse_errors;
+                            all_devices.extend(response.devices);
+
+                            if count < page_size as usize || response.page_details.next_page_url.is_none() {
+                                let _ = tx.send(Event::AccountDevicesFetched(Ok(DevicesResponse {
                                     page_details: response.page_details,
                                     devices: all_devices,
-                                }))).unwrap();
+                                    parse_errors,
+                                })));
                                 break;
                             }
+
+                            let total = response.page_details.total_count.map(|t| t.max(0) as usize);
+                            let _ = tx.send(Event::AccountDevicesProgress(all_devices.len(), total));
                             current_page += 1;
                         }
                         Err(e) => {
-                            tx.send(Event::DevicesFetched(site_uid.clone(), Err(format!("{:#}", e)))).unwrap();
+                            let _ = tx.send(Event::AccountDevicesFetched(Err(e.into())));
                             break;
                         }
                     }
@@ -2173,96 +3970,217 @@ impl App {
     }
 
     fn search_devices(&mut self, query: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        self.popups.device_search_results.clear();
+        self.popups.device_search_pages = 0;
+        self.popups.device_search_total = None;
+        self.popups.device_search_has_more = false;
+        self.popups.device_search_loaded = 0;
+        let (hostname, filter) = split_search_query(&query);
+        match filter {
+            Ok(filter) => {
+                self.popups.device_search_filter = filter;
+                self.fetch_search_page(hostname, 0, tx);
+            }
+            Err(e) => self.popups.device_search_error = Some(e),
+        }
+    }
+
+    /// Loads the next page of the current search onto the results, if there is one.
+    fn search_more_devices(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if self.popups.device_search_has_more && !self.popups.device_search_loading {
+            let (hostname, _) = split_search_query(&self.popups.last_searched_query);
+            self.fetch_search_page(hostname, self.popups.device_search_pages, tx);
+        }
+    }
+
+    /// Runs `query` straight away, e.g. picked from the saved and recent searches.
+    fn run_device_search(&mut self, query: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        self.popups.device_search_query = query.clone();
+        self.popups.last_searched_query = query.clone();
+        self.popups.last_search_input = None;
+        self.search_devices(query, tx);
+    }
+
+    /// Handles typing the name to save the current search under.
+    fn handle_save_search_input(&mut self, key: KeyEvent) {
+        let Some(name) = self.popups.saving_search.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.popups.saving_search = None,
+            KeyCode::Enter if !name.trim().is_empty() => {
+                let name = name.trim().to_string();
+                self.popups.saving_search = None;
+                let query = self.popups.device_search_query.clone();
+                self.search_history.save(&name, &query);
+                self.cache_put(cache::SEARCH_HISTORY_KEY, &self.search_history);
+                self.show_toast(format!("Saved search '{}'", name));
+            }
+            KeyCode::Char(c) => name.push(c),
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn fetch_search_page(
+        &mut self,
+        query: String,
+        page: i32,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
         if let Some(client) = &self.client {
-            self.device_search_loading = true;
-            self.device_search_error = None;
-            self.device_search_results.clear();
-            
-            // Log search trigger
-             let _ = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("debug.log")
-                .map(|mut f| {
-                     use std::io::Write;
-                     writeln!(f, "Triggering API Search for: {}", query).unwrap();
-                });
+            self.popups.device_search_loading = true;
+            self.popups.device_search_error = None;
+
+            tracing::debug!(%query, page, "triggering device search");
 
             let client = client.clone();
-            tokio::spawn(async move {
+            let on_panic = move |e| Event::DeviceSearchResultsFetched(page, Err(e));
+            self.tasks.spawn_fetch("fetch_search_page", on_panic, async move {
                 let result = client
-                    .search_devices(&query)
+                    .search_devices(&query, page, SEARCH_PAGE_SIZE)
                     .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::DeviceSearchResultsFetched(result)).unwrap();
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::DeviceSearchResultsFetched(page, result));
             });
         }
     }
 
     fn fetch_activity_logs(
         &mut self,
-        _device_uid: String,
+        device_uid: String,
         device_id: i32,
         site_id: i32,
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
         if let Some(client) = &self.client {
-            self.activity_logs_loading = true;
-            self.activity_logs_error = None;
-            self.activity_logs.clear();
+            self.device_detail.activity_logs_loading = true;
+            self.device_detail.activity_logs_error = None;
+            self.device_detail.activity_logs.clear();
+            self.device_detail.new_activity_count = 0;
+            self.device_detail.activity_polling = false;
 
-            let client = client.clone();
-            tokio::spawn(async move {
-                // Calculate date range: last 24 hours
-                let now = chrono::Utc::now();
-                let yesterday = now - chrono::Duration::days(1);
-                let from_str = yesterday.format("%Y-%m-%dT%H:%M:%SZ").to_string();
-                let until_str = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
-
-                // Since we cannot filter by device UID directly in the API for this endpoint (based on error message),
-                // we filter by site_id and "device" entity type, then filter in memory for the specific device ID.
-                let result = client
-                    .get_activity_logs(
-                        None,                                  // Page (None = empty/first)
-                        100,                                   // Size (Increase to likely catch the device activity)
-                        Some("desc".to_string()),              // Order
-                        Some(from_str),                        // From (Last 24h)
-                        Some(until_str),                       // Until (Now)
-                        Some(vec!["device".to_string()]),      // Entities: "device" literal
-                        None,                                  // Categories
-                        None,                                  // Actions
-                        Some(vec![site_id]),                   // SiteIds
-                        None,                                  // UserIds
-                    )
-                    .await
-                    .map(|mut response| {
-                        // Client-side filtering for the specific device
-                        response.activities.retain(|log| {
-                            log.device_id == Some(device_id)
-                        });
-                        response
-                    })
-                    .map_err(|e: anyhow::Error| e.to_string());
+            // Last 24 hours; live fetches continue from `now`
+            let now = chrono::Utc::now();
+            self.device_detail.activity_synced_at = Some(now);
+            self.device_detail.activity_polled_at = Some(std::time::Instant::now());
 
-                tx.send(Event::ActivityLogsFetched(result)).unwrap();
+            let client = client.clone();
+            let generation = self.device_detail.fetches.generation();
+            let uid = device_uid.clone();
+            let on_panic = move |e| Event::ActivityLogsFetched(generation, uid, Err(e));
+            let tasks = &self.tasks;
+            self.device_detail.fetches.spawn(tasks, "fetch_activity_logs", on_panic, async move {
+                let from = now - chrono::Duration::days(1);
+                let result = device_activity(client, device_id, site_id, from, now).await;
+                let _ = tx.send(Event::ActivityLogsFetched(generation, device_uid, result));
             });
         }
     }
 
+    /// Fetches the activities of the open device since the last fetch, every
+    /// `ACTIVITY_POLL_INTERVAL` while its detail or an activity is shown.
+    fn poll_activity_logs(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let detail = &self.device_detail;
+        if !matches!(self.current_view, CurrentView::DeviceDetail | CurrentView::ActivityDetail)
+            || detail.activity_logs_loading
+            || detail.activity_polling
+            || detail.activity_polled_at.is_none_or(|at| at.elapsed() < ACTIVITY_POLL_INTERVAL)
+        {
+            return;
+        }
+        let (Some(client), Some(device), Some(synced_at)) =
+            (self.client.clone(), detail.selected_device.as_ref(), detail.activity_synced_at)
+        else {
+            return;
+        };
+        let (device_uid, device_id, site_id) = (device.uid.clone(), device.id, device.site_id);
+        self.device_detail.activity_polling = true;
+        self.device_detail.activity_polled_at = Some(std::time::Instant::now());
+
+        let now = chrono::Utc::now();
+        // Overlap the last range a little for activities recorded late; the
+        // merge drops the ones already listed
+        let from = synced_at - ACTIVITY_POLL_OVERLAP;
+        let generation = self.device_detail.fetches.generation();
+        let uid = device_uid.clone();
+        let on_panic = move |e| Event::ActivityLogsPolled(generation, uid, now, Err(e));
+        self.device_detail.fetches.spawn(&self.tasks, "poll_activity_logs", on_panic, async move {
+            let result = device_activity(client, device_id, site_id, from, now).await;
+            let _ = tx.send(Event::ActivityLogsPolled(generation, device_uid, now, result));
+        });
+    }
+
+    /// Fetches the device's alerts resolved in the last `resolved_alert_days`.
+    /// They come most recent first, so paging stops at a page with none in
+    /// the window.
+    pub fn fetch_resolved_alerts(
+        &mut self,
+        device_uid: String,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.device_detail.resolved_alerts_loading = true;
+        self.device_detail.resolved_alerts_error = None;
+        self.device_detail.resolved_alerts.clear();
+
+        let lookback = chrono::Duration::days(i64::from(self.resolved_alert_days));
+        let since = chrono::Utc::now() - lookback;
+        let generation = self.device_detail.fetches.generation();
+        let uid = device_uid.clone();
+        let on_panic = move |e| Event::ResolvedAlertsFetched(generation, uid, Err(e));
+        let tasks = &self.tasks;
+        self.device_detail.fetches.spawn(tasks, "fetch_resolved_alerts", on_panic, async move {
+            let mut all_alerts = Vec::new();
+            let mut current_page = 0;
+            let page_size = client.page_size();
+
+            let result = loop {
+                let page = client.get_device_resolved_alerts(&device_uid, current_page, page_size);
+                match page.await {
+                    Ok(response) => {
+                        let count = response.alerts.len();
+                        let before = all_alerts.len();
+                        all_alerts.extend(
+                            response.alerts.into_iter().filter(|a| resolved_since(a, since)),
+                        );
+
+                        let last_page = count < page_size as usize
+                            || response.page_details.next_page_url.is_none();
+                        if last_page || all_alerts.len() == before {
+                            break Ok(all_alerts);
+                        }
+                        current_page += 1;
+                    }
+                    Err(e) => break Err(e.into()),
+                }
+            };
+            let _ = tx.send(Event::ResolvedAlertsFetched(generation, device_uid, result));
+        });
+    }
+
     pub fn fetch_open_alerts(
         &mut self,
         device_uid: String,
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
         if let Some(client) = self.client.clone() {
-            self.open_alerts_loading = true;
-            self.open_alerts_error = None;
-            self.open_alerts.clear();
+            self.device_detail.open_alerts_loading = true;
+            self.device_detail.open_alerts_error = None;
+            self.device_detail.open_alerts.clear();
             
-            tokio::spawn(async move {
+            let generation = self.device_detail.fetches.generation();
+            let uid = device_uid.clone();
+            let on_panic = move |e| Event::OpenAlertsFetched(generation, uid, Err(e));
+            let tasks = &self.tasks;
+            self.device_detail.fetches.spawn(tasks, "fetch_open_alerts", on_panic, async move {
                 let mut all_alerts = Vec::new();
                 let mut current_page = 0;
-                let page_size = 250;
+                let page_size = client.page_size();
 
                 loop {
                     match client.get_device_open_alerts(&device_uid, current_page, page_size).await {
@@ -2271,13 +4189,13 @@ impl App {
                             all_alerts.extend(response.alerts);
                             
                             if count < page_size as usize || response.page_details.next_page_url.is_none() {
-                                tx.send(Event::OpenAlertsFetched(device_uid, Ok(all_alerts))).unwrap();
+                                let _ = tx.send(Event::OpenAlertsFetched(generation, device_uid, Ok(all_alerts)));
                                 break;
                             }
                             current_page += 1;
                         }
                         Err(e) => {
-                            tx.send(Event::OpenAlertsFetched(device_uid, Err(e.to_string()))).unwrap();
+                            let _ = tx.send(Event::OpenAlertsFetched(generation, device_uid, Err(e.into())));
                             break;
                         }
                     }
@@ -2292,14 +4210,18 @@ impl App {
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
         if let Some(client) = self.client.clone() {
-            self.site_open_alerts_loading = true;
-            self.site_open_alerts_error = None;
-            self.site_open_alerts.clear();
-
-            tokio::spawn(async move {
+            self.site_detail.site_open_alerts_loading = true;
+            self.site_detail.site_open_alerts_error = None;
+            self.site_detail.site_open_alerts.clear();
+
+            let generation = self.site_detail.fetches.generation();
+            let uid = site_uid.clone();
+            let on_panic = move |e| Event::SiteOpenAlertsFetched(generation, uid, Err(e));
+            let tasks = &self.tasks;
+            self.site_detail.fetches.spawn(tasks, "fetch_site_open_alerts", on_panic, async move {
                 let mut all_alerts = Vec::new();
                 let mut current_page = 0;
-                let page_size = 250;
+                let page_size = client.page_size();
 
                 loop {
                     match client.get_site_open_alerts(&site_uid, current_page, page_size).await {
@@ -2308,13 +4230,13 @@ impl App {
                             all_alerts.extend(response.alerts);
                             
                             if count < page_size as usize || response.page_details.next_page_url.is_none() {
-                                tx.send(Event::SiteOpenAlertsFetched(site_uid, Ok(all_alerts))).unwrap();
+                                let _ = tx.send(Event::SiteOpenAlertsFetched(generation, site_uid, Ok(all_alerts)));
                                 break;
                             }
                             current_page += 1;
                         }
                         Err(e) => {
-                            tx.send(Event::SiteOpenAlertsFetched(site_uid, Err(e.to_string()))).unwrap();
+                            let _ = tx.send(Event::SiteOpenAlertsFetched(generation, site_uid, Err(e.into())));
                             break;
                         }
                     }
@@ -2330,64 +4252,189 @@ impl App {
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
         if let Some(client) = &self.client {
-            self.job_result_loading = true;
-            self.job_result_error = None;
-            self.selected_job_result = None;
-            self.selected_job_row_index = 0; // Reset index
+            self.device_detail.job_result_loading = true;
+            self.device_detail.job_result_error = None;
+            self.device_detail.selected_job_result = None;
+            self.device_detail.selected_job_row_index = 0; // Reset index
+            self.device_detail.collapsed_job_components.clear();
+            self.device_detail.job_rows_list_state = ListState::default();
 
             let client = client.clone();
-            tokio::spawn(async move {
+            let generation = self.device_detail.fetches.generation();
+            let on_panic = move |e| Event::JobResultFetched(generation, Err(e));
+            self.device_detail.fetches.spawn(&self.tasks, "fetch_job_result", on_panic, async move {
                 let result = client
                     .get_job_result(&job_uid, &device_uid)
                     .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::JobResultFetched(result)).unwrap();
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::JobResultFetched(generation, result));
             });
         }
     }
 
+    /// Fetches the StdOut of the job and shows the part of `component`.
     fn fetch_job_stdout(
         &mut self,
         job_uid: String,
         device_uid: String,
+        component: &ComponentResult,
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
+        let Some(component_uid) = component.component_uid.clone() else {
+            self.popups.open(Popup::JobOutput);
+            self.popups.popup_title = "StdOut".to_string();
+            self.popups.popup_content = "Component UID missing".to_string();
+            return;
+        };
         if let Some(client) = &self.client {
-            self.popup_loading = true;
-            self.show_popup = true;
-            self.popup_title = "StdOut".to_string();
-            self.popup_content = "Loading...".to_string();
+            self.popups.popup_loading = true;
+            self.popups.open(Popup::JobOutput);
+            self.popups.popup_title = match &component.component_name {
+                Some(name) => format!("StdOut: {}", name),
+                None => "StdOut".to_string(),
+            };
+            self.popups.popup_content = "Loading...".to_string();
 
             let client = client.clone();
-            tokio::spawn(async move {
+            let on_panic = {
+                let component_uid = component_uid.clone();
+                move |e| Event::JobStdOutFetched(component_uid, Err(e))
+            };
+            self.tasks.spawn_fetch("fetch_job_stdout", on_panic, async move {
                 let result = client
                     .get_job_stdout(&job_uid, &device_uid)
                     .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::JobStdOutFetched(result)).unwrap();
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::JobStdOutFetched(component_uid, result));
             });
         }
     }
 
+    /// Shows the `kind` output of one component from the outputs of its job.
+    fn show_job_output(
+        &mut self,
+        component_uid: &str,
+        result: Result<Vec<JobStdOutput>, ApiError>,
+        kind: &str,
+    ) {
+        self.popups.popup_loading = false;
+        self.popups.popup_content = match result {
+            Ok(outputs) => match outputs
+                .into_iter()
+                .find(|o| o.component_uid.as_deref() == Some(component_uid))
+            {
+                Some(output) => output.std_data.unwrap_or_else(|| format!("No {} data", kind)),
+                None => format!("No {} found for this component", kind),
+            },
+            Err(e) => format!("Error: {}", e),
+        };
+    }
+
+    /// Starts following the output of the selected job row while its job is
+    /// still running, or stops following.
+    fn toggle_job_follow(&mut self) {
+        if self.popups.job_follow.take().is_some() {
+            return;
+        }
+        let Some(job_result) = &self.device_detail.selected_job_result else {
+            return;
+        };
+        if is_finished(job_result.job_deployment_status.as_deref().unwrap_or("")) {
+            return;
+        }
+        let (Some(job_uid), Some(device_uid)) =
+            (job_result.job_uid.clone(), job_result.device_uid.clone())
+        else {
+            return;
+        };
+        // A running component may not have reported output yet, so its header follows StdOut
+        let (index, stderr) = match self.device_detail.selected_job_row() {
+            Some(JobViewRow::ComponentHeader(i) | JobViewRow::StdOutLink(i)) => (i, false),
+            Some(JobViewRow::StdErrLink(i)) => (i, true),
+            None => return,
+        };
+        let Some(component_uid) = job_result
+            .component_results
+            .as_ref()
+            .and_then(|c| c.get(index))
+            .and_then(|c| c.component_uid.clone())
+        else {
+            return;
+        };
+        if !self.popups.is_open(Popup::JobOutput) {
+            self.popups.open(Popup::JobOutput);
+            self.popups.popup_loading = true;
+            self.popups.popup_title = if stderr { "StdErr" } else { "StdOut" }.to_string();
+            self.popups.popup_content = "Loading...".to_string();
+        }
+        self.popups.job_follow = Some(JobFollow {
+            poll: JobPoll::new(job_uid, device_uid),
+            component_uid,
+            stderr,
+        });
+    }
+
+    /// Fetches the followed job output again when the next poll is due.
+    fn follow_job_output(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let (Some(client), Some(follow)) = (&self.client, self.popups.job_follow.as_mut()) else {
+            return;
+        };
+        if !follow.poll.tick() {
+            return;
+        }
+        let client = client.clone();
+        let (job_uid, device_uid) = (follow.poll.job_uid.clone(), follow.poll.device_uid.clone());
+        let stderr = follow.stderr;
+        self.tasks.spawn("follow_job_output", async move {
+            let result = async {
+                let job_result = client.get_job_result(&job_uid, &device_uid).await?;
+                let outputs = if stderr {
+                    client.get_job_stderr(&job_uid, &device_uid).await?
+                } else {
+                    client.get_job_stdout(&job_uid, &device_uid).await?
+                };
+                anyhow::Ok((job_result, outputs))
+            }
+            .await
+            .map_err(ApiError::from);
+            let _ = tx.send(Event::JobOutputFollowed(job_uid, result));
+        });
+    }
+
+    /// Fetches the StdErr of the job and shows the part of `component`.
     fn fetch_job_stderr(
         &mut self,
         job_uid: String,
         device_uid: String,
+        component: &ComponentResult,
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
+        let Some(component_uid) = component.component_uid.clone() else {
+            self.popups.open(Popup::JobOutput);
+            self.popups.popup_title = "StdErr".to_string();
+            self.popups.popup_content = "Component UID missing".to_string();
+            return;
+        };
         if let Some(client) = &self.client {
-            self.popup_loading = true;
-            self.show_popup = true;
-            self.popup_title = "StdErr".to_string();
-            self.popup_content = "Loading...".to_string();
+            self.popups.popup_loading = true;
+            self.popups.open(Popup::JobOutput);
+            self.popups.popup_title = match &component.component_name {
+                Some(name) => format!("StdErr: {}", name),
+                None => "StdErr".to_string(),
+            };
+            self.popups.popup_content = "Loading...".to_string();
 
             let client = client.clone();
-            tokio::spawn(async move {
+            let on_panic = {
+                let component_uid = component_uid.clone();
+                move |e| Event::JobStdErrFetched(component_uid, Err(e))
+            };
+            self.tasks.spawn_fetch("fetch_job_stderr", on_panic, async move {
                 let result = client
                     .get_job_stderr(&job_uid, &device_uid)
                     .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::JobStdErrFetched(result)).unwrap();
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::JobStdErrFetched(component_uid, result));
             });
         }
     }
@@ -2399,1593 +4446,2114 @@ impl App {
     ) {
         if let Some(client) = &self.client {
             let client = client.clone();
-            tokio::spawn(async move {
+            let on_panic = {
+                let site_uid = site_uid.clone();
+                move |e| Event::SiteVariablesFetched(site_uid, Err(e))
+            };
+            self.tasks.spawn_fetch("fetch_site_variables", on_panic, async move {
                 let result = client
                     .get_site_variables(&site_uid)
                     .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::SiteVariablesFetched(site_uid, result))
-                    .unwrap();
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::SiteVariablesFetched(site_uid, result));
             });
         }
     }
 
-    fn fetch_sophos_cases(
-        &self,
-        tenant_id: String,
-        data_region: Option<String>,
-        tx: tokio::sync::mpsc::UnboundedSender<Event>,
-    ) {
-        if let Some(client) = &self.sophos_client {
-            let client = client.clone();
-            let t_id = tenant_id.clone();
-            tokio::spawn(async move {
-                // First get tenant to find data region IF not provided
-                let cases_result = async {
-                    let region = if let Some(r) = data_region {
-                        r
-                    } else {
-                        let tenant = client.get_tenant(&t_id).await?;
-                        tenant.data_region
-                    };
-
-                    let cases = client.get_cases(&t_id, &region).await?;
-                    Ok(cases)
-                }
+    fn fetch_account_variables(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.account_variables.loading = true;
+        let on_panic = |e| Event::AccountVariablesFetched(Err(e));
+        self.tasks.spawn_fetch("fetch_account_variables", on_panic, async move {
+            let result = client
+                .get_account_variables()
                 .await
-                .map_err(|e: anyhow::Error| e.to_string());
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::AccountVariablesFetched(result));
+        });
+    }
 
-                tx.send(Event::SophosCasesFetched(tenant_id, cases_result))
-                    .unwrap();
-            });
+    /// Loads the Sophos cases of a site mapped to Sophos, own or inherited,
+    /// for its incident counts.
+    fn fetch_site_mdr_cases(&self, site: &Site, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let mapping = MdrMapping::resolve(site, &self.security.mdr_defaults);
+        if mapping.is_sophos()
+            && let Some(tenant_id) = mapping.id()
+        {
+            // A known region saves the tenant lookup
+            self.fetch_sophos_cases(tenant_id.to_string(), mapping.region().map(String::from), tx);
         }
     }
 
-    fn fetch_sophos_endpoint(
-        &mut self,
+    fn fetch_sophos_cases(
+        &self,
         tenant_id: String,
         data_region: Option<String>,
-        hostname: String,
         tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
-        if self.sophos_endpoints.contains_key(&hostname) {
-            // Already have data? Maybe refresh? For now, if we have it, skip or always fetch?
-            // Let's always fetch to be safe or maybe check if we want to cache.
-            // The instructions say "if the antivirus name contains Sophos...".
-            // Implementation: Always fetch for now as this is called via user action or specific criteria.
-        }
-
         if let Some(client) = &self.sophos_client {
             let client = client.clone();
             let t_id = tenant_id.clone();
-            let h_name = hostname.clone();
-
-            // Set loading
-            self.sophos_loading.insert(hostname.clone(), true);
-
-            tokio::spawn(async move {
-                let endpoints_result = async {
+            let on_panic = {
+                let tenant_id = tenant_id.clone();
+                move |e| Event::SophosCasesFetched(tenant_id, Err(e))
+            };
+            self.tasks.spawn_fetch("fetch_sophos_cases", on_panic, async move {
+                // First get tenant to find data region IF not provided
+                let cases_result = async {
                     let region = if let Some(r) = data_region {
                         r
                     } else {
-                        // We might need to fetch tenant to get region if not passed.
-                        // However in the calling code (handle_key_event) we might not have region easily if we don't have variables.
-                        // But we plan to look up from variables.
                         let tenant = client.get_tenant(&t_id).await?;
                         tenant.data_region
                     };
 
-                    let endpoints = client.get_endpoints(&t_id, &region, &h_name).await?;
-                    Ok(endpoints)
+                    let cases = client.get_cases(&t_id, &region).await?;
+                    anyhow::Ok(cases)
                 }
                 .await
-                .map_err(|e: anyhow::Error| e.to_string());
+                .map_err(ApiError::from);
 
-                tx.send(Event::SophosEndpointsFetched(h_name, endpoints_result))
-                    .unwrap();
+                let _ = tx.send(Event::SophosCasesFetched(tenant_id, cases_result));
             });
         }
     }
 
-    fn fetch_datto_av_agent(
-        &mut self,
-        hostname: String,
-        udf: Option<crate::api::datto::types::Udf>,
-        tx: tokio::sync::mpsc::UnboundedSender<Event>,
-    ) {
-        if let Some(client) = &self.datto_av_client {
-            let client = client.clone();
-            let h_name = hostname.clone();
-
-            // Check UDF 30 for ID
-            let agent_id = udf.as_ref().and_then(|u| u.udf30.clone());
-
-            self.datto_av_loading.insert(hostname.clone(), true);
+    fn handle_key_event(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        tracing::trace!(code = ?key.code, mode = ?self.input_state.mode, "key event");
 
-            tokio::spawn(async move {
-                let result = async {
-                    if let Some(id) = agent_id {
-                        if !id.is_empty() {
-                            match client.get_agent_detail(&id).await {
-                                Ok(agent) => return Ok(agent),
-                                Err(_) => {
-                                    // Ignored error (likely ID mismatch or network glitch), falling back to hostname search
-                                }
-                            }
-                        }
+        self.sync_popups();
+        match self.popups.top() {
+            Some(Popup::SessionRestore) => {
+                if let Some(session) = self.popups.pending_session.take() {
+                    match key.code {
+                        KeyCode::Enter | KeyCode::Char('y') => self.restore_session(session, tx),
+                        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {}
+                        _ => self.popups.pending_session = Some(session),
                     }
-                    // Fallback to filter search by hostname
-                    let agents = client.get_agent_details(&h_name).await?;
-                    // Assuming we want the first match if any
-                    agents
-                        .into_iter()
-                        .next()
-                        .ok_or_else(|| anyhow::anyhow!("No agent found"))
                 }
-                .await
-                .map_err(|e: anyhow::Error| e.to_string());
-
-                tx.send(Event::DattoAvAgentFetched(h_name, result)).unwrap();
-            });
-        }
-    }
-
-    fn fetch_datto_av_alerts(
-        &self,
-        agent_id: String,
-        hostname: String,
-        tx: tokio::sync::mpsc::UnboundedSender<Event>,
-    ) {
-        if let Some(client) = &self.datto_av_client {
-            let client = client.clone();
-            tokio::spawn(async move {
-                let result = client
-                    .get_agent_alerts(&agent_id)
-                    .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::DattoAvAlertsFetched(hostname, result))
-                    .unwrap();
-            });
-        }
-    }
-
-    fn fetch_datto_av_policies(
-        &self,
-        agent_id: String,
-        hostname: String,
-        tx: tokio::sync::mpsc::UnboundedSender<Event>,
-    ) {
-        if let Some(client) = &self.datto_av_client {
-            let client = client.clone();
-            tokio::spawn(async move {
-                let result = client
-                    .get_agent_policies(&agent_id)
-                    .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::DattoAvPoliciesFetched(hostname, result))
-                    .unwrap();
-            });
+            }
+            Some(Popup::Confirmation) => self.handle_confirmation_input(key, tx),
+            Some(Popup::RunComponent) => self.handle_run_component_input(key, tx),
+            Some(Popup::QuickActions) => self.handle_quick_action_input(key, tx),
+            Some(Popup::Warranty) => self.handle_warranty_input(key, tx),
+            Some(Popup::DeviceEdit) => self.handle_device_edit_input(key, tx),
+            Some(Popup::Decommission) => self.handle_decommission_input(key, tx),
+            Some(Popup::SiteDeletion) => self.handle_site_deletion_input(key, tx),
+            Some(Popup::NetTools) => self.handle_net_tools_input(key, tx),
+            Some(Popup::SiteMove) => self.handle_site_move_input(key, tx),
+            Some(Popup::VariableCopy) => self.handle_variable_copy_input(key, tx),
+            Some(Popup::ProfileSwitcher) => self.handle_profile_switcher_input(key, tx),
+            Some(Popup::LogViewer) => self.handle_log_viewer_input(key),
+            Some(Popup::ErrorHistory) => self.handle_error_history_input(key),
+            Some(Popup::RecentDevices) => self.handle_recent_devices_input(key, tx),
+            Some(Popup::Scans) => self.handle_scans_input(key),
+            Some(Popup::AddToCollection) => self.handle_add_to_collection_input(key),
+            Some(Popup::Notifications) => self.handle_notifications_input(key),
+            Some(Popup::NotesViewer) => self.handle_notes_viewer_input(key),
+            Some(Popup::VariableTemplates) => self.handle_variable_templates_input(key, tx),
+            Some(Popup::Reboot) => self.handle_reboot_input(key, tx),
+            Some(Popup::DeviceSearch) if self.popups.saving_search.is_some() => {
+                self.handle_save_search_input(key)
+            }
+            Some(Popup::DeviceSearch) => self.handle_device_search_input(key, tx),
+            Some(Popup::Input) => self.handle_input_modal(key, tx),
+            Some(Popup::AlertDetail) => self.handle_alert_detail_input(key, tx),
+            // Overlays of a view are driven by its reducer
+            Some(Popup::JobOutput | Popup::DeviceVariables) | None => self.handle_view_key(key, tx),
         }
+        self.sync_popups();
     }
 
-    #[allow(dead_code)]
-    fn scan_datto_av_agent(
-        &mut self,
-        agent_id: String,
-        hostname: String,
-        tx: tokio::sync::mpsc::UnboundedSender<Event>,
-    ) {
-        if let Some(client) = &self.datto_av_client {
-            self.scan_status
-                .insert(hostname.clone(), crate::event::ScanStatus::Starting);
-            let client = client.clone();
-            tokio::spawn(async move {
-                let result = client
-                    .scan_agent(&agent_id)
-                    .await
-                    .map_err(|e: anyhow::Error| e.to_string());
-                tx.send(Event::DattoAvScanStarted(hostname, result))
-                    .unwrap();
-            });
+    /// Puts the popups that show while their state is set (an open input,
+    /// a pending confirmation, ...) on the popup stack, or takes them off
+    /// once it's cleared.
+    fn sync_popups(&mut self) {
+        let open = [
+            (Popup::Input, self.input_state.mode == InputMode::Editing),
+            (Popup::AlertDetail, self.popups.alert_detail.is_some()),
+            (Popup::Decommission, self.popups.decommission.is_some()),
+            (Popup::SiteDeletion, self.popups.site_deletion.is_some()),
+            (Popup::NetTools, self.popups.net_tools.is_some()),
+            (Popup::VariableCopy, self.popups.variable_copy.is_some()),
+            (Popup::SessionRestore, self.popups.pending_session.is_some()),
+            // Last, so it lands on top of the popup that asked for it
+            (Popup::Confirmation, self.popups.confirmation.is_some()),
+        ];
+        for (popup, open) in open {
+            self.popups.set_open(popup, open);
         }
     }
 
-    #[allow(dead_code)]
-    fn scan_sophos_endpoint(
-        &mut self,
-        endpoint_id: String,
-        hostname: String,
-        tx: tokio::sync::mpsc::UnboundedSender<Event>,
-    ) {
-        if let Some(device) = &self.selected_device {
-            // We need tenant ID and region.
-            if let Some(site) = self.sites.iter().find(|s| s.uid == device.site_uid) {
-                if let Some(vars) = &site.variables {
-                    if let Some(id_var) = vars.iter().find(|v| v.name == "tuiMdrId") {
-                        let region = vars
-                            .iter()
-                            .find(|v| v.name == "tuiMdrRegion")
-                            .map(|v| v.value.clone());
-
-                        if let Some(client) = &self.sophos_client {
-                            let client = client.clone();
-                            let t_id = id_var.value.clone();
-                            self.scan_status
-                                .insert(hostname.clone(), crate::event::ScanStatus::Starting);
-
-                            tokio::spawn(async move {
-                                let result = async {
-                                    let region = if let Some(r) = region {
-                                        r
-                                    } else {
-                                        let tenant = client.get_tenant(&t_id).await?;
-                                        tenant.data_region
-                                    };
-                                    client.start_scan(&t_id, &region, &endpoint_id).await
-                                }
-                                .await
-                                .map_err(|e: anyhow::Error| e.to_string());
-
-                                tx.send(Event::SophosScanStarted(hostname, result)).unwrap();
-                            });
-                        }
+    /// The input modal for variables, settings, UDFs and notes.
+    fn handle_input_modal(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        match key.code {
+            KeyCode::Esc => {
+                self.input_state.mode = InputMode::Normal;
+                self.input_state.acknowledging = None;
+                self.site_detail.is_choosing_udf_columns = false;
+                self.site_detail.editing_udf_cell = None;
+                self.device_detail.editing_tags = false;
+            }
+            KeyCode::Enter => {
+                // Check if we are editing a setting or a variable
+                if let Some((kind, item_id)) = self.input_state.acknowledging.take() {
+                    let note = self.input_state.name_buffer.trim().to_string();
+                    self.set_acknowledged(kind, &item_id, Some(note));
+                } else if let Some(field) = self.input_state.editing_setting {
+                    // Update the corresponding field in site_edit_state from the buffer
+                    if let Some(value) = self.site_detail.site_edit_state.text_mut(field) {
+                        *value = self.input_state.name_buffer.clone();
                     }
+                    self.submit_site_update(tx);
+                } else if std::mem::take(&mut self.site_detail.is_choosing_udf_columns) {
+                    self.submit_udf_columns();
+                } else if let Some((device_uid, number)) =
+                    self.site_detail.editing_udf_cell.take()
+                {
+                    let value = self.input_state.value_buffer.clone();
+                    self.submit_udf(device_uid, number, value, tx);
+                } else if let Some(_) = self.device_detail.editing_udf_index {
+                    // UDF Submit
+                    self.submit_device_udf(tx);
+                } else if self.current_view == CurrentView::AccountVariables {
+                    self.submit_account_variable(tx);
+                } else {
+                    // Variable Submit
+                    self.submit_variable(tx);
+                }
+                self.input_state.mode = InputMode::Normal;
+            }
+            KeyCode::Tab => {
+                // Switch field
+                // Only switch if NOT editing a UDF (UDFs are single value only)
+                if self.device_detail.editing_udf_index.is_none()
+                    && self.site_detail.editing_udf_cell.is_none()
+                    && !self.site_detail.is_choosing_udf_columns
+                {
+                    self.input_state.active_field = match self.input_state.active_field {
+                        InputField::Name => InputField::Value,
+                        InputField::Value => InputField::Name,
+                        // No tab switching for simple single-field settings edits for now, keep it simple
+                        _ => self.input_state.active_field,
+                    };
                 }
             }
+            KeyCode::Backspace => {
+                match self.input_state.active_field {
+                    InputField::Name
+                    | InputField::SiteName
+                    | InputField::SiteDescription
+                    | InputField::SiteNotes
+                    | InputField::SiteSetting => {
+                        self.input_state.name_buffer.pop();
+                    }
+                    InputField::Value => {
+                        self.input_state.value_buffer.pop();
+                    }
+                };
+            }
+            // Number settings only take digits
+            KeyCode::Char(c)
+                if !c.is_ascii_digit()
+                    && self
+                        .input_state
+                        .editing_setting
+                        .is_some_and(|field| field.kind() == SettingKind::Number) => {}
+            KeyCode::Char(c) => {
+                match self.input_state.active_field {
+                    InputField::Name
+                    | InputField::SiteName
+                    | InputField::SiteDescription
+                    | InputField::SiteNotes
+                    | InputField::SiteSetting => {
+                        self.input_state.name_buffer.push(c);
+                    }
+                    InputField::Value => {
+                        self.input_state.value_buffer.push(c);
+                    }
+                };
+            }
+            _ => {}
         }
     }
 
-    fn handle_key_event(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        // DEBUG LOG
-        /*
-        let _ = std::fs::OpenOptions::new().create(true).append(true).open("debug.log").map(|mut f| {
-             use std::io::Write;
-             writeln!(f, "Key Event: {:?} | Mode: {:?}", key.code, self.input_state.mode).unwrap();
-        });
-        */
-        
-        // Handle Run Component Input
-        if self.show_run_component {
-            self.handle_run_component_input(key, tx);
+    /// Keys for the current view when no popup takes them.
+    fn handle_view_key(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        // Component filter captures typing while active
+        if self.components.is_filtering && self.current_view == CurrentView::Components {
+            self.handle_component_filter_input(key);
             return;
         }
 
-        if self.show_quick_actions {
-            self.handle_quick_action_input(key, tx);
+        // Site list filter captures typing while active
+        if self.site_list.is_filtering && self.current_view == CurrentView::List {
+            self.handle_site_filter_input(key);
             return;
         }
 
-        if self.show_warranty_popup {
-            self.handle_warranty_input(key, tx);
+        // Device filter bar captures typing while active
+        if self.site_detail.is_device_filtering && self.current_view == CurrentView::Detail {
+            self.handle_device_filter_input(key);
             return;
         }
 
-        if self.show_site_move {
-            self.handle_site_move_input(key, tx);
+        // Software search captures typing while active
+        if self.device_detail.is_software_searching
+            && self.current_view == CurrentView::DeviceDetail
+            && self.device_detail.device_detail_tab == DeviceDetailTab::Software
+        {
+            self.handle_software_search_input(key);
             return;
         }
 
-        if self.show_reboot_popup {
-            self.handle_reboot_input(key, tx);
-            return;
+        if let Some(action) = action::keymap(self.current_view, key) {
+            self.dispatch(action, tx);
         }
+    }
 
-        // Handle Device Search Input
-        if self.show_device_search {
-            self.handle_device_search_input(key, tx);
-            return;
+    fn handle_site_filter_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.site_list.is_filtering = false;
+                self.site_list.filter_query.clear();
+                self.site_list.refresh_visible();
+            }
+            KeyCode::Enter => {
+                self.site_list.is_filtering = false;
+            }
+            KeyCode::Char(c) => {
+                self.site_list.filter_query.push(c);
+                self.site_list.refresh_visible();
+            }
+            KeyCode::Backspace => {
+                self.site_list.filter_query.pop();
+                self.site_list.refresh_visible();
+            }
+            _ => {}
         }
+    }
 
-        // Handle Input Mode first
-        if self.input_state.mode == InputMode::Editing {
-            match key.code {
-                KeyCode::Esc => {
-                    self.input_state.mode = InputMode::Normal;
-                }
-                KeyCode::Enter => {
-                    // Check if we are editing a setting or a variable
-                    if let Some(field) = self.input_state.editing_setting {
-                        // Update the corresponding field in site_edit_state from the buffer
-                        match field {
-                            SiteEditField::Name => {
-                                self.site_edit_state.name = self.input_state.name_buffer.clone()
-                            }
-                            SiteEditField::Description => {
-                                self.site_edit_state.description =
-                                    self.input_state.name_buffer.clone()
-                            }
-                            SiteEditField::Notes => {
-                                self.site_edit_state.notes = self.input_state.name_buffer.clone()
-                            }
-                        }
-                        self.submit_site_update(tx);
-                    } else if let Some(_) = self.editing_udf_index {
-                        // UDF Submit
-                        self.submit_device_udf(tx);
-                    } else {
-                        // Variable Submit
-                        self.submit_variable(tx);
-                    }
-                    self.input_state.mode = InputMode::Normal;
-                }
-                KeyCode::Tab => {
-                    // Switch field
-                    // Only switch if NOT editing a UDF (UDFs are single value only)
-                    if self.editing_udf_index.is_none() {
-                        self.input_state.active_field = match self.input_state.active_field {
-                            InputField::Name => InputField::Value,
-                            InputField::Value => InputField::Name,
-                            // No tab switching for simple single-field settings edits for now, keep it simple
-                            _ => self.input_state.active_field,
-                        };
-                    }
-                }
-                KeyCode::Backspace => {
-                    match self.input_state.active_field {
-                        InputField::Name
-                        | InputField::SiteName
-                        | InputField::SiteDescription
-                        | InputField::SiteNotes => {
-                            self.input_state.name_buffer.pop();
-                        }
-                        InputField::Value => {
-                            self.input_state.value_buffer.pop();
-                        }
-                    };
-                }
-                KeyCode::Char(c) => {
-                    match self.input_state.active_field {
-                        InputField::Name
-                        | InputField::SiteName
-                        | InputField::SiteDescription
-                        | InputField::SiteNotes => {
-                            self.input_state.name_buffer.push(c);
-                        }
-                        InputField::Value => {
-                            self.input_state.value_buffer.push(c);
-                        }
-                    };
-                }
-                _ => {}
+    fn handle_component_filter_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.components.is_filtering = false;
+                self.components.filter_query.clear();
+                self.components.refresh_visible();
             }
-            return;
+            KeyCode::Enter => {
+                self.components.is_filtering = false;
+            }
+            KeyCode::Char(c) => {
+                self.components.filter_query.push(c);
+                self.components.refresh_visible();
+            }
+            KeyCode::Backspace => {
+                self.components.filter_query.pop();
+                self.components.refresh_visible();
+            }
+            _ => {}
         }
+    }
 
+    fn handle_device_filter_input(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Char('/') => {
-                if self.current_view == CurrentView::DeviceDetail && self.device_detail_tab == DeviceDetailTab::Software {
-                    self.is_software_searching = true;
-                    self.software_search_query.clear();
-                    self.filter_software();
-                } else {
-                    self.show_device_search = true;
-                    self.device_search_query.clear();
-                    self.device_search_results.clear();
-                    self.last_search_input = None;
-                    self.last_searched_query.clear();
-                    self.device_search_error = None;
-                }
-                return;
+            KeyCode::Esc => {
+                self.site_detail.is_device_filtering = false;
+                self.site_detail.device_filter_query.clear();
+                self.site_detail.apply_device_filter();
+            }
+            KeyCode::Enter => {
+                self.site_detail.is_device_filtering = false;
+            }
+            KeyCode::Char(c) => {
+                self.site_detail.device_filter_query.push(c);
+                self.site_detail.apply_device_filter();
+            }
+            KeyCode::Backspace => {
+                self.site_detail.device_filter_query.pop();
+                self.site_detail.apply_device_filter();
             }
             _ => {}
         }
+    }
 
-        match self.current_view {
-            CurrentView::List => match key.code {
-                KeyCode::Char('q') => self.should_quit = true,
-                KeyCode::Char('j') | KeyCode::Down => self.next_row(),
-                KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
-                KeyCode::Char('r') => {
-                    self.fetch_sites(tx);
-                }
-                KeyCode::Enter => {
-                    if let Some(idx) = self.table_state.selected() {
-                        self.navigate_to_site_detail(idx, tx);
+    fn handle_software_search_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.device_detail.is_software_searching = false;
+                self.device_detail.software_search_query.clear();
+                self.device_detail.filter_software();
+            }
+            KeyCode::Enter => {
+                self.device_detail.is_software_searching = false;
+            }
+            KeyCode::Char(c) => {
+                self.device_detail.software_search_query.push(c);
+                self.device_detail.filter_software();
+            }
+            KeyCode::Backspace => {
+                self.device_detail.software_search_query.pop();
+                self.device_detail.filter_software();
+            }
+            _ => {}
+        }
+    }
+
+    fn open_create_variable_modal(&mut self) {
+        self.input_state = InputState {
+            mode: InputMode::Editing,
+            name_buffer: String::new(),
+            value_buffer: String::new(),
+            active_field: InputField::Name,
+            is_creating: true,
+            editing_variable_id: None,
+            editing_setting: None,
+            acknowledging: None,
+        };
+    }
+
+    fn open_edit_variable_modal(&mut self) {
+        if let Some(idx) = self.site_detail.variables_table_state.selected() {
+            if let Some(site) = self.site_list.selected_site() {
+                if let Some(vars) = &site.variables {
+                    if let Some(var) = vars.get(idx).cloned() {
+                        self.open_variable_modal(&var);
                     }
                 }
-                _ => {}
-            },
-            CurrentView::Detail => match key.code {
-                KeyCode::Esc | KeyCode::Char('q') => {
-                    self.current_view = CurrentView::List;
-                }
-                KeyCode::Tab => {
-                    self.detail_tab = match self.detail_tab {
-                        SiteDetailTab::Devices => SiteDetailTab::Alerts,
-                        SiteDetailTab::Alerts => SiteDetailTab::Variables,
-                        SiteDetailTab::Variables => SiteDetailTab::Settings,
-                        SiteDetailTab::Settings => SiteDetailTab::Devices,
+            }
+        }
+    }
+
+    /// Opens the input modal on an existing site or account variable.
+    fn open_variable_modal(&mut self, var: &SiteVariable) {
+        tracing::debug!(name = %var.name, "opening variable edit modal");
+        self.input_state = InputState {
+            mode: InputMode::Editing,
+            name_buffer: var.name.clone(),
+            value_buffer: var.value.clone(), // Note: Masked values might be empty/hidden
+            active_field: InputField::Value, // Start on Value usually for edits
+            is_creating: false,
+            editing_variable_id: Some(var.id),
+            editing_setting: None,
+            acknowledging: None,
+        };
+    }
+
+    /// Asks to delete the selected site variable.
+    fn confirm_variable_delete(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(site) = self.site_list.selected_site() else {
+            return;
+        };
+        let Some(variable) = self
+            .site_detail
+            .variables_table_state
+            .selected()
+            .and_then(|idx| site.variables.as_ref()?.get(idx))
+        else {
+            return;
+        };
+        let confirmation = Confirmation::new(
+            ConfirmKind::VariableDelete,
+            "Delete Variable",
+            format!("Delete variable {} from {}?", variable.name, site.name),
+            PendingAction::DeleteVariable { site_uid: site.uid.clone(), variable_id: variable.id },
+        );
+        self.request_confirmation(confirmation, tx);
+    }
+
+    fn delete_site_variable(
+        &mut self,
+        site_uid: String,
+        variable_id: i32,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        tracing::info!(%site_uid, variable_id, "deleting site variable");
+        self.tasks.spawn("delete_site_variable", async move {
+            let result = client
+                .delete_site_variable(&site_uid, variable_id)
+                .await
+                .map(|()| variable_id)
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::VariableDeleted(site_uid, result));
+        });
+    }
+
+    /// Asks to delete the selected account variable.
+    fn confirm_account_variable_delete(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(variable) = self.account_variables.selected_variable() else {
+            return;
+        };
+        let confirmation = Confirmation::new(
+            ConfirmKind::VariableDelete,
+            "Delete Account Variable",
+            format!("Delete account variable {}? Every site loses it.", variable.name),
+            PendingAction::DeleteAccountVariable { variable_id: variable.id },
+        );
+        self.request_confirmation(confirmation, tx);
+    }
+
+    fn delete_account_variable(
+        &mut self,
+        variable_id: i32,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        tracing::info!(variable_id, "deleting account variable");
+        self.tasks.spawn("delete_account_variable", async move {
+            let result = client
+                .delete_account_variable(variable_id)
+                .await
+                .map(|()| variable_id)
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::AccountVariableDeleted(result));
+        });
+    }
+
+    fn submit_account_variable(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let name = self.input_state.name_buffer.clone();
+        let value = self.input_state.value_buffer.clone();
+        if self.input_state.is_creating {
+            self.tasks.spawn("submit_account_variable", async move {
+                let req = CreateVariableRequest { name, value, masked: false };
+                let result = client
+                    .create_account_variable(req)
+                    .await
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::AccountVariableCreated(result));
+            });
+        } else if let Some(id) = self.input_state.editing_variable_id {
+            self.tasks.spawn("submit_account_variable", async move {
+                let req = UpdateVariableRequest { name, value };
+                let result = client
+                    .update_account_variable(id, req)
+                    .await
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::AccountVariableUpdated(result));
+            });
+        }
+    }
+
+    fn submit_variable(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if let Some(site) = self.site_list.selected_site().cloned() {
+            let site_uid = site.uid;
+            let client = self.client.as_ref().unwrap().clone();
+            let name = self.input_state.name_buffer.clone();
+            let value = self.input_state.value_buffer.clone();
+
+            if self.input_state.is_creating {
+                // Create
+                self.tasks.spawn("submit_variable", async move {
+                    let req = CreateVariableRequest {
+                        name,
+                        value,
+                        masked: false, // Default to false for now
                     };
+                    let result = client
+                        .create_site_variable(&site_uid, req)
+                        .await
+                        .map_err(ApiError::from);
+                    let _ = tx.send(Event::VariableCreated(site_uid, result));
+                });
+            } else if let Some(id) = self.input_state.editing_variable_id {
+                // Update
+                self.tasks.spawn("submit_variable", async move {
+                    let req = UpdateVariableRequest { name, value };
+                    let result = client
+                        .update_site_variable(&site_uid, id, req)
+                        .await
+                        .map_err(ApiError::from);
+                    let _ = tx.send(Event::VariableUpdated(site_uid, result));
+                });
+            }
+        }
+    }
 
-                    // Populate Settings state when switching to it
-                    if self.detail_tab == SiteDetailTab::Settings {
-                        self.populate_site_edit_state();
-                    }
-                }
-                // Determine context based on tab
-                KeyCode::Enter if self.detail_tab == SiteDetailTab::Devices => {
-                    if let Some(idx) = self.devices_table_state.selected() {
-                        if let Some(device) = self.devices.get(idx).cloned() {
-                            self.navigate_to_device_detail(device, tx);
-                        }
-                    }
-                }
-                KeyCode::Enter if self.detail_tab == SiteDetailTab::Alerts => {
-                    if let Some(idx) = self.site_open_alerts_table_state.selected() {
-                        if let Some(alert) = self.site_open_alerts.get(idx) {
-                            if let Some(source) = &alert.alert_source_info {
-                                if let Some(device_uid) = &source.device_uid {
-                                    // We need the full Device object to navigate. 
-                                    // Usually we have it in self.devices if the site is the same.
-                                    if let Some(device) = self.devices.iter().find(|d| d.uid == *device_uid).cloned() {
-                                        self.navigate_to_device_detail(device, tx);
-                                    } else {
-                                        // If not found in current site devices (maybe alert is from different site? unlikely in site detail view)
-                                        // Or maybe devices haven't loaded. 
-                                        // We can try to fetch the device if we had a get_device by UID api.
-                                        // For now, assume it's in the current site.
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                KeyCode::Char('j') | KeyCode::Down => match self.detail_tab {
-                    SiteDetailTab::Devices => self.next_device(),
-                    SiteDetailTab::Alerts => self.next_site_alert(),
-                    SiteDetailTab::Variables => self.next_variable(),
-                    SiteDetailTab::Settings => self.next_setting(),
-                },
-                KeyCode::Char('k') | KeyCode::Up => match self.detail_tab {
-                    SiteDetailTab::Devices => self.prev_device(),
-                    SiteDetailTab::Alerts => self.prev_site_alert(),
-                    SiteDetailTab::Variables => self.prev_variable(),
-                    SiteDetailTab::Settings => self.prev_setting(),
-                },
-                KeyCode::Char('e') => {
-                    if self.detail_tab == SiteDetailTab::Variables {
-                        self.open_edit_variable_modal();
-                    } else if self.detail_tab == SiteDetailTab::Settings {
-                        self.open_edit_setting_modal();
-                    }
-                }
-                KeyCode::Char(' ') if self.detail_tab == SiteDetailTab::Devices => {
-                    if let Some(idx) = self.devices_table_state.selected() {
-                        if let Some(device) = self.devices.get(idx) {
-                            if self.selected_device_uids.contains(&device.uid) {
-                                self.selected_device_uids.remove(&device.uid);
-                            } else {
-                                self.selected_device_uids.insert(device.uid.clone());
-                            }
-                        }
-                    }
-                }
-                // Variable Actions (Enter/Space on "Create +" row)
-                KeyCode::Enter | KeyCode::Char(' ')
-                    if self.detail_tab == SiteDetailTab::Variables =>
-                {
-                    if let Some(idx) = self.variables_table_state.selected() {
-                        if let Some(site_idx) = self.table_state.selected() {
-                            if let Some(site) = self.sites.get(site_idx) {
-                                let var_count =
-                                    site.variables.as_ref().map(|v| v.len()).unwrap_or(0);
-                                if idx == var_count {
-                                    self.open_create_variable_modal();
-                                } else {
-                                    self.open_edit_variable_modal();
-                                }
-                            }
-                        }
-                    }
-                }
-                // Settings Actions
-                KeyCode::Char(' ') | KeyCode::Enter
-                    if self.detail_tab == SiteDetailTab::Settings =>
-                {
-                    // Toggle boolean settings for quick action, or submit if purely selecting
-                    self.toggle_setting(tx.clone());
-                }
-                KeyCode::Char('r') => {
-                    self.show_quick_actions = true;
-                    self.quick_actions = vec![QuickAction::ReloadData];
-                    self.quick_action_list_state.select(Some(0));
-                }
-                _ => {}
-            },
-            CurrentView::DeviceDetail => {
-                if self.is_software_searching && self.device_detail_tab == DeviceDetailTab::Software {
-                    match key.code {
-                        KeyCode::Esc => {
-                            self.is_software_searching = false;
-                            self.software_search_query.clear();
-                            self.filter_software();
-                        }
-                        KeyCode::Enter => {
-                            self.is_software_searching = false;
-                        }
-                        KeyCode::Char(c) => {
-                            self.software_search_query.push(c);
-                            self.filter_software();
-                        }
-                        KeyCode::Backspace => {
-                            self.software_search_query.pop();
-                            self.filter_software();
-                        }
-                        _ => {}
-                    }
+    /// Changes the selected template would make to the selected site.
+    pub fn selected_template_changes(&self) -> Vec<VariableChange> {
+        let template = self
+            .popups
+            .variable_template_state
+            .selected()
+            .and_then(|i| self.variable_templates.get(i));
+        match (template, self.site_list.selected_site()) {
+            (Some(template), Some(site)) => template_changes(
+                template,
+                &site.name,
+                site.variables.as_deref().unwrap_or_default(),
+            ),
+            _ => Vec::new(),
+        }
+    }
+
+    fn handle_variable_templates_input(
+        &mut self,
+        key: KeyEvent,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        if self.popups.template_applying {
+            return;
+        }
+        let count = self.variable_templates.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.popups.close(Popup::VariableTemplates),
+            KeyCode::Down | KeyCode::Char('j') => {
+                select_next(&mut self.popups.variable_template_state, count)
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                select_prev(&mut self.popups.variable_template_state, count)
+            }
+            KeyCode::Enter => self.apply_variable_template(tx),
+            _ => {}
+        }
+    }
+
+    /// Writes the changes of the selected template to the selected site.
+    fn apply_variable_template(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let changes: Vec<VariableChange> = self
+            .selected_template_changes()
+            .into_iter()
+            .filter(VariableChange::is_change)
+            .collect();
+        let Some(site) = self.site_list.selected_site() else {
+            return;
+        };
+        if changes.is_empty() {
+            self.popups.close(Popup::VariableTemplates);
+            return;
+        }
+        tracing::info!(site = %site.name, changes = changes.len(), "applying variable template");
+        self.popups.template_applying = true;
+        self.write_variable_changes(site.uid.clone(), changes, tx);
+    }
+
+    /// Starts copying the marked variables of the selected site (all of them if
+    /// none are marked), beginning with the target site picker.
+    fn open_variable_copy(&mut self) {
+        let Some(site) = self.site_list.selected_site() else {
+            return;
+        };
+        let selected = &self.site_detail.selected_variable_ids;
+        let (masked, variables): (Vec<SiteVariable>, Vec<SiteVariable>) = site
+            .variables
+            .iter()
+            .flatten()
+            .filter(|v| selected.is_empty() || selected.contains(&v.id))
+            .cloned()
+            .partition(|v| v.masked);
+        if variables.is_empty() {
+            self.report_error(
+                "Datto",
+                Some(CurrentView::Detail),
+                "No variables to copy (masked values can't be read)".to_string(),
+            );
+            return;
+        }
+        self.popups.variable_copy = Some(VariableCopy {
+            source_uid: site.uid.clone(),
+            source_name: site.name.clone(),
+            variables,
+            masked_skipped: masked.len(),
+            ..Default::default()
+        });
+        // The site picker goes on top of the copy
+        self.popups.open(Popup::VariableCopy);
+        self.popups.open(Popup::SiteMove);
+        self.popups.site_move_query.clear();
+        self.filter_sites_for_move();
+    }
+
+    /// Diffs the copy against the target's variables, fetching them first if
+    /// they aren't loaded yet.
+    fn choose_variable_copy_target(
+        &mut self,
+        site_uid: String,
+        name: String,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(copy) = &mut self.popups.variable_copy else {
+            return;
+        };
+        copy.target = Some((site_uid.clone(), name));
+        let loaded = self
+            .site_list
+            .sites
+            .iter()
+            .find(|s| s.uid == site_uid)
+            .and_then(|s| s.variables.clone());
+        match loaded {
+            Some(variables) => self.diff_variable_copy(&site_uid, &variables),
+            None => self.fetch_site_variables(site_uid, tx),
+        }
+    }
+
+    fn diff_variable_copy(&mut self, site_uid: &str, variables: &[SiteVariable]) {
+        if let Some(copy) = &mut self.popups.variable_copy
+            && copy.target.as_ref().is_some_and(|(uid, _)| uid == site_uid)
+            && copy.changes.is_none()
+        {
+            copy.changes = Some(copy_changes(&copy.variables, variables));
+        }
+    }
+
+    fn handle_variable_copy_input(
+        &mut self,
+        key: KeyEvent,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(copy) = &mut self.popups.variable_copy else {
+            return;
+        };
+        if copy.applying {
+            return;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.popups.variable_copy = None,
+            KeyCode::Char('o') if copy.current_conflict().is_some() => copy.resolve(true, false),
+            KeyCode::Char('s') if copy.current_conflict().is_some() => copy.resolve(false, false),
+            KeyCode::Char('O') if copy.current_conflict().is_some() => copy.resolve(true, true),
+            KeyCode::Char('S') if copy.current_conflict().is_some() => copy.resolve(false, true),
+            KeyCode::Enter if copy.changes.is_some() && copy.current_conflict().is_none() => {
+                let changes = copy.to_write();
+                let Some((site_uid, name)) = copy.target.clone() else {
+                    return;
+                };
+                if changes.is_empty() {
+                    self.popups.variable_copy = None;
                     return;
                 }
+                tracing::info!(from = %copy.source_name, to = %name, changes = changes.len(), "copying variables");
+                copy.applying = true;
+                self.write_variable_changes(site_uid, changes, tx);
+            }
+            _ => {}
+        }
+    }
 
-                if self.show_device_variables {
-                    match key.code {
-                        KeyCode::Esc | KeyCode::Char('v') | KeyCode::Char('q') => {
-                            self.show_device_variables = false;
-                        }
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            let next = match self.udf_table_state.selected() {
-                                Some(i) => {
-                                    if i >= 29 {
-                                        0
-                                    } else {
-                                        i + 1
-                                    }
-                                }
-                                None => 0,
-                            };
-                            self.udf_table_state.select(Some(next));
-                        }
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            let next = match self.udf_table_state.selected() {
-                                Some(i) => {
-                                    if i == 0 {
-                                        29
-                                    } else {
-                                        i - 1
-                                    }
-                                }
-                                None => 0,
-                            };
-                            self.udf_table_state.select(Some(next));
-                        }
-                        KeyCode::Enter | KeyCode::Char(' ') => {
-                            self.open_edit_udf_modal();
-                        }
-                        _ => {}
-                    }
-                    return;
+    /// Creates and updates variables on a site one request at a time, stopping
+    /// at the first failure, then sends `Event::VariablesWritten`.
+    fn write_variable_changes(
+        &self,
+        site_uid: String,
+        changes: Vec<VariableChange>,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.tasks.spawn("write_variable_changes", async move {
+            let mut written = 0;
+            let mut result = Ok(());
+            for change in changes {
+                result = match change {
+                    VariableChange::Create { name, value } => client
+                        .create_site_variable(
+                            &site_uid,
+                            CreateVariableRequest {
+                                name,
+                                value,
+                                masked: false,
+                            },
+                        )
+                        .await
+                        .map(|_| ()),
+                    VariableChange::Update { id, name, new, .. } => client
+                        .update_site_variable(&site_uid, id, UpdateVariableRequest { name, value: new })
+                        .await
+                        .map(|_| ()),
+                    VariableChange::Unchanged { .. } => Ok(()),
+                };
+                if result.is_err() {
+                    break;
                 }
+                written += 1;
+            }
+            let result = result
+                .map(|_| written)
+                .map_err(|e| e.context(format!("{} written before the error", written)))
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::VariablesWritten(site_uid, result));
+        });
+    }
 
-                match key.code {
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        // Clear scan loading state for this device if needed
-                        if let Some(device) = self.selected_device.take() {
-                            self.scan_status.remove(&device.hostname);
-                            
-                            // Find the site this device belongs to
-                            if let Some(site_idx) = self.sites.iter().position(|s| s.uid == device.site_uid) {
-                                self.navigate_to_site_detail(site_idx, tx);
-                            } else {
-                                // Site not in current list (common if coming from search)
-                                // Fetch it directly
-                                self.current_view = CurrentView::Detail;
-                                self.fetch_site(device.site_uid.clone(), tx.clone());
-                                self.fetch_devices(device.site_uid.clone(), tx.clone());
-                                self.fetch_site_variables(device.site_uid.clone(), tx.clone());
-                            }
-                        } else {
-                            self.current_view = CurrentView::Detail;
-                        }
-                        
-                        // Reset tab to default when leaving? Or keep state? Resetting is safer for now.
-                        self.device_detail_tab = DeviceDetailTab::OpenAlerts;
-                    }
-                    KeyCode::Tab | KeyCode::BackTab => {
-                        let is_software_supported = if let Some(device) = &self.selected_device {
-                            device.device_class.as_ref().map(|s| s.trim().to_lowercase()).as_deref() == Some("device")
-                        } else {
-                            false
-                        };
-
-                        let is_backtab = matches!(key.code, KeyCode::BackTab);
-
-                        self.device_detail_tab = match self.device_detail_tab {
-                            DeviceDetailTab::OpenAlerts => {
-                                if is_backtab {
-                                    if is_software_supported {
-                                        DeviceDetailTab::Software
-                                    } else {
-                                        DeviceDetailTab::Activities
-                                    }
-                                } else {
-                                    DeviceDetailTab::Activities
-                                }
-                            }
-                            DeviceDetailTab::Activities => {
-                                if is_backtab {
-                                    DeviceDetailTab::OpenAlerts
-                                } else if is_software_supported {
-                                    DeviceDetailTab::Software
-                                } else {
-                                    DeviceDetailTab::OpenAlerts
-                                }
-                            }
-                            DeviceDetailTab::Software => {
-                                if is_backtab {
-                                    DeviceDetailTab::Activities
-                                } else {
-                                    DeviceDetailTab::OpenAlerts
-                                }
-                            }
-                        };
-                    }
-                    KeyCode::Char('v') => {
-                        self.show_device_variables = true;
-                        if self.udf_table_state.selected().is_none() {
-                            self.udf_table_state.select(Some(0));
-                        }
-                    }
-                    KeyCode::Char('r') => {
-                        self.show_quick_actions = true;
-                        self.quick_actions = vec![
-                            QuickAction::ScheduleReboot,
-                            QuickAction::RunComponent,
-                            QuickAction::MoveToSite,
-                            QuickAction::UpdateWarranty,
-                        ];
-                        
-                        // Check if AV is Sophos or Datto for AV Scan action
-                        if let Some(device) = &self.selected_device {
-                            let is_sophos = device.antivirus.as_ref()
-                                .and_then(|av| av.antivirus_product.as_ref())
-                                .map(|prod| prod.to_lowercase().contains("sophos"))
-                                .unwrap_or(false);
-                            let is_datto = device.antivirus.as_ref()
-                                .and_then(|av| av.antivirus_product.as_ref())
-                                .map(|prod| {
-                                    let p = prod.to_lowercase();
-                                    p.contains("datto av") || p.contains("datto edr")
-                                })
-                                .unwrap_or(false);
-                            
-                            if is_sophos || is_datto {
-                                self.quick_actions.push(QuickAction::RunAvScan);
-                            }
+    fn populate_site_edit_state(&mut self) {
+        if let Some(site) = self.site_list.selected_site() {
+            tracing::debug!(site = %site.name, "populating site edit state");
 
-                            if device.web_remote_url.is_some() {
-                                self.quick_actions.push(QuickAction::OpenWebRemote);
-                            }
-                        }
-                        self.quick_action_list_state.select(Some(0));
-                    }
-                    KeyCode::Char('j') | KeyCode::Down => match self.device_detail_tab {
-                        DeviceDetailTab::Activities => self.next_activity_log(),
-                        DeviceDetailTab::OpenAlerts => self.next_open_alert(),
-                        DeviceDetailTab::Software => self.next_software(),
-                    },
-                    KeyCode::Char('k') | KeyCode::Up => match self.device_detail_tab {
-                        DeviceDetailTab::Activities => self.prev_activity_log(),
-                        DeviceDetailTab::OpenAlerts => self.prev_open_alert(),
-                        DeviceDetailTab::Software => self.prev_software(),
-                    },
-                    KeyCode::Enter | KeyCode::Char(' ') => match self.device_detail_tab {
-                        DeviceDetailTab::Activities => {
-                            if let Some(idx) = self.activity_logs_table_state.selected() {
-                                if let Some(log) = self.activity_logs.get(idx) {
-                                    self.selected_activity_log = Some(log.clone());
-                                    self.current_view = CurrentView::ActivityDetail;
-
-                                    // Parse job ID from details and fetch job result
-                                    if let Some(details) = &log.details {
-                                        if let Ok(parsed) =
-                                            serde_json::from_str::<serde_json::Value>(details)
-                                        {
-                                            if let Some(job_uid) =
-                                                parsed.get("job.uid").and_then(|v| v.as_str())
-                                            {
-                                                if let Some(device) = &self.selected_device {
-                                                    self.fetch_job_result(
-                                                        job_uid.to_string(),
-                                                        device.uid.clone(),
-                                                        tx.clone(),
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        DeviceDetailTab::OpenAlerts => {
-                            // Currently no detailed view for open alerts, but could be added later
-                        }
-                        DeviceDetailTab::Software => {
-                            // Currently no detailed view for software, but could be added later
-                        }
-                    },
-                    _ => {}
-                }
+            self.site_detail.site_edit_state = SiteEditState::from_site(site);
+        }
+    }
+
+    fn submit_site_update(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if let Some(site) = self.site_list.selected_site().cloned() {
+            let req = self.site_detail.site_edit_state.update_request();
+            let changes = site_changes(&site, &req);
+            if !changes.is_empty() {
+                let confirmation = Confirmation::new(
+                    ConfirmKind::SiteUpdate,
+                    "Update Site",
+                    format!("Save these changes to {}?", site.name),
+                    PendingAction::SiteUpdate { site_uid: site.uid, req },
+                )
+                .with_changes(changes);
+                self.request_confirmation(confirmation, tx);
+                return;
             }
-            CurrentView::ActivityDetail => {
-                if self.show_popup {
-                    match key.code {
-                        KeyCode::Esc | KeyCode::Char('q') => {
-                            self.show_popup = false;
-                        }
-                        _ => {}
-                    }
+            // The proxy is saved on its own; until it has a type, its other
+            // fields are only kept here
+            let proxy = match self.site_detail.site_edit_state.proxy() {
+                Ok(proxy) => proxy,
+                Err(e) => {
+                    self.show_toast(e);
                     return;
                 }
-
-                match key.code {
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        self.current_view = CurrentView::DeviceDetail;
-                        self.selected_activity_log = None;
-                        self.selected_job_result = None;
-                        self.job_result_error = None;
-                    }
-                    KeyCode::Char('j') | KeyCode::Down => {
-                        if let Some(job_result) = &self.selected_job_result {
-                            let rows = generate_job_rows(job_result);
-                            if !rows.is_empty() && self.selected_job_row_index < rows.len() - 1 {
-                                self.selected_job_row_index += 1;
-                            }
-                        }
-                    }
-                    KeyCode::Char('k') | KeyCode::Up => {
-                        if self.selected_job_row_index > 0 {
-                            self.selected_job_row_index -= 1;
-                        }
-                    }
-                    KeyCode::Enter => {
-                        if let Some(job_result) = &self.selected_job_result {
-                            let rows = generate_job_rows(job_result);
-                            if let Some(row) = rows.get(self.selected_job_row_index) {
-                                match row {
-                                    JobViewRow::StdOutLink(_) => {
-                                        if let Some(job_uid) = &job_result.job_uid {
-                                            if let Some(device_uid) = &job_result.device_uid {
-                                                self.fetch_job_stdout(
-                                                    job_uid.clone(),
-                                                    device_uid.clone(),
-                                                    tx.clone(),
-                                                );
-                                            }
-                                        }
-                                    }
-                                    JobViewRow::StdErrLink(_) => {
-                                        if let Some(job_uid) = &job_result.job_uid {
-                                            if let Some(device_uid) = &job_result.device_uid {
-                                                self.fetch_job_stderr(
-                                                    job_uid.clone(),
-                                                    device_uid.clone(),
-                                                    tx.clone(),
-                                                );
-                                            }
-                                        }
-                                    }
-                                    _ => {} // Do nothing for header selection
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
+            };
+            // Nothing to save; don't push the whole request over newer values
+            let changes = proxy_changes(&site, &proxy);
+            if changes.is_empty() {
+                return;
             }
+            let confirmation = Confirmation::new(
+                ConfirmKind::SiteUpdate,
+                "Update Proxy",
+                format!("Save the proxy settings of {}?", site.name),
+                PendingAction::SiteProxyUpdate { site_uid: site.uid, proxy },
+            )
+            .with_changes(changes);
+            self.request_confirmation(confirmation, tx);
         }
     }
 
-    fn open_create_variable_modal(&mut self) {
+    fn send_site_proxy_update(
+        &mut self,
+        site_uid: String,
+        proxy: Option<ProxySettings>,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        tracing::debug!(%site_uid, removing = proxy.is_none(), "submitting site proxy update");
+
+        self.tasks.spawn("send_site_proxy_update", async move {
+            let result = match &proxy {
+                Some(settings) => client.update_site_proxy(&site_uid, settings).await,
+                None => client.delete_site_proxy(&site_uid).await,
+            };
+            let result = result.map(|_| proxy).map_err(ApiError::from);
+            let _ = tx.send(Event::SiteProxyUpdated(site_uid, result));
+        });
+    }
+
+    fn send_site_update(
+        &mut self,
+        site_uid: String,
+        req: UpdateSiteRequest,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        tracing::debug!(%site_uid, payload = ?req, "submitting site update");
+
+        self.tasks.spawn("send_site_update", async move {
+            let result = client
+                .update_site(&site_uid, req)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::SiteUpdated(result));
+        });
+    }
+
+    fn open_edit_setting_modal(&mut self) {
+        // Ensure site edit state is fresh
+        // self.populate_site_edit_state(); // This is called on tab switch, should be fine.
+
+        // Determine which setting is selected
+        let setting_idx = self.site_detail.settings_table_state.selected().unwrap_or(0);
+        let Some(&field_type) = SiteEditField::ALL.get(setting_idx) else {
+            return;
+        };
+        // Toggles and choices are handled by Space/Enter
+        let Some(current_value) = self.site_detail.site_edit_state.text_mut(field_type).cloned()
+        else {
+            return;
+        };
+
+        let active_input = match field_type {
+            SiteEditField::Name => InputField::SiteName,
+            SiteEditField::Description => InputField::SiteDescription,
+            SiteEditField::Notes => InputField::SiteNotes,
+            _ => InputField::SiteSetting,
+        };
+
         self.input_state = InputState {
             mode: InputMode::Editing,
-            name_buffer: String::new(),
-            value_buffer: String::new(),
-            active_field: InputField::Name,
-            is_creating: true,
+            name_buffer: current_value, // Re-use name_buffer for the single value being edited
+            value_buffer: String::new(), // Not used for single-value setting edit
+            active_field: active_input, // Tells us which field on the SiteEditState to update on submit
+            is_creating: false,
             editing_variable_id: None,
-            editing_setting: None,
+            editing_setting: Some(field_type),
+            acknowledging: None,
+        };
+    }
+
+    fn toggle_setting(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let setting_idx = self.site_detail.settings_table_state.selected().unwrap_or(0);
+        let Some(&field) = SiteEditField::ALL.get(setting_idx) else {
+            return;
+        };
+        match field.kind() {
+            SettingKind::Toggle | SettingKind::Choice => {
+                self.site_detail.site_edit_state.toggle(field);
+                self.submit_site_update(tx);
+            }
+            _ => {
+                // If it's a text field, Enter also behaves like 'e' -> Open Edit
+                self.open_edit_setting_modal();
+            }
+        }
+    }
+
+    pub fn open_edit_udf_modal(&mut self) {
+        if let Some(device) = &self.device_detail.selected_device {
+            if let Some(idx) = self.device_detail.udf_table_state.selected() {
+                // Get current value
+                let val = device
+                    .udf
+                    .as_ref()
+                    .and_then(|udf| udf.get(idx + 1))
+                    .map(str::to_string);
+
+                self.input_state = InputState {
+                    mode: InputMode::Editing,
+                    name_buffer: udf_label(&self.udf_labels, idx + 1), // Using name buffer for Label display
+                    value_buffer: val.unwrap_or_default(),
+                    active_field: InputField::Value, // Start on Value
+                    is_creating: false,
+                    editing_variable_id: None,
+                    editing_setting: None,
+                    acknowledging: None,
+                };
+                self.device_detail.editing_udf_index = Some(idx);
+            }
+        }
+    }
+
+    pub fn submit_device_udf(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(idx) = self.device_detail.editing_udf_index.take() else {
+            return;
+        };
+        let device_uid = self.device_detail.selected_device.as_ref().map(|d| d.uid.clone());
+        if let Some(device_uid) = device_uid {
+            let mut value = self.input_state.value_buffer.clone();
+            if std::mem::take(&mut self.device_detail.editing_tags) {
+                value = format_tags(&parse_tags(&value));
+            }
+            self.submit_udf(device_uid, idx + 1, value, tx);
+        }
+    }
+
+    /// Opens the input modal on the open device's tags, which are kept in
+    /// the profile's tags UDF.
+    pub fn open_edit_tags_modal(&mut self) {
+        let Some(udf) = self.tags_udf else {
+            self.show_toast("Set tags_udf in the profile to tag devices".to_string());
+            return;
+        };
+        let Some(device) = &self.device_detail.selected_device else {
+            return;
+        };
+        self.input_state = InputState {
+            mode: InputMode::Editing,
+            name_buffer: "Tags".to_string(),
+            value_buffer: device_tags(device, udf).join(", "),
+            active_field: InputField::Value,
+            ..Default::default()
+        };
+        self.device_detail.editing_udf_index = Some(udf - 1);
+        self.device_detail.editing_tags = true;
+    }
+
+    /// Recounts the tags of the account's devices for the Tags view.
+    fn rebuild_tags(&mut self) {
+        if let Some(udf) = self.tags_udf {
+            self.tags.rebuild(&self.report.devices, udf);
+        }
+    }
+
+    /// Sets UDF `number` of a device, asking first when that replaces a value.
+    fn submit_udf(
+        &mut self,
+        device_uid: String,
+        number: usize,
+        value: String,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let current = self
+            .device_detail
+            .selected_device
+            .iter()
+            .chain(self.site_detail.devices.items().iter())
+            .find(|d| d.uid == device_uid)
+            .and_then(|d| d.udf.as_ref()?.get(number).map(str::to_string))
+            .unwrap_or_default();
+        let action = PendingAction::Udf { device_uid, number, value: value.clone() };
+        if current.is_empty() || current == value {
+            self.run_confirmed(action, tx);
+            return;
+        }
+        let confirmation = Confirmation::new(
+            ConfirmKind::UdfOverwrite,
+            "Overwrite UDF",
+            format!(
+                "Replace {} \"{}\" with \"{}\"?",
+                udf_label(&self.udf_labels, number),
+                current,
+                value
+            ),
+            action,
+        );
+        self.request_confirmation(confirmation, tx);
+    }
+
+    /// Sets the UDF locally on the open device and in the site's and the
+    /// account's device lists, then sends it.
+    fn apply_udf(
+        &mut self,
+        device_uid: &str,
+        number: usize,
+        value: String,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let update = UdfUpdate::default().set(number, value);
+        let set = |device: &mut Device| update.apply(device.udf.get_or_insert_with(Udf::default));
+        let mut updated = None;
+        let devices = self.site_detail.devices.items_mut();
+        if let Some(device) = devices.iter_mut().find(|d| d.uid == device_uid) {
+            set(device);
+            updated = Some(device.clone());
+        }
+        if let Some(device) = &updated {
+            let devices = self.site_detail.devices.items();
+            self.cache_put(&cache::devices_key(&device.site_uid), &devices);
+        }
+        if let Some(open) = self
+            .device_detail
+            .selected_device
+            .as_mut()
+            .filter(|d| d.uid == device_uid)
+        {
+            set(open);
+            updated = Some(open.clone());
+        }
+        if let Some(device) = self.report.devices.iter_mut().find(|d| d.uid == device_uid) {
+            set(device);
+            self.rebuild_tags();
+        }
+        if let Some(device) = updated {
+            self.send_udf_update(&device, update, tx);
+        }
+    }
+
+    fn send_udf_update(
+        &self,
+        device: &Device,
+        update: UdfUpdate,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let device_uid = device.uid.clone();
+        let hostname = device.hostname.clone();
+        self.tasks.spawn("send_udf_update", async move {
+            let result = client
+                .update_device_udf(&device_uid, &update)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::DeviceUdfUpdated(hostname, result));
+        });
+    }
+
+    fn open_udf_columns_modal(&mut self) {
+        let columns = self.site_detail.matrix_columns(&self.udf_labels);
+        self.input_state = InputState {
+            mode: InputMode::Editing,
+            name_buffer: columns.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", "),
+            ..Default::default()
         };
+        self.site_detail.is_choosing_udf_columns = true;
     }
 
-    fn open_edit_variable_modal(&mut self) {
-        if let Some(idx) = self.variables_table_state.selected() {
-            if let Some(site_idx) = self.table_state.selected() {
-                if let Some(site) = self.sites.get(site_idx) {
-                    if let Some(vars) = &site.variables {
-                        if let Some(var) = vars.get(idx) {
-                            // DEBUG LOGGING
-                            let _ = std::fs::OpenOptions::new()
-                                .create(true)
-                                .append(true)
-                                .open("debug.log")
-                                .map(|mut f| {
-                                    use std::io::Write;
-                                    writeln!(
-                                        f,
-                                        "Opening Edit Modal for variable: {} - Value: {}",
-                                        var.name, var.value
-                                    )
-                                    .unwrap();
-                                });
-                            self.input_state = InputState {
-                                mode: InputMode::Editing,
-                                name_buffer: var.name.clone(),
-                                value_buffer: var.value.clone(), // Note: Masked values might be empty/hidden
-                                active_field: InputField::Value, // Start on Value usually for edits
-                                is_creating: false,
-                                editing_variable_id: Some(var.id),
-                                editing_setting: None,
-                            };
-                        }
-                    }
-                }
+    fn submit_udf_columns(&mut self) {
+        match parse_udf_columns(&self.input_state.name_buffer) {
+            Ok(columns) => {
+                self.site_detail.udf_columns = columns;
+                self.site_detail.udf_column = 0;
             }
+            Err(e) => self.report_error("UDF", Some(CurrentView::Detail), e),
         }
     }
 
-    fn submit_variable(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(idx) = self.table_state.selected() {
-            if let Some(site) = self.sites.get(idx).cloned() {
-                let site_uid = site.uid;
-                let client = self.client.as_ref().unwrap().clone();
-                let name = self.input_state.name_buffer.clone();
-                let value = self.input_state.value_buffer.clone();
-
-                if self.input_state.is_creating {
-                    // Create
-                    tokio::spawn(async move {
-                        let req = CreateVariableRequest {
-                            name,
-                            value,
-                            masked: false, // Default to false for now
-                        };
-                        let result = client
-                            .create_site_variable(&site_uid, req)
-                            .await
-                            .map_err(|e: anyhow::Error| e.to_string());
-                        tx.send(Event::VariableCreated(site_uid, result)).unwrap();
-                    });
-                } else if let Some(id) = self.input_state.editing_variable_id {
-                    // Update
-                    tokio::spawn(async move {
-                        let req = UpdateVariableRequest { name, value };
-                        let result = client
-                            .update_site_variable(&site_uid, id, req)
-                            .await
-                            .map_err(|e: anyhow::Error| e.to_string());
-                        tx.send(Event::VariableUpdated(site_uid, result)).unwrap();
-                    });
-                }
-            }
+    /// Opens the edit modal for the selected cell of the site UDF matrix.
+    fn open_edit_udf_cell_modal(&mut self) {
+        let columns = self.site_detail.matrix_columns(&self.udf_labels);
+        let Some(&number) = columns.get(self.site_detail.udf_column) else {
+            return;
+        };
+        let Some(device) = self.site_detail.selected_device() else {
+            return;
+        };
+        let value = device.udf.as_ref().and_then(|udf| udf.get(number)).unwrap_or_default();
+        self.input_state = InputState {
+            mode: InputMode::Editing,
+            value_buffer: value.to_string(),
+            active_field: InputField::Value,
+            is_creating: false,
+            ..Default::default()
+        };
+        self.site_detail.editing_udf_cell = Some((device.uid.clone(), number));
+    }
+
+    fn filter_sites_for_move(&mut self) {
+        if self.popups.site_move_query.is_empty() {
+            self.popups.filtered_sites = self.site_list
+                .own_sites()
+                .filter(|s| self.popups.variable_copy.as_ref().is_none_or(|c| c.source_uid != s.uid))
+                .cloned()
+                .collect();
+        } else {
+            let query = self.popups.site_move_query.to_lowercase();
+            self.popups.filtered_sites = self.site_list
+                .own_sites()
+                .filter(|s| s.name.to_lowercase().contains(&query))
+                .filter(|s| self.popups.variable_copy.as_ref().is_none_or(|c| c.source_uid != s.uid))
+                .cloned()
+                .collect();
+        }
+        
+        if !self.popups.filtered_sites.is_empty() {
+            self.popups.site_move_table_state.select(Some(0));
+        } else {
+            self.popups.site_move_table_state.select(None);
         }
     }
 
-    fn populate_site_edit_state(&mut self) {
-        if let Some(idx) = self.table_state.selected() {
-            if let Some(site) = self.sites.get(idx) {
-                // DEBUG LOGGING
-                let _ = std::fs::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open("debug.log")
-                    .map(|mut f| {
-                        use std::io::Write;
-                        writeln!(
-                            f,
-                            "Populating state from site: {} - Desc: {:?}",
-                            site.name, site.description
-                        )
-                        .unwrap();
-                    });
-
-                self.site_edit_state = SiteEditState {
-                    name: site.name.clone(),
-                    description: site.description.clone().unwrap_or_default(),
-                    notes: site.notes.clone().unwrap_or_default(),
-                    on_demand: site.on_demand.unwrap_or(false),
-                    splashtop_auto_install: site.splashtop_auto_install.unwrap_or(false),
-                    active_field: SiteEditField::Name,
-                    is_editing: true,
-                };
+    fn move_selected_device(&mut self, site_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if let Some(client) = &self.client {
+            if let Some(device) = &self.device_detail.selected_device {
+                self.site_list.is_loading = true;
+                let client = client.clone();
+                let device_uid = device.uid.clone();
+                self.tasks.spawn("move_selected_device", async move {
+                    let result = client.move_device(&device_uid, &site_uid).await.map_err(ApiError::from);
+                    let _ = tx.send(Event::DeviceMoved(result));
+                });
             }
         }
     }
 
-    fn submit_site_update(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(idx) = self.table_state.selected() {
-            if let Some(site) = self.sites.get(idx).cloned() {
-                let site_uid = site.uid;
-                let client = self.client.as_ref().unwrap().clone();
-                let req = UpdateSiteRequest {
-                    name: self.site_edit_state.name.clone(),
-                    description: Some(self.site_edit_state.description.clone()),
-                    notes: Some(self.site_edit_state.notes.clone()),
-                    on_demand: Some(self.site_edit_state.on_demand),
-                    splashtop_auto_install: Some(self.site_edit_state.splashtop_auto_install),
-                };
+    fn open_warranty_popup(&mut self) {
+        self.popups.open(Popup::Warranty);
+        self.popups.warranty_error = None;
+        self.popups.warranty_focus = WarrantyFocus::Year;
+        
+        // Parse yyyy-mm-dd, empty when there is no date yet
+        self.popups.warranty_segments = self
+            .device_detail
+            .selected_device
+            .as_ref()
+            .and_then(|device| device.warranty_date.as_deref())
+            .map(date_segments)
+            .unwrap_or_default();
+    }
 
-                // DEBUG LOG
-                let _ = std::fs::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open("debug.log")
-                    .map(|mut f| {
-                        use std::io::Write;
-                        writeln!(f, "Submitting Site Update for UID: {}", site_uid).unwrap();
-                        writeln!(f, "Payload: {:?}", req).unwrap();
-                    });
-
-                tokio::spawn(async move {
-                    let result = client
-                        .update_site(&site_uid, req)
-                        .await
-                        .map_err(|e: anyhow::Error| e.to_string());
-                    tx.send(Event::SiteUpdated(result)).unwrap();
-                });
+    fn handle_warranty_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        match key.code {
+            KeyCode::Esc => {
+                self.popups.close(Popup::Warranty);
+            }
+            KeyCode::Tab | KeyCode::Right => {
+                self.popups.warranty_focus = self.popups.warranty_focus.next();
+            }
+            KeyCode::BackTab | KeyCode::Left => {
+                self.popups.warranty_focus = self.popups.warranty_focus.prev();
+            }
+            KeyCode::Up => {
+                adjust_date_segment(&mut self.popups.warranty_segments, self.popups.warranty_focus, 1);
+            }
+            KeyCode::Down => {
+                adjust_date_segment(&mut self.popups.warranty_segments, self.popups.warranty_focus, -1);
+            }
+            KeyCode::Enter => {
+                self.submit_warranty_update(tx);
+            }
+            KeyCode::Backspace => {
+                self.popups.warranty_segments[self.popups.warranty_focus.index()].pop();
+            }
+            KeyCode::Char('x') => {
+                self.popups.warranty_segments = [String::new(), String::new(), String::new()];
+            }
+            KeyCode::Char(c) if c.is_digit(10) => {
+                push_date_digit(&mut self.popups.warranty_segments, &mut self.popups.warranty_focus, c);
             }
+            _ => {}
         }
     }
 
-    fn next_variable(&mut self) {
-        if let Some(site_idx) = self.table_state.selected() {
-            if let Some(site) = self.sites.get(site_idx) {
-                // Allow selecting up to len() (which is the "Create +" button)
-                let count = site.variables.as_ref().map(|v| v.len()).unwrap_or(0);
-
-                let i = match self.variables_table_state.selected() {
-                    Some(i) => {
-                        if i >= count {
-                            0
-                        } else {
-                            i + 1
-                        }
-                    }
-                    None => 0,
-                };
-                self.variables_table_state.select(Some(i));
+    fn submit_warranty_update(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        match compose_warranty_date(&self.popups.warranty_segments) {
+            Ok(date) => {
+                self.popups.close(Popup::Warranty);
+                self.send_warranty_update(date, tx);
             }
+            Err(e) => self.popups.warranty_error = Some(e),
         }
     }
 
-    fn prev_variable(&mut self) {
-        if let Some(site_idx) = self.table_state.selected() {
-            if let Some(site) = self.sites.get(site_idx) {
-                let count = site.variables.as_ref().map(|v| v.len()).unwrap_or(0);
+    /// Writes the open device's warranty date; `None` clears it.
+    fn send_warranty_update(
+        &mut self,
+        date: Option<String>,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let Some(device_uid) = self.device_detail.selected_device.as_ref().map(|d| d.uid.clone())
+        else {
+            return;
+        };
+        self.site_list.is_loading = true;
+        self.tasks.spawn("send_warranty_update", async move {
+            let result = client
+                .update_device_warranty(&device_uid, date.clone())
+                .await
+                .map(|()| date)
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::WarrantyUpdated(device_uid, result));
+        });
+    }
+
+    fn open_device_edit_popup(&mut self) {
+        let Some(device) = &self.device_detail.selected_device else {
+            return;
+        };
+        self.popups.device_edit_fields = [
+            device.description.clone().unwrap_or_default(),
+            device.display_name.clone().unwrap_or_default(),
+        ];
+        self.popups.device_edit_focus = 0;
+        self.popups.open(Popup::DeviceEdit);
+    }
 
-                let i = match self.variables_table_state.selected() {
-                    Some(i) => {
-                        if i == 0 {
-                            count
-                        } else {
-                            i - 1
-                        }
-                    }
-                    None => 0,
-                };
-                self.variables_table_state.select(Some(i));
+    fn handle_device_edit_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let field = &mut self.popups.device_edit_fields[self.popups.device_edit_focus];
+        match key.code {
+            KeyCode::Esc => self.popups.close(Popup::DeviceEdit),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Down | KeyCode::Up => {
+                self.popups.device_edit_focus = 1 - self.popups.device_edit_focus;
+            }
+            KeyCode::Enter => self.submit_device_edit(tx),
+            KeyCode::Backspace => {
+                field.pop();
             }
+            KeyCode::Char(c) => field.push(c),
+            _ => {}
         }
     }
 
-    fn next_row(&mut self) {
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i >= self.sites.len().saturating_sub(1) {
-                    0 // Loop back to top
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    /// Asks to save the fields of the device edit popup that changed.
+    fn submit_device_edit(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        self.popups.close(Popup::DeviceEdit);
+        let Some(device) = self.device_detail.selected_device.clone() else {
+            return;
+        };
+        let [description, display_name] =
+            self.popups.device_edit_fields.clone().map(|value| value.trim().to_string());
+        let req = UpdateDeviceRequest {
+            description: Some(description),
+            display_name: Some(display_name),
+        };
+        let changes = device_changes(&device, &req);
+        if changes.is_empty() {
+            return;
+        }
+        // Only send the changed fields, so the other keeps any newer value
+        let changed = |field: &str| changes.iter().any(|c| c.field == field);
+        let req = UpdateDeviceRequest {
+            description: req.description.filter(|_| changed("Description")),
+            display_name: req.display_name.filter(|_| changed("Friendly Name")),
         };
-        self.table_state.select(Some(i));
+        let confirmation = Confirmation::new(
+            ConfirmKind::DeviceUpdate,
+            "Update Device",
+            format!("Save these changes to {}?", device.hostname),
+            PendingAction::DeviceUpdate { device_uid: device.uid, req },
+        )
+        .with_changes(changes);
+        self.request_confirmation(confirmation, tx);
     }
 
-    fn previous_row(&mut self) {
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.sites.len().saturating_sub(1) // Loop to bottom
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+    fn send_device_update(
+        &mut self,
+        device_uid: String,
+        req: UpdateDeviceRequest,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
         };
-        self.table_state.select(Some(i));
+        self.tasks.spawn("send_device_update", async move {
+            let result = client
+                .update_device(&device_uid, &req)
+                .await
+                .map(|()| req)
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::DeviceUpdated(device_uid, result));
+        });
     }
 
-    fn next_device(&mut self) {
-        let i = match self.devices_table_state.selected() {
-            Some(i) => {
-                if i >= self.devices.len().saturating_sub(1) {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    fn toggle_device_watch(&mut self) {
+        let Some(device) = &self.device_detail.selected_device else {
+            return;
         };
-        self.devices_table_state.select(Some(i));
+        let message = if self.device_watch.toggle(device) {
+            format!(
+                "Watching {}: checking every {}s",
+                device.hostname,
+                WATCH_INTERVAL.as_secs()
+            )
+        } else {
+            format!("Stopped watching {}", device.hostname)
+        };
+        self.show_toast(message);
     }
 
-    fn prev_device(&mut self) {
-        let i = match self.devices_table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.devices.len().saturating_sub(1)
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+    /// Fetches the watched devices that are due, to catch them changing state.
+    fn poll_watched_devices(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(client) = &self.client else {
+            return;
         };
-        self.devices_table_state.select(Some(i));
+        for device_uid in self.device_watch.due(std::time::Instant::now()) {
+            let client = client.clone();
+            let tx = tx.clone();
+            self.tasks.spawn("poll_watched_devices", async move {
+                let result = client
+                    .get_device(&device_uid)
+                    .await
+                    .map(Box::new)
+                    .map_err(ApiError::from);
+                let _ = tx.send(Event::WatchedDevicePolled(device_uid, result));
+            });
+        }
     }
 
-    fn next_site_alert(&mut self) {
-        let i = match self.site_open_alerts_table_state.selected() {
-            Some(i) => {
-                if i >= self.site_open_alerts.len().saturating_sub(1) {
-                    0
-                } else {
-                    i + 1
-                }
+    fn handle_net_tools_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(state) = &mut self.popups.net_tools else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.popups.net_tools = None,
+            KeyCode::Tab => state.next_tool(),
+            KeyCode::Down | KeyCode::Char('j') => state.move_target(true),
+            KeyCode::Up | KeyCode::Char('k') => state.move_target(false),
+            KeyCode::Char(c)
+                if state.tool == NetTool::Port && c.is_ascii_digit() && state.port.len() < 5 =>
+            {
+                state.port.push(c);
+            }
+            KeyCode::Backspace if state.tool == NetTool::Port => {
+                state.port.pop();
+            }
+            KeyCode::Enter => {
+                let label = state.label();
+                let host = state.address().to_string();
+                let tool = state.tool;
+                let port = match tool {
+                    NetTool::Port => match state.port() {
+                        Ok(port) => port,
+                        Err(e) => {
+                            state.results.push(NetResult { label, outcome: Err(e) });
+                            return;
+                        }
+                    },
+                    _ => 0,
+                };
+                state.running += 1;
+                self.tasks.spawn("handle_net_tools_input", async move {
+                    let outcome = match tool {
+                        NetTool::Ping => net::ping(&host).await,
+                        NetTool::Port => net::check_port(&host, port)
+                            .await
+                            .map(|took| format!("Open ({} ms)", took.as_millis())),
+                        NetTool::Dns => net::lookup(&host).await.map(|ips| ips.join(", ")),
+                    };
+                    let _ = tx.send(Event::NetToolFinished(label, outcome));
+                });
             }
-            None => 0,
+            _ => {}
+        }
+    }
+
+    /// Runs launcher `index` against the open device: in the background, or
+    /// in this terminal with the TUI suspended (see `run_in_terminal`).
+    fn start_launcher(&mut self, index: usize) {
+        let (Some(launcher), Some(device)) =
+            (self.launchers.get(index), &self.device_detail.selected_device)
+        else {
+            return;
         };
-        self.site_open_alerts_table_state.select(Some(i));
+        let args = launcher_args(launcher, device);
+        if !launcher.background {
+            self.pending_launch = Some(args);
+            return;
+        }
+        let name = launcher.name.clone();
+        match launcher::spawn_background(&args) {
+            Ok(()) => self.show_toast(format!("Started {}", name)),
+            Err(e) => self.report_error("Launcher", Some(CurrentView::DeviceDetail), e),
+        }
     }
 
-    fn prev_site_alert(&mut self) {
-        let i = match self.site_open_alerts_table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.site_open_alerts.len().saturating_sub(1)
-                } else {
-                    i - 1
-                }
+    /// Runs the only launcher, or offers them in a menu when there are several.
+    fn open_launchers(&mut self) {
+        match self.launchers.len() {
+            0 => {
+                self.show_toast("No launchers configured (add [[launchers]] to the config)".to_string())
             }
-            None => 0,
+            1 => self.start_launcher(0),
+            count => {
+                self.popups.quick_actions = (0..count).map(QuickAction::Launcher).collect();
+                self.popups.quick_action_list_state.select(Some(0));
+                self.popups.open(Popup::QuickActions);
+            }
+        }
+    }
+
+    fn open_decommission_popup(&mut self) {
+        let Some(device) = self.device_detail.selected_device.clone() else {
+            return;
         };
-        self.site_open_alerts_table_state.select(Some(i));
+        self.popups.decommission = Some(Decommission {
+            av: self.device_av_endpoint(&device),
+            device_uid: device.uid,
+            hostname: device.hostname,
+            ..Default::default()
+        });
     }
 
-    fn next_setting(&mut self) {
-        let i = match self.settings_table_state.selected() {
-            Some(i) => {
-                if i >= 4 {
-                    // 5 items: Name, Desc, Notes, OnDemand, Splashtop (0-4)
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    fn handle_decommission_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(decommission) = &mut self.popups.decommission else {
+            return;
         };
-        self.settings_table_state.select(Some(i));
+        if decommission.running {
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => self.popups.decommission = None,
+            KeyCode::Tab if decommission.av.is_some() => {
+                decommission.remove_av = !decommission.remove_av;
+            }
+            KeyCode::Enter if decommission.confirmed() => {
+                decommission.running = true;
+                let decommission = decommission.clone();
+                self.send_decommission(decommission, tx);
+            }
+            KeyCode::Backspace => {
+                decommission.typed.pop();
+            }
+            KeyCode::Char(c) => decommission.typed.push(c),
+            _ => {}
+        }
     }
 
-    fn prev_setting(&mut self) {
-        let i = match self.settings_table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    4
-                } else {
-                    i - 1
+    /// Deletes the device from RMM, then its AV endpoint if that was asked for.
+    /// The AV endpoint is left alone when the RMM delete fails.
+    fn send_decommission(
+        &mut self,
+        decommission: Decommission,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let sophos = self.sophos_client.clone();
+        let datto_av = self.datto_av_client.clone();
+        self.tasks.spawn("send_decommission", async move {
+            let Decommission { device_uid, hostname, .. } = &decommission;
+            let result = client
+                .delete_device(device_uid)
+                .await
+                .map_err(ApiError::from);
+            let removed = result.is_ok();
+            let _ = tx.send(Event::DeviceDecommissioned(device_uid.clone(), result));
+            if !removed {
+                return;
+            }
+            match decommission.av_to_remove() {
+                Some(AvEndpoint::Sophos { tenant_id, region, endpoint_id }) => {
+                    let Some(client) = sophos else { return };
+                    let result = client
+                        .delete_endpoint(&tenant_id, &region, &endpoint_id)
+                        .await
+                        .map_err(ApiError::from);
+                    let _ = tx.send(Event::SophosEndpointDeleted(hostname.clone(), result));
+                }
+                Some(AvEndpoint::DattoAv { agent_id }) => {
+                    let Some(client) = datto_av else { return };
+                    let result = client
+                        .delete_agent(&agent_id)
+                        .await
+                        .map_err(ApiError::from);
+                    let _ = tx.send(Event::DattoAvAgentDeleted(hostname.clone(), result));
                 }
+                None => {}
             }
-            None => 0,
-        };
-        self.settings_table_state.select(Some(i));
+        });
     }
 
-    fn open_edit_setting_modal(&mut self) {
-        // Ensure site edit state is fresh
-        // self.populate_site_edit_state(); // This is called on tab switch, should be fine.
+    fn open_site_deletion_popup(&mut self) {
+        let Some(site) = self.site_list.selected_site() else {
+            return;
+        };
+        let reported = site.devices_status.as_ref().map_or(0, |s| s.number_of_devices.max(0));
+        self.popups.site_deletion = Some(SiteDeletion {
+            site_uid: site.uid.clone(),
+            name: site.name.clone(),
+            device_count: (reported as usize).max(self.site_detail.devices.len()),
+            ..Default::default()
+        });
+    }
 
-        // Determine which setting is selected
-        let setting_idx = self.settings_table_state.selected().unwrap_or(0);
-        let (field_type, current_value) = match setting_idx {
-            0 => (SiteEditField::Name, self.site_edit_state.name.clone()),
-            1 => (
-                SiteEditField::Description,
-                self.site_edit_state.description.clone(),
-            ),
-            2 => (SiteEditField::Notes, self.site_edit_state.notes.clone()),
-            // boolean fields technically "edit" via toggle, but could support text input "true"/"false" if desired.
-            // For now, let's only support Editing Modal for the text fields.
-            // Bools are handled by Space/Enter toggle.
-            _ => return,
+    fn handle_site_deletion_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let Some(deletion) = &mut self.popups.site_deletion else {
+            return;
         };
+        if deletion.running {
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => self.popups.site_deletion = None,
+            KeyCode::Enter if deletion.confirmed() => {
+                deletion.running = true;
+                let site_uid = deletion.site_uid.clone();
+                self.send_site_deletion(site_uid, tx);
+            }
+            KeyCode::Backspace => {
+                deletion.typed.pop();
+            }
+            KeyCode::Char(c) if deletion.device_count == 0 => deletion.typed.push(c),
+            _ => {}
+        }
+    }
 
-        let active_input = match setting_idx {
-            0 => InputField::SiteName,
-            1 => InputField::SiteDescription,
-            2 => InputField::SiteNotes,
-            _ => InputField::Name, // Fallback
+    /// Deletes the site after checking with the API that it has no devices
+    /// left; the loaded counts may be stale.
+    fn send_site_deletion(
+        &mut self,
+        site_uid: String,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
         };
+        self.tasks.spawn("send_site_deletion", async move {
+            let result = async {
+                let site = client.get_site(&site_uid).await?;
+                let devices = site.devices_status.map_or(0, |s| s.number_of_devices);
+                if devices > 0 {
+                    anyhow::bail!("{} still has {} devices", site.name, devices);
+                }
+                client.delete_site(&site_uid).await
+            }
+            .await
+            .map_err(ApiError::from);
+            let _ = tx.send(Event::SiteDeleted(site_uid, result));
+        });
+    }
 
-        self.input_state = InputState {
-            mode: InputMode::Editing,
-            name_buffer: current_value, // Re-use name_buffer for the single value being edited
-            value_buffer: String::new(), // Not used for single-value setting edit
-            active_field: active_input, // Tells us which field on the SiteEditState to update on submit
-            is_creating: false,
-            editing_variable_id: None,
-            editing_setting: Some(field_type),
+    fn handle_site_move_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        match key.code {
+            KeyCode::Esc => {
+                self.popups.close(Popup::SiteMove);
+                if self.popups.variable_copy.take().is_none() {
+                    self.popups.open(Popup::QuickActions);
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(i) = self.popups.site_move_table_state.selected() {
+                    let next = if i >= self.popups.filtered_sites.len().saturating_sub(1) { 0 } else { i + 1 };
+                    self.popups.site_move_table_state.select(Some(next));
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                if let Some(i) = self.popups.site_move_table_state.selected() {
+                    let next = if i == 0 { self.popups.filtered_sites.len().saturating_sub(1) } else { i - 1 };
+                    self.popups.site_move_table_state.select(Some(next));
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(i) = self.popups.site_move_table_state.selected() {
+                    if let Some(site) = self.popups.filtered_sites.get(i) {
+                        let (site_uid, name) = (site.uid.clone(), site.name.clone());
+                        self.popups.close(Popup::SiteMove);
+                        if self.popups.variable_copy.is_some() {
+                            self.choose_variable_copy_target(site_uid, name, tx);
+                        } else {
+                            self.move_selected_device(site_uid, tx);
+                        }
+                    }
+                }
+            }
+            KeyCode::Char(c) => {
+                self.popups.site_move_query.push(c);
+                self.filter_sites_for_move();
+            }
+            KeyCode::Backspace => {
+                self.popups.site_move_query.pop();
+                self.filter_sites_for_move();
+            }
+            _ => {}
+        }
+    }
+
+    /// Opens the on-disk cache for the active profile. Caching is skipped (with a warning) if it can't be opened.
+    pub fn open_cache(&mut self) {
+        self.cache = match Cache::open_for_profile(&self.active_profile) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                tracing::warn!(error = %e, "cache disabled");
+                None
+            }
         };
+        self.load_acknowledgements();
+        self.notifications = NotificationsState::new(
+            self.cache
+                .as_ref()
+                .and_then(|c| c.get::<SeenItems>(cache::SEEN_ITEMS_KEY))
+                .map(|cached| cached.value),
+        );
+        self.site_history = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get(cache::SITE_HISTORY_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        self.site_list.pinned = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get::<Vec<String>>(cache::PINNED_SITES_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        self.site_list.collapsed = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get(cache::COLLAPSED_GROUPS_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        self.site_list.refresh_visible();
+        self.recent_devices = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get::<Vec<Device>>(cache::RECENT_DEVICES_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        self.search_history = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get(cache::SEARCH_HISTORY_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        self.components.starred = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get::<Vec<String>>(cache::STARRED_COMPONENTS_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        self.components.history = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get::<Vec<ComponentRun>>(cache::COMPONENT_HISTORY_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        let collections = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get::<Vec<DeviceCollection>>(cache::COLLECTIONS_KEY))
+            .map(|cached| cached.value)
+            .unwrap_or_default();
+        self.collections.set_collections(collections);
     }
 
-    fn toggle_setting(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        let setting_idx = self.settings_table_state.selected().unwrap_or(0);
-        match setting_idx {
-            3 => {
-                // On Demand
-                self.site_edit_state.on_demand = !self.site_edit_state.on_demand;
-                self.submit_site_update(tx);
-            }
-            4 => {
-                // Splashtop
-                self.site_edit_state.splashtop_auto_install =
-                    !self.site_edit_state.splashtop_auto_install;
-                self.submit_site_update(tx);
+    /// Moves `device` to the front of the recent devices and persists the list.
+    fn record_recent_device(&mut self, device: &Device) {
+        self.recent_devices.retain(|d| d.uid != device.uid);
+        self.recent_devices.insert(0, device.clone());
+        self.recent_devices.truncate(RECENT_DEVICES_LIMIT);
+        self.cache_put(cache::RECENT_DEVICES_KEY, &self.recent_devices);
+    }
+
+    fn handle_recent_devices_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let count = self.recent_devices.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => {
+                self.popups.close(Popup::RecentDevices);
             }
-            _ => {
-                // If it's a text field, Enter also behaves like 'e' -> Open Edit
-                self.open_edit_setting_modal();
+            KeyCode::Down | KeyCode::Char('j') => select_next(&mut self.popups.recent_devices_state, count),
+            KeyCode::Up | KeyCode::Char('k') => select_prev(&mut self.popups.recent_devices_state, count),
+            KeyCode::Enter => {
+                if let Some(device) = self.popups
+                    .recent_devices_state
+                    .selected()
+                    .and_then(|i| self.recent_devices.get(i))
+                    .cloned()
+                {
+                    self.popups.close(Popup::RecentDevices);
+                    self.popups.close(Popup::DeviceVariables);
+                    self.navigate_to_device_detail(device, tx);
+                }
             }
+            _ => {}
         }
     }
 
-    pub fn open_edit_udf_modal(&mut self) {
-        if let Some(device) = &self.selected_device {
-            if let Some(idx) = self.udf_table_state.selected() {
-                // Get current value
-                let val = if let Some(udf) = &device.udf {
-                    match idx {
-                        0 => udf.udf1.clone(),
-                        1 => udf.udf2.clone(),
-                        2 => udf.udf3.clone(),
-                        3 => udf.udf4.clone(),
-                        4 => udf.udf5.clone(),
-                        5 => udf.udf6.clone(),
-                        6 => udf.udf7.clone(),
-                        7 => udf.udf8.clone(),
-                        8 => udf.udf9.clone(),
-                        9 => udf.udf10.clone(),
-                        10 => udf.udf11.clone(),
-                        11 => udf.udf12.clone(),
-                        12 => udf.udf13.clone(),
-                        13 => udf.udf14.clone(),
-                        14 => udf.udf15.clone(),
-                        15 => udf.udf16.clone(),
-                        16 => udf.udf17.clone(),
-                        17 => udf.udf18.clone(),
-                        18 => udf.udf19.clone(),
-                        19 => udf.udf20.clone(),
-                        20 => udf.udf21.clone(),
-                        21 => udf.udf22.clone(),
-                        22 => udf.udf23.clone(),
-                        23 => udf.udf24.clone(),
-                        24 => udf.udf25.clone(),
-                        25 => udf.udf26.clone(),
-                        26 => udf.udf27.clone(),
-                        27 => udf.udf28.clone(),
-                        28 => udf.udf29.clone(),
-                        29 => udf.udf30.clone(),
-                        _ => None,
-                    }
-                } else {
-                    None
-                };
+    fn handle_scans_input(&mut self, key: KeyEvent) {
+        let count = self.security.scans.jobs.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('V') => {
+                self.popups.close(Popup::Scans);
+            }
+            KeyCode::Down | KeyCode::Char('j') => select_next(&mut self.popups.scans_state, count),
+            KeyCode::Up | KeyCode::Char('k') => select_prev(&mut self.popups.scans_state, count),
+            _ => {}
+        }
+    }
 
-                self.input_state = InputState {
-                    mode: InputMode::Editing,
-                    name_buffer: format!("UDF {}", idx + 1), // Using name buffer for Label display
-                    value_buffer: val.unwrap_or_default(),
-                    active_field: InputField::Value, // Start on Value
-                    is_creating: false,
-                    editing_variable_id: None,
-                    editing_setting: None,
-                };
-                self.editing_udf_index = Some(idx);
-            }
-        }
-    }
-
-    pub fn submit_device_udf(&mut self, _tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(mut device) = self.selected_device.take() {
-            if let Some(idx) = self.editing_udf_index {
-                let new_val = self.input_state.value_buffer.clone();
-                // Update local device UDF
-                let mut udf = device.udf.clone().unwrap_or(crate::api::datto::types::Udf {
-                    udf1: None,
-                    udf2: None,
-                    udf3: None,
-                    udf4: None,
-                    udf5: None,
-                    udf6: None,
-                    udf7: None,
-                    udf8: None,
-                    udf9: None,
-                    udf10: None,
-                    udf11: None,
-                    udf12: None,
-                    udf13: None,
-                    udf14: None,
-                    udf15: None,
-                    udf16: None,
-                    udf17: None,
-                    udf18: None,
-                    udf19: None,
-                    udf20: None,
-                    udf21: None,
-                    udf22: None,
-                    udf23: None,
-                    udf24: None,
-                    udf25: None,
-                    udf26: None,
-                    udf27: None,
-                    udf28: None,
-                    udf29: None,
-                    udf30: None,
-                });
+    fn load_acknowledgements(&mut self) {
+        self.acknowledgements.clear();
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        match cache.acknowledgements() {
+            Ok(acks) => {
+                self.acknowledgements = acks
+                    .into_iter()
+                    .map(|a| ((a.kind.clone(), a.item_id.clone()), a))
+                    .collect();
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to load acknowledgements"),
+        }
+    }
 
-                let val_opt = Some(new_val.clone());
-
-                // Update specific field
-                match idx {
-                    0 => udf.udf1 = val_opt,
-                    1 => udf.udf2 = val_opt,
-                    2 => udf.udf3 = val_opt,
-                    3 => udf.udf4 = val_opt,
-                    4 => udf.udf5 = val_opt,
-                    5 => udf.udf6 = val_opt,
-                    6 => udf.udf7 = val_opt,
-                    7 => udf.udf8 = val_opt,
-                    8 => udf.udf9 = val_opt,
-                    9 => udf.udf10 = val_opt,
-                    10 => udf.udf11 = val_opt,
-                    11 => udf.udf12 = val_opt,
-                    12 => udf.udf13 = val_opt,
-                    13 => udf.udf14 = val_opt,
-                    14 => udf.udf15 = val_opt,
-                    15 => udf.udf16 = val_opt,
-                    16 => udf.udf17 = val_opt,
-                    17 => udf.udf18 = val_opt,
-                    18 => udf.udf19 = val_opt,
-                    19 => udf.udf20 = val_opt,
-                    20 => udf.udf21 = val_opt,
-                    21 => udf.udf22 = val_opt,
-                    22 => udf.udf23 = val_opt,
-                    23 => udf.udf24 = val_opt,
-                    24 => udf.udf25 = val_opt,
-                    25 => udf.udf26 = val_opt,
-                    26 => udf.udf27 = val_opt,
-                    27 => udf.udf28 = val_opt,
-                    28 => udf.udf29 = val_opt,
-                    29 => udf.udf30 = val_opt,
-                    _ => {}
-                }
+    pub fn acknowledgement(&self, kind: &str, item_id: &str) -> Option<&Acknowledgement> {
+        self.acknowledgements.get(&(kind.to_string(), item_id.to_string()))
+    }
 
-                device.udf = Some(udf.clone());
-                self.selected_device = Some(device.clone()); // Restore with updated value locally
-                self.editing_udf_index = None;
+    pub fn alert_acknowledgement(&self, alert: &Alert) -> Option<&Acknowledgement> {
+        alert
+            .alert_uid
+            .as_deref()
+            .and_then(|uid| self.acknowledgement(cache::ALERT_ACK, uid))
+    }
 
-                // API Call
-                if let Some(client) = self.client.clone() {
-                    let device_uid = device.uid.clone();
-                    tokio::spawn(async move {
-                        // Ignoring result for now as per previous pattern or log to stderr
-                        if let Err(e) = client.update_device_udf(&device_uid, &udf).await {
-                            eprintln!("Failed to update UDF: {}", e);
+    /// Acknowledges an item with `note`, or clears its acknowledgement when `None`.
+    /// Kept in memory only when there is no cache (e.g. demo mode).
+    fn set_acknowledged(&mut self, kind: &'static str, item_id: &str, note: Option<String>) {
+        let key = (kind.to_string(), item_id.to_string());
+        match note {
+            Some(note) => {
+                let ack = match &self.cache {
+                    Some(cache) => match cache.acknowledge(kind, item_id, &note) {
+                        Ok(ack) => ack,
+                        Err(e) => {
+                            self.report_error("Cache", None, format!("Failed to save acknowledgement: {:#}", e));
+                            return;
                         }
-                    });
+                    },
+                    None => Acknowledgement {
+                        kind: kind.to_string(),
+                        item_id: item_id.to_string(),
+                        note,
+                        acked_at: chrono::Utc::now(),
+                    },
+                };
+                self.acknowledgements.insert(key, ack);
+            }
+            None => {
+                if let Some(cache) = &self.cache
+                    && let Err(e) = cache.unacknowledge(kind, item_id)
+                {
+                    self.report_error("Cache", None, format!("Failed to clear acknowledgement: {:#}", e));
+                    return;
                 }
-            } else {
-                self.selected_device = Some(device); // Restore
+                self.acknowledgements.remove(&key);
             }
         }
     }
 
-    fn next_open_alert(&mut self) {
-        let i = match self.open_alerts_table_state.selected() {
-            Some(i) => {
-                if i >= self.open_alerts.len().saturating_sub(1) {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    /// Clears the acknowledgement on an alert, or asks for a note to acknowledge it.
+    fn toggle_alert_acknowledgement(&mut self, alert_uid: String) {
+        if self.acknowledgement(cache::ALERT_ACK, &alert_uid).is_some() {
+            self.set_acknowledged(cache::ALERT_ACK, &alert_uid, None);
+            return;
+        }
+        self.input_state = InputState {
+            mode: InputMode::Editing,
+            acknowledging: Some((cache::ALERT_ACK, alert_uid)),
+            ..Default::default()
         };
-        self.open_alerts_table_state.select(Some(i));
     }
 
-    fn prev_open_alert(&mut self) {
-        let i = match self.open_alerts_table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.open_alerts.len().saturating_sub(1)
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.open_alerts_table_state.select(Some(i));
+    fn save_seen_items(&self) {
+        self.cache_put(cache::SEEN_ITEMS_KEY, &self.notifications.seen);
     }
 
-    fn next_activity_log(&mut self) {
-        let i = match self.activity_logs_table_state.selected() {
-            Some(i) => {
-                if i >= self.activity_logs.len().saturating_sub(1) {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
-        };
-        self.activity_logs_table_state.select(Some(i));
+    /// Records today's snapshot of every site of this account and saves the history.
+    fn record_site_history(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        for site in self.site_list.own_sites() {
+            let alerts = self.site_list.open_alert_count(site);
+            self.site_history.record(site, alerts, today);
+        }
+        self.cache_put(cache::SITE_HISTORY_KEY, &self.site_history);
     }
 
-    fn prev_activity_log(&mut self) {
-        let i = match self.activity_logs_table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.activity_logs.len().saturating_sub(1)
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+    fn cache_put<T: serde::Serialize>(&self, key: &str, value: &T) {
+        if let Some(cache) = &self.cache
+            && let Err(e) = cache.put(key, value)
+        {
+            tracing::warn!(%key, error = %e, "failed to write cache");
+        }
+    }
+
+    /// Re-sorts the site list; the incident columns depend on `incident_stats`.
+    fn sort_sites(&mut self) {
+        self.site_list.sort_sites(&self.security.incident_stats, &self.security.mdr_defaults);
+    }
+
+    fn load_cached_sites(&mut self) {
+        let Some(cached) = self.cache.as_ref().and_then(|c| c.get::<Vec<Site>>(cache::SITES_KEY)) else {
+            return;
         };
-        self.activity_logs_table_state.select(Some(i));
+        self.site_list.set_sites(cached.value);
+        self.sort_sites();
+        self.site_list.sites_cached_at = Some(cached.fetched_at);
+        self.site_list.total_count = self.site_list.sites.len() as i32;
+        self.apply_cached_variables();
     }
 
-    fn next_software(&mut self) {
-        let i = match self.device_software_table_state.selected() {
-            Some(i) => {
-                if i >= self.filtered_software.len().saturating_sub(1) {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    /// Fills in variables for sites that don't have any yet from the cache.
+    fn apply_cached_variables(&mut self) {
+        let Some(cache) = &self.cache else {
+            return;
         };
-        self.device_software_table_state.select(Some(i));
+        for site in self.site_list.sites.iter_mut().filter(|s| s.variables.is_none()) {
+            if let Some(cached) = cache.get::<Vec<SiteVariable>>(&cache::variables_key(&site.uid)) {
+                site.variables = Some(cached.value);
+                self.site_detail.variables_cached_at.insert(site.uid.clone(), cached.fetched_at);
+            }
+        }
+        self.site_list.refresh_visible();
     }
 
-    fn prev_software(&mut self) {
-        let i = match self.device_software_table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.filtered_software.len().saturating_sub(1)
-                } else {
-                    i - 1
-                }
+    /// Records a non-fatal error: logged, kept in the history popup and shown as a
+    /// banner on `view` (or on every view when `None`) until acknowledged.
+    /// Takes the integration states of a newly connected profile and reports
+    /// the integrations that are misconfigured or failed to authenticate.
+    pub fn set_integrations(&mut self, statuses: Vec<IntegrationStatus>) {
+        for status in &statuses {
+            let source = status.provider.title();
+            if let ConfigState::Invalid(e) = &status.config {
+                let message = format!("{} is disabled, invalid config: {}", source, e);
+                self.report_error(source, None, message);
             }
-            None => 0,
-        };
-        self.device_software_table_state.select(Some(i));
+            if let Some((_, Err(e))) = &status.last_auth {
+                self.report_error(source, None, format!("{} Auth Failed: {}", source, e));
+            }
+        }
+        self.integrations.set_statuses(statuses);
     }
 
-    fn filter_sites_for_move(&mut self) {
-        if self.site_move_query.is_empty() {
-            self.filtered_sites = self.sites.clone();
-        } else {
-            let query = self.site_move_query.to_lowercase();
-            self.filtered_sites = self.sites
-                .iter()
-                .filter(|s| s.name.to_lowercase().contains(&query))
-                .cloned()
-                .collect();
+    /// Re-reads the profile's config and authenticates `provider` again,
+    /// replacing its client when that works.
+    fn reauthenticate(
+        &mut self,
+        provider: Provider,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        let status = self.integrations.status_mut(provider);
+        if status.authenticating {
+            return;
         }
-        
-        if !self.filtered_sites.is_empty() {
-            self.site_move_table_state.select(Some(0));
-        } else {
-            self.site_move_table_state.select(None);
+        if status.config == ConfigState::Demo {
+            status.record_auth(Ok(()));
+            return;
         }
+        status.authenticating = true;
+        // A profile only set through the environment isn't in the file
+        let profile = self
+            .profiles
+            .contains(&self.active_profile)
+            .then(|| self.active_profile.clone());
+        self.tasks.spawn("reauthenticate", async move {
+            let (config, result) = match Config::load(profile.as_deref()) {
+                Ok(config) => (
+                    Some(provider_config_state(provider, &config)),
+                    authenticate_provider(provider, config).await,
+                ),
+                Err(e) => (None, Err(e)),
+            };
+            let result = result.map_err(|e| format!("{:#}", e));
+            let _ = tx.send(Event::IntegrationAuthenticated(provider, config, result));
+        });
     }
 
-    fn move_selected_device(&mut self, site_uid: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        if let Some(client) = &self.client {
-            if let Some(device) = &self.selected_device {
-                self.is_loading = true;
-                let client = client.clone();
-                let device_uid = device.uid.clone();
-                tokio::spawn(async move {
-                    let result = client.move_device(&device_uid, &site_uid).await.map_err(|e: anyhow::Error| e.to_string());
-                    tx.send(Event::DeviceMoved(result)).unwrap();
-                });
-            }
-        }
+    pub fn show_toast(&mut self, message: String) {
+        self.toast = Some((message, std::time::Instant::now()));
     }
 
-    fn open_warranty_popup(&mut self) {
-        self.show_warranty_popup = true;
-        self.warranty_error = None;
-        self.warranty_focus = WarrantyFocus::Year;
-        
-        if let Some(device) = &self.selected_device {
-            if let Some(date) = &device.warranty_date {
-                // Parse yyyy-mm-dd
-                let parts: Vec<&str> = date.split('-').collect();
-                if parts.len() == 3 {
-                    self.warranty_segments[0] = parts[0].to_string();
-                    self.warranty_segments[1] = parts[1].to_string();
-                    self.warranty_segments[2] = parts[2].to_string();
-                    return;
-                }
-            }
+    pub fn report_error(&mut self, source: &'static str, view: Option<CurrentView>, message: String) {
+        tracing::warn!(source, "{}", message);
+        if self.errors.len() >= MAX_ERROR_HISTORY {
+            self.errors.pop_front();
         }
-        
-        // Default to empty or current year? Let's use empty
-        self.warranty_segments = [String::new(), String::new(), String::new()];
+        self.errors.push_back(ErrorEntry {
+            timestamp: chrono::Local::now(),
+            source,
+            message,
+            view,
+            seen: false,
+        });
     }
 
-    fn adjust_warranty_segment(&mut self, delta: i32) {
-        let idx = match self.warranty_focus {
-            WarrantyFocus::Year => 0,
-            WarrantyFocus::Month => 1,
-            WarrantyFocus::Day => 2,
-        };
-        
-        let mut val: i32 = self.warranty_segments[idx].parse().unwrap_or(0);
-        val += delta;
-        
-        match self.warranty_focus {
-            WarrantyFocus::Year => { if val < 0 { val = 9999; } if val > 9999 { val = 0; } },
-            WarrantyFocus::Month => { if val < 1 { val = 12; } if val > 12 { val = 1; } },
-            WarrantyFocus::Day => { if val < 1 { val = 31; } if val > 31 { val = 1; } },
+    /// Reports an API call that failed, as "`context`: `error`". Refused
+    /// writes get a toast (see `handle_event`), not an error banner.
+    pub fn report_api_error(
+        &mut self,
+        source: &'static str,
+        view: Option<CurrentView>,
+        context: &str,
+        error: &ApiError,
+    ) {
+        if *error == ApiError::ReadOnly {
+            tracing::info!(source, "{}: {}", context, error);
+            return;
         }
-        
-        if self.warranty_focus == WarrantyFocus::Year {
-            self.warranty_segments[idx] = format!("{:04}", val);
-        } else {
-            self.warranty_segments[idx] = format!("{:02}", val);
+        self.report_error(source, view, format!("{}: {}", context, error));
+    }
+
+    /// Most recent unacknowledged error to show as a banner on the current view.
+    pub fn active_error(&self) -> Option<&ErrorEntry> {
+        self.errors
+            .iter()
+            .rev()
+            .find(|e| !e.seen && e.view.is_none_or(|v| v == self.current_view))
+    }
+
+    /// Marks errors for `view` as seen, or all errors when `None`.
+    fn acknowledge_errors(&mut self, view: Option<CurrentView>) {
+        for entry in &mut self.errors {
+            if view.is_none() || entry.view == view {
+                entry.seen = true;
+            }
         }
     }
 
-    fn handle_warranty_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+    fn handle_error_history_input(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Esc => {
-                self.show_warranty_popup = false;
-            }
-            KeyCode::Tab | KeyCode::Right => {
-                self.warranty_focus = match self.warranty_focus {
-                    WarrantyFocus::Year => WarrantyFocus::Month,
-                    WarrantyFocus::Month => WarrantyFocus::Day,
-                    WarrantyFocus::Day => WarrantyFocus::Year,
-                };
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('E') => {
+                self.popups.close(Popup::ErrorHistory);
             }
-            KeyCode::BackTab | KeyCode::Left => {
-                self.warranty_focus = match self.warranty_focus {
-                    WarrantyFocus::Year => WarrantyFocus::Day,
-                    WarrantyFocus::Month => WarrantyFocus::Year,
-                    WarrantyFocus::Day => WarrantyFocus::Month,
-                };
-            }
-            KeyCode::Up => {
-                self.adjust_warranty_segment(1);
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(i) = self.popups.error_history_state.selected() {
+                    let next = if i >= self.errors.len().saturating_sub(1) { 0 } else { i + 1 };
+                    self.popups.error_history_state.select(Some(next));
+                }
             }
-            KeyCode::Down => {
-                self.adjust_warranty_segment(-1);
+            KeyCode::Up | KeyCode::Char('k') => {
+                if let Some(i) = self.popups.error_history_state.selected() {
+                    let next = if i == 0 { self.errors.len().saturating_sub(1) } else { i - 1 };
+                    self.popups.error_history_state.select(Some(next));
+                }
             }
-            KeyCode::Enter => {
-                self.submit_warranty_update(tx);
+            KeyCode::Char('c') => {
+                self.errors.clear();
+                self.popups.error_history_state.select(None);
             }
-            KeyCode::Backspace => {
-                let idx = match self.warranty_focus {
-                    WarrantyFocus::Year => 0,
-                    WarrantyFocus::Month => 1,
-                    WarrantyFocus::Day => 2,
-                };
-                self.warranty_segments[idx].pop();
+            _ => {}
+        }
+    }
+
+    fn handle_notifications_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('b') => {
+                self.popups.close(Popup::Notifications);
             }
-            KeyCode::Char('x') => {
-                self.warranty_segments = [String::new(), String::new(), String::new()];
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.notifications.scroll = self.notifications.scroll.saturating_add(1);
             }
-            KeyCode::Char(c) if c.is_digit(10) => {
-                let idx = match self.warranty_focus {
-                    WarrantyFocus::Year => 0,
-                    WarrantyFocus::Month => 1,
-                    WarrantyFocus::Day => 2,
-                };
-                
-                let limit = if self.warranty_focus == WarrantyFocus::Year { 4 } else { 2 };
-                let mut s = self.warranty_segments[idx].clone();
-                s.push(c);
-                if s.len() > limit {
-                    s.remove(0);
-                }
-                self.warranty_segments[idx] = s;
-                
-                // Auto-advance
-                if self.warranty_segments[idx].len() == limit {
-                    if self.warranty_focus == WarrantyFocus::Year {
-                        self.warranty_focus = WarrantyFocus::Month;
-                    } else if self.warranty_focus == WarrantyFocus::Month {
-                        self.warranty_focus = WarrantyFocus::Day;
-                    }
-                }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.notifications.scroll = self.notifications.scroll.saturating_sub(1);
             }
             _ => {}
         }
     }
 
-    fn submit_warranty_update(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
-        let year = &self.warranty_segments[0];
-        let month = &self.warranty_segments[1];
-        let day = &self.warranty_segments[2];
-
-        let date_str = if year.is_empty() && month.is_empty() && day.is_empty() {
-            None
-        } else {
-            // Basic validation
-            if year.len() != 4 || month.len() != 2 || day.len() != 2 {
-                self.warranty_error = Some("Invalid date format. Use YYYY-MM-DD".to_string());
-                return;
+    fn handle_notes_viewer_input(&mut self, key: KeyEvent) {
+        let scroll = &mut self.popups.notes_scroll;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('o') => {
+                self.popups.close(Popup::NotesViewer);
             }
-            Some(format!("{}-{}-{}", year, month, day))
-        };
+            KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+            KeyCode::PageDown => *scroll = scroll.saturating_add(10),
+            KeyCode::PageUp => *scroll = scroll.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => *scroll = 0,
+            _ => {}
+        }
+    }
 
-        if let Some(client) = &self.client {
-            if let Some(device) = &self.selected_device {
-                self.is_loading = true;
-                let client = client.clone();
-                let device_uid = device.uid.clone();
-                self.show_warranty_popup = false;
-                tokio::spawn(async move {
-                    let result = client.update_device_warranty(&device_uid, date_str).await.map_err(|e: anyhow::Error| e.to_string());
-                    tx.send(Event::WarrantyUpdated(result)).unwrap();
-                });
+    fn handle_log_viewer_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L') => {
+                self.popups.close(Popup::LogViewer);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.popups.log_viewer_scroll = self.popups.log_viewer_scroll.saturating_add(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.popups.log_viewer_scroll = self.popups.log_viewer_scroll.saturating_sub(1);
+            }
+            KeyCode::PageUp => {
+                self.popups.log_viewer_scroll = self.popups.log_viewer_scroll.saturating_add(20);
             }
+            KeyCode::PageDown => {
+                self.popups.log_viewer_scroll = self.popups.log_viewer_scroll.saturating_sub(20);
+            }
+            KeyCode::Char('g') => self.popups.log_viewer_scroll = usize::MAX,
+            KeyCode::Char('G') => self.popups.log_viewer_scroll = 0,
+            _ => {}
         }
     }
 
-    fn handle_site_move_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+    fn open_profile_switcher(&mut self) {
+        self.profiles = Config::profile_names();
+        self.popups.open(Popup::ProfileSwitcher);
+        let current = self.profiles.iter().position(|p| *p == self.active_profile);
+        self.popups.profile_list_state
+            .select(current.or(if self.profiles.is_empty() { None } else { Some(0) }));
+    }
+
+    fn handle_profile_switcher_input(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
         match key.code {
-            KeyCode::Esc => {
-                self.show_site_move = false;
-                self.show_quick_actions = true;
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.popups.close(Popup::ProfileSwitcher);
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                if let Some(i) = self.site_move_table_state.selected() {
-                    let next = if i >= self.filtered_sites.len().saturating_sub(1) { 0 } else { i + 1 };
-                    self.site_move_table_state.select(Some(next));
+                if let Some(i) = self.popups.profile_list_state.selected() {
+                    let next = if i >= self.profiles.len().saturating_sub(1) { 0 } else { i + 1 };
+                    self.popups.profile_list_state.select(Some(next));
                 }
             }
             KeyCode::Up | KeyCode::Char('k') => {
-                if let Some(i) = self.site_move_table_state.selected() {
-                    let next = if i == 0 { self.filtered_sites.len().saturating_sub(1) } else { i - 1 };
-                    self.site_move_table_state.select(Some(next));
+                if let Some(i) = self.popups.profile_list_state.selected() {
+                    let next = if i == 0 { self.profiles.len().saturating_sub(1) } else { i - 1 };
+                    self.popups.profile_list_state.select(Some(next));
                 }
             }
             KeyCode::Enter => {
-                if let Some(i) = self.site_move_table_state.selected() {
-                    if let Some(site) = self.filtered_sites.get(i) {
-                        let site_uid = site.uid.clone();
-                        self.show_site_move = false;
-                        self.move_selected_device(site_uid, tx);
-                    }
+                if self.popups.profile_switching {
+                    return;
+                }
+                if let Some(name) = self.popups
+                    .profile_list_state
+                    .selected()
+                    .and_then(|i| self.profiles.get(i))
+                    .cloned()
+                {
+                    self.switch_profile(name, tx);
                 }
-            }
-            KeyCode::Char(c) => {
-                self.site_move_query.push(c);
-                self.filter_sites_for_move();
-            }
-            KeyCode::Backspace => {
-                self.site_move_query.pop();
-                self.filter_sites_for_move();
             }
             _ => {}
         }
     }
 
+    /// Connects the profile `name` in the background; `Event::ProfileSwitched`
+    /// swaps it in.
+    fn switch_profile(&mut self, name: String, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        self.popups.profile_switching = true;
+        self.tasks.spawn("switch_profile", async move {
+            let result = match Config::load(Some(&name)) {
+                Ok(config) => {
+                    let settings = ProfileSettings::from(&config);
+                    ProfileClients::connect(config).await.map(|clients| (clients, settings))
+                }
+                Err(e) => Err(e),
+            }
+            .map_err(|e| e.to_string());
+            let _ = tx.send(Event::ProfileSwitched(name, result));
+        });
+    }
+
+    /// Switches to the account of a merged site and opens the site there.
+    pub fn open_merged_site(
+        &mut self,
+        account: String,
+        site_uid: String,
+        tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) {
+        if self.popups.profile_switching {
+            return;
+        }
+        self.open_site_after_switch = Some(site_uid);
+        self.switch_profile(account, tx);
+    }
+
+    /// Shows or hides the sites of the other accounts in `merged_profiles`,
+    /// fetching them when shown.
+    pub fn toggle_merged_sites(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if self.merged_profiles.iter().all(|p| *p == self.active_profile) {
+            self.show_toast("Set merged_profiles in the config file to merge accounts".to_string());
+            return;
+        }
+        self.site_list.show_merged = !self.site_list.show_merged;
+        self.site_list.refresh_visible();
+        if self.site_list.show_merged {
+            self.fetch_merged_sites(tx);
+        }
+    }
+
+    /// Fetches the sites of every other account in `merged_profiles`, each with
+    /// its own client.
+    fn fetch_merged_sites(&self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        for profile in self.merged_profiles.iter().filter(|p| **p != self.active_profile) {
+            let profile = profile.clone();
+            let tx = tx.clone();
+            let on_panic = {
+                let profile = profile.clone();
+                move |e| Event::MergedSitesFetched(profile, Err(e))
+            };
+            self.tasks.spawn_fetch("fetch_merged_sites", on_panic, async move {
+                let result = account_sites(&profile).await.map_err(ApiError::from);
+                let _ = tx.send(Event::MergedSitesFetched(profile, result));
+            });
+        }
+    }
+
     fn handle_device_search_input(
         &mut self,
         key: KeyEvent,
@@ -3993,51 +6561,79 @@ impl App {
     ) {
         match key.code {
             KeyCode::Esc => {
-                self.show_device_search = false;
+                self.popups.close(Popup::DeviceSearch);
+                self.popups.component_to_run = None;
+            }
+            KeyCode::Enter if self.popups.device_search_query.is_empty() => {
+                let query = self
+                    .popups
+                    .device_search_history_state
+                    .selected()
+                    .and_then(|i| self.search_history.query(i))
+                    .map(str::to_string);
+                if let Some(query) = query {
+                    self.run_device_search(query, tx);
+                }
             }
             KeyCode::Enter => {
                 // Select device
-                if let Some(idx) = self.device_search_table_state.selected() {
-                    if let Some(device) = self.device_search_results.get(idx).cloned() {
-                        self.show_device_search = false;
-                        self.navigate_to_device_detail(device, tx);
+                if let Some(device) = self.popups.device_search_results.selected().cloned() {
+                    self.search_history.record(&self.popups.last_searched_query);
+                    self.cache_put(cache::SEARCH_HISTORY_KEY, &self.search_history);
+                    self.popups.close(Popup::DeviceSearch);
+                    self.navigate_to_device_detail(device, tx);
+                    if let Some(component) = self.popups.component_to_run.take() {
+                        self.start_component_run(component);
                     }
                 }
             }
+            KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let devices = self.popups.device_search_results.items().to_vec();
+                self.open_add_to_collection(devices);
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if self.popups.device_search_query.trim().is_empty() {
+                    return;
+                }
+                self.popups.saving_search = Some(String::new());
+            }
+            // Alt+1..9 runs the saved search of that number
+            KeyCode::Char(c @ '1'..='9') if key.modifiers.contains(KeyModifiers::ALT) => {
+                let index = c as usize - '1' as usize;
+                if let Some(search) = self.search_history.saved.get(index) {
+                    let query = search.query.clone();
+                    self.run_device_search(query, tx);
+                }
+            }
             KeyCode::Char(c) => {
-                self.device_search_query.push(c);
-                self.last_search_input = Some(std::time::Instant::now());
+                self.popups.device_search_query.push(c);
+                self.popups.last_search_input = Some(std::time::Instant::now());
             }
             KeyCode::Backspace => {
-                self.device_search_query.pop();
-                self.last_search_input = Some(std::time::Instant::now());
-            }
-            KeyCode::Down | KeyCode::Tab => {
-                let i = match self.device_search_table_state.selected() {
-                    Some(i) => {
-                        if i >= self.device_search_results.len().saturating_sub(1) {
-                            0
-                        } else {
-                            i + 1
-                        }
-                    }
-                    None => 0,
-                };
-                self.device_search_table_state.select(Some(i));
+                self.popups.device_search_query.pop();
+                self.popups.last_search_input = Some(std::time::Instant::now());
             }
-            KeyCode::Up | KeyCode::BackTab => {
-                let i = match self.device_search_table_state.selected() {
-                    Some(i) => {
-                        if i == 0 {
-                            self.device_search_results.len().saturating_sub(1)
-                        } else {
-                            i - 1
-                        }
-                    }
-                    None => 0,
-                };
-                self.device_search_table_state.select(Some(i));
+            KeyCode::PageDown => self.search_more_devices(tx),
+            KeyCode::Delete if self.popups.device_search_query.is_empty() => {
+                if let Some(i) = self.popups.device_search_history_state.selected() {
+                    self.search_history.remove(i);
+                    self.cache_put(cache::SEARCH_HISTORY_KEY, &self.search_history);
+                    let count = self.search_history.len();
+                    self.popups
+                        .device_search_history_state
+                        .select((count > 0).then(|| i.min(count - 1)));
+                }
+            }
+            KeyCode::Down | KeyCode::Tab if self.popups.device_search_query.is_empty() => {
+                let count = self.search_history.len();
+                select_next(&mut self.popups.device_search_history_state, count);
+            }
+            KeyCode::Up | KeyCode::BackTab if self.popups.device_search_query.is_empty() => {
+                let count = self.search_history.len();
+                select_prev(&mut self.popups.device_search_history_state, count);
             }
+            KeyCode::Down | KeyCode::Tab => self.popups.device_search_results.next(),
+            KeyCode::Up | KeyCode::BackTab => self.popups.device_search_results.prev(),
             _ => {}
         }
     }
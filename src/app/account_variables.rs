@@ -0,0 +1,92 @@
+use super::table_view::{select_next, select_prev};
+use crate::api::datto::types::SiteVariable;
+use ratatui::widgets::TableState;
+
+/// State of the Account Variables view. Like the site Variables tab, the row
+/// after the last variable is "+ Create new".
+#[derive(Debug, Default)]
+pub struct AccountVariablesState {
+    /// Sorted by name
+    pub variables: Vec<SiteVariable>,
+    pub loading: bool,
+    pub error: Option<String>,
+    pub table_state: TableState,
+}
+
+impl AccountVariablesState {
+    pub fn set_variables(&mut self, mut variables: Vec<SiteVariable>) {
+        variables.sort_by_key(|v| v.name.to_lowercase());
+        self.variables = variables;
+        self.error = None;
+        self.clamp_selection();
+    }
+
+    /// Puts an updated variable in place of the one with its ID.
+    pub fn replace(&mut self, variable: SiteVariable) {
+        if let Some(existing) = self.variables.iter_mut().find(|v| v.id == variable.id) {
+            *existing = variable;
+        }
+    }
+
+    pub fn remove(&mut self, variable_id: i32) {
+        self.variables.retain(|v| v.id != variable_id);
+        self.clamp_selection();
+    }
+
+    /// The selected variable; `None` on the create row.
+    pub fn selected_variable(&self) -> Option<&SiteVariable> {
+        self.table_state.selected().and_then(|i| self.variables.get(i))
+    }
+
+    pub fn is_create_row_selected(&self) -> bool {
+        self.table_state.selected() == Some(self.variables.len())
+    }
+
+    pub fn next_row(&mut self) {
+        select_next(&mut self.table_state, self.variables.len() + 1);
+    }
+
+    pub fn prev_row(&mut self) {
+        select_prev(&mut self.table_state, self.variables.len() + 1);
+    }
+
+    /// Keeps the selection on a row that still exists, the create row at most.
+    fn clamp_selection(&mut self) {
+        let last = self.variables.len();
+        let selected = self.table_state.selected().map_or(0, |i| i.min(last));
+        self.table_state.select(Some(selected));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(id: i32, name: &str) -> SiteVariable {
+        SiteVariable {
+            id,
+            name: name.to_string(),
+            value: String::new(),
+            masked: false,
+        }
+    }
+
+    #[test]
+    fn test_selection_stays_on_a_variable_or_the_create_row() {
+        let mut state = AccountVariablesState::default();
+        state.set_variables(vec![variable(2, "psaUrl"), variable(1, "AdminUser")]);
+        assert_eq!(state.selected_variable().map(|v| v.id), Some(1));
+
+        state.prev_row();
+        assert!(state.is_create_row_selected());
+        state.next_row();
+        state.next_row();
+        assert_eq!(state.selected_variable().map(|v| v.name.as_str()), Some("psaUrl"));
+
+        state.remove(2);
+        assert!(state.is_create_row_selected());
+        state.remove(1);
+        assert_eq!(state.table_state.selected(), Some(0));
+        assert!(state.selected_variable().is_none());
+    }
+}
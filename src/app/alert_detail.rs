@@ -0,0 +1,110 @@
+use crate::api::datto::types::Alert;
+use crate::config::Runbook;
+
+/// Keys longer than this aren't taken for a `key: value` pair; the line is
+/// likely a sentence with a colon in it.
+const MAX_KEY_LEN: usize = 32;
+
+/// The alert shown in the Alert Detail popup.
+#[derive(Debug, Clone)]
+pub struct AlertDetail {
+    pub alert: Alert,
+    /// The configured fix for the alert's monitor, if any
+    pub runbook: Option<Runbook>,
+    pub scroll: u16,
+}
+
+impl AlertDetail {
+    pub fn new(alert: Alert, runbooks: &[Runbook]) -> Self {
+        let runbook = runbook_for(runbooks, &alert).cloned();
+        Self { alert, runbook, scroll: 0 }
+    }
+
+    /// What raised the alert, e.g. "Online offline status" for
+    /// `online_offline_status_ctx`.
+    pub fn monitor(&self) -> Option<String> {
+        let class = self.alert.alert_context.as_ref()?.class.as_deref()?;
+        let words = class.trim_end_matches("_ctx").replace('_', " ");
+        let mut chars = words.trim().chars();
+        let first = chars.next()?;
+        Some(first.to_uppercase().chain(chars).collect())
+    }
+}
+
+/// The first runbook for the monitor that raised `alert`.
+pub fn runbook_for<'a>(runbooks: &'a [Runbook], alert: &Alert) -> Option<&'a Runbook> {
+    let class = alert.alert_context.as_ref()?.class.as_deref()?;
+    let monitor = |name: &str| name.trim().trim_end_matches("_ctx").to_lowercase();
+    runbooks.iter().find(|r| monitor(&r.monitor) == monitor(class))
+}
+
+/// The alert's diagnostics as (key, value) lines: JSON is pretty-printed,
+/// `key: value` and `key=value` lines are split, and any other line comes
+/// with an empty key.
+pub fn diagnostic_lines(diagnostics: &str) -> Vec<(String, String)> {
+    let text = diagnostics.trim();
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(text)
+        && (json.is_object() || json.is_array())
+    {
+        let pretty = serde_json::to_string_pretty(&json).unwrap_or_else(|_| text.to_string());
+        return pretty.lines().map(|line| (String::new(), line.to_string())).collect();
+    }
+    text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let pair = line
+                .split_once(": ")
+                .or_else(|| line.split_once('='))
+                .filter(|(key, _)| !key.trim().is_empty() && key.trim().len() <= MAX_KEY_LEN);
+            match pair {
+                Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+                None => (String::new(), line.to_string()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics_split_into_pairs_or_pretty_json() {
+        let lines = diagnostic_lines(
+            "Service: Spooler\nState=Stopped\n\nThe service stopped unexpectedly, restart it: now",
+        );
+        assert_eq!(
+            lines,
+            vec![
+                ("Service".to_string(), "Spooler".to_string()),
+                ("State".to_string(), "Stopped".to_string()),
+                (
+                    String::new(),
+                    "The service stopped unexpectedly, restart it: now".to_string()
+                ),
+            ]
+        );
+
+        let json = diagnostic_lines(r#"{"disk":"C:","free":"4%"}"#);
+        assert_eq!(json.len(), 4);
+        assert!(json.iter().all(|(key, _)| key.is_empty()));
+        assert_eq!(json[1].1, r#"  "disk": "C:","#);
+
+        let alert: Alert = serde_json::from_value(serde_json::json!({
+            "alertContext": { "@class": "online_offline_status_ctx", "status": "OFFLINE" },
+        }))
+        .unwrap();
+        let runbook = |monitor: &str| Runbook {
+            monitor: monitor.to_string(),
+            name: format!("Fix {}", monitor),
+            url: Some("https://wiki.example/offline".to_string()),
+            component_uid: None,
+            variables: Default::default(),
+        };
+        let runbooks = [runbook("srvc_status"), runbook("Online_Offline_Status")];
+        let detail = AlertDetail::new(alert, &runbooks);
+        assert_eq!(detail.monitor().as_deref(), Some("Online offline status"));
+        assert_eq!(detail.runbook, Some(runbooks[1].clone()));
+    }
+}
@@ -0,0 +1,498 @@
+use super::security::{SecurityProvider, security_provider};
+use super::table_view::{select_next, select_prev};
+use super::{App, Confirmation, PendingAction, Popup, QuickAction};
+use crate::api::datto::types::{Device, QuickJobRequest};
+use crate::api::error::ApiError;
+use crate::cache;
+use crate::config::ConfirmKind;
+use crate::event::Event;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::TableState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// A named set of devices from any sites, kept in the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceCollection {
+    pub name: String,
+    /// Snapshots from when the devices were added, by hostname
+    pub devices: Vec<Device>,
+}
+
+/// What runs on every device of a collection.
+#[derive(Debug, Clone)]
+pub enum CollectionAction {
+    RunComponent(QuickJobRequest),
+    Scan,
+    /// Reboot now or at the given time
+    Reboot(Option<NaiveDateTime>),
+}
+
+impl CollectionAction {
+    pub fn label(&self) -> String {
+        match self {
+            CollectionAction::RunComponent(req) => req.job_name.clone(),
+            CollectionAction::Scan => "AV Scan".to_string(),
+            CollectionAction::Reboot(None) => "Reboot".to_string(),
+            CollectionAction::Reboot(Some(at)) => {
+                format!("Reboot at {}", at.format("%Y-%m-%d %H:%M"))
+            }
+        }
+    }
+}
+
+/// A job or reboot sent to every device of a collection this session,
+/// counted as the devices' results come in.
+#[derive(Debug, Clone)]
+pub struct CollectionRun {
+    pub id: u64,
+    pub collection: String,
+    /// `CollectionAction::label` of what ran
+    pub label: String,
+    pub started_at: DateTime<Utc>,
+    pub total: usize,
+    pub succeeded: usize,
+    /// Hostname and error of each device it failed on
+    pub failures: Vec<(String, String)>,
+}
+
+impl CollectionRun {
+    pub fn is_finished(&self) -> bool {
+        self.succeeded + self.failures.len() >= self.total
+    }
+}
+
+/// State of the Collections view: the saved collections on the left, the
+/// devices of the selected one on the right.
+#[derive(Debug, Default)]
+pub struct CollectionsState {
+    /// By name
+    pub collections: Vec<DeviceCollection>,
+    pub collection_state: TableState,
+    pub device_state: TableState,
+    /// j/k move in the device list rather than the collection list
+    pub devices_focused: bool,
+    /// Runs of this session, oldest first
+    pub runs: Vec<CollectionRun>,
+    next_run_id: u64,
+    /// Devices of a scan run waiting for their agent to load, by hostname
+    queued_scans: HashMap<String, (u64, Device)>,
+}
+
+impl CollectionsState {
+    pub fn set_collections(&mut self, collections: Vec<DeviceCollection>) {
+        self.collections = collections;
+        self.collections.sort_by_key(|c| c.name.to_lowercase());
+        self.clamp_selection();
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DeviceCollection> {
+        self.collections.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Adds `devices` to the collection `name`, creating it if there's none
+    /// by that name (in any case). Returns how many weren't in it yet.
+    pub fn add(&mut self, name: &str, devices: &[Device]) -> usize {
+        let existing = self.collections.iter().position(|c| c.name.eq_ignore_ascii_case(name));
+        let index = existing.unwrap_or_else(|| {
+            let devices = Vec::new();
+            self.collections.push(DeviceCollection { name: name.to_string(), devices });
+            self.collections.len() - 1
+        });
+        let collection = &mut self.collections[index];
+        let before = collection.devices.len();
+        for device in devices {
+            if !collection.devices.iter().any(|d| d.uid == device.uid) {
+                collection.devices.push(device.clone());
+            }
+        }
+        collection.devices.sort_by_key(|d| d.hostname.to_lowercase());
+        let added = collection.devices.len() - before;
+        let name = collection.name.clone();
+        let collections = std::mem::take(&mut self.collections);
+        self.set_collections(collections);
+        let index = self.collections.iter().position(|c| c.name == name);
+        self.collection_state.select(index);
+        self.device_state.select(Some(0));
+        added
+    }
+
+    pub fn selected_collection(&self) -> Option<&DeviceCollection> {
+        self.collection_state.selected().and_then(|i| self.collections.get(i))
+    }
+
+    pub fn selected_device(&self) -> Option<&Device> {
+        let devices = &self.selected_collection()?.devices;
+        self.device_state.selected().and_then(|i| devices.get(i))
+    }
+
+    /// Deletes the selected collection, returning its name.
+    pub fn delete_selected(&mut self) -> Option<String> {
+        let index = self.collection_state.selected().filter(|&i| i < self.collections.len())?;
+        let removed = self.collections.remove(index);
+        self.clamp_selection();
+        Some(removed.name)
+    }
+
+    /// Takes the selected device out of its collection, returning its hostname.
+    pub fn remove_selected_device(&mut self) -> Option<String> {
+        let index = self.collection_state.selected()?;
+        let device = self.device_state.selected()?;
+        let devices = &mut self.collections.get_mut(index)?.devices;
+        if device >= devices.len() {
+            return None;
+        }
+        let removed = devices.remove(device);
+        self.clamp_selection();
+        Some(removed.hostname)
+    }
+
+    pub fn next_row(&mut self) {
+        if self.devices_focused {
+            let count = self.selected_collection().map_or(0, |c| c.devices.len());
+            select_next(&mut self.device_state, count);
+        } else if !self.collections.is_empty() {
+            select_next(&mut self.collection_state, self.collections.len());
+            self.device_state.select(Some(0));
+        }
+    }
+
+    pub fn prev_row(&mut self) {
+        if self.devices_focused {
+            let count = self.selected_collection().map_or(0, |c| c.devices.len());
+            select_prev(&mut self.device_state, count);
+        } else if !self.collections.is_empty() {
+            select_prev(&mut self.collection_state, self.collections.len());
+            self.device_state.select(Some(0));
+        }
+    }
+
+    /// Records a run of `label` on the `total` devices of `collection`;
+    /// returns its id.
+    pub fn start_run(&mut self, collection: &str, label: String, total: usize) -> u64 {
+        self.next_run_id += 1;
+        self.runs.push(CollectionRun {
+            id: self.next_run_id,
+            collection: collection.to_string(),
+            label,
+            started_at: Utc::now(),
+            total,
+            succeeded: 0,
+            failures: Vec::new(),
+        });
+        self.next_run_id
+    }
+
+    /// Counts one device's result; returns the run once every device has
+    /// answered.
+    pub fn record(
+        &mut self,
+        id: u64,
+        hostname: &str,
+        result: Result<(), String>,
+    ) -> Option<&CollectionRun> {
+        let run = self.runs.iter_mut().find(|run| run.id == id)?;
+        match result {
+            Ok(()) => run.succeeded += 1,
+            Err(e) => run.failures.push((hostname.to_string(), e)),
+        }
+        run.is_finished().then_some(&*run)
+    }
+
+    /// Keeps both selections on rows that still exist.
+    fn clamp_selection(&mut self) {
+        let clamp = |state: &mut TableState, len: usize| {
+            state.select((len > 0).then(|| state.selected().unwrap_or(0).min(len - 1)));
+        };
+        clamp(&mut self.collection_state, self.collections.len());
+        let count = self.selected_collection().map_or(0, |c| c.devices.len());
+        clamp(&mut self.device_state, count);
+    }
+}
+
+impl App {
+    /// Opens the Add To Collection popup for `devices`, on the selected
+    /// collection.
+    pub(super) fn open_add_to_collection(&mut self, devices: Vec<Device>) {
+        if devices.is_empty() {
+            return;
+        }
+        self.popups.open(Popup::AddToCollection);
+        self.popups.collection_devices = devices;
+        self.popups.collection_list_state.select(self.collections.collection_state.selected());
+        self.popups.collection_name =
+            self.collections.selected_collection().map(|c| c.name.clone()).unwrap_or_default();
+    }
+
+    /// Typing names a new collection; Up/Down pick an existing one.
+    pub(super) fn handle_add_to_collection_input(&mut self, key: KeyEvent) {
+        let count = self.collections.collections.len();
+        match key.code {
+            KeyCode::Esc => self.popups.close(Popup::AddToCollection),
+            KeyCode::Down | KeyCode::Up => {
+                let state = &mut self.popups.collection_list_state;
+                if key.code == KeyCode::Down {
+                    select_next(state, count);
+                } else {
+                    select_prev(state, count);
+                }
+                let collections = &self.collections.collections;
+                if let Some(collection) = state.selected().and_then(|i| collections.get(i)) {
+                    self.popups.collection_name = collection.name.clone();
+                }
+            }
+            KeyCode::Enter => {
+                let name = self.popups.collection_name.trim().to_string();
+                if name.is_empty() {
+                    return;
+                }
+                let devices = std::mem::take(&mut self.popups.collection_devices);
+                let added = self.collections.add(&name, &devices);
+                self.save_collections();
+                self.popups.close(Popup::AddToCollection);
+                let name = self.collections.get(&name).map_or(name, |c| c.name.clone());
+                let message = match devices.len() - added {
+                    0 => format!("Added {} devices to {}", added, name),
+                    present => {
+                        format!("Added {} devices to {} ({} already in it)", added, name, present)
+                    }
+                };
+                self.show_toast(message);
+            }
+            KeyCode::Char(c) => self.popups.collection_name.push(c),
+            KeyCode::Backspace => {
+                self.popups.collection_name.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn save_collections(&self) {
+        self.cache_put(cache::COLLECTIONS_KEY, &self.collections.collections);
+    }
+
+    /// Removes the selected device from its collection, or deletes the
+    /// selected collection when the collection list has the focus.
+    pub(super) fn remove_from_collections(&mut self) {
+        let message = if self.collections.devices_focused {
+            let name = self.collections.selected_collection().map(|c| c.name.clone());
+            self.collections
+                .remove_selected_device()
+                .zip(name)
+                .map(|(hostname, name)| format!("Removed {} from {}", hostname, name))
+        } else {
+            self.collections.delete_selected().map(|name| format!("Deleted collection {}", name))
+        };
+        if let Some(message) = message {
+            self.save_collections();
+            self.show_toast(message);
+        }
+    }
+
+    /// The actions that run on every device of the selected collection.
+    pub(super) fn open_collection_quick_actions(&mut self) {
+        if self.collections.selected_collection().is_none_or(|c| c.devices.is_empty()) {
+            self.show_toast("Add devices to the collection with '+' first".to_string());
+            return;
+        }
+        self.popups.open(Popup::QuickActions);
+        self.popups.quick_actions = vec![
+            QuickAction::CollectionRunComponent,
+            QuickAction::CollectionScan,
+            QuickAction::CollectionReboot,
+        ];
+        self.popups.quick_action_list_state.select(Some(0));
+    }
+
+    /// Name of the collection selected in the Collections view.
+    pub(super) fn selected_collection_name(&self) -> Option<String> {
+        self.collections.selected_collection().map(|c| c.name.clone())
+    }
+
+    /// Asks before running `action` on the whole collection.
+    pub(super) fn confirm_collection_run(
+        &mut self,
+        collection: String,
+        action: CollectionAction,
+        tx: UnboundedSender<Event>,
+    ) {
+        let count = self.collections.get(&collection).map_or(0, |c| c.devices.len());
+        let confirmation = Confirmation::new(
+            ConfirmKind::BulkRun,
+            "Run On Collection",
+            format!("{} on all {} devices of {}?", action.label(), count, collection),
+            PendingAction::CollectionRun { collection, action },
+        );
+        self.request_confirmation(confirmation, tx);
+    }
+
+    /// Runs `action` on every device of `collection`, whatever their site,
+    /// closing the popup it was set up in.
+    pub(super) fn run_collection_action(
+        &mut self,
+        collection: String,
+        action: CollectionAction,
+        tx: &UnboundedSender<Event>,
+    ) {
+        self.popups.close(Popup::RunComponent);
+        self.popups.close(Popup::Reboot);
+        self.popups.collection_target = None;
+        let Some(devices) = self.collections.get(&collection).map(|c| c.devices.clone()) else {
+            return;
+        };
+        let client = self.client.clone();
+        if client.is_none() && !matches!(action, CollectionAction::Scan) {
+            return;
+        }
+        let id = self.collections.start_run(&collection, action.label(), devices.len());
+        let (label, count) = (action.label(), devices.len());
+        tracing::info!(%collection, action = %label, devices = count, "running on collection");
+        self.show_toast(format!("{} started on {} devices of {}", label, count, collection));
+
+        for device in devices {
+            let Device { uid, hostname, .. } = device.clone();
+            let (client, tx) = (client.clone(), tx.clone());
+            match (&action, client) {
+                (CollectionAction::Scan, _) => self.scan_collection_device(id, device, &tx),
+                (CollectionAction::RunComponent(req), Some(client)) => {
+                    let req = req.clone();
+                    self.tasks.spawn("collection_quick_job", async move {
+                        let result = client.run_quick_job(&uid, req).await.map(|_| ());
+                        let result = result.map_err(ApiError::from);
+                        let _ = tx.send(Event::CollectionActionDone(id, hostname, result));
+                    });
+                }
+                (CollectionAction::Reboot(at), Some(client)) => {
+                    let at = *at;
+                    self.tasks.spawn("collection_reboot", async move {
+                        let result = client.schedule_reboot(&uid, at).await.map(|_| ());
+                        let result = result.map_err(ApiError::from);
+                        let _ = tx.send(Event::CollectionActionDone(id, hostname, result));
+                    });
+                }
+                (_, None) => {}
+            }
+        }
+    }
+
+    /// Scans `device` for run `id`. A device whose agent hasn't loaded yet is
+    /// queued while it loads, and `run_queued_scan` takes it from there.
+    fn scan_collection_device(&mut self, id: u64, device: Device, tx: &UnboundedSender<Event>) {
+        let hostname = device.hostname.clone();
+        let Some(provider) = security_provider(&device) else {
+            self.collection_result(id, &hostname, Err("No supported antivirus".to_string()));
+            return;
+        };
+        if self.security.scans.is_active(&hostname) {
+            self.collection_result(id, &hostname, Err("A scan is already running".to_string()));
+            return;
+        }
+        if self.start_scan(provider, &device, tx) {
+            self.collection_result(id, &hostname, Ok(()));
+            return;
+        }
+        if !self.security.agent_loading(&hostname) {
+            provider.fetch(self, &device, tx);
+        }
+        if self.security.agent_loading(&hostname) {
+            self.collections.queued_scans.insert(hostname, (id, device));
+        } else {
+            let error = format!("{} isn't set up for this device", provider.name());
+            self.collection_result(id, &hostname, Err(error));
+        }
+    }
+
+    /// Whether `provider` started a scan of `device`.
+    fn start_scan(
+        &mut self,
+        provider: &dyn SecurityProvider,
+        device: &Device,
+        tx: &UnboundedSender<Event>,
+    ) -> bool {
+        let before = self.security.scans.jobs.len();
+        provider.scan(self, device, tx);
+        self.security.scans.jobs.len() > before
+    }
+
+    /// Scans the device a collection run queued, now its agent lookup is done.
+    pub(super) fn run_queued_scan(&mut self, hostname: &str, tx: &UnboundedSender<Event>) {
+        let Some((id, device)) = self.collections.queued_scans.remove(hostname) else {
+            return;
+        };
+        let result = match security_provider(&device) {
+            Some(provider) if self.start_scan(provider, &device, tx) => Ok(()),
+            Some(provider) => Err(format!("No {} agent found", provider.name())),
+            None => Err("No supported antivirus".to_string()),
+        };
+        self.collection_result(id, hostname, result);
+    }
+
+    /// Counts one device's result of run `id`, summing the run up once every
+    /// device has answered.
+    pub(super) fn collection_result(
+        &mut self,
+        id: u64,
+        hostname: &str,
+        result: Result<(), String>,
+    ) {
+        if let Err(e) = &result {
+            tracing::warn!(%hostname, error = %e, "collection run failed on device");
+        }
+        let summary = self.collections.record(id, hostname, result).map(|run| {
+            if run.failures.is_empty() {
+                format!("{} on {}: done on all {} devices", run.label, run.collection, run.total)
+            } else {
+                let hostnames: Vec<&str> = run.failures.iter().map(|(h, _)| h.as_str()).collect();
+                format!(
+                    "{} on {}: {} done, failed on {}",
+                    run.label,
+                    run.collection,
+                    run.succeeded,
+                    hostnames.join(", ")
+                )
+            }
+        });
+        if let Some(summary) = summary {
+            self.show_toast(summary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(uid: &str, site: &str) -> Device {
+        DeviceBuilder::new(&uid.to_uppercase()).uid(uid).site(site).build()
+    }
+
+    #[test]
+    fn test_collections_span_sites() {
+        let mut state = CollectionsState::default();
+        let pos = [device("pos2", "s1"), device("pos1", "s2")];
+        assert_eq!(state.add("POS", &pos), 2);
+        assert_eq!(state.add("Servers", &[device("dc1", "s1")]), 1);
+        // Names match in any case, and devices already in it aren't added twice
+        assert_eq!(state.add("pos", &[device("pos1", "s2"), device("pos3", "s3")]), 1);
+        assert_eq!(state.collections.len(), 2);
+        assert_eq!(state.selected_collection().unwrap().name, "POS");
+        let hostnames: Vec<_> =
+            state.get("POS").unwrap().devices.iter().map(|d| d.hostname.as_str()).collect();
+        assert_eq!(hostnames, ["POS1", "POS2", "POS3"]);
+
+        state.devices_focused = true;
+        state.prev_row();
+        assert_eq!(state.remove_selected_device().as_deref(), Some("POS3"));
+        assert_eq!(state.selected_device().unwrap().hostname, "POS2");
+        assert_eq!(state.delete_selected().as_deref(), Some("POS"));
+        assert_eq!(state.selected_collection().unwrap().name, "Servers");
+
+        let id = state.start_run("Servers", "Reboot".to_string(), 2);
+        assert!(state.record(id, "DC1", Ok(())).is_none());
+        let run = state.record(id, "DC2", Err("Offline".to_string())).unwrap();
+        assert_eq!((run.succeeded, run.failures.len()), (1, 1));
+    }
+}
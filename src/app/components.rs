@@ -0,0 +1,359 @@
+use super::table_view::{select_next, select_prev};
+use crate::api::datto::types::{Component, ComponentVariable, QuickJobVariable};
+use crate::common::utils::fuzzy_match;
+use ratatui::widgets::TableState;
+use serde::{Deserialize, Serialize};
+
+/// How many component runs are remembered.
+const COMPONENT_HISTORY_LIMIT: usize = 20;
+
+/// A component run from the Run Component popup, kept so it can be re-run
+/// with the same inputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentRun {
+    pub uid: String,
+    pub name: String,
+    pub variables: Vec<QuickJobVariable>,
+    /// Unix seconds
+    pub ran_at: i64,
+}
+
+/// One allowed value of a selection ("map") component variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableChoice {
+    pub label: String,
+    pub value: String,
+}
+
+pub fn is_selection(var: &ComponentVariable) -> bool {
+    var.variable_type
+        .as_deref()
+        .is_some_and(|t| t.eq_ignore_ascii_case("map") || t.eq_ignore_ascii_case("selection"))
+}
+
+/// Allowed values of a selection variable. They come from `options` when the
+/// API sends them, otherwise from the default value, which then holds one
+/// `label=value` entry per line (or comma separated). Empty for other types.
+pub fn variable_choices(var: &ComponentVariable) -> Vec<VariableChoice> {
+    if !is_selection(var) {
+        return Vec::new();
+    }
+    let entries: Vec<&str> = match (&var.options, &var.default_val) {
+        (Some(options), _) if !options.is_empty() => options.iter().map(String::as_str).collect(),
+        (_, Some(default)) if default.contains('\n') => default.lines().collect(),
+        (_, Some(default)) if default.contains(',') => default.split(',').collect(),
+        _ => Vec::new(),
+    };
+    entries
+        .into_iter()
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((label, value)) => VariableChoice {
+                label: label.trim().to_string(),
+                value: value.trim().to_string(),
+            },
+            None => VariableChoice {
+                label: entry.to_string(),
+                value: entry.to_string(),
+            },
+        })
+        .collect()
+}
+
+/// Which input the Run Component popup shows for a variable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariableKind {
+    Text,
+    /// A selection with known allowed values, see [`variable_choices`]
+    Selection,
+    Boolean,
+    /// YYYY-MM-DD
+    Date,
+    Integer,
+}
+
+pub fn variable_kind(var: &ComponentVariable) -> VariableKind {
+    if is_selection(var) {
+        return if variable_choices(var).is_empty() {
+            VariableKind::Text
+        } else {
+            VariableKind::Selection
+        };
+    }
+    match var.variable_type.as_deref().map(str::to_lowercase).as_deref() {
+        Some("boolean" | "bool") => VariableKind::Boolean,
+        Some("date") => VariableKind::Date,
+        Some("integer" | "int" | "number") => VariableKind::Integer,
+        _ => VariableKind::Text,
+    }
+}
+
+/// Checks `value` against the type of `var`. Dates and numbers may be left
+/// empty.
+pub fn check_variable_value(var: &ComponentVariable, value: &str) -> Result<(), String> {
+    let valid = match variable_kind(var) {
+        VariableKind::Text => true,
+        VariableKind::Selection => variable_choices(var).iter().any(|c| c.value == value),
+        VariableKind::Boolean => {
+            value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false")
+        }
+        VariableKind::Date => {
+            value.is_empty() || chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+        }
+        VariableKind::Integer => value.is_empty() || value.parse::<i64>().is_ok(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(match variable_kind(var) {
+            VariableKind::Date => format!("'{}' is not a valid YYYY-MM-DD date", value),
+            VariableKind::Integer => format!("'{}' is not a whole number", value),
+            _ => format!("'{}' is not an allowed value of {}", value, var.name),
+        })
+    }
+}
+
+/// The first value (index, message) that doesn't fit its variable's type.
+pub fn invalid_variable(
+    definitions: &[ComponentVariable],
+    values: &[QuickJobVariable],
+) -> Option<(usize, String)> {
+    values.iter().enumerate().find_map(|(i, value)| {
+        let definition = definitions.iter().find(|d| d.name == value.name)?;
+        check_variable_value(definition, &value.value)
+            .err()
+            .map(|message| (i, message))
+    })
+}
+
+/// State of the account-wide components view.
+#[derive(Debug, Default)]
+pub struct ComponentsState {
+    /// All components of the account, sorted by name
+    pub components: Vec<Component>,
+    pub loading: bool,
+    pub error: Option<String>,
+    /// Fuzzy filter on name, category and description, typed after `/`
+    pub filter_query: String,
+    pub is_filtering: bool,
+    /// Selection within the visible rows (see `visible_components`)
+    pub table_state: TableState,
+    /// Uids of starred components, persisted in the cache
+    pub starred: Vec<String>,
+    /// Recent runs, newest first and one per component, persisted in the cache
+    pub history: Vec<ComponentRun>,
+    /// Indexes into `components` that pass the filter
+    visible: Vec<usize>,
+}
+
+impl ComponentsState {
+    pub fn set_components(&mut self, mut components: Vec<Component>) {
+        components.sort_by_key(|c| c.name.to_lowercase());
+        self.components = components;
+        self.error = None;
+        self.refresh_visible();
+    }
+
+    pub fn is_starred(&self, uid: &str) -> bool {
+        self.starred.iter().any(|s| s == uid)
+    }
+
+    /// Stars `uid`, or unstars it if already starred. Returns the new state.
+    pub fn toggle_star(&mut self, uid: &str) -> bool {
+        if self.is_starred(uid) {
+            self.starred.retain(|s| s != uid);
+            false
+        } else {
+            self.starred.push(uid.to_string());
+            true
+        }
+    }
+
+    pub fn last_run(&self, uid: &str) -> Option<&ComponentRun> {
+        self.history.iter().find(|run| run.uid == uid)
+    }
+
+    /// Moves `run` to the front of the history, replacing the component's older run.
+    pub fn record_run(&mut self, run: ComponentRun) {
+        self.history.retain(|r| r.uid != run.uid);
+        self.history.insert(0, run);
+        self.history.truncate(COMPONENT_HISTORY_LIMIT);
+    }
+
+    /// Orders `components` for the Run Component search: starred first, then
+    /// recently run (newest first), then the rest in their current order.
+    pub fn rank(&self, components: &mut [Component]) {
+        components.sort_by_key(|c| {
+            let history = self.history.iter().position(|r| r.uid == c.uid);
+            (!self.is_starred(&c.uid), history.is_none(), history)
+        });
+    }
+
+    pub fn visible_components(&self) -> impl Iterator<Item = &Component> {
+        self.visible.iter().filter_map(|&i| self.components.get(i))
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.visible.len()
+    }
+
+    pub fn selected_component(&self) -> Option<&Component> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.visible.get(i))
+            .and_then(|&i| self.components.get(i))
+    }
+
+    pub fn next_row(&mut self) {
+        select_next(&mut self.table_state, self.visible.len());
+    }
+
+    pub fn prev_row(&mut self) {
+        select_prev(&mut self.table_state, self.visible.len());
+    }
+
+    /// Recomputes the visible rows after the filter changed, keeping the
+    /// selected component if it still passes.
+    pub fn refresh_visible(&mut self) {
+        let selected = self.selected_component().map(|c| c.uid.clone());
+        self.visible = self
+            .components
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| {
+                fuzzy_match(&self.filter_query, &c.name)
+                    || c.category_code
+                        .as_deref()
+                        .is_some_and(|cat| fuzzy_match(&self.filter_query, cat))
+                    || c.description.as_deref().is_some_and(|d| {
+                        d.to_lowercase().contains(&self.filter_query.to_lowercase())
+                    })
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let position = selected.and_then(|uid| {
+            self.visible
+                .iter()
+                .position(|&i| self.components[i].uid == uid)
+        });
+        self.table_state.select(match position {
+            Some(p) => Some(p),
+            None if self.visible.is_empty() => None,
+            None => Some(0),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(uid: &str, name: &str, category: &str) -> Component {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uid": uid,
+            "name": name,
+            "categoryCode": category,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rank_starred_then_recent() {
+        let mut state = ComponentsState::default();
+        let mut components = vec![
+            component("a", "A", ""),
+            component("b", "B", ""),
+            component("c", "C", ""),
+            component("d", "D", ""),
+        ];
+        state.toggle_star("c");
+        for uid in ["d", "b"] {
+            state.record_run(ComponentRun {
+                uid: uid.to_string(),
+                name: uid.to_uppercase(),
+                variables: Vec::new(),
+                ran_at: 0,
+            });
+        }
+        state.rank(&mut components);
+        let order: Vec<_> = components.iter().map(|c| c.uid.as_str()).collect();
+        assert_eq!(order, vec!["c", "b", "d", "a"]);
+    }
+
+    #[test]
+    fn test_selection_choices_and_validation() {
+        let var: ComponentVariable = serde_json::from_value(serde_json::json!({
+            "name": "mode",
+            "type": "map",
+            "defaultVal": "Quick scan=quick\nFull scan=full\n",
+        }))
+        .unwrap();
+        let choices = variable_choices(&var);
+        assert_eq!(
+            choices,
+            vec![
+                VariableChoice {
+                    label: "Quick scan".into(),
+                    value: "quick".into()
+                },
+                VariableChoice {
+                    label: "Full scan".into(),
+                    value: "full".into()
+                },
+            ]
+        );
+
+        let value = |v: &str| QuickJobVariable {
+            name: "mode".into(),
+            value: v.into(),
+        };
+        let definitions = vec![var];
+        assert!(invalid_variable(&definitions, &[value("full")]).is_none());
+        assert_eq!(
+            invalid_variable(&definitions, &[value("Full scan")]).map(|(i, _)| i),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let var = |kind: &str| -> ComponentVariable {
+            serde_json::from_value(serde_json::json!({ "name": "v", "type": kind })).unwrap()
+        };
+        assert_eq!(variable_kind(&var("Boolean")), VariableKind::Boolean);
+        assert!(check_variable_value(&var("Boolean"), "True").is_ok());
+        assert!(check_variable_value(&var("Boolean"), "yes").is_err());
+        assert!(check_variable_value(&var("Date"), "2026-02-28").is_ok());
+        assert!(check_variable_value(&var("Date"), "2026-02-30").is_err());
+        assert!(check_variable_value(&var("Date"), "").is_ok());
+        assert!(check_variable_value(&var("Integer"), "-5").is_ok());
+        assert!(check_variable_value(&var("Integer"), "5a").is_err());
+        // A selection without known values falls back to free text
+        assert_eq!(variable_kind(&var("map")), VariableKind::Text);
+    }
+
+    #[test]
+    fn test_filter_matches_name_and_category() {
+        let mut state = ComponentsState::default();
+        state.set_components(vec![
+            component("b", "Clear Spooler", "Scripts"),
+            component("a", "Audit Disks", "Monitors"),
+        ]);
+        let names: Vec<_> = state
+            .visible_components()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Audit Disks", "Clear Spooler"]);
+
+        state.table_state.select(Some(1));
+        state.filter_query = "script".to_string();
+        state.refresh_visible();
+        assert_eq!(state.visible_count(), 1);
+        assert_eq!(
+            state.selected_component().map(|c| c.uid.as_str()),
+            Some("b")
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use super::CollectionAction;
+use super::site_settings::ProxyType;
+use crate::api::datto::types::{
+    Device, ProxySettings, QuickJobRequest, Site, UpdateDeviceRequest, UpdateSiteRequest,
+};
+use crate::config::ConfirmKind;
+
+/// A destructive change held back by the confirmation popup; it runs once
+/// the user confirms (or right away when its kind isn't in `confirm_actions`).
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    SiteUpdate {
+        site_uid: String,
+        req: UpdateSiteRequest,
+    },
+    /// Save the site's proxy settings; `None` removes the proxy
+    SiteProxyUpdate {
+        site_uid: String,
+        proxy: Option<ProxySettings>,
+    },
+    DeviceUpdate {
+        device_uid: String,
+        req: UpdateDeviceRequest,
+    },
+    /// Set UDF `number` of a device
+    Udf {
+        device_uid: String,
+        number: usize,
+        value: String,
+    },
+    DeleteVariable {
+        site_uid: String,
+        variable_id: i32,
+    },
+    DeleteAccountVariable {
+        variable_id: i32,
+    },
+    /// Scan the open device with its antivirus
+    AvScan,
+    /// Isolate the open device in its antivirus console
+    Isolate,
+    /// Reboot the open device now or at the given time
+    Reboot(Option<chrono::NaiveDateTime>),
+    ResolveAlert {
+        alert_uid: String,
+    },
+    /// Run a job's component again on its device
+    RerunJob {
+        device_uid: String,
+        req: QuickJobRequest,
+    },
+    /// Cancel a scheduled job before it runs
+    CancelJob {
+        job_uid: String,
+    },
+    /// Run `action` on every device of the named collection
+    CollectionRun {
+        collection: String,
+        action: CollectionAction,
+    },
+}
+
+/// One field a confirmed action changes, shown as old → new.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+/// Fields of `site` that `req` changes. Unset optional fields in `req` are
+/// left alone by the API, so they aren't changes.
+pub fn site_changes(site: &Site, req: &UpdateSiteRequest) -> Vec<FieldChange> {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let flag = |value: Option<bool>| if value.unwrap_or(false) { "On" } else { "Off" }.to_string();
+    let mut changes = Vec::new();
+    let mut push = |field, old: String, new: Option<String>| {
+        if let Some(new) = new.filter(|new| *new != old) {
+            changes.push(FieldChange { field, old, new });
+        }
+    };
+    push("Name", site.name.clone(), Some(req.name.clone()));
+    push("Description", text(&site.description), req.description.clone());
+    push("Notes", text(&site.notes), req.notes.clone());
+    push("On Demand", flag(site.on_demand), req.on_demand.map(|v| flag(Some(v))));
+    push(
+        "Splashtop Auto Install",
+        flag(site.splashtop_auto_install),
+        req.splashtop_auto_install.map(|v| flag(Some(v))),
+    );
+    push("Autotask Company ID", text(&site.autotask_company_id), req.autotask_company_id.clone());
+    push(
+        "Autotask Company Name",
+        text(&site.autotask_company_name),
+        req.autotask_company_name.clone(),
+    );
+    changes
+}
+
+/// Proxy fields that saving `proxy` changes on `site`; adding or removing the
+/// proxy is a single change. Passwords are masked.
+pub fn proxy_changes(site: &Site, proxy: &Option<ProxySettings>) -> Vec<FieldChange> {
+    let kind = |p: &ProxySettings| ProxyType::parse(p.type_field.as_deref().unwrap_or_default());
+    let summary = |p: &ProxySettings| {
+        let host = p.host.clone().unwrap_or_default();
+        match p.port {
+            Some(port) => format!("{} {}:{}", kind(p).name(), host, port),
+            None => format!("{} {}", kind(p).name(), host),
+        }
+    };
+    let current = site.proxy_settings.as_ref().filter(|p| kind(p) != ProxyType::None);
+    let (old, new) = match (current, proxy) {
+        (None, None) => return Vec::new(),
+        (old, None) | (old @ None, _) => {
+            let none = || "None".to_string();
+            return vec![FieldChange {
+                field: "Proxy",
+                old: old.map_or_else(none, summary),
+                new: proxy.as_ref().map_or_else(none, summary),
+            }];
+        }
+        (Some(old), Some(new)) => (old, new),
+    };
+    let mut changes = Vec::new();
+    let mut push = |field, old: String, new: String| {
+        if old != new {
+            changes.push(FieldChange { field, old, new });
+        }
+    };
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let port = |p: &ProxySettings| p.port.map(|port| port.to_string()).unwrap_or_default();
+    push("Proxy Type", kind(old).name().to_string(), kind(new).name().to_string());
+    push("Proxy Host", text(&old.host), text(&new.host));
+    push("Proxy Port", port(old), port(new));
+    push("Proxy Username", text(&old.username), text(&new.username));
+    if old.password != new.password {
+        let mask = |value: &Option<String>| if value.is_some() { "••••" } else { "" }.to_string();
+        changes.push(FieldChange {
+            field: "Proxy Password",
+            old: mask(&old.password),
+            new: if new.password.is_some() { "(new password)".to_string() } else { String::new() },
+        });
+    }
+    changes
+}
+
+/// Fields of `device` that `req` changes.
+pub fn device_changes(device: &Device, req: &UpdateDeviceRequest) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut push = |field, old: &Option<String>, new: &Option<String>| {
+        let old = old.clone().unwrap_or_default();
+        if let Some(new) = new.clone().filter(|new| *new != old) {
+            changes.push(FieldChange { field, old, new });
+        }
+    };
+    push("Description", &device.description, &req.description);
+    push("Friendly Name", &device.display_name, &req.display_name);
+    changes
+}
+
+/// Contents of the confirmation popup.
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+    pub kind: ConfirmKind,
+    pub title: String,
+    pub message: String,
+    /// Shown below the message as a diff
+    pub changes: Vec<FieldChange>,
+    pub action: PendingAction,
+}
+
+impl Confirmation {
+    pub fn new(
+        kind: ConfirmKind,
+        title: impl Into<String>,
+        message: impl Into<String>,
+        action: PendingAction,
+    ) -> Self {
+        Self {
+            kind,
+            title: title.into(),
+            message: message.into(),
+            changes: Vec::new(),
+            action,
+        }
+    }
+
+    pub fn with_changes(mut self, changes: Vec<FieldChange>) -> Self {
+        self.changes = changes;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    #[test]
+    fn test_site_changes_lists_only_changed_fields() {
+        let site: Site = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uid": "s1",
+            "name": "Acme",
+            "notes": "Gate code 1234",
+            "onDemand": false,
+        }))
+        .unwrap();
+        let req = UpdateSiteRequest {
+            name: "Acme".to_string(),
+            description: Some(String::new()),
+            notes: Some("Gate code 4321".to_string()),
+            on_demand: Some(true),
+            splashtop_auto_install: None,
+            autotask_company_id: None,
+            autotask_company_name: None,
+        };
+        let changes = site_changes(&site, &req);
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    field: "Notes",
+                    old: "Gate code 1234".to_string(),
+                    new: "Gate code 4321".to_string(),
+                },
+                FieldChange { field: "On Demand", old: "Off".to_string(), new: "On".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_device_changes_ignore_unset_fields() {
+        let device = DeviceBuilder::new("PC-1").with("description", "Reception").build();
+        let req = UpdateDeviceRequest {
+            description: Some("Reception".to_string()),
+            display_name: Some("Front desk".to_string()),
+        };
+        let changes = device_changes(&device, &req);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].field, changes[0].new.as_str()), ("Friendly Name", "Front desk"));
+        assert!(device_changes(&device, &UpdateDeviceRequest::default()).is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+/// Where the device's antivirus endpoint lives, found from its AV product.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvEndpoint {
+    Sophos {
+        tenant_id: String,
+        region: String,
+        endpoint_id: String,
+    },
+    DattoAv {
+        agent_id: String,
+    },
+}
+
+impl AvEndpoint {
+    pub fn console(&self) -> &'static str {
+        match self {
+            AvEndpoint::Sophos { .. } => "Sophos",
+            AvEndpoint::DattoAv { .. } => "Datto AV",
+        }
+    }
+}
+
+/// Removing a device from RMM, guarded by typing its hostname.
+#[derive(Debug, Clone, Default)]
+pub struct Decommission {
+    pub device_uid: String,
+    pub hostname: String,
+    /// What the user typed so far
+    pub typed: String,
+    /// The device's antivirus endpoint, if it was found
+    pub av: Option<AvEndpoint>,
+    /// Also delete `av` once the device is removed
+    pub remove_av: bool,
+    pub running: bool,
+}
+
+impl Decommission {
+    /// Whether the typed text is the hostname. Hostnames aren't case-sensitive,
+    /// but a partial or padded match doesn't count.
+    pub fn confirmed(&self) -> bool {
+        !self.hostname.is_empty() && self.typed.eq_ignore_ascii_case(&self.hostname)
+    }
+
+    /// The AV endpoint to delete along with the device, if asked to.
+    pub fn av_to_remove(&self) -> Option<AvEndpoint> {
+        self.av.clone().filter(|_| self.remove_av)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_full_hostname_confirms() {
+        let mut decommission = Decommission {
+            hostname: "ACME-PC01".to_string(),
+            av: Some(AvEndpoint::DattoAv {
+                agent_id: "a1".to_string(),
+            }),
+            ..Default::default()
+        };
+        for typed in ["", "ACME-PC0", "ACME-PC01 ", "ACME-PC011"] {
+            decommission.typed = typed.to_string();
+            assert!(!decommission.confirmed(), "{:?}", typed);
+        }
+        decommission.typed = "acme-pc01".to_string();
+        assert!(decommission.confirmed());
+
+        assert_eq!(decommission.av_to_remove(), None);
+        decommission.remove_av = true;
+        assert_eq!(decommission.av_to_remove().map(|av| av.console()), Some("Datto AV"));
+    }
+}
@@ -0,0 +1,372 @@
+use super::fetches::Fetches;
+use super::sort::{cmp_text, priority_rank};
+use super::table_view::{TableView, select_next, select_prev};
+use super::{CurrentView, DeviceDetailTab, JobViewRow};
+use crate::api::datto::types::{ActivityLog, Alert, Device, JobResult, Software};
+use crate::common::jobs::{activity_job_summary, generate_job_rows};
+use chrono::{DateTime, Utc};
+use ratatui::widgets::{ListState, TableState};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+
+/// Column headers of the device alerts table, in sort-column order.
+pub const OPEN_ALERT_COLUMNS: [&str; 3] = ["Priority", "Diagnostics", "Time"];
+
+/// Column headers of the resolved alerts table, in sort-column order.
+pub const RESOLVED_ALERT_COLUMNS: [&str; 5] =
+    ["Priority", "Diagnostics", "Raised", "Resolved", "Resolved By"];
+
+/// Column headers of the activities table, in sort-column order.
+pub const ACTIVITY_COLUMNS: [&str; 6] = ["Time", "Activity", "Status", "Action", "Category", "User"];
+
+/// Rows in the device UDF overlay.
+pub const UDF_COUNT: usize = 30;
+
+/// Lines PgUp/PgDn move the device info and security panes.
+const SCROLL_STEP: u16 = 5;
+
+/// The device detail panes that scroll when their content is cut off.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum DevicePane {
+    #[default]
+    Info,
+    Security,
+}
+
+/// State of the device detail view and the activity detail view opened from it.
+#[derive(Debug, Default)]
+pub struct DeviceDetailState {
+    pub selected_device: Option<Device>,
+    pub device_detail_tab: DeviceDetailTab,
+    /// The report or tag list the device was opened from, which Back returns
+    /// to instead of the site
+    pub opened_from: Option<CurrentView>,
+    /// Fetches for the open device, cancelled when another one is opened
+    pub fetches: Fetches,
+
+    // Activity Logs
+    pub activity_logs: TableView<ActivityLog>,
+    pub activity_logs_loading: bool,
+    pub activity_logs_error: Option<String>,
+    /// End of the range the activities were last fetched for; the next live
+    /// fetch starts from here
+    pub activity_synced_at: Option<DateTime<Utc>>,
+    /// When the last live fetch was started
+    pub activity_polled_at: Option<Instant>,
+    pub activity_polling: bool,
+    /// Activities added by live fetches since the table was last moved through
+    pub new_activity_count: usize,
+
+    // Open Alerts
+    pub open_alerts: TableView<Alert>,
+    pub open_alerts_loading: bool,
+    pub open_alerts_error: Option<String>,
+
+    // Resolved Alerts, within the configured lookback
+    pub resolved_alerts: TableView<Alert>,
+    pub resolved_alerts_loading: bool,
+    pub resolved_alerts_error: Option<String>,
+
+    // Software
+    /// Filtered by `software_search_query`
+    pub device_software: TableView<Software>,
+    pub software_search_query: String,
+    pub is_software_searching: bool,
+    pub device_software_loading: bool,
+    pub device_software_error: Option<String>,
+
+    // Policies, listed from the device and its antivirus agent
+    pub policy_state: TableState,
+
+    // Job Results
+    pub selected_activity_log: Option<ActivityLog>,
+    pub selected_job_result: Option<JobResult>,
+    pub job_result_loading: bool,
+    pub job_result_error: Option<String>,
+    pub selected_job_row_index: usize,
+    /// Components of the job result shown without their output links
+    pub collapsed_job_components: HashSet<usize>,
+    /// Viewport of the job components list; it scrolls to the selected row
+    pub job_rows_list_state: ListState,
+
+    // Info and security panes; render clamps the offsets to their content
+    /// The pane PgUp/PgDn scroll
+    pub scrolled_pane: DevicePane,
+    pub info_scroll: u16,
+    pub security_scroll: u16,
+
+    // UDF overlay
+    pub udf_table_state: TableState,
+    pub editing_udf_index: Option<usize>,
+    /// The UDF being edited is the tags UDF, whose value is normalized
+    pub editing_tags: bool,
+}
+
+/// Display name of UDF `number` (1-based): the account's label if configured,
+/// e.g. "Asset Tag (UDF 17)", otherwise just "UDF 17".
+pub fn udf_label(labels: &BTreeMap<usize, String>, number: usize) -> String {
+    match labels.get(&number) {
+        Some(label) => format!("{} (UDF {})", label, number),
+        None => format!("UDF {}", number),
+    }
+}
+
+/// Whether `alert` was resolved at or after `since`. Alerts without a
+/// resolution time go by when they were raised, and are kept if neither is
+/// known.
+pub fn resolved_since(alert: &Alert, since: DateTime<Utc>) -> bool {
+    alert.resolved_at().or_else(|| alert.raised_at()).is_none_or(|at| at >= since)
+}
+
+/// User an activity ran as, "System" when none is recorded.
+pub fn activity_user(log: &ActivityLog) -> String {
+    log.user
+        .as_ref()
+        .and_then(|u| u.user_name.clone())
+        .unwrap_or_else(|| "System".to_string())
+}
+
+impl DeviceDetailState {
+    /// Whether `device_uid` is the open device.
+    pub fn is_selected(&self, device_uid: &str) -> bool {
+        self.selected_device.as_ref().is_some_and(|d| d.uid == device_uid)
+    }
+
+    /// Rows of the job result in the activity detail, without the links of
+    /// collapsed components.
+    pub fn job_rows(&self) -> Vec<JobViewRow> {
+        self.selected_job_result
+            .as_ref()
+            .map(|job| generate_job_rows(job, &self.collapsed_job_components))
+            .unwrap_or_default()
+    }
+
+    pub fn selected_job_row(&self) -> Option<JobViewRow> {
+        self.job_rows().get(self.selected_job_row_index).cloned()
+    }
+
+    /// Collapses or expands the component of the selected row; its header
+    /// stays selected.
+    pub fn toggle_job_component(&mut self) {
+        let Some(component) = self.selected_job_row().map(|row| row.component()) else {
+            return;
+        };
+        if !self.collapsed_job_components.remove(&component) {
+            self.collapsed_job_components.insert(component);
+        }
+        self.select_job_component(component);
+    }
+
+    /// Selects the header of component `index` (0-based) and expands it.
+    pub fn jump_to_job_component(&mut self, index: usize) {
+        self.collapsed_job_components.remove(&index);
+        self.select_job_component(index);
+    }
+
+    fn select_job_component(&mut self, index: usize) {
+        if let Some(row) =
+            self.job_rows().iter().position(|row| *row == JobViewRow::ComponentHeader(index))
+        {
+            self.selected_job_row_index = row;
+        }
+    }
+
+    /// Moves through the activities, which also marks the live ones as seen.
+    pub fn move_activity_log(&mut self, forward: bool) {
+        self.new_activity_count = 0;
+        if forward {
+            self.activity_logs.next();
+        } else {
+            self.activity_logs.prev();
+        }
+    }
+
+    /// Scrolls the pane chosen with `switch_pane` down (or up) a few lines.
+    pub fn scroll(&mut self, down: bool) {
+        let offset = match self.scrolled_pane {
+            DevicePane::Info => &mut self.info_scroll,
+            DevicePane::Security => &mut self.security_scroll,
+        };
+        *offset = if down {
+            offset.saturating_add(SCROLL_STEP)
+        } else {
+            offset.saturating_sub(SCROLL_STEP)
+        };
+    }
+
+    pub fn switch_pane(&mut self) {
+        self.scrolled_pane = match self.scrolled_pane {
+            DevicePane::Info => DevicePane::Security,
+            DevicePane::Security => DevicePane::Info,
+        };
+    }
+
+    /// Back to the top of both panes, for another device.
+    pub fn reset_scroll(&mut self) {
+        self.info_scroll = 0;
+        self.security_scroll = 0;
+    }
+
+    pub fn next_udf(&mut self) {
+        select_next(&mut self.udf_table_state, UDF_COUNT);
+    }
+
+    pub fn prev_udf(&mut self) {
+        select_prev(&mut self.udf_table_state, UDF_COUNT);
+    }
+
+    /// Re-applies `software_search_query` to the software list.
+    pub fn filter_software(&mut self) {
+        let query = self.software_search_query.to_lowercase();
+        self.device_software.filter(|s| {
+            query.is_empty()
+                || s.name.to_lowercase().contains(&query)
+                || s.version.to_lowercase().contains(&query)
+        });
+        self.device_software.select_first();
+    }
+
+    pub fn sort_open_alerts(&mut self) {
+        let time = |a: &Alert| a.raised_at();
+        self.open_alerts.sort_by_column(|column, a, b| match column {
+            0 => priority_rank(a.priority.as_deref()).cmp(&priority_rank(b.priority.as_deref())),
+            1 => cmp_text(
+                a.diagnostics.as_deref().unwrap_or(""),
+                b.diagnostics.as_deref().unwrap_or(""),
+            ),
+            _ => time(a).cmp(&time(b)),
+        });
+    }
+
+    /// Sorts the resolved alerts by the chosen column, most recently resolved
+    /// first while unsorted.
+    pub fn sort_resolved_alerts(&mut self) {
+        if self.resolved_alerts.sort.column.is_none() {
+            self.resolved_alerts.sort_by(|a, b| b.resolved_at().cmp(&a.resolved_at()));
+            return;
+        }
+        let text = |v: &Option<String>| v.clone().unwrap_or_default();
+        self.resolved_alerts.sort_by_column(|column, a, b| match column {
+            0 => priority_rank(a.priority.as_deref()).cmp(&priority_rank(b.priority.as_deref())),
+            1 => cmp_text(&text(&a.diagnostics), &text(&b.diagnostics)),
+            2 => a.raised_at().cmp(&b.raised_at()),
+            3 => a.resolved_at().cmp(&b.resolved_at()),
+            _ => cmp_text(&text(&a.resolved_by), &text(&b.resolved_by)),
+        });
+    }
+
+    /// Adds the activities from a live fetch that aren't listed yet, newest
+    /// first, keeping the selected row. Returns how many were added.
+    pub fn merge_new_activities(&mut self, mut logs: Vec<ActivityLog>) -> usize {
+        let known: HashSet<&str> =
+            self.activity_logs.items().iter().filter_map(|l| l.id.as_deref()).collect();
+        logs.retain(|l| l.id.as_deref().is_none_or(|id| !known.contains(id)));
+        logs.sort_by_key(|l| std::cmp::Reverse(l.at()));
+        let added = logs.len();
+        if added == 0 {
+            return 0;
+        }
+
+        self.activity_logs.prepend(logs);
+        self.sort_activity_logs();
+        self.new_activity_count += added;
+        added
+    }
+
+    pub fn sort_activity_logs(&mut self) {
+        let text = |v: &Option<String>| v.clone().unwrap_or_default();
+        self.activity_logs.sort_by_column(|column, a, b| match column {
+            0 => a.at().cmp(&b.at()),
+            1 => cmp_text(&activity_job_summary(a).0, &activity_job_summary(b).0),
+            2 => cmp_text(&activity_job_summary(a).1, &activity_job_summary(b).1),
+            3 => cmp_text(&text(&a.action), &text(&b.action)),
+            4 => cmp_text(&text(&a.category), &text(&b.category)),
+            _ => cmp_text(&activity_user(a), &activity_user(b)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(id: &str, date: f64) -> ActivityLog {
+        serde_json::from_value(serde_json::json!({ "id": id, "date": date })).unwrap()
+    }
+
+    #[test]
+    fn test_merge_new_activities_skips_known_and_keeps_selection() {
+        let mut state = DeviceDetailState::default();
+        state.activity_logs.set_items(vec![log("b", 20.0), log("a", 10.0)]);
+        state.activity_logs.state.select(Some(1));
+
+        // The live fetch overlaps the last one, so "b" comes back again
+        let polled = vec![log("c", 30.0), log("b", 20.0), log("d", 40.0)];
+        let added = state.merge_new_activities(polled);
+        assert_eq!(added, 2);
+        assert_eq!(state.new_activity_count, 2);
+        let ids: Vec<_> = state.activity_logs.rows().filter_map(|l| l.id.as_deref()).collect();
+        assert_eq!(ids, ["d", "c", "b", "a"]);
+        assert_eq!(state.activity_logs.state.selected(), Some(3));
+
+        state.move_activity_log(true);
+        assert_eq!(state.new_activity_count, 0);
+    }
+
+    #[test]
+    fn test_resolved_alerts_stay_within_the_lookback() {
+        let alert = |value: serde_json::Value| -> Alert { serde_json::from_value(value).unwrap() };
+        let since = DateTime::from_timestamp(1_000_000, 0).unwrap();
+        let mut state = DeviceDetailState::default();
+        state.resolved_alerts.set_items(vec![
+            alert(serde_json::json!({ "alertUid": "old", "resolvedOn": 500_000 })),
+            alert(serde_json::json!({ "alertUid": "new", "resolvedOn": 2_000_000 })),
+            // Raised before the window but resolved inside it
+            alert(serde_json::json!({
+                "alertUid": "long", "timestamp": 100, "resolvedOn": 1_500_000,
+            })),
+            alert(serde_json::json!({ "alertUid": "unknown" })),
+        ]);
+        state.resolved_alerts.retain(|a| resolved_since(a, since));
+        state.sort_resolved_alerts();
+        let uids: Vec<_> =
+            state.resolved_alerts.rows().filter_map(|a| a.alert_uid.as_deref()).collect();
+        assert_eq!(uids, ["new", "long", "unknown"]);
+
+        state.resolved_alerts.sort.column = Some(3);
+        state.sort_resolved_alerts();
+        let uids: Vec<_> =
+            state.resolved_alerts.rows().filter_map(|a| a.alert_uid.as_deref()).collect();
+        assert_eq!(uids, ["unknown", "long", "new"]);
+    }
+
+    #[test]
+    fn test_collapsed_job_components_hide_their_output_links() {
+        let component = |name: &str| {
+            serde_json::json!({ "componentName": name, "hasStdOut": true, "hasStdErr": true })
+        };
+        let mut state = DeviceDetailState {
+            selected_job_result: Some(
+                serde_json::from_value(serde_json::json!({
+                    "componentResults": [component("A"), component("B"), component("C")],
+                }))
+                .unwrap(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(state.job_rows().len(), 9);
+
+        // Collapsing from an output link selects the component's header
+        state.selected_job_row_index = 4;
+        state.toggle_job_component();
+        assert_eq!(state.job_rows().len(), 7);
+        assert_eq!(state.selected_job_row(), Some(JobViewRow::ComponentHeader(1)));
+
+        state.jump_to_job_component(2);
+        assert_eq!(state.selected_job_row(), Some(JobViewRow::ComponentHeader(2)));
+        state.jump_to_job_component(1);
+        assert_eq!(state.job_rows().len(), 9);
+        state.jump_to_job_component(7);
+        assert_eq!(state.selected_job_row(), Some(JobViewRow::ComponentHeader(1)));
+    }
+}
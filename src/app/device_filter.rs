@@ -0,0 +1,107 @@
+use super::site_detail::{device_type_label, patch_status_label};
+use crate::api::datto::types::Device;
+
+/// A parsed device filter such as `online:true patch:RebootRequired os:server`.
+///
+/// Terms are space-separated and all must match. `key:value` terms test one
+/// field; bare words match the hostname or description. Text matches are
+/// case-insensitive substrings.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DeviceFilter {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Online(bool),
+    Patch(String),
+    Os(String),
+    Type(String),
+    Host(String),
+    Text(String),
+}
+
+/// Keys understood by [`DeviceFilter::parse`], for error messages.
+const KEYS: &str = "online, patch, os, type, host";
+
+impl DeviceFilter {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+        for word in query.split_whitespace() {
+            let Some((key, value)) = word.split_once(':') else {
+                terms.push(Term::Text(word.to_lowercase()));
+                continue;
+            };
+            let value = value.to_lowercase();
+            let term = match key.to_lowercase().as_str() {
+                "online" => match value.as_str() {
+                    "true" | "yes" | "1" => Term::Online(true),
+                    "false" | "no" | "0" => Term::Online(false),
+                    _ => return Err(format!("online expects true or false, got '{}'", value)),
+                },
+                "patch" => Term::Patch(value),
+                "os" => Term::Os(value),
+                "type" => Term::Type(value),
+                "host" | "hostname" => Term::Host(value),
+                other => return Err(format!("Unknown filter '{}' (use {})", other, KEYS)),
+            };
+            terms.push(term);
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, device: &Device) -> bool {
+        let contains = |field: Option<&str>, needle: &str| {
+            field.is_some_and(|f| f.to_lowercase().contains(needle))
+        };
+        self.terms.iter().all(|term| match term {
+            Term::Online(online) => device.online == *online,
+            Term::Patch(status) => patch_status_label(device).to_lowercase().contains(status),
+            Term::Os(os) => contains(device.operating_system.as_deref(), os),
+            Term::Type(t) => device_type_label(device).to_lowercase().contains(t),
+            Term::Host(host) => contains(Some(&device.hostname), host),
+            Term::Text(text) => {
+                contains(Some(&device.hostname), text)
+                    || contains(device.description.as_deref(), text)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(hostname: &str, online: bool, os: &str, patch: &str) -> Device {
+        DeviceBuilder::new(hostname)
+            .uid(hostname)
+            .site("site")
+            .online(online)
+            .with("operatingSystem", os)
+            .patch_status(patch)
+            .build()
+    }
+
+    #[test]
+    fn test_device_filter_terms() {
+        let dc = device("DC01", true, "Windows Server 2022", "RebootRequired");
+        let laptop = device("LAPTOP-7", false, "Windows 11 Pro", "FullyPatched");
+
+        let filter = DeviceFilter::parse("online:true patch:RebootRequired os:server").unwrap();
+        assert!(filter.matches(&dc));
+        assert!(!filter.matches(&laptop));
+
+        let filter = DeviceFilter::parse("laptop online:false").unwrap();
+        assert!(!filter.matches(&dc));
+        assert!(filter.matches(&laptop));
+
+        assert!(DeviceFilter::parse("").unwrap().is_empty());
+        assert!(DeviceFilter::parse("color:red").is_err());
+        assert!(DeviceFilter::parse("online:maybe").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use super::device_filter::DeviceFilter;
+use serde::{Deserialize, Serialize};
+
+/// How many recent device searches are remembered.
+const SEARCH_HISTORY_LIMIT: usize = 10;
+
+/// A device search kept under a name, e.g. "Kiosks" for `KIOSK- online:false`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+/// Saved and recent device searches of the profile, stored in the cache.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHistory {
+    pub saved: Vec<SavedSearch>,
+    /// Newest first
+    pub recent: Vec<String>,
+}
+
+impl SearchHistory {
+    /// Moves `query` to the front of the recent searches.
+    pub fn record(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.recent.retain(|q| !q.eq_ignore_ascii_case(query));
+        self.recent.insert(0, query.to_string());
+        self.recent.truncate(SEARCH_HISTORY_LIMIT);
+    }
+
+    /// Saves `query` as `name`, replacing a saved search of the same name.
+    pub fn save(&mut self, name: &str, query: &str) {
+        let search = SavedSearch {
+            name: name.trim().to_string(),
+            query: query.trim().to_string(),
+        };
+        match self.saved.iter_mut().find(|s| s.name.eq_ignore_ascii_case(&search.name)) {
+            Some(existing) => *existing = search,
+            None => self.saved.push(search),
+        }
+    }
+
+    /// Saved searches, then recent ones, as listed in the search popup.
+    pub fn len(&self) -> usize {
+        self.saved.len() + self.recent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The query of the entry at `index` of the list.
+    pub fn query(&self, index: usize) -> Option<&str> {
+        match index.checked_sub(self.saved.len()) {
+            None => Some(self.saved[index].query.as_str()),
+            Some(recent) => self.recent.get(recent).map(String::as_str),
+        }
+    }
+
+    /// Removes the entry at `index` of the list.
+    pub fn remove(&mut self, index: usize) {
+        match index.checked_sub(self.saved.len()) {
+            None => {
+                self.saved.remove(index);
+            }
+            Some(recent) if recent < self.recent.len() => {
+                self.recent.remove(recent);
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Splits a search into the hostname text sent to the API and the
+/// `key:value` filter terms applied to the results, e.g. `KIOSK- online:false`.
+pub fn split_search_query(query: &str) -> (String, Result<DeviceFilter, String>) {
+    let (filters, words): (Vec<&str>, Vec<&str>) =
+        query.split_whitespace().partition(|word| word.contains(':'));
+    (words.join(" "), DeviceFilter::parse(&filters.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_lists_saved_then_recent_searches() {
+        let mut history = SearchHistory::default();
+        history.record("KIOSK-");
+        history.record("srv");
+        history.record("kiosk-");
+        assert_eq!(history.recent, vec!["kiosk-", "srv"]);
+
+        history.save("Kiosks", "KIOSK- online:false");
+        history.save("kiosks", "KIOSK-");
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.query(0), Some("KIOSK-"));
+        assert_eq!(history.query(2), Some("srv"));
+        assert_eq!(history.query(3), None);
+
+        history.remove(0);
+        assert_eq!(history.query(0), Some("kiosk-"));
+
+        let (hostname, filter) = split_search_query(" KIOSK-  online:false ");
+        assert_eq!(hostname, "KIOSK-");
+        assert!(!filter.unwrap().is_empty());
+        assert!(split_search_query("srv os:").1.is_ok());
+        assert!(split_search_query("srv colour:red").1.is_err());
+    }
+}
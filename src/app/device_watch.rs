@@ -0,0 +1,99 @@
+use crate::api::datto::types::Device;
+use std::time::{Duration, Instant};
+
+/// How often the background refresher polls a watched device.
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A device whose online status is polled while it's watched.
+#[derive(Debug, Clone)]
+pub struct WatchedDevice {
+    pub uid: String,
+    pub hostname: String,
+    pub online: bool,
+    /// When the last poll was sent
+    last_poll: Instant,
+    polling: bool,
+}
+
+/// Devices watched this session, e.g. a server coming back from a reboot.
+#[derive(Debug, Default)]
+pub struct DeviceWatch {
+    pub devices: Vec<WatchedDevice>,
+}
+
+impl DeviceWatch {
+    pub fn is_watched(&self, uid: &str) -> bool {
+        self.devices.iter().any(|d| d.uid == uid)
+    }
+
+    /// Starts or stops watching `device`; returns whether it is now watched.
+    /// Its current status is the baseline, so the first poll only reports a change.
+    pub fn toggle(&mut self, device: &Device) -> bool {
+        if self.is_watched(&device.uid) {
+            self.devices.retain(|d| d.uid != device.uid);
+            return false;
+        }
+        self.devices.push(WatchedDevice {
+            uid: device.uid.clone(),
+            hostname: device.hostname.clone(),
+            online: device.online,
+            last_poll: Instant::now(),
+            polling: false,
+        });
+        true
+    }
+
+    /// Uids of the devices due a poll at `now`, marked as polling until
+    /// their result is recorded.
+    pub fn due(&mut self, now: Instant) -> Vec<String> {
+        self.devices
+            .iter_mut()
+            .filter(|d| !d.polling && now.duration_since(d.last_poll) >= WATCH_INTERVAL)
+            .map(|d| {
+                d.polling = true;
+                d.last_poll = now;
+                d.uid.clone()
+            })
+            .collect()
+    }
+
+    /// Records a poll of `uid`; `None` when it failed. Returns the device if
+    /// its online status changed.
+    pub fn record(&mut self, uid: &str, online: Option<bool>) -> Option<&WatchedDevice> {
+        let device = self.devices.iter_mut().find(|d| d.uid == uid)?;
+        device.polling = false;
+        let online = online.filter(|online| *online != device.online)?;
+        device.online = online;
+        Some(device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(online: bool) -> Device {
+        DeviceBuilder::new("SRV-01").online(online).build()
+    }
+
+    #[test]
+    fn test_watched_devices_report_status_changes() {
+        let mut watch = DeviceWatch::default();
+        assert!(watch.toggle(&device(false)));
+        let start = Instant::now();
+        assert!(watch.due(start).is_empty());
+
+        let later = start + WATCH_INTERVAL;
+        assert_eq!(watch.due(later), vec!["d1".to_string()]);
+        // Not polled again while the first poll is out
+        assert!(watch.due(later + WATCH_INTERVAL).is_empty());
+        assert!(watch.record("d1", Some(false)).is_none());
+        assert!(watch.record("d1", None).is_none());
+        assert!(watch.record("d1", Some(true)).is_some_and(|d| d.online));
+
+        assert!(!watch.toggle(&device(true)));
+        assert!(!watch.is_watched("d1"));
+        assert!(watch.record("d1", Some(false)).is_none());
+    }
+}
@@ -0,0 +1,69 @@
+use super::tasks::Supervisor;
+use crate::api::error::ApiError;
+use crate::event::Event;
+use std::future::Future;
+use tokio::task::AbortHandle;
+
+/// The fetches started for what a view shows, e.g. the open device. Opening
+/// something else or leaving the view cancels them: running ones are aborted
+/// and the generation moves on, so a result that was already on its way is
+/// recognised by its older generation and dropped.
+#[derive(Debug, Default)]
+pub struct Fetches {
+    generation: u64,
+    running: Vec<AbortHandle>,
+}
+
+impl Fetches {
+    /// The generation to tag the results of new fetches with.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Whether a result tagged with `generation` is for what's shown now.
+    pub fn is_current(&self, generation: u64) -> bool {
+        generation == self.generation
+    }
+
+    /// Spawns `fetch` under `tasks` (see `Supervisor::spawn_fetch`), to be
+    /// aborted by the next `cancel`.
+    pub fn spawn<F, E>(&mut self, tasks: &Supervisor, name: &'static str, on_panic: E, fetch: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+        E: FnOnce(ApiError) -> Event + Send + 'static,
+    {
+        self.running.retain(|handle| !handle.is_finished());
+        self.running.push(tasks.spawn_fetch(name, on_panic, fetch));
+    }
+
+    /// Aborts the running fetches and makes their results stale.
+    pub fn cancel(&mut self) {
+        for handle in self.running.drain(..) {
+            handle.abort();
+        }
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_aborts_and_outdates() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut fetches = Fetches::default();
+        let stale = fetches.generation();
+        let on_panic = |e| Event::SitesFetched(Err(e));
+        fetches.spawn(&Supervisor::default(), "fetch_sites", on_panic, async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            tx.send(stale).unwrap();
+        });
+        fetches.cancel();
+        assert!(!fetches.is_current(stale));
+        assert!(fetches.is_current(fetches.generation()));
+        // The aborted fetch dropped its sender without sending
+        assert_eq!(rx.recv().await, None);
+    }
+}
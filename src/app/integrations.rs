@@ -0,0 +1,171 @@
+use super::table_view::{select_next, select_prev};
+use crate::api::error::ApiError;
+use crate::config::Integration;
+use crate::wizard::Provider;
+use chrono::{DateTime, Utc};
+use ratatui::widgets::TableState;
+
+/// How an integration's settings resolved, see [`Integration`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigState {
+    NotConfigured,
+    Invalid(String),
+    Configured,
+    /// Fake data from `--demo`
+    Demo,
+}
+
+impl<T> From<&Integration<T>> for ConfigState {
+    fn from(integration: &Integration<T>) -> Self {
+        match integration {
+            Integration::NotConfigured => ConfigState::NotConfigured,
+            Integration::Invalid(e) => ConfigState::Invalid(e.clone()),
+            Integration::Configured(_) => ConfigState::Configured,
+        }
+    }
+}
+
+impl ConfigState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConfigState::NotConfigured => "Not configured",
+            ConfigState::Invalid(_) => "Invalid",
+            ConfigState::Configured => "Configured",
+            ConfigState::Demo => "Demo",
+        }
+    }
+}
+
+/// What the Integrations view shows about one provider.
+#[derive(Debug, Clone)]
+pub struct IntegrationStatus {
+    pub provider: Provider,
+    pub config: ConfigState,
+    /// When authentication last ran and how it went. RocketCyber and Datto AV
+    /// use a plain API key, so they are checked with a lookup instead.
+    pub last_auth: Option<(DateTime<Utc>, Result<(), String>)>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<(DateTime<Utc>, String)>,
+    /// A re-authentication from the Integrations view is running
+    pub authenticating: bool,
+}
+
+impl IntegrationStatus {
+    pub fn new(provider: Provider, config: ConfigState) -> Self {
+        Self {
+            provider,
+            config,
+            last_auth: None,
+            last_success: None,
+            last_error: None,
+            authenticating: false,
+        }
+    }
+
+    pub fn record_auth(&mut self, result: Result<(), String>) {
+        let now = Utc::now();
+        match &result {
+            Ok(()) => self.last_success = Some(now),
+            Err(e) => self.last_error = Some((now, e.clone())),
+        }
+        self.last_auth = Some((now, result));
+        self.authenticating = false;
+    }
+
+    /// Notes the outcome of an API call made through the provider's client.
+    pub fn record_call(&mut self, result: Result<(), &ApiError>) {
+        match result {
+            Ok(()) => self.last_success = Some(Utc::now()),
+            Err(e) => self.last_error = Some((Utc::now(), e.to_string())),
+        }
+    }
+}
+
+/// State of the Integrations view: one row per provider, in `Provider::ALL` order.
+#[derive(Debug)]
+pub struct IntegrationsState {
+    pub statuses: Vec<IntegrationStatus>,
+    pub table_state: TableState,
+}
+
+impl Default for IntegrationsState {
+    fn default() -> Self {
+        Self {
+            statuses: Provider::ALL
+                .iter()
+                .map(|&p| IntegrationStatus::new(p, ConfigState::NotConfigured))
+                .collect(),
+            table_state: TableState::default().with_selected(Some(0)),
+        }
+    }
+}
+
+impl IntegrationsState {
+    /// Replaces the statuses, keeping any provider missing from `statuses`.
+    pub fn set_statuses(&mut self, statuses: Vec<IntegrationStatus>) {
+        for status in statuses {
+            let provider = status.provider;
+            *self.status_mut(provider) = status;
+        }
+    }
+
+    pub fn status(&self, provider: Provider) -> &IntegrationStatus {
+        self.statuses
+            .iter()
+            .find(|s| s.provider == provider)
+            .expect("every provider has a status")
+    }
+
+    pub fn status_mut(&mut self, provider: Provider) -> &mut IntegrationStatus {
+        self.statuses
+            .iter_mut()
+            .find(|s| s.provider == provider)
+            .expect("every provider has a status")
+    }
+
+    pub fn selected_provider(&self) -> Option<Provider> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.statuses.get(i))
+            .map(|s| s.provider)
+    }
+
+    pub fn next_row(&mut self) {
+        select_next(&mut self.table_state, self.statuses.len());
+    }
+
+    pub fn prev_row(&mut self) {
+        select_prev(&mut self.table_state, self.statuses.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_and_calls_update_status() {
+        let mut state = IntegrationsState::default();
+        let sophos = state.status_mut(Provider::Sophos);
+        sophos.record_auth(Err("401 Unauthorized".to_string()));
+        assert!(sophos.last_success.is_none());
+        assert_eq!(
+            sophos.last_error.as_ref().map(|(_, e)| e.as_str()),
+            Some("401 Unauthorized")
+        );
+
+        sophos.record_call(Ok(()));
+        assert!(sophos.last_success.is_some());
+        // A later success doesn't hide the last error
+        assert!(sophos.last_error.is_some());
+
+        let invalid: Integration<()> = Integration::Invalid("missing secret".to_string());
+        state.set_statuses(vec![IntegrationStatus::new(
+            Provider::DattoAv,
+            ConfigState::from(&invalid),
+        )]);
+        assert_eq!(state.statuses.len(), Provider::ALL.len());
+        assert_eq!(state.status(Provider::DattoAv).config.label(), "Invalid");
+        assert!(state.status(Provider::Sophos).last_auth.is_some());
+    }
+}
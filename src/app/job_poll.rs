@@ -0,0 +1,156 @@
+use crate::api::datto::types::JobResult;
+use crate::api::error::ApiError;
+use std::time::{Duration, Instant};
+
+/// How often the status of a job started from the Run Component popup is fetched.
+pub const JOB_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Polls in a row that may fail (e.g. before the device picked the job up)
+/// before polling gives up.
+const MAX_POLL_ERRORS: u32 = 10;
+
+const SPINNER: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// Deployment statuses after which a job no longer changes.
+pub fn is_finished(status: &str) -> bool {
+    matches!(
+        status.to_lowercase().as_str(),
+        "success" | "failure" | "error" | "warning" | "expired"
+    )
+}
+
+/// Whether a job with this status is waiting to run, so it can be cancelled.
+pub fn is_scheduled(status: &str) -> bool {
+    status.eq_ignore_ascii_case("scheduled")
+}
+
+/// Live status of the job started by the Run Component popup, shown in its
+/// Result step until the job finishes or the popup closes.
+#[derive(Debug, Clone)]
+pub struct JobPoll {
+    pub job_uid: String,
+    pub device_uid: String,
+    /// Latest result, None until the first successful poll
+    pub result: Option<JobResult>,
+    pub last_error: Option<String>,
+    errors: u32,
+    last_poll: Option<Instant>,
+    in_flight: bool,
+    ticks: usize,
+}
+
+impl JobPoll {
+    pub fn new(job_uid: String, device_uid: String) -> Self {
+        Self {
+            job_uid,
+            device_uid,
+            result: None,
+            last_error: None,
+            errors: 0,
+            last_poll: None,
+            in_flight: false,
+            ticks: 0,
+        }
+    }
+
+    /// Deployment status so far; a job counts as scheduled until it reports.
+    pub fn status(&self) -> &str {
+        self.result
+            .as_ref()
+            .and_then(|r| r.job_deployment_status.as_deref())
+            .unwrap_or("Scheduled")
+    }
+
+    pub fn is_done(&self) -> bool {
+        is_finished(self.status()) || self.errors >= MAX_POLL_ERRORS
+    }
+
+    pub fn spinner(&self) -> &'static str {
+        SPINNER[self.ticks % SPINNER.len()]
+    }
+
+    /// Called on every tick; true when the next poll should be sent, which
+    /// is then counted as in flight.
+    pub fn tick(&mut self) -> bool {
+        self.ticks += 1;
+        if self.in_flight || self.is_done() {
+            return false;
+        }
+        if self.last_poll.is_some_and(|at| at.elapsed() < JOB_POLL_INTERVAL) {
+            return false;
+        }
+        self.in_flight = true;
+        true
+    }
+
+    pub fn update(&mut self, result: Result<JobResult, ApiError>) {
+        self.in_flight = false;
+        self.last_poll = Some(Instant::now());
+        match result {
+            Ok(result) => {
+                self.result = Some(result);
+                self.last_error = None;
+                self.errors = 0;
+            }
+            Err(e) => {
+                self.last_error = Some(e.to_string());
+                self.errors += 1;
+            }
+        }
+    }
+}
+
+/// Follow mode of the job output popup: the output is fetched again on every
+/// poll, like `tail -f`, until the job finishes.
+#[derive(Debug, Clone)]
+pub struct JobFollow {
+    pub poll: JobPoll,
+    pub component_uid: String,
+    /// Following StdErr instead of StdOut
+    pub stderr: bool,
+}
+
+/// Appends what `latest` adds to `current`. Output that no longer starts with
+/// what is shown (e.g. it was trimmed) replaces it.
+pub fn append_new_output(current: &mut String, latest: &str) {
+    match latest.strip_prefix(current.as_str()) {
+        Some(new) => current.push_str(new),
+        None => *current = latest.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(status: &str) -> JobResult {
+        serde_json::from_value(serde_json::json!({ "jobDeploymentStatus": status })).unwrap()
+    }
+
+    #[test]
+    fn test_poll_until_finished() {
+        let mut poll = JobPoll::new("job".into(), "device".into());
+        assert!(poll.tick());
+        // One poll at a time, then wait for the interval
+        assert!(!poll.tick());
+        poll.update(Ok(result("Running")));
+        assert!(!poll.is_done());
+        assert!(!poll.tick());
+
+        poll.last_poll = Some(Instant::now() - JOB_POLL_INTERVAL);
+        assert!(poll.tick());
+        poll.update(Ok(result("Success")));
+        assert!(poll.is_done());
+        poll.last_poll = Some(Instant::now() - JOB_POLL_INTERVAL);
+        assert!(!poll.tick());
+    }
+
+    #[test]
+    fn test_append_new_output() {
+        let mut shown = "Stopping...\n".to_string();
+        append_new_output(&mut shown, "Stopping...\nStarting...\n");
+        assert_eq!(shown, "Stopping...\nStarting...\n");
+        append_new_output(&mut shown, "Done.");
+        assert_eq!(shown, "Done.");
+    }
+}
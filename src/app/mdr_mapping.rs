@@ -0,0 +1,164 @@
+use crate::api::datto::types::{Site, SiteVariable};
+
+/// Site variable naming the MDR console a site is managed in, e.g. "Sophos".
+pub const PROVIDER_VARIABLE: &str = "tuiMdrProvider";
+/// Site variable holding the site's tenant or account id in that console.
+pub const ID_VARIABLE: &str = "tuiMdrId";
+/// Site variable holding the Sophos data region, saving a tenant lookup.
+pub const REGION_VARIABLE: &str = "tuiMdrRegion";
+
+/// Where a value of a site's MDR mapping was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingSource {
+    /// Set on the site, with no account default
+    Site,
+    /// Set on the site in place of a different account default
+    SiteOverride,
+    /// Not set on the site; the account variable's value
+    Account,
+}
+
+impl MappingSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            MappingSource::Site => "site",
+            MappingSource::SiteOverride => "site, overrides account",
+            MappingSource::Account => "account default",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MappingValue {
+    pub value: String,
+    pub source: MappingSource,
+}
+
+/// The account variables sites without their own MDR variables fall back to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MdrDefaults {
+    pub provider: Option<String>,
+    pub id: Option<String>,
+    pub region: Option<String>,
+}
+
+impl MdrDefaults {
+    pub fn from_variables(variables: &[SiteVariable]) -> Self {
+        Self {
+            provider: value(variables, PROVIDER_VARIABLE),
+            id: value(variables, ID_VARIABLE),
+            region: value(variables, REGION_VARIABLE),
+        }
+    }
+}
+
+/// A site's MDR variables, each from the site or else the account.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MdrMapping {
+    pub provider: Option<MappingValue>,
+    pub id: Option<MappingValue>,
+    pub region: Option<MappingValue>,
+}
+
+impl MdrMapping {
+    /// The mapping of `site`; a site whose variables haven't loaded yet only
+    /// has the account defaults. Sites of other accounts in the merged site
+    /// list don't inherit the active account's defaults.
+    pub fn resolve(site: &Site, defaults: &MdrDefaults) -> Self {
+        let variables = site.variables.as_deref().unwrap_or_default();
+        let no_defaults = MdrDefaults::default();
+        let defaults = if site.account.is_some() { &no_defaults } else { defaults };
+        let pick = |name: &str, default: &Option<String>| {
+            let (value, source) = match (value(variables, name), default) {
+                (Some(own), Some(default)) if own != *default => (own, MappingSource::SiteOverride),
+                (Some(own), _) => (own, MappingSource::Site),
+                (None, Some(default)) => (default.clone(), MappingSource::Account),
+                (None, None) => return None,
+            };
+            Some(MappingValue { value, source })
+        };
+        Self {
+            provider: pick(PROVIDER_VARIABLE, &defaults.provider),
+            id: pick(ID_VARIABLE, &defaults.id),
+            region: pick(REGION_VARIABLE, &defaults.region),
+        }
+    }
+
+    pub fn provider(&self) -> Option<&str> {
+        self.provider.as_ref().map(|v| v.value.as_str())
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|v| v.value.as_str())
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_ref().map(|v| v.value.as_str())
+    }
+
+    /// Whether the provider or id comes from the account variables.
+    pub fn inherits(&self) -> bool {
+        [&self.provider, &self.id]
+            .into_iter()
+            .flatten()
+            .any(|v| v.source == MappingSource::Account)
+    }
+
+    /// Whether the site is mapped to Sophos Central.
+    pub fn is_sophos(&self) -> bool {
+        self.provider().is_some_and(|p| p.eq_ignore_ascii_case("sophos"))
+    }
+}
+
+/// The non-empty value of the variable `name`.
+fn value(variables: &[SiteVariable], name: &str) -> Option<String> {
+    variables
+        .iter()
+        .find(|v| v.name == name)
+        .map(|v| v.value.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, value: &str) -> SiteVariable {
+        SiteVariable { id: 0, name: name.to_string(), value: value.to_string(), masked: false }
+    }
+
+    #[test]
+    fn test_site_variables_fall_back_to_account_defaults() {
+        let defaults = MdrDefaults::from_variables(&[
+            variable(PROVIDER_VARIABLE, "Sophos"),
+            variable(REGION_VARIABLE, "eu02"),
+            variable(ID_VARIABLE, ""),
+        ]);
+        assert_eq!(defaults.id, None);
+
+        let mut site: Site = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uid": "s1",
+            "name": "Acme",
+        }))
+        .unwrap();
+        site.variables =
+            Some(vec![variable(ID_VARIABLE, "tenant-1"), variable(REGION_VARIABLE, "us01")]);
+
+        let mapping = MdrMapping::resolve(&site, &defaults);
+        assert!(mapping.is_sophos());
+        assert_eq!(mapping.provider.as_ref().unwrap().source, MappingSource::Account);
+        assert_eq!(mapping.id.as_ref().unwrap().source, MappingSource::Site);
+        assert_eq!(mapping.region.as_ref().unwrap().source, MappingSource::SiteOverride);
+        assert_eq!(mapping.region(), Some("us01"));
+        assert!(mapping.inherits());
+
+        site.variables = Some(vec![variable(PROVIDER_VARIABLE, "Sophos")]);
+        let own = MdrMapping::resolve(&site, &MdrDefaults::default());
+        assert_eq!(own.provider.unwrap().source, MappingSource::Site);
+        assert_eq!(own.id, None);
+
+        site.account = Some("other".to_string());
+        assert_eq!(MdrMapping::resolve(&site, &defaults).region, None);
+    }
+}
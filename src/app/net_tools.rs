@@ -0,0 +1,113 @@
+use crate::api::datto::types::Device;
+use crate::common::net_tools::NetTool;
+
+/// Port checked by default, the one remote desktop listens on.
+pub const DEFAULT_PORT: &str = "3389";
+
+/// The outcome of one check, e.g. ("Ping 10.0.0.5", Ok(summary)).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetResult {
+    pub label: String,
+    pub outcome: Result<String, String>,
+}
+
+/// The network tools popup of a device: the addresses to check, the tool
+/// picked and the results so far.
+#[derive(Debug, Clone)]
+pub struct NetToolsState {
+    pub hostname: String,
+    /// (label, address) of the hostname and the device's known IPs
+    pub targets: Vec<(&'static str, String)>,
+    pub target: usize,
+    pub tool: NetTool,
+    pub port: String,
+    /// Oldest first
+    pub results: Vec<NetResult>,
+    /// Checks still running
+    pub running: usize,
+}
+
+impl NetToolsState {
+    pub fn new(device: &Device) -> Self {
+        let ip = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty())
+                .map(str::to_string)
+        };
+        let mut targets = vec![("Hostname", device.hostname.clone())];
+        targets.extend(ip(&device.int_ip_address).map(|ip| ("Internal IP", ip)));
+        targets.extend(ip(&device.ext_ip_address).map(|ip| ("External IP", ip)));
+        Self {
+            hostname: device.hostname.clone(),
+            targets,
+            target: 0,
+            tool: NetTool::Ping,
+            port: DEFAULT_PORT.to_string(),
+            results: Vec::new(),
+            running: 0,
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.targets[self.target].1
+    }
+
+    pub fn next_tool(&mut self) {
+        let index = NetTool::ALL.iter().position(|t| *t == self.tool).unwrap_or(0);
+        self.tool = NetTool::ALL[(index + 1) % NetTool::ALL.len()];
+    }
+
+    pub fn move_target(&mut self, forward: bool) {
+        let count = self.targets.len();
+        self.target = if forward {
+            (self.target + 1) % count
+        } else {
+            (self.target + count - 1) % count
+        };
+    }
+
+    /// The port to check, if `port` holds a valid one.
+    pub fn port(&self) -> Result<u16, String> {
+        match self.port.parse::<u16>() {
+            Ok(port) if port > 0 => Ok(port),
+            _ => Err(format!("'{}' is not a port (1-65535)", self.port)),
+        }
+    }
+
+    /// Label of a check of the selected target with the current tool.
+    pub fn label(&self) -> String {
+        match self.tool {
+            NetTool::Port => format!("{} {}:{}", self.tool.name(), self.address(), self.port),
+            tool => format!("{} {}", tool.name(), self.address()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    #[test]
+    fn test_targets_and_port() {
+        let device = DeviceBuilder::new("SRV-01")
+            .with("intIpAddress", "10.0.0.5")
+            .with("extIpAddress", " ")
+            .build();
+        let mut state = NetToolsState::new(&device);
+        let labels: Vec<&str> = state.targets.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec!["Hostname", "Internal IP"]);
+
+        state.move_target(false);
+        assert_eq!(state.address(), "10.0.0.5");
+        state.next_tool();
+        assert_eq!(state.label(), "TCP Port 10.0.0.5:3389");
+        assert_eq!(state.port(), Ok(3389));
+        for port in ["0", "70000", ""] {
+            state.port = port.to_string();
+            assert!(state.port().is_err(), "{:?}", port);
+        }
+    }
+}
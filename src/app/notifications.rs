@@ -0,0 +1,211 @@
+use super::sort::priority_rank;
+use crate::api::datto::types::Alert;
+use crate::api::rocket_cyber::types::Incident;
+use crate::common::time;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Severity group of RocketCyber incidents, which have no severity of their own
+pub const INCIDENT_SEVERITY: &str = "Incident";
+
+/// Alert uids and incident ids known to a session, saved in the cache so the
+/// next session can tell what is new.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SeenItems {
+    pub alerts: BTreeSet<String>,
+    pub incidents: BTreeSet<String>,
+}
+
+/// An alert or incident that the last session hadn't seen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// [`crate::cache::ALERT_ACK`] or [`crate::cache::INCIDENT_ACK`]
+    pub kind: &'static str,
+    pub id: String,
+    pub site: String,
+    /// Alert priority, or [`INCIDENT_SEVERITY`]
+    pub severity: String,
+    pub title: String,
+    pub at: Option<DateTime<Utc>>,
+}
+
+/// Notifications of one severity within a site.
+pub type SeverityGroup<'a> = (&'a str, Vec<&'a Notification>);
+
+/// State of the header bell and the Notification Center popup.
+#[derive(Debug, Default)]
+pub struct NotificationsState {
+    /// What the last session saw. `None` on the first run, when nothing counts
+    /// as new and this session only records the baseline.
+    baseline: Option<SeenItems>,
+    /// What this session has seen, written to the cache for the next one
+    pub seen: SeenItems,
+    /// New since the last session, in arrival order
+    pub items: Vec<Notification>,
+    /// Items that arrived since the popup was last opened; shown on the bell
+    pub unread: usize,
+    pub scroll: u16,
+}
+
+impl NotificationsState {
+    pub fn new(baseline: Option<SeenItems>) -> Self {
+        Self {
+            seen: baseline.clone().unwrap_or_default(),
+            baseline,
+            ..Self::default()
+        }
+    }
+
+    /// Records open alerts. With `complete` (the account-wide list) alerts
+    /// missing from `alerts` are forgotten, so the saved set doesn't grow forever.
+    pub fn observe_alerts(&mut self, alerts: &[Alert], complete: bool) {
+        if complete {
+            self.seen.alerts.clear();
+        }
+        for alert in alerts {
+            let Some(uid) = alert.alert_uid.clone() else {
+                continue;
+            };
+            self.seen.alerts.insert(uid.clone());
+            if self.baseline.as_ref().is_none_or(|b| b.alerts.contains(&uid)) {
+                continue;
+            }
+            let source = alert.alert_source_info.as_ref();
+            let diagnostics = alert.diagnostics.clone().unwrap_or_default();
+            let title = match source.and_then(|s| s.device_name.as_ref()) {
+                Some(device) => format!("{}: {}", device, diagnostics),
+                None => diagnostics,
+            };
+            self.push(Notification {
+                kind: crate::cache::ALERT_ACK,
+                id: uid,
+                site: source
+                    .and_then(|s| s.site_name.clone())
+                    .unwrap_or_else(|| "Unknown site".to_string()),
+                severity: alert.priority.clone().unwrap_or_else(|| "Unknown".to_string()),
+                title,
+                at: alert.raised_at(),
+            });
+        }
+    }
+
+    /// Records the full incident list; only unresolved incidents notify.
+    pub fn observe_incidents(&mut self, incidents: &[Incident]) {
+        self.seen.incidents = incidents.iter().map(|i| i.id.to_string()).collect();
+        for incident in incidents {
+            let id = incident.id.to_string();
+            if incident.status.eq_ignore_ascii_case("resolved")
+                || self.baseline.as_ref().is_none_or(|b| b.incidents.contains(&id))
+            {
+                continue;
+            }
+            self.push(Notification {
+                kind: crate::cache::INCIDENT_ACK,
+                id,
+                site: incident.account_name.clone(),
+                severity: INCIDENT_SEVERITY.to_string(),
+                title: incident.title.clone(),
+                at: time::parse_timestamp_str(&incident.created_at),
+            });
+        }
+    }
+
+    fn push(&mut self, notification: Notification) {
+        let known = self
+            .items
+            .iter()
+            .any(|n| n.kind == notification.kind && n.id == notification.id);
+        if !known {
+            self.items.push(notification);
+            self.unread += 1;
+        }
+    }
+
+    pub fn mark_read(&mut self) {
+        self.unread = 0;
+    }
+
+    /// Items by site (alphabetical), then by severity (most severe first,
+    /// incidents last), newest first within a severity.
+    pub fn grouped(&self) -> Vec<(&str, Vec<SeverityGroup<'_>>)> {
+        let mut items: Vec<&Notification> = self.items.iter().collect();
+        items.sort_by(|a, b| {
+            a.site
+                .to_lowercase()
+                .cmp(&b.site.to_lowercase())
+                .then_with(|| severity_rank(b).cmp(&severity_rank(a)))
+                .then_with(|| b.at.cmp(&a.at))
+        });
+
+        let mut sites: Vec<(&str, Vec<SeverityGroup<'_>>)> = Vec::new();
+        for item in items {
+            if sites.last().is_none_or(|(site, _)| *site != item.site) {
+                sites.push((&item.site, Vec::new()));
+            }
+            let groups = &mut sites.last_mut().expect("pushed above").1;
+            match groups.last_mut() {
+                Some((severity, list)) if *severity == item.severity => list.push(item),
+                _ => groups.push((&item.severity, vec![item])),
+            }
+        }
+        sites
+    }
+}
+
+fn severity_rank(notification: &Notification) -> u8 {
+    if notification.kind == crate::cache::INCIDENT_ACK {
+        0
+    } else {
+        priority_rank(Some(&notification.severity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(uid: &str, priority: &str, site: &str) -> Alert {
+        serde_json::from_value(serde_json::json!({
+            "alertUid": uid,
+            "priority": priority,
+            "diagnostics": "Disk full",
+            "alertSourceInfo": { "deviceName": "PC-1", "siteName": site },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_items_unseen_last_session_are_new() {
+        // The first run only records the baseline
+        let mut first = NotificationsState::new(None);
+        first.observe_alerts(&[alert("a1", "High", "Acme")], true);
+        assert!(first.items.is_empty());
+
+        let mut state = NotificationsState::new(Some(first.seen.clone()));
+        state.observe_alerts(
+            &[
+                alert("a1", "High", "Acme"),
+                alert("a2", "Low", "Acme"),
+                alert("a3", "Critical", "Acme"),
+                alert("a4", "Low", "Beta"),
+            ],
+            true,
+        );
+        // Seeing the same alert again from a site fetch doesn't count twice
+        state.observe_alerts(&[alert("a2", "Low", "Acme")], false);
+        assert_eq!(state.unread, 3);
+        assert_eq!(state.items[0].title, "PC-1: Disk full");
+
+        let grouped = state.grouped();
+        let summary: Vec<(&str, Vec<&str>)> = grouped
+            .iter()
+            .map(|(site, groups)| (*site, groups.iter().map(|(s, _)| *s).collect()))
+            .collect();
+        assert_eq!(summary, vec![("Acme", vec!["Critical", "Low"]), ("Beta", vec!["Low"])]);
+
+        state.mark_read();
+        assert_eq!(state.unread, 0);
+        assert_eq!(state.items.len(), 3);
+    }
+}
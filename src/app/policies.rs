@@ -0,0 +1,117 @@
+use super::App;
+use super::site_detail::{device_av, patch_status_label};
+use super::table_view::{select_next, select_prev};
+use crate::api::datto::types::Device;
+
+/// A policy applied to a device, and whether the device complies with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyRow {
+    pub name: String,
+    /// "Patch" or "Antivirus"
+    pub kind: &'static str,
+    /// What the policy is applied to
+    pub target: String,
+    /// The status the compliance is judged from
+    pub status: String,
+    /// None when the status doesn't tell
+    pub compliant: Option<bool>,
+}
+
+/// The policies applied to `device`: its patch policy and antivirus from
+/// the RMM device, and the policies of its Datto AV agent from `av_policies`
+/// when it runs one. The RMM API doesn't list monitoring policies.
+pub fn device_policies(device: &Device, av_policies: Option<&serde_json::Value>) -> Vec<PolicyRow> {
+    let mut rows = Vec::new();
+
+    let patch_status = patch_status_label(device);
+    rows.push(PolicyRow {
+        name: if patch_status == "NoPolicy" { "None" } else { "Patch Management" }.to_string(),
+        kind: "Patch",
+        target: device.hostname.clone(),
+        status: patch_status.to_string(),
+        compliant: match patch_status {
+            "Unknown" => None,
+            status => Some(status == "FullyPatched"),
+        },
+    });
+
+    let (product, av_status) = device_av(device);
+    let av_ok = av_status == "RunningAndUpToDate";
+    rows.push(PolicyRow {
+        name: product.to_string(),
+        kind: "Antivirus",
+        target: device.hostname.clone(),
+        status: av_status.to_string(),
+        compliant: match av_status {
+            "Unknown" if product != "None" => None,
+            _ => Some(av_ok),
+        },
+    });
+
+    // A list, or an object holding one, depending on the API version
+    let listed = av_policies
+        .and_then(|p| p.as_array().or_else(|| p.get("policies").and_then(|p| p.as_array())));
+    for policy in listed.into_iter().flatten() {
+        let text = |key: &str| policy.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let enabled = policy.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+        rows.push(PolicyRow {
+            name: text("name").unwrap_or_else(|| "Unnamed policy".to_string()),
+            kind: "Antivirus",
+            target: text("target").unwrap_or_else(|| device.hostname.clone()),
+            status: if enabled { av_status.to_string() } else { "Disabled".to_string() },
+            compliant: enabled.then_some(av_ok),
+        });
+    }
+    rows
+}
+
+impl App {
+    /// Policies of the device open in device detail.
+    pub fn selected_device_policies(&self) -> Vec<PolicyRow> {
+        let Some(device) = &self.device_detail.selected_device else {
+            return Vec::new();
+        };
+        device_policies(device, self.security.datto_av_policies.get(&device.hostname))
+    }
+
+    pub(crate) fn move_policy(&mut self, down: bool) {
+        let len = self.selected_device_policies().len();
+        let state = &mut self.device_detail.policy_state;
+        if down { select_next(state, len) } else { select_prev(state, len) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(patch: &str, av: &str) -> Device {
+        DeviceBuilder::new("WS01").patch_status(patch).antivirus(Some("Datto AV"), Some(av)).build()
+    }
+
+    #[test]
+    fn test_device_policies_compliance() {
+        let policies = serde_json::json!([
+            { "name": "Default Workstation Policy", "enabled": true },
+            { "name": "Servers", "enabled": false, "target": "Servers group" },
+        ]);
+        let rows = device_policies(&device("FullyPatched", "RunningAndUpToDate"), Some(&policies));
+        let summary: Vec<_> =
+            rows.iter().map(|r| (r.name.as_str(), r.target.as_str(), r.compliant)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("Patch Management", "WS01", Some(true)),
+                ("Datto AV", "WS01", Some(true)),
+                ("Default Workstation Policy", "WS01", Some(true)),
+                ("Servers", "Servers group", None),
+            ]
+        );
+
+        let rows = device_policies(&device("NoPolicy", "RunningAndNotUpToDate"), None);
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].name.as_str(), rows[0].compliant), ("None", Some(false)));
+        assert_eq!(rows[1].compliant, Some(false));
+    }
+}
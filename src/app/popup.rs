@@ -0,0 +1,422 @@
+use super::{
+    AlertDetail, Confirmation, Decommission, JobFollow, JobPoll, NetToolsState, QuickAction,
+    RebootFocus, RunComponentStep, Session, SiteDeletion, VariableCopy, WarrantyFocus,
+};
+use crate::api::datto::types::{Component, Device, QuickJobResponse, QuickJobVariable, Site};
+use super::device_filter::DeviceFilter;
+use super::split_search_query;
+use super::table_view::TableView;
+use crate::common::utils::match_quality;
+use ratatui::widgets::TableState;
+
+/// A popup or overlay on top of the view.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Popup {
+    JobOutput,
+    DeviceSearch,
+    RunComponent,
+    QuickActions,
+    Reboot,
+    /// The site picker, for moving a device or choosing a variable copy target
+    SiteMove,
+    Warranty,
+    DeviceEdit,
+    ProfileSwitcher,
+    LogViewer,
+    ErrorHistory,
+    RecentDevices,
+    /// The antivirus scans started this session
+    Scans,
+    /// Picks or names the collection to add devices to
+    AddToCollection,
+    Notifications,
+    NotesViewer,
+    VariableTemplates,
+    /// The UDF overlay of device detail
+    DeviceVariables,
+    // Open while their state is set; `App::sync_popups` keeps them in step
+    Input,
+    AlertDetail,
+    Decommission,
+    SiteDeletion,
+    NetTools,
+    VariableCopy,
+    SessionRestore,
+    Confirmation,
+}
+
+/// State of the popups and overlays that sit on top of any view.
+#[derive(Debug)]
+pub struct PopupState {
+    /// Open popups, in the order they were opened; the last one gets the keys
+    /// and is drawn on top
+    stack: Vec<Popup>,
+
+    // Job Output Popup
+    pub popup_title: String,
+    pub popup_content: String,
+    pub popup_loading: bool,
+    /// Set while the job output shown is followed
+    pub job_follow: Option<JobFollow>,
+
+    // Device Search Popup
+    pub device_search_query: String,
+    pub device_search_results: TableView<Device>,
+    pub device_search_loading: bool,
+    pub device_search_error: Option<String>,
+    /// Pages of the current search loaded so far
+    pub device_search_pages: i32,
+    /// Matches of the whole search, as reported by the API
+    pub device_search_total: Option<usize>,
+    pub device_search_has_more: bool,
+    /// Devices the API returned for the search, before `device_search_filter`
+    pub device_search_loaded: usize,
+    /// The `key:value` terms of the search, applied to the API's results
+    pub device_search_filter: DeviceFilter,
+    /// Selection in the saved and recent searches, listed while the search is empty
+    pub device_search_history_state: TableState,
+    /// Name being typed to save the search under
+    pub saving_search: Option<String>,
+    pub last_search_input: Option<std::time::Instant>,
+    pub last_searched_query: String,
+    /// Set when the search picks a device to run this component on
+    pub component_to_run: Option<Component>,
+
+    // Run Component Popup
+    pub run_component_step: RunComponentStep,
+    pub components: Vec<Component>,
+    pub filtered_components: Vec<Component>,
+    pub component_search_query: String,
+    pub component_list_state: TableState,
+    pub selected_component: Option<Component>,
+    pub component_variables: Vec<QuickJobVariable>,
+    pub component_variable_index: usize,
+    pub component_variable_input: String,
+    /// Cursor in the allowed values of a selection variable
+    pub component_choice_state: TableState,
+    /// YYYY, MM, DD of a date variable
+    pub component_date_segments: [String; 3],
+    pub component_date_focus: WarrantyFocus,
+    /// Why the entered value was refused
+    pub component_variable_error: Option<String>,
+    pub last_job_response: Option<QuickJobResponse>,
+    /// Live status of the job in `last_job_response`
+    pub job_poll: Option<JobPoll>,
+    pub component_error: Option<String>,
+    pub components_loading: bool,
+
+    // Quick Actions Menu
+    pub quick_action_list_state: TableState,
+    pub quick_actions: Vec<QuickAction>,
+
+    /// Collection the Run Component or Reboot popup runs against instead of
+    /// the open device; opening either popup clears it
+    pub collection_target: Option<String>,
+
+    // Reboot Popup
+    pub reboot_now: bool,
+    pub reboot_segments: [String; 5], // YY, MM, DD, HH, mm
+    pub reboot_focus: RebootFocus,
+    pub reboot_error: Option<String>,
+    pub reboot_submitting: bool,
+    /// Set once the reboot job is created; the popup then only closes
+    pub reboot_confirmation: Option<String>,
+
+    // Move Site
+    pub site_move_table_state: TableState,
+    pub site_move_query: String,
+    pub filtered_sites: Vec<Site>,
+
+    /// Variable copy in progress; while set, the site picker above chooses its target
+    pub variable_copy: Option<VariableCopy>,
+
+    // Warranty Update
+    pub warranty_segments: [String; 3], // YYYY, MM, DD
+    pub warranty_focus: WarrantyFocus,
+    pub warranty_error: Option<String>,
+
+    // Device Edit
+    pub device_edit_fields: [String; 2], // Description, Friendly Name
+    pub device_edit_focus: usize,
+
+    /// Decommission in progress; the popup shows while set
+    pub decommission: Option<Decommission>,
+
+    /// Site deletion in progress; the popup shows while set
+    pub site_deletion: Option<SiteDeletion>,
+
+    /// Network tools of the open device; the popup shows while set
+    pub net_tools: Option<NetToolsState>,
+
+    // Profile Switcher
+    pub profile_list_state: TableState,
+    pub profile_switching: bool,
+
+    // Log Viewer
+    pub log_viewer_scroll: usize, // Lines scrolled up from the newest entry
+
+    // Error History
+    pub error_history_state: TableState,
+
+    // Recent Devices
+    pub recent_devices_state: TableState,
+
+    // Scans, newest first
+    pub scans_state: TableState,
+
+    // Add To Collection
+    pub collection_devices: Vec<Device>,
+    /// Collection to add to; a new name creates it
+    pub collection_name: String,
+    pub collection_list_state: TableState,
+
+    // Site Notes Viewer
+    pub notes_scroll: u16,
+
+    // Variable Templates
+    pub variable_template_state: TableState,
+    pub template_applying: bool,
+
+    /// Session from the last run, shown as a restore prompt until answered
+    pub pending_session: Option<Session>,
+
+    /// Destructive action waiting for a yes or no
+    pub confirmation: Option<Confirmation>,
+
+    /// The device alert shown with its parsed diagnostics
+    pub alert_detail: Option<AlertDetail>,
+}
+
+impl PopupState {
+    /// Opens `popup` on top of the others, moving it there if it's open.
+    pub fn open(&mut self, popup: Popup) {
+        self.close(popup);
+        self.stack.push(popup);
+        if matches!(popup, Popup::RunComponent | Popup::Reboot) {
+            self.collection_target = None;
+        }
+    }
+
+    pub fn close(&mut self, popup: Popup) {
+        self.stack.retain(|&p| p != popup);
+    }
+
+    pub fn is_open(&self, popup: Popup) -> bool {
+        self.stack.contains(&popup)
+    }
+
+    /// The popup that gets the keys, if any.
+    pub fn top(&self) -> Option<Popup> {
+        self.stack.last().copied()
+    }
+
+    /// The open popups, bottom first.
+    pub fn open_popups(&self) -> Vec<Popup> {
+        self.stack.clone()
+    }
+
+    /// Opens or closes `popup` to match `open`, leaving its place in the
+    /// stack alone when it already matches.
+    pub fn set_open(&mut self, popup: Popup, open: bool) {
+        if !open {
+            self.close(popup);
+        } else if !self.is_open(popup) {
+            self.stack.push(popup);
+        }
+    }
+
+    /// How much of the search is shown, e.g. "Showing 50 of 312 devices".
+    pub fn device_search_summary(&self) -> String {
+        let loaded = self.device_search_loaded;
+        let total = self.device_search_total.unwrap_or(loaded).max(loaded);
+        let mut summary = if total > loaded || self.device_search_has_more {
+            format!("Showing {} of {} devices", loaded, total)
+        } else {
+            format!("Found {} devices", loaded)
+        };
+        if !self.device_search_filter.is_empty() {
+            summary.push_str(&format!(", {} match the filter", self.device_search_results.len()));
+        }
+        if self.device_search_has_more {
+            summary.push_str(" (PageDown for more)");
+        }
+        summary
+    }
+
+    /// Orders the search results by how well they match the searched text, the
+    /// hostname before the site, keeping the API order between equal matches.
+    /// The selected device stays selected.
+    pub fn rank_device_search_results(&mut self) {
+        let (query, _) = split_search_query(&self.last_searched_query);
+        let quality = |d: &Device| {
+            (
+                match_quality(&query, &d.hostname),
+                match_quality(&query, d.site_name.as_deref().unwrap_or("")),
+            )
+        };
+        self.device_search_results.sort_by(|a, b| quality(b).cmp(&quality(a)));
+    }
+}
+
+impl Default for PopupState {
+    fn default() -> Self {
+        Self {
+            stack: Vec::new(),
+
+            popup_title: String::new(),
+            popup_content: String::new(),
+            popup_loading: false,
+            job_follow: None,
+
+            device_search_query: String::new(),
+            device_search_results: TableView::default(),
+            device_search_loading: false,
+            device_search_error: None,
+            device_search_pages: 0,
+            device_search_total: None,
+            device_search_has_more: false,
+            device_search_loaded: 0,
+            device_search_filter: DeviceFilter::default(),
+            device_search_history_state: TableState::default(),
+            saving_search: None,
+            last_search_input: None,
+            last_searched_query: String::new(),
+            component_to_run: None,
+
+            run_component_step: RunComponentStep::Search,
+            components: Vec::new(),
+            filtered_components: Vec::new(),
+            component_search_query: String::new(),
+            component_list_state: TableState::default(),
+            selected_component: None,
+            component_variables: Vec::new(),
+            component_variable_index: 0,
+            component_variable_input: String::new(),
+            component_choice_state: TableState::default(),
+            component_date_segments: Default::default(),
+            component_date_focus: WarrantyFocus::Year,
+            component_variable_error: None,
+            last_job_response: None,
+            job_poll: None,
+            component_error: None,
+            components_loading: false,
+
+            quick_action_list_state: TableState::default(),
+            quick_actions: Vec::new(),
+
+            collection_target: None,
+
+            reboot_now: true,
+            reboot_segments: Default::default(),
+            reboot_focus: RebootFocus::RebootNow,
+            reboot_error: None,
+            reboot_submitting: false,
+            reboot_confirmation: None,
+
+            site_move_table_state: TableState::default(),
+            site_move_query: String::new(),
+            filtered_sites: Vec::new(),
+
+            variable_copy: None,
+
+            warranty_segments: Default::default(),
+            warranty_focus: WarrantyFocus::Year,
+            warranty_error: None,
+            device_edit_fields: Default::default(),
+            device_edit_focus: 0,
+            decommission: None,
+            site_deletion: None,
+            net_tools: None,
+
+            profile_list_state: TableState::default(),
+            profile_switching: false,
+
+            log_viewer_scroll: 0,
+
+            error_history_state: TableState::default(),
+
+            recent_devices_state: TableState::default(),
+
+            scans_state: TableState::default(),
+
+            collection_devices: Vec::new(),
+            collection_name: String::new(),
+            collection_list_state: TableState::default(),
+
+            notes_scroll: 0,
+
+            variable_template_state: TableState::default(),
+            template_applying: false,
+
+            pending_session: None,
+
+            confirmation: None,
+
+            alert_detail: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    #[test]
+    fn test_popups_stack_in_opening_order() {
+        let mut popups = PopupState::default();
+        popups.open(Popup::QuickActions);
+        popups.set_open(Popup::Confirmation, true);
+        assert_eq!(popups.top(), Some(Popup::Confirmation));
+
+        // Already open: stays where it is
+        popups.set_open(Popup::QuickActions, true);
+        assert_eq!(popups.open_popups(), [Popup::QuickActions, Popup::Confirmation]);
+        popups.open(Popup::QuickActions);
+        assert_eq!(popups.top(), Some(Popup::QuickActions));
+
+        popups.close(Popup::QuickActions);
+        popups.set_open(Popup::Confirmation, false);
+        assert_eq!(popups.top(), None);
+    }
+
+    #[test]
+    fn test_device_search_summary_shows_the_total() {
+        let mut popups = PopupState::default();
+        let device = DeviceBuilder::new("SRV-01").build();
+        popups.device_search_results.set_items(vec![device; 50]);
+        popups.device_search_loaded = 50;
+        popups.device_search_total = Some(312);
+        popups.device_search_has_more = true;
+        assert_eq!(popups.device_search_summary(), "Showing 50 of 312 devices (PageDown for more)");
+
+        popups.device_search_total = Some(50);
+        popups.device_search_has_more = false;
+        assert_eq!(popups.device_search_summary(), "Found 50 devices");
+    }
+
+    #[test]
+    fn test_search_results_rank_prefix_over_substring_and_hostname_over_site() {
+        let device = |uid: &str, hostname: &str, site: &str| {
+            DeviceBuilder::new(hostname).uid(uid).with("siteName", site).build()
+        };
+        let mut popups = PopupState {
+            last_searched_query: "srv".to_string(),
+            ..Default::default()
+        };
+        popups.device_search_results.set_items(vec![
+            device("a", "ACME-SRV01", "Acme"),
+            device("b", "PC-01", "SRV Hosting"),
+            device("c", "SRV02", "Acme"),
+            device("d", "srv", "Acme"),
+            device("e", "ACME-SRV03", "Srv Co"),
+        ]);
+        popups.device_search_results.state.select(Some(1));
+        popups.rank_device_search_results();
+
+        let order: Vec<&str> =
+            popups.device_search_results.rows().map(|d| d.uid.as_str()).collect();
+        assert_eq!(order, vec!["d", "c", "e", "a", "b"]);
+        assert_eq!(popups.device_search_results.state.selected(), Some(4));
+    }
+}
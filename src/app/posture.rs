@@ -0,0 +1,111 @@
+use super::site_detail::{device_av, patch_status_label};
+use crate::api::datto::types::Device;
+use std::collections::HashMap;
+
+/// Points lost when no device runs up-to-date antivirus, in proportion to
+/// the devices without it.
+const AV_WEIGHT: f64 = 40.0;
+/// Points lost when no device is fully patched, likewise.
+const PATCH_WEIGHT: f64 = 30.0;
+/// Points lost per open critical alert, up to `MAX_ALERT_PENALTY`.
+const ALERT_PENALTY: u32 = 5;
+const MAX_ALERT_PENALTY: u32 = 15;
+/// Points lost per active incident, up to `MAX_INCIDENT_PENALTY`.
+const INCIDENT_PENALTY: u32 = 15;
+const MAX_INCIDENT_PENALTY: u32 = 30;
+
+/// Scores from here up are good, and from `FAIR_SCORE` up fair.
+pub const GOOD_SCORE: u8 = 80;
+pub const FAIR_SCORE: u8 = 50;
+
+/// Antivirus and patch counts of a site's computers. Printers, ESXi hosts
+/// and network devices run neither, so they aren't counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeviceHealth {
+    pub devices: usize,
+    /// Devices whose antivirus is running and up to date
+    pub av_protected: usize,
+    pub fully_patched: usize,
+}
+
+impl DeviceHealth {
+    /// Counts of each site in `devices`, keyed by site uid.
+    pub fn by_site(devices: &[Device]) -> HashMap<String, DeviceHealth> {
+        let mut sites: HashMap<String, DeviceHealth> = HashMap::new();
+        let computers = devices
+            .iter()
+            .filter(|d| d.device_class.as_deref().is_none_or(|c| c.eq_ignore_ascii_case("device")));
+        for device in computers {
+            let health = sites.entry(device.site_uid.clone()).or_default();
+            health.devices += 1;
+            if device_av(device).1 == "RunningAndUpToDate" {
+                health.av_protected += 1;
+            }
+            if patch_status_label(device) == "FullyPatched" {
+                health.fully_patched += 1;
+            }
+        }
+        sites
+    }
+}
+
+/// A site's security posture from 0 (worst) to 100: 100 less the share of
+/// computers without working antivirus or patches, open critical alerts and
+/// active incidents. None for sites without computers.
+pub fn posture_score(
+    health: DeviceHealth,
+    critical_alerts: usize,
+    active_incidents: usize,
+) -> Option<u8> {
+    if health.devices == 0 {
+        return None;
+    }
+    let share_missing = |count: usize| 1.0 - count as f64 / health.devices as f64;
+    let gaps = AV_WEIGHT * share_missing(health.av_protected)
+        + PATCH_WEIGHT * share_missing(health.fully_patched);
+    let alerts = (critical_alerts as u32).saturating_mul(ALERT_PENALTY).min(MAX_ALERT_PENALTY);
+    let incidents =
+        (active_incidents as u32).saturating_mul(INCIDENT_PENALTY).min(MAX_INCIDENT_PENALTY);
+    let score = 100.0 - gaps.round() - f64::from(alerts + incidents);
+    Some(score.max(0.0) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(site: &str, class: &str, av: &str, patch: &str) -> Device {
+        DeviceBuilder::new("PC01")
+            .site(site)
+            .with("deviceClass", class)
+            .antivirus(Some("Sophos"), Some(av))
+            .patch_status(patch)
+            .build()
+    }
+
+    #[test]
+    fn test_posture_score() {
+        let devices = [
+            device("s1", "device", "RunningAndUpToDate", "FullyPatched"),
+            device("s1", "device", "NotRunning", "InstallError"),
+            device("s1", "printer", "NotDetected", "NoPolicy"),
+            device("s2", "esxihost", "NotDetected", "NoPolicy"),
+        ];
+        let sites = DeviceHealth::by_site(&devices);
+        let health = sites["s1"];
+        assert_eq!(health, DeviceHealth { devices: 2, av_protected: 1, fully_patched: 1 });
+        assert!(!sites.contains_key("s2"));
+        assert_eq!(posture_score(DeviceHealth::default(), 0, 0), None);
+
+        // Half the computers unprotected and unpatched: 100 - 20 - 15
+        assert_eq!(posture_score(health, 0, 0), Some(65));
+        assert_eq!(posture_score(health, 1, 1), Some(45));
+        // Penalties are capped, and the score stops at zero
+        assert_eq!(posture_score(health, 10, 10), Some(20));
+        let bare = DeviceHealth { devices: 3, av_protected: 0, fully_patched: 0 };
+        assert_eq!(posture_score(bare, 10, 10), Some(0));
+        let clean = DeviceHealth { devices: 3, av_protected: 3, fully_patched: 3 };
+        assert_eq!(posture_score(clean, 0, 0), Some(100));
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+/// Longest the screen goes without a redraw, so ages ("3m ago") and
+/// countdowns keep moving while nothing else happens.
+const IDLE_REDRAW: Duration = Duration::from_secs(5);
+
+/// Whether the next loop iteration has to draw. Input and API results
+/// always request a frame; a tick only does when something changed with
+/// time (a toast expiring, a spinner turning), which keeps an idle session
+/// cheap, e.g. over SSH.
+#[derive(Debug, Clone)]
+pub struct Redraw {
+    requested: bool,
+    drawn_at: Option<Instant>,
+}
+
+impl Default for Redraw {
+    fn default() -> Self {
+        Self { requested: true, drawn_at: None }
+    }
+}
+
+impl Redraw {
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.requested || self.drawn_at.is_none_or(|at| at.elapsed() >= IDLE_REDRAW)
+    }
+
+    /// Records a frame drawn just now.
+    pub fn drawn(&mut self) {
+        self.requested = false;
+        self.drawn_at = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redraws_on_request_or_when_idle_too_long() {
+        let mut redraw = Redraw::default();
+        assert!(redraw.is_due());
+        redraw.drawn();
+        assert!(!redraw.is_due());
+        redraw.request();
+        assert!(redraw.is_due());
+
+        redraw.drawn();
+        redraw.drawn_at = Some(Instant::now() - IDLE_REDRAW);
+        assert!(redraw.is_due());
+    }
+}
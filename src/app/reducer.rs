@@ -0,0 +1,876 @@
+//! Applies [`Action`]s to the app state, one reducer per view.
+
+use super::sort::SortState;
+use super::security::security_provider;
+use super::{
+    ACTIVITY_COLUMNS, App, ConfigState, CurrentView, DEVICE_COLUMNS, DeviceDetailTab, JobViewRow,
+    OPEN_ALERT_COLUMNS, Popup, QuickAction, RESOLVED_ALERT_COLUMNS, ReportKind, SITE_ALERT_COLUMNS,
+    SITE_COLUMNS, SiteDetailTab,
+};
+use crate::action::Action;
+use crate::cache;
+use crate::common::export::{ExportFormat, export_path, write_export};
+use crate::common::jobs::activity_job_uid;
+use crate::event::Event;
+use crate::wizard::Provider;
+use std::path::Path;
+use tokio::sync::mpsc::UnboundedSender;
+
+impl App {
+    /// Applies `action`: app-wide actions first, then the current view's reducer.
+    pub fn dispatch(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        tracing::trace!(?action, view = ?self.current_view, "dispatch");
+
+        match action {
+            Action::OpenSearch => self.open_search(),
+            Action::OpenLogViewer => {
+                self.popups.open(Popup::LogViewer);
+                self.popups.log_viewer_scroll = 0;
+            }
+            Action::OpenErrorHistory => {
+                self.popups.open(Popup::ErrorHistory);
+                self.acknowledge_errors(None);
+                self.popups.error_history_state
+                    .select(if self.errors.is_empty() { None } else { Some(0) });
+            }
+            // Shows the Integrations view, where the results come in
+            Action::ReauthenticateAll => {
+                self.current_view = CurrentView::Integrations;
+                for provider in Provider::ALL {
+                    if self.integrations.status(provider).config != ConfigState::NotConfigured {
+                        self.reauthenticate(provider, tx.clone());
+                    }
+                }
+            }
+            Action::OpenNotifications => {
+                self.popups.open(Popup::Notifications);
+                self.notifications.scroll = 0;
+                self.notifications.mark_read();
+            }
+            Action::OpenInWeb => self.open_portal_link(),
+            Action::OpenRecentDevices => {
+                self.popups.open(Popup::RecentDevices);
+                self.popups.recent_devices_state
+                    .select(if self.recent_devices.is_empty() { None } else { Some(0) });
+            }
+            Action::OpenScans => {
+                self.popups.open(Popup::Scans);
+                self.popups.scans_state
+                    .select(if self.security.scans.jobs.is_empty() { None } else { Some(0) });
+            }
+            _ => match self.current_view {
+                CurrentView::List => self.reduce_site_list(action, tx),
+                CurrentView::Detail => self.reduce_site_detail(action, tx),
+                CurrentView::DeviceDetail => self.reduce_device_detail(action, tx),
+                CurrentView::ActivityDetail => self.reduce_activity_detail(action, tx),
+                CurrentView::Report => self.reduce_report(action, tx),
+                CurrentView::Components => self.reduce_components(action, tx),
+                CurrentView::Integrations => self.reduce_integrations(action, tx),
+                CurrentView::AccountVariables => self.reduce_account_variables(action, tx),
+                CurrentView::Tags => self.reduce_tags(action, tx),
+                CurrentView::Collections => self.reduce_collections(action, tx),
+            },
+        }
+    }
+
+    fn open_search(&mut self) {
+        if self.current_view == CurrentView::DeviceDetail
+            && self.device_detail.device_detail_tab == DeviceDetailTab::Software
+        {
+            self.device_detail.is_software_searching = true;
+            self.device_detail.software_search_query.clear();
+            self.device_detail.filter_software();
+        } else {
+            self.popups.open(Popup::DeviceSearch);
+            self.popups.device_search_query.clear();
+            self.popups.device_search_results.clear();
+            self.popups.last_search_input = None;
+            self.popups.last_searched_query.clear();
+            self.popups.device_search_error = None;
+            self.popups.saving_search = None;
+            let saved = self.search_history.len();
+            self.popups.device_search_history_state.select((saved > 0).then_some(0));
+        }
+    }
+
+    fn reduce_site_list(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::NextRow => self.site_list.next_row(),
+            Action::PrevRow => self.site_list.previous_row(),
+            Action::Refresh => {
+                if self.site_list.show_merged {
+                    self.fetch_merged_sites(tx.clone());
+                }
+                self.fetch_sites(tx);
+            }
+            Action::OpenProfileSwitcher => self.open_profile_switcher(),
+            Action::ToggleMergedSites => self.toggle_merged_sites(tx),
+            Action::OpenFilter => self.site_list.is_filtering = true,
+            Action::OpenReports | Action::OpenWatchlist => {
+                if action == Action::OpenWatchlist && self.report.kind != ReportKind::OfflineServers {
+                    self.report.kind = ReportKind::OfflineServers;
+                    self.report.table_state.select(None);
+                    self.report.rebuild();
+                }
+                self.current_view = CurrentView::Report;
+                if self.report.devices.is_empty() && !self.report.loading {
+                    self.fetch_account_devices(tx);
+                }
+            }
+            Action::OpenIntegrations => self.current_view = CurrentView::Integrations,
+            Action::OpenAccountVariables => {
+                self.current_view = CurrentView::AccountVariables;
+                if self.account_variables.variables.is_empty() && !self.account_variables.loading {
+                    self.fetch_account_variables(tx);
+                }
+            }
+            Action::OpenTags => {
+                self.current_view = CurrentView::Tags;
+                if self.report.devices.is_empty() && !self.report.loading {
+                    self.fetch_account_devices(tx);
+                }
+            }
+            Action::OpenCollections => self.current_view = CurrentView::Collections,
+            Action::OpenComponents => {
+                self.current_view = CurrentView::Components;
+                if self.components.components.is_empty() && !self.components.loading {
+                    self.fetch_components(tx);
+                }
+            }
+            Action::ToggleHideEmptySites => {
+                self.site_list.hide_empty = !self.site_list.hide_empty;
+                self.site_list.refresh_visible();
+            }
+            Action::TogglePin => {
+                self.site_list.toggle_pin_selected();
+                self.cache_put(cache::PINNED_SITES_KEY, &self.site_list.pinned);
+            }
+            Action::CycleSort | Action::ReverseSort => {
+                adjust_sort(&mut self.site_list.sort, action, SITE_COLUMNS.len());
+                self.sort_sites();
+            }
+            Action::Select => {
+                if self.site_list.toggle_selected_section() {
+                    self.cache_put(cache::COLLAPSED_GROUPS_KEY, &self.site_list.collapsed);
+                } else if let Some(site) = self.site_list.selected_site() {
+                    match site.account.clone() {
+                        Some(account) => self.open_merged_site(account, site.uid.clone(), tx),
+                        None => self.navigate_to_site_detail(site.uid.clone(), tx),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reduce_site_detail(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        match action {
+            Action::Back => {
+                self.site_detail.fetches.cancel();
+                self.current_view = CurrentView::List;
+            }
+            Action::NextTab | Action::PrevTab => {
+                self.site_detail.detail_tab = if action == Action::NextTab {
+                    match self.site_detail.detail_tab {
+                        SiteDetailTab::Devices => SiteDetailTab::Alerts,
+                        SiteDetailTab::Alerts => SiteDetailTab::Variables,
+                        SiteDetailTab::Variables => SiteDetailTab::Udfs,
+                        SiteDetailTab::Udfs => SiteDetailTab::Settings,
+                        SiteDetailTab::Settings => SiteDetailTab::Devices,
+                    }
+                } else {
+                    match self.site_detail.detail_tab {
+                        SiteDetailTab::Devices => SiteDetailTab::Settings,
+                        SiteDetailTab::Alerts => SiteDetailTab::Devices,
+                        SiteDetailTab::Variables => SiteDetailTab::Alerts,
+                        SiteDetailTab::Udfs => SiteDetailTab::Variables,
+                        SiteDetailTab::Settings => SiteDetailTab::Udfs,
+                    }
+                };
+
+                // Populate Settings state when switching to it
+                if self.site_detail.detail_tab == SiteDetailTab::Settings {
+                    self.populate_site_edit_state();
+                }
+            }
+            Action::NextRow => match self.site_detail.detail_tab {
+                SiteDetailTab::Devices | SiteDetailTab::Udfs => self.site_detail.devices.next(),
+                SiteDetailTab::Alerts => self.site_detail.site_open_alerts.next(),
+                SiteDetailTab::Variables => self.site_detail.next_variable(self.selected_site_variable_count()),
+                SiteDetailTab::Settings => self.site_detail.next_setting(),
+            },
+            Action::PrevRow => match self.site_detail.detail_tab {
+                SiteDetailTab::Devices | SiteDetailTab::Udfs => self.site_detail.devices.prev(),
+                SiteDetailTab::Alerts => self.site_detail.site_open_alerts.prev(),
+                SiteDetailTab::Variables => self.site_detail.prev_variable(self.selected_site_variable_count()),
+                SiteDetailTab::Settings => self.site_detail.prev_setting(),
+            },
+            Action::OpenFilter
+                if matches!(self.site_detail.detail_tab, SiteDetailTab::Devices | SiteDetailTab::Udfs) =>
+            {
+                self.site_detail.is_device_filtering = true;
+            }
+            Action::NextColumn | Action::PrevColumn
+                if self.site_detail.detail_tab == SiteDetailTab::Udfs =>
+            {
+                let count = self.site_detail.matrix_columns(&self.udf_labels).len();
+                self.site_detail.move_udf_column(action == Action::NextColumn, count);
+            }
+            Action::CopyVariables if self.site_detail.detail_tab == SiteDetailTab::Variables => {
+                self.open_variable_copy();
+            }
+            Action::Delete if self.site_detail.detail_tab == SiteDetailTab::Variables => {
+                self.confirm_variable_delete(tx);
+            }
+            Action::DeleteSite => self.open_site_deletion_popup(),
+            // The marked devices, else the selected one
+            Action::AddToCollection if self.site_detail.detail_tab == SiteDetailTab::Devices => {
+                let detail = &self.site_detail;
+                let mut devices: Vec<_> = detail
+                    .devices
+                    .items()
+                    .iter()
+                    .filter(|d| detail.selected_device_uids.contains(&d.uid))
+                    .cloned()
+                    .collect();
+                if devices.is_empty() {
+                    devices.extend(detail.selected_device().cloned());
+                }
+                self.open_add_to_collection(devices);
+            }
+            Action::ApplyTemplate
+                if self.site_detail.detail_tab == SiteDetailTab::Variables
+                    && self.site_list.selected_site().is_some() =>
+            {
+                self.popups.open(Popup::VariableTemplates);
+                self.popups.variable_template_state.select(
+                    (!self.variable_templates.is_empty()).then_some(0),
+                );
+            }
+            Action::ChooseColumns if self.site_detail.detail_tab == SiteDetailTab::Udfs => {
+                self.open_udf_columns_modal();
+            }
+            Action::CycleSort | Action::ReverseSort => match self.site_detail.detail_tab {
+                SiteDetailTab::Devices => {
+                    adjust_sort(&mut self.site_detail.devices.sort, action, DEVICE_COLUMNS.len());
+                    self.site_detail.sort_devices();
+                }
+                SiteDetailTab::Alerts => {
+                    adjust_sort(
+                        &mut self.site_detail.site_open_alerts.sort,
+                        action,
+                        SITE_ALERT_COLUMNS.len(),
+                    );
+                    self.site_detail.sort_site_alerts();
+                }
+                _ => {}
+            },
+            Action::ViewNotes => {
+                let has_notes = self
+                    .site_list
+                    .selected_site()
+                    .and_then(|site| site.notes.as_deref())
+                    .is_some_and(|notes| !notes.trim().is_empty());
+                if has_notes {
+                    self.popups.open(Popup::NotesViewer);
+                    self.popups.notes_scroll = 0;
+                } else {
+                    self.show_toast("This site has no notes".to_string());
+                }
+            }
+            Action::Edit => match self.site_detail.detail_tab {
+                SiteDetailTab::Variables => self.open_edit_variable_modal(),
+                SiteDetailTab::Udfs => self.open_edit_udf_cell_modal(),
+                SiteDetailTab::Settings => self.open_edit_setting_modal(),
+                _ => {}
+            },
+            Action::Select | Action::ToggleSelect => match self.site_detail.detail_tab {
+                SiteDetailTab::Devices if action == Action::Select => {
+                    if let Some(device) = self.site_detail.selected_device().cloned() {
+                        self.navigate_to_device_detail(device, tx);
+                    }
+                }
+                SiteDetailTab::Devices => {
+                    let detail = &mut self.site_detail;
+                    if let Some(uid) = detail.selected_device().map(|d| d.uid.clone())
+                        && !detail.selected_device_uids.remove(&uid)
+                    {
+                        detail.selected_device_uids.insert(uid);
+                    }
+                }
+                SiteDetailTab::Alerts if action == Action::Select => {
+                    // Alerts in site detail belong to the current site, so the device is in the devices list
+                    let device_uid = self
+                        .site_detail
+                        .site_open_alerts
+                        .selected()
+                        .and_then(|alert| alert.alert_source_info.as_ref())
+                        .and_then(|source| source.device_uid.clone());
+                    let devices = self.site_detail.devices.items();
+                    if let Some(device) =
+                        device_uid.and_then(|uid| devices.iter().find(|d| d.uid == uid).cloned())
+                    {
+                        self.navigate_to_device_detail(device, tx);
+                    }
+                }
+                SiteDetailTab::Alerts => {}
+                SiteDetailTab::Udfs => self.open_edit_udf_cell_modal(),
+                // Space marks variables for copying; the "Create +" row can't be marked
+                SiteDetailTab::Variables if action == Action::ToggleSelect => {
+                    let id = self
+                        .site_detail
+                        .variables_table_state
+                        .selected()
+                        .and_then(|idx| self.site_list.selected_site()?.variables.as_ref()?.get(idx))
+                        .map(|v| v.id);
+                    if let Some(id) = id
+                        && !self.site_detail.selected_variable_ids.remove(&id)
+                    {
+                        self.site_detail.selected_variable_ids.insert(id);
+                    }
+                }
+                SiteDetailTab::Variables => {
+                    // The row after the last variable is "Create +"
+                    if let Some(idx) = self.site_detail.variables_table_state.selected()
+                        && self.site_list.selected_site().is_some()
+                    {
+                        if idx == self.selected_site_variable_count() {
+                            self.open_create_variable_modal();
+                        } else {
+                            self.open_edit_variable_modal();
+                        }
+                    }
+                }
+                // Toggle boolean settings for quick action, or submit if purely selecting
+                SiteDetailTab::Settings => self.toggle_setting(tx),
+            },
+            Action::Acknowledge if self.site_detail.detail_tab == SiteDetailTab::Alerts => {
+                let alert_uid = self
+                    .site_detail
+                    .site_open_alerts
+                    .selected()
+                    .and_then(|alert| alert.alert_uid.clone());
+                if let Some(uid) = alert_uid {
+                    self.toggle_alert_acknowledgement(uid);
+                }
+            }
+            Action::OpenQuickActions => {
+                self.popups.open(Popup::QuickActions);
+                self.popups.quick_actions = vec![QuickAction::ReloadData];
+                self.popups.quick_action_list_state.select(Some(0));
+            }
+            _ => {}
+        }
+    }
+
+    fn reduce_device_detail(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        if self.popups.is_open(Popup::DeviceVariables) {
+            self.reduce_device_variables(action);
+            return;
+        }
+
+        match action {
+            Action::Back => self.leave_device_detail(tx),
+            Action::NextDevice | Action::PrevDevice => self.open_sibling_device(action == Action::NextDevice, tx),
+            Action::NextTab | Action::PrevTab => {
+                let is_software_supported = self.device_detail.selected_device.as_ref().is_some_and(|device| {
+                    device.device_class.as_ref().map(|s| s.trim().to_lowercase()).as_deref()
+                        == Some("device")
+                });
+
+                let mut tabs = vec![
+                    DeviceDetailTab::OpenAlerts,
+                    DeviceDetailTab::ResolvedAlerts,
+                    DeviceDetailTab::Activities,
+                ];
+                if is_software_supported {
+                    tabs.push(DeviceDetailTab::Software);
+                }
+                tabs.push(DeviceDetailTab::Policies);
+                let current = tabs
+                    .iter()
+                    .position(|&tab| tab == self.device_detail.device_detail_tab)
+                    .unwrap_or(0);
+                let next = if action == Action::PrevTab {
+                    current.checked_sub(1).unwrap_or(tabs.len() - 1)
+                } else {
+                    (current + 1) % tabs.len()
+                };
+                self.device_detail.device_detail_tab = tabs[next];
+            }
+            Action::ToggleDeviceVariables => {
+                self.popups.open(Popup::DeviceVariables);
+                if self.device_detail.udf_table_state.selected().is_none() {
+                    self.device_detail.udf_table_state.select(Some(0));
+                }
+            }
+            Action::OpenQuickActions => self.open_device_quick_actions(),
+            Action::Edit => self.open_device_edit_popup(),
+            Action::EditTags => self.open_edit_tags_modal(),
+            Action::AddToCollection => {
+                let device = self.device_detail.selected_device.clone();
+                self.open_add_to_collection(device.into_iter().collect());
+            }
+            Action::ToggleWatch => self.toggle_device_watch(),
+            Action::Launch => self.open_launchers(),
+            Action::ScrollDown | Action::ScrollUp => {
+                self.device_detail.scroll(action == Action::ScrollDown)
+            }
+            Action::SwitchPane => self.device_detail.switch_pane(),
+            Action::NextRow => match self.device_detail.device_detail_tab {
+                DeviceDetailTab::Activities => self.device_detail.move_activity_log(true),
+                DeviceDetailTab::OpenAlerts => self.device_detail.open_alerts.next(),
+                DeviceDetailTab::ResolvedAlerts => self.device_detail.resolved_alerts.next(),
+                DeviceDetailTab::Software => self.device_detail.device_software.next(),
+                DeviceDetailTab::Policies => self.move_policy(true),
+            },
+            Action::PrevRow => match self.device_detail.device_detail_tab {
+                DeviceDetailTab::Activities => self.device_detail.move_activity_log(false),
+                DeviceDetailTab::OpenAlerts => self.device_detail.open_alerts.prev(),
+                DeviceDetailTab::ResolvedAlerts => self.device_detail.resolved_alerts.prev(),
+                DeviceDetailTab::Software => self.device_detail.device_software.prev(),
+                DeviceDetailTab::Policies => self.move_policy(false),
+            },
+            Action::CycleSort | Action::ReverseSort => match self.device_detail.device_detail_tab {
+                DeviceDetailTab::OpenAlerts => {
+                    adjust_sort(
+                        &mut self.device_detail.open_alerts.sort,
+                        action,
+                        OPEN_ALERT_COLUMNS.len(),
+                    );
+                    self.device_detail.sort_open_alerts();
+                }
+                DeviceDetailTab::ResolvedAlerts => {
+                    adjust_sort(
+                        &mut self.device_detail.resolved_alerts.sort,
+                        action,
+                        RESOLVED_ALERT_COLUMNS.len(),
+                    );
+                    self.device_detail.sort_resolved_alerts();
+                }
+                DeviceDetailTab::Activities => {
+                    adjust_sort(
+                        &mut self.device_detail.activity_logs.sort,
+                        action,
+                        ACTIVITY_COLUMNS.len(),
+                    );
+                    self.device_detail.sort_activity_logs();
+                }
+                DeviceDetailTab::Software | DeviceDetailTab::Policies => {}
+            },
+            Action::Acknowledge if self.device_detail.device_detail_tab == DeviceDetailTab::OpenAlerts => {
+                let alert_uid = self
+                    .device_detail
+                    .open_alerts
+                    .selected()
+                    .and_then(|alert| alert.alert_uid.clone());
+                if let Some(uid) = alert_uid {
+                    self.toggle_alert_acknowledgement(uid);
+                }
+            }
+            Action::CancelJob if self.device_detail.device_detail_tab == DeviceDetailTab::Activities => {
+                self.confirm_job_cancel(tx)
+            }
+            Action::Select | Action::ToggleSelect => match self.device_detail.device_detail_tab {
+                DeviceDetailTab::Activities => self.open_activity_detail(tx),
+                DeviceDetailTab::OpenAlerts => self.open_alert_detail(),
+                DeviceDetailTab::ResolvedAlerts
+                | DeviceDetailTab::Software
+                | DeviceDetailTab::Policies => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// The UDF overlay on the device detail view.
+    fn reduce_device_variables(&mut self, action: Action) {
+        match action {
+            Action::Back | Action::ToggleDeviceVariables => {
+                self.popups.close(Popup::DeviceVariables)
+            }
+            Action::NextRow => self.device_detail.next_udf(),
+            Action::PrevRow => self.device_detail.prev_udf(),
+            Action::Select | Action::ToggleSelect => self.open_edit_udf_modal(),
+            _ => {}
+        }
+    }
+
+    fn leave_device_detail(&mut self, tx: UnboundedSender<Event>) {
+        self.device_detail.fetches.cancel();
+        if let Some(device) = self.device_detail.selected_device.take() {
+            if let Some(view) = self.device_detail.opened_from.take() {
+                self.current_view = view;
+            } else if self.site_list.sites.iter().any(|s| s.uid == device.site_uid) {
+                self.navigate_to_site_detail(device.site_uid.clone(), tx);
+            } else {
+                // Site not in current list (common if coming from search), fetch it directly
+                self.current_view = CurrentView::Detail;
+                self.site_detail.fetches.cancel();
+                self.fetch_site(device.site_uid.clone(), tx.clone());
+                self.fetch_devices(device.site_uid.clone(), tx.clone());
+                self.fetch_site_variables(device.site_uid.clone(), tx);
+            }
+        } else {
+            self.current_view = CurrentView::Detail;
+        }
+
+        // Reset tab to default when leaving
+        self.device_detail.device_detail_tab = DeviceDetailTab::OpenAlerts;
+    }
+
+    /// Replaces the open device with its neighbour in the site's device list
+    /// (as filtered and sorted there), keeping the current tab where possible.
+    fn open_sibling_device(&mut self, forward: bool, tx: UnboundedSender<Event>) {
+        let Some(current) = self.device_detail.selected_device.clone() else {
+            return;
+        };
+        let Some(next) = self
+            .site_detail
+            .select_sibling_device(&current.uid, forward)
+            .filter(|d| d.uid != current.uid)
+            .cloned()
+        else {
+            return;
+        };
+        self.popups.close(Popup::DeviceVariables);
+
+        let opened_from = self.device_detail.opened_from;
+        let tab = self.device_detail.device_detail_tab;
+        self.navigate_to_device_detail(next, tx);
+        self.device_detail.opened_from = opened_from;
+
+        let is_software_supported = self.device_detail.selected_device.as_ref().is_some_and(|device| {
+            device.device_class.as_ref().map(|s| s.trim().to_lowercase()).as_deref() == Some("device")
+        });
+        self.device_detail.device_detail_tab = if tab == DeviceDetailTab::Software && !is_software_supported {
+            DeviceDetailTab::OpenAlerts
+        } else {
+            tab
+        };
+    }
+
+    fn open_device_quick_actions(&mut self) {
+        self.popups.open(Popup::QuickActions);
+        self.popups.quick_actions = vec![
+            QuickAction::ScheduleReboot,
+            QuickAction::RunComponent,
+            QuickAction::MoveToSite,
+            QuickAction::UpdateWarranty,
+            QuickAction::EditDevice,
+            QuickAction::NetworkTools,
+            QuickAction::Decommission,
+        ];
+
+        if let Some(device) = &self.device_detail.selected_device {
+            // AV actions are only offered for consoles we integrate with
+            if let Some(provider) = security_provider(device) {
+                self.popups.quick_actions.push(QuickAction::RunAvScan);
+                if provider.can_isolate() {
+                    self.popups.quick_actions.push(QuickAction::IsolateDevice);
+                }
+            }
+
+            if device.web_remote_url.is_some() {
+                self.popups.quick_actions.push(QuickAction::OpenWebRemote);
+            }
+        }
+        self.popups
+            .quick_actions
+            .extend((0..self.launchers.len()).map(QuickAction::Launcher));
+        self.popups
+            .quick_actions
+            .extend((0..self.custom_quick_actions.len()).map(QuickAction::Custom));
+        self.popups.quick_action_list_state.select(Some(0));
+    }
+
+    fn open_activity_detail(&mut self, tx: UnboundedSender<Event>) {
+        let Some(log) = self.device_detail.activity_logs.selected().cloned() else {
+            return;
+        };
+        self.current_view = CurrentView::ActivityDetail;
+
+        // Parse job ID from details and fetch job result
+        let job_uid = activity_job_uid(&log);
+        self.device_detail.selected_activity_log = Some(log);
+
+        if let (Some(job_uid), Some(device)) = (job_uid, &self.device_detail.selected_device) {
+            self.fetch_job_result(job_uid, device.uid.clone(), tx);
+        }
+    }
+
+    fn reduce_activity_detail(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        if self.popups.is_open(Popup::JobOutput) {
+            match action {
+                Action::Back => {
+                    self.popups.close(Popup::JobOutput);
+                    self.popups.job_follow = None;
+                }
+                Action::FollowOutput => self.toggle_job_follow(),
+                _ => {}
+            }
+            return;
+        }
+
+        match action {
+            Action::Back => {
+                self.current_view = CurrentView::DeviceDetail;
+                self.device_detail.selected_activity_log = None;
+                self.device_detail.selected_job_result = None;
+                self.device_detail.job_result_error = None;
+            }
+            Action::NextRow => {
+                let detail = &mut self.device_detail;
+                if detail.selected_job_row_index + 1 < detail.job_rows().len() {
+                    detail.selected_job_row_index += 1;
+                }
+            }
+            Action::PrevRow => {
+                self.device_detail.selected_job_row_index = self.device_detail.selected_job_row_index.saturating_sub(1);
+            }
+            Action::Select => {
+                let Some(job_result) = &self.device_detail.selected_job_result else {
+                    return;
+                };
+                let (Some(job_uid), Some(device_uid)) =
+                    (job_result.job_uid.clone(), job_result.device_uid.clone())
+                else {
+                    return;
+                };
+                let row = self.device_detail.selected_job_row();
+                let component = row.and_then(|row| {
+                    job_result.component_results.as_ref()?.get(row.component()).cloned()
+                });
+                match (row, component) {
+                    (Some(JobViewRow::StdOutLink(_)), Some(component)) => {
+                        self.fetch_job_stdout(job_uid, device_uid, &component, tx)
+                    }
+                    (Some(JobViewRow::StdErrLink(_)), Some(component)) => {
+                        self.fetch_job_stderr(job_uid, device_uid, &component, tx)
+                    }
+                    (Some(JobViewRow::ComponentHeader(_)), _) => {
+                        self.device_detail.toggle_job_component()
+                    }
+                    _ => {}
+                }
+            }
+            Action::ToggleSelect => self.device_detail.toggle_job_component(),
+            Action::Rerun => self.fetch_job_components(tx),
+            Action::CancelJob => self.confirm_job_cancel(tx),
+            Action::JumpTo(index) => self.device_detail.jump_to_job_component(index),
+            Action::FollowOutput => self.toggle_job_follow(),
+            _ => {}
+        }
+    }
+}
+
+impl App {
+    fn reduce_report(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        match action {
+            // Back leaves a patch drill-down before leaving the view
+            Action::Back if self.report.close_patch_site() => {}
+            Action::Back => self.current_view = CurrentView::List,
+            Action::NextRow => self.report.next_row(),
+            Action::PrevRow => self.report.prev_row(),
+            Action::NextTab | Action::PrevTab => {
+                let kinds = ReportKind::ALL;
+                let i = kinds.iter().position(|k| *k == self.report.kind).unwrap_or(0);
+                let next = if action == Action::NextTab {
+                    (i + 1) % kinds.len()
+                } else {
+                    (i + kinds.len() - 1) % kinds.len()
+                };
+                self.report.kind = kinds[next];
+                self.report.table_state.select(None);
+                self.report.rebuild();
+            }
+            Action::Refresh => self.fetch_account_devices(tx),
+            Action::Increase | Action::Decrease if self.report.kind == ReportKind::Warranty => {
+                let step = if action == Action::Increase { 30 } else { -30 };
+                self.report.adjust_warranty_days(step);
+            }
+            Action::Export(format) => self.export_report(format),
+            Action::Select if self.report.kind == ReportKind::Patch && self.report.patch_site.is_none() => {
+                self.report.open_patch_site();
+            }
+            Action::Select => {
+                if let Some(device) = self.report.selected_device().cloned() {
+                    self.navigate_to_device_detail(device, tx);
+                    self.device_detail.opened_from = Some(CurrentView::Report);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reduce_components(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        match action {
+            Action::Back => self.current_view = CurrentView::List,
+            Action::NextRow => self.components.next_row(),
+            Action::PrevRow => self.components.prev_row(),
+            Action::Refresh => self.fetch_components(tx),
+            Action::OpenFilter => self.components.is_filtering = true,
+            Action::TogglePin => {
+                if let Some(uid) = self.components.selected_component().map(|c| c.uid.clone()) {
+                    self.toggle_component_star(&uid);
+                }
+            }
+            // "Run against device...": pick the device with the device search
+            Action::Select => {
+                if let Some(component) = self.components.selected_component().cloned() {
+                    self.open_search();
+                    self.popups.component_to_run = Some(component);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reduce_account_variables(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        match action {
+            Action::Back => self.current_view = CurrentView::List,
+            Action::NextRow => self.account_variables.next_row(),
+            Action::PrevRow => self.account_variables.prev_row(),
+            Action::Refresh => self.fetch_account_variables(tx),
+            // The row after the last variable is "Create +"
+            Action::Select => {
+                if self.account_variables.is_create_row_selected() {
+                    self.open_create_variable_modal();
+                } else if let Some(variable) = self.account_variables.selected_variable().cloned() {
+                    self.open_variable_modal(&variable);
+                }
+            }
+            Action::Delete => self.confirm_account_variable_delete(tx),
+            _ => {}
+        }
+    }
+
+    fn reduce_tags(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        let udf = self.tags_udf.unwrap_or_default();
+        match action {
+            Action::Back => self.current_view = CurrentView::List,
+            Action::NextRow => self.tags.next_row(&self.report.devices, udf),
+            Action::PrevRow => self.tags.prev_row(&self.report.devices, udf),
+            Action::SwitchPane => self.tags.devices_focused = !self.tags.devices_focused,
+            Action::Refresh => self.fetch_account_devices(tx),
+            Action::Select => {
+                if let Some(device) = self.tags.selected_device().cloned() {
+                    self.navigate_to_device_detail(device, tx);
+                    self.device_detail.opened_from = Some(CurrentView::Tags);
+                }
+            }
+            // The selected device, or every device of the tag from the tag list
+            Action::AddToCollection => {
+                let devices = if self.tags.devices_focused {
+                    self.tags.selected_device().cloned().into_iter().collect()
+                } else {
+                    self.tags.devices.clone()
+                };
+                self.open_add_to_collection(devices);
+            }
+            _ => {}
+        }
+    }
+
+    fn reduce_collections(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        match action {
+            Action::Back => self.current_view = CurrentView::List,
+            Action::NextRow => self.collections.next_row(),
+            Action::PrevRow => self.collections.prev_row(),
+            Action::SwitchPane => {
+                self.collections.devices_focused = !self.collections.devices_focused
+            }
+            // Enter on a collection moves into its devices
+            Action::Select if !self.collections.devices_focused => {
+                self.collections.devices_focused = true;
+            }
+            Action::Select => {
+                if let Some(device) = self.collections.selected_device().cloned() {
+                    self.navigate_to_device_detail(device, tx);
+                    self.device_detail.opened_from = Some(CurrentView::Collections);
+                }
+            }
+            Action::Delete => self.remove_from_collections(),
+            Action::OpenQuickActions => self.open_collection_quick_actions(),
+            _ => {}
+        }
+    }
+
+    fn reduce_integrations(&mut self, action: Action, tx: UnboundedSender<Event>) {
+        match action {
+            Action::Back => self.current_view = CurrentView::List,
+            Action::NextRow => self.integrations.next_row(),
+            Action::PrevRow => self.integrations.prev_row(),
+            Action::Reauthenticate | Action::Select => {
+                if let Some(provider) = self.integrations.selected_provider() {
+                    self.reauthenticate(provider, tx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes the current report table to the working directory.
+    fn export_report(&mut self, format: ExportFormat) {
+        let (headers, rows) = self.report.export_table();
+        let path = export_path(Path::new("."), &self.report.export_name(), format.extension());
+        match write_export(path, &format.render(&headers, &rows)) {
+            Ok(path) => {
+                tracing::info!(path = %path.display(), rows = rows.len(), "exported report");
+                self.report.export_message = Some(format!("Exported {} rows to {}", rows.len(), path.display()));
+            }
+            Err(e) => self.report_error("Export", Some(CurrentView::Report), format!("{:#}", e)),
+        }
+    }
+}
+
+/// `s` moves to the next column, `S` flips the direction.
+fn adjust_sort(sort: &mut SortState, action: Action, columns: usize) {
+    if action == Action::ReverseSort {
+        sort.reverse();
+    } else {
+        sort.cycle(columns);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::Site;
+    use crate::app::MAX_ERROR_HISTORY;
+
+    fn site(uid: &str) -> Site {
+        serde_json::from_value(serde_json::json!({ "id": 1, "uid": uid, "name": uid })).unwrap()
+    }
+
+    #[test]
+    fn test_dispatch_site_navigation() {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::default();
+        app.site_list.set_sites(vec![site("a"), site("b")]);
+
+        app.dispatch(Action::NextRow, tx.clone());
+        assert_eq!(app.site_list.table_state.selected(), Some(1));
+
+        app.current_view = CurrentView::Detail;
+        app.dispatch(Action::PrevTab, tx.clone());
+        assert_eq!(app.site_detail.detail_tab, SiteDetailTab::Settings);
+        app.dispatch(Action::Back, tx.clone());
+        assert_eq!(app.current_view, CurrentView::List);
+
+        app.dispatch(Action::Quit, tx);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_error_history_drops_the_oldest_errors() {
+        let mut app = App::default();
+        for i in 0..MAX_ERROR_HISTORY + 5 {
+            app.report_error("test", None, format!("error {}", i));
+        }
+        assert_eq!(app.errors.len(), MAX_ERROR_HISTORY);
+        assert_eq!(app.errors.front().unwrap().message, "error 5");
+        let newest = format!("error {}", MAX_ERROR_HISTORY + 4);
+        assert_eq!(app.errors.back().unwrap().message, newest);
+    }
+}
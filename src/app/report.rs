@@ -0,0 +1,954 @@
+use super::site_detail::patch_status_label;
+use super::sort::cmp_text;
+use super::table_view::{select_next, select_prev};
+use crate::api::datto::types::Device;
+use crate::common::time;
+use crate::common::warranty::{
+    EXPIRING_SOON_DAYS, WarrantyStatus, parse_warranty_date, warranty_status,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use ratatui::widgets::TableState;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Account-wide reports built from every device in the account.
+#[derive(Debug, Default, PartialEq, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum ReportKind {
+    #[default]
+    Warranty,
+    Patch,
+    Antivirus,
+    OfflineServers,
+    Breakdown,
+}
+
+impl ReportKind {
+    pub const ALL: [ReportKind; 5] = [
+        ReportKind::Warranty,
+        ReportKind::Patch,
+        ReportKind::Antivirus,
+        ReportKind::OfflineServers,
+        ReportKind::Breakdown,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ReportKind::Warranty => "Warranty",
+            ReportKind::Patch => "Patch Compliance",
+            ReportKind::Antivirus => "AV Coverage",
+            ReportKind::OfflineServers => "Offline Servers",
+            ReportKind::Breakdown => "Device Breakdown",
+        }
+    }
+
+    /// Base name for exported files.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ReportKind::Warranty => "warranty-report",
+            ReportKind::Patch => "patch-report",
+            ReportKind::Antivirus => "av-coverage-report",
+            ReportKind::OfflineServers => "offline-servers",
+            ReportKind::Breakdown => "device-breakdown",
+        }
+    }
+}
+
+/// Column headers of the warranty report (also the CSV header).
+pub const WARRANTY_COLUMNS: [&str; 5] = ["Site", "Hostname", "Warranty Date", "Days Left", "Status"];
+
+/// A device whose warranty has expired or ends within the report window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarrantyRow {
+    pub site: String,
+    pub hostname: String,
+    pub device_uid: String,
+    pub warranty_date: String,
+    pub days_left: i64,
+    pub status: WarrantyStatus,
+}
+
+impl WarrantyRow {
+    pub fn cells(&self) -> Vec<String> {
+        vec![
+            self.site.clone(),
+            self.hostname.clone(),
+            self.warranty_date.clone(),
+            self.days_left.to_string(),
+            self.status.label().to_string(),
+        ]
+    }
+}
+
+/// Devices with a warranty that is expired or ends within `days` of `today`,
+/// grouped by site and soonest first within each site.
+pub fn warranty_rows(devices: &[Device], today: NaiveDate, days: i64) -> Vec<WarrantyRow> {
+    let mut rows: Vec<WarrantyRow> = devices
+        .iter()
+        .filter_map(|device| {
+            let date_str = device.warranty_date.as_deref()?;
+            let date = parse_warranty_date(date_str)?;
+            let days_left = date.signed_duration_since(today).num_days();
+            if days_left > days {
+                return None;
+            }
+            Some(WarrantyRow {
+                site: device_site_name(device),
+                hostname: device.hostname.clone(),
+                device_uid: device.uid.clone(),
+                warranty_date: date_str.to_string(),
+                days_left,
+                status: warranty_status(Some(date_str), today, days),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        a.site
+            .to_lowercase()
+            .cmp(&b.site.to_lowercase())
+            .then(a.days_left.cmp(&b.days_left))
+    });
+    rows
+}
+
+/// Patch statuses counted in their own column of the patch report; any other
+/// status (ApprovedPending, NoData, Unknown...) counts as "Other".
+pub const PATCH_STATUSES: [&str; 4] = ["FullyPatched", "RebootRequired", "InstallError", "NoPolicy"];
+
+/// Column headers of the per-site patch summary.
+pub const PATCH_COLUMNS: [&str; 7] = [
+    "Site",
+    "Devices",
+    "FullyPatched",
+    "RebootRequired",
+    "InstallError",
+    "NoPolicy",
+    "Other",
+];
+
+/// Column headers of the patch drill-down (devices that aren't fully patched).
+pub const PATCH_DEVICE_COLUMNS: [&str; 4] = ["Site", "Hostname", "Operating System", "Patch Status"];
+
+/// Device counts per patch status for one site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchSiteRow {
+    pub site: String,
+    pub site_uid: String,
+    pub total: usize,
+    /// Counts in `PATCH_STATUSES` order
+    pub counts: [usize; 4],
+    pub other: usize,
+}
+
+impl PatchSiteRow {
+    pub fn cells(&self) -> Vec<String> {
+        let mut cells = vec![self.site.clone(), self.total.to_string()];
+        cells.extend(self.counts.iter().map(|c| c.to_string()));
+        cells.push(self.other.to_string());
+        cells
+    }
+}
+
+/// A device in the patch drill-down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchDeviceRow {
+    pub site: String,
+    pub hostname: String,
+    pub device_uid: String,
+    pub operating_system: String,
+    pub status: String,
+}
+
+impl PatchDeviceRow {
+    pub fn cells(&self) -> Vec<String> {
+        vec![
+            self.site.clone(),
+            self.hostname.clone(),
+            self.operating_system.clone(),
+            self.status.clone(),
+        ]
+    }
+}
+
+fn device_site_name(device: &Device) -> String {
+    device.site_name.clone().unwrap_or_else(|| device.site_uid.clone())
+}
+
+/// Patch status counts per site, ordered by site name.
+pub fn patch_rows(devices: &[Device]) -> Vec<PatchSiteRow> {
+    let mut rows: Vec<PatchSiteRow> = Vec::new();
+    for device in devices {
+        let index = match rows.iter().position(|r| r.site_uid == device.site_uid) {
+            Some(i) => i,
+            None => {
+                rows.push(PatchSiteRow {
+                    site: device_site_name(device),
+                    site_uid: device.site_uid.clone(),
+                    total: 0,
+                    counts: [0; 4],
+                    other: 0,
+                });
+                rows.len() - 1
+            }
+        };
+        let row = &mut rows[index];
+        row.total += 1;
+        match PATCH_STATUSES.iter().position(|s| *s == patch_status_label(device)) {
+            Some(i) => row.counts[i] += 1,
+            None => row.other += 1,
+        }
+    }
+    rows.sort_by(|a, b| cmp_text(&a.site, &b.site));
+    rows
+}
+
+/// Devices in `site_uid` that aren't fully patched, grouped by status.
+pub fn patch_device_rows(devices: &[Device], site_uid: &str) -> Vec<PatchDeviceRow> {
+    let mut rows: Vec<PatchDeviceRow> = devices
+        .iter()
+        .filter(|d| d.site_uid == site_uid && patch_status_label(d) != "FullyPatched")
+        .map(|d| PatchDeviceRow {
+            site: device_site_name(d),
+            hostname: d.hostname.clone(),
+            device_uid: d.uid.clone(),
+            operating_system: d.operating_system.clone().unwrap_or_default(),
+            status: patch_status_label(d).to_string(),
+        })
+        .collect();
+    rows.sort_by(|a, b| a.status.cmp(&b.status).then(cmp_text(&a.hostname, &b.hostname)));
+    rows
+}
+
+/// Antivirus statuses reported by the agent, in cross-tab column order.
+pub const AV_STATUSES: [&str; 4] = [
+    "RunningAndUpToDate",
+    "RunningAndNotUpToDate",
+    "NotRunning",
+    "NotDetected",
+];
+
+/// Column headers of the product by status cross-tab.
+pub const AV_PRODUCT_COLUMNS: [&str; 7] = [
+    "Product",
+    "Devices",
+    "RunningAndUpToDate",
+    "RunningAndNotUpToDate",
+    "NotRunning",
+    "NotDetected",
+    "Other",
+];
+
+/// Column headers of the list of devices without working AV (also the export).
+pub const AV_GAP_COLUMNS: [&str; 4] = ["Site", "Hostname", "Product", "Status"];
+
+fn antivirus_product(device: &Device) -> &str {
+    device
+        .antivirus
+        .as_ref()
+        .and_then(|av| av.antivirus_product.as_deref())
+        .unwrap_or("None")
+}
+
+fn antivirus_status(device: &Device) -> &str {
+    device
+        .antivirus
+        .as_ref()
+        .and_then(|av| av.antivirus_status.as_deref())
+        .unwrap_or("Unknown")
+}
+
+/// Device counts per antivirus status for one product.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvProductRow {
+    pub product: String,
+    pub total: usize,
+    /// Counts in `AV_STATUSES` order
+    pub counts: [usize; 4],
+    pub other: usize,
+}
+
+impl AvProductRow {
+    pub fn cells(&self) -> Vec<String> {
+        let mut cells = vec![self.product.clone(), self.total.to_string()];
+        cells.extend(self.counts.iter().map(|c| c.to_string()));
+        cells.push(self.other.to_string());
+        cells
+    }
+}
+
+/// A device whose antivirus is not detected or not running.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvGapRow {
+    pub site: String,
+    pub hostname: String,
+    pub device_uid: String,
+    pub product: String,
+    pub status: String,
+}
+
+impl AvGapRow {
+    pub fn cells(&self) -> Vec<String> {
+        vec![
+            self.site.clone(),
+            self.hostname.clone(),
+            self.product.clone(),
+            self.status.clone(),
+        ]
+    }
+}
+
+/// Cross-tab of antivirus product by status, most common product first.
+pub fn av_product_rows(devices: &[Device]) -> Vec<AvProductRow> {
+    let mut rows: Vec<AvProductRow> = Vec::new();
+    for device in devices {
+        let product = antivirus_product(device);
+        let index = match rows.iter().position(|r| r.product == product) {
+            Some(i) => i,
+            None => {
+                rows.push(AvProductRow {
+                    product: product.to_string(),
+                    total: 0,
+                    counts: [0; 4],
+                    other: 0,
+                });
+                rows.len() - 1
+            }
+        };
+        let row = &mut rows[index];
+        row.total += 1;
+        match AV_STATUSES.iter().position(|s| *s == antivirus_status(device)) {
+            Some(i) => row.counts[i] += 1,
+            None => row.other += 1,
+        }
+    }
+    rows.sort_by(|a, b| b.total.cmp(&a.total).then(cmp_text(&a.product, &b.product)));
+    rows
+}
+
+/// Devices with NotDetected or NotRunning antivirus, grouped by site.
+pub fn av_gap_rows(devices: &[Device]) -> Vec<AvGapRow> {
+    let mut rows: Vec<AvGapRow> = devices
+        .iter()
+        .filter(|d| matches!(antivirus_status(d), "NotDetected" | "NotRunning"))
+        .map(|d| AvGapRow {
+            site: device_site_name(d),
+            hostname: d.hostname.clone(),
+            device_uid: d.uid.clone(),
+            product: antivirus_product(d).to_string(),
+            status: antivirus_status(d).to_string(),
+        })
+        .collect();
+    rows.sort_by(|a, b| cmp_text(&a.site, &b.site).then(cmp_text(&a.hostname, &b.hostname)));
+    rows
+}
+
+/// Column headers of the offline servers watchlist (also the export).
+pub const OFFLINE_SERVER_COLUMNS: [&str; 5] =
+    ["Site", "Hostname", "Operating System", "Last Seen", "Offline For"];
+
+/// Servers are recognised by device category, chassis type or a server OS.
+pub fn is_server(device: &Device) -> bool {
+    let device_type = device.device_type.as_ref();
+    device_type
+        .and_then(|dt| dt.category.as_deref())
+        .is_some_and(|c| c.eq_ignore_ascii_case("server"))
+        || device_type.and_then(|dt| dt.type_field.as_deref()) == Some("Main System Chassis")
+        || device
+            .operating_system
+            .as_deref()
+            .is_some_and(|os| os.to_lowercase().contains("server"))
+}
+
+/// An offline server on the watchlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfflineServerRow {
+    pub site: String,
+    pub hostname: String,
+    pub device_uid: String,
+    pub operating_system: String,
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Seconds since `last_seen`, when known
+    pub offline_secs: Option<i64>,
+}
+
+impl OfflineServerRow {
+    pub fn cells(&self) -> Vec<String> {
+        vec![
+            self.site.clone(),
+            self.hostname.clone(),
+            self.operating_system.clone(),
+            self.last_seen
+                .map(|ts| time::in_display_zone(ts).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "N/A".to_string()),
+            self.offline_secs.map(offline_for).unwrap_or_else(|| "Unknown".to_string()),
+        ]
+    }
+}
+
+/// Short offline duration such as "45m", "6h" or "3d 4h".
+pub fn offline_for(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+/// Offline servers, longest offline first; servers without a last-seen time
+/// come last.
+pub fn offline_server_rows(devices: &[Device], now: DateTime<Utc>) -> Vec<OfflineServerRow> {
+    let mut rows: Vec<OfflineServerRow> = devices
+        .iter()
+        .filter(|d| !d.online && is_server(d))
+        .map(|d| {
+            let last_seen = d.last_seen_at();
+            OfflineServerRow {
+                site: device_site_name(d),
+                hostname: d.hostname.clone(),
+                device_uid: d.uid.clone(),
+                operating_system: d.operating_system.clone().unwrap_or_default(),
+                last_seen,
+                offline_secs: last_seen.map(|ts| (now - ts).num_seconds()),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| match (a.offline_secs, b.offline_secs) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+    rows
+}
+
+/// Column headers of the device breakdown export.
+pub const BREAKDOWN_COLUMNS: [&str; 3] = ["Breakdown", "Value", "Devices"];
+
+/// Operating systems charted by name; the less common ones are counted as "Other".
+pub const BREAKDOWN_TOP_OS: usize = 8;
+
+/// Device counts of one scope (the whole account or a site) by device
+/// category, operating system and online status.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceBreakdown {
+    pub total: usize,
+    /// Most common first
+    pub categories: Vec<(String, usize)>,
+    /// Most common first, at most `BREAKDOWN_TOP_OS` plus "Other"
+    pub operating_systems: Vec<(String, usize)>,
+    pub online: usize,
+    pub offline: usize,
+}
+
+impl DeviceBreakdown {
+    /// One row per charted value, for export.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        let row = |group: &str, value: &str, count: usize| {
+            vec![group.to_string(), value.to_string(), count.to_string()]
+        };
+        let mut rows: Vec<Vec<String>> = self
+            .categories
+            .iter()
+            .map(|(value, count)| row("Category", value, *count))
+            .collect();
+        rows.extend(
+            self.operating_systems
+                .iter()
+                .map(|(value, count)| row("Operating System", value, *count)),
+        );
+        rows.push(row("Status", "Online", self.online));
+        rows.push(row("Status", "Offline", self.offline));
+        rows
+    }
+}
+
+/// Counts of each value, most common first (ties alphabetically).
+fn ranked_counts<'a>(values: impl Iterator<Item = &'a str>) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for value in values {
+        match counts.iter_mut().find(|(v, _)| v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value.to_string(), 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(cmp_text(&a.0, &b.0)));
+    counts
+}
+
+/// Breakdown of `devices` for the dashboard charts.
+pub fn device_breakdown<'a>(devices: impl Iterator<Item = &'a Device> + Clone) -> DeviceBreakdown {
+    let categories = ranked_counts(devices.clone().map(|d| {
+        d.device_type
+            .as_ref()
+            .and_then(|dt| dt.category.as_deref())
+            .filter(|c| !c.is_empty())
+            .unwrap_or("Unknown")
+    }));
+    let mut operating_systems = ranked_counts(devices.clone().map(|d| {
+        d.operating_system
+            .as_deref()
+            .map(str::trim)
+            .filter(|os| !os.is_empty())
+            .unwrap_or("Unknown")
+    }));
+    if operating_systems.len() > BREAKDOWN_TOP_OS {
+        let other = operating_systems.drain(BREAKDOWN_TOP_OS..).map(|(_, count)| count).sum();
+        operating_systems.push(("Other".to_string(), other));
+    }
+    let online = devices.clone().filter(|d| d.online).count();
+    let total = devices.count();
+    DeviceBreakdown {
+        total,
+        categories,
+        operating_systems,
+        online,
+        offline: total - online,
+    }
+}
+
+/// A selectable scope of the breakdown dashboard: the account or one site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakdownScope {
+    /// `None` for the whole account
+    pub site_uid: Option<String>,
+    pub name: String,
+    pub devices: usize,
+}
+
+/// "All sites" followed by every site with devices, ordered by name.
+pub fn breakdown_scopes(devices: &[Device]) -> Vec<BreakdownScope> {
+    let mut sites: Vec<BreakdownScope> = Vec::new();
+    for device in devices {
+        match sites.iter_mut().find(|s| s.site_uid.as_ref() == Some(&device.site_uid)) {
+            Some(scope) => scope.devices += 1,
+            None => sites.push(BreakdownScope {
+                site_uid: Some(device.site_uid.clone()),
+                name: device_site_name(device),
+                devices: 1,
+            }),
+        }
+    }
+    sites.sort_by(|a, b| cmp_text(&a.name, &b.name));
+    let all = BreakdownScope {
+        site_uid: None,
+        name: "All sites".to_string(),
+        devices: devices.len(),
+    };
+    std::iter::once(all).chain(sites).collect()
+}
+
+/// State of the reports view. Every report shares one account-wide device fetch.
+#[derive(Debug)]
+pub struct ReportState {
+    pub kind: ReportKind,
+    pub devices: Vec<Device>,
+    pub loading: bool,
+    /// (loaded, total) while the account device fetch is running
+    pub progress: Option<(usize, Option<usize>)>,
+    pub table_state: TableState,
+    /// Warranty report window: expired plus ending within this many days
+    pub warranty_days: i64,
+    pub warranty_rows: Vec<WarrantyRow>,
+    pub patch_rows: Vec<PatchSiteRow>,
+    /// Site uid drilled into from the patch summary
+    pub patch_site: Option<String>,
+    pub patch_devices: Vec<PatchDeviceRow>,
+    pub av_products: Vec<AvProductRow>,
+    /// Devices without working AV; the selectable rows of the AV report
+    pub av_gaps: Vec<AvGapRow>,
+    pub offline_servers: Vec<OfflineServerRow>,
+    /// Rows of the breakdown dashboard; the selected one is charted
+    pub breakdown_scopes: Vec<BreakdownScope>,
+    pub breakdown: DeviceBreakdown,
+    /// Offline server uids seen by the last fetch; `None` until the first one,
+    /// so servers already offline at startup don't trigger notifications
+    known_offline: Option<HashSet<String>>,
+    /// Background refresh interval for the account devices (off when `None`)
+    pub refresh_interval: Option<Duration>,
+    /// When the last account device fetch started
+    pub last_fetch: Option<Instant>,
+    /// Send a desktop notification when a server goes offline
+    pub notify_offline: bool,
+    /// Result of the last export, shown under the table
+    pub export_message: Option<String>,
+}
+
+impl Default for ReportState {
+    fn default() -> Self {
+        Self {
+            kind: ReportKind::default(),
+            devices: Vec::new(),
+            loading: false,
+            progress: None,
+            table_state: TableState::default(),
+            warranty_days: EXPIRING_SOON_DAYS,
+            warranty_rows: Vec::new(),
+            patch_rows: Vec::new(),
+            patch_site: None,
+            patch_devices: Vec::new(),
+            av_products: Vec::new(),
+            av_gaps: Vec::new(),
+            offline_servers: Vec::new(),
+            breakdown_scopes: Vec::new(),
+            breakdown: DeviceBreakdown::default(),
+            known_offline: None,
+            refresh_interval: None,
+            last_fetch: None,
+            notify_offline: false,
+            export_message: None,
+        }
+    }
+}
+
+impl ReportState {
+    /// Rows in the current report's table.
+    pub fn row_count(&self) -> usize {
+        match self.kind {
+            ReportKind::Warranty => self.warranty_rows.len(),
+            ReportKind::Patch if self.patch_site.is_some() => self.patch_devices.len(),
+            ReportKind::Patch => self.patch_rows.len(),
+            ReportKind::Antivirus => self.av_gaps.len(),
+            ReportKind::OfflineServers => self.offline_servers.len(),
+            ReportKind::Breakdown => self.breakdown_scopes.len(),
+        }
+    }
+
+    pub fn next_row(&mut self) {
+        let count = self.row_count();
+        select_next(&mut self.table_state, count);
+        self.update_breakdown();
+    }
+
+    pub fn prev_row(&mut self) {
+        let count = self.row_count();
+        select_prev(&mut self.table_state, count);
+        self.update_breakdown();
+    }
+
+    /// Recounts the breakdown charts for the selected scope.
+    fn update_breakdown(&mut self) {
+        if self.kind != ReportKind::Breakdown {
+            return;
+        }
+        let site_uid = self.selected_breakdown_scope().and_then(|s| s.site_uid.clone());
+        self.breakdown = device_breakdown(
+            self.devices
+                .iter()
+                .filter(|d| site_uid.as_ref().is_none_or(|uid| &d.site_uid == uid)),
+        );
+    }
+
+    pub fn set_devices(&mut self, devices: Vec<Device>) {
+        self.devices = devices;
+        self.rebuild();
+    }
+
+    /// Widens or narrows the warranty window by `delta` days (never below zero).
+    pub fn adjust_warranty_days(&mut self, delta: i64) {
+        self.warranty_days = (self.warranty_days + delta).max(0);
+        self.rebuild();
+    }
+
+    /// Recomputes report rows from `devices`.
+    pub fn rebuild(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        self.warranty_rows = warranty_rows(&self.devices, today, self.warranty_days);
+        self.patch_rows = patch_rows(&self.devices);
+        self.patch_devices = match &self.patch_site {
+            Some(uid) => patch_device_rows(&self.devices, uid),
+            None => Vec::new(),
+        };
+        self.av_products = av_product_rows(&self.devices);
+        self.av_gaps = av_gap_rows(&self.devices);
+        self.offline_servers = offline_server_rows(&self.devices, Utc::now());
+        self.breakdown_scopes = breakdown_scopes(&self.devices);
+        let count = self.row_count();
+        self.table_state.select(match self.table_state.selected() {
+            _ if count == 0 => None,
+            Some(i) => Some(i.min(count - 1)),
+            None => Some(0),
+        });
+        self.update_breakdown();
+    }
+
+    /// Whether the background refresh is due. The first fetch happens on the
+    /// first tick so the watchlist has a baseline.
+    pub fn refresh_due(&self) -> bool {
+        let Some(interval) = self.refresh_interval else {
+            return false;
+        };
+        !self.loading && self.last_fetch.is_none_or(|t| t.elapsed() >= interval)
+    }
+
+    /// Offline servers that weren't offline in the previous fetch. Empty after
+    /// the first fetch, which only records the baseline.
+    pub fn newly_offline_servers(&mut self) -> Vec<OfflineServerRow> {
+        let current: HashSet<String> =
+            self.offline_servers.iter().map(|r| r.device_uid.clone()).collect();
+        let new_rows = match &self.known_offline {
+            Some(known) => self
+                .offline_servers
+                .iter()
+                .filter(|r| !known.contains(&r.device_uid))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        self.known_offline = Some(current);
+        new_rows
+    }
+
+    /// Drills into the selected site of the patch summary. Returns false if
+    /// nothing is selected.
+    pub fn open_patch_site(&mut self) -> bool {
+        let Some(row) = self.table_state.selected().and_then(|i| self.patch_rows.get(i)) else {
+            return false;
+        };
+        self.patch_site = Some(row.site_uid.clone());
+        self.table_state.select(None);
+        self.rebuild();
+        true
+    }
+
+    /// Returns from the patch drill-down to the summary, keeping the site selected.
+    /// Returns false if no drill-down was open.
+    pub fn close_patch_site(&mut self) -> bool {
+        let Some(uid) = self.patch_site.take() else {
+            return false;
+        };
+        self.rebuild();
+        if let Some(position) = self.patch_rows.iter().position(|r| r.site_uid == uid) {
+            self.table_state.select(Some(position));
+        }
+        true
+    }
+
+    /// Headers and cells of the current report table, for export.
+    pub fn export_table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        match self.kind {
+            ReportKind::Warranty => (
+                WARRANTY_COLUMNS.to_vec(),
+                self.warranty_rows.iter().map(WarrantyRow::cells).collect(),
+            ),
+            ReportKind::Patch if self.patch_site.is_some() => (
+                PATCH_DEVICE_COLUMNS.to_vec(),
+                self.patch_devices.iter().map(PatchDeviceRow::cells).collect(),
+            ),
+            ReportKind::Patch => (
+                PATCH_COLUMNS.to_vec(),
+                self.patch_rows.iter().map(PatchSiteRow::cells).collect(),
+            ),
+            ReportKind::Antivirus => (
+                AV_GAP_COLUMNS.to_vec(),
+                self.av_gaps.iter().map(AvGapRow::cells).collect(),
+            ),
+            ReportKind::OfflineServers => (
+                OFFLINE_SERVER_COLUMNS.to_vec(),
+                self.offline_servers.iter().map(OfflineServerRow::cells).collect(),
+            ),
+            ReportKind::Breakdown => (BREAKDOWN_COLUMNS.to_vec(), self.breakdown.rows()),
+        }
+    }
+
+    /// Base file name for exporting the current table.
+    pub fn export_name(&self) -> String {
+        match (&self.kind, self.patch_devices.first()) {
+            (ReportKind::Patch, Some(row)) if self.patch_site.is_some() => {
+                format!("{}-{}", self.kind.file_name(), file_safe(&row.site))
+            }
+            (ReportKind::Breakdown, _) => match self.selected_breakdown_scope() {
+                Some(scope) if scope.site_uid.is_some() => {
+                    format!("{}-{}", self.kind.file_name(), file_safe(&scope.name))
+                }
+                _ => self.kind.file_name().to_string(),
+            },
+            _ => self.kind.file_name().to_string(),
+        }
+    }
+
+    /// Device behind the selected row, for drilling into device detail.
+    pub fn selected_device(&self) -> Option<&Device> {
+        let i = self.table_state.selected()?;
+        let uid = match self.kind {
+            ReportKind::Warranty => &self.warranty_rows.get(i)?.device_uid,
+            ReportKind::Patch if self.patch_site.is_some() => &self.patch_devices.get(i)?.device_uid,
+            ReportKind::Patch => return None,
+            ReportKind::Antivirus => &self.av_gaps.get(i)?.device_uid,
+            ReportKind::OfflineServers => &self.offline_servers.get(i)?.device_uid,
+            ReportKind::Breakdown => return None,
+        };
+        self.devices.iter().find(|d| &d.uid == uid)
+    }
+
+    /// The breakdown scope whose charts are shown.
+    pub fn selected_breakdown_scope(&self) -> Option<&BreakdownScope> {
+        self.breakdown_scopes.get(self.table_state.selected()?)
+    }
+}
+
+/// Lowercases `name` and replaces anything but letters and digits with dashes.
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(hostname: &str, site: &str, warranty: Option<&str>) -> Device {
+        patched_device(hostname, site, warranty, "FullyPatched")
+    }
+
+    fn patched_device(hostname: &str, site: &str, warranty: Option<&str>, patch: &str) -> Device {
+        DeviceBuilder::new(hostname)
+            .uid(hostname)
+            .site(site)
+            .with("siteName", site)
+            .with("warrantyDate", warranty)
+            .patch_status(patch)
+            .build()
+    }
+
+    fn av_device(hostname: &str, site: &str, product: Option<&str>, status: &str) -> Device {
+        let mut device = device(hostname, site, None);
+        device.antivirus = Some(crate::api::datto::types::Antivirus {
+            antivirus_product: product.map(str::to_string),
+            antivirus_status: Some(status.to_string()),
+        });
+        device
+    }
+
+    #[test]
+    fn test_av_cross_tab_and_gaps() {
+        let devices = vec![
+            av_device("B-1", "Beta", None, "NotDetected"),
+            av_device("A-1", "Alpha", Some("Datto AV"), "RunningAndUpToDate"),
+            av_device("A-2", "Alpha", Some("Datto AV"), "NotRunning"),
+            av_device("A-3", "Alpha", Some("Sophos"), "RunningAndNotUpToDate"),
+        ];
+
+        let products = av_product_rows(&devices);
+        assert_eq!(products[0].cells(), vec!["Datto AV", "2", "1", "0", "1", "0", "0"]);
+        assert_eq!(products.len(), 3);
+
+        let rows = av_gap_rows(&devices);
+        let gaps: Vec<(&str, &str)> = rows
+            .iter()
+            .map(|r| (r.hostname.as_str(), r.status.as_str()))
+            .collect();
+        assert_eq!(gaps, vec![("A-2", "NotRunning"), ("B-1", "NotDetected")]);
+    }
+
+    #[test]
+    fn test_warranty_rows_window_and_grouping() {
+        let today = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let devices = vec![
+            device("B-OLD", "Beta", Some("2025-12-01")),
+            device("A-SOON", "Alpha", Some("2026-02-01")),
+            device("A-OLD", "Alpha", Some("2025-06-01")),
+            device("A-FINE", "Alpha", Some("2027-01-01")),
+            device("A-NONE", "Alpha", None),
+        ];
+
+        let rows = warranty_rows(&devices, today, 30);
+        let hosts: Vec<&str> = rows.iter().map(|r| r.hostname.as_str()).collect();
+        assert_eq!(hosts, vec!["A-OLD", "A-SOON", "B-OLD"]);
+        assert_eq!(rows[0].status, WarrantyStatus::Expired);
+        assert_eq!(rows[1].status, WarrantyStatus::ExpiringSoon);
+        assert_eq!(rows[1].days_left, 17);
+
+        assert_eq!(warranty_rows(&devices, today, 0).len(), 2);
+    }
+
+    #[test]
+    fn test_patch_summary_and_drill_down() {
+        let devices = vec![
+            patched_device("B-1", "Beta", None, "FullyPatched"),
+            patched_device("A-1", "Alpha", None, "RebootRequired"),
+            patched_device("A-2", "Alpha", None, "FullyPatched"),
+            patched_device("A-3", "Alpha", None, "ApprovedPending"),
+        ];
+        let mut state = ReportState {
+            kind: ReportKind::Patch,
+            ..Default::default()
+        };
+        state.set_devices(devices);
+        assert_eq!(state.patch_rows[0].cells(), vec!["Alpha", "3", "1", "1", "0", "0", "1"]);
+        assert_eq!(state.patch_rows[1].site, "Beta");
+
+        assert!(state.open_patch_site());
+        let hosts: Vec<&str> = state.patch_devices.iter().map(|r| r.hostname.as_str()).collect();
+        assert_eq!(hosts, vec!["A-3", "A-1"]);
+        assert_eq!(state.selected_device().map(|d| d.uid.as_str()), Some("A-3"));
+        assert_eq!(state.export_name(), "patch-report-alpha");
+
+        assert!(state.close_patch_site());
+        assert_eq!(state.table_state.selected(), Some(0));
+        assert!(!state.close_patch_site());
+    }
+
+    #[test]
+    fn test_breakdown_per_scope() {
+        let mut server = device("DC01", "Beta", None);
+        server.online = false;
+        server.operating_system = Some("Windows Server 2022".to_string());
+        server.device_type = Some(crate::api::datto::types::DeviceType {
+            category: Some("Server".to_string()),
+            type_field: None,
+        });
+        let mut state = ReportState {
+            kind: ReportKind::Breakdown,
+            ..Default::default()
+        };
+        state.set_devices(vec![device("A-1", "Alpha", None), server, device("A-2", "Alpha", None)]);
+
+        let scopes: Vec<(&str, usize)> = state
+            .breakdown_scopes
+            .iter()
+            .map(|s| (s.name.as_str(), s.devices))
+            .collect();
+        assert_eq!(scopes, vec![("All sites", 3), ("Alpha", 2), ("Beta", 1)]);
+        assert_eq!(
+            state.breakdown.categories,
+            vec![("Unknown".to_string(), 2), ("Server".to_string(), 1)]
+        );
+        assert_eq!((state.breakdown.online, state.breakdown.offline), (2, 1));
+
+        state.next_row();
+        state.next_row();
+        assert_eq!(state.breakdown.total, 1);
+        assert_eq!(state.breakdown.operating_systems[0].0, "Windows Server 2022");
+        assert_eq!(state.export_name(), "device-breakdown-beta");
+        assert_eq!(state.export_table().1.last().unwrap(), &vec!["Status", "Offline", "1"]);
+    }
+
+    #[test]
+    fn test_offline_servers_sorted_and_notified_once() {
+        let now = DateTime::parse_from_rfc3339("2026-01-15T12:00:00Z").unwrap().to_utc();
+        let hours_ago = |h: i64| (now.timestamp_millis() - h * 3_600_000).into();
+        let mut dc = device("DC01", "Alpha", None);
+        dc.online = false;
+        dc.operating_system = Some("Windows Server 2022".to_string());
+        dc.last_seen = Some(hours_ago(2));
+        let mut fs = dc.clone();
+        fs.uid = "FS01".to_string();
+        fs.hostname = "FS01".to_string();
+        fs.last_seen = Some(hours_ago(50));
+        let mut laptop = device("LT01", "Alpha", None);
+        laptop.online = false;
+
+        let rows = offline_server_rows(&[dc.clone(), laptop, fs.clone()], now);
+        let hosts: Vec<&str> = rows.iter().map(|r| r.hostname.as_str()).collect();
+        assert_eq!(hosts, vec!["FS01", "DC01"]);
+        assert_eq!(rows[0].cells()[4], "2d 2h");
+
+        let mut state = ReportState::default();
+        state.set_devices(vec![fs.clone()]);
+        assert!(state.newly_offline_servers().is_empty());
+        state.set_devices(vec![fs, dc]);
+        let new: Vec<String> = state.newly_offline_servers().into_iter().map(|r| r.hostname).collect();
+        assert_eq!(new, vec!["DC01"]);
+        assert!(state.newly_offline_servers().is_empty());
+    }
+}
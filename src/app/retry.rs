@@ -0,0 +1,86 @@
+use crate::api::error::ApiError;
+use std::time::{Duration, Instant};
+
+/// Retries of a failed load before its error is left on screen.
+const MAX_RETRIES: u32 = 3;
+
+/// Automatic retries of a load that failed with an error worth another try
+/// (see `ApiError::retry_delay`), waiting twice as long each time. `T` names
+/// what to load again, e.g. the site uid of a device list.
+#[derive(Debug, Clone)]
+pub struct Retry<T = ()> {
+    attempts: u32,
+    /// What failed last, kept so a failure of something else starts over
+    target: Option<T>,
+    due: Option<Instant>,
+}
+
+impl<T> Default for Retry<T> {
+    fn default() -> Self {
+        Self { attempts: 0, target: None, due: None }
+    }
+}
+
+impl<T: Clone + PartialEq> Retry<T> {
+    /// Schedules another load of `target` after `error`.
+    ///
+    /// # Returns
+    /// The wait, or `None` if the error isn't retryable or the retries are used up.
+    pub fn schedule(&mut self, target: T, error: &ApiError) -> Option<Duration> {
+        if self.target.as_ref() != Some(&target) {
+            self.attempts = 0;
+        }
+        self.target = Some(target);
+        self.due = None;
+        let delay = error.retry_delay()?;
+        if self.attempts >= MAX_RETRIES {
+            return None;
+        }
+        let delay = if matches!(error, ApiError::RateLimited { retry_after: Some(_), .. }) {
+            delay
+        } else {
+            delay * 2u32.pow(self.attempts)
+        };
+        self.attempts += 1;
+        self.due = Some(Instant::now() + delay);
+        Some(delay)
+    }
+
+    /// The target once its retry is due, clearing it.
+    pub fn take_due(&mut self) -> Option<T> {
+        if self.due.is_some_and(|due| Instant::now() >= due) {
+            self.due = None;
+            return self.target.clone();
+        }
+        None
+    }
+
+    /// Forgets the failures after a successful load.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retries_back_off_and_give_up() {
+        let mut retry = Retry::default();
+        let outage = ApiError::Network("connection reset".to_string());
+        let delays: Vec<_> = (0..4).map(|_| retry.schedule("site", &outage)).collect();
+        let secs = |s| Some(Duration::from_secs(s));
+        assert_eq!(delays, [secs(5), secs(10), secs(20), None]);
+        assert_eq!(retry.take_due(), None);
+
+        assert_eq!(retry.schedule("other", &outage), secs(5));
+        let limited = ApiError::RateLimited { retry_after: secs(30), message: String::new() };
+        assert_eq!(retry.schedule("other", &limited), secs(30));
+        assert_eq!(retry.schedule("other", &ApiError::Auth(String::new())), None);
+
+        retry.due = Some(Instant::now());
+        assert_eq!(retry.take_due(), Some("other"));
+        assert_eq!(retry.take_due(), None);
+    }
+}
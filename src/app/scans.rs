@@ -0,0 +1,203 @@
+use crate::api::error::ApiError;
+use crate::event::ScanProgress;
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+
+/// How often a scan the console can report on is checked.
+const SCAN_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Checks in a row that may fail before the scan is given up on.
+const MAX_POLL_ERRORS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanState {
+    /// Waiting for the console to accept the request
+    Starting,
+    /// Accepted by a console that doesn't report how the scan goes
+    Requested,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl ScanState {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScanState::Starting => "Starting",
+            ScanState::Requested => "Requested",
+            ScanState::Running => "Running",
+            ScanState::Completed => "Completed",
+            ScanState::Failed => "Failed",
+        }
+    }
+
+    /// Whether the scan no longer changes (or can't be followed).
+    pub fn is_finished(self) -> bool {
+        matches!(self, ScanState::Requested | ScanState::Completed | ScanState::Failed)
+    }
+}
+
+/// An antivirus scan started from the TUI this session.
+#[derive(Debug, Clone)]
+pub struct ScanJob {
+    pub id: u64,
+    pub hostname: String,
+    /// `SecurityProvider::name` of the console running the scan
+    pub provider: &'static str,
+    pub started_at: DateTime<Utc>,
+    pub state: ScanState,
+    /// Why the scan failed, or what the console reported when it finished
+    pub result: Option<String>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// What the console follows the scan by (a Defender machine action id,
+    /// a SentinelOne agent UUID); None when it can't be followed
+    pub handle: Option<String>,
+    errors: u32,
+    last_poll: Option<Instant>,
+    in_flight: bool,
+}
+
+impl ScanJob {
+    fn finish(&mut self, state: ScanState, result: Option<String>) {
+        self.state = state;
+        self.result = result;
+        self.finished_at = Some(Utc::now());
+    }
+
+    /// True when the next check should be sent, which is then counted as in
+    /// flight.
+    fn poll_due(&mut self) -> bool {
+        if self.in_flight || self.state != ScanState::Running || self.handle.is_none() {
+            return false;
+        }
+        if self.last_poll.is_some_and(|at| at.elapsed() < SCAN_POLL_INTERVAL) {
+            return false;
+        }
+        self.in_flight = true;
+        true
+    }
+}
+
+/// The session's scans, oldest first.
+#[derive(Debug, Default)]
+pub struct ScanJobs {
+    pub jobs: Vec<ScanJob>,
+    next_id: u64,
+}
+
+impl ScanJobs {
+    /// Records a scan of `hostname` being requested; returns its id.
+    pub fn start(&mut self, hostname: &str, provider: &'static str) -> u64 {
+        self.next_id += 1;
+        self.jobs.push(ScanJob {
+            id: self.next_id,
+            hostname: hostname.to_string(),
+            provider,
+            started_at: Utc::now(),
+            state: ScanState::Starting,
+            result: None,
+            finished_at: None,
+            handle: None,
+            errors: 0,
+            last_poll: None,
+            in_flight: false,
+        });
+        self.next_id
+    }
+
+    pub fn get(&self, id: u64) -> Option<&ScanJob> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut ScanJob> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    /// The most recent scan of `hostname`.
+    pub fn latest(&self, hostname: &str) -> Option<&ScanJob> {
+        self.jobs.iter().rev().find(|job| job.hostname == hostname)
+    }
+
+    /// Whether a scan of `hostname` is still starting or running.
+    pub fn is_active(&self, hostname: &str) -> bool {
+        self.latest(hostname).is_some_and(|job| !job.state.is_finished())
+    }
+
+    /// The console's answer to the scan request; a handle means the scan can
+    /// be followed until it finishes.
+    pub fn started(&mut self, id: u64, result: Result<Option<String>, ApiError>) {
+        let Some(job) = self.get_mut(id) else {
+            return;
+        };
+        match result {
+            Ok(Some(handle)) => {
+                job.state = ScanState::Running;
+                job.handle = Some(handle);
+            }
+            Ok(None) => {
+                job.state = ScanState::Requested;
+                job.result = Some("Progress is only shown in the console".to_string());
+            }
+            Err(e) => job.finish(ScanState::Failed, Some(e.to_string())),
+        }
+    }
+
+    /// Ids of the running scans due a check, now counted as in flight.
+    pub fn due_polls(&mut self) -> Vec<u64> {
+        self.jobs.iter_mut().filter_map(|job| job.poll_due().then_some(job.id)).collect()
+    }
+
+    pub fn polled(&mut self, id: u64, result: Result<ScanProgress, ApiError>) {
+        let Some(job) = self.get_mut(id) else {
+            return;
+        };
+        job.in_flight = false;
+        job.last_poll = Some(Instant::now());
+        match result {
+            Ok(ScanProgress::Running) => job.errors = 0,
+            Ok(ScanProgress::Completed(detail)) => job.finish(ScanState::Completed, detail),
+            Ok(ScanProgress::Failed(reason)) => job.finish(ScanState::Failed, Some(reason)),
+            Err(e) => {
+                job.errors += 1;
+                if job.errors >= MAX_POLL_ERRORS {
+                    job.finish(ScanState::Failed, Some(format!("Lost track of the scan: {}", e)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_lifecycle() {
+        let mut scans = ScanJobs::default();
+        let followed = scans.start("PC01", "Microsoft Defender");
+        let requested = scans.start("PC02", "Sophos");
+        let failed = scans.start("PC02", "Sophos");
+        assert!(scans.is_active("PC01"));
+
+        scans.started(followed, Ok(Some("action-1".to_string())));
+        scans.started(requested, Ok(None));
+        scans.started(failed, Err(ApiError::ReadOnly));
+        assert_eq!(scans.get(requested).unwrap().state, ScanState::Requested);
+        assert_eq!(scans.latest("PC02").unwrap().state, ScanState::Failed);
+        assert!(!scans.is_active("PC02"));
+
+        // Only the followed scan is polled, one check at a time
+        assert_eq!(scans.due_polls(), [followed]);
+        assert!(scans.due_polls().is_empty());
+        scans.polled(followed, Ok(ScanProgress::Running));
+        assert!(scans.due_polls().is_empty());
+
+        scans.get_mut(followed).unwrap().last_poll = Some(Instant::now() - SCAN_POLL_INTERVAL);
+        assert_eq!(scans.due_polls(), [followed]);
+        scans.polled(followed, Ok(ScanProgress::Completed(Some("No threats".to_string()))));
+        let job = scans.get(followed).unwrap();
+        assert_eq!(job.state, ScanState::Completed);
+        assert!(job.finished_at.is_some());
+        assert!(!scans.is_active("PC01"));
+    }
+}
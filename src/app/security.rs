@@ -0,0 +1,243 @@
+mod datto_av;
+mod defender;
+mod sentinelone;
+mod sophos;
+
+use super::{App, AvEndpoint, CurrentView, IncidentStats, MdrDefaults, ScanJob, ScanJobs};
+use crate::api::datto::types::Device;
+use crate::api::datto_av::types::{AgentDetail, Alert as AvAlert};
+use crate::api::error::ApiError;
+use crate::api::defender::types::{Alert as DefenderAlert, Machine};
+use crate::api::rocket_cyber::types::{Agent, Incident};
+use crate::api::sentinelone::types::{Agent as S1Agent, Threat};
+use crate::api::sophos::Endpoint;
+use crate::event::{Event, ScanProgress};
+use std::collections::HashMap;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Security integration data (RocketCyber, Sophos, Datto AV, Defender,
+/// SentinelOne), keyed by hostname unless noted otherwise.
+#[derive(Debug, Default)]
+pub struct SecurityState {
+    pub incidents: Vec<Incident>,
+    /// Keyed by lowercased RocketCyber account name and by account id
+    pub incident_stats: HashMap<String, IncidentStats>,
+    /// The account's `tuiMdr*` variables, for sites that don't set their own
+    pub mdr_defaults: MdrDefaults,
+
+    pub sophos_endpoints: HashMap<String, Endpoint>,
+    pub sophos_loading: HashMap<String, bool>,
+
+    pub rocket_agents: HashMap<String, Agent>,
+    pub rocket_loading: HashMap<String, bool>,
+
+    pub datto_av_agents: HashMap<String, AgentDetail>,
+    pub datto_av_loading: HashMap<String, bool>,
+    pub datto_av_alerts: HashMap<String, Vec<AvAlert>>,
+    pub datto_av_policies: HashMap<String, serde_json::Value>,
+
+    pub defender_machines: HashMap<String, Machine>,
+    pub defender_loading: HashMap<String, bool>,
+    /// The machine's alerts that aren't resolved
+    pub defender_alerts: HashMap<String, Vec<DefenderAlert>>,
+
+    pub sentinelone_agents: HashMap<String, S1Agent>,
+    pub sentinelone_loading: HashMap<String, bool>,
+    /// The agent's threats that aren't resolved
+    pub sentinelone_threats: HashMap<String, Vec<Threat>>,
+
+    /// Scans started this session, kept after leaving the device
+    pub scans: ScanJobs,
+}
+
+impl SecurityState {
+    /// Whether the device's agent is being looked up in any console.
+    pub fn agent_loading(&self, hostname: &str) -> bool {
+        [
+            &self.sophos_loading,
+            &self.datto_av_loading,
+            &self.defender_loading,
+            &self.sentinelone_loading,
+        ]
+        .iter()
+        .any(|loading| loading.get(hostname).copied().unwrap_or(false))
+    }
+}
+
+/// An antivirus console a device's agent is managed in, picked by the
+/// device's antivirus product. A new console is a module implementing this
+/// plus an entry in `PROVIDERS`.
+pub trait SecurityProvider: Sync {
+    /// Console name for menus and messages, e.g. "Sophos"
+    fn name(&self) -> &'static str;
+
+    /// Whether `device` reports this console's product.
+    fn detect(&self, device: &Device) -> bool;
+
+    /// Starts loading the device's agent into `app.security`.
+    fn fetch(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>);
+
+    /// Starts a scan of the device, once its agent has loaded, recording it
+    /// in `app.security.scans`.
+    fn scan(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>);
+
+    /// Checks on a running scan, for consoles that gave `ScanJobs::started`
+    /// a handle to follow it by.
+    fn poll_scan(&self, _app: &App, _job: &ScanJob, _tx: &UnboundedSender<Event>) {}
+
+    /// Whether `isolate` does anything for this console.
+    fn can_isolate(&self) -> bool {
+        false
+    }
+
+    /// Cuts the device off the network, once its agent has loaded.
+    fn isolate(&self, _app: &mut App, _device: &Device, _tx: &UnboundedSender<Event>) {}
+
+    /// The device's loaded agent, to remove along with the device.
+    fn endpoint(&self, app: &App, device: &Device) -> Option<AvEndpoint>;
+}
+
+/// Every console, in the order they are tried.
+const PROVIDERS: [&dyn SecurityProvider; 4] = [
+    &sophos::Sophos,
+    &datto_av::DattoAv,
+    &defender::Defender,
+    &sentinelone::SentinelOne,
+];
+
+/// The console managing `device`'s antivirus, if it's one we integrate with.
+pub fn security_provider(device: &Device) -> Option<&'static dyn SecurityProvider> {
+    PROVIDERS.into_iter().find(|provider| provider.detect(device))
+}
+
+/// The console called `name`, for following its scans.
+fn provider_named(name: &str) -> Option<&'static dyn SecurityProvider> {
+    PROVIDERS.into_iter().find(|provider| provider.name() == name)
+}
+
+/// The device's antivirus product, lowercased; empty when none is reported.
+fn av_product(device: &Device) -> String {
+    device
+        .antivirus
+        .as_ref()
+        .and_then(|av| av.antivirus_product.as_ref())
+        .map(|p| p.to_lowercase())
+        .unwrap_or_default()
+}
+
+impl App {
+    /// Loads the device's agent from the console its antivirus product
+    /// belongs to.
+    pub(super) fn fetch_device_security(&mut self, device: &Device, tx: &UnboundedSender<Event>) {
+        if let Some(provider) = security_provider(device) {
+            provider.fetch(self, device, tx);
+        }
+    }
+
+    /// Starts a scan of the open device in its antivirus console.
+    pub(super) fn start_av_scan(&mut self, tx: UnboundedSender<Event>) {
+        let Some(device) = self.device_detail.selected_device.clone() else {
+            return;
+        };
+        if self.security.scans.is_active(&device.hostname) {
+            self.show_toast(format!("A scan of {} is already running", device.hostname));
+            return;
+        }
+        if let Some(provider) = security_provider(&device) {
+            provider.scan(self, &device, &tx);
+        }
+    }
+
+    /// Records the console's answer to a scan request; a handle means the
+    /// scan is followed until it finishes.
+    pub(super) fn scan_started(&mut self, id: u64, result: Result<Option<String>, ApiError>) {
+        if let Err(e) = &result
+            && let Some(job) = self.security.scans.get(id)
+        {
+            let (provider, hostname) = (job.provider, job.hostname.clone());
+            self.report_api_error(
+                provider,
+                Some(CurrentView::DeviceDetail),
+                &format!("Failed to start {} scan for {}", provider, hostname),
+                e,
+            );
+        }
+        self.security.scans.started(id, result);
+    }
+
+    /// Records a check on a running scan. A finished scan is announced and
+    /// its device's agent reloaded if the device is open.
+    pub(super) fn scan_polled(
+        &mut self,
+        id: u64,
+        result: Result<ScanProgress, ApiError>,
+        tx: &UnboundedSender<Event>,
+    ) {
+        self.security.scans.polled(id, result);
+        let Some(job) = self.security.scans.get(id).filter(|job| job.finished_at.is_some()) else {
+            return;
+        };
+        let job = job.clone();
+        let state = job.state.label();
+        self.show_toast(format!("{} scan of {}: {}", job.provider, job.hostname, state));
+        if let Some(device) = self
+            .device_detail
+            .selected_device
+            .clone()
+            .filter(|device| device.hostname == job.hostname)
+        {
+            self.fetch_device_security(&device, tx);
+        }
+    }
+
+    /// Checks on the running scans that are due, from the tick handler.
+    pub(super) fn poll_scans(&mut self, tx: &UnboundedSender<Event>) {
+        for id in self.security.scans.due_polls() {
+            let Some(job) = self.security.scans.get(id).cloned() else {
+                continue;
+            };
+            if let Some(provider) = provider_named(job.provider) {
+                provider.poll_scan(self, &job, tx);
+            }
+        }
+    }
+
+    /// Isolates the open device in its antivirus console.
+    pub(super) fn isolate_device(&mut self, tx: UnboundedSender<Event>) {
+        let Some(device) = self.device_detail.selected_device.clone() else {
+            return;
+        };
+        if let Some(provider) = security_provider(&device).filter(|p| p.can_isolate()) {
+            provider.isolate(self, &device, &tx);
+        }
+    }
+
+    /// The loaded antivirus endpoint of `device`, for the decommission popup.
+    pub(super) fn device_av_endpoint(&self, device: &Device) -> Option<AvEndpoint> {
+        security_provider(device)?.endpoint(self, device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(product: Option<&str>) -> Device {
+        DeviceBuilder::new("ACME-PC01").antivirus(product, None).build()
+    }
+
+    #[test]
+    fn test_provider_follows_the_antivirus_product() {
+        let name = |product| security_provider(&device(product)).map(|p| p.name());
+        assert_eq!(name(Some("Sophos Intercept X")), Some("Sophos"));
+        assert_eq!(name(Some("Datto EDR")), Some("Datto AV"));
+        assert_eq!(name(Some("datto av")), Some("Datto AV"));
+        assert_eq!(name(Some("Windows Defender")), Some("Microsoft Defender"));
+        assert_eq!(name(Some("Sentinel Agent")), Some("SentinelOne"));
+        assert_eq!(name(Some("Webroot SecureAnywhere")), None);
+        assert_eq!(name(None), None);
+        assert!(security_provider(&device(Some("Sophos"))).unwrap().can_isolate());
+        assert!(security_provider(&device(Some("SentinelOne"))).unwrap().can_isolate());
+    }
+}
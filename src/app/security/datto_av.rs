@@ -0,0 +1,120 @@
+use super::{SecurityProvider, av_product};
+use crate::api::datto::types::{Device, Udf};
+use crate::api::error::ApiError;
+use crate::app::{App, AvEndpoint};
+use crate::event::Event;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Datto AV (and Datto EDR); agents are found by the id cached in UDF 30,
+/// falling back to a hostname search.
+pub struct DattoAv;
+
+impl SecurityProvider for DattoAv {
+    fn name(&self) -> &'static str {
+        "Datto AV"
+    }
+
+    fn detect(&self, device: &Device) -> bool {
+        let product = av_product(device);
+        product.contains("datto av") || product.contains("datto edr")
+    }
+
+    fn fetch(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        app.fetch_datto_av_agent(device.hostname.clone(), device.udf.clone(), tx.clone());
+    }
+
+    fn scan(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        let Some(AvEndpoint::DattoAv { agent_id }) = self.endpoint(app, device) else {
+            return;
+        };
+        let Some(client) = app.datto_av_client.clone() else {
+            return;
+        };
+        let id = app.security.scans.start(&device.hostname, self.name());
+        let tx = tx.clone();
+        app.tasks.spawn("start_av_scan", async move {
+            let result = client.scan_agent(&agent_id).await.map_err(ApiError::from);
+            let _ = tx.send(Event::DattoAvScanStarted(id, result));
+        });
+    }
+
+    fn endpoint(&self, app: &App, device: &Device) -> Option<AvEndpoint> {
+        app.datto_av_client.as_ref()?;
+        let agent = app.security.datto_av_agents.get(&device.hostname)?;
+        Some(AvEndpoint::DattoAv { agent_id: agent.id.clone() })
+    }
+}
+
+impl App {
+    fn fetch_datto_av_agent(
+        &mut self,
+        hostname: String,
+        udf: Option<Udf>,
+        tx: UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.datto_av_client.clone() else {
+            return;
+        };
+        // Agent id cached by an earlier lookup
+        let agent_id = udf.and_then(|u| u.udf30).filter(|id| !id.is_empty());
+        self.security.datto_av_loading.insert(hostname.clone(), true);
+
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::DattoAvAgentFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_datto_av_agent", on_panic, async move {
+            let result = async {
+                // A stale or mismatched id falls back to the hostname search
+                if let Some(id) = agent_id
+                    && let Ok(agent) = client.get_agent_detail(&id).await
+                {
+                    return Ok(agent);
+                }
+                let agents = client.get_agent_details(&hostname).await?;
+                agents.into_iter().next().ok_or_else(|| anyhow::anyhow!("No agent found"))
+            }
+            .await
+            .map_err(ApiError::from);
+            let _ = tx.send(Event::DattoAvAgentFetched(hostname, result));
+        });
+    }
+
+    pub fn fetch_datto_av_alerts(
+        &self,
+        agent_id: String,
+        hostname: String,
+        tx: UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.datto_av_client.clone() else {
+            return;
+        };
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::DattoAvAlertsFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_datto_av_alerts", on_panic, async move {
+            let result = client.get_agent_alerts(&agent_id).await.map_err(ApiError::from);
+            let _ = tx.send(Event::DattoAvAlertsFetched(hostname, result));
+        });
+    }
+
+    pub fn fetch_datto_av_policies(
+        &self,
+        agent_id: String,
+        hostname: String,
+        tx: UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.datto_av_client.clone() else {
+            return;
+        };
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::DattoAvPoliciesFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_datto_av_policies", on_panic, async move {
+            let result = client.get_agent_policies(&agent_id).await.map_err(ApiError::from);
+            let _ = tx.send(Event::DattoAvPoliciesFetched(hostname, result));
+        });
+    }
+}
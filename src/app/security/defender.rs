@@ -0,0 +1,112 @@
+use super::{SecurityProvider, av_product};
+use crate::api::datto::types::Device;
+use crate::api::defender::types::MachineAction;
+use crate::api::error::ApiError;
+use crate::app::{App, AvEndpoint, ScanJob};
+use crate::event::{Event, ScanProgress};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Microsoft Defender for Business; machines are looked up by DNS name in the
+/// tenant of the profile's app registration.
+pub struct Defender;
+
+impl SecurityProvider for Defender {
+    fn name(&self) -> &'static str {
+        "Microsoft Defender"
+    }
+
+    fn detect(&self, device: &Device) -> bool {
+        av_product(device).contains("defender")
+    }
+
+    fn fetch(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        app.fetch_defender_machine(device.hostname.clone(), tx.clone());
+    }
+
+    fn scan(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        let Some(machine) = app.security.defender_machines.get(&device.hostname) else {
+            return;
+        };
+        let Some(client) = app.defender_client.clone() else {
+            return;
+        };
+        let machine_id = machine.id.clone();
+        let id = app.security.scans.start(&device.hostname, self.name());
+        let comment = format!("Quick scan of {} from Kyber TUI", device.hostname);
+        let tx = tx.clone();
+        app.tasks.spawn("start_av_scan", async move {
+            let result = client.run_av_scan(&machine_id, &comment).await.map_err(ApiError::from);
+            let _ = tx.send(Event::DefenderScanStarted(id, result));
+        });
+    }
+
+    /// Follows the scan's machine action.
+    fn poll_scan(&self, app: &App, job: &ScanJob, tx: &UnboundedSender<Event>) {
+        let (Some(client), Some(action_id)) = (app.defender_client.clone(), job.handle.clone())
+        else {
+            return;
+        };
+        let id = job.id;
+        let tx = tx.clone();
+        app.tasks.spawn("poll_av_scan", async move {
+            let result = client
+                .get_machine_action(&action_id)
+                .await
+                .map(|action| scan_progress(&action))
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::DefenderScanPolled(id, result));
+        });
+    }
+
+    /// Machines leave Defender on their own once they stop reporting.
+    fn endpoint(&self, _app: &App, _device: &Device) -> Option<AvEndpoint> {
+        None
+    }
+}
+
+/// Where a scan is, by its machine action's status.
+fn scan_progress(action: &MachineAction) -> ScanProgress {
+    match action.status.as_deref() {
+        Some("Succeeded") => ScanProgress::Completed(None),
+        Some("TimeOut") => ScanProgress::Failed("Timed out".to_string()),
+        Some(status @ ("Failed" | "Cancelled")) => ScanProgress::Failed(status.to_string()),
+        _ => ScanProgress::Running,
+    }
+}
+
+impl App {
+    fn fetch_defender_machine(&mut self, hostname: String, tx: UnboundedSender<Event>) {
+        let Some(client) = self.defender_client.clone() else {
+            return;
+        };
+        self.security.defender_loading.insert(hostname.clone(), true);
+
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::DefenderMachinesFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_defender_machine", on_panic, async move {
+            let result = client.get_machines(&hostname).await.map_err(ApiError::from);
+            let _ = tx.send(Event::DefenderMachinesFetched(hostname, result));
+        });
+    }
+
+    pub fn fetch_defender_alerts(
+        &self,
+        machine_id: String,
+        hostname: String,
+        tx: UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.defender_client.clone() else {
+            return;
+        };
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::DefenderAlertsFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_defender_alerts", on_panic, async move {
+            let result = client.get_machine_alerts(&machine_id).await.map_err(ApiError::from);
+            let _ = tx.send(Event::DefenderAlertsFetched(hostname, result));
+        });
+    }
+}
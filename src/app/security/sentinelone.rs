@@ -0,0 +1,166 @@
+use super::{SecurityProvider, av_product};
+use crate::api::datto::types::Device;
+use crate::api::error::ApiError;
+use crate::api::sentinelone::types::Agent;
+use crate::app::{App, AvEndpoint, ScanJob};
+use crate::common::time::parse_timestamp_str;
+use crate::event::{Event, ScanProgress};
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// SentinelOne; agents are found by the UUID in the profile's
+/// `agent_uuid_udf`, falling back to a hostname search.
+pub struct SentinelOne;
+
+impl SecurityProvider for SentinelOne {
+    fn name(&self) -> &'static str {
+        "SentinelOne"
+    }
+
+    /// Windows reports the agent as "Sentinel Agent".
+    fn detect(&self, device: &Device) -> bool {
+        av_product(device).contains("sentinel")
+    }
+
+    fn fetch(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        let uuid = app
+            .sentinelone_uuid_udf
+            .and_then(|number| device.udf.as_ref()?.get(number))
+            .filter(|uuid| !uuid.is_empty())
+            .map(str::to_string);
+        app.fetch_sentinelone_agent(device.hostname.clone(), uuid, tx.clone());
+    }
+
+    fn scan(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        let Some(agent) = app.security.sentinelone_agents.get(&device.hostname) else {
+            return;
+        };
+        let Some(client) = app.sentinelone_client.clone() else {
+            return;
+        };
+        let (agent_id, uuid) = (agent.id.clone(), agent.uuid.clone());
+        let id = app.security.scans.start(&device.hostname, self.name());
+        let tx = tx.clone();
+        app.tasks.spawn("start_av_scan", async move {
+            let result = client.initiate_scan(&agent_id).await.map(|()| uuid);
+            let _ = tx.send(Event::SentinelOneScanStarted(id, result.map_err(ApiError::from)));
+        });
+    }
+
+    /// Follows the scan status the agent reports.
+    fn poll_scan(&self, app: &App, job: &ScanJob, tx: &UnboundedSender<Event>) {
+        let (Some(client), Some(uuid)) = (app.sentinelone_client.clone(), job.handle.clone())
+        else {
+            return;
+        };
+        let (id, started_at) = (job.id, job.started_at);
+        let tx = tx.clone();
+        app.tasks.spawn("poll_av_scan", async move {
+            let result = async {
+                let agent = client.get_agent_by_uuid(&uuid).await?;
+                agent
+                    .map(|agent| scan_progress(&agent, started_at))
+                    .ok_or_else(|| anyhow::anyhow!("Agent no longer found"))
+            }
+            .await
+            .map_err(ApiError::from);
+            let _ = tx.send(Event::SentinelOneScanPolled(id, result));
+        });
+    }
+
+    fn can_isolate(&self) -> bool {
+        true
+    }
+
+    fn isolate(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        let Some(agent) = app.security.sentinelone_agents.get(&device.hostname) else {
+            return;
+        };
+        let Some(client) = app.sentinelone_client.clone() else {
+            return;
+        };
+        let agent_id = agent.id.clone();
+        let hostname = device.hostname.clone();
+        let tx = tx.clone();
+        app.tasks.spawn("disconnect_sentinelone_agent", async move {
+            let result = client.disconnect_from_network(&agent_id).await.map_err(ApiError::from);
+            let _ = tx.send(Event::SentinelOneAgentDisconnected(hostname, result));
+        });
+    }
+
+    /// Agents are decommissioned in the SentinelOne console.
+    fn endpoint(&self, _app: &App, _device: &Device) -> Option<AvEndpoint> {
+        None
+    }
+}
+
+/// Where the scan started at `since` is. The agent only reports its latest
+/// scan, so an earlier one that finished means ours hasn't begun yet.
+fn scan_progress(agent: &Agent, since: DateTime<Utc>) -> ScanProgress {
+    let after = |at: &Option<String>| {
+        at.as_deref().and_then(parse_timestamp_str).is_some_and(|at| at >= since)
+    };
+    match agent.scan_status.as_deref() {
+        Some("finished") if after(&agent.scan_finished_at) => {
+            let threats = agent.active_threats.unwrap_or(0);
+            ScanProgress::Completed(Some(format!("{} active threats", threats)))
+        }
+        Some("aborted") if after(&agent.scan_started_at) => {
+            ScanProgress::Failed("Aborted on the agent".to_string())
+        }
+        _ => ScanProgress::Running,
+    }
+}
+
+impl App {
+    fn fetch_sentinelone_agent(
+        &mut self,
+        hostname: String,
+        uuid: Option<String>,
+        tx: UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.sentinelone_client.clone() else {
+            return;
+        };
+        self.security.sentinelone_loading.insert(hostname.clone(), true);
+
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::SentinelOneAgentFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_sentinelone_agent", on_panic, async move {
+            let result = async {
+                // A stale or mistyped UUID falls back to the hostname search
+                if let Some(uuid) = uuid
+                    && let Ok(Some(agent)) = client.get_agent_by_uuid(&uuid).await
+                {
+                    return Ok(agent);
+                }
+                let agents = client.get_agents(&hostname).await?;
+                agents.into_iter().next().ok_or_else(|| anyhow::anyhow!("No agent found"))
+            }
+            .await
+            .map_err(ApiError::from);
+            let _ = tx.send(Event::SentinelOneAgentFetched(hostname, result));
+        });
+    }
+
+    pub fn fetch_sentinelone_threats(
+        &self,
+        agent_id: String,
+        hostname: String,
+        tx: UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.sentinelone_client.clone() else {
+            return;
+        };
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::SentinelOneThreatsFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_sentinelone_threats", on_panic, async move {
+            let result = client.get_threats(&agent_id).await.map_err(ApiError::from);
+            let _ = tx.send(Event::SentinelOneThreatsFetched(hostname, result));
+        });
+    }
+}
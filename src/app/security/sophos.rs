@@ -0,0 +1,127 @@
+use super::{SecurityProvider, av_product};
+use crate::api::datto::types::Device;
+use crate::api::error::ApiError;
+use crate::app::{App, AvEndpoint, MdrMapping};
+use crate::event::Event;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Region assumed when the site doesn't set `tuiMdrRegion`.
+const DEFAULT_REGION: &str = "us01";
+
+/// Sophos Central; endpoints are looked up by hostname in the tenant named by
+/// the site's `tuiMdrId` variable (or the account's).
+pub struct Sophos;
+
+/// The Sophos tenant id and data region of the device's site, from its
+/// `tuiMdrId` and `tuiMdrRegion` variables or the account defaults.
+fn site_tenant(app: &App, device: &Device) -> Option<(String, Option<String>)> {
+    let site = app.site_list.sites.iter().find(|s| s.uid == device.site_uid)?;
+    let mapping = MdrMapping::resolve(site, &app.security.mdr_defaults);
+    Some((mapping.id()?.to_string(), mapping.region().map(String::from)))
+}
+
+impl SecurityProvider for Sophos {
+    fn name(&self) -> &'static str {
+        "Sophos"
+    }
+
+    fn detect(&self, device: &Device) -> bool {
+        av_product(device).contains("sophos")
+    }
+
+    fn fetch(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        if let Some((tenant_id, region)) = site_tenant(app, device) {
+            app.fetch_sophos_endpoint(tenant_id, region, device.hostname.clone(), tx.clone());
+        }
+    }
+
+    /// Refreshes the endpoint too, so the panel shows the scan's outcome.
+    fn scan(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        self.fetch(app, device, tx);
+        let Some(AvEndpoint::Sophos { tenant_id, region, endpoint_id }) = self.endpoint(app, device)
+        else {
+            return;
+        };
+        let Some(client) = app.sophos_client.clone() else {
+            return;
+        };
+        let id = app.security.scans.start(&device.hostname, self.name());
+        let tx = tx.clone();
+        app.tasks.spawn("start_av_scan", async move {
+            let result = client
+                .start_scan(&tenant_id, &region, &endpoint_id)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::SophosScanStarted(id, result));
+        });
+    }
+
+    fn can_isolate(&self) -> bool {
+        true
+    }
+
+    fn isolate(&self, app: &mut App, device: &Device, tx: &UnboundedSender<Event>) {
+        let Some(AvEndpoint::Sophos { tenant_id, region, endpoint_id }) = self.endpoint(app, device)
+        else {
+            return;
+        };
+        let Some(client) = app.sophos_client.clone() else {
+            return;
+        };
+        let hostname = device.hostname.clone();
+        let tx = tx.clone();
+        app.tasks.spawn("isolate_sophos_endpoint", async move {
+            let comment = format!("Isolated {} from Kyber TUI", hostname);
+            let result = client
+                .isolate_endpoint(&tenant_id, &region, &endpoint_id, &comment)
+                .await
+                .map_err(ApiError::from);
+            let _ = tx.send(Event::SophosEndpointIsolated(hostname, result));
+        });
+    }
+
+    fn endpoint(&self, app: &App, device: &Device) -> Option<AvEndpoint> {
+        app.sophos_client.as_ref()?;
+        let (tenant_id, region) = site_tenant(app, device)?;
+        let endpoint = app.security.sophos_endpoints.get(&device.hostname)?;
+        Some(AvEndpoint::Sophos {
+            tenant_id,
+            region: region.unwrap_or_else(|| DEFAULT_REGION.to_string()),
+            endpoint_id: endpoint.id.clone(),
+        })
+    }
+}
+
+impl App {
+    /// Looks up the Sophos endpoint named `hostname`, finding the tenant's
+    /// data region first when the site doesn't set it.
+    fn fetch_sophos_endpoint(
+        &mut self,
+        tenant_id: String,
+        data_region: Option<String>,
+        hostname: String,
+        tx: UnboundedSender<Event>,
+    ) {
+        let Some(client) = self.sophos_client.clone() else {
+            return;
+        };
+        self.security.sophos_loading.insert(hostname.clone(), true);
+
+        let on_panic = {
+            let hostname = hostname.clone();
+            move |e| Event::SophosEndpointsFetched(hostname, Err(e))
+        };
+        self.tasks.spawn_fetch("fetch_sophos_endpoint", on_panic, async move {
+            let result = async {
+                let region = match data_region {
+                    Some(region) => region,
+                    None => client.get_tenant(&tenant_id).await?.data_region,
+                };
+                client.get_endpoints(&tenant_id, &region, &hostname).await
+            }
+            .await
+            .map_err(ApiError::from);
+            let _ = tx.send(Event::SophosEndpointsFetched(hostname, result));
+        });
+    }
+}
@@ -0,0 +1,133 @@
+use super::{App, CurrentView, DeviceDetailTab, ReportKind, SiteDetailTab};
+use crate::api::datto::types::Device;
+use crate::cache;
+use crate::event::Event;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Navigation state saved to the cache so a drill-down survives a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub view: CurrentView,
+    /// Breadcrumb at save time, shown in the restore prompt
+    pub summary: String,
+    pub site_uid: Option<String>,
+    pub site_tab: SiteDetailTab,
+    /// Snapshot of the open device; detail data is fetched again on restore
+    pub device: Option<Device>,
+    pub device_tab: DeviceDetailTab,
+    pub report_kind: ReportKind,
+    pub site_filter: String,
+    pub hide_empty: bool,
+    pub device_filter: String,
+}
+
+impl Session {
+    /// Whether restoring would change anything from a fresh start.
+    pub fn is_worth_restoring(&self) -> bool {
+        self.view != CurrentView::List
+            || self.hide_empty
+            || !self.site_filter.is_empty()
+    }
+}
+
+impl App {
+    fn current_session(&self) -> Session {
+        Session {
+            view: self.current_view,
+            summary: crate::ui::breadcrumb(self)
+                .into_iter()
+                .skip(1)
+                .collect::<Vec<_>>()
+                .join(" › "),
+            site_uid: self.site_list.selected_site().map(|s| s.uid.clone()),
+            site_tab: self.site_detail.detail_tab,
+            device: self.device_detail.selected_device.clone(),
+            device_tab: self.device_detail.device_detail_tab,
+            report_kind: self.report.kind,
+            site_filter: self.site_list.filter_query.clone(),
+            hide_empty: self.site_list.hide_empty,
+            device_filter: self.site_detail.device_filter_query.clone(),
+        }
+    }
+
+    /// Writes the navigation state to the cache if it changed since the last save.
+    pub(super) fn persist_session(&mut self) {
+        if self.cache.is_none() || self.popups.pending_session.is_some() {
+            return;
+        }
+        let session = self.current_session();
+        let Ok(json) = serde_json::to_string(&session) else {
+            return;
+        };
+        if self.saved_session.as_deref() != Some(json.as_str()) {
+            self.cache_put(cache::SESSION_KEY, &session);
+            self.saved_session = Some(json);
+        }
+    }
+
+    /// Offers the session saved by the last run, if it went anywhere.
+    pub(super) fn offer_session_restore(&mut self) {
+        self.popups.pending_session = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.get::<Session>(cache::SESSION_KEY))
+            .map(|cached| cached.value)
+            .filter(Session::is_worth_restoring);
+    }
+
+    /// Re-opens the views of `session`, fetching their data as if navigated to.
+    pub(super) fn restore_session(&mut self, session: Session, tx: UnboundedSender<Event>) {
+        tracing::info!(view = ?session.view, summary = %session.summary, "restoring session");
+        self.site_list.filter_query = session.site_filter;
+        self.site_list.hide_empty = session.hide_empty;
+        self.site_list.refresh_visible();
+        self.site_detail.device_filter_query = session.device_filter;
+        self.site_detail.apply_device_filter();
+        self.report.kind = session.report_kind;
+        self.report.rebuild();
+
+        match session.view {
+            CurrentView::List => {
+                if let Some(uid) = &session.site_uid {
+                    self.site_list.select_site(uid);
+                }
+            }
+            CurrentView::Report => {
+                self.current_view = CurrentView::Report;
+                self.fetch_account_devices(tx);
+            }
+            CurrentView::Components => {
+                self.current_view = CurrentView::Components;
+                self.fetch_components(tx);
+            }
+            CurrentView::Integrations => self.current_view = CurrentView::Integrations,
+            CurrentView::AccountVariables => {
+                self.current_view = CurrentView::AccountVariables;
+                self.fetch_account_variables(tx);
+            }
+            CurrentView::Tags => {
+                self.current_view = CurrentView::Tags;
+                self.fetch_account_devices(tx);
+            }
+            CurrentView::Collections => self.current_view = CurrentView::Collections,
+            CurrentView::Detail => {
+                if let Some(uid) = session.site_uid {
+                    self.navigate_to_site_detail(uid, tx);
+                    self.site_detail.detail_tab = session.site_tab;
+                }
+            }
+            // The activity itself isn't saved; reopen its device
+            CurrentView::DeviceDetail | CurrentView::ActivityDetail => {
+                let Some(device) = session.device else {
+                    return;
+                };
+                // Load the parent site too so Back and n/N work as usual
+                self.navigate_to_site_detail(device.site_uid.clone(), tx.clone());
+                self.site_detail.detail_tab = session.site_tab;
+                self.navigate_to_device_detail(device, tx);
+                self.device_detail.device_detail_tab = session.device_tab;
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+/// Deleting a site, guarded by it having no devices and by typing its name.
+#[derive(Debug, Clone, Default)]
+pub struct SiteDeletion {
+    pub site_uid: String,
+    pub name: String,
+    /// Devices the site still has, from its device status and the loaded
+    /// device list
+    pub device_count: usize,
+    /// What the user typed so far
+    pub typed: String,
+    pub running: bool,
+}
+
+impl SiteDeletion {
+    /// Whether the site is empty and the typed text is its full name. Unlike
+    /// hostnames, site names are matched case-sensitively.
+    pub fn confirmed(&self) -> bool {
+        self.device_count == 0 && !self.name.is_empty() && self.typed == self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_an_empty_site_with_its_full_name_confirms() {
+        let mut deletion = SiteDeletion {
+            name: "Acme Ltd".to_string(),
+            device_count: 2,
+            typed: "Acme Ltd".to_string(),
+            ..Default::default()
+        };
+        assert!(!deletion.confirmed());
+
+        deletion.device_count = 0;
+        assert!(deletion.confirmed());
+        for typed in ["Acme", "acme ltd", "Acme Ltd "] {
+            deletion.typed = typed.to_string();
+            assert!(!deletion.confirmed(), "{:?}", typed);
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use super::device_filter::DeviceFilter;
+use super::fetches::Fetches;
+use super::retry::Retry;
+use super::sort::{cmp_text, priority_rank};
+use super::table_view::{TableView, select_next, select_prev};
+use super::{SiteDetailTab, SiteEditField, SiteEditState};
+use crate::api::datto::types::{Alert, Device};
+use chrono::{DateTime, Utc};
+use ratatui::widgets::TableState;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Column headers of the device table, in sort-column order.
+pub const DEVICE_COLUMNS: [&str; 6] =
+    ["Hostname", "Type", "Status", "Patch Status", "Last Seen", "AV"];
+
+/// Column headers of the site alerts table, in sort-column order.
+pub const SITE_ALERT_COLUMNS: [&str; 3] = ["Priority", "Diagnostics", "Computer Name"];
+
+/// Rows in the site settings tab.
+const SETTINGS_COUNT: usize = SiteEditField::ALL.len();
+
+/// UDF matrix columns shown before any are chosen, when no UDF labels are configured.
+const DEFAULT_UDF_COLUMNS: [usize; 5] = [1, 2, 3, 4, 5];
+
+/// State of the site detail view (devices, alerts, variables and settings tabs).
+#[derive(Debug, Default)]
+pub struct SiteDetailState {
+    pub detail_tab: SiteDetailTab,
+
+    // Devices
+    /// Filtered by `device_filter`
+    pub devices: TableView<Device>,
+    pub devices_loading: bool,
+    pub devices_error: Option<String>,
+    /// Reloads of a site's devices after a failed fetch, by site uid
+    pub devices_retry: Retry<String>,
+    /// Fetches for the open site, cancelled when another one is opened
+    pub fetches: Fetches,
+    pub selected_device_uids: HashSet<String>,
+    /// Variables marked with Space in the variables tab, by id
+    pub selected_variable_ids: HashSet<i32>,
+    pub devices_cached_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// (loaded, total) while a multi-page device fetch is running
+    pub devices_progress: Option<(usize, Option<usize>)>,
+    /// Filter text typed after `f` (see `DeviceFilter`)
+    pub device_filter_query: String,
+    pub is_device_filtering: bool,
+    /// Parse error for `device_filter_query`; the last valid filter stays applied
+    pub device_filter_error: Option<String>,
+    device_filter: DeviceFilter,
+
+    // Site Open Alerts
+    pub site_open_alerts: TableView<Alert>,
+    pub site_open_alerts_loading: bool,
+    pub site_open_alerts_error: Option<String>,
+
+    // Variables & Settings
+    pub variables_table_state: TableState,
+    pub settings_table_state: TableState,
+    pub site_edit_state: SiteEditState,
+    /// Keyed by site uid; set while that site's variables come from the cache
+    pub variables_cached_at: HashMap<String, chrono::DateTime<chrono::Utc>>,
+
+    // UDF matrix (rows are the visible devices and share their selection)
+    /// UDF numbers chosen with `c`; empty means `matrix_columns`' default
+    pub udf_columns: Vec<usize>,
+    /// Selected column, an index into `matrix_columns`
+    pub udf_column: usize,
+    pub is_choosing_udf_columns: bool,
+    /// (device uid, UDF number) of the cell open in the edit modal
+    pub editing_udf_cell: Option<(String, usize)>,
+}
+
+/// Parses a UDF column list such as `1-5, 17 18`: numbers and ranges from 1 to
+/// 30, separated by commas or spaces. Duplicates are dropped, order is kept.
+pub fn parse_udf_columns(text: &str) -> Result<Vec<usize>, String> {
+    let number = |s: &str| match s.trim().parse::<usize>() {
+        Ok(n @ 1..=30) => Ok(n),
+        _ => Err(format!("'{}' is not a UDF number from 1 to 30", s.trim())),
+    };
+    let mut columns = Vec::new();
+    for part in text.split([',', ' ']).filter(|p| !p.trim().is_empty()) {
+        let range = match part.split_once('-') {
+            Some((from, to)) => number(from)?..=number(to)?,
+            None => {
+                let n = number(part)?;
+                n..=n
+            }
+        };
+        for n in range {
+            if !columns.contains(&n) {
+                columns.push(n);
+            }
+        }
+    }
+    if columns.is_empty() {
+        return Err("Enter at least one UDF number".to_string());
+    }
+    Ok(columns)
+}
+
+/// Device type as shown in the device table ("Main System Chassis" reads as "Server").
+pub fn device_type_label(device: &Device) -> String {
+    match device
+        .device_type
+        .as_ref()
+        .and_then(|dt| dt.type_field.as_deref())
+    {
+        Some("Main System Chassis") => "Server".to_string(),
+        Some(t) => t.to_string(),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn patch_status_label(device: &Device) -> &str {
+    device
+        .patch_management
+        .as_ref()
+        .and_then(|pm| pm.patch_status.as_deref())
+        .unwrap_or("Unknown")
+}
+
+/// Antivirus product and status of a device, as reported by the agent.
+pub fn device_av(device: &Device) -> (&str, &str) {
+    let av = device.antivirus.as_ref();
+    (
+        av.and_then(|av| av.antivirus_product.as_deref())
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or("None"),
+        av.and_then(|av| av.antivirus_status.as_deref()).unwrap_or("Unknown"),
+    )
+}
+
+/// How long ago the agent last checked in, if it reported a time.
+pub fn last_seen_age(device: &Device, now: DateTime<Utc>) -> Option<chrono::Duration> {
+    let last_seen = device.last_seen_at()?;
+    Some(now - last_seen)
+}
+
+/// Computer name an alert was raised on.
+pub fn alert_device_name(alert: &Alert) -> &str {
+    alert
+        .alert_source_info
+        .as_ref()
+        .and_then(|s| s.device_name.as_deref())
+        .unwrap_or("N/A")
+}
+
+impl SiteDetailState {
+    pub fn is_device_filter_active(&self) -> bool {
+        !self.device_filter.is_empty()
+    }
+
+    pub fn selected_device(&self) -> Option<&Device> {
+        self.devices.selected()
+    }
+
+    /// Selects the device after (or before) `uid` in the visible rows, wrapping,
+    /// and returns it. `None` if `uid` isn't one of the visible devices.
+    pub fn select_sibling_device(&mut self, uid: &str, forward: bool) -> Option<&Device> {
+        let position = self.devices.rows().position(|d| d.uid == uid)?;
+        self.devices.state.select(Some(position));
+        if forward {
+            self.devices.next();
+        } else {
+            self.devices.prev();
+        }
+        self.devices.selected()
+    }
+
+    /// Replaces the device list, applying the current sort and filter and
+    /// selecting the first row.
+    pub fn set_devices(&mut self, devices: Vec<Device>) {
+        self.devices.set_items(devices);
+        self.sort_devices();
+        self.refresh_visible_devices();
+        self.devices.select_first();
+    }
+
+    /// Re-parses `device_filter_query` and refreshes the visible rows.
+    pub fn apply_device_filter(&mut self) {
+        match DeviceFilter::parse(&self.device_filter_query) {
+            Ok(filter) => {
+                self.device_filter = filter;
+                self.device_filter_error = None;
+            }
+            Err(e) => self.device_filter_error = Some(e),
+        }
+        self.refresh_visible_devices();
+    }
+
+    /// Shows the devices passing the filter, keeping the selected one
+    /// highlighted if it is still shown.
+    fn refresh_visible_devices(&mut self) {
+        let filter = &self.device_filter;
+        self.devices.filter(|d| filter.matches(d));
+    }
+
+    /// UDF numbers shown in the matrix: the chosen ones, else the labelled ones,
+    /// else UDF 1-5.
+    pub fn matrix_columns(&self, labels: &BTreeMap<usize, String>) -> Vec<usize> {
+        if !self.udf_columns.is_empty() {
+            self.udf_columns.clone()
+        } else if !labels.is_empty() {
+            labels.keys().copied().collect()
+        } else {
+            DEFAULT_UDF_COLUMNS.to_vec()
+        }
+    }
+
+    /// Moves the matrix column selection by one, wrapping within `count` columns.
+    pub fn move_udf_column(&mut self, forward: bool, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let current = self.udf_column.min(count - 1);
+        self.udf_column = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+    }
+
+    /// `count` is the number of variables; one extra row holds the "Create +" button.
+    pub fn next_variable(&mut self, count: usize) {
+        select_next(&mut self.variables_table_state, count + 1);
+    }
+
+    pub fn prev_variable(&mut self, count: usize) {
+        select_prev(&mut self.variables_table_state, count + 1);
+    }
+
+    pub fn next_setting(&mut self) {
+        select_next(&mut self.settings_table_state, SETTINGS_COUNT);
+    }
+
+    pub fn prev_setting(&mut self) {
+        select_prev(&mut self.settings_table_state, SETTINGS_COUNT);
+    }
+
+    pub fn sort_devices(&mut self) {
+        let last_seen = |d: &Device| d.last_seen_at();
+        self.devices.sort_by_column(|column, a, b| match column {
+            0 => cmp_text(&a.hostname, &b.hostname),
+            1 => cmp_text(&device_type_label(a), &device_type_label(b)),
+            2 => a.online.cmp(&b.online),
+            3 => cmp_text(patch_status_label(a), patch_status_label(b)),
+            4 => last_seen(a).cmp(&last_seen(b)),
+            _ => {
+                let (a_product, a_status) = device_av(a);
+                let (b_product, b_status) = device_av(b);
+                cmp_text(a_product, b_product).then_with(|| a_status.cmp(b_status))
+            }
+        });
+    }
+
+    pub fn sort_site_alerts(&mut self) {
+        self.site_open_alerts.sort_by_column(|column, a, b| match column {
+            0 => priority_rank(a.priority.as_deref()).cmp(&priority_rank(b.priority.as_deref())),
+            1 => cmp_text(
+                a.diagnostics.as_deref().unwrap_or(""),
+                b.diagnostics.as_deref().unwrap_or(""),
+            ),
+            _ => cmp_text(alert_device_name(a), alert_device_name(b)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+    use crate::common::utils::format_duration_short;
+
+    #[test]
+    fn test_device_av() {
+        let mut device = DeviceBuilder::new("PC-1").build();
+        assert_eq!(device_av(&device), ("None", "Unknown"));
+        device.antivirus = Some(crate::api::datto::types::Antivirus {
+            antivirus_product: Some("Sophos Intercept X".to_string()),
+            antivirus_status: Some("NotRunning".to_string()),
+        });
+        assert_eq!(device_av(&device), ("Sophos Intercept X", "NotRunning"));
+    }
+
+    #[test]
+    fn test_last_seen_age() {
+        let now = Utc::now();
+        let mut device = DeviceBuilder::new("PC-1").online(false).build();
+        assert_eq!(last_seen_age(&device, now), None);
+        let seen = now - chrono::Duration::seconds(3 * 86400 + 4 * 3600 + 59);
+        device.last_seen = Some(serde_json::json!(seen.timestamp_millis()));
+        let age = last_seen_age(&device, now).unwrap();
+        assert_eq!(format_duration_short(age.num_seconds()), "3d 4h");
+        assert_eq!(format_duration_short(2 * 3600), "2h");
+        assert_eq!(format_duration_short(-5), "now");
+    }
+
+    #[test]
+    fn test_parse_udf_columns() {
+        assert_eq!(parse_udf_columns("17, 1-3 2").unwrap(), vec![17, 1, 2, 3]);
+        assert!(parse_udf_columns("0").is_err());
+        assert!(parse_udf_columns("5-31").is_err());
+        assert!(parse_udf_columns(" , ").is_err());
+    }
+}
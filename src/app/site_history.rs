@@ -0,0 +1,110 @@
+use crate::api::datto::types::Site;
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Days of history kept per site and shown in the site detail sparklines.
+pub const HISTORY_DAYS: u64 = 30;
+
+/// One site's counts on one day; the last fetch of the day wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiteSnapshot {
+    /// `YYYY-MM-DD`, so dates order as text
+    pub date: String,
+    pub devices: u64,
+    pub offline: u64,
+    /// `None` until the account's open alerts have loaded that day
+    pub alerts: Option<u64>,
+}
+
+/// A `HISTORY_DAYS` series ending today, with `None` for days without a snapshot.
+pub type Series = Vec<Option<u64>>;
+
+/// Daily snapshots of every site, saved in the cache to spot trends.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SiteHistory {
+    /// Site uid to snapshots, oldest first
+    sites: BTreeMap<String, Vec<SiteSnapshot>>,
+}
+
+impl SiteHistory {
+    /// Records today's counts of `site`. `alerts` of `None` keeps the count
+    /// already recorded today, if any. Snapshots older than the window are dropped.
+    pub fn record(&mut self, site: &Site, alerts: Option<usize>, today: NaiveDate) {
+        let status = site.devices_status.as_ref();
+        let snapshots = self.sites.entry(site.uid.clone()).or_default();
+        let start = window_start(today).to_string();
+        let today = today.to_string();
+        let earlier_alerts = match snapshots.last() {
+            Some(last) if last.date == today => snapshots.pop().and_then(|s| s.alerts),
+            _ => None,
+        };
+        snapshots.push(SiteSnapshot {
+            date: today.clone(),
+            devices: status.map_or(0, |s| s.number_of_devices.max(0) as u64),
+            offline: status.map_or(0, |s| s.number_of_offline_devices.max(0) as u64),
+            alerts: alerts.map(|a| a as u64).or(earlier_alerts),
+        });
+        snapshots.retain(|s| s.date >= start);
+    }
+
+    /// Device, offline and open alert series of `site_uid`, or `None` if
+    /// nothing was recorded for it.
+    pub fn series(&self, site_uid: &str, today: NaiveDate) -> Option<[Series; 3]> {
+        let snapshots = self.sites.get(site_uid)?;
+        let mut series: [Series; 3] = Default::default();
+        for day in window_start(today).iter_days().take(HISTORY_DAYS as usize) {
+            let day = day.to_string();
+            let snapshot = snapshots.iter().find(|s| s.date == day);
+            series[0].push(snapshot.map(|s| s.devices));
+            series[1].push(snapshot.map(|s| s.offline));
+            series[2].push(snapshot.and_then(|s| s.alerts));
+        }
+        Some(series)
+    }
+}
+
+/// First day of the window ending on `today`.
+fn window_start(today: NaiveDate) -> NaiveDate {
+    today - Days::new(HISTORY_DAYS - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(devices: i32, offline: i32) -> Site {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uid": "s1",
+            "name": "Acme",
+            "devicesStatus": {
+                "numberOfDevices": devices,
+                "numberOfOnlineDevices": devices - offline,
+                "numberOfOfflineDevices": offline,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_one_snapshot_per_day_within_the_window() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let mut history = SiteHistory::default();
+        history.record(&site(10, 1), Some(4), day(1));
+        history.record(&site(12, 2), Some(5), day(2));
+        // A later sites fetch the same day keeps the alert count
+        history.record(&site(13, 0), None, day(2));
+
+        let [devices, offline, alerts] = history.series("s1", day(2)).unwrap();
+        assert_eq!(devices.len(), HISTORY_DAYS as usize);
+        assert_eq!(devices[HISTORY_DAYS as usize - 2..], [Some(10), Some(13)]);
+        assert_eq!(offline.last(), Some(&Some(0)));
+        assert_eq!(alerts.last(), Some(&Some(5)));
+        assert_eq!(devices[0], None);
+        assert!(history.series("s2", day(2)).is_none());
+
+        history.record(&site(13, 0), None, day(31));
+        assert_eq!(history.sites["s1"].len(), 2);
+    }
+}
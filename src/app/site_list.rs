@@ -0,0 +1,566 @@
+use super::posture::{DeviceHealth, posture_score};
+use super::retry::Retry;
+use super::sort::{SortState, cmp_text};
+use super::{IncidentStats, MdrDefaults, MdrMapping};
+use super::table_view::{select_next, select_prev};
+use crate::api::datto::types::{Alert, Device, Site};
+use crate::common::site_groups::{SiteGroup, site_group};
+use crate::common::utils::fuzzy_match;
+use ratatui::widgets::TableState;
+use std::collections::{BTreeSet, HashMap};
+
+/// Column headers of the site table, in sort-column order.
+pub const SITE_COLUMNS: [&str; 7] =
+    ["Site Name", "Devices", "Active", "Resolved", "Alerts", "Posture", "UID"];
+
+/// Title of the section holding the sites no `site_groups` entry matches.
+pub const UNGROUPED_TITLE: &str = "Ungrouped";
+
+/// A collapsible section of the site list, see [`SiteGroup`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteSection {
+    pub title: String,
+    /// Indexes into `sites` of the section's sites that pass the filter
+    sites: Vec<usize>,
+    pub collapsed: bool,
+}
+
+/// A row of the site table.
+#[derive(Debug, Clone, Copy)]
+pub enum SiteRow<'a> {
+    Section(&'a SiteSection),
+    Site(&'a Site),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RowRef {
+    Site(usize),
+    Section(usize),
+}
+
+/// What the selection is on, to find it again after the rows change.
+#[derive(Debug, Clone, PartialEq)]
+enum RowKey {
+    Site(String),
+    Section(String),
+}
+
+/// State of the site list (the home view).
+#[derive(Debug, Default)]
+pub struct SiteListState {
+    pub sites: Vec<Site>,
+    pub is_loading: bool,
+    /// Reloads after a failed fetch
+    pub retry: Retry,
+    /// Selection within the visible rows (see `rows`)
+    pub table_state: TableState,
+    pub current_page: i32,
+    pub total_pages: i32,
+    pub total_count: i32,
+    /// Set while the list shows cached data that hasn't been refreshed yet
+    pub sites_cached_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub sort: SortState,
+    /// Fuzzy name filter typed after `/`
+    pub filter_query: String,
+    pub is_filtering: bool,
+    /// Hide sites without devices, such as "Deleted Devices" and "Managed"
+    pub hide_empty: bool,
+    /// Uids of sites pinned to the top of the list, persisted in the cache
+    pub pinned: Vec<String>,
+    /// Also list the sites of the other accounts in `merged_profiles`
+    pub show_merged: bool,
+    /// Sections from the config file; without any the list is flat
+    pub groups: Vec<SiteGroup>,
+    /// Titles of the collapsed sections, persisted in the cache
+    pub collapsed: BTreeSet<String>,
+    /// Open Datto RMM alerts by site uid, once the account's alerts are loaded
+    pub open_alerts: Option<HashMap<String, usize>>,
+    /// Open critical alerts by site uid, counted along with `open_alerts`
+    pub critical_alerts: HashMap<String, usize>,
+    /// Antivirus and patch counts by site uid, once the account devices
+    /// (shared with the reports) are loaded
+    pub device_health: Option<HashMap<String, DeviceHealth>>,
+    /// Rows in display order: pinned sites, then each section's header and
+    /// (unless collapsed) its sites
+    rows: Vec<RowRef>,
+    sections: Vec<SiteSection>,
+    /// How many of the leading `rows` are pinned sites
+    pinned_count: usize,
+    /// Sites passing the filter, including those in collapsed sections
+    matched_count: usize,
+}
+
+/// Key into `SecurityState::incident_stats` for a site: its `tuiMdrId`
+/// (its own or the account default) if set, otherwise the lowercased site name.
+pub fn incident_key(site: &Site, defaults: &MdrDefaults) -> String {
+    MdrMapping::resolve(site, defaults)
+        .id
+        .map(|id| id.value)
+        .unwrap_or_else(|| site.name.to_lowercase())
+}
+
+fn device_count(site: &Site) -> i32 {
+    site.devices_status
+        .as_ref()
+        .map(|s| s.number_of_devices)
+        .unwrap_or(0)
+}
+
+fn alert_count(open_alerts: &Option<HashMap<String, usize>>, site: &Site) -> usize {
+    open_alerts
+        .as_ref()
+        .and_then(|counts| counts.get(&site.uid))
+        .copied()
+        .unwrap_or(0)
+}
+
+fn is_critical(alert: &Alert) -> bool {
+    alert.priority.as_deref().is_some_and(|p| p.eq_ignore_ascii_case("critical"))
+}
+
+/// The site's posture score (see `posture_score`), once the account devices
+/// are loaded.
+fn posture(
+    device_health: &Option<HashMap<String, DeviceHealth>>,
+    critical_alerts: &HashMap<String, usize>,
+    site: &Site,
+    incidents: &IncidentStats,
+) -> Option<u8> {
+    let health = device_health.as_ref()?.get(&site.uid).copied().unwrap_or_default();
+    let critical = critical_alerts.get(&site.uid).copied().unwrap_or(0);
+    posture_score(health, critical, incidents.active.max(0) as usize)
+}
+
+impl SiteListState {
+    /// Rows of the site table: sites passing the filter in sort order, under
+    /// their section headers when `groups` are configured.
+    pub fn rows(&self) -> impl Iterator<Item = SiteRow<'_>> {
+        self.rows.iter().filter_map(|row| match *row {
+            RowRef::Site(i) => self.sites.get(i).map(SiteRow::Site),
+            RowRef::Section(i) => self.sections.get(i).map(SiteRow::Section),
+        })
+    }
+
+    /// Sites shown in the table, leaving out section headers.
+    pub fn visible_sites(&self) -> impl Iterator<Item = &Site> {
+        self.rows().filter_map(|row| match row {
+            SiteRow::Site(site) => Some(site),
+            SiteRow::Section(_) => None,
+        })
+    }
+
+    /// Sites passing the filter, counting those in collapsed sections.
+    pub fn visible_count(&self) -> usize {
+        self.matched_count
+    }
+
+    /// Every site of `section` that passes the filter, collapsed or not.
+    pub fn section_sites<'a>(&'a self, section: &'a SiteSection) -> impl Iterator<Item = &'a Site> {
+        section.sites.iter().filter_map(|&i| self.sites.get(i))
+    }
+
+    /// Open alerts of the site, or `None` until the account's alerts are loaded
+    /// (and for sites of other accounts).
+    pub fn open_alert_count(&self, site: &Site) -> Option<usize> {
+        if site.account.is_some() {
+            return None;
+        }
+        self.open_alerts.as_ref().map(|_| alert_count(&self.open_alerts, site))
+    }
+
+    /// Sites of the active account, leaving out merged sites of other accounts.
+    pub fn own_sites(&self) -> impl Iterator<Item = &Site> {
+        self.sites.iter().filter(|s| s.account.is_none())
+    }
+
+    /// Counts the account's open alerts per site.
+    pub fn set_open_alerts(&mut self, alerts: &[Alert]) {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        self.critical_alerts.clear();
+        for alert in alerts {
+            let Some(uid) = alert.alert_source_info.as_ref().and_then(|s| s.site_uid.clone())
+            else {
+                continue;
+            };
+            if is_critical(alert) {
+                *self.critical_alerts.entry(uid.clone()).or_default() += 1;
+            }
+            *counts.entry(uid).or_default() += 1;
+        }
+        self.open_alerts = Some(counts);
+    }
+
+    /// Updates the counts of one site from its freshly fetched open alerts.
+    pub fn set_site_open_alerts(&mut self, site_uid: &str, alerts: &[Alert]) {
+        if let Some(counts) = &mut self.open_alerts {
+            counts.insert(site_uid.to_string(), alerts.len());
+            let critical = alerts.iter().filter(|a| is_critical(a)).count();
+            self.critical_alerts.insert(site_uid.to_string(), critical);
+        }
+    }
+
+    /// Recounts `device_health` from the account devices.
+    pub fn set_account_devices(&mut self, devices: &[Device]) {
+        self.device_health = Some(DeviceHealth::by_site(devices));
+    }
+
+    /// The site's security posture from 0 to 100, once the account devices
+    /// are loaded; None for sites without computers.
+    pub fn posture(&self, site: &Site, incidents: &IncidentStats) -> Option<u8> {
+        posture(&self.device_health, &self.critical_alerts, site, incidents)
+    }
+
+    /// Number of rows in the "Pinned" section at the top of the table.
+    pub fn pinned_visible_count(&self) -> usize {
+        self.pinned_count
+    }
+
+    pub fn is_pinned(&self, uid: &str) -> bool {
+        self.pinned.iter().any(|p| p == uid)
+    }
+
+    /// Pins the selected site, or unpins it if already pinned. Returns the
+    /// site's new pinned state, or `None` with nothing selected.
+    pub fn toggle_pin_selected(&mut self) -> Option<bool> {
+        let uid = self.selected_site()?.uid.clone();
+        let pinned = if self.is_pinned(&uid) {
+            self.pinned.retain(|p| *p != uid);
+            false
+        } else {
+            self.pinned.push(uid);
+            true
+        };
+        self.refresh_visible();
+        Some(pinned)
+    }
+
+    pub fn is_filter_active(&self) -> bool {
+        self.hide_empty || !self.filter_query.is_empty()
+    }
+
+    pub fn selected_site(&self) -> Option<&Site> {
+        match self.table_state.selected().and_then(|i| self.rows.get(i))? {
+            RowRef::Site(i) => self.sites.get(*i),
+            RowRef::Section(_) => None,
+        }
+    }
+
+    pub fn selected_section(&self) -> Option<&SiteSection> {
+        match self.table_state.selected().and_then(|i| self.rows.get(i))? {
+            RowRef::Section(i) => self.sections.get(*i),
+            RowRef::Site(_) => None,
+        }
+    }
+
+    /// Collapses or expands the section whose header is selected. Returns false
+    /// when a site is selected instead.
+    pub fn toggle_selected_section(&mut self) -> bool {
+        let Some(title) = self.selected_section().map(|s| s.title.clone()) else {
+            return false;
+        };
+        if !self.collapsed.remove(&title) {
+            self.collapsed.insert(title);
+        }
+        self.refresh_visible();
+        true
+    }
+
+    pub fn next_row(&mut self) {
+        select_next(&mut self.table_state, self.rows.len());
+    }
+
+    pub fn previous_row(&mut self) {
+        select_prev(&mut self.table_state, self.rows.len());
+    }
+
+    fn row_key(&self, row: RowRef) -> Option<RowKey> {
+        match row {
+            RowRef::Site(i) => self.sites.get(i).map(|s| RowKey::Site(s.uid.clone())),
+            RowRef::Section(i) => self.sections.get(i).map(|s| RowKey::Section(s.title.clone())),
+        }
+    }
+
+    fn selected_key(&self) -> Option<RowKey> {
+        let row = *self.table_state.selected().and_then(|i| self.rows.get(i))?;
+        self.row_key(row)
+    }
+
+    /// Replaces the active account's sites, keeping the selected site if it's
+    /// still there and otherwise selecting the first visible row. Callers re-sort
+    /// afterwards since the incident columns need `incident_stats`.
+    pub fn set_sites(&mut self, sites: Vec<Site>) {
+        let selected = self.selected_key();
+        // Sites of other accounts come from their own fetches
+        let merged: Vec<Site> = self.sites.drain(..).filter(|s| s.account.is_some()).collect();
+        self.sites = sites;
+        self.sites.extend(merged);
+        self.refresh_visible_keeping(selected);
+    }
+
+    /// Replaces the merged sites of the account with profile `account`.
+    /// Callers re-sort afterwards, as with `set_sites`.
+    pub fn set_account_sites(&mut self, account: &str, sites: Vec<Site>) {
+        let selected = self.selected_key();
+        self.sites.retain(|s| s.account.as_deref() != Some(account));
+        self.sites.extend(sites.into_iter().map(|mut site| {
+            site.account = Some(account.to_string());
+            site
+        }));
+        self.refresh_visible_keeping(selected);
+    }
+
+    /// Adds a site that isn't part of the loaded page (e.g. opened from search).
+    pub fn push_site(&mut self, site: Site) {
+        let uid = site.uid.clone();
+        self.sites.push(site);
+        self.refresh_visible();
+        self.select_site(&uid);
+    }
+
+    /// Drops a deleted site and its pin. Returns whether the pin list changed.
+    pub fn remove_site(&mut self, uid: &str) -> bool {
+        let pins = self.pinned.len();
+        self.sites.retain(|s| s.uid != uid);
+        self.pinned.retain(|p| p != uid);
+        self.refresh_visible();
+        self.pinned.len() != pins
+    }
+
+    /// Selects the site with `uid`, clearing the filter if it hides that site
+    /// and expanding its section. Returns false if the site isn't loaded.
+    pub fn select_site(&mut self, uid: &str) -> bool {
+        let Some(index) = self.sites.iter().position(|s| s.uid == uid) else {
+            return false;
+        };
+        if !self.rows.contains(&RowRef::Site(index)) {
+            if let Some(section) = self.sections.iter().find(|s| s.sites.contains(&index)) {
+                self.collapsed.remove(&section.title);
+            } else {
+                self.filter_query.clear();
+                self.hide_empty = false;
+                self.show_merged |= self.sites[index].account.is_some();
+            }
+            self.refresh_visible();
+        }
+        let position = self.rows.iter().position(|&row| row == RowRef::Site(index));
+        self.table_state.select(position);
+        true
+    }
+
+    /// Recomputes the visible rows after the filter changed.
+    pub fn refresh_visible(&mut self) {
+        let selected = self.selected_key();
+        self.refresh_visible_keeping(selected);
+    }
+
+    /// Recomputes the visible rows, keeping `selected` highlighted if it still
+    /// passes the filter and falling back to the first row.
+    fn refresh_visible_keeping(&mut self, selected: Option<RowKey>) {
+        let (pinned, rest): (Vec<usize>, Vec<usize>) = self
+            .sites
+            .iter()
+            .enumerate()
+            .filter(|(_, site)| self.show_merged || site.account.is_none())
+            .filter(|(_, site)| !self.hide_empty || device_count(site) > 0)
+            .filter(|(_, site)| fuzzy_match(&self.filter_query, &site.name))
+            .map(|(i, _)| i)
+            .partition(|&i| self.is_pinned(&self.sites[i].uid));
+        self.pinned_count = pinned.len();
+        self.matched_count = pinned.len() + rest.len();
+        self.rows = pinned.into_iter().map(RowRef::Site).collect();
+        self.sections.clear();
+        if self.groups.is_empty() {
+            self.rows.extend(rest.into_iter().map(RowRef::Site));
+        } else {
+            self.build_sections(rest);
+        }
+        let position = selected.and_then(|key| {
+            self.rows
+                .iter()
+                .position(|&row| self.row_key(row).as_ref() == Some(&key))
+        });
+        self.table_state.select(match position {
+            Some(p) => Some(p),
+            None if self.rows.is_empty() => None,
+            None => Some(0),
+        });
+    }
+
+    /// Puts the unpinned sites `rest` under section headers: sections in
+    /// `groups` order (by title within a variable's sections), then the ungrouped.
+    fn build_sections(&mut self, rest: Vec<usize>) {
+        let mut sections: Vec<(usize, String, Vec<usize>)> = Vec::new();
+        for i in rest {
+            let (order, title) = site_group(&self.groups, &self.sites[i])
+                .unwrap_or((self.groups.len(), UNGROUPED_TITLE.to_string()));
+            match sections.iter_mut().find(|(_, t, _)| *t == title) {
+                Some((_, _, sites)) => sites.push(i),
+                None => sections.push((order, title, vec![i])),
+            }
+        }
+        sections.sort_by_cached_key(|(order, title, _)| (*order, title.to_lowercase()));
+
+        // A typed filter shows its matches even in collapsed sections
+        let expand_all = !self.filter_query.is_empty();
+        for (_, title, sites) in sections {
+            let collapsed = !expand_all && self.collapsed.contains(&title);
+            self.rows.push(RowRef::Section(self.sections.len()));
+            if !collapsed {
+                self.rows.extend(sites.iter().map(|&i| RowRef::Site(i)));
+            }
+            self.sections.push(SiteSection {
+                title,
+                sites,
+                collapsed,
+            });
+        }
+    }
+
+    /// Re-applies the active sort; incident columns read from `stats`.
+    pub fn sort_sites(&mut self, stats: &HashMap<String, IncidentStats>, defaults: &MdrDefaults) {
+        let selected = self.selected_key();
+        let incidents =
+            |site: &Site| stats.get(&incident_key(site, defaults)).cloned().unwrap_or_default();
+        let open_alerts = &self.open_alerts;
+        // Sites without a score sort after the worst one
+        let score = |site: &Site| {
+            posture(&self.device_health, &self.critical_alerts, site, &incidents(site))
+                .map_or(u16::MAX, u16::from)
+        };
+        self.sort
+            .apply(&mut self.sites, &mut TableState::default(), |column, a, b| match column {
+                0 => cmp_text(&a.name, &b.name),
+                1 => device_count(a).cmp(&device_count(b)),
+                2 => incidents(a).active.cmp(&incidents(b).active),
+                3 => incidents(a).resolved.cmp(&incidents(b).resolved),
+                4 => alert_count(open_alerts, a).cmp(&alert_count(open_alerts, b)),
+                5 => score(a).cmp(&score(b)),
+                _ => a.uid.cmp(&b.uid),
+            });
+        self.refresh_visible_keeping(selected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(uid: &str, name: &str, devices: i32) -> Site {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uid": uid,
+            "name": name,
+            "devicesStatus": {
+                "numberOfDevices": devices,
+                "numberOfOnlineDevices": 0,
+                "numberOfOfflineDevices": devices,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_filter_and_hide_empty() {
+        let mut state = SiteListState::default();
+        state.set_sites(vec![
+            site("a", "Acme Corp", 4),
+            site("d", "Deleted Devices", 0),
+            site("g", "Globex", 2),
+        ]);
+        assert_eq!(state.visible_count(), 3);
+
+        state.filter_query = "acp".to_string();
+        state.refresh_visible();
+        assert_eq!(state.selected_site().map(|s| s.uid.as_str()), Some("a"));
+        assert_eq!(state.visible_count(), 1);
+
+        state.filter_query.clear();
+        state.hide_empty = true;
+        state.refresh_visible();
+        assert!(state.visible_sites().all(|s| s.uid != "d"));
+
+        // Selecting a hidden site clears the filter rather than failing
+        assert!(state.select_site("d"));
+        assert!(!state.hide_empty);
+        assert_eq!(state.selected_site().map(|s| s.uid.as_str()), Some("d"));
+    }
+
+    #[test]
+    fn test_pinned_sites_come_first() {
+        let mut state = SiteListState::default();
+        state.set_sites(vec![site("a", "Acme Corp", 4), site("g", "Globex", 2)]);
+        state.select_site("g");
+        assert_eq!(state.toggle_pin_selected(), Some(true));
+
+        let order: Vec<_> = state.visible_sites().map(|s| s.uid.as_str()).collect();
+        assert_eq!(order, vec!["g", "a"]);
+        assert_eq!(state.pinned_visible_count(), 1);
+        // The selection follows the site to its pinned row
+        assert_eq!(state.table_state.selected(), Some(0));
+
+        assert_eq!(state.toggle_pin_selected(), Some(false));
+        assert_eq!(state.pinned_visible_count(), 0);
+        assert_eq!(state.table_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_merged_sites_survive_a_refresh_of_the_active_account() {
+        let mut state = SiteListState::default();
+        state.set_sites(vec![site("a", "Acme Corp", 4)]);
+        state.set_account_sites("eu", vec![site("e", "Euro GmbH", 2)]);
+        // Hidden until the merged list is turned on
+        assert_eq!(state.visible_count(), 1);
+
+        state.show_merged = true;
+        state.set_sites(vec![site("a", "Acme Corp", 5), site("g", "Globex", 1)]);
+        assert_eq!(state.visible_count(), 3);
+        assert_eq!(state.own_sites().count(), 2);
+        let merged = state.sites.iter().find(|s| s.uid == "e").unwrap();
+        assert_eq!(merged.account.as_deref(), Some("eu"));
+        assert_eq!(state.open_alert_count(merged), None);
+    }
+
+    #[test]
+    fn test_groups_are_collapsible_sections() {
+        use crate::common::site_groups::SiteGroupFields;
+
+        let mut state = SiteListState::default();
+        state.groups = vec![
+            SiteGroup::try_from(SiteGroupFields {
+                name: Some("Healthcare".to_string()),
+                pattern: Some("clinic".to_string()),
+                variable: None,
+            })
+            .unwrap(),
+        ];
+        state.set_sites(vec![
+            site("a", "Acme Corp", 4),
+            site("n", "Northside Clinic", 2),
+            site("s", "Southside Clinic", 3),
+        ]);
+        let titles = |state: &SiteListState| -> Vec<String> {
+            state
+                .rows()
+                .map(|row| match row {
+                    SiteRow::Section(s) => format!("[{}]", s.title),
+                    SiteRow::Site(s) => s.uid.clone(),
+                })
+                .collect()
+        };
+        assert_eq!(titles(&state), vec!["[Healthcare]", "n", "s", "[Ungrouped]", "a"]);
+        assert_eq!(state.selected_section().map(|s| s.title.as_str()), Some("Healthcare"));
+        let healthcare = state.selected_section().unwrap();
+        let devices: i32 = state.section_sites(healthcare).map(device_count).sum();
+        assert_eq!(devices, 5);
+
+        assert!(state.toggle_selected_section());
+        assert_eq!(titles(&state), vec!["[Healthcare]", "[Ungrouped]", "a"]);
+        assert_eq!(state.visible_count(), 3);
+
+        // Opening a site of a collapsed section expands it
+        assert!(state.select_site("s"));
+        assert!(state.collapsed.is_empty());
+        assert_eq!(state.table_state.selected(), Some(2));
+        assert!(!state.toggle_selected_section());
+    }
+}
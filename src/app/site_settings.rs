@@ -0,0 +1,293 @@
+use crate::api::datto::types::{ProxySettings, Site, UpdateSiteRequest};
+
+/// A row of the site Settings tab.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum SiteEditField {
+    #[default]
+    Name,
+    Description,
+    Notes,
+    OnDemand,
+    SplashtopAutoInstall,
+    AutotaskCompanyId,
+    AutotaskCompanyName,
+    ProxyType,
+    ProxyHost,
+    ProxyPort,
+    ProxyUsername,
+    ProxyPassword,
+}
+
+/// How a setting is edited.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SettingKind {
+    /// Typed in the input modal
+    Text,
+    /// Typed in the input modal, digits only
+    Number,
+    /// Typed in the input modal and shown masked
+    Secret,
+    /// Switched with Space/Enter
+    Toggle,
+    /// Stepped through its choices with Space/Enter
+    Choice,
+}
+
+impl SiteEditField {
+    /// Rows of the Settings tab, in order.
+    pub const ALL: [SiteEditField; 12] = [
+        SiteEditField::Name,
+        SiteEditField::Description,
+        SiteEditField::Notes,
+        SiteEditField::OnDemand,
+        SiteEditField::SplashtopAutoInstall,
+        SiteEditField::AutotaskCompanyId,
+        SiteEditField::AutotaskCompanyName,
+        SiteEditField::ProxyType,
+        SiteEditField::ProxyHost,
+        SiteEditField::ProxyPort,
+        SiteEditField::ProxyUsername,
+        SiteEditField::ProxyPassword,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SiteEditField::Name => "Name",
+            SiteEditField::Description => "Description",
+            SiteEditField::Notes => "Notes",
+            SiteEditField::OnDemand => "On Demand",
+            SiteEditField::SplashtopAutoInstall => "Splashtop Auto-Install",
+            SiteEditField::AutotaskCompanyId => "Autotask Company ID",
+            SiteEditField::AutotaskCompanyName => "Autotask Company Name",
+            SiteEditField::ProxyType => "Proxy Type",
+            SiteEditField::ProxyHost => "Proxy Host",
+            SiteEditField::ProxyPort => "Proxy Port",
+            SiteEditField::ProxyUsername => "Proxy Username",
+            SiteEditField::ProxyPassword => "Proxy Password",
+        }
+    }
+
+    pub fn kind(self) -> SettingKind {
+        match self {
+            SiteEditField::OnDemand | SiteEditField::SplashtopAutoInstall => SettingKind::Toggle,
+            SiteEditField::ProxyType => SettingKind::Choice,
+            SiteEditField::ProxyPort => SettingKind::Number,
+            SiteEditField::ProxyPassword => SettingKind::Secret,
+            _ => SettingKind::Text,
+        }
+    }
+}
+
+/// Proxy the site's agents connect through.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum ProxyType {
+    #[default]
+    None,
+    Http,
+    Socks4,
+    Socks5,
+}
+
+impl ProxyType {
+    const ALL: [ProxyType; 4] = [ProxyType::None, ProxyType::Http, ProxyType::Socks4, ProxyType::Socks5];
+
+    /// Reads the `type` of the API's proxy settings; types it doesn't know
+    /// are taken as HTTP.
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "" => ProxyType::None,
+            "socks4" => ProxyType::Socks4,
+            "socks5" => ProxyType::Socks5,
+            _ => ProxyType::Http,
+        }
+    }
+
+    /// The `type` sent to the API; `None` for no proxy.
+    pub fn api_name(self) -> Option<&'static str> {
+        match self {
+            ProxyType::None => None,
+            ProxyType::Http => Some("http"),
+            ProxyType::Socks4 => Some("socks4"),
+            ProxyType::Socks5 => Some("socks5"),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProxyType::None => "None",
+            ProxyType::Http => "HTTP",
+            ProxyType::Socks4 => "SOCKS4",
+            ProxyType::Socks5 => "SOCKS5",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// The values of the Settings tab, edited before they're saved to the site.
+#[derive(Debug, Default)]
+pub struct SiteEditState {
+    pub name: String,
+    pub description: String,
+    pub notes: String,
+    pub on_demand: bool,
+    pub splashtop_auto_install: bool,
+    pub autotask_company_id: String,
+    pub autotask_company_name: String,
+    pub proxy_type: ProxyType,
+    pub proxy_host: String,
+    pub proxy_port: String,
+    pub proxy_username: String,
+    pub proxy_password: String,
+    pub active_field: SiteEditField,
+    pub is_editing: bool, // Track if we are in "edit mode" for settings (or just viewing) - simplification: settings is always editable input fields
+}
+
+impl SiteEditState {
+    pub fn from_site(site: &Site) -> Self {
+        let proxy = site.proxy_settings.clone().unwrap_or_default();
+        let text = |value: Option<String>| value.unwrap_or_default();
+        Self {
+            name: site.name.clone(),
+            description: text(site.description.clone()),
+            notes: text(site.notes.clone()),
+            on_demand: site.on_demand.unwrap_or(false),
+            splashtop_auto_install: site.splashtop_auto_install.unwrap_or(false),
+            autotask_company_id: text(site.autotask_company_id.clone()),
+            autotask_company_name: text(site.autotask_company_name.clone()),
+            proxy_type: ProxyType::parse(proxy.type_field.as_deref().unwrap_or_default()),
+            proxy_host: text(proxy.host),
+            proxy_port: proxy.port.map(|port| port.to_string()).unwrap_or_default(),
+            proxy_username: text(proxy.username),
+            proxy_password: text(proxy.password),
+            active_field: SiteEditField::Name,
+            is_editing: true,
+        }
+    }
+
+    /// The value of a setting typed in the input modal; `None` for toggles
+    /// and choices.
+    pub fn text_mut(&mut self, field: SiteEditField) -> Option<&mut String> {
+        match field {
+            SiteEditField::Name => Some(&mut self.name),
+            SiteEditField::Description => Some(&mut self.description),
+            SiteEditField::Notes => Some(&mut self.notes),
+            SiteEditField::AutotaskCompanyId => Some(&mut self.autotask_company_id),
+            SiteEditField::AutotaskCompanyName => Some(&mut self.autotask_company_name),
+            SiteEditField::ProxyHost => Some(&mut self.proxy_host),
+            SiteEditField::ProxyPort => Some(&mut self.proxy_port),
+            SiteEditField::ProxyUsername => Some(&mut self.proxy_username),
+            SiteEditField::ProxyPassword => Some(&mut self.proxy_password),
+            SiteEditField::OnDemand
+            | SiteEditField::SplashtopAutoInstall
+            | SiteEditField::ProxyType => None,
+        }
+    }
+
+    /// Switches a toggle or steps a choice to its next value.
+    pub fn toggle(&mut self, field: SiteEditField) {
+        match field {
+            SiteEditField::OnDemand => self.on_demand = !self.on_demand,
+            SiteEditField::SplashtopAutoInstall => {
+                self.splashtop_auto_install = !self.splashtop_auto_install
+            }
+            SiteEditField::ProxyType => self.proxy_type = self.proxy_type.next(),
+            _ => {}
+        }
+    }
+
+    /// A setting as shown in the Settings tab; the password is masked.
+    pub fn display(&self, field: SiteEditField) -> String {
+        let flag = |on: bool| if on { "[x] Enabled" } else { "[ ] Disabled" }.to_string();
+        match field {
+            SiteEditField::OnDemand => flag(self.on_demand),
+            SiteEditField::SplashtopAutoInstall => flag(self.splashtop_auto_install),
+            SiteEditField::ProxyType => self.proxy_type.name().to_string(),
+            SiteEditField::ProxyPassword => "•".repeat(self.proxy_password.chars().count()),
+            SiteEditField::Name => self.name.clone(),
+            SiteEditField::Description => self.description.clone(),
+            SiteEditField::Notes => self.notes.clone(),
+            SiteEditField::AutotaskCompanyId => self.autotask_company_id.clone(),
+            SiteEditField::AutotaskCompanyName => self.autotask_company_name.clone(),
+            SiteEditField::ProxyHost => self.proxy_host.clone(),
+            SiteEditField::ProxyPort => self.proxy_port.clone(),
+            SiteEditField::ProxyUsername => self.proxy_username.clone(),
+        }
+    }
+
+    /// The site update for every setting but the proxy, which has its own
+    /// endpoint.
+    pub fn update_request(&self) -> UpdateSiteRequest {
+        UpdateSiteRequest {
+            name: self.name.clone(),
+            description: Some(self.description.clone()),
+            notes: Some(self.notes.clone()),
+            on_demand: Some(self.on_demand),
+            splashtop_auto_install: Some(self.splashtop_auto_install),
+            autotask_company_id: Some(self.autotask_company_id.trim().to_string()),
+            autotask_company_name: Some(self.autotask_company_name.trim().to_string()),
+        }
+    }
+
+    /// The proxy settings to save; `None` removes the proxy. Errors when the
+    /// proxy has no host or an invalid port.
+    pub fn proxy(&self) -> Result<Option<ProxySettings>, String> {
+        let Some(kind) = self.proxy_type.api_name() else {
+            return Ok(None);
+        };
+        let text = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        let host = text(&self.proxy_host).ok_or("The proxy needs a host")?;
+        let port = match text(&self.proxy_port) {
+            None => None,
+            Some(port) => match port.parse::<u16>() {
+                Ok(number) if number > 0 => Some(i32::from(number)),
+                _ => return Err(format!("'{}' is not a port (1-65535)", port)),
+            },
+        };
+        Ok(Some(ProxySettings {
+            host: Some(host),
+            password: text(&self.proxy_password),
+            port,
+            type_field: Some(kind.to_string()),
+            username: text(&self.proxy_username),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::proxy_changes;
+
+    #[test]
+    fn test_proxy_settings_round_trip_and_validation() {
+        let site: Site = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uid": "s1",
+            "name": "Acme",
+            "proxySettings": { "type": "socks5", "host": "proxy.acme", "port": 1080 },
+        }))
+        .unwrap();
+        let mut state = SiteEditState::from_site(&site);
+        assert_eq!(state.proxy_type, ProxyType::Socks5);
+        let proxy = state.proxy().unwrap();
+        assert!(proxy_changes(&site, &proxy).is_empty());
+
+        state.proxy_port = "70000".to_string();
+        assert!(state.proxy().is_err());
+        state.proxy_port.clear();
+        state.proxy_host = " ".to_string();
+        assert_eq!(state.proxy(), Err("The proxy needs a host".to_string()));
+
+        // Stepping past SOCKS5 removes the proxy, whatever the other fields hold
+        state.toggle(SiteEditField::ProxyType);
+        assert_eq!(state.proxy(), Ok(None));
+        let changes = proxy_changes(&site, &None);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new, "None");
+        assert_eq!(changes[0].old, "SOCKS5 proxy.acme:1080");
+    }
+}
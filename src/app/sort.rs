@@ -0,0 +1,108 @@
+use ratatui::widgets::TableState;
+use std::cmp::Ordering;
+
+/// Active sort of a table. `column` indexes the table's header labels; `None`
+/// keeps the order the API returned.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SortState {
+    pub column: Option<usize>,
+    pub descending: bool,
+}
+
+impl SortState {
+    /// Moves to the next of `columns` columns (wrapping), ascending.
+    pub fn cycle(&mut self, columns: usize) {
+        self.column = match self.column {
+            Some(c) if c + 1 < columns => Some(c + 1),
+            _ if columns == 0 => None,
+            _ => Some(0),
+        };
+        self.descending = false;
+    }
+
+    /// Flips the direction of the active column. Does nothing while unsorted.
+    pub fn reverse(&mut self) {
+        if self.column.is_some() {
+            self.descending = !self.descending;
+        }
+    }
+
+    /// Header labels with an arrow on the active column.
+    pub fn header(&self, labels: &[&str]) -> Vec<String> {
+        labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| match self.column {
+                Some(c) if c == i && self.descending => format!("{} ▼", label),
+                Some(c) if c == i => format!("{} ▲", label),
+                _ => label.to_string(),
+            })
+            .collect()
+    }
+
+    /// Sorts `items` by the active column using `cmp(column, a, b)`, keeping the
+    /// selected row on the same item. Stable, so ties keep their previous order.
+    pub fn apply<T>(
+        &self,
+        items: &mut Vec<T>,
+        table_state: &mut TableState,
+        cmp: impl Fn(usize, &T, &T) -> Ordering,
+    ) {
+        let Some(column) = self.column else {
+            return;
+        };
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.sort_by(|&a, &b| {
+            let ord = cmp(column, &items[a], &items[b]);
+            if self.descending { ord.reverse() } else { ord }
+        });
+
+        if let Some(selected) = table_state.selected() {
+            table_state.select(order.iter().position(|&i| i == selected));
+        }
+
+        let mut taken: Vec<Option<T>> = items.drain(..).map(Some).collect();
+        items.extend(order.into_iter().filter_map(|i| taken[i].take()));
+    }
+}
+
+/// Case-insensitive string comparison for text columns.
+pub fn cmp_text(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Orders alert priorities by severity (Information < Low < Moderate < High < Critical).
+pub fn priority_rank(priority: Option<&str>) -> u8 {
+    match priority.map(|p| p.to_lowercase()).as_deref() {
+        Some("critical") => 5,
+        Some("high") => 4,
+        Some("moderate") | Some("medium") => 3,
+        Some("low") => 2,
+        Some("information") => 1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_keeps_selection_on_item() {
+        let mut items = vec![3, 1, 2];
+        let mut table_state = TableState::default();
+        table_state.select(Some(0));
+
+        let mut sort = SortState::default();
+        sort.cycle(1);
+        sort.apply(&mut items, &mut table_state, |_, a, b| a.cmp(b));
+        assert_eq!(items, vec![1, 2, 3]);
+        assert_eq!(table_state.selected(), Some(2));
+
+        sort.reverse();
+        sort.apply(&mut items, &mut table_state, |_, a, b| a.cmp(b));
+        assert_eq!(items, vec![3, 2, 1]);
+        assert_eq!(table_state.selected(), Some(0));
+        assert_eq!(sort.header(&["N"]), vec!["N ▼"]);
+    }
+}
@@ -0,0 +1,278 @@
+use super::sort::SortState;
+use ratatui::widgets::TableState;
+use std::cmp::Ordering;
+
+/// Moves a table selection down one row, wrapping to the top.
+pub fn select_next(state: &mut TableState, len: usize) {
+    let i = match state.selected() {
+        Some(i) if i + 1 < len => i + 1,
+        _ => 0,
+    };
+    state.select(Some(i));
+}
+
+/// Moves a table selection up one row, wrapping to the bottom.
+pub fn select_prev(state: &mut TableState, len: usize) {
+    let i = match state.selected() {
+        Some(0) => len.saturating_sub(1),
+        Some(i) => i - 1,
+        None => 0,
+    };
+    state.select(Some(i));
+}
+
+/// The rows of a table with its selection, sort and filter, e.g. a device's
+/// open alerts. All rows are kept in sorted order; the filter picks the ones
+/// shown, and the selection is a position among those.
+#[derive(Debug, Clone)]
+pub struct TableView<T> {
+    items: Vec<T>,
+    /// Indices into `items` of the shown rows, ascending
+    visible: Vec<usize>,
+    pub state: TableState,
+    pub sort: SortState,
+}
+
+impl<T> Default for TableView<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            visible: Vec::new(),
+            state: TableState::default(),
+            sort: SortState::default(),
+        }
+    }
+}
+
+impl<T> TableView<T> {
+    /// Replaces the rows, showing all of them with the first one selected.
+    /// The caller re-applies its sort and filter.
+    pub fn set_items(&mut self, items: Vec<T>) {
+        self.visible = (0..items.len()).collect();
+        self.items = items;
+        self.select_first();
+    }
+
+    pub fn clear(&mut self) {
+        self.set_items(Vec::new());
+    }
+
+    /// All rows, shown or not.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// All rows, to change in place. The filter isn't re-applied.
+    pub fn items_mut(&mut self) -> &mut [T] {
+        &mut self.items
+    }
+
+    /// The rows passing the filter, in table order.
+    pub fn rows(&self) -> impl Iterator<Item = &T> {
+        self.visible.iter().map(|&i| &self.items[i])
+    }
+
+    /// Number of rows shown.
+    pub fn len(&self) -> usize {
+        self.visible.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visible.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.selected_item().map(|i| &self.items[i])
+    }
+
+    /// Index into `items` of the selected row.
+    fn selected_item(&self) -> Option<usize> {
+        self.state.selected().and_then(|i| self.visible.get(i)).copied()
+    }
+
+    pub fn next(&mut self) {
+        select_next(&mut self.state, self.visible.len());
+    }
+
+    pub fn prev(&mut self) {
+        select_prev(&mut self.state, self.visible.len());
+    }
+
+    /// Selects the first row shown, if any.
+    pub fn select_first(&mut self) {
+        self.state.select(if self.visible.is_empty() { None } else { Some(0) });
+    }
+
+    /// Selects the row `item` (an index into `items`) if it's shown, else the
+    /// first row.
+    fn reselect(&mut self, item: Option<usize>) {
+        match item.and_then(|item| self.visible.iter().position(|&i| i == item)) {
+            Some(position) => self.state.select(Some(position)),
+            None => self.select_first(),
+        }
+    }
+
+    /// Shows only the rows `keep` accepts, keeping the selected one if it is
+    /// still shown.
+    pub fn filter(&mut self, keep: impl Fn(&T) -> bool) {
+        let selected = self.selected_item();
+        self.visible = (0..self.items.len()).filter(|&i| keep(&self.items[i])).collect();
+        self.reselect(selected);
+    }
+
+    /// Sorts by the active `sort` column using `cmp(column, a, b)`, see
+    /// `SortState::apply`. Does nothing while unsorted.
+    pub fn sort_by_column(&mut self, cmp: impl Fn(usize, &T, &T) -> Ordering) {
+        let Some(column) = self.sort.column else {
+            return;
+        };
+        let descending = self.sort.descending;
+        self.sort_by(|a, b| {
+            let ord = cmp(column, a, b);
+            if descending { ord.reverse() } else { ord }
+        });
+    }
+
+    /// Reorders the rows by `cmp`, keeping the filter and the selected row.
+    /// Stable, so ties keep their previous order.
+    pub fn sort_by(&mut self, cmp: impl Fn(&T, &T) -> Ordering) {
+        let selected = self.selected_item();
+        let mut order: Vec<usize> = (0..self.items.len()).collect();
+        order.sort_by(|&a, &b| cmp(&self.items[a], &self.items[b]));
+        // Where each row moved to
+        let mut moved = vec![0; order.len()];
+        for (to, &from) in order.iter().enumerate() {
+            moved[from] = to;
+        }
+
+        let mut taken: Vec<Option<T>> = self.items.drain(..).map(Some).collect();
+        self.items.extend(order.iter().filter_map(|&i| taken[i].take()));
+        self.visible = self.visible.iter().map(|&i| moved[i]).collect();
+        self.visible.sort_unstable();
+        self.reselect(selected.map(|i| moved[i]));
+    }
+
+    /// Adds `items` above the rows, shown whatever the filter, keeping the
+    /// selection on the row it was on.
+    pub fn prepend(&mut self, items: Vec<T>) {
+        let added = items.len();
+        let selected = self.selected_item();
+        self.items.splice(0..0, items);
+        self.visible = (0..added).chain(self.visible.iter().map(|&i| i + added)).collect();
+        self.reselect(selected.map(|i| i + added));
+    }
+
+    /// Adds `items` below the rows, shown whatever the filter.
+    pub fn append(&mut self, items: Vec<T>) {
+        let start = self.items.len();
+        self.items.extend(items);
+        self.visible.extend(start..self.items.len());
+        if self.state.selected().is_none() {
+            self.select_first();
+        }
+    }
+
+    /// Drops the rows `keep` rejects. The selection stays at its position,
+    /// moving up when the last rows went.
+    pub fn retain(&mut self, keep: impl Fn(&T) -> bool) {
+        let position = self.state.selected();
+        let kept: Vec<bool> = self.items.iter().map(&keep).collect();
+        // Where each kept row moves to
+        let mut moved = Vec::with_capacity(kept.len());
+        let mut count = 0;
+        for &keep in &kept {
+            moved.push(count);
+            count += usize::from(keep);
+        }
+        self.visible = self.visible.iter().filter(|&&i| kept[i]).map(|&i| moved[i]).collect();
+        let mut kept = kept.into_iter();
+        self.items.retain(|_| kept.next().unwrap_or(false));
+        self.state.select(match position {
+            _ if self.visible.is_empty() => None,
+            Some(p) => Some(p.min(self.visible.len() - 1)),
+            None => None,
+        });
+    }
+
+    /// The shown rows and the selection, to render them together.
+    pub fn rows_and_state(&mut self) -> (Vec<&T>, &mut TableState) {
+        let rows = self.visible.iter().map(|&i| &self.items[i]).collect();
+        (rows, &mut self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(items: Vec<u32>) -> TableView<u32> {
+        let mut view = TableView::default();
+        view.set_items(items);
+        view
+    }
+
+    fn shown(view: &TableView<u32>) -> Vec<u32> {
+        view.rows().copied().collect()
+    }
+
+    #[test]
+    fn test_navigation_wraps_around_the_shown_rows() {
+        let mut view = view(vec![1, 2, 3, 4]);
+        assert_eq!(view.selected(), Some(&1));
+        view.prev();
+        assert_eq!(view.selected(), Some(&4));
+        view.next();
+        assert_eq!(view.selected(), Some(&1));
+
+        view.filter(|&n| n % 2 == 0);
+        assert_eq!(shown(&view), [2, 4]);
+        // The selected row was filtered out, so the first shown one takes over
+        assert_eq!(view.selected(), Some(&2));
+        view.next();
+        view.next();
+        assert_eq!(view.selected(), Some(&2));
+
+        view.clear();
+        view.next();
+        assert_eq!(view.selected(), None);
+    }
+
+    #[test]
+    fn test_sort_and_filter_keep_the_selected_row() {
+        let mut view = view(vec![3, 1, 4, 2]);
+        view.state.select(Some(2));
+        view.filter(|&n| n != 1);
+        assert_eq!(view.selected(), Some(&4));
+
+        view.sort.cycle(1);
+        view.sort_by_column(|_, a, b| a.cmp(b));
+        assert_eq!(shown(&view), [2, 3, 4]);
+        assert_eq!(view.items(), [1, 2, 3, 4]);
+        assert_eq!(view.selected(), Some(&4));
+
+        view.sort.reverse();
+        view.sort_by_column(|_, a, b| a.cmp(b));
+        assert_eq!(shown(&view), [4, 3, 2]);
+        assert_eq!(view.state.selected(), Some(0));
+
+        view.filter(|_| true);
+        assert_eq!(shown(&view), [4, 3, 2, 1]);
+        assert_eq!(view.selected(), Some(&4));
+    }
+
+    #[test]
+    fn test_prepend_and_retain_keep_the_selection_in_place() {
+        let mut view = view(vec![10, 20, 30]);
+        view.state.select(Some(1));
+        view.prepend(vec![1, 2]);
+        assert_eq!(shown(&view), [1, 2, 10, 20, 30]);
+        assert_eq!(view.selected(), Some(&20));
+
+        view.retain(|&n| n != 1);
+        assert_eq!(view.selected(), Some(&30));
+        view.state.select(Some(3));
+        view.retain(|&n| n < 30);
+        assert_eq!(shown(&view), [2, 10, 20]);
+        assert_eq!(view.selected(), Some(&20));
+    }
+}
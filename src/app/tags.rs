@@ -0,0 +1,146 @@
+use super::table_view::{select_next, select_prev};
+use crate::api::datto::types::Device;
+use ratatui::widgets::TableState;
+use std::collections::BTreeMap;
+
+/// Tags in the comma-separated value of the tags UDF: lowercased, with
+/// inner spaces as dashes ("POS Terminal" is "pos-terminal"), sorted and
+/// without repeats.
+pub fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = value
+        .split(',')
+        .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// The tags UDF value storing `tags`.
+pub fn format_tags(tags: &[String]) -> String {
+    tags.join(",")
+}
+
+/// Tags of `device`, kept in UDF `udf`.
+pub fn device_tags(device: &Device, udf: usize) -> Vec<String> {
+    device.udf.as_ref().and_then(|u| u.get(udf)).map(parse_tags).unwrap_or_default()
+}
+
+/// State of the Tags view: the tags of every device in the account, and the
+/// devices carrying the selected one.
+#[derive(Debug, Default)]
+pub struct TagsState {
+    /// Tag and the number of devices carrying it, by tag
+    pub tags: Vec<(String, usize)>,
+    pub tag_state: TableState,
+    /// Devices with the selected tag, by hostname
+    pub devices: Vec<Device>,
+    pub device_state: TableState,
+    /// j/k move in the device list rather than the tag list
+    pub devices_focused: bool,
+}
+
+impl TagsState {
+    /// Counts the tags of the account's `devices`, keeping the selected tag
+    /// while a device still carries it.
+    pub fn rebuild(&mut self, devices: &[Device], udf: usize) {
+        let selected = self.selected_tag().map(str::to_string);
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for tag in devices.iter().flat_map(|d| device_tags(d, udf)) {
+            *counts.entry(tag).or_default() += 1;
+        }
+        self.tags = counts.into_iter().collect();
+        let kept = selected.and_then(|tag| self.tags.iter().position(|(t, _)| *t == tag));
+        self.tag_state.select(kept.or((!self.tags.is_empty()).then_some(0)));
+        self.load_devices(devices, udf);
+    }
+
+    /// Lists the devices carrying the selected tag.
+    fn load_devices(&mut self, devices: &[Device], udf: usize) {
+        self.devices = match self.selected_tag() {
+            Some(tag) => devices
+                .iter()
+                .filter(|d| device_tags(d, udf).iter().any(|t| t == tag))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        self.devices.sort_by_key(|d| d.hostname.to_lowercase());
+        self.device_state.select((!self.devices.is_empty()).then_some(0));
+    }
+
+    pub fn selected_tag(&self) -> Option<&str> {
+        self.tag_state.selected().and_then(|i| self.tags.get(i)).map(|(tag, _)| tag.as_str())
+    }
+
+    pub fn selected_device(&self) -> Option<&Device> {
+        self.device_state.selected().and_then(|i| self.devices.get(i))
+    }
+
+    /// Moves down the focused list; a new tag lists its devices from the
+    /// account's `devices`.
+    pub fn next_row(&mut self, devices: &[Device], udf: usize) {
+        if self.devices_focused {
+            select_next(&mut self.device_state, self.devices.len());
+        } else if !self.tags.is_empty() {
+            select_next(&mut self.tag_state, self.tags.len());
+            self.load_devices(devices, udf);
+        }
+    }
+
+    pub fn prev_row(&mut self, devices: &[Device], udf: usize) {
+        if self.devices_focused {
+            select_prev(&mut self.device_state, self.devices.len());
+        } else if !self.tags.is_empty() {
+            select_prev(&mut self.tag_state, self.tags.len());
+            self.load_devices(devices, udf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    fn device(hostname: &str, tags: &str) -> Device {
+        DeviceBuilder::new(hostname)
+            .uid(hostname)
+            .with("udf", serde_json::json!({ "udf5": tags }))
+            .build()
+    }
+
+    #[test]
+    fn test_tags_across_devices() {
+        let tags = parse_tags(" POS Terminal,, front-desk ,pos-terminal");
+        assert_eq!(tags, ["front-desk", "pos-terminal"]);
+        assert_eq!(format_tags(&parse_tags("b, a")), "a,b");
+
+        let devices = [
+            device("POS02", "pos-terminal"),
+            device("DC01", "Domain-Controller"),
+            device("POS01", "pos-terminal, front-desk"),
+            device("WS01", ""),
+        ];
+        let mut state = TagsState::default();
+        state.rebuild(&devices, 5);
+        let counts: Vec<_> = state.tags.iter().map(|(t, n)| (t.as_str(), *n)).collect();
+        assert_eq!(counts, [("domain-controller", 1), ("front-desk", 1), ("pos-terminal", 2)]);
+
+        state.prev_row(&devices, 5);
+        assert_eq!(state.selected_tag(), Some("pos-terminal"));
+        let hostnames: Vec<_> = state.devices.iter().map(|d| d.hostname.as_str()).collect();
+        assert_eq!(hostnames, ["POS01", "POS02"]);
+
+        state.devices_focused = true;
+        state.next_row(&devices, 5);
+        assert_eq!(state.selected_device().map(|d| d.hostname.as_str()), Some("POS02"));
+
+        // The tag stays selected while devices still carry it
+        state.rebuild(&devices[..3], 5);
+        assert_eq!(state.selected_tag(), Some("pos-terminal"));
+        state.rebuild(&devices[1..2], 5);
+        assert_eq!(state.selected_tag(), Some("domain-controller"));
+    }
+}
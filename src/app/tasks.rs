@@ -0,0 +1,123 @@
+use crate::api::error::ApiError;
+use crate::event::Event;
+use std::any::Any;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::AbortHandle;
+
+tokio::task_local! {
+    static TASK: &'static str;
+}
+
+/// Name of the supervised task running on this thread, if any. The panic
+/// hook uses it to leave the terminal alone when only a background task
+/// panicked (see `tui::install_panic_hook`).
+pub fn current_task() -> Option<&'static str> {
+    TASK.try_with(|name| *name).ok()
+}
+
+/// Runs the app's background work (API calls, mostly) and watches it: a task
+/// that panics is logged and reported as `Event::TaskFailed` for the error
+/// history instead of vanishing, and a fetch started with `spawn_fetch` still
+/// sends its result event, so whatever it was loading stops loading.
+#[derive(Debug, Clone, Default)]
+pub struct Supervisor {
+    running: Arc<AtomicUsize>,
+    /// Where failures are reported; set once the event loop runs
+    tx: Arc<OnceLock<UnboundedSender<Event>>>,
+}
+
+impl Supervisor {
+    pub fn attach(&self, tx: UnboundedSender<Event>) {
+        let _ = self.tx.set(tx);
+    }
+
+    /// Tasks spawned and not finished yet.
+    pub fn running(&self) -> usize {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Spawns `task`, named `name` in logs and the error history.
+    pub fn spawn<F>(&self, name: &'static str, task: F) -> AbortHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.supervise(name, None::<fn(ApiError) -> Event>, task)
+    }
+
+    /// Spawns a fetch whose result event ends a loading state. Should the
+    /// task panic, the event `on_panic` builds from the error is sent instead.
+    pub fn spawn_fetch<F, E>(&self, name: &'static str, on_panic: E, task: F) -> AbortHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+        E: FnOnce(ApiError) -> Event + Send + 'static,
+    {
+        self.supervise(name, Some(on_panic), task)
+    }
+
+    fn supervise<F, E>(&self, name: &'static str, on_panic: Option<E>, task: F) -> AbortHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+        E: FnOnce(ApiError) -> Event + Send + 'static,
+    {
+        let handle = tokio::spawn(TASK.scope(name, task));
+        let abort = handle.abort_handle();
+        let running = self.running.clone();
+        let tx = self.tx.clone();
+        running.fetch_add(1, Ordering::Relaxed);
+        tokio::spawn(async move {
+            let result = handle.await;
+            running.fetch_sub(1, Ordering::Relaxed);
+            // Aborted tasks were cancelled on purpose (see `Fetches`)
+            let Err(e) = result else { return };
+            if !e.is_panic() {
+                return;
+            }
+            let message = panic_message(e.into_panic());
+            tracing::error!(task = name, %message, "background task panicked");
+            let Some(tx) = tx.get() else { return };
+            if let Some(on_panic) = on_panic {
+                let error = ApiError::Other(format!("{} failed: {}", name, message));
+                let _ = tx.send(on_panic(error));
+            }
+            let _ = tx.send(Event::TaskFailed(name, message));
+        });
+        abort
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "panicked".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_panicking_fetch_still_reports() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let tasks = Supervisor::default();
+        tasks.attach(tx);
+        tasks.spawn_fetch("fetch_sites", |e| Event::SitesFetched(Err(e)), async {
+            assert_eq!(current_task(), Some("fetch_sites"));
+            panic!("page out of range");
+        });
+
+        let Some(Event::SitesFetched(Err(error))) = rx.recv().await else {
+            panic!("expected the fallback result");
+        };
+        assert_eq!(error.to_string(), "fetch_sites failed: page out of range");
+        assert!(matches!(rx.recv().await, Some(Event::TaskFailed("fetch_sites", _))));
+        assert_eq!(tasks.running(), 0);
+        assert_eq!(current_task(), None);
+    }
+}
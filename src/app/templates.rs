@@ -0,0 +1,125 @@
+use crate::api::datto::types::SiteVariable;
+use crate::config::VariableTemplate;
+
+/// What applying a variable template does to one variable of a site.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableChange {
+    Create {
+        name: String,
+        value: String,
+    },
+    Update {
+        id: i32,
+        name: String,
+        old: String,
+        new: String,
+    },
+    Unchanged {
+        name: String,
+        value: String,
+    },
+}
+
+impl VariableChange {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Create { name, .. }
+            | Self::Update { name, .. }
+            | Self::Unchanged { name, .. } => name,
+        }
+    }
+
+    pub fn is_change(&self) -> bool {
+        !matches!(self, Self::Unchanged { .. })
+    }
+}
+
+/// Diffs `template` against a site's current variables, in template order.
+/// Masked variables can't be compared, so they are always updated.
+pub fn template_changes(
+    template: &VariableTemplate,
+    site_name: &str,
+    existing: &[SiteVariable],
+) -> Vec<VariableChange> {
+    template
+        .variables
+        .iter()
+        .map(|(name, value)| {
+            let value = value.replace("{site}", site_name);
+            match existing.iter().find(|v| v.name == *name) {
+                None => VariableChange::Create {
+                    name: name.clone(),
+                    value,
+                },
+                Some(var) if !var.masked && var.value == value => VariableChange::Unchanged {
+                    name: name.clone(),
+                    value,
+                },
+                Some(var) => VariableChange::Update {
+                    id: var.id,
+                    name: name.clone(),
+                    old: if var.masked {
+                        "(masked)".to_string()
+                    } else {
+                        var.value.clone()
+                    },
+                    new: value,
+                },
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(id: i32, name: &str, value: &str) -> SiteVariable {
+        SiteVariable {
+            id,
+            name: name.to_string(),
+            value: value.to_string(),
+            masked: false,
+        }
+    }
+
+    #[test]
+    fn test_template_changes() {
+        let template = VariableTemplate {
+            name: "MDR".to_string(),
+            variables: [
+                ("tuiColor", "green"),
+                ("tuiMdrId", "{site}"),
+                ("tuiMdrProvider", "Sophos"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        };
+        let existing = vec![
+            variable(1, "tuiColor", "green"),
+            variable(2, "tuiMdrId", "old"),
+        ];
+
+        let changes = template_changes(&template, "Acme", &existing);
+        assert_eq!(
+            changes,
+            vec![
+                VariableChange::Unchanged {
+                    name: "tuiColor".into(),
+                    value: "green".into()
+                },
+                VariableChange::Update {
+                    id: 2,
+                    name: "tuiMdrId".into(),
+                    old: "old".into(),
+                    new: "Acme".into(),
+                },
+                VariableChange::Create {
+                    name: "tuiMdrProvider".into(),
+                    value: "Sophos".into()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use super::VariableChange;
+use crate::api::datto::types::SiteVariable;
+
+/// Copying variables from one site to another, from picking the target site
+/// through answering a skip/overwrite prompt for each conflict.
+#[derive(Debug, Clone, Default)]
+pub struct VariableCopy {
+    pub source_uid: String,
+    pub source_name: String,
+    /// Variables to copy; masked ones are left out since their values can't be read
+    pub variables: Vec<SiteVariable>,
+    pub masked_skipped: usize,
+    /// (uid, name) of the site picked as the destination
+    pub target: Option<(String, String)>,
+    /// Diff against the target's variables, once they are loaded
+    pub changes: Option<Vec<VariableChange>>,
+    /// Answers to the conflicts (updates in `changes`) so far, true to overwrite
+    pub overwrite: Vec<bool>,
+    pub applying: bool,
+}
+
+/// Diffs copied `variables` against the target site's `existing` ones. Names
+/// that already exist with another value become updates, i.e. conflicts.
+pub fn copy_changes(variables: &[SiteVariable], existing: &[SiteVariable]) -> Vec<VariableChange> {
+    variables
+        .iter()
+        .map(|var| match existing.iter().find(|v| v.name == var.name) {
+            None => VariableChange::Create {
+                name: var.name.clone(),
+                value: var.value.clone(),
+            },
+            Some(old) if !old.masked && old.value == var.value => VariableChange::Unchanged {
+                name: var.name.clone(),
+                value: var.value.clone(),
+            },
+            Some(old) => VariableChange::Update {
+                id: old.id,
+                name: var.name.clone(),
+                old: if old.masked {
+                    "(masked)".to_string()
+                } else {
+                    old.value.clone()
+                },
+                new: var.value.clone(),
+            },
+        })
+        .collect()
+}
+
+impl VariableCopy {
+    fn conflicts(&self) -> impl Iterator<Item = &VariableChange> {
+        self.changes
+            .iter()
+            .flatten()
+            .filter(|c| matches!(c, VariableChange::Update { .. }))
+    }
+
+    /// The conflict waiting for an answer, if any.
+    pub fn current_conflict(&self) -> Option<&VariableChange> {
+        self.conflicts().nth(self.overwrite.len())
+    }
+
+    /// Answers the current conflict, or all remaining ones with `all`.
+    pub fn resolve(&mut self, overwrite: bool, all: bool) {
+        let remaining = self
+            .conflicts()
+            .count()
+            .saturating_sub(self.overwrite.len());
+        let count = if all { remaining } else { remaining.min(1) };
+        self.overwrite.extend(std::iter::repeat_n(overwrite, count));
+    }
+
+    /// Creates plus the conflicts answered with overwrite.
+    pub fn to_write(&self) -> Vec<VariableChange> {
+        let mut answers = self.overwrite.iter();
+        self.changes
+            .iter()
+            .flatten()
+            .filter(|c| match c {
+                VariableChange::Create { .. } => true,
+                VariableChange::Update { .. } => answers.next().copied().unwrap_or(false),
+                VariableChange::Unchanged { .. } => false,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(id: i32, name: &str, value: &str) -> SiteVariable {
+        SiteVariable {
+            id,
+            name: name.to_string(),
+            value: value.to_string(),
+            masked: false,
+        }
+    }
+
+    #[test]
+    fn test_copy_conflicts() {
+        let mut copy = VariableCopy {
+            variables: vec![
+                variable(1, "a", "1"),
+                variable(2, "b", "2"),
+                variable(3, "c", "3"),
+                variable(4, "d", "4"),
+            ],
+            ..Default::default()
+        };
+        let existing = vec![
+            variable(10, "b", "old"),
+            variable(11, "c", "old"),
+            variable(12, "d", "4"),
+        ];
+        copy.changes = Some(copy_changes(&copy.variables, &existing));
+
+        assert_eq!(copy.current_conflict().map(VariableChange::name), Some("b"));
+        copy.resolve(false, false);
+        assert_eq!(copy.current_conflict().map(VariableChange::name), Some("c"));
+        copy.resolve(true, true);
+        assert!(copy.current_conflict().is_none());
+
+        let names: Vec<_> = copy
+            .to_write()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["a", "c"]);
+    }
+}
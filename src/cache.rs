@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+/// A cached value and when it was fetched from the API.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// A local "seen by me" mark on an alert or incident, with an optional note.
+/// Independent of the item's upstream mute/resolve state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Acknowledgement {
+    /// What `item_id` refers to, e.g. [`ALERT_ACK`]
+    pub kind: String,
+    pub item_id: String,
+    pub note: String,
+    pub acked_at: DateTime<Utc>,
+}
+
+/// Acknowledgement kind for Datto RMM alerts (keyed by alert uid).
+pub const ALERT_ACK: &str = "alert";
+/// Acknowledgement kind for RocketCyber incidents (keyed by incident id).
+pub const INCIDENT_ACK: &str = "incident";
+
+/// SQLite-backed key/value store of API responses (JSON), one database per profile.
+#[derive(Debug)]
+pub struct Cache {
+    conn: Connection,
+}
+
+pub const SITES_KEY: &str = "sites";
+/// Uids of the sites pinned to the top of the site list (not an API response)
+pub const PINNED_SITES_KEY: &str = "pinned_sites";
+/// Titles of the collapsed site list sections
+pub const COLLAPSED_GROUPS_KEY: &str = "collapsed_site_groups";
+/// Snapshots of the most recently opened devices, newest first
+pub const RECENT_DEVICES_KEY: &str = "recent_devices";
+/// Saved and recent device search queries
+pub const SEARCH_HISTORY_KEY: &str = "search_history";
+/// Navigation state of the last run, offered for restore at startup
+pub const SESSION_KEY: &str = "session";
+/// Uids of the starred components
+pub const STARRED_COMPONENTS_KEY: &str = "starred_components";
+/// Recently run components with their variable values, newest first
+pub const COMPONENT_HISTORY_KEY: &str = "component_history";
+/// Alert and incident ids seen by the last session, for the Notification Center
+pub const SEEN_ITEMS_KEY: &str = "seen_items";
+/// Daily device, offline and open alert counts per site, for trend sparklines
+pub const SITE_HISTORY_KEY: &str = "site_history";
+/// Named device collections built across sites, by name
+pub const COLLECTIONS_KEY: &str = "device_collections";
+
+pub fn devices_key(site_uid: &str) -> String {
+    format!("devices:{}", site_uid)
+}
+
+pub fn variables_key(site_uid: &str) -> String {
+    format!("variables:{}", site_uid)
+}
+
+/// `~/.cache/datto_tui/<profile>.sqlite` (platform equivalent elsewhere).
+pub fn cache_path(profile: &str) -> Option<PathBuf> {
+    let name = if profile.is_empty() { "default" } else { profile };
+    dirs::cache_dir().map(|d| d.join("datto_tui").join(format!("{}.sqlite", name)))
+}
+
+impl Cache {
+    pub fn open_for_profile(profile: &str) -> Result<Self> {
+        let path = cache_path(profile).context("Could not determine cache directory")?;
+        if let Some(parent) = path.parent() {
+            crate::config::create_private_dir(parent)?;
+        }
+        Self::open(&path)
+    }
+
+    /// Opens or creates the cache at `path`, readable by the user only (0600
+    /// on unix) as it holds device and alert data. SQLite gives its journal
+    /// files the same mode.
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open cache {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .with_context(|| format!("Failed to restrict {}", path.display()))?;
+        }
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS entries (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                fetched_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS acknowledgements (
+                kind TEXT NOT NULL,
+                item_id TEXT NOT NULL,
+                note TEXT NOT NULL,
+                acked_at INTEGER NOT NULL,
+                PRIMARY KEY (kind, item_id)
+            )",
+        )
+        .context("Failed to initialise cache schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Returns `None` for missing entries and for entries that no longer deserialize
+    /// (e.g. after a type change), so a bad cache never blocks startup.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<Cached<T>> {
+        let row: Option<(String, i64)> = self
+            .conn
+            .query_row(
+                "SELECT value, fetched_at FROM entries WHERE key = ?1",
+                params![key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .ok()
+            .flatten();
+        let (json, fetched_at) = row?;
+        let value = match serde_json::from_str(&json) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::debug!(%key, error = %e, "discarding unreadable cache entry");
+                return None;
+            }
+        };
+        Some(Cached {
+            value,
+            fetched_at: DateTime::from_timestamp(fetched_at, 0)?,
+        })
+    }
+
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_string(value)?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO entries (key, value, fetched_at) VALUES (?1, ?2, ?3)",
+            params![key, json, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Marks an item as acknowledged, replacing any earlier note.
+    pub fn acknowledge(&self, kind: &str, item_id: &str, note: &str) -> Result<Acknowledgement> {
+        let acked_at = Utc::now();
+        self.conn.execute(
+            "INSERT OR REPLACE INTO acknowledgements (kind, item_id, note, acked_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind, item_id, note, acked_at.timestamp()],
+        )?;
+        Ok(Acknowledgement {
+            kind: kind.to_string(),
+            item_id: item_id.to_string(),
+            note: note.to_string(),
+            acked_at,
+        })
+    }
+
+    pub fn unacknowledge(&self, kind: &str, item_id: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM acknowledgements WHERE kind = ?1 AND item_id = ?2",
+            params![kind, item_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn acknowledgements(&self) -> Result<Vec<Acknowledgement>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT kind, item_id, note, acked_at FROM acknowledgements")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Acknowledgement {
+                kind: row.get(0)?,
+                item_id: row.get(1)?,
+                note: row.get(2)?,
+                acked_at: DateTime::from_timestamp(row.get(3)?, 0).unwrap_or_default(),
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_get_roundtrip() {
+        let cache = Cache::open_in_memory().unwrap();
+        assert!(cache.get::<Vec<String>>("sites").is_none());
+
+        cache.put("sites", &vec!["a".to_string(), "b".to_string()]).unwrap();
+        let cached = cache.get::<Vec<String>>("sites").unwrap();
+        assert_eq!(cached.value, vec!["a", "b"]);
+
+        // Wrong shape is treated as a miss rather than an error
+        assert!(cache.get::<u32>("sites").is_none());
+    }
+
+    #[test]
+    fn test_acknowledgements_roundtrip() {
+        let cache = Cache::open_in_memory().unwrap();
+        cache.acknowledge(ALERT_ACK, "a1", "looking into it").unwrap();
+        cache.acknowledge(ALERT_ACK, "a1", "vendor ticket open").unwrap();
+        cache.acknowledge(INCIDENT_ACK, "a1", "").unwrap();
+
+        let acks = cache.acknowledgements().unwrap();
+        assert_eq!(acks.len(), 2);
+        let alert = acks.iter().find(|a| a.kind == ALERT_ACK).unwrap();
+        assert_eq!(alert.note, "vendor ticket open");
+
+        cache.unacknowledge(ALERT_ACK, "a1").unwrap();
+        let acks = cache.acknowledgements().unwrap();
+        assert_eq!(acks.len(), 1);
+        assert_eq!(acks[0].kind, INCIDENT_ACK);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cache_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("datto_tui_cache_{}", std::process::id()));
+        let path = dir.join("nested").join("default.sqlite");
+        crate::config::create_private_dir(path.parent().unwrap()).unwrap();
+        // A cache created before files were restricted
+        std::fs::write(&path, "").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let cache = Cache::open(&path).unwrap();
+        cache.put("sites", &vec!["a".to_string()]).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let mode = std::fs::metadata(path.parent().unwrap()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+use crate::api::datto::RmmApi;
+use crate::api::datto::types::{QuickJobComponent, QuickJobRequest, QuickJobVariable};
+use crate::api::lenient::partial_load_message;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+
+/// Command line interface. Running without a subcommand starts the TUI.
+#[derive(Parser, Debug)]
+#[command(name = "kyber_tui", version, about = "Unified MSP terminal interface")]
+pub struct Cli {
+    /// Output format for headless subcommands
+    #[arg(long, short, value_enum, default_value_t = OutputFormat::Table, global = true)]
+    pub output: OutputFormat,
+
+    /// Named profile from config.toml
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Use built-in fake data instead of the live APIs (no config needed)
+    #[arg(long, global = true)]
+    pub demo: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Datto RMM sites
+    Sites {
+        #[command(subcommand)]
+        action: SitesCommand,
+    },
+    /// Datto RMM devices
+    Devices {
+        #[command(subcommand)]
+        action: DevicesCommand,
+    },
+    /// Datto RMM alerts
+    Alerts {
+        #[command(subcommand)]
+        action: AlertsCommand,
+    },
+    /// Datto RMM quick jobs
+    Job {
+        #[command(subcommand)]
+        action: JobCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SitesCommand {
+    /// List all sites in the account
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DevicesCommand {
+    /// Search devices account-wide by hostname
+    Search { query: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AlertsCommand {
+    /// List open alerts for a site
+    Open {
+        #[arg(long)]
+        site: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum JobCommand {
+    /// Run a component as a quick job on a device
+    Run {
+        #[arg(long)]
+        device: String,
+        #[arg(long)]
+        component: String,
+        /// Component variable as name=value (repeatable)
+        #[arg(long = "var", value_parser = parse_variable)]
+        variables: Vec<(String, String)>,
+        /// Job name shown in Datto RMM
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+fn parse_variable(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .ok_or_else(|| format!("expected name=value, got '{}'", s))
+}
+
+/// Runs a headless subcommand against an authenticated Datto client and prints the result.
+pub async fn run(command: Command, format: OutputFormat, client: &dyn RmmApi) -> Result<()> {
+    match command {
+        Command::Sites {
+            action: SitesCommand::List,
+        } => {
+            let mut sites = Vec::new();
+            let mut page = 0;
+            let page_size = client.page_size();
+            loop {
+                let response = client.get_sites(page, page_size, None).await?;
+                let count = response.sites.len();
+                sites.extend(response.sites);
+                if count < page_size as usize || response.page_details.next_page_url.is_none() {
+                    break;
+                }
+                page += 1;
+            }
+            sites.sort_by_key(|s| s.name.to_lowercase());
+
+            print_output(format, &sites, &["UID", "Name", "Devices"], |s| {
+                vec![
+                    s.uid.clone(),
+                    s.name.clone(),
+                    s.devices_status
+                        .as_ref()
+                        .map(|d| d.number_of_devices.to_string())
+                        .unwrap_or_default(),
+                ]
+            })
+        }
+        Command::Devices {
+            action: DevicesCommand::Search { query },
+        } => {
+            let mut devices = Vec::new();
+            let mut page = 0;
+            let page_size = client.page_size();
+            loop {
+                let response = client.search_devices(&query, page, page_size).await?;
+                let count = response.page_len();
+                if let Some(message) =
+                    partial_load_message("devices", response.devices.len(), response.parse_errors)
+                {
+                    eprintln!("Warning: {}", message);
+                }
+                devices.extend(response.devices);
+                if count < page_size as usize || response.page_details.next_page_url.is_none() {
+                    break;
+                }
+                page += 1;
+            }
+            print_output(
+                format,
+                &devices,
+                &["UID", "Hostname", "Site", "Status", "OS"],
+                |d| {
+                    vec![
+                        d.uid.clone(),
+                        d.hostname.clone(),
+                        d.site_name.clone().unwrap_or_default(),
+                        if d.online { "Online" } else { "Offline" }.to_string(),
+                        d.operating_system.clone().unwrap_or_default(),
+                    ]
+                },
+            )
+        }
+        Command::Alerts {
+            action: AlertsCommand::Open { site },
+        } => {
+            let mut alerts = Vec::new();
+            let mut page = 0;
+            let page_size = client.page_size();
+            loop {
+                let response = client.get_site_open_alerts(&site, page, page_size).await?;
+                let count = response.alerts.len();
+                alerts.extend(response.alerts);
+                if count < page_size as usize || response.page_details.next_page_url.is_none() {
+                    break;
+                }
+                page += 1;
+            }
+
+            print_output(
+                format,
+                &alerts,
+                &["UID", "Priority", "Device", "Diagnostics"],
+                |a| {
+                    vec![
+                        a.alert_uid.clone().unwrap_or_default(),
+                        a.priority.clone().unwrap_or_default(),
+                        a.alert_source_info
+                            .as_ref()
+                            .and_then(|s| s.device_name.clone())
+                            .unwrap_or_default(),
+                        a.diagnostics
+                            .as_deref()
+                            .unwrap_or("")
+                            .replace("\r\n", " ")
+                            .replace('\n', " ")
+                            .trim()
+                            .to_string(),
+                    ]
+                },
+            )
+        }
+        Command::Job {
+            action:
+                JobCommand::Run {
+                    device,
+                    component,
+                    variables,
+                    name,
+                },
+        } => {
+            let req = QuickJobRequest {
+                job_name: name.unwrap_or_else(|| format!("Run Component: {}", component)),
+                job_component: QuickJobComponent {
+                    component_uid: component,
+                    variables: variables
+                        .into_iter()
+                        .map(|(name, value)| QuickJobVariable { name, value })
+                        .collect(),
+                },
+            };
+            let response = client
+                .run_quick_job(&device, req)
+                .await
+                .context("Failed to run quick job")?;
+
+            let jobs: Vec<_> = response.job.into_iter().collect();
+            print_output(format, &jobs, &["Job UID", "Name", "Status"], |j| {
+                vec![
+                    j.uid.clone().unwrap_or_default(),
+                    j.name.clone().unwrap_or_default(),
+                    j.status.clone().unwrap_or_default(),
+                ]
+            })
+        }
+    }
+}
+
+/// Prints items either as pretty JSON or as a whitespace-aligned table built from `columns`.
+fn print_output<T: Serialize>(
+    format: OutputFormat,
+    items: &[T],
+    headers: &[&str],
+    columns: impl Fn(&T) -> Vec<String>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(items)?);
+        }
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = items.iter().map(columns).collect();
+            print!("{}", format_table(headers, &rows));
+        }
+    }
+    Ok(())
+}
+
+fn format_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if let Some(w) = widths.get_mut(i) {
+                *w = (*w).max(cell.chars().count());
+            }
+        }
+    }
+
+    let render_line = |cells: Vec<&str>| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<width$}", c, width = w))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("{}\n", line.trim_end())
+    };
+
+    let mut out = render_line(headers.to_vec());
+    for row in rows {
+        out.push_str(&render_line(row.iter().map(|s| s.as_str()).collect()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table_aligns_columns() {
+        let rows = vec![
+            vec!["abc".to_string(), "Site One".to_string()],
+            vec!["defghi".to_string(), "Two".to_string()],
+        ];
+        let out = format_table(&["UID", "Name"], &rows);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "UID     Name");
+        assert_eq!(lines[1], "abc     Site One");
+        assert_eq!(lines[2], "defghi  Two");
+    }
+
+    #[tokio::test]
+    async fn test_job_run_is_refused_in_read_only_mode() {
+        use crate::api::mock::MockRmm;
+        use crate::api::read_only::{READ_ONLY_MESSAGE, ReadOnlyRmm};
+        use std::sync::Arc;
+
+        let client = ReadOnlyRmm(Arc::new(MockRmm::default()));
+        let command = Command::Job {
+            action: JobCommand::Run {
+                device: "demo-device-2000".to_string(),
+                component: "demo-component-1".to_string(),
+                variables: Vec::new(),
+                name: None,
+            },
+        };
+        let error = run(command, OutputFormat::Json, &client).await.unwrap_err();
+        assert_eq!(error.root_cause().to_string(), READ_ONLY_MESSAGE);
+    }
+
+    #[test]
+    fn test_parse_variable() {
+        assert_eq!(
+            parse_variable("rebootNow=true"),
+            Ok(("rebootNow".to_string(), "true".to_string()))
+        );
+        assert!(parse_variable("novalue").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Quotes a CSV field if it contains a comma, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders a header row and data rows as CSV text.
+///
+/// # Arguments
+/// * `headers` - Column names for the first line.
+/// * `rows` - One `Vec` of cell values per line.
+pub fn to_csv(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    let mut push_line = |cells: Vec<String>| {
+        out.push_str(&cells.join(","));
+        out.push_str("\r\n");
+    };
+    push_line(headers.iter().map(|h| csv_field(h)).collect());
+    for row in rows {
+        push_line(row.iter().map(|c| csv_field(c)).collect());
+    }
+    out
+}
+
+/// Renders rows as a JSON array of objects keyed by header. Integer cells
+/// (counts, days left) are written as numbers.
+pub fn to_json(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let objects: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| {
+            let map: serde_json::Map<String, serde_json::Value> = headers
+                .iter()
+                .zip(row)
+                .map(|(h, c)| {
+                    let value = match c.parse::<i64>() {
+                        Ok(n) => serde_json::Value::from(n),
+                        Err(_) => serde_json::Value::String(c.clone()),
+                    };
+                    (h.to_string(), value)
+                })
+                .collect();
+            serde_json::Value::Object(map)
+        })
+        .collect();
+    serde_json::to_string_pretty(&objects).unwrap_or_default()
+}
+
+/// Output formats offered by report exports.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn render(self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        match self {
+            ExportFormat::Csv => to_csv(headers, rows),
+            ExportFormat::Json => to_json(headers, rows),
+        }
+    }
+}
+
+/// File name for an export made today, e.g. `warranty-report-2026-01-15.csv`.
+pub fn export_path(dir: &Path, name: &str, extension: &str) -> PathBuf {
+    let date = chrono::Local::now().format("%Y-%m-%d");
+    dir.join(format!("{}-{}.{}", name, date, extension))
+}
+
+/// Writes `contents` to `path` and returns the path for display.
+pub fn write_export(path: PathBuf, contents: &str) -> Result<PathBuf> {
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv_quotes_fields() {
+        let csv = to_csv(
+            &["Site", "Hostname"],
+            &[vec!["Acme, Inc".to_string(), "say \"hi\"".to_string()]],
+        );
+        assert_eq!(csv, "Site,Hostname\r\n\"Acme, Inc\",\"say \"\"hi\"\"\"\r\n");
+    }
+
+    #[test]
+    fn test_to_json_keys_by_header() {
+        let json = to_json(&["Site", "Devices"], &[vec!["Acme".to_string(), "3".to_string()]]);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, serde_json::json!([{ "Site": "Acme", "Devices": 3 }]));
+    }
+}
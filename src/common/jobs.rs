@@ -1,5 +1,6 @@
-use crate::api::datto::types::JobResult;
+use crate::api::datto::types::{ActivityLog, JobResult};
 use crate::app::JobViewRow;
+use std::collections::HashSet;
 
 /// Generates a list of JobViewRow enums based on the contents of a JobResult.
 /// This determines how the job result detail view should be structured,
@@ -7,14 +8,18 @@ use crate::app::JobViewRow;
 ///
 /// # Arguments
 /// * `job_result` - The JobResult containing component outcomes and output status.
+/// * `collapsed` - Indexes of the components shown without their output links.
 ///
 /// # Returns
 /// A vector of JobViewRow used by the UI to render the job detail page.
-pub fn generate_job_rows(job_result: &JobResult) -> Vec<JobViewRow> {
+pub fn generate_job_rows(job_result: &JobResult, collapsed: &HashSet<usize>) -> Vec<JobViewRow> {
     let mut rows = Vec::new();
     if let Some(components) = &job_result.component_results {
         for (idx, comp) in components.iter().enumerate() {
             rows.push(JobViewRow::ComponentHeader(idx));
+            if collapsed.contains(&idx) {
+                continue;
+            }
             if comp.has_std_out == Some(true) {
                 rows.push(JobViewRow::StdOutLink(idx));
             }
@@ -25,3 +30,41 @@ pub fn generate_job_rows(job_result: &JobResult) -> Vec<JobViewRow> {
     }
     rows
 }
+
+/// Extracts the job name and status from an activity log's `details` JSON.
+/// Falls back to the raw details and an empty status for non-job activities.
+///
+/// # Arguments
+/// * `log` - The ActivityLog entry to summarize.
+///
+/// # Returns
+/// A `(name, status)` tuple as shown in the activities table.
+pub fn activity_job_summary(log: &ActivityLog) -> (String, String) {
+    let mut job_status = String::new();
+    let mut job_name = log.details.clone().unwrap_or_default();
+
+    if let Some(details_json) = &log.details
+        && let Ok(parsed) = serde_json::from_str::<serde_json::Value>(details_json)
+    {
+        if let Some(status) = parsed.get("job.status").and_then(|s| s.as_str()) {
+            job_status = status.to_string();
+        }
+        if let Some(name) = parsed.get("job.name").and_then(|s| s.as_str()) {
+            job_name = name.to_string();
+        }
+    }
+
+    (job_name, job_status)
+}
+
+/// Reads the UID of the job an activity belongs to from its details.
+///
+/// # Arguments
+/// * `log` - The ActivityLog entry of a job.
+///
+/// # Returns
+/// The `job.uid` of the details, if the activity is a job's.
+pub fn activity_job_uid(log: &ActivityLog) -> Option<String> {
+    let details = serde_json::from_str::<serde_json::Value>(log.details.as_ref()?).ok()?;
+    details.get("job.uid").and_then(|v| v.as_str()).map(String::from)
+}
@@ -0,0 +1,85 @@
+use crate::api::datto::types::Device;
+use crate::config::Launcher;
+use std::process::{Command, Stdio};
+
+/// The launcher's command for `device`, with its placeholders filled in.
+pub fn launcher_args(launcher: &Launcher, device: &Device) -> Vec<String> {
+    let ip = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+            .map(str::to_string)
+    };
+    let int_ip = ip(&device.int_ip_address).unwrap_or_else(|| device.hostname.clone());
+    let ext_ip = ip(&device.ext_ip_address).unwrap_or_default();
+    launcher
+        .command
+        .iter()
+        .map(|arg| {
+            arg.replace("{hostname}", &device.hostname)
+                .replace("{ext_ip}", &ext_ip)
+                .replace("{ip}", &int_ip)
+        })
+        .collect()
+}
+
+fn command(args: &[String]) -> Result<Command, String> {
+    let (program, rest) = args.split_first().ok_or("The launcher has an empty command")?;
+    let mut command = Command::new(program);
+    command.args(rest);
+    Ok(command)
+}
+
+/// Starts `args` detached from the terminal; the TUI keeps running.
+pub fn spawn_background(args: &[String]) -> Result<(), String> {
+    let mut child = command(args)?
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", args[0], e))?;
+    // Reap it off the UI thread
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Runs `args` on the terminal until it exits. The caller suspends the TUI
+/// around this.
+pub async fn run_foreground(args: &[String]) -> Result<(), String> {
+    let status = tokio::process::Command::from(command(args)?)
+        .status()
+        .await
+        .map_err(|e| format!("Failed to start {}: {}", args[0], e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", args[0], status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::DeviceBuilder;
+
+    #[test]
+    fn test_placeholders_come_from_the_device() {
+        let mut device = DeviceBuilder::new("SRV-01").with("intIpAddress", "10.0.0.5").build();
+        let launcher = Launcher {
+            name: "RDP".to_string(),
+            command: vec!["xfreerdp".into(), "/v:{ip}".into(), "/t:{hostname} {ext_ip}".into()],
+            background: true,
+        };
+        assert_eq!(launcher_args(&launcher, &device), vec!["xfreerdp", "/v:10.0.0.5", "/t:SRV-01 "]);
+
+        // Without an internal IP, {ip} falls back to the hostname
+        device.int_ip_address = None;
+        device.ext_ip_address = Some("203.0.113.9".to_string());
+        assert_eq!(
+            launcher_args(&launcher, &device),
+            vec!["xfreerdp", "/v:SRV-01", "/t:SRV-01 203.0.113.9"]
+        );
+        assert!(spawn_background(&[]).is_err());
+    }
+}
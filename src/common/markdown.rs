@@ -0,0 +1,72 @@
+use crate::ui::theme;
+use ratatui::prelude::*;
+
+/// Renders free text with a little markdown: `#` headers, `-`/`*`/`+` bullets
+/// and `**bold**`. Line breaks are kept as written; anything else shows as is.
+pub fn markdown_lines(text: &str) -> Vec<Line<'static>> {
+    let theme = theme::current();
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+            let header = trimmed.trim_start_matches('#');
+            if header.len() < trimmed.len() && (header.is_empty() || header.starts_with(' ')) {
+                let style = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+                return Line::from(Span::styled(header.trim().to_string(), style));
+            }
+            let mut spans = vec![Span::raw(indent.to_string())];
+            let body = match trimmed.split_at_checked(2) {
+                Some(("- " | "* " | "+ ", rest)) => {
+                    spans.push(Span::styled("• ", Style::default().fg(theme.accent)));
+                    rest
+                }
+                _ => trimmed,
+            };
+            spans.extend(bold_spans(body));
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Splits `text` on `**` pairs; an unpaired `**` is kept as text.
+fn bold_spans(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("**") {
+        let Some(len) = rest[start + 2..].find("**") else {
+            break;
+        };
+        if start > 0 {
+            spans.push(Span::raw(rest[..start].to_string()));
+        }
+        let bold = &rest[start + 2..start + 2 + len];
+        spans.push(Span::styled(bold.to_string(), Style::default().add_modifier(Modifier::BOLD)));
+        rest = &rest[start + 4 + len..];
+    }
+    if !rest.is_empty() {
+        spans.push(Span::raw(rest.to_string()));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(line: &Line) -> Vec<String> {
+        line.spans.iter().map(|s| s.content.to_string()).collect()
+    }
+
+    #[test]
+    fn test_headers_bullets_and_bold() {
+        let lines = markdown_lines("## Access\r\n  - Gate code **1234**\n\n#hashtag\nA ** B");
+        assert_eq!(lines.len(), 5);
+        assert_eq!(texts(&lines[0]), vec!["Access"]);
+        assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(texts(&lines[1]), vec!["  ", "• ", "Gate code ", "1234"]);
+        assert!(lines[1].spans[3].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(texts(&lines[2]), vec![""]);
+        assert_eq!(texts(&lines[3]), vec!["", "#hashtag"]);
+        assert_eq!(texts(&lines[4]), vec!["", "A ** B"]);
+    }
+}
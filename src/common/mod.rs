@@ -1,2 +1,11 @@
+pub mod export;
 pub mod jobs;
+pub mod launcher;
+pub mod markdown;
+pub mod net_tools;
+pub mod notify;
+pub mod site_groups;
+pub mod site_rules;
+pub mod time;
 pub mod utils;
+pub mod warranty;
@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+use tokio::net::{TcpStream, lookup_host};
+use tokio::process::Command;
+
+/// How long a port check or DNS lookup may take.
+pub const NET_TIMEOUT: Duration = Duration::from_secs(3);
+/// Echo requests sent by a ping.
+pub const PING_COUNT: u32 = 4;
+
+/// A reachability check run from this machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetTool {
+    Ping,
+    Port,
+    Dns,
+}
+
+impl NetTool {
+    pub const ALL: [NetTool; 3] = [NetTool::Ping, NetTool::Port, NetTool::Dns];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NetTool::Ping => "Ping",
+            NetTool::Port => "TCP Port",
+            NetTool::Dns => "DNS Lookup",
+        }
+    }
+}
+
+/// Pings `host` with the system `ping`, since ICMP sockets need privileges.
+/// Returns the packet and round-trip summary lines.
+pub async fn ping(host: &str) -> Result<String, String> {
+    let count = PING_COUNT.to_string();
+    let mut command = Command::new("ping");
+    if cfg!(windows) {
+        command.args(["-n", &count, host]);
+    } else {
+        command.args(["-c", &count, host]);
+    }
+    let output = command
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ping: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let summary = ping_summary(&stdout);
+    if output.status.success() {
+        Ok(summary.unwrap_or_else(|| "Reachable".to_string()))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(summary
+            .or_else(|| stderr.lines().next().map(str::to_string))
+            .unwrap_or_else(|| "No reply".to_string()))
+    }
+}
+
+/// The packet loss and round-trip lines of `ping` output, joined; `None` if
+/// neither is there.
+pub fn ping_summary(output: &str) -> Option<String> {
+    let lines: Vec<&str> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            lower.contains("packets transmitted")
+                || lower.starts_with("packets:")
+                || lower.starts_with("rtt ")
+                || lower.starts_with("round-trip")
+                || lower.starts_with("minimum =")
+        })
+        .collect();
+    (!lines.is_empty()).then(|| lines.join(" | "))
+}
+
+/// Opens a TCP connection to `host:port`; returns how long it took.
+pub async fn check_port(host: &str, port: u16) -> Result<Duration, String> {
+    let start = Instant::now();
+    match tokio::time::timeout(NET_TIMEOUT, TcpStream::connect((host, port))).await {
+        Ok(Ok(_)) => Ok(start.elapsed()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("No answer within {}s", NET_TIMEOUT.as_secs())),
+    }
+}
+
+/// Resolves `host` with the system resolver; returns its addresses.
+pub async fn lookup(host: &str) -> Result<Vec<String>, String> {
+    let addresses = tokio::time::timeout(NET_TIMEOUT, lookup_host((host, 0)))
+        .await
+        .map_err(|_| format!("No answer within {}s", NET_TIMEOUT.as_secs()))?
+        .map_err(|e| e.to_string())?;
+    let mut ips: Vec<String> = addresses.map(|a| a.ip().to_string()).collect();
+    ips.dedup();
+    Ok(ips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ping_summary_on_linux_and_windows() {
+        let linux = "PING 10.0.0.5 (10.0.0.5) 56(84) bytes of data.\n\
+                     64 bytes from 10.0.0.5: icmp_seq=1 ttl=64 time=0.4 ms\n\n\
+                     --- 10.0.0.5 ping statistics ---\n\
+                     4 packets transmitted, 4 received, 0% packet loss, time 3004ms\n\
+                     rtt min/avg/max/mdev = 0.3/0.4/0.5/0.1 ms\n";
+        assert_eq!(
+            ping_summary(linux).as_deref(),
+            Some(
+                "4 packets transmitted, 4 received, 0% packet loss, time 3004ms | \
+                 rtt min/avg/max/mdev = 0.3/0.4/0.5/0.1 ms"
+            )
+        );
+
+        let windows = "Ping statistics for 10.0.0.5:\r\n    \
+                       Packets: Sent = 4, Received = 0, Lost = 4 (100% loss),\r\n";
+        assert_eq!(
+            ping_summary(windows).as_deref(),
+            Some("Packets: Sent = 4, Received = 0, Lost = 4 (100% loss),")
+        );
+        assert_eq!(ping_summary("ping: unknown host"), None);
+    }
+}
@@ -0,0 +1,37 @@
+use std::process::{Command, Stdio};
+
+/// Shows a desktop notification using the platform's command-line notifier
+/// (`notify-send` on Linux, `osascript` on macOS). Runs in the background and
+/// only logs failures, since a missing notifier shouldn't interrupt the TUI.
+pub fn desktop_notification(summary: &str, body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            body.replace('"', "\\\""),
+            summary.replace('"', "\\\"")
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=Kyber TUI").arg(summary).arg(body);
+        command
+    } else {
+        tracing::debug!(%summary, "desktop notifications not supported on this platform");
+        return;
+    };
+
+    let spawned = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        // Reap the notifier off the UI thread
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => tracing::warn!(error = %e, "failed to send desktop notification"),
+    }
+}
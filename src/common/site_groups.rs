@@ -0,0 +1,148 @@
+use crate::api::datto::types::Site;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// A `[[site_groups]]` entry: a collapsible section of the site list. Sites
+/// whose name matches `pattern` go into the section called `name`; with
+/// `variable`, sites are grouped by that site variable's value instead (one
+/// section per value, or a single section called `name` when it's set).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SiteGroupFields", into = "SiteGroupFields")]
+pub struct SiteGroup {
+    fields: SiteGroupFields,
+    /// `fields.pattern`, compiled case-insensitively
+    pattern: Option<Regex>,
+}
+
+/// `SiteGroup` as written in the config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteGroupFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+}
+
+impl TryFrom<SiteGroupFields> for SiteGroup {
+    type Error = String;
+
+    fn try_from(fields: SiteGroupFields) -> Result<Self, String> {
+        let pattern = match (&fields.pattern, &fields.variable) {
+            (Some(pattern), None) => {
+                if fields.name.is_none() {
+                    return Err(format!("site group '{}' needs a name", pattern));
+                }
+                let regex = RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("site group pattern '{}': {}", pattern, e))?;
+                Some(regex)
+            }
+            (None, Some(_)) => None,
+            _ => return Err("a site group needs either a pattern or a variable".to_string()),
+        };
+        Ok(Self { fields, pattern })
+    }
+}
+
+impl From<SiteGroup> for SiteGroupFields {
+    fn from(group: SiteGroup) -> Self {
+        group.fields
+    }
+}
+
+impl PartialEq for SiteGroup {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+    }
+}
+
+impl SiteGroup {
+    /// Title of this group's section for `site`, or `None` if the site isn't in it.
+    pub fn title_for(&self, site: &Site) -> Option<String> {
+        if let Some(pattern) = &self.pattern {
+            return self.fields.name.clone().filter(|_| pattern.is_match(&site.name));
+        }
+        let variable = self.fields.variable.as_deref()?;
+        let value = site
+            .variables
+            .as_deref()?
+            .iter()
+            .find(|v| v.name == variable)
+            .map(|v| v.value.trim())
+            .filter(|value| !value.is_empty())?;
+        Some(self.fields.name.clone().unwrap_or_else(|| value.to_string()))
+    }
+}
+
+/// The first group `site` falls into, as (index into `groups`, section title).
+pub fn site_group(groups: &[SiteGroup], site: &Site) -> Option<(usize, String)> {
+    groups
+        .iter()
+        .enumerate()
+        .find_map(|(i, group)| group.title_for(site).map(|title| (i, title)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::types::SiteVariable;
+
+    fn site(name: &str, vertical: Option<&str>) -> Site {
+        let mut site: Site = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "uid": name,
+            "name": name,
+        }))
+        .unwrap();
+        site.variables = Some(
+            vertical
+                .map(|value| SiteVariable {
+                    id: 1,
+                    name: "vertical".to_string(),
+                    value: value.to_string(),
+                    masked: false,
+                })
+                .into_iter()
+                .collect(),
+        );
+        site
+    }
+
+    #[test]
+    fn test_sites_fall_into_the_first_matching_group() {
+        let groups = vec![
+            SiteGroup::try_from(SiteGroupFields {
+                name: Some("Healthcare".to_string()),
+                pattern: Some("clinic|medical".to_string()),
+                variable: None,
+            })
+            .unwrap(),
+            SiteGroup::try_from(SiteGroupFields {
+                variable: Some("vertical".to_string()),
+                ..Default::default()
+            })
+            .unwrap(),
+        ];
+
+        let group = |name, vertical| site_group(&groups, &site(name, vertical));
+        assert_eq!(group("Northside CLINIC", Some("Legal")), Some((0, "Healthcare".to_string())));
+        assert_eq!(group("Smith & Jones", Some("Legal")), Some((1, "Legal".to_string())));
+        assert_eq!(group("Acme", Some(" ")), None);
+
+        let invalid = SiteGroup::try_from(SiteGroupFields {
+            pattern: Some("clinic".to_string()),
+            ..Default::default()
+        });
+        assert!(invalid.is_err());
+        let invalid = SiteGroup::try_from(SiteGroupFields {
+            name: Some("Broken".to_string()),
+            pattern: Some("(".to_string()),
+            ..Default::default()
+        });
+        assert!(invalid.is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::api::datto::types::SiteVariable;
+use crate::ui::theme;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// A `[[site_rules]]` entry: sites matching `when` get `color` and/or `badge`
+/// in the site list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiteRule {
+    pub when: Condition,
+    /// Same names as the `tuiColor` variable (see [`named_color`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Short text shown after the site name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub badge: Option<String>,
+}
+
+/// What rules can test about a site.
+#[derive(Debug, Default)]
+pub struct SiteFacts<'a> {
+    pub devices: i64,
+    pub online_devices: i64,
+    pub offline_devices: i64,
+    /// Only known once account devices are loaded (reports or the watchlist)
+    pub offline_servers: i64,
+    pub active_incidents: i64,
+    pub resolved_incidents: i64,
+    pub variables: &'a [SiteVariable],
+}
+
+/// A rule expression such as `active_incidents > 0 and variable tuiMdrId`:
+/// clauses joined with `and`. Kept as text in the config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Condition {
+    text: String,
+    clauses: Vec<Clause>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Clause {
+    Compare(Metric, Op, i64),
+    /// Variable set to a non-empty value
+    HasVariable(String),
+    Variable(String, bool, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Metric {
+    Devices,
+    OnlineDevices,
+    OfflineDevices,
+    OfflineServers,
+    ActiveIncidents,
+    ResolvedIncidents,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl Metric {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "devices" => Metric::Devices,
+            "online_devices" => Metric::OnlineDevices,
+            "offline_devices" => Metric::OfflineDevices,
+            "offline_servers" => Metric::OfflineServers,
+            "active_incidents" => Metric::ActiveIncidents,
+            "resolved_incidents" => Metric::ResolvedIncidents,
+            _ => return None,
+        })
+    }
+
+    fn value(self, facts: &SiteFacts) -> i64 {
+        match self {
+            Metric::Devices => facts.devices,
+            Metric::OnlineDevices => facts.online_devices,
+            Metric::OfflineDevices => facts.offline_devices,
+            Metric::OfflineServers => facts.offline_servers,
+            Metric::ActiveIncidents => facts.active_incidents,
+            Metric::ResolvedIncidents => facts.resolved_incidents,
+        }
+    }
+}
+
+impl Op {
+    fn parse(op: &str) -> Option<Self> {
+        Some(match op {
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            "==" => Op::Eq,
+            "!=" => Op::Ne,
+            _ => return None,
+        })
+    }
+
+    fn test(self, a: i64, b: i64) -> bool {
+        match self {
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+        }
+    }
+}
+
+fn parse_clause(text: &str) -> Result<Clause, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    match words.as_slice() {
+        ["variable", name] => Ok(Clause::HasVariable(name.to_string())),
+        ["variable", name, op @ ("==" | "!="), value @ ..] if !value.is_empty() => {
+            let value = value.join(" ");
+            let value = value.trim_matches('"');
+            Ok(Clause::Variable(
+                name.to_string(),
+                *op == "==",
+                value.to_string(),
+            ))
+        }
+        [metric, op, number] => {
+            let metric =
+                Metric::parse(metric).ok_or_else(|| format!("unknown value '{}'", metric))?;
+            let op = Op::parse(op).ok_or_else(|| format!("unknown operator '{}'", op))?;
+            let number = number
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", number))?;
+            Ok(Clause::Compare(metric, op, number))
+        }
+        _ => Err(format!("can't read '{}'", text)),
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        let clauses = text
+            .split(" and ")
+            .map(parse_clause)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("site rule '{}': {}", text, e))?;
+        Ok(Self { text, clauses })
+    }
+}
+
+impl From<Condition> for String {
+    fn from(condition: Condition) -> Self {
+        condition.text
+    }
+}
+
+impl Condition {
+    pub fn matches(&self, facts: &SiteFacts) -> bool {
+        self.clauses.iter().all(|clause| match clause {
+            Clause::Compare(metric, op, number) => op.test(metric.value(facts), *number),
+            Clause::HasVariable(name) => facts
+                .variables
+                .iter()
+                .any(|v| v.name == *name && !v.value.is_empty()),
+            Clause::Variable(name, equal, value) => {
+                let actual = facts.variables.iter().find(|v| v.name == *name);
+                actual.is_some_and(|v| v.value == *value) == *equal
+            }
+        })
+    }
+}
+
+/// Color for a `tuiColor` or site rule color name.
+pub fn named_color(name: &str) -> Option<Color> {
+    let theme = theme::current();
+    match name.to_lowercase().as_str() {
+        "red" => Some(theme.error),
+        "blue" => Some(theme.info),
+        "green" => Some(theme.success),
+        "yellow" => Some(theme.warning),
+        "magenta" => Some(theme.special),
+        "cyan" => Some(theme.accent),
+        "white" => Some(theme.text),
+        "gray" => Some(theme.muted),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(text: &str) -> Condition {
+        Condition::try_from(text.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_conditions() {
+        let variables = vec![SiteVariable {
+            id: 1,
+            name: "tuiMdrProvider".to_string(),
+            value: "Sophos".to_string(),
+            masked: false,
+        }];
+        let facts = SiteFacts {
+            active_incidents: 2,
+            offline_servers: 0,
+            variables: &variables,
+            ..Default::default()
+        };
+
+        assert!(condition("active_incidents > 0").matches(&facts));
+        assert!(!condition("active_incidents > 0 and offline_servers >= 1").matches(&facts));
+        assert!(condition("variable tuiMdrProvider").matches(&facts));
+        assert!(condition("variable tuiMdrProvider == \"Sophos\"").matches(&facts));
+        assert!(condition("variable tuiMdrId != Sophos").matches(&facts));
+        assert!(!condition("variable tuiMdrId").matches(&facts));
+
+        assert!(Condition::try_from("incidents > 0".to_string()).is_err());
+        assert!(Condition::try_from("devices > many".to_string()).is_err());
+    }
+}
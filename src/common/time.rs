@@ -0,0 +1,266 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Offset, Utc};
+use chrono_tz::Tz;
+use serde_json::Value;
+use std::sync::RwLock;
+
+/// The zone timestamps are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TimeZoneSetting {
+    /// The system's zone, from `TZ` or the OS settings
+    #[default]
+    Local,
+    Utc,
+    /// A fixed offset from UTC, e.g. `-05:00`
+    Fixed(FixedOffset),
+    /// An IANA zone such as `America/Chicago`, which follows its daylight
+    /// saving changes
+    Named(Tz),
+}
+
+impl TimeZoneSetting {
+    /// Reads `local` (or `auto`), `utc`, an offset such as `+02:00`,
+    /// `-0530`, `+2` or `UTC-5`, or an IANA zone name such as
+    /// `Europe/London` (case-sensitive).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "local" | "auto" => Some(TimeZoneSetting::Local),
+            "utc" | "gmt" | "z" => Some(TimeZoneSetting::Utc),
+            offset => parse_offset(offset)
+                .map(TimeZoneSetting::Fixed)
+                .or_else(|| value.trim().parse().ok().map(TimeZoneSetting::Named)),
+        }
+    }
+
+    /// The zone's offset at `dt`; the local one follows daylight saving.
+    fn offset_at(self, dt: DateTime<Utc>) -> FixedOffset {
+        match self {
+            TimeZoneSetting::Local => dt.with_timezone(&Local).offset().fix(),
+            TimeZoneSetting::Utc => Utc.fix(),
+            TimeZoneSetting::Fixed(offset) => offset,
+            TimeZoneSetting::Named(tz) => dt.with_timezone(&tz).offset().fix(),
+        }
+    }
+
+    /// `dt` as the wall-clock time in this zone.
+    pub fn at(self, dt: DateTime<Utc>) -> DateTime<FixedOffset> {
+        dt.with_timezone(&self.offset_at(dt))
+    }
+}
+
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.strip_prefix("utc").or_else(|| value.strip_prefix("gmt")).unwrap_or(value);
+    let (sign, rest) = match value.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some(parts) => parts,
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// How timestamps are rendered, from the `timezone` and `relative_times`
+/// config settings.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TimeSettings {
+    pub zone: TimeZoneSetting,
+    /// Show "2h ago" instead of the date and time
+    pub relative: bool,
+}
+
+static CURRENT: RwLock<TimeSettings> =
+    RwLock::new(TimeSettings { zone: TimeZoneSetting::Local, relative: false });
+
+/// The active settings. Render functions read these once per timestamp.
+pub fn settings() -> TimeSettings {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set(settings: TimeSettings) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+/// Epoch values above this are taken as milliseconds: in seconds it would be
+/// the year 2286, while in milliseconds it's April 1970.
+const MILLIS_THRESHOLD: f64 = 10_000_000_000.0;
+
+/// Reads a timestamp the API sent as a JSON number or string: epoch seconds
+/// or milliseconds, possibly fractional or quoted, or an ISO 8601 date.
+///
+/// # Returns
+/// The UTC time, or `None` for anything else, including zero and negative
+/// epochs, which the API uses for "never".
+pub fn parse_timestamp(val: &Value) -> Option<DateTime<Utc>> {
+    match val {
+        Value::Number(n) => n.as_f64().and_then(from_epoch),
+        Value::String(s) => parse_timestamp_str(s),
+        _ => None,
+    }
+}
+
+/// An epoch in seconds or milliseconds (see `MILLIS_THRESHOLD`).
+pub fn from_epoch(epoch: f64) -> Option<DateTime<Utc>> {
+    if !epoch.is_finite() || epoch <= 0.0 {
+        return None;
+    }
+    let millis = if epoch > MILLIS_THRESHOLD { epoch } else { epoch * 1000.0 };
+    DateTime::from_timestamp_millis(millis.round() as i64)
+}
+
+/// A timestamp string: RFC 3339, an ISO date and time without an offset
+/// (taken as UTC, as the API sends them), or an epoch in digits.
+pub fn parse_timestamp_str(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.to_utc());
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(naive.and_utc());
+        }
+    }
+    s.parse::<f64>().ok().and_then(from_epoch)
+}
+
+/// `dt` in the configured zone, for callers that need their own format
+/// (e.g. report exports).
+pub fn in_display_zone(dt: DateTime<Utc>) -> DateTime<FixedOffset> {
+    settings().zone.at(dt)
+}
+
+/// A point in time as shown in panels and tables: "MM/DD/YYYY HH:MMam" in
+/// the configured zone, or "2h ago" with relative times on.
+pub fn format_datetime(dt: DateTime<Utc>) -> String {
+    format_datetime_with(dt, settings(), Utc::now())
+}
+
+fn format_datetime_with(dt: DateTime<Utc>, settings: TimeSettings, now: DateTime<Utc>) -> String {
+    if settings.relative {
+        relative(dt, now)
+    } else {
+        dt.with_timezone(&settings.zone.offset_at(dt)).format("%m/%d/%Y %I:%M%P").to_string()
+    }
+}
+
+/// How long before `now` `dt` was: "just now", "5m ago", "3h ago" or
+/// "2d ago"; times after `now` read "in 3h".
+pub fn relative(dt: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - dt).num_seconds();
+    let span = secs.unsigned_abs();
+    let text = match span {
+        0..60 => return "just now".to_string(),
+        60..3600 => format!("{}m", span / 60),
+        3600..86400 => format!("{}h", span / 3600),
+        _ => format!("{}d", span / 86400),
+    };
+    if secs < 0 {
+        format!("in {}", text)
+    } else {
+        format!("{} ago", text)
+    }
+}
+
+/// Formats a timestamp from the API (seconds, milliseconds or an ISO string)
+/// with `format_datetime`.
+///
+/// # Arguments
+/// * `ts_option` - The raw timestamp value, if the API sent one.
+///
+/// # Returns
+/// The formatted time, the value itself if it's a string that isn't a
+/// timestamp, or "N/A".
+pub fn format_timestamp(ts_option: Option<Value>) -> String {
+    let Some(val) = ts_option else {
+        return "N/A".to_string();
+    };
+    if let Some(dt) = parse_timestamp(&val) {
+        return format_datetime(dt);
+    }
+    match val.as_str() {
+        Some(s) => s.to_string(),
+        None => "N/A".to_string(),
+    }
+}
+
+/// A parsed timestamp with `format_datetime`, or "N/A".
+pub fn format_time(dt: Option<DateTime<Utc>>) -> String {
+    dt.map_or_else(|| "N/A".to_string(), format_datetime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zones_and_relative_times() {
+        let offset = |secs: i32| Some(TimeZoneSetting::Fixed(FixedOffset::east_opt(secs).unwrap()));
+        assert_eq!(TimeZoneSetting::parse("Auto"), Some(TimeZoneSetting::Local));
+        assert_eq!(TimeZoneSetting::parse(" UTC "), Some(TimeZoneSetting::Utc));
+        assert_eq!(TimeZoneSetting::parse("+02:00"), offset(7200));
+        assert_eq!(TimeZoneSetting::parse("-0530"), offset(-19800));
+        assert_eq!(TimeZoneSetting::parse("UTC-5"), offset(-18000));
+        for bad in ["america/chicago", "Mars/Olympus", "+25", "05:00", ""] {
+            assert_eq!(TimeZoneSetting::parse(bad), None, "{:?}", bad);
+        }
+
+        // Named zones follow daylight saving, unlike fixed offsets
+        let zone = TimeZoneSetting::parse("America/Chicago").unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        assert_eq!(zone.offset_at(at("2026-01-15T12:00:00Z")).local_minus_utc(), -6 * 3600);
+        assert_eq!(zone.offset_at(at("2026-07-15T12:00:00Z")).local_minus_utc(), -5 * 3600);
+
+        let now = DateTime::parse_from_rfc3339("2026-01-15T12:00:00Z").unwrap().to_utc();
+        let ago = |secs: i64| now - chrono::Duration::seconds(secs);
+        let chicago = TimeSettings { zone: TimeZoneSetting::parse("-06:00").unwrap(), relative: false };
+        assert_eq!(format_datetime_with(ago(7200), chicago, now), "01/15/2026 04:00am");
+        let relative = TimeSettings { relative: true, ..chicago };
+        assert_eq!(format_datetime_with(ago(7200), relative, now), "2h ago");
+        assert_eq!(format_datetime_with(ago(59), relative, now), "just now");
+        assert_eq!(format_datetime_with(ago(-3 * 86400), relative, now), "in 3d");
+    }
+
+    #[test]
+    fn test_timestamp_edge_cases() {
+        let expected = DateTime::parse_from_rfc3339("2026-01-15T04:27:51Z").unwrap().to_utc();
+        let parse = |v: Value| parse_timestamp(&v);
+        for value in [
+            serde_json::json!(1768451271),
+            serde_json::json!(1768451271000_i64),
+            serde_json::json!(1768451271000.0),
+            serde_json::json!("1768451271000"),
+            serde_json::json!("2026-01-15T04:27:51Z"),
+            serde_json::json!("2026-01-14T22:27:51-06:00"),
+            serde_json::json!("2026-01-15T04:27:51"),
+            serde_json::json!(" 2026-01-15 04:27:51 "),
+        ] {
+            assert_eq!(parse(value.clone()), Some(expected), "{}", value);
+        }
+
+        let fractional = parse(serde_json::json!(1768451271.25)).unwrap();
+        assert_eq!(fractional.timestamp_subsec_millis(), 250);
+        let with_millis = parse(serde_json::json!("2026-01-15T04:27:51.330Z")).unwrap();
+        assert_eq!(with_millis.timestamp_subsec_millis(), 330);
+
+        for value in [
+            serde_json::json!(0),
+            serde_json::json!(-1),
+            serde_json::json!(""),
+            serde_json::json!("never"),
+            serde_json::json!("2026-02-30T00:00:00Z"),
+            serde_json::json!(true),
+            Value::Null,
+        ] {
+            assert_eq!(parse(value.clone()), None, "{}", value);
+        }
+        assert_eq!(from_epoch(f64::NAN), None);
+        assert_eq!(from_epoch(f64::INFINITY), None);
+    }
+}
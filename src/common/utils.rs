@@ -1,3 +1,4 @@
+use crate::ui::theme;
 use chrono::DateTime;
 use ratatui::{
     prelude::*,
@@ -7,44 +8,99 @@ use ratatui::{
     },
 };
 
-/// Formats a timestamp from a serde_json::Value (either milliseconds or ISO string)
-/// into a human-readable date/time string in the Central US timezone.
+/// Formats how long ago a timestamp was, for stale-data indicators.
 ///
 /// # Arguments
-/// * `ts_option` - An Option containing a serde_json::Value representing the timestamp.
+/// * `ts` - The UTC time the data was fetched.
 ///
 /// # Returns
-/// A formatted string "MM/DD/YYYY HH:MMam/pm" or "N/A" if invalid.
-pub fn format_timestamp(ts_option: Option<serde_json::Value>) -> String {
-    if let Some(val) = ts_option {
-        if let Some(ts_f64) = val.as_f64() {
-            // Check if milliseconds (likely) or seconds
-            // 2026 timestamp: 1768448871000 is definitely millis (13 digits)
-            // Anything > 10,000,000,000 is likely millis
-            let (seconds, nanoseconds) = if ts_f64 > 10_000_000_000.0 {
-                let s = (ts_f64 / 1000.0) as i64;
-                let n = ((ts_f64 % 1000.0) * 1_000_000.0) as u32;
-                (s, n)
-            } else {
-                let s = ts_f64 as i64;
-                let n = ((ts_f64 - s as f64) * 1_000_000_000.0) as u32;
-                (s, n)
-            };
+/// A short string such as "just now", "5m ago", "3h ago" or "2d ago".
+pub fn format_age(ts: DateTime<chrono::Utc>) -> String {
+    super::time::relative(ts.min(chrono::Utc::now()), chrono::Utc::now())
+}
 
-            if let Some(dt) = DateTime::from_timestamp(seconds, nanoseconds) {
-                let local_dt = dt.with_timezone(&chrono::Local);
-                return local_dt.format("%m/%d/%Y %I:%M%P").to_string();
-            }
-        } else if let Some(s) = val.as_str() {
-            // Try to parse ISO string
-            if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
-                let local_dt = dt.with_timezone(&chrono::Local);
-                return local_dt.format("%m/%d/%Y %I:%M%P").to_string();
-            }
-            return s.to_string();
-        }
+/// Formats a duration in its two largest units, for ages in tables.
+///
+/// # Arguments
+/// * `secs` - The duration in seconds; negative counts as zero.
+///
+/// # Returns
+/// A short string such as "now", "45s", "12m", "2h 5m" or "3d 4h".
+pub fn format_duration_short(secs: i64) -> String {
+    let secs = secs.max(0);
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    match secs {
+        0 => "now".to_string(),
+        1..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", minutes),
+        3600..86400 if minutes == 0 => format!("{}h", hours),
+        3600..86400 => format!("{}h {}m", hours, minutes),
+        _ if hours == 0 => format!("{}d", days),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// Case-insensitive fuzzy match: every character of `query` appears in `text`
+/// in order, not necessarily adjacent ("acm" matches "Acme Corp").
+///
+/// # Arguments
+/// * `query` - The typed filter; an empty query matches everything.
+/// * `text` - The candidate string.
+///
+/// # Returns
+/// True if `text` matches.
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|q| chars.any(|c| c == q))
+}
+
+/// Where `query` first appears in `text`, ignoring ASCII case.
+///
+/// # Arguments
+/// * `query` - The searched text; an empty query never matches.
+/// * `text` - The candidate string.
+///
+/// # Returns
+/// The byte range of the match in `text`, if there is one.
+pub fn find_ignore_case(query: &str, text: &str) -> Option<std::ops::Range<usize>> {
+    if query.is_empty() {
+        return None;
+    }
+    let start = text.to_ascii_lowercase().find(&query.to_ascii_lowercase())?;
+    Some(start..start + query.len())
+}
+
+/// How well `text` matches `query`: 3 when equal, 2 for a prefix, 1 for a
+/// substring and 0 otherwise, ignoring ASCII case.
+pub fn match_quality(query: &str, text: &str) -> u8 {
+    match find_ignore_case(query, text) {
+        Some(range) if range.start == 0 && range.end == text.len() => 3,
+        Some(range) if range.start == 0 => 2,
+        Some(_) => 1,
+        None => 0,
+    }
+}
+
+/// Text for a loading indicator: the spinner frame for the current tick,
+/// noting API requests that are backing off before a retry.
+///
+/// # Arguments
+/// * `text` - The usual loading message.
+///
+/// # Returns
+/// `text` behind the spinner, with the number of retrying requests appended
+/// while there are any.
+pub fn loading_text(text: &str) -> String {
+    let spinner = crate::ui::loading::spinner();
+    match crate::api::throttle::retrying() {
+        0 => format!("{} {}", spinner, text),
+        1 => format!("{} {} (retrying a failed request)", spinner, text),
+        n => format!("{} {} (retrying {} failed requests)", spinner, text, n),
     }
-    "N/A".to_string()
 }
 
 /// Calculates a centered rectangle of a given percentage size within another Rect.
@@ -94,6 +150,7 @@ pub fn draw_pie_chart(
     total: i32,
     data: &[(f64, Color, &str)],
 ) {
+    let theme = theme::current();
     let canvas = Canvas::default()
         .block(Block::default().borders(Borders::ALL).title(title))
         .x_bounds([-100.0, 100.0])
@@ -103,7 +160,7 @@ pub fn draw_pie_chart(
                 ctx.print(
                     0.0,
                     0.0,
-                    Span::styled("No Data", Style::default().fg(Color::DarkGray)),
+                    Span::styled("No Data", Style::default().fg(theme.dim)),
                 );
                 return;
             }
@@ -156,8 +213,8 @@ pub fn draw_pie_chart(
                     total_str,
                     Style::default()
                         .add_modifier(Modifier::BOLD)
-                        .fg(Color::White)
-                        .bg(Color::Black),
+                        .fg(theme.text)
+                        .bg(theme.background),
                 ),
             );
         });
@@ -182,13 +239,6 @@ pub fn open_browser(url: &str) {
     };
 
     if let Err(e) = result {
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("debug.log")
-            .map(|mut f| {
-                use std::io::Write;
-                writeln!(f, "Failed to open browser: {}", e).unwrap();
-            });
+        tracing::warn!(error = %e, "failed to open browser");
     }
 }
@@ -0,0 +1,108 @@
+use crate::ui::theme;
+use chrono::NaiveDate;
+use ratatui::style::Color;
+
+/// Warranty end dates within this many days count as "coming up".
+pub const EXPIRING_SOON_DAYS: i64 = 30;
+
+/// How a device's warranty date compares to today.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarrantyStatus {
+    /// No warranty date recorded
+    Missing,
+    /// Set but not a `YYYY-MM-DD` date
+    Invalid,
+    Expired,
+    ExpiringSoon,
+    Ok,
+}
+
+impl WarrantyStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            WarrantyStatus::Missing => "Missing",
+            WarrantyStatus::Invalid => "Invalid",
+            WarrantyStatus::Expired => "Expired",
+            WarrantyStatus::ExpiringSoon => "Expiring",
+            WarrantyStatus::Ok => "OK",
+        }
+    }
+
+    /// Color used for the warranty indicator on the device detail view and in reports.
+    pub fn color(self) -> Color {
+        let theme = theme::current();
+        match self {
+            WarrantyStatus::Missing | WarrantyStatus::Expired => theme.error,
+            WarrantyStatus::ExpiringSoon => theme.warning,
+            WarrantyStatus::Ok => theme.success,
+            WarrantyStatus::Invalid => theme.text,
+        }
+    }
+}
+
+/// Parses a Datto `warrantyDate` ("YYYY-MM-DD").
+pub fn parse_warranty_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Builds the date to write from the warranty popup's (year, month, day)
+/// segments: `None` when all are empty, which clears the warranty.
+pub fn compose_warranty_date(segments: &[String; 3]) -> Result<Option<String>, String> {
+    if segments.iter().all(String::is_empty) {
+        return Ok(None);
+    }
+    let [year, month, day] = segments;
+    let date = format!("{}-{}-{}", year, month, day);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return Err("Invalid date format. Use YYYY-MM-DD".to_string());
+    }
+    match parse_warranty_date(&date) {
+        Some(_) => Ok(Some(date)),
+        None => Err(format!("{} is not a date", date)),
+    }
+}
+
+/// Classifies a warranty date relative to `today`; dates within `soon_days` are
+/// [`WarrantyStatus::ExpiringSoon`].
+///
+/// # Arguments
+/// * `date` - The device's `warranty_date`, if any.
+/// * `today` - The local date to compare against.
+/// * `soon_days` - How many days ahead counts as expiring soon.
+pub fn warranty_status(date: Option<&str>, today: NaiveDate, soon_days: i64) -> WarrantyStatus {
+    let Some(date) = date else {
+        return WarrantyStatus::Missing;
+    };
+    let Some(date) = parse_warranty_date(date) else {
+        return WarrantyStatus::Invalid;
+    };
+    let days = date.signed_duration_since(today).num_days();
+    if days < 0 {
+        WarrantyStatus::Expired
+    } else if days <= soon_days {
+        WarrantyStatus::ExpiringSoon
+    } else {
+        WarrantyStatus::Ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composed_date_must_exist() {
+        let segments = |y: &str, m: &str, d: &str| [y.to_string(), m.to_string(), d.to_string()];
+        assert_eq!(compose_warranty_date(&Default::default()), Ok(None));
+        assert_eq!(
+            compose_warranty_date(&segments("2028", "02", "29")),
+            Ok(Some("2028-02-29".to_string()))
+        );
+        assert_eq!(
+            compose_warranty_date(&segments("2027", "02", "29")),
+            Err("2027-02-29 is not a date".to_string())
+        );
+        assert!(compose_warranty_date(&segments("2027", "2", "01")).is_err());
+        assert!(compose_warranty_date(&segments("", "", "01")).is_err());
+    }
+}
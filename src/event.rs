@@ -1,4 +1,7 @@
 use crate::api::datto::types::{ActivityLogsResponse, DevicesResponse, JobResult, SitesResponse};
+use crate::api::error::ApiError;
+use crate::app::ConfigState;
+use crate::wizard::Provider;
 use anyhow::Result;
 use crossterm::event::{Event as CrosstermEvent, EventStream, KeyEvent, MouseEvent};
 use futures::{FutureExt, StreamExt};
@@ -10,58 +13,230 @@ pub enum Event {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Resize(u16, u16),
-    SitesFetched(Result<SitesResponse, String>),
-    DevicesFetched(String, Result<DevicesResponse, String>),
-    IncidentsFetched(Result<Vec<crate::api::rocket_cyber::types::Incident>, String>),
+    SitesFetched(Result<SitesResponse, ApiError>),
+    /// (Fetch generation, Site UID, Result); see `app::Fetches` for the generation
+    DevicesFetched(u64, String, Result<DevicesResponse, ApiError>),
+    /// (Generation, Site UID, loaded, total if known)
+    DevicesProgress(u64, String, usize, Option<usize>),
+    AccountDevicesFetched(Result<DevicesResponse, ApiError>),
+    AccountDevicesProgress(usize, Option<usize>), // (loaded, total if known)
+    IncidentsFetched(Result<Vec<crate::api::rocket_cyber::types::Incident>, ApiError>),
     SiteVariablesFetched(
         String,
-        Result<Vec<crate::api::datto::types::SiteVariable>, String>,
+        Result<Vec<crate::api::datto::types::SiteVariable>, ApiError>,
     ), // (Site UID, Result)
     VariableCreated(
         String,
-        Result<crate::api::datto::types::SiteVariable, String>,
+        Result<crate::api::datto::types::SiteVariable, ApiError>,
     ),
     VariableUpdated(
         String,
-        Result<crate::api::datto::types::SiteVariable, String>,
+        Result<crate::api::datto::types::SiteVariable, ApiError>,
     ),
-    SiteUpdated(Result<crate::api::datto::types::Site, String>),
-    SophosCasesFetched(String, Result<Vec<crate::api::sophos::Case>, String>),
-    SophosEndpointsFetched(String, Result<Vec<crate::api::sophos::Endpoint>, String>), // (Hostname, Result)
-    SophosScanStarted(String, Result<(), String>), // (Hostname, Result)
+    VariablesWritten(String, Result<usize, ApiError>), // (Site UID, variables written)
+    VariableDeleted(String, Result<i32, ApiError>), // (Site UID, variable ID)
+    AccountVariablesFetched(Result<Vec<crate::api::datto::types::SiteVariable>, ApiError>),
+    AccountVariableCreated(Result<crate::api::datto::types::SiteVariable, ApiError>),
+    AccountVariableUpdated(Result<crate::api::datto::types::SiteVariable, ApiError>),
+    AccountVariableDeleted(Result<i32, ApiError>), // (Variable ID)
+    SiteUpdated(Result<crate::api::datto::types::Site, ApiError>),
+    SophosCasesFetched(String, Result<Vec<crate::api::sophos::Case>, ApiError>),
+    SophosEndpointsFetched(String, Result<Vec<crate::api::sophos::Endpoint>, ApiError>), // (Hostname, Result)
+    SophosScanStarted(u64, Result<(), ApiError>), // (Scan id, Result)
+    SophosEndpointIsolated(String, Result<(), ApiError>), // (Hostname, Result)
     DattoAvAgentFetched(
         String,
-        Result<crate::api::datto_av::types::AgentDetail, String>,
+        Result<crate::api::datto_av::types::AgentDetail, ApiError>,
     ), // (Hostname, Result)
-    DattoAvScanStarted(String, Result<(), String>), // (Hostname, Result)
-    ScanStatusChanged(String, ScanStatus),
+    DattoAvScanStarted(u64, Result<(), ApiError>), // (Scan id, Result)
     DattoAvAlertsFetched(
         String,
-        Result<Vec<crate::api::datto_av::types::Alert>, String>,
+        Result<Vec<crate::api::datto_av::types::Alert>, ApiError>,
     ),
-    DattoAvPoliciesFetched(String, Result<serde_json::Value, String>),
+    DattoAvPoliciesFetched(String, Result<serde_json::Value, ApiError>),
+    /// (Hostname, Result)
+    DefenderMachinesFetched(String, Result<Vec<crate::api::defender::types::Machine>, ApiError>),
+    /// (Hostname, Result)
+    DefenderAlertsFetched(String, Result<Vec<crate::api::defender::types::Alert>, ApiError>),
+    /// (Scan id, the scan's machine action)
+    DefenderScanStarted(u64, Result<crate::api::defender::types::MachineAction, ApiError>),
+    DefenderScanPolled(u64, Result<ScanProgress, ApiError>), // (Scan id, Result)
+    /// (Hostname, Result)
+    SentinelOneAgentFetched(String, Result<crate::api::sentinelone::types::Agent, ApiError>),
+    SentinelOneThreatsFetched(
+        String,
+        Result<Vec<crate::api::sentinelone::types::Threat>, ApiError>,
+    ), // (Hostname, Result)
+    SentinelOneScanStarted(u64, Result<String, ApiError>), // (Scan id, agent UUID)
+    SentinelOneScanPolled(u64, Result<ScanProgress, ApiError>), // (Scan id, Result)
+    SentinelOneAgentDisconnected(String, Result<(), ApiError>), // (Hostname, Result)
     RocketCyberAgentFetched(
         String,
-        Result<Option<crate::api::rocket_cyber::types::Agent>, String>,
+        Result<Option<crate::api::rocket_cyber::types::Agent>, ApiError>,
+    ),
+    /// A page of the device search, 0 for the first
+    DeviceSearchResultsFetched(i32, Result<DevicesResponse, ApiError>),
+    /// (Generation, DeviceUID, Result)
+    ActivityLogsFetched(u64, String, Result<ActivityLogsResponse, ApiError>),
+    /// Live fetch of new activities: (Generation, DeviceUID, end of the range fetched, Result)
+    ActivityLogsPolled(
+        u64,
+        String,
+        chrono::DateTime<chrono::Utc>,
+        Result<ActivityLogsResponse, ApiError>,
+    ),
+    /// (Generation, DeviceUID, Result)
+    OpenAlertsFetched(u64, String, Result<Vec<crate::api::datto::types::Alert>, ApiError>),
+    /// Alerts resolved within the lookback window: (Generation, DeviceUID, Result)
+    ResolvedAlertsFetched(u64, String, Result<Vec<crate::api::datto::types::Alert>, ApiError>),
+    /// (Generation, SiteUID, Result)
+    SiteOpenAlertsFetched(u64, String, Result<Vec<crate::api::datto::types::Alert>, ApiError>),
+    /// Open alerts of the whole account, for the Notification Center
+    AccountAlertsFetched(Result<Vec<crate::api::datto::types::Alert>, ApiError>),
+    JobResultFetched(u64, Result<JobResult, ApiError>), // (Generation, Result)
+    /// Outputs of a job, shown for one of its components (ComponentUID, Result)
+    JobStdOutFetched(String, Result<Vec<crate::api::datto::types::JobStdOutput>, ApiError>),
+    JobStdErrFetched(String, Result<Vec<crate::api::datto::types::JobStdOutput>, ApiError>),
+    /// Components of a job to re-run (JobUID, Result)
+    JobComponentsFetched(String, Result<Vec<crate::api::datto::types::JobComponent>, ApiError>),
+    JobCancelled(String, Result<(), ApiError>), // (JobUID, Result)
+    ComponentsFetched(Result<crate::api::datto::types::ComponentsResponse, ApiError>),
+    QuickJobExecuted(Result<crate::api::datto::types::QuickJobResponse, ApiError>),
+    /// Status poll of the job started from the Run Component popup (JobUID, Result)
+    JobStatusPolled(String, Result<JobResult, ApiError>),
+    /// Job result and the followed output stream (JobUID, Result)
+    JobOutputFollowed(
+        String,
+        Result<(JobResult, Vec<crate::api::datto::types::JobStdOutput>), ApiError>,
     ),
-    DeviceSearchResultsFetched(Result<DevicesResponse, String>),
-    ActivityLogsFetched(Result<ActivityLogsResponse, String>),
-    OpenAlertsFetched(String, Result<Vec<crate::api::datto::types::Alert>, String>), // (DeviceUID, Result)
-    SiteOpenAlertsFetched(String, Result<Vec<crate::api::datto::types::Alert>, String>), // (SiteUID, Result)
-    JobResultFetched(Result<JobResult, String>),
-    JobStdOutFetched(Result<Vec<crate::api::datto::types::JobStdOutput>, String>),
-    JobStdErrFetched(Result<Vec<crate::api::datto::types::JobStdOutput>, String>),
-    ComponentsFetched(Result<crate::api::datto::types::ComponentsResponse, String>),
-    QuickJobExecuted(Result<crate::api::datto::types::QuickJobResponse, String>),
-    DeviceMoved(Result<(), String>),
-    WarrantyUpdated(Result<(), String>),
-    DeviceSoftwareFetched(String, Result<Vec<crate::api::datto::types::Software>, String>),
+    /// Job UID of the created reboot job
+    RebootScheduled(Result<String, ApiError>),
+    /// One device's job or reboot of a collection run (Run ID, Hostname, Result)
+    CollectionActionDone(u64, String, Result<(), ApiError>),
+    DeviceUdfUpdated(String, Result<(), ApiError>), // (Hostname, Result)
+    DeviceMoved(Result<(), ApiError>),
+    /// The warranty date written, `None` if cleared: (Device UID, Result)
+    WarrantyUpdated(String, Result<Option<String>, ApiError>),
+    /// (Device UID, the fields written)
+    DeviceUpdated(String, Result<crate::api::datto::types::UpdateDeviceRequest, ApiError>),
+    DeviceDecommissioned(String, Result<(), ApiError>), // (Device UID, Result)
+    SiteDeleted(String, Result<(), ApiError>),          // (Site UID, Result)
+    AlertResolved(String, Result<(), ApiError>),        // (Alert UID, Result)
+    /// The proxy settings saved, `None` if removed: (Site UID, Result)
+    SiteProxyUpdated(String, Result<Option<crate::api::datto::types::ProxySettings>, ApiError>),
+    NetToolFinished(String, Result<String, String>), // (Check label, Result)
+    /// Boxed, as a whole device would make every event as large: (Device UID)
+    WatchedDevicePolled(String, Result<Box<crate::api::datto::types::Device>, ApiError>),
+    SophosEndpointDeleted(String, Result<(), ApiError>), // (Hostname, Result)
+    DattoAvAgentDeleted(String, Result<(), ApiError>), // (Hostname, Result)
+    /// (Generation, DeviceUID, Result)
+    DeviceSoftwareFetched(u64, String, Result<Vec<crate::api::datto::types::Software>, ApiError>),
+    /// The clients and settings of a profile switched to: (Profile name, Result)
+    ProfileSwitched(
+        String,
+        Result<(crate::app::ProfileClients, crate::config::ProfileSettings), String>,
+    ),
+    /// Sites of another account for the merged site list: (Profile name, Result)
+    MergedSitesFetched(String, Result<Vec<crate::api::datto::types::Site>, ApiError>),
+    SetupValidated(usize, Result<(), String>), // (Wizard step, Result)
+    /// A background task panicked: (task name, panic message); see `app::Supervisor`
+    TaskFailed(&'static str, String),
+    /// Re-authentication from the Integrations view, with the provider's
+    /// config state as re-read (None if the config couldn't be read)
+    IntegrationAuthenticated(
+        Provider,
+        Option<ConfigState>,
+        Result<crate::app::ProviderClient, String>,
+    ),
+}
+
+/// `Ok` or the error of an event's result.
+fn outcome<T>(result: &Result<T, ApiError>) -> Result<(), &ApiError> {
+    result.as_ref().map(|_| ())
+}
+
+impl Event {
+    /// The provider whose API call produced this event, and how the call went.
+    pub fn api_outcome(&self) -> Option<(Provider, Result<(), &ApiError>)> {
+        Some(match self {
+            Event::SitesFetched(r) => (Provider::Datto, outcome(r)),
+            Event::DevicesFetched(_, _, r) => (Provider::Datto, outcome(r)),
+            Event::AccountDevicesFetched(r) => (Provider::Datto, outcome(r)),
+            Event::SiteVariablesFetched(_, r) => (Provider::Datto, outcome(r)),
+            Event::VariableCreated(_, r) | Event::VariableUpdated(_, r) => {
+                (Provider::Datto, outcome(r))
+            }
+            Event::VariablesWritten(_, r) => (Provider::Datto, outcome(r)),
+            Event::VariableDeleted(_, r) => (Provider::Datto, outcome(r)),
+            Event::AccountVariablesFetched(r) => (Provider::Datto, outcome(r)),
+            Event::AccountVariableCreated(r) | Event::AccountVariableUpdated(r) => {
+                (Provider::Datto, outcome(r))
+            }
+            Event::AccountVariableDeleted(r) => (Provider::Datto, outcome(r)),
+            Event::SiteUpdated(r) => (Provider::Datto, outcome(r)),
+            Event::DeviceSearchResultsFetched(_, r) => (Provider::Datto, outcome(r)),
+            Event::ActivityLogsFetched(_, _, r) => (Provider::Datto, outcome(r)),
+            Event::ActivityLogsPolled(_, _, _, r) => (Provider::Datto, outcome(r)),
+            Event::OpenAlertsFetched(_, _, r)
+            | Event::ResolvedAlertsFetched(_, _, r)
+            | Event::SiteOpenAlertsFetched(_, _, r)
+            | Event::AccountAlertsFetched(r) => (Provider::Datto, outcome(r)),
+            Event::JobResultFetched(_, r) | Event::JobStatusPolled(_, r) => {
+                (Provider::Datto, outcome(r))
+            }
+            Event::JobStdOutFetched(_, r) | Event::JobStdErrFetched(_, r) => {
+                (Provider::Datto, outcome(r))
+            }
+            Event::JobOutputFollowed(_, r) => (Provider::Datto, outcome(r)),
+            Event::JobComponentsFetched(_, r) => (Provider::Datto, outcome(r)),
+            Event::JobCancelled(_, r) => (Provider::Datto, outcome(r)),
+            Event::ComponentsFetched(r) => (Provider::Datto, outcome(r)),
+            Event::QuickJobExecuted(r) => (Provider::Datto, outcome(r)),
+            Event::RebootScheduled(r) => (Provider::Datto, outcome(r)),
+            Event::CollectionActionDone(_, _, r) => (Provider::Datto, outcome(r)),
+            Event::DeviceUdfUpdated(_, r) => (Provider::Datto, outcome(r)),
+            Event::DeviceMoved(r) => (Provider::Datto, outcome(r)),
+            Event::WarrantyUpdated(_, r) => (Provider::Datto, outcome(r)),
+            Event::DeviceUpdated(_, r) => (Provider::Datto, outcome(r)),
+            Event::DeviceDecommissioned(_, r) => (Provider::Datto, outcome(r)),
+            Event::SiteDeleted(_, r) => (Provider::Datto, outcome(r)),
+            Event::AlertResolved(_, r) => (Provider::Datto, outcome(r)),
+            Event::SiteProxyUpdated(_, r) => (Provider::Datto, outcome(r)),
+            Event::WatchedDevicePolled(_, r) => (Provider::Datto, outcome(r)),
+            Event::DeviceSoftwareFetched(_, _, r) => (Provider::Datto, outcome(r)),
+            Event::IncidentsFetched(r) => (Provider::RocketCyber, outcome(r)),
+            Event::RocketCyberAgentFetched(_, r) => (Provider::RocketCyber, outcome(r)),
+            Event::SophosCasesFetched(_, r) => (Provider::Sophos, outcome(r)),
+            Event::SophosEndpointsFetched(_, r) => (Provider::Sophos, outcome(r)),
+            Event::SophosScanStarted(_, r) => (Provider::Sophos, outcome(r)),
+            Event::SophosEndpointIsolated(_, r) => (Provider::Sophos, outcome(r)),
+            Event::SophosEndpointDeleted(_, r) => (Provider::Sophos, outcome(r)),
+            Event::DattoAvAgentFetched(_, r) => (Provider::DattoAv, outcome(r)),
+            Event::DattoAvScanStarted(_, r) => (Provider::DattoAv, outcome(r)),
+            Event::DattoAvAgentDeleted(_, r) => (Provider::DattoAv, outcome(r)),
+            Event::DattoAvAlertsFetched(_, r) => (Provider::DattoAv, outcome(r)),
+            Event::DattoAvPoliciesFetched(_, r) => (Provider::DattoAv, outcome(r)),
+            Event::DefenderMachinesFetched(_, r) => (Provider::Defender, outcome(r)),
+            Event::DefenderAlertsFetched(_, r) => (Provider::Defender, outcome(r)),
+            Event::DefenderScanStarted(_, r) => (Provider::Defender, outcome(r)),
+            Event::DefenderScanPolled(_, r) => (Provider::Defender, outcome(r)),
+            Event::SentinelOneAgentFetched(_, r) => (Provider::SentinelOne, outcome(r)),
+            Event::SentinelOneThreatsFetched(_, r) => (Provider::SentinelOne, outcome(r)),
+            Event::SentinelOneScanStarted(_, r) => (Provider::SentinelOne, outcome(r)),
+            Event::SentinelOneScanPolled(_, r) => (Provider::SentinelOne, outcome(r)),
+            Event::SentinelOneAgentDisconnected(_, r) => (Provider::SentinelOne, outcome(r)),
+            _ => return None,
+        })
+    }
 }
 
+/// What a console reports about a scan started from the TUI.
 #[derive(Debug, Clone, PartialEq)]
-pub enum ScanStatus {
-    Starting,
-    Started,
+pub enum ScanProgress {
+    Running,
+    /// With what the console said about the outcome, if anything
+    Completed(Option<String>),
+    Failed(String),
 }
 
 #[derive(Debug)]
@@ -69,14 +244,31 @@ pub struct EventHandler {
     _tx: mpsc::UnboundedSender<Event>,
     rx: mpsc::UnboundedReceiver<Event>,
     _task: tokio::task::JoinHandle<()>,
+    tick_rate: std::time::Duration,
 }
 
 impl EventHandler {
     pub fn new(tick_rate: std::time::Duration) -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
         let _tx = tx.clone();
-        let task_tx = tx.clone();
-        let _task = tokio::spawn(async move {
+        let _task = Self::spawn_reader(tx, tick_rate);
+        Self { _tx, rx, _task, tick_rate }
+    }
+
+    /// Stops reading the terminal, so a child process run in it gets the input.
+    pub fn pause(&mut self) {
+        self._task.abort();
+    }
+
+    pub fn resume(&mut self) {
+        self._task = Self::spawn_reader(self._tx.clone(), self.tick_rate);
+    }
+
+    fn spawn_reader(
+        task_tx: mpsc::UnboundedSender<Event>,
+        tick_rate: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
             let mut reader = EventStream::new();
             let mut interval = tokio::time::interval(tick_rate);
             loop {
@@ -104,8 +296,7 @@ impl EventHandler {
                     }
                 };
             }
-        });
-        Self { _tx, rx, _task }
+        })
     }
 
     pub fn sender(&self) -> mpsc::UnboundedSender<Event> {
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// How many formatted lines the in-app log viewer keeps.
+const MAX_BUFFERED_LINES: usize = 1000;
+const MAX_LOG_FILES: usize = 7;
+
+static BUFFER: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// `$XDG_STATE_HOME/datto_tui/logs`, falling back to the local data dir on platforms without a state dir.
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|d| d.join("datto_tui").join("logs"))
+}
+
+/// Writer that feeds the in-app log viewer.
+struct BufferWriter;
+
+impl Write for BufferWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        let mut lines = BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+        for line in text.lines() {
+            if lines.len() >= MAX_BUFFERED_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Installs the global subscriber: a daily rolling file in [`log_dir`] plus the in-memory
+/// buffer behind [`recent_lines`]. A bare level such as `debug` applies to this crate only;
+/// full `EnvFilter` directives (e.g. `kyber_tui=debug,reqwest=trace`) are used as-is.
+///
+/// The returned guard flushes the file writer on drop and must be held until exit.
+pub fn init(level: &str) -> Result<WorkerGuard> {
+    let dir = log_dir().context("Could not determine log directory")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let appender = Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("kyber_tui")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .context("Failed to create log file appender")?;
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+
+    let directive = if level.contains('=') {
+        level.to_string()
+    } else {
+        format!("{}={}", env!("CARGO_CRATE_NAME"), level)
+    };
+    let filter = EnvFilter::try_new(&directive)
+        .with_context(|| format!("Invalid log level '{}'", level))?;
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(file_writer).with_ansi(false))
+        .with(
+            fmt::layer()
+                .with_writer(|| BufferWriter)
+                .with_ansi(false)
+                .with_target(false),
+        )
+        .try_init()
+        .context("Failed to install log subscriber")?;
+
+    Ok(guard)
+}
+
+/// Most recent formatted log lines, oldest first.
+pub fn recent_lines() -> Vec<String> {
+    BUFFER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
@@ -1,49 +1,194 @@
+pub mod action;
 pub mod api;
 pub mod app;
+pub mod cache;
+pub mod cli;
 pub mod common;
 pub mod config;
 pub mod event;
+pub mod logging;
 pub mod pages;
 pub mod tui;
 pub mod ui;
+pub mod wizard;
 
 use anyhow::Result;
-use api::datto::DattoClient;
-use api::datto_av::DattoAvClient;
-use api::sophos::SophosClient;
-use app::App;
-use config::Config;
+use api::datto::{DattoClient, RmmApi};
+use api::mock::{MockAv, MockDefender, MockMdr, MockRmm, MockSentinelOne, MockSoc, TAGS_UDF};
+use api::read_only::ReadOnlyRmm;
+use app::{App, ConfigState, IntegrationStatus, ProfileClients};
+use clap::Parser;
+use cli::Cli;
+use common::time::{self, TimeSettings, TimeZoneSetting};
+use config::{Config, ProfileSettings};
 use event::EventHandler;
+use std::sync::Arc;
 use std::time::Duration;
+use ui::theme::Theme;
+use wizard::Provider;
+
+/// Profile name shown in the status bar when running with `--demo`.
+const DEMO_PROFILE: &str = "demo";
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Load config
-    let config = Config::from_env().unwrap_or_else(|e| {
-        eprintln!("Failed to load config: {}", e);
-        std::process::exit(1);
-    });
-
-    // Initialize API Client
-    let mut client = DattoClient::new(config.datto).expect("Failed to create API client");
-    let rocket_client = crate::api::rocket_cyber::RocketCyberClient::new(config.rocket).ok(); // Create Rocket client
-    let sophos_client = SophosClient::new(config.sophos).ok(); // Create Sophos client
-    let datto_av_client = DattoAvClient::new(config.datto_av).ok(); // Create Datto AV client
-
-    // Authenticate
-    if let Err(e) = client.authenticate().await {
-        eprintln!("Warning: Authentication failed: {}", e);
+    let cli = Cli::parse();
+
+    let tick_rate = Duration::from_millis(250);
+    let mut session = None;
+
+    // Load config, running the setup wizard on first launch. Demo mode needs none.
+    let config = if cli.demo {
+        None
+    } else {
+        Some(match Config::load(cli.profile.as_deref()) {
+            Ok(config) => config,
+            Err(e) if cli.command.is_none() && !config::config_file_exists() => {
+                let mut terminal = tui::init()?;
+                tui::install_panic_hook();
+                let mut events = EventHandler::new(tick_rate);
+                match wizard::run(&mut terminal, &mut events).await {
+                    Ok(Some(config)) => {
+                        session = Some((terminal, events));
+                        config
+                    }
+                    Ok(None) => {
+                        tui::restore()?;
+                        eprintln!("Setup cancelled. Failed to load config: {}", e);
+                        std::process::exit(1);
+                    }
+                    Err(err) => {
+                        tui::restore()?;
+                        return Err(err);
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to load config: {}", e);
+                std::process::exit(1);
+            }
+        })
+    };
+
+    let profile = config
+        .as_ref()
+        .map_or_else(|| DEMO_PROFILE.to_string(), |c| c.profile.clone());
+
+    // Keep the guard alive so buffered log lines are flushed on exit
+    let _log_guard = match logging::init(
+        config
+            .as_ref()
+            .and_then(|c| c.log_level.as_deref())
+            .unwrap_or(logging::DEFAULT_LOG_LEVEL),
+    ) {
+        Ok(guard) => Some(guard),
+        Err(e) => {
+            eprintln!("Warning: logging disabled: {:#}", e);
+            None
+        }
+    };
+    tracing::info!(%profile, "starting");
+
+    if let Some(name) = config.as_ref().and_then(|c| c.theme.as_ref()) {
+        match Theme::by_name(name) {
+            Some(theme) => ui::theme::set(theme),
+            None => eprintln!("Warning: unknown theme '{}', using dark", name),
+        }
+    }
+
+    if let Some(config) = &config {
+        let mut zone = TimeZoneSetting::Local;
+        if let Some(name) = &config.timezone {
+            match TimeZoneSetting::parse(name) {
+                Some(parsed) => zone = parsed,
+                None => eprintln!(
+                    "Warning: unknown timezone '{}' (expected local, utc, an offset such as \
+                     -06:00 or an IANA name such as America/Chicago), using the local one",
+                    name
+                ),
+            }
+        }
+        time::set(TimeSettings { zone, relative: config.relative_times });
+
+        if let Err(e) = api::http::configure(&config.network) {
+            if session.is_some() {
+                tui::restore()?;
+            }
+            eprintln!("Invalid network settings: {:#}", e);
+            std::process::exit(1);
+        }
     }
 
-    // Setup terminal
-    let mut terminal = tui::init()?;
-    tui::install_panic_hook();
+    // Initialize API Client (fake data in demo mode)
+    let (client, datto_auth): (Arc<dyn RmmApi>, _) = match &config {
+        Some(config) => {
+            let mut client =
+                DattoClient::new(config.datto.clone()).expect("Failed to create API client");
+            let auth = match client.ping().await {
+                Ok(()) => client.authenticate().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &auth {
+                tracing::warn!(error = %e, "Datto authentication failed");
+                eprintln!("Warning: Authentication failed: {:#}", e);
+            }
+            (Arc::new(client), auth.map_err(|e| format!("{:#}", e)))
+        }
+        None => (Arc::new(MockRmm::default()), Ok(())),
+    };
 
-    // Create app and event handler including tick rate
-    let mut app = App::new(Some(client), rocket_client, sophos_client, datto_av_client);
+    // Headless subcommands print their output and exit without starting the TUI
+    if let Some(command) = cli.command {
+        // Read-only profiles refuse writes here as in the TUI, whose clients
+        // are wrapped in `ProfileClients::with_datto`
+        let client: Arc<dyn RmmApi> = match &config {
+            Some(config) if config.read_only => Arc::new(ReadOnlyRmm(client)),
+            _ => client,
+        };
+        return cli::run(command, cli.output, client.as_ref()).await;
+    }
 
-    let tick_rate = Duration::from_millis(250);
-    let mut events = EventHandler::new(tick_rate);
+    // Setup terminal (already running if the setup wizard was shown)
+    let (mut terminal, mut events) = match session {
+        Some(session) => session,
+        None => {
+            let terminal = tui::init()?;
+            tui::install_panic_hook();
+            (terminal, EventHandler::new(tick_rate))
+        }
+    };
+
+    let settings = config.as_ref().map(ProfileSettings::from).unwrap_or_default();
+
+    // Create the remaining clients; Sophos needs a token before any request
+    let clients = match config {
+        Some(config) => ProfileClients::with_datto(client, datto_auth, config).await,
+        None => ProfileClients {
+            datto: client,
+            rocket: Some(Arc::new(MockSoc)),
+            sophos: Some(Arc::new(MockMdr)),
+            datto_av: Some(Arc::new(MockAv)),
+            defender: Some(Arc::new(MockDefender)),
+            sentinelone: Some(Arc::new(MockSentinelOne)),
+            sentinelone_uuid_udf: None,
+            udf_labels: Default::default(),
+            tags_udf: Some(TAGS_UDF),
+            integrations: Provider::ALL
+                .iter()
+                .map(|&p| IntegrationStatus::new(p, ConfigState::Demo))
+                .collect(),
+            read_only: false,
+        },
+    };
+
+    // Create app
+    let demo = cli.demo;
+    let mut app = App::new(clients, settings);
+    app.active_profile = profile;
+    if !demo {
+        app.profiles = Config::profile_names();
+        app.open_cache();
+    }
 
     // Run the app (async)
     let res = app.run(&mut terminal, &mut events).await;
@@ -0,0 +1,67 @@
+use crate::app::App;
+use crate::ui::{loading, theme};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+};
+
+/// Variables shared by every site of the account, laid out like the site
+/// Variables tab.
+pub fn render_account_variables(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let state = app.account_variables();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Account Variables ({})", state.variables.len()))
+        .title_bottom(Line::from(" Enter: edit | x: delete | r: reload ").right_aligned());
+
+    if let Some(err) = &state.error {
+        let text = Paragraph::new(format!("Error: {}", err))
+            .style(Style::default().fg(theme.error))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+    if state.loading && state.variables.is_empty() {
+        loading::render_loading_table(frame, area, block, "Loading account variables...", 3);
+        return;
+    }
+
+    let mut rows: Vec<Row> = state
+        .variables
+        .iter()
+        .map(|var| {
+            Row::new(vec![
+                Cell::from(var.name.clone()),
+                Cell::from(var.value.clone()),
+                Cell::from(if var.masked { "*" } else { "" }),
+            ])
+        })
+        .collect();
+    rows.push(Row::new(vec![
+        Cell::from(Span::styled(
+            "+ Create new",
+            Style::default().add_modifier(Modifier::BOLD | Modifier::ITALIC),
+        )),
+        Cell::from(""),
+        Cell::from(""),
+    ]));
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(30),
+            Constraint::Percentage(60),
+            Constraint::Percentage(10),
+        ],
+    )
+    .header(
+        Row::new(vec!["Name", "Value", "Masked"]).style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.account_variables_mut().table_state);
+}
@@ -1,19 +1,23 @@
+use crate::api::datto::types::ComponentResult;
 use crate::app::{App, JobViewRow};
-use crate::common::jobs::generate_job_rows;
-use crate::common::utils::format_timestamp;
+use crate::common::time::format_time;
+use crate::common::utils::loading_text;
+use crate::ui::theme;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
 };
 
 pub fn render_activity_detail(app: &mut App, frame: &mut Frame, area: Rect) {
-    if let Some(log) = &app.selected_activity_log {
+    let theme = theme::current();
+    let mut component_items = Vec::new();
+    if let Some(log) = &app.device_detail().selected_activity_log {
         let block = Block::default()
             .borders(Borders::ALL)
             .title("Activity Log Details");
 
         // Format date
-        let date_str = format_timestamp(log.date.map(serde_json::Value::from));
+        let date_str = format_time(log.at());
 
         let user_name = log
             .user
@@ -58,12 +62,12 @@ pub fn render_activity_detail(app: &mut App, frame: &mut Frame, area: Rect) {
         extra_details.sort_by(|a, b| a.0.cmp(&b.0));
 
         let status_style = match job_status.to_lowercase().as_str() {
-            "expired" => Style::default().fg(Color::Rgb(255, 165, 0)), // Orange
-            "scheduled" => Style::default().fg(Color::Blue),
-            "running" => Style::default().fg(Color::Cyan),
-            "success" => Style::default().fg(Color::Green),
-            "warning" => Style::default().fg(Color::Rgb(255, 165, 0)), // Orange
-            "failure" => Style::default().fg(Color::Red),
+            "expired" => Style::default().fg(theme.caution),
+            "scheduled" => Style::default().fg(theme.info),
+            "running" => Style::default().fg(theme.accent),
+            "success" => Style::default().fg(theme.success),
+            "warning" => Style::default().fg(theme.caution),
+            "failure" => Style::default().fg(theme.error),
             _ => Style::default(),
         };
 
@@ -119,31 +123,24 @@ pub fn render_activity_detail(app: &mut App, frame: &mut Frame, area: Rect) {
         ];
 
         // Job Results Section
-        if app.job_result_loading {
+        if app.device_detail().job_result_loading {
             lines.push(Line::from(Span::styled(
-                "Loading Job Results...",
-                Style::default().fg(Color::Yellow),
+                loading_text("Loading Job Results..."),
+                Style::default().fg(theme.warning),
             )));
-        } else if let Some(err) = &app.job_result_error {
+        } else if let Some(err) = &app.device_detail().job_result_error {
             lines.push(Line::from(Span::styled(
                 format!("Error fetching job results: {}", err),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.error),
             )));
-        } else if let Some(job_result) = &app.selected_job_result {
+        } else if let Some(job_result) = &app.device_detail().selected_job_result {
             lines.push(Line::from(Span::styled(
                 "Job Results:",
                 Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
             )));
 
             let status = job_result.job_deployment_status.as_deref().unwrap_or("N/A");
-            let deployment_status_color = match status.to_lowercase().as_str() {
-                "success" => Color::Green,
-                "failure" | "error" => Color::Red,
-                "warning" | "expired" => Color::Rgb(255, 165, 0), // Orange
-                "scheduled" => Color::Blue,
-                "running" => Color::Cyan,
-                _ => Color::White,
-            };
+            let deployment_status_color = job_status_color(status);
 
             lines.push(Line::from(vec![
                 Span::styled(
@@ -152,125 +149,55 @@ pub fn render_activity_detail(app: &mut App, frame: &mut Frame, area: Rect) {
                 ),
                 Span::styled(status, Style::default().fg(deployment_status_color)),
             ]));
-            let ran_on_str = format_timestamp(job_result.ran_on.clone());
+            let ran_on_str = format_time(job_result.ran_at());
 
             lines.push(Line::from(vec![
                 Span::styled("Ran On: ", Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(ran_on_str),
             ]));
 
-            lines.push(Line::from(""));
-            lines.push(Line::from(Span::styled(
-                "Components:",
-                Style::default().add_modifier(Modifier::BOLD),
-            )));
-
-            if let Some(components) = &job_result.component_results {
-                let rows = generate_job_rows(job_result);
-                // We need to match displayed lines to rows.
-                // iterate rows and render.
-
-                for (row_index, row) in rows.iter().enumerate() {
-                    let is_selected = row_index == app.selected_job_row_index;
-                    let style = if is_selected {
-                        Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED)
-                    } else {
-                        Style::default()
-                    };
-
-                    match row {
-                        JobViewRow::ComponentHeader(idx) => {
-                            if let Some(comp) = components.get(*idx) {
-                                let status_color = match comp
-                                    .component_status
-                                    .as_deref()
-                                    .unwrap_or("")
-                                    .to_lowercase()
-                                    .as_str()
-                                {
-                                    "success" => Color::Green,
-                                    "failure" | "error" => Color::Red,
-                                    "warning" => Color::Yellow,
-                                    _ => Color::White,
-                                };
-
-                                let prefix = if is_selected { "> " } else { "- " };
-
-                                // Component Name Line
-                                lines.push(Line::from(vec![
-                                    Span::styled(prefix, style),
-                                    Span::styled(
-                                        comp.component_name
-                                            .as_deref()
-                                            .unwrap_or("Unknown Component"),
-                                        style.add_modifier(Modifier::BOLD),
-                                    ),
-                                    Span::raw(": "),
-                                    Span::styled(
-                                        comp.component_status.as_deref().unwrap_or("N/A"),
-                                        if is_selected {
-                                            style
-                                        } else {
-                                            Style::default().fg(status_color)
-                                        },
-                                    ),
-                                ]));
-
-                                // Warnings (indented)
-                                if let Some(warnings) = comp.number_of_warnings {
-                                    if warnings > 0 {
-                                        lines.push(Line::from(vec![
-                                            Span::raw("    Warnings: "),
-                                            Span::styled(
-                                                warnings.to_string(),
-                                                Style::default().fg(Color::Yellow),
-                                            ),
-                                        ]));
-                                    }
-                                }
-                            }
-                        }
-                        JobViewRow::StdOutLink(_) => {
-                            lines.push(Line::from(vec![
-                                Span::raw("    "),
-                                Span::styled(
-                                    "View Standard Output",
-                                    if is_selected {
-                                        style.fg(Color::Cyan)
-                                    } else {
-                                        Style::default().fg(Color::Cyan)
-                                    },
-                                ),
-                            ]));
-                        }
-                        JobViewRow::StdErrLink(_) => {
-                            lines.push(Line::from(vec![
-                                Span::raw("    "),
-                                Span::styled(
-                                    "View Standard Error",
-                                    if is_selected {
-                                        style.fg(Color::Red)
-                                    } else {
-                                        Style::default().fg(Color::Red)
-                                    },
-                                ),
-                            ]));
-                        }
-                    }
+            match &job_result.component_results {
+                Some(components) => {
+                    component_items = job_component_items(app, components);
+                }
+                None => {
+                    lines.push(Line::from(""));
+                    lines.push(Line::from("No components found."));
                 }
-            } else {
-                lines.push(Line::from("No components found."));
             }
         } else {
             // Only show this if we aren't loading and don't have a result yet (e.g. no job UID found)
             lines.push(Line::from(Span::styled(
                 "No Job Result information available.",
-                Style::default().fg(Color::Gray),
+                Style::default().fg(theme.muted),
             )));
         }
 
+        if component_items.is_empty() {
+            let p = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+            frame.render_widget(p, area);
+            return;
+        }
+
+        let info_height = (lines.len() as u16 + 2).min(area.height / 2);
+        let [info_area, components_area] =
+            Layout::vertical([Constraint::Length(info_height), Constraint::Min(3)]).areas(area);
         let p = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
-        frame.render_widget(p, area);
+        frame.render_widget(p, info_area);
+
+        let list = List::new(component_items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Components")
+                    .title_bottom(
+                        Line::from(" 1-9: jump | Space: collapse | Enter: open ").right_aligned(),
+                    ),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+        let detail = app.device_detail_mut();
+        detail.job_rows_list_state.select(Some(detail.selected_job_row_index));
+        frame.render_stateful_widget(list, components_area, &mut detail.job_rows_list_state);
     } else {
         frame.render_widget(
             Paragraph::new("No activity log selected")
@@ -279,3 +206,65 @@ pub fn render_activity_detail(app: &mut App, frame: &mut Frame, area: Rect) {
         );
     }
 }
+
+/// One line per job row: a numbered header per component, with its
+/// warnings, and the output links of the expanded ones.
+fn job_component_items(app: &App, components: &[ComponentResult]) -> Vec<ListItem<'static>> {
+    let theme = theme::current();
+    let detail = app.device_detail();
+    detail
+        .job_rows()
+        .into_iter()
+        .map(|row| match row {
+            JobViewRow::ComponentHeader(idx) => {
+                let Some(comp) = components.get(idx) else {
+                    return ListItem::new("");
+                };
+                let status = comp.component_status.clone().unwrap_or_else(|| "N/A".to_string());
+                let marker = if detail.collapsed_job_components.contains(&idx) {
+                    "▸"
+                } else {
+                    "▾"
+                };
+                let mut spans = vec![
+                    Span::styled(format!("[{}] ", idx + 1), Style::default().fg(theme.muted)),
+                    Span::raw(format!("{} ", marker)),
+                    Span::styled(
+                        comp.component_name.clone().unwrap_or_else(|| "Unknown Component".into()),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(": "),
+                    Span::styled(status.clone(), Style::default().fg(job_status_color(&status))),
+                ];
+                if let Some(warnings) = comp.number_of_warnings.filter(|w| *w > 0) {
+                    spans.push(Span::styled(
+                        format!("  ({} warnings)", warnings),
+                        Style::default().fg(theme.warning),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            }
+            JobViewRow::StdOutLink(_) => ListItem::new(Line::from(vec![
+                Span::raw("      "),
+                Span::styled("View Standard Output", Style::default().fg(theme.accent)),
+            ])),
+            JobViewRow::StdErrLink(_) => ListItem::new(Line::from(vec![
+                Span::raw("      "),
+                Span::styled("View Standard Error", Style::default().fg(theme.error)),
+            ])),
+        })
+        .collect()
+}
+
+/// Color of a job deployment status.
+pub fn job_status_color(status: &str) -> Color {
+    let theme = theme::current();
+    match status.to_lowercase().as_str() {
+        "success" => theme.success,
+        "failure" | "error" => theme.error,
+        "warning" | "expired" => theme.caution,
+        "scheduled" => theme.info,
+        "running" => theme.accent,
+        _ => theme.text,
+    }
+}
@@ -0,0 +1,173 @@
+use crate::app::App;
+use crate::common::time::format_time;
+use crate::ui::{layout, theme};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+};
+
+/// Runs listed under the devices, newest first.
+const SHOWN_RUNS: usize = 5;
+
+/// Saved collections on the left; the devices of the selected one on the
+/// right, above the runs of this session.
+pub fn render_collections(app: &mut App, frame: &mut Frame, area: Rect) {
+    let chunks = layout::panes(area, 30);
+    render_collection_list(app, frame, chunks[0]);
+
+    let runs = app.collections().runs.len().min(SHOWN_RUNS);
+    if runs == 0 {
+        render_collection_devices(app, frame, chunks[1]);
+        return;
+    }
+    let [devices, runs_area] =
+        Layout::vertical([Constraint::Min(5), Constraint::Length(runs as u16 + 3)])
+            .areas(chunks[1]);
+    render_collection_devices(app, frame, devices);
+    render_runs(app, frame, runs_area);
+}
+
+fn render_collection_list(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let state = app.collections();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(layout::pane_border(!state.devices_focused))
+        .title(format!("Collections ({})", state.collections.len()));
+
+    if state.collections.is_empty() {
+        let text = Paragraph::new(
+            "No collections yet. Press '+' on a device, on marked devices of a site, on a tag \
+             or Ctrl+A in the device search to add devices to a new collection.",
+        )
+        .style(Style::default().fg(theme.dim))
+        .wrap(Wrap { trim: true })
+        .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let rows: Vec<Row> = state
+        .collections
+        .iter()
+        .map(|collection| {
+            Row::new(vec![
+                Cell::from(collection.name.clone()),
+                Cell::from(collection.devices.len().to_string()),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Min(10), Constraint::Length(7)])
+        .header(
+            Row::new(vec!["Name", "Devices"]).style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.collections_mut().collection_state);
+}
+
+fn render_collection_devices(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let state = app.collections();
+    let collection = state.selected_collection();
+    let title = match collection {
+        Some(collection) => format!("{} ({})", collection.name, collection.devices.len()),
+        None => "Devices".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(layout::pane_border(state.devices_focused))
+        .title(title)
+        .title_bottom(
+            Line::from(" Enter: open device | r: run on collection | x: remove ").right_aligned(),
+        );
+
+    // Site names from the site list, as the snapshots may predate a rename
+    let site_name = |uid: &str| {
+        app.site_list().sites.iter().find(|s| s.uid == uid).map(|s| s.name.clone())
+    };
+    let rows: Vec<Row> = collection
+        .map(|c| c.devices.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|device| {
+            let (status, color) =
+                if device.online { ("Online", theme.success) } else { ("Offline", theme.dim) };
+            let site = site_name(&device.site_uid).or_else(|| device.site_name.clone());
+            Row::new(vec![
+                Cell::from(device.hostname.clone()),
+                Cell::from(site.unwrap_or_default()),
+                Cell::from(Span::styled(status, Style::default().fg(color))),
+                Cell::from(device.operating_system.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Length(8),
+            Constraint::Percentage(40),
+        ],
+    )
+    .header(
+        Row::new(vec!["Hostname", "Site", "Status", "OS"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.collections_mut().device_state);
+}
+
+/// The latest runs on collections, with the devices each failed on.
+fn render_runs(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let rows: Vec<Row> = app
+        .collections()
+        .runs
+        .iter()
+        .rev()
+        .take(SHOWN_RUNS)
+        .map(|run| {
+            let answered = run.succeeded + run.failures.len();
+            let (progress, color) = if !run.is_finished() {
+                (format!("{}/{}", answered, run.total), theme.warning)
+            } else if run.failures.is_empty() {
+                (format!("{}/{}", run.succeeded, run.total), theme.success)
+            } else {
+                (format!("{}/{}", run.succeeded, run.total), theme.error)
+            };
+            let failures: Vec<String> =
+                run.failures.iter().map(|(host, e)| format!("{}: {}", host, e)).collect();
+            Row::new(vec![
+                Cell::from(format_time(Some(run.started_at))),
+                Cell::from(run.collection.clone()),
+                Cell::from(run.label.clone()),
+                Cell::from(Span::styled(progress, Style::default().fg(color))),
+                Cell::from(failures.join("; ")),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(17),
+            Constraint::Percentage(15),
+            Constraint::Percentage(25),
+            Constraint::Length(7),
+            Constraint::Percentage(45),
+        ],
+    )
+    .header(
+        Row::new(vec!["Started", "Collection", "Action", "Done", "Failed"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title("Runs"));
+
+    frame.render_widget(table, area);
+}
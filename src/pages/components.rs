@@ -0,0 +1,182 @@
+use crate::app::App;
+use crate::common::utils::format_age;
+use crate::ui::{layout, loading, theme};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+};
+
+pub fn render_components(app: &mut App, frame: &mut Frame, area: Rect) {
+    let chunks = layout::panes(area, 50);
+
+    render_component_list(app, frame, chunks[0]);
+    render_component_detail(app, frame, chunks[1]);
+}
+
+fn render_component_list(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let state = app.components();
+
+    let mut title = format!("Components ({})", state.components.len());
+    if state.is_filtering || !state.filter_query.is_empty() {
+        title.push_str(&format!(" [Filter: {}", state.filter_query));
+        if state.is_filtering {
+            title.push('_');
+        }
+        title.push(']');
+        title.push_str(&format!(
+            " {}/{}",
+            state.visible_count(),
+            state.components.len()
+        ));
+    }
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    if let Some(err) = &state.error {
+        let text = Paragraph::new(format!("Error: {}", err))
+            .style(Style::default().fg(theme.error))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+    if state.loading && state.components.is_empty() {
+        loading::render_loading_table(frame, area, block, "Loading components...", 3);
+        return;
+    }
+
+    let rows: Vec<Row> = state
+        .visible_components()
+        .map(|component| {
+            let star = if state.is_starred(&component.uid) {
+                "★ "
+            } else {
+                ""
+            };
+            Row::new(vec![
+                Cell::from(format!("{}{}", star, component.name)),
+                Cell::from(component.category_code.clone().unwrap_or_default()),
+                Cell::from(
+                    component
+                        .variables
+                        .as_ref()
+                        .map_or(0, |v| v.len())
+                        .to_string(),
+                ),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(60),
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(
+        Row::new(vec!["Name", "Category", "Vars"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(theme.highlight),
+    )
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.components_mut().table_state);
+}
+
+fn render_component_detail(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Details")
+        .title_bottom(Line::from(" Enter: run against device... | f: star ").right_aligned());
+    let Some(component) = app.components().selected_component() else {
+        frame.render_widget(Paragraph::new("No component selected").block(block), area);
+        return;
+    };
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Min(0)])
+        .split(inner);
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(theme.muted));
+    let credentials = match component.credentials_required {
+        Some(true) => Span::styled("Required", Style::default().fg(theme.warning)),
+        Some(false) => Span::raw("Not required"),
+        None => Span::styled("Unknown", Style::default().fg(theme.muted)),
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            component.name.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(vec![
+            label("Category:    "),
+            Span::raw(component.category_code.clone().unwrap_or_default()),
+        ]),
+        Line::from(vec![label("Credentials: "), credentials]),
+        Line::from(vec![
+            label("UID:         "),
+            Span::raw(component.uid.clone()),
+        ]),
+        Line::from(vec![
+            label("Last run:    "),
+            Span::raw(
+                app.components()
+                    .last_run(&component.uid)
+                    .and_then(|run| chrono::DateTime::from_timestamp(run.ran_at, 0))
+                    .map_or_else(|| "Never".to_string(), format_age),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(
+            component
+                .description
+                .clone()
+                .unwrap_or_else(|| "No description".to_string()),
+        ),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), chunks[0]);
+
+    let mut variables = component.variables.clone().unwrap_or_default();
+    variables.sort_by_key(|v| v.variables_idx.unwrap_or(0));
+    let rows: Vec<Row> = variables
+        .iter()
+        .map(|var| {
+            Row::new(vec![
+                Cell::from(var.name.clone()),
+                Cell::from(var.variable_type.clone().unwrap_or_default()),
+                Cell::from(var.default_val.clone().unwrap_or_default()),
+                Cell::from(var.description.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+            Constraint::Percentage(20),
+            Constraint::Percentage(40),
+        ],
+    )
+    .header(
+        Row::new(vec!["Variable", "Type", "Default", "Description"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::TOP)
+            .title(format!("Variables ({})", variables.len())),
+    );
+    frame.render_widget(table, chunks[1]);
+}
@@ -1,22 +1,68 @@
-use crate::app::{App, DeviceDetailTab};
-use crate::common::utils::format_timestamp;
+use crate::api::datto::types::Alert;
+use crate::app::{
+    ACTIVITY_COLUMNS, App, DeviceDetailTab, DevicePane, MappingSource, MdrMapping,
+    OPEN_ALERT_COLUMNS, Popup, RESOLVED_ALERT_COLUMNS, ScanState, SecurityState, activity_user,
+    device_tags,
+};
+use crate::common::jobs::activity_job_summary;
+use crate::common::time::{format_time, format_timestamp};
+use crate::common::utils::loading_text;
+use crate::common::warranty::{EXPIRING_SOON_DAYS, warranty_status};
 use crate::pages::popups::render_device_variables_popup;
+use crate::pages::site_detail::ack_footer;
+use crate::ui::layout::{self, Column};
+use crate::ui::table::render_table_view;
+use crate::ui::{loading, theme};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Cell, Paragraph, Row, Table, Tabs, Wrap},
 };
 
+/// Columns of the open alerts table, by `OPEN_ALERT_COLUMNS`.
+const OPEN_ALERT_TABLE: [Column; 3] = [
+    Column::new(Constraint::Length(15), 15, 1),     // Priority
+    Column::new(Constraint::Percentage(60), 16, 0), // Diagnostics
+    Column::new(Constraint::Length(22), 22, 2),     // Time
+];
+
+/// Columns of the resolved alerts table, by `RESOLVED_ALERT_COLUMNS`.
+const RESOLVED_ALERT_TABLE: [Column; 5] = [
+    Column::new(Constraint::Length(12), 12, 1),     // Priority
+    Column::new(Constraint::Percentage(50), 16, 0), // Diagnostics
+    Column::new(Constraint::Length(22), 22, 3),     // Raised
+    Column::new(Constraint::Length(22), 22, 2),     // Resolved
+    Column::new(Constraint::Length(16), 12, 4),     // Resolved By
+];
+
+/// Columns of the activities table, by `ACTIVITY_COLUMNS`.
+const ACTIVITY_TABLE: [Column; 6] = [
+    Column::new(Constraint::Length(22), 22, 1),     // Time
+    Column::new(Constraint::Percentage(40), 16, 0), // Job Name
+    Column::new(Constraint::Length(12), 12, 1),     // Status
+    Column::new(Constraint::Length(15), 15, 3),     // Action
+    Column::new(Constraint::Length(10), 10, 4),     // Category
+    Column::new(Constraint::Length(15), 15, 2),     // User
+];
+
+/// Columns of the software table.
+const SOFTWARE_TABLE: [Column; 2] = [
+    Column::new(Constraint::Percentage(70), 20, 0), // Name
+    Column::new(Constraint::Percentage(30), 10, 1), // Version
+];
+
 pub fn render_device_detail(app: &mut App, frame: &mut Frame, area: Rect) {
-    let selected_device_opt = app.selected_device.clone();
+    let theme = theme::current();
+    let selected_device_opt = app.device_detail().selected_device.clone();
 
     if let Some(device) = selected_device_opt {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(area);
+        let chunks = layout::panes(area, 50);
 
         // --- Left Pane: Device Info ---
-        render_device_info(&device, frame, chunks[0]);
+        let watched = app.device_watch().is_watched(&device.uid);
+        let focused = app.device_detail().scrolled_pane == DevicePane::Info;
+        let tags = app.tags_udf.map(|udf| device_tags(&device, udf));
+        let scroll = &mut app.device_detail_mut().info_scroll;
+        render_device_info(&device, tags, watched, focused, scroll, frame, chunks[0]);
 
         // --- Right Pane: Security & Activities ---
         let right_chunks = Layout::default()
@@ -31,7 +77,7 @@ pub fn render_device_detail(app: &mut App, frame: &mut Frame, area: Rect) {
         render_device_security(app, &device, frame, right_chunks[0]);
 
         // Tabs
-        let mut tab_titles = vec!["Open Alerts", "Activities"];
+        let mut tab_titles = vec!["Open Alerts", "Resolved", "Activities"];
         let is_software_supported = device
             .device_class
             .as_ref()
@@ -41,11 +87,14 @@ pub fn render_device_detail(app: &mut App, frame: &mut Frame, area: Rect) {
         if is_software_supported {
             tab_titles.push("Software");
         }
+        tab_titles.push("Policies");
 
-        let tab_index = match app.device_detail_tab {
+        let tab_index = match app.device_detail().device_detail_tab {
             DeviceDetailTab::OpenAlerts => 0,
-            DeviceDetailTab::Activities => 1,
-            DeviceDetailTab::Software => 2,
+            DeviceDetailTab::ResolvedAlerts => 1,
+            DeviceDetailTab::Activities => 2,
+            DeviceDetailTab::Software => 3,
+            DeviceDetailTab::Policies => tab_titles.len() - 1,
         };
 
         // Ensure tab_index is within bounds (e.g. if we switch from a device with Software to one without)
@@ -61,20 +110,23 @@ pub fn render_device_detail(app: &mut App, frame: &mut Frame, area: Rect) {
             .highlight_style(
                 Style::default()
                     .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
+                    .fg(theme.accent),
             );
         frame.render_widget(tabs, right_chunks[1]);
 
         // Content
-        match app.device_detail_tab {
+        match app.device_detail().device_detail_tab {
             DeviceDetailTab::OpenAlerts => render_open_alerts(app, frame, right_chunks[2]),
+            DeviceDetailTab::ResolvedAlerts => render_resolved_alerts(app, frame, right_chunks[2]),
             DeviceDetailTab::Activities => render_device_activities(app, frame, right_chunks[2]),
             DeviceDetailTab::Software => render_software(app, frame, right_chunks[2]),
+            DeviceDetailTab::Policies => render_policies(app, frame, right_chunks[2]),
         }
 
         // --- Variables Popup ---
-        if app.show_device_variables {
-            render_device_variables_popup(&device, frame, &mut app.udf_table_state);
+        if app.popups().is_open(Popup::DeviceVariables) {
+            let labels = app.udf_labels.clone();
+            render_device_variables_popup(&device, &labels, frame, &mut app.device_detail_mut().udf_table_state);
         }
     } else {
         frame.render_widget(
@@ -85,90 +137,170 @@ pub fn render_device_detail(app: &mut App, frame: &mut Frame, area: Rect) {
 }
 
 fn render_open_alerts(app: &mut App, frame: &mut Frame, area: Rect) {
-    let block = Block::default().borders(Borders::ALL).title("Open Alerts");
+    let theme = theme::current();
+    let mut block = Block::default().borders(Borders::ALL).title("Open Alerts ('a': acknowledge)");
 
-    if app.open_alerts_loading {
-        frame.render_widget(Paragraph::new("Loading alerts...").block(block), area);
+    if app.device_detail().open_alerts_loading {
+        let columns = OPEN_ALERT_COLUMNS.len();
+        loading::render_loading_table(frame, area, block, "Loading alerts...", columns);
         return;
     }
 
-    if let Some(err) = &app.open_alerts_error {
+    if let Some(err) = &app.device_detail().open_alerts_error {
         frame.render_widget(
             Paragraph::new(format!("Error: {}", err))
-                .style(Style::default().fg(Color::Red))
+                .style(Style::default().fg(theme.error))
                 .block(block),
             area,
         );
         return;
     }
 
-    if app.open_alerts.is_empty() {
+    if app.device_detail().open_alerts.is_empty() {
         frame.render_widget(Paragraph::new("No open alerts.").block(block), area);
         return;
     }
 
-    let rows: Vec<Row> = app
+    // Acknowledged alerts render dimmed; the selected one's note goes in the footer
+    let detail = app.device_detail();
+    let acked: Vec<bool> = detail
         .open_alerts
-        .iter()
-        .enumerate()
-        .map(|(i, alert)| {
-            let style = if Some(i) == app.open_alerts_table_state.selected() {
-                Style::default().add_modifier(Modifier::REVERSED)
-            } else {
-                Style::default()
-            };
+        .rows()
+        .map(|alert| app.alert_acknowledgement(alert).is_some())
+        .collect();
+    if let Some(ack) =
+        detail.open_alerts.selected().and_then(|alert| app.alert_acknowledgement(alert))
+    {
+        block = block.title_bottom(ack_footer(ack));
+    }
 
+    let view = &mut app.device_detail_mut().open_alerts;
+    render_table_view(
+        frame,
+        area,
+        block,
+        view,
+        &OPEN_ALERT_TABLE,
+        &OPEN_ALERT_COLUMNS,
+        |i, alert, fit| {
             let priority = alert.priority.as_deref().unwrap_or("Unknown");
-            let priority_style = match priority.to_lowercase().as_str() {
-                "critical" => Style::default().fg(Color::Red),
-                "high" => Style::default().fg(Color::Rgb(255, 165, 0)), // Orange
-                "medium" => Style::default().fg(Color::Yellow),
-                "low" => Style::default().fg(Color::Blue),
-                _ => Style::default(),
-            };
-
-            let diagnostics = alert
-                .diagnostics
-                .as_deref()
-                .unwrap_or("N/A")
-                .replace("\r\n", " ")
-                .replace('\n', " ")
-                .trim()
-                .to_string();
+            let priority_style = priority_style(priority);
+            let diagnostics = diagnostics_line(alert);
 
             // Format Time
-            let time_str = format_timestamp(alert.timestamp.clone());
+            let time_str = format_time(alert.raised_at());
+            let (priority_style, diagnostics, style) = if acked[i] {
+                let dimmed = Style::default().fg(theme.muted).add_modifier(Modifier::DIM);
+                (Style::default(), format!("✓ {}", diagnostics), dimmed)
+            } else {
+                (priority_style, diagnostics, Style::default())
+            };
 
-            Row::new(vec![
+            Row::new(fit.pick(vec![
                 Cell::from(Span::styled(priority, priority_style)),
                 Cell::from(diagnostics),
                 Cell::from(time_str),
-            ])
+            ]))
             .style(style)
-        })
-        .collect();
+        },
+    );
+}
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(15),     // Priority
-            Constraint::Percentage(60), // Diagnostics
-            Constraint::Length(22),     // Time
-        ],
-    )
-    .header(
-        Row::new(vec!["Priority", "Diagnostics", "Time"])
-            .style(Style::default().add_modifier(Modifier::BOLD)),
-    )
-    .block(block)
-    .highlight_symbol(">> ");
+/// The alert priority colored by severity.
+fn priority_style(priority: &str) -> Style {
+    let theme = theme::current();
+    match priority.to_lowercase().as_str() {
+        "critical" => Style::default().fg(theme.error),
+        "high" => Style::default().fg(theme.caution),
+        "medium" => Style::default().fg(theme.warning),
+        "low" => Style::default().fg(theme.info),
+        _ => Style::default(),
+    }
+}
+
+/// The alert's diagnostics on one line.
+fn diagnostics_line(alert: &Alert) -> String {
+    alert
+        .diagnostics
+        .as_deref()
+        .unwrap_or("N/A")
+        .replace("\r\n", " ")
+        .replace('\n', " ")
+        .trim()
+        .to_string()
+}
+
+fn render_resolved_alerts(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let title = format!("Resolved Alerts (last {} days)", app.resolved_alert_days);
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let detail = app.device_detail();
+
+    if detail.resolved_alerts_loading {
+        let columns = RESOLVED_ALERT_COLUMNS.len();
+        loading::render_loading_table(frame, area, block, "Loading resolved alerts...", columns);
+        return;
+    }
+
+    if let Some(err) = &detail.resolved_alerts_error {
+        frame.render_widget(
+            Paragraph::new(format!("Error: {}", err))
+                .style(Style::default().fg(theme.error))
+                .block(block),
+            area,
+        );
+        return;
+    }
 
-    frame.render_stateful_widget(table, area, &mut app.open_alerts_table_state);
+    if detail.resolved_alerts.is_empty() {
+        let text = format!("No alerts resolved in the last {} days.", app.resolved_alert_days);
+        frame.render_widget(Paragraph::new(text).block(block), area);
+        return;
+    }
+
+    let view = &mut app.device_detail_mut().resolved_alerts;
+    render_table_view(
+        frame,
+        area,
+        block,
+        view,
+        &RESOLVED_ALERT_TABLE,
+        &RESOLVED_ALERT_COLUMNS,
+        |_, alert, fit| {
+            let priority = alert.priority.as_deref().unwrap_or("Unknown");
+            // Muted alerts never notified anyone, so they are told apart
+            let (diagnostics, style) = if alert.muted == Some(true) {
+                let muted = Style::default().fg(theme.muted);
+                (format!("(muted) {}", diagnostics_line(alert)), muted)
+            } else {
+                (diagnostics_line(alert), Style::default())
+            };
+
+            Row::new(fit.pick(vec![
+                Cell::from(Span::styled(priority, priority_style(priority))),
+                Cell::from(diagnostics),
+                Cell::from(format_time(alert.raised_at())),
+                Cell::from(format_time(alert.resolved_at())),
+                Cell::from(alert.resolved_by.clone().unwrap_or_default()),
+            ]))
+            .style(style)
+        },
+    );
 }
 
-fn render_device_info(device: &crate::api::datto::types::Device, frame: &mut Frame, area: Rect) {
+/// `tags` is None when the profile keeps no tags.
+fn render_device_info(
+    device: &crate::api::datto::types::Device,
+    tags: Option<Vec<String>>,
+    watched: bool,
+    focused: bool,
+    scroll: &mut u16,
+    frame: &mut Frame,
+    area: Rect,
+) {
+    let theme = theme::current();
     // Format Dates
-    let last_seen_str = format_timestamp(device.last_seen.clone());
+    let last_seen_str = format_time(device.last_seen_at());
     let last_reboot_str = format_timestamp(device.last_reboot.clone());
     let last_audit_str = format_timestamp(device.last_audit_date.clone());
     let creation_date_str = format_timestamp(device.creation_date.clone());
@@ -181,13 +313,13 @@ fn render_device_info(device: &crate::api::datto::types::Device, frame: &mut Fra
         .unwrap_or_else(|| "Unknown".to_string());
 
     let (patch_status_text, patch_color) = match patch_status_raw.as_str() {
-        "FullyPatched" => ("Fully Patched", Color::Green),
-        "ApprovedPending" => ("Approved Pending", Color::Cyan),
-        "InstallError" => ("Install Error", Color::Yellow),
-        "RebootRequired" => ("Reboot Required", Color::Rgb(255, 165, 0)), // Orange
-        "NoData" => ("No Data", Color::Red),
-        "NoPolicy" => ("No Policy", Color::Gray),
-        _ => (patch_status_raw.as_str(), Color::White),
+        "FullyPatched" => ("Fully Patched", theme.success),
+        "ApprovedPending" => ("Approved Pending", theme.accent),
+        "InstallError" => ("Install Error", theme.warning),
+        "RebootRequired" => ("Reboot Required", theme.caution),
+        "NoData" => ("No Data", theme.error),
+        "NoPolicy" => ("No Policy", theme.muted),
+        _ => (patch_status_raw.as_str(), theme.text),
     };
 
     let (patches_installed, patches_pending, patches_not_approved) =
@@ -203,25 +335,31 @@ fn render_device_info(device: &crate::api::datto::types::Device, frame: &mut Fra
 
     // --- Warranty Logic ---
     let warranty_date_str = device.warranty_date.as_deref().unwrap_or("N/A");
-    let warranty_color = if warranty_date_str == "N/A" {
-        Color::Red
-    } else {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(warranty_date_str, "%Y-%m-%d") {
-            let today = chrono::Local::now().date_naive();
-            let duration = date.signed_duration_since(today);
-            if duration.num_days() < 0 {
-                Color::Red // Expired
-            } else if duration.num_days() <= 30 {
-                Color::Yellow // Coming up
-            } else {
-                Color::Green // OK
-            }
-        } else {
-            Color::White // Parse error
-        }
-    };
-
-    let text = vec![
+    let warranty_color = warranty_status(
+        device.warranty_date.as_deref(),
+        chrono::Local::now().date_naive(),
+        EXPIRING_SOON_DAYS,
+    )
+    .color();
+
+    let mut text = vec![Line::from(vec![
+        Span::styled("Description: ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(device.description.as_deref().filter(|d| !d.is_empty()).unwrap_or("N/A")),
+    ])];
+    if let Some(name) = device.display_name.as_deref().filter(|n| !n.is_empty()) {
+        text.push(Line::from(vec![
+            Span::styled("Friendly Name: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(name),
+        ]));
+    }
+    if let Some(tags) = tags {
+        let tags = if tags.is_empty() { "None ('t' to add)".to_string() } else { tags.join(", ") };
+        text.push(Line::from(vec![
+            Span::styled("Tags: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(tags),
+        ]));
+    }
+    text.extend([
         Line::from(vec![
             Span::styled(
                 "Patch Status: ",
@@ -308,16 +446,16 @@ fn render_device_info(device: &crate::api::datto::types::Device, frame: &mut Fra
             Span::styled("■ ", Style::default().fg(warranty_color)),
             Span::raw(warranty_date_str),
         ]),
-    ];
+    ]);
 
     let status_color = if device.online {
-        Color::Green
+        theme.success
     } else {
-        Color::DarkGray
+        theme.dim
     };
     let status_text = if device.online { "Online" } else { "Offline" };
 
-    let title = Line::from(vec![
+    let mut title = Line::from(vec![
         Span::raw("Device Info: "),
         Span::styled(
             &device.hostname,
@@ -327,151 +465,131 @@ fn render_device_info(device: &crate::api::datto::types::Device, frame: &mut Fra
         Span::styled("■ ", Style::default().fg(status_color)),
         Span::raw(status_text),
     ]);
+    if watched {
+        title.push_span(Span::styled(" (watching)", Style::default().fg(theme.accent)));
+    }
 
-    let info_block = Block::default().borders(Borders::ALL).title(title);
+    let info_block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(pane_border(focused));
 
-    let p = Paragraph::new(text)
-        .block(info_block)
-        .wrap(Wrap { trim: true });
-    frame.render_widget(p, area);
+    let p = Paragraph::new(text).wrap(Wrap { trim: true });
+    layout::render_scrolled(frame, area, info_block, p, scroll);
+}
+
+/// Border of a scrollable pane; 'p' moves the focus between them.
+fn pane_border(focused: bool) -> Style {
+    if focused {
+        Style::default().fg(theme::current().accent)
+    } else {
+        Style::default()
+    }
 }
 
 fn render_device_activities(app: &mut App, frame: &mut Frame, area: Rect) {
-    let block = Block::default().borders(Borders::ALL).title("Activities");
+    let theme = theme::current();
+    // Kept up to date by live fetches; new ones are counted until the table is moved through
+    let mut title = vec![Span::raw("Activities (live)")];
+    let new_count = app.device_detail().new_activity_count;
+    if new_count > 0 {
+        title.push(Span::styled(
+            match new_count {
+                1 => " 1 new activity ".to_string(),
+                n => format!(" {} new activities ", n),
+            },
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD),
+        ));
+    }
+    let block = Block::default().borders(Borders::ALL).title(Line::from(title));
 
-    if app.activity_logs_loading {
-        frame.render_widget(Paragraph::new("Loading activities...").block(block), area);
+    if app.device_detail().activity_logs_loading {
+        let columns = ACTIVITY_COLUMNS.len();
+        loading::render_loading_table(frame, area, block, "Loading activities...", columns);
         return;
     }
 
-    if let Some(err) = &app.activity_logs_error {
+    if let Some(err) = &app.device_detail().activity_logs_error {
         frame.render_widget(
             Paragraph::new(format!("Error: {}", err))
-                .style(Style::default().fg(Color::Red))
+                .style(Style::default().fg(theme.error))
                 .block(block),
             area,
         );
         return;
     }
 
-    if app.activity_logs.is_empty() {
+    if app.device_detail().activity_logs.is_empty() {
         frame.render_widget(Paragraph::new("No activities found.").block(block), area);
         return;
     }
 
-    let rows: Vec<Row> = app
-        .activity_logs
-        .iter()
-        .enumerate()
-        .map(|(i, log)| {
-            let style = if Some(i) == app.activity_logs_table_state.selected() {
-                Style::default().add_modifier(Modifier::REVERSED)
-            } else {
-                Style::default()
-            };
-
-            // Convert date (f64 timestamp) to readable string
-            let date_str = format_timestamp(log.date.map(serde_json::Value::from));
+    let view = &mut app.device_detail_mut().activity_logs;
+    render_table_view(frame, area, block, view, &ACTIVITY_TABLE, &ACTIVITY_COLUMNS, |_, log, fit| {
+        // Convert date (f64 timestamp) to readable string
+        let date_str = format_time(log.at());
 
-            let user_name = log
-                .user
-                .as_ref()
-                .and_then(|u| u.user_name.clone())
-                .unwrap_or_else(|| "System".to_string());
-
-            // Parse Details JSON if possible to extract Job Name and Status
-            let mut job_status = String::new();
-            let mut job_name = log.details.clone().unwrap_or_default();
-
-            if let Some(details_json) = &log.details {
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(details_json) {
-                    if let Some(status) = parsed.get("job.status").and_then(|s| s.as_str()) {
-                        job_status = status.to_string();
-                    }
-                    if let Some(name) = parsed.get("job.name").and_then(|s| s.as_str()) {
-                        job_name = name.to_string();
-                    }
-                }
-            }
-
-            let status_style = match job_status.to_lowercase().as_str() {
-                "expired" => Style::default().fg(Color::Rgb(255, 165, 0)), // Orange
-                "scheduled" => Style::default().fg(Color::Blue),
-                "running" => Style::default().fg(Color::Cyan),
-                "success" => Style::default().fg(Color::Green),
-                "warning" => Style::default().fg(Color::Rgb(255, 165, 0)), // Orange
-                "failure" => Style::default().fg(Color::Red),
-                _ => Style::default(),
-            };
+        let user_name = activity_user(log);
 
-            Row::new(vec![
-                Cell::from(date_str),
-                Cell::from(job_name), // Display Job Name instead of raw details
-                Cell::from(Span::styled(job_status, status_style)), // Display Status
-                Cell::from(log.action.as_deref().unwrap_or("")),
-                Cell::from(log.category.as_deref().unwrap_or("")),
-                Cell::from(user_name),
-            ])
-            .style(style)
-        })
-        .collect();
+        // Parse Details JSON if possible to extract Job Name and Status
+        let (job_name, job_status) = activity_job_summary(log);
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(22),     // Time
-            Constraint::Percentage(40), // Job Name
-            Constraint::Length(12),     // Status
-            Constraint::Length(15),     // Action
-            Constraint::Length(10),     // Category
-            Constraint::Length(15),     // User
-        ],
-    )
-    .header(
-        Row::new(vec![
-            "Time", "Activity", "Status", "Action", "Category", "User",
-        ])
-        .style(Style::default().add_modifier(Modifier::BOLD)),
-    )
-    .block(block)
-    .highlight_symbol(">> ");
+        let status_style = match job_status.to_lowercase().as_str() {
+            "expired" => Style::default().fg(theme.caution),
+            "scheduled" => Style::default().fg(theme.info),
+            "running" => Style::default().fg(theme.accent),
+            "success" => Style::default().fg(theme.success),
+            "warning" => Style::default().fg(theme.caution),
+            "failure" => Style::default().fg(theme.error),
+            _ => Style::default(),
+        };
 
-    frame.render_stateful_widget(table, area, &mut app.activity_logs_table_state);
+        Row::new(fit.pick(vec![
+            Cell::from(date_str),
+            Cell::from(job_name), // Display Job Name instead of raw details
+            Cell::from(Span::styled(job_status, status_style)), // Display Status
+            Cell::from(log.action.as_deref().unwrap_or("")),
+            Cell::from(log.category.as_deref().unwrap_or("")),
+            Cell::from(user_name),
+        ]))
+    });
 }
 
 fn render_software(app: &mut App, frame: &mut Frame, area: Rect) {
-    let title = if !app.software_search_query.is_empty() || app.is_software_searching {
-        format!("Software (Search: {})", app.software_search_query)
+    let theme = theme::current();
+    let title = if !app.device_detail().software_search_query.is_empty() || app.device_detail().is_software_searching {
+        format!("Software (Search: {})", app.device_detail().software_search_query)
     } else {
         "Software".to_string()
     };
     let block = Block::default().borders(Borders::ALL).title(title);
 
-    if app.device_software_loading {
-        frame.render_widget(Paragraph::new("Loading software...").block(block), area);
+    if app.device_detail().device_software_loading {
+        loading::render_loading_table(frame, area, block, "Loading software...", 2);
         return;
     }
 
-    if let Some(err) = &app.device_software_error {
+    if let Some(err) = &app.device_detail().device_software_error {
         frame.render_widget(
             Paragraph::new(format!("Error: {}", err))
-                .style(Style::default().fg(Color::Red))
+                .style(Style::default().fg(theme.error))
                 .block(block),
             area,
         );
         return;
     }
 
-    if app.device_software.is_empty() {
+    if app.device_detail().device_software.items().is_empty() {
         frame.render_widget(Paragraph::new("No software found.").block(block), area);
         return;
     }
 
-    if app.filtered_software.is_empty() && !app.software_search_query.is_empty() {
+    let detail = app.device_detail();
+    if detail.device_software.is_empty() && !detail.software_search_query.is_empty() {
         frame.render_widget(
             Paragraph::new(format!(
                 "No software matches '{}'",
-                app.software_search_query
+                app.device_detail().software_search_query
             ))
             .block(block),
             area,
@@ -479,37 +597,67 @@ fn render_software(app: &mut App, frame: &mut Frame, area: Rect) {
         return;
     }
 
+    let view = &mut app.device_detail_mut().device_software;
+    render_table_view(
+        frame,
+        area,
+        block,
+        view,
+        &SOFTWARE_TABLE,
+        &["Name",
+        "Version"],
+        |_, sw, fit| {
+            Row::new(fit.pick(vec![Cell::from(sw.name.as_str()), Cell::from(sw.version.as_str())]))
+        },
+    );
+}
+
+/// Read-only list of the policies applied to the device and whether it
+/// complies with each.
+fn render_policies(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let block = Block::default().borders(Borders::ALL).title("Policies").title_bottom(
+        Line::from(" Monitoring policies aren't listed by the RMM API; see Open Alerts ")
+            .right_aligned(),
+    );
+
     let rows: Vec<Row> = app
-        .filtered_software
-        .iter()
-        .enumerate()
-        .map(|(i, sw)| {
-            let style = if Some(i) == app.device_software_table_state.selected() {
-                Style::default().add_modifier(Modifier::REVERSED)
-            } else {
-                Style::default()
+        .selected_device_policies()
+        .into_iter()
+        .map(|policy| {
+            let (compliant, color) = match policy.compliant {
+                Some(true) => ("Yes", theme.success),
+                Some(false) => ("No", theme.error),
+                None => ("-", theme.dim),
             };
-
             Row::new(vec![
-                Cell::from(sw.name.clone()),
-                Cell::from(sw.version.clone()),
+                Cell::from(policy.name),
+                Cell::from(policy.kind),
+                Cell::from(policy.target),
+                Cell::from(policy.status),
+                Cell::from(Span::styled(compliant, Style::default().fg(color))),
             ])
-            .style(style)
         })
         .collect();
-
     let table = Table::new(
         rows,
         [
-            Constraint::Percentage(70), // Name
-            Constraint::Percentage(30), // Version
+            Constraint::Percentage(30),
+            Constraint::Length(10),
+            Constraint::Percentage(20),
+            Constraint::Percentage(30),
+            Constraint::Length(10),
         ],
     )
-    .header(Row::new(vec!["Name", "Version"]).style(Style::default().add_modifier(Modifier::BOLD)))
+    .header(
+        Row::new(vec!["Policy", "Type", "Target", "Status", "Compliant"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
     .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
     .highlight_symbol(">> ");
 
-    frame.render_stateful_widget(table, area, &mut app.device_software_table_state);
+    frame.render_stateful_widget(table, area, &mut app.device_detail_mut().policy_state);
 }
 
 fn render_device_security(
@@ -518,7 +666,12 @@ fn render_device_security(
     frame: &mut Frame,
     area: Rect,
 ) {
-    let block = Block::default().borders(Borders::ALL).title("Security");
+    let theme = theme::current();
+    let focused = app.device_detail().scrolled_pane == DevicePane::Security;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Security")
+        .border_style(pane_border(focused));
 
     let mut lines = Vec::new();
 
@@ -554,11 +707,11 @@ fn render_device_security(
     }
 
     let av_status_color = match av_status_raw {
-        "RunningAndUpToDate" => Color::Green,
-        "RunningAndNotUpToDate" => Color::Yellow,
-        "NotDetected" => Color::Rgb(255, 165, 0), // Orange
-        "NotRunning" => Color::Red,
-        _ => Color::White,
+        "RunningAndUpToDate" => theme.success,
+        "RunningAndNotUpToDate" => theme.warning,
+        "NotDetected" => theme.caution,
+        "NotRunning" => theme.error,
+        _ => theme.text,
     };
 
     // Always show basic Product and Status
@@ -572,17 +725,21 @@ fn render_device_security(
         Span::styled(av_status_formatted, Style::default().fg(av_status_color)),
     ]));
 
+    if let Some(line) = mdr_mapping_line(app, device) {
+        lines.push(line);
+    }
+
     if av_product_lower.contains("sophos") {
-        if let Some(loading) = app.sophos_loading.get(&device.hostname) {
+        if let Some(loading) = app.security().sophos_loading.get(&device.hostname) {
             if *loading {
                 lines.push(Line::from(Span::styled(
-                    "Loading Sophos data...",
-                    Style::default().fg(Color::Yellow),
+                    loading_text("Loading Sophos data..."),
+                    Style::default().fg(theme.warning),
                 )));
             }
         }
 
-        if let Some(endpoint) = app.sophos_endpoints.get(&device.hostname) {
+        if let Some(endpoint) = app.security().sophos_endpoints.get(&device.hostname) {
             let health = endpoint
                 .health
                 .as_ref()
@@ -591,10 +748,10 @@ fn render_device_security(
                 .unwrap_or("Unknown");
 
             let health_color = match health.to_lowercase().as_str() {
-                "good" => Color::Green,
-                "bad" => Color::Red,
-                "suspicious" => Color::Yellow,
-                _ => Color::White,
+                "good" => theme.success,
+                "bad" => theme.error,
+                "suspicious" => theme.warning,
+                _ => theme.text,
             };
 
             lines.push(Line::from(vec![
@@ -613,20 +770,15 @@ fn render_device_security(
                 Span::styled(
                     if isolated { "Isolated" } else { "Not Isolated" },
                     if isolated {
-                        Style::default().fg(Color::Red)
+                        Style::default().fg(theme.error)
                     } else {
-                        Style::default().fg(Color::Green)
+                        Style::default().fg(theme.success)
                     },
                 ),
             ]));
 
-            if let Some(status) = app.scan_status.get(&device.hostname) {
-                lines.push(Line::from(vec![
-                    Span::raw("Scan Status: "),
-                    Span::styled(format!("{:?}", status), Style::default().fg(Color::Cyan)),
-                ]));
-            }
-        } else if !app
+            lines.extend(scan_status_line(app.security(), &device.hostname));
+        } else if !app.security()
             .sophos_loading
             .get(&device.hostname)
             .cloned()
@@ -635,16 +787,16 @@ fn render_device_security(
             lines.push(Line::from("Detailed Sophos data not available."));
         }
     } else if av_product_lower.contains("datto av") || av_product_lower.contains("datto edr") {
-        if let Some(loading) = app.datto_av_loading.get(&device.hostname) {
+        if let Some(loading) = app.security().datto_av_loading.get(&device.hostname) {
             if *loading {
                 lines.push(Line::from(Span::styled(
-                    "Loading Datto AV data...",
-                    Style::default().fg(Color::Yellow),
+                    loading_text("Loading Datto AV data..."),
+                    Style::default().fg(theme.warning),
                 )));
             }
         }
 
-        if let Some(agent) = app.datto_av_agents.get(&device.hostname) {
+        if let Some(agent) = app.security().datto_av_agents.get(&device.hostname) {
             lines.push(Line::from(vec![
                 Span::raw("Agent Status: "),
                 Span::raw(agent.status.as_deref().unwrap_or("Unknown")),
@@ -654,13 +806,8 @@ fn render_device_security(
                 Span::raw(agent.version.as_deref().unwrap_or("Unknown")),
             ]));
 
-            if let Some(status) = app.scan_status.get(&device.hostname) {
-                lines.push(Line::from(vec![
-                    Span::raw("Scan Status: "),
-                    Span::styled(format!("{:?}", status), Style::default().fg(Color::Cyan)),
-                ]));
-            }
-        } else if !app
+            lines.extend(scan_status_line(app.security(), &device.hostname));
+        } else if !app.security()
             .datto_av_loading
             .get(&device.hostname)
             .cloned()
@@ -668,19 +815,23 @@ fn render_device_security(
         {
             lines.push(Line::from("Detailed Datto AV data not available."));
         }
+    } else if av_product_lower.contains("defender") {
+        render_defender_lines(app, &device.hostname, &mut lines);
+    } else if av_product_lower.contains("sentinel") {
+        render_sentinelone_lines(app, &device.hostname, &mut lines);
     }
 
     // Rocket Cyber Info
-    if let Some(loading) = app.rocket_loading.get(&device.hostname) {
+    if let Some(loading) = app.security().rocket_loading.get(&device.hostname) {
         if *loading {
             lines.push(Line::from(Span::styled(
-                "Loading Rocket Cyber data...",
-                Style::default().fg(Color::Yellow),
+                loading_text("Loading Rocket Cyber data..."),
+                Style::default().fg(theme.warning),
             )));
         }
     }
 
-    if let Some(agent) = app.rocket_agents.get(&device.hostname) {
+    if let Some(agent) = app.security().rocket_agents.get(&device.hostname) {
         lines.push(Line::from("")); // Spacer
         lines.push(Line::from(Span::styled(
             "Rocket Cyber",
@@ -688,9 +839,9 @@ fn render_device_security(
         )));
 
         let conn_color = if agent.connectivity.to_lowercase() == "online" {
-            Color::Green
+            theme.success
         } else {
-            Color::Red
+            theme.error
         };
 
         lines.push(Line::from(vec![
@@ -711,6 +862,197 @@ fn render_device_security(
         ]));
     }
 
-    let p = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
-    frame.render_widget(p, area);
+    let mut scroll = app.device_detail().security_scroll;
+    let p = Paragraph::new(lines).wrap(Wrap { trim: true });
+    layout::render_scrolled(frame, area, block, p, &mut scroll);
+    app.device_detail_mut().security_scroll = scroll;
+}
+
+/// The site's MDR provider and id, each marked with whether the site sets it
+/// or inherits the account default.
+fn mdr_mapping_line(
+    app: &App,
+    device: &crate::api::datto::types::Device,
+) -> Option<Line<'static>> {
+    let theme = theme::current();
+    let site = app.site_list().sites.iter().find(|s| s.uid == device.site_uid)?;
+    let mapping = MdrMapping::resolve(site, &app.security().mdr_defaults);
+    if mapping.provider.is_none() && mapping.id.is_none() {
+        return None;
+    }
+    let mut spans = vec![Span::styled("MDR: ", Style::default().add_modifier(Modifier::BOLD))];
+    for value in [&mapping.provider, &mapping.id].into_iter().flatten() {
+        if spans.len() > 1 {
+            spans.push(Span::raw(" · "));
+        }
+        let color = match value.source {
+            MappingSource::Account => theme.info,
+            MappingSource::SiteOverride => theme.warning,
+            MappingSource::Site => theme.muted,
+        };
+        spans.push(Span::raw(value.value.clone()));
+        spans.push(Span::styled(
+            format!(" ({})", value.source.label()),
+            Style::default().fg(color),
+        ));
+    }
+    Some(Line::from(spans))
+}
+
+/// The state of the device's latest scan this session, if any.
+fn scan_status_line(security: &SecurityState, hostname: &str) -> Option<Line<'static>> {
+    let theme = theme::current();
+    let job = security.scans.latest(hostname)?;
+    let color = match job.state {
+        ScanState::Completed => theme.success,
+        ScanState::Failed => theme.error,
+        _ => theme.accent,
+    };
+    Some(Line::from(vec![
+        Span::raw("Scan Status: "),
+        Span::styled(job.state.label(), Style::default().fg(color)),
+    ]))
+}
+
+/// Alerts or threats listed under a console's agent; the rest are only counted.
+const AGENT_ALERTS_SHOWN: usize = 3;
+
+/// Health, risk and open alerts of the device's Defender machine.
+fn render_defender_lines(app: &App, hostname: &str, lines: &mut Vec<Line<'_>>) {
+    let theme = theme::current();
+    let security = app.security();
+    let loading = security.defender_loading.get(hostname).copied().unwrap_or(false);
+    if loading {
+        lines.push(Line::from(Span::styled(
+            loading_text("Loading Defender data..."),
+            Style::default().fg(theme.warning),
+        )));
+    }
+
+    let Some(machine) = security.defender_machines.get(hostname) else {
+        if !loading {
+            lines.push(Line::from("Detailed Defender data not available."));
+        }
+        return;
+    };
+
+    let health = machine.health_status.clone().unwrap_or_else(|| "Unknown".to_string());
+    let health_color = match health.as_str() {
+        "Active" => theme.success,
+        "Inactive" | "NoSensorData" => theme.error,
+        "ImpairedCommunication" | "NoSensorDataImpairedCommunication" => theme.warning,
+        _ => theme.text,
+    };
+    lines.push(Line::from(vec![
+        Span::raw("Health: "),
+        Span::styled(health, Style::default().fg(health_color)),
+    ]));
+
+    let risk = machine.risk_score.clone().unwrap_or_else(|| "Unknown".to_string());
+    let risk_color = match risk.as_str() {
+        "High" => theme.error,
+        "Medium" => theme.warning,
+        "Low" | "Informational" | "None" => theme.success,
+        _ => theme.text,
+    };
+    lines.push(Line::from(vec![
+        Span::raw("Risk Score: "),
+        Span::styled(risk, Style::default().fg(risk_color)),
+    ]));
+    lines.push(Line::from(format!(
+        "Exposure: {}",
+        machine.exposure_level.as_deref().unwrap_or("Unknown")
+    )));
+    lines.push(Line::from(format!(
+        "Last Seen: {}",
+        format_timestamp(machine.last_seen.clone().map(serde_json::Value::String))
+    )));
+
+    lines.extend(scan_status_line(security, hostname));
+
+    let Some(alerts) = security.defender_alerts.get(hostname) else {
+        return;
+    };
+    let alert_color = if alerts.is_empty() { theme.success } else { theme.error };
+    lines.push(Line::from(vec![
+        Span::raw("Active Alerts: "),
+        Span::styled(alerts.len().to_string(), Style::default().fg(alert_color)),
+    ]));
+    for alert in alerts.iter().take(AGENT_ALERTS_SHOWN) {
+        let severity = alert.severity.clone().unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(format!("[{}] ", severity), priority_style(&severity)),
+            Span::raw(alert.title.clone().unwrap_or_else(|| alert.id.clone())),
+        ]));
+    }
+    if alerts.len() > AGENT_ALERTS_SHOWN {
+        lines.push(Line::from(format!("  ...and {} more", alerts.len() - AGENT_ALERTS_SHOWN)));
+    }
+}
+
+/// Network status and open threats of the device's SentinelOne agent.
+fn render_sentinelone_lines(app: &App, hostname: &str, lines: &mut Vec<Line<'_>>) {
+    let theme = theme::current();
+    let security = app.security();
+    let loading = security.sentinelone_loading.get(hostname).copied().unwrap_or(false);
+    if loading {
+        lines.push(Line::from(Span::styled(
+            loading_text("Loading SentinelOne data..."),
+            Style::default().fg(theme.warning),
+        )));
+    }
+
+    let Some(agent) = security.sentinelone_agents.get(hostname) else {
+        if !loading {
+            lines.push(Line::from("Detailed SentinelOne data not available."));
+        }
+        return;
+    };
+
+    let (network, network_color) = if agent.is_disconnected() {
+        ("Disconnected", theme.error)
+    } else {
+        ("Connected", theme.success)
+    };
+    lines.push(Line::from(vec![
+        Span::raw("Network: "),
+        Span::styled(network, Style::default().fg(network_color)),
+    ]));
+    lines.push(Line::from(format!(
+        "Agent Version: {}",
+        agent.agent_version.as_deref().unwrap_or("Unknown")
+    )));
+    lines.push(Line::from(format!(
+        "Last Active: {}",
+        format_timestamp(agent.last_active_date.clone().map(serde_json::Value::String))
+    )));
+
+    lines.extend(scan_status_line(security, hostname));
+
+    let Some(threats) = security.sentinelone_threats.get(hostname) else {
+        return;
+    };
+    let threat_color = if threats.is_empty() { theme.success } else { theme.error };
+    lines.push(Line::from(vec![
+        Span::raw("Active Threats: "),
+        Span::styled(threats.len().to_string(), Style::default().fg(threat_color)),
+    ]));
+    for threat in threats.iter().take(AGENT_ALERTS_SHOWN) {
+        let info = &threat.threat_info;
+        let confidence = info.confidence_level.clone().unwrap_or_default();
+        let style = if confidence == "malicious" {
+            Style::default().fg(theme.error)
+        } else {
+            Style::default().fg(theme.warning)
+        };
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(format!("[{}] ", confidence), style),
+            Span::raw(info.threat_name.clone().unwrap_or_else(|| threat.id.clone())),
+        ]));
+    }
+    if threats.len() > AGENT_ALERTS_SHOWN {
+        lines.push(Line::from(format!("  ...and {} more", threats.len() - AGENT_ALERTS_SHOWN)));
+    }
 }
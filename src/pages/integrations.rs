@@ -0,0 +1,154 @@
+use crate::app::{App, ConfigState, IntegrationStatus};
+use crate::common::utils::format_age;
+use crate::ui::theme;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+};
+
+pub fn render_integrations(app: &mut App, frame: &mut Frame, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(8), Constraint::Min(0)])
+        .split(area);
+
+    render_integration_list(app, frame, chunks[0]);
+    render_integration_detail(app, frame, chunks[1]);
+}
+
+fn config_style(config: &ConfigState) -> Style {
+    let theme = theme::current();
+    Style::default().fg(match config {
+        ConfigState::Configured => theme.success,
+        ConfigState::Invalid(_) => theme.error,
+        ConfigState::NotConfigured => theme.muted,
+        ConfigState::Demo => theme.info,
+    })
+}
+
+fn auth_cell(status: &IntegrationStatus) -> Cell<'static> {
+    let theme = theme::current();
+    if status.authenticating {
+        return Cell::from("Authenticating...").style(Style::default().fg(theme.warning));
+    }
+    match &status.last_auth {
+        Some((at, Ok(()))) => Cell::from(format!("OK ({})", format_age(*at)))
+            .style(Style::default().fg(theme.success)),
+        Some((at, Err(_))) => Cell::from(format!("Failed ({})", format_age(*at)))
+            .style(Style::default().fg(theme.error)),
+        None => Cell::from("-").style(Style::default().fg(theme.muted)),
+    }
+}
+
+fn render_integration_list(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let rows: Vec<Row> = app
+        .integrations()
+        .statuses
+        .iter()
+        .map(|status| {
+            Row::new(vec![
+                Cell::from(status.provider.title()),
+                Cell::from(status.config.label()).style(config_style(&status.config)),
+                auth_cell(status),
+                Cell::from(
+                    status
+                        .last_success
+                        .map_or_else(|| "-".to_string(), format_age),
+                ),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(20),
+            Constraint::Percentage(30),
+            Constraint::Percentage(25),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Integration",
+            "Config",
+            "Last Auth",
+            "Last Successful Call",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title("Integrations"))
+    .row_highlight_style(
+        Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(theme.highlight),
+    )
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.integrations_mut().table_state);
+}
+
+fn render_integration_detail(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Details")
+        .title_bottom(Line::from(" a: re-authenticate ").right_aligned());
+    let Some(status) = app
+        .integrations()
+        .selected_provider()
+        .map(|p| app.integrations().status(p))
+    else {
+        frame.render_widget(Paragraph::new("No integration selected").block(block), area);
+        return;
+    };
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(theme.muted));
+    let config = match &status.config {
+        ConfigState::NotConfigured => {
+            "No settings in this profile or the environment; the integration is off".to_string()
+        }
+        ConfigState::Invalid(e) => e.clone(),
+        ConfigState::Configured => "Settings complete".to_string(),
+        ConfigState::Demo => "Fake data from --demo".to_string(),
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            status.provider.title(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(vec![
+            label("Config:      "),
+            Span::styled(config, config_style(&status.config)),
+        ]),
+    ];
+    if let Some((at, result)) = &status.last_auth {
+        let (text, color) = match result {
+            Ok(()) => ("Succeeded".to_string(), theme.success),
+            Err(e) => (format!("Failed: {}", e), theme.error),
+        };
+        lines.push(Line::from(vec![
+            label("Last auth:   "),
+            Span::styled(text, Style::default().fg(color)),
+            Span::styled(
+                format!(" ({})", format_age(*at)),
+                Style::default().fg(theme.muted),
+            ),
+        ]));
+    }
+    if let Some((at, e)) = &status.last_error {
+        lines.push(Line::from(vec![
+            label("Last error:  "),
+            Span::styled(e.clone(), Style::default().fg(theme.error)),
+            Span::styled(
+                format!(" ({})", format_age(*at)),
+                Style::default().fg(theme.muted),
+            ),
+        ]));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: true }).block(block),
+        area,
+    );
+}
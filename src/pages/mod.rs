@@ -1,5 +1,12 @@
+pub mod account_variables;
 pub mod activity_detail;
+pub mod collections;
+pub mod components;
 pub mod device_detail;
+pub mod integrations;
 pub mod popups;
+pub mod report;
+pub mod setup_wizard;
 pub mod site_detail;
 pub mod site_list;
+pub mod tags;
@@ -1,28 +1,73 @@
-use crate::app::{App, InputField, QuickAction, RebootFocus, RunComponentStep};
-use crate::common::utils::centered_rect;
+use crate::app::{
+    App, CurrentView, INCIDENT_SEVERITY, InputField, Popup, QuickAction, RebootFocus,
+    RunComponentStep, ScanState, SettingKind, VariableChange, VariableKind, diagnostic_lines,
+    split_search_query, udf_label,
+};
+use crate::common::markdown::markdown_lines;
+use crate::common::net_tools::NetTool;
+use crate::common::time::{format_time, format_timestamp};
+use crate::common::utils::{centered_rect, find_ignore_case, format_age, loading_text};
+use crate::pages::activity_detail::job_status_color;
+use crate::pages::site_detail::{av_cell, last_seen_cell};
+use crate::ui::layout::Column;
+use crate::ui::table::render_table_view;
+use crate::ui::theme;
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
 };
+use std::collections::BTreeMap;
+
+/// Column headers of the device search results.
+const SEARCH_RESULT_COLUMNS: [&str; 7] =
+    ["Hostname", "Site", "Status", "OS", "Patch", "Last Seen", "AV"];
+
+/// Columns of the device search results, by `SEARCH_RESULT_COLUMNS`.
+const SEARCH_RESULT_TABLE: [Column; 7] = [
+    Column::new(Constraint::Percentage(18), 12, 0), // Hostname
+    Column::new(Constraint::Percentage(17), 10, 1), // Site
+    Column::new(Constraint::Percentage(8), 7, 2),   // Status
+    Column::new(Constraint::Percentage(18), 10, 4), // OS
+    Column::new(Constraint::Percentage(11), 8, 5),  // Patch
+    Column::new(Constraint::Percentage(10), 9, 3),  // Last Seen
+    Column::new(Constraint::Percentage(18), 10, 4), // AV
+];
 
 pub fn render_input_modal(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let area = centered_rect(60, 20, frame.area());
     frame.render_widget(Clear, area); // Clear background
 
-    let (title, is_single_field_edit) = if let Some(field) = &app.input_state.editing_setting {
-        (format!("Edit Setting: {:?}", field), true)
-    } else if let Some(idx) = app.editing_udf_index {
-        (format!("Edit UDF {}", idx + 1), true)
-    } else if app.input_state.is_creating {
-        ("Create Variable".to_string(), false)
+    let (title, is_single_field_edit) = if app.input_state.acknowledging.is_some() {
+        ("Acknowledge Alert".to_string(), true)
+    } else if let Some(field) = &app.input_state.editing_setting {
+        (format!("Edit Setting: {}", field.label()), true)
+    } else if app.site_detail().is_choosing_udf_columns {
+        ("UDF Columns".to_string(), true)
+    } else if let Some((uid, number)) = &app.site_detail().editing_udf_cell {
+        let hostname = app
+            .site_detail()
+            .devices
+            .items()
+            .iter()
+            .find(|d| &d.uid == uid)
+            .map(|d| d.hostname.as_str())
+            .unwrap_or("device");
+        (format!("Edit {} on {}", udf_label(&app.udf_labels, *number), hostname), true)
+    } else if app.device_detail().editing_tags {
+        ("Edit Tags".to_string(), true)
+    } else if let Some(idx) = app.device_detail().editing_udf_index {
+        (format!("Edit {}", udf_label(&app.udf_labels, idx + 1)), true)
     } else {
-        ("Edit Variable".to_string(), false)
+        let scope = if app.current_view == CurrentView::AccountVariables { "Account " } else { "" };
+        let verb = if app.input_state.is_creating { "Create" } else { "Edit" };
+        (format!("{} {}Variable", verb, scope), false)
     };
 
     let block = Block::default()
         .borders(Borders::ALL)
         .title(title)
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.popup_bg));
 
     frame.render_widget(block, area);
 
@@ -48,13 +93,25 @@ pub fn render_input_modal(app: &mut App, frame: &mut Frame) {
         .split(area);
 
     if is_single_field_edit {
-        let (buffer, label) = if app.editing_udf_index.is_some() {
+        let (buffer, label) = if app.input_state.acknowledging.is_some() {
+            (app.input_state.name_buffer.clone(), "Note (optional)")
+        } else if app.site_detail().is_choosing_udf_columns {
+            (app.input_state.name_buffer.clone(), "UDF numbers, e.g. 1-5, 17")
+        } else if app.device_detail().editing_tags {
+            (app.input_state.value_buffer.clone(), "Tags, comma-separated")
+        } else if app.site_detail().editing_udf_cell.is_some()
+            || app.device_detail().editing_udf_index.is_some()
+        {
             (app.input_state.value_buffer.clone(), "Value")
+        } else if app.input_state.editing_setting.is_some_and(|f| f.kind() == SettingKind::Secret) {
+            ("•".repeat(app.input_state.name_buffer.chars().count()), "Value")
+        } else if app.input_state.editing_setting.is_some_and(|f| f.kind() == SettingKind::Number) {
+            (app.input_state.name_buffer.clone(), "Value (digits)")
         } else {
             (app.input_state.name_buffer.clone(), "Value")
         };
 
-        let input_style = Style::default().fg(Color::Yellow);
+        let input_style = Style::default().fg(theme.highlight);
         let input_block = Block::default()
             .borders(Borders::ALL)
             .title(label)
@@ -68,7 +125,7 @@ pub fn render_input_modal(app: &mut App, frame: &mut Frame) {
     } else {
         // Variable Edit
         let name_style = if app.input_state.active_field == InputField::Name {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(theme.highlight)
         } else {
             Style::default()
         };
@@ -81,7 +138,7 @@ pub fn render_input_modal(app: &mut App, frame: &mut Frame) {
 
         // Value Input
         let value_style = if app.input_state.active_field == InputField::Value {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(theme.highlight)
         } else {
             Style::default()
         };
@@ -100,23 +157,24 @@ pub fn render_input_modal(app: &mut App, frame: &mut Frame) {
 }
 
 pub fn render_quick_action_menu(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let area = centered_rect(40, 30, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Quick Actions (Esc to cancel)")
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.popup_bg));
 
-    let rows: Vec<Row> = app
+    let rows: Vec<Row> = app.popups()
         .quick_actions
         .iter()
         .enumerate()
         .map(|(i, action)| {
-            let style = if Some(i) == app.quick_action_list_state.selected() {
+            let style = if Some(i) == app.popups().quick_action_list_state.selected() {
                 Style::default()
                     .add_modifier(Modifier::REVERSED)
-                    .fg(Color::Yellow)
+                    .fg(theme.highlight)
             } else {
                 Style::default()
             };
@@ -125,12 +183,28 @@ pub fn render_quick_action_menu(app: &mut App, frame: &mut Frame) {
                 QuickAction::ScheduleReboot => "Schedule Reboot",
                 QuickAction::RunComponent => "Run Component",
                 QuickAction::RunAvScan => "Run AV Scan",
+                QuickAction::IsolateDevice => "Isolate From Network",
                 QuickAction::OpenWebRemote => "Open Web Remote",
                 QuickAction::ReloadData => "Reload Data",
                 QuickAction::MoveToSite => "Move Device to Site",
                 QuickAction::UpdateWarranty => "Update Warranty",
                 QuickAction::ClearWarranty => "Clear Warranty",
-            };
+                QuickAction::EditDevice => "Edit Description / Friendly Name",
+                QuickAction::Decommission => "Decommission Device",
+                QuickAction::NetworkTools => "Network Tools (Ping / Port / DNS)",
+                QuickAction::Launcher(index) => app
+                    .launchers
+                    .get(*index)
+                    .map_or("Launcher", |launcher| launcher.name.as_str()),
+                QuickAction::Custom(index) => app
+                    .custom_quick_actions
+                    .get(*index)
+                    .map_or("Custom Action", |action| action.name.as_str()),
+                QuickAction::CollectionRunComponent => "Run Component on Collection",
+                QuickAction::CollectionScan => "Run AV Scan on Collection",
+                QuickAction::CollectionReboot => "Schedule Reboot of Collection",
+            }
+            .to_string();
 
             Row::new(vec![Cell::from(label)]).style(style)
         })
@@ -140,17 +214,18 @@ pub fn render_quick_action_menu(app: &mut App, frame: &mut Frame) {
         .block(block)
         .highlight_symbol(">> ");
 
-    frame.render_stateful_widget(table, area, &mut app.quick_action_list_state);
+    frame.render_stateful_widget(table, area, &mut app.popups_mut().quick_action_list_state);
 }
 
 pub fn render_warranty_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let area = centered_rect(50, 20, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Update Warranty Date (YYYY-MM-DD)")
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.popup_bg));
     frame.render_widget(block.clone(), area);
 
     let layout = Layout::default()
@@ -181,8 +256,8 @@ pub fn render_warranty_popup(app: &mut App, frame: &mut Frame) {
     ];
 
     for i in 0..3 {
-        let style = if app.warranty_focus == focuses[i] {
-            Style::default().fg(Color::Yellow)
+        let style = if app.popups().warranty_focus == focuses[i] {
+            Style::default().fg(theme.highlight)
         } else {
             Style::default()
         };
@@ -191,14 +266,14 @@ pub fn render_warranty_popup(app: &mut App, frame: &mut Frame) {
             .borders(Borders::ALL)
             .title(labels[i])
             .style(style);
-        let p = Paragraph::new(app.warranty_segments[i].clone())
+        let p = Paragraph::new(app.popups().warranty_segments[i].clone())
             .block(block)
             .alignment(Alignment::Center);
         frame.render_widget(p, segments_layout[i]);
     }
 
-    if let Some(err) = &app.warranty_error {
-        let err_p = Paragraph::new(err.as_str()).style(Style::default().fg(Color::Red));
+    if let Some(err) = &app.popups().warranty_error {
+        let err_p = Paragraph::new(err.as_str()).style(Style::default().fg(theme.error));
         frame.render_widget(err_p, layout[1]);
     }
 
@@ -208,14 +283,313 @@ pub fn render_warranty_popup(app: &mut App, frame: &mut Frame) {
     frame.render_widget(instructions, layout[2]);
 }
 
+pub fn render_device_edit_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(60, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let hostname = app
+        .device_detail()
+        .selected_device
+        .as_ref()
+        .map(|d| d.hostname.clone())
+        .unwrap_or_default();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Edit Device: {}", hostname))
+        .style(Style::default().bg(theme.popup_bg));
+    frame.render_widget(block.clone(), area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Description
+            Constraint::Length(3), // Friendly Name
+            Constraint::Min(0),    // Instructions
+        ])
+        .split(block.inner(area));
+
+    let popups = app.popups();
+    for (i, label) in ["Description", "Friendly Name"].into_iter().enumerate() {
+        let focused = popups.device_edit_focus == i;
+        let style = if focused {
+            Style::default().fg(theme.highlight)
+        } else {
+            Style::default()
+        };
+        let mut value = popups.device_edit_fields[i].clone();
+        if focused {
+            value.push('_');
+        }
+        let input = Paragraph::new(value)
+            .block(Block::default().borders(Borders::ALL).title(label).style(style));
+        frame.render_widget(input, layout[i]);
+    }
+
+    let instructions = Paragraph::new(
+        "Tab: Switch | Enter: Save | Esc: Cancel (the hostname comes from the agent)",
+    )
+    .alignment(Alignment::Center)
+    .style(Style::default().add_modifier(Modifier::ITALIC));
+    frame.render_widget(instructions, layout[2]);
+}
+
+pub fn render_decommission_popup(app: &mut App, frame: &mut Frame) {
+    let Some(decommission) = &app.popups().decommission else {
+        return;
+    };
+    let theme = theme::current();
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Decommission Device: {}", decommission.hostname))
+        .border_style(Style::default().fg(theme.error))
+        .style(Style::default().bg(theme.popup_bg));
+    frame.render_widget(block.clone(), area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Warning
+            Constraint::Length(3), // Hostname
+            Constraint::Length(2), // AV console
+            Constraint::Min(0),    // Instructions
+        ])
+        .split(block.inner(area));
+
+    let warning = Paragraph::new(format!(
+        "This removes {} from RMM. Its agent stops reporting and its history is lost. \
+         Type the hostname to confirm.",
+        decommission.hostname
+    ))
+    .wrap(Wrap { trim: true })
+    .style(Style::default().fg(theme.error));
+    frame.render_widget(warning, layout[0]);
+
+    let style = if decommission.confirmed() {
+        Style::default().fg(theme.error).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.highlight)
+    };
+    let mut typed = decommission.typed.clone();
+    if !decommission.running {
+        typed.push('_');
+    }
+    let input = Paragraph::new(typed)
+        .block(Block::default().borders(Borders::ALL).title("Hostname").style(style));
+    frame.render_widget(input, layout[1]);
+
+    let av = match &decommission.av {
+        Some(av) => format!(
+            "[{}] Also remove it from {}",
+            if decommission.remove_av { "x" } else { " " },
+            av.console()
+        ),
+        None => "No Sophos or Datto AV endpoint found for this device".to_string(),
+    };
+    frame.render_widget(Paragraph::new(av), layout[2]);
+
+    let instructions = if decommission.running {
+        "Decommissioning..."
+    } else if decommission.av.is_some() {
+        "Tab: Toggle AV removal | Enter: Decommission | Esc: Cancel"
+    } else {
+        "Enter: Decommission | Esc: Cancel"
+    };
+    let instructions = Paragraph::new(instructions)
+        .alignment(Alignment::Center)
+        .style(Style::default().add_modifier(Modifier::ITALIC));
+    frame.render_widget(instructions, layout[3]);
+}
+
+pub fn render_site_deletion_popup(app: &mut App, frame: &mut Frame) {
+    let Some(deletion) = &app.popups().site_deletion else {
+        return;
+    };
+    let theme = theme::current();
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Delete Site: {}", deletion.name))
+        .border_style(Style::default().fg(theme.error))
+        .style(Style::default().bg(theme.popup_bg));
+    frame.render_widget(block.clone(), area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Warning
+            Constraint::Length(3), // Site name
+            Constraint::Min(0),    // Instructions
+        ])
+        .split(block.inner(area));
+
+    let warning = if deletion.device_count > 0 {
+        format!(
+            "{} still has {} devices. Move or decommission them before deleting the site.",
+            deletion.name, deletion.device_count
+        )
+    } else {
+        format!(
+            "This deletes {} from the account along with its variables and settings. \
+             Type the site name to confirm.",
+            deletion.name
+        )
+    };
+    let warning = Paragraph::new(warning)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(theme.error));
+    frame.render_widget(warning, layout[0]);
+
+    let style = if deletion.confirmed() {
+        Style::default().fg(theme.error).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.highlight)
+    };
+    let mut typed = deletion.typed.clone();
+    if !deletion.running && deletion.device_count == 0 {
+        typed.push('_');
+    }
+    let input = Paragraph::new(typed)
+        .block(Block::default().borders(Borders::ALL).title("Site Name").style(style));
+    frame.render_widget(input, layout[1]);
+
+    let instructions = if deletion.running {
+        "Deleting..."
+    } else if deletion.device_count > 0 {
+        "Esc: Close"
+    } else {
+        "Enter: Delete | Esc: Cancel"
+    };
+    let instructions = Paragraph::new(instructions)
+        .alignment(Alignment::Center)
+        .style(Style::default().add_modifier(Modifier::ITALIC));
+    frame.render_widget(instructions, layout[2]);
+}
+
+pub fn render_net_tools_popup(app: &mut App, frame: &mut Frame) {
+    let Some(state) = &app.popups().net_tools else {
+        return;
+    };
+    let theme = theme::current();
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Network Tools: {}", state.hostname))
+        .style(Style::default().bg(theme.popup_bg));
+    frame.render_widget(block.clone(), area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1),                              // Tools
+            Constraint::Length(state.targets.len() as u16 + 2), // Targets
+            Constraint::Min(3),                                 // Results
+            Constraint::Length(1),                              // Instructions
+        ])
+        .split(block.inner(area));
+
+    let mut tools = vec![Span::raw("Tool: ")];
+    for tool in NetTool::ALL {
+        let style = if tool == state.tool {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        tools.push(Span::styled(format!(" {} ", tool.name()), style));
+        tools.push(Span::raw(" "));
+    }
+    if state.tool == NetTool::Port {
+        tools.push(Span::raw("Port: "));
+        tools.push(Span::styled(
+            format!("{}_", state.port),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(tools)), layout[0]);
+
+    let targets: Vec<Line> = state
+        .targets
+        .iter()
+        .enumerate()
+        .map(|(i, (label, address))| {
+            let style = if i == state.target {
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let marker = if i == state.target { ">> " } else { "   " };
+            Line::styled(format!("{}{}: {}", marker, label, address), style)
+        })
+        .collect();
+    let targets = Paragraph::new(targets)
+        .block(Block::default().borders(Borders::ALL).title("Target"));
+    frame.render_widget(targets, layout[1]);
+
+    // Newest results at the bottom, keeping the latest in view
+    let mut results: Vec<Line> = state
+        .results
+        .iter()
+        .map(|result| {
+            let (mark, color, text) = match &result.outcome {
+                Ok(text) => ("✔ ", theme.success, text),
+                Err(text) => ("✘ ", theme.error, text),
+            };
+            Line::from(vec![
+                Span::styled(mark, Style::default().fg(color)),
+                Span::styled(
+                    format!("{}: ", result.label),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(text.clone()),
+            ])
+        })
+        .collect();
+    if state.running > 0 {
+        results.push(Line::styled(
+            format!("Running {} check(s)...", state.running),
+            Style::default().fg(theme.warning),
+        ));
+    }
+    let visible = layout[2].height.saturating_sub(2) as usize;
+    let skip = results.len().saturating_sub(visible);
+    let results = Paragraph::new(results.into_iter().skip(skip).collect::<Vec<_>>())
+        .block(Block::default().borders(Borders::ALL).title("Results"));
+    frame.render_widget(results, layout[2]);
+
+    let instructions = if state.tool == NetTool::Port {
+        "Tab: Tool | j/k: Target | 0-9: Port | Enter: Run | Esc: Close"
+    } else {
+        "Tab: Tool | j/k: Target | Enter: Run | Esc: Close"
+    };
+    let instructions = Paragraph::new(instructions)
+        .alignment(Alignment::Center)
+        .style(Style::default().add_modifier(Modifier::ITALIC));
+    frame.render_widget(instructions, layout[3]);
+}
+
 pub fn render_reboot_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let area = centered_rect(50, 40, frame.area());
     frame.render_widget(Clear, area);
 
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .title("Schedule Reboot")
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.popup_bg));
+    if let Some(collection) = &app.popups().collection_target {
+        block = block.title(Line::from(format!(" Collection: {} ", collection)).right_aligned());
+    }
     frame.render_widget(block.clone(), area);
 
     let layout = Layout::default()
@@ -230,12 +604,12 @@ pub fn render_reboot_popup(app: &mut App, frame: &mut Frame) {
         .split(block.inner(area));
 
     // Reboot Now Checkbox
-    let now_style = if app.reboot_focus == RebootFocus::RebootNow {
-        Style::default().fg(Color::Yellow)
+    let now_style = if app.popups().reboot_focus == RebootFocus::RebootNow {
+        Style::default().fg(theme.highlight)
     } else {
         Style::default()
     };
-    let now_text = if app.reboot_now {
+    let now_text = if app.popups().reboot_now {
         "[x] Reboot Now"
     } else {
         "[ ] Reboot Now"
@@ -271,10 +645,10 @@ pub fn render_reboot_popup(app: &mut App, frame: &mut Frame) {
     ];
 
     for i in 0..5 {
-        let style = if app.reboot_focus == focuses[i] {
-            Style::default().fg(Color::Yellow)
-        } else if app.reboot_now {
-            Style::default().fg(Color::DarkGray)
+        let style = if app.popups().reboot_focus == focuses[i] {
+            Style::default().fg(theme.highlight)
+        } else if app.popups().reboot_now {
+            Style::default().fg(theme.dim)
         } else {
             Style::default()
         };
@@ -283,45 +657,61 @@ pub fn render_reboot_popup(app: &mut App, frame: &mut Frame) {
             .borders(Borders::ALL)
             .title(labels[i])
             .style(style);
-        let p = Paragraph::new(app.reboot_segments[i].clone())
+        let p = Paragraph::new(app.popups().reboot_segments[i].clone())
             .block(block)
             .alignment(Alignment::Center);
         frame.render_widget(p, segments_layout[i]);
     }
 
-    // Error Message
-    if let Some(err) = &app.reboot_error {
-        let err_p = Paragraph::new(err.as_str()).style(Style::default().fg(Color::Red));
-        frame.render_widget(err_p, layout[2]);
+    // Status: confirmation, progress or validation error
+    let popups = app.popups();
+    let status = if let Some(confirmation) = &popups.reboot_confirmation {
+        Some((confirmation.clone(), theme.success))
+    } else if popups.reboot_submitting {
+        Some(("Submitting reboot job...".to_string(), theme.warning))
+    } else {
+        popups.reboot_error.clone().map(|err| (err, theme.error))
+    };
+    if let Some((text, color)) = status {
+        frame.render_widget(Paragraph::new(text).style(Style::default().fg(color)), layout[2]);
     }
 
     // Instructions
-    let instructions = Paragraph::new("Space: Toggle | Tab: Switch | Enter: Submit | Esc: Cancel")
+    let instructions = if popups.reboot_confirmation.is_some() {
+        "Enter/Esc: Close"
+    } else {
+        "Space: Toggle | Tab: Switch | Enter: Submit | Esc: Cancel"
+    };
+    let instructions = Paragraph::new(instructions)
         .alignment(Alignment::Center)
         .style(Style::default().add_modifier(Modifier::ITALIC));
     frame.render_widget(instructions, layout[3]);
 }
 
 pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let area = centered_rect(70, 70, frame.area());
     frame.render_widget(Clear, area);
 
-    let title = match app.run_component_step {
+    let title = match app.popups().run_component_step {
         RunComponentStep::Search => "Run Component - Select (Esc to cancel)",
         RunComponentStep::FillVariables => "Run Component - Variables (Esc to back)",
         RunComponentStep::Review => "Run Component - Review (Esc to back, Enter to Run)",
         RunComponentStep::Result => "Run Component - Result (Enter/Esc to close)",
     };
 
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .title(title)
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.popup_bg));
+    if let Some(collection) = &app.popups().collection_target {
+        block = block.title(Line::from(format!(" Collection: {} ", collection)).right_aligned());
+    }
     frame.render_widget(block.clone(), area);
 
     let inner_area = block.inner(area);
 
-    match app.run_component_step {
+    match app.popups().run_component_step {
         RunComponentStep::Search => {
             let layout = Layout::default()
                 .direction(Direction::Vertical)
@@ -334,35 +724,44 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
             // Search Input
             let input_block = Block::default()
                 .borders(Borders::ALL)
-                .title("Search Component");
-            let input = Paragraph::new(app.component_search_query.clone()).block(input_block);
+                .title("Search Component")
+                .title_bottom(Line::from(" Ctrl+S: star | Ctrl+R: re-run with last inputs ").right_aligned());
+            let input = Paragraph::new(app.popups().component_search_query.clone()).block(input_block);
             frame.render_widget(input, layout[0]);
 
             // Component List
-            if app.components_loading {
+            if app.popups().components_loading {
                 frame.render_widget(
-                    Paragraph::new("Loading components...").alignment(Alignment::Center),
+                    Paragraph::new(loading_text("Loading components...")).alignment(Alignment::Center),
                     layout[1],
                 );
-            } else if let Some(err) = &app.component_error {
+            } else if let Some(err) = &app.popups().component_error {
                 frame.render_widget(
                     Paragraph::new(format!("Error: {}", err))
-                        .style(Style::default().fg(Color::Red)),
+                        .style(Style::default().fg(theme.error)),
                     layout[1],
                 );
             } else {
-                let rows: Vec<Row> = app
+                let rows: Vec<Row> = app.popups()
                     .filtered_components
                     .iter()
                     .enumerate()
                     .map(|(i, comp)| {
-                        let style = if Some(i) == app.component_list_state.selected() {
+                        let style = if Some(i) == app.popups().component_list_state.selected() {
                             Style::default().add_modifier(Modifier::REVERSED)
                         } else {
                             Style::default()
                         };
+                        // ★ starred, ↻ run before (Ctrl+R re-runs with the same inputs)
+                        let marker = if app.components().is_starred(&comp.uid) {
+                            "★ "
+                        } else if app.components().last_run(&comp.uid).is_some() {
+                            "↻ "
+                        } else {
+                            ""
+                        };
                         Row::new(vec![
-                            Cell::from(comp.name.clone()),
+                            Cell::from(format!("{}{}", marker, comp.name)),
                             Cell::from(comp.category_code.clone().unwrap_or_default()),
                             Cell::from(comp.description.clone().unwrap_or_default()),
                         ])
@@ -384,14 +783,16 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
                 )
                 .highlight_symbol(">> ");
 
-                frame.render_stateful_widget(table, layout[1], &mut app.component_list_state);
+                frame.render_stateful_widget(table, layout[1], &mut app.popups_mut().component_list_state);
             }
         }
         RunComponentStep::FillVariables => {
-            if let Some(component) = &app.selected_component {
+            let choices = app.current_variable_choices();
+            let kind = app.current_variable_kind();
+            if let Some(component) = &app.popups().selected_component {
                 if let Some(vars) = &component.variables {
                     if let Some(current_var) =
-                        app.component_variables.get(app.component_variable_index)
+                        app.popups().component_variables.get(app.popups().component_variable_index)
                     {
                         let def = vars.iter().find(|v| v.name == current_var.name);
 
@@ -400,15 +801,20 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
                             .constraints([
                                 Constraint::Length(3), // Progress
                                 Constraint::Length(5), // Variable Info
-                                Constraint::Length(3), // Input
-                                Constraint::Min(0),    // Description/Help
+                                // Input, or the allowed values of a selection
+                                Constraint::Length(if kind == VariableKind::Selection {
+                                    choices.len().min(8) as u16 + 2
+                                } else {
+                                    3
+                                }),
+                                Constraint::Min(0), // Description/Help
                             ])
                             .split(inner_area);
 
                         let progress = format!(
                             "Variable {} of {}",
-                            app.component_variable_index + 1,
-                            app.component_variables.len()
+                            app.popups().component_variable_index + 1,
+                            app.popups().component_variables.len()
                         );
                         frame.render_widget(
                             Paragraph::new(progress).alignment(Alignment::Center),
@@ -443,15 +849,89 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
                         ];
                         frame.render_widget(Paragraph::new(info_text), layout[1]);
 
-                        let input_block = Block::default()
+                        let mut input_block = Block::default()
                             .borders(Borders::ALL)
-                            .title("Value")
-                            .style(Style::default().fg(Color::Yellow));
-
-                        let input_val = app.component_variable_input.clone();
+                            .title(match kind {
+                                VariableKind::Text => "Value",
+                                VariableKind::Selection => {
+                                    "Value (j/k to choose, Shift+Tab: previous variable)"
+                                }
+                                VariableKind::Boolean => "Value (Space to toggle)",
+                                VariableKind::Date => {
+                                    "Value (Tab: next part, Up/Down: change, x: clear)"
+                                }
+                                VariableKind::Integer => "Value (whole number)",
+                            })
+                            .style(Style::default().fg(theme.highlight));
+                        if let Some(err) = &app.popups().component_variable_error {
+                            input_block = input_block.title_bottom(
+                                Line::from(Span::styled(
+                                    format!(" {} ", err),
+                                    Style::default().fg(theme.error),
+                                ))
+                                .right_aligned(),
+                            );
+                        }
 
-                        frame
-                            .render_widget(Paragraph::new(input_val).block(input_block), layout[2]);
+                        let input_val = app.popups().component_variable_input.clone();
+                        if kind == VariableKind::Boolean {
+                            let checkbox = if input_val == "true" { "[x] " } else { "[ ] " };
+                            frame.render_widget(
+                                Paragraph::new(format!("{}{}", checkbox, input_val))
+                                    .block(input_block),
+                                layout[2],
+                            );
+                        } else if kind == VariableKind::Date {
+                            let popups = app.popups();
+                            let mut spans = Vec::new();
+                            for (i, placeholder) in ["YYYY", "MM", "DD"].into_iter().enumerate() {
+                                if i > 0 {
+                                    spans.push(Span::raw("-"));
+                                }
+                                let segment = &popups.component_date_segments[i];
+                                let mut style = if segment.is_empty() {
+                                    Style::default().fg(theme.muted)
+                                } else {
+                                    Style::default()
+                                };
+                                if popups.component_date_focus.index() == i {
+                                    style = style.add_modifier(Modifier::REVERSED);
+                                }
+                                let text = if segment.is_empty() { placeholder } else { segment };
+                                spans.push(Span::styled(text.to_string(), style));
+                            }
+                            frame.render_widget(
+                                Paragraph::new(Line::from(spans)).block(input_block),
+                                layout[2],
+                            );
+                        } else if kind != VariableKind::Selection {
+                            frame.render_widget(
+                                Paragraph::new(input_val).block(input_block),
+                                layout[2],
+                            );
+                        } else {
+                            let rows: Vec<Row> = choices
+                                .iter()
+                                .map(|choice| {
+                                    let mut cells = vec![Cell::from(choice.label.clone())];
+                                    if choice.label != choice.value {
+                                        cells.push(Cell::from(choice.value.clone()).style(
+                                            Style::default().fg(theme.muted),
+                                        ));
+                                    }
+                                    Row::new(cells)
+                                })
+                                .collect();
+                            let table = Table::new(
+                                rows,
+                                [Constraint::Percentage(60), Constraint::Percentage(40)],
+                            )
+                            .block(input_block)
+                            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                            .highlight_symbol(">> ");
+                            let mut state = app.popups().component_choice_state;
+                            frame.render_stateful_widget(table, layout[2], &mut state);
+                        }
 
                         if let Some(d) = def {
                             if let Some(desc) = &d.description {
@@ -479,19 +959,19 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
                 ])
                 .split(inner_area);
 
-            if let Some(comp) = &app.selected_component {
+            if let Some(comp) = &app.popups().selected_component {
                 frame.render_widget(
                     Paragraph::new(format!("Review Job: {}", comp.name))
                         .style(
                             Style::default()
                                 .add_modifier(Modifier::BOLD)
-                                .fg(Color::Cyan),
+                                .fg(theme.accent),
                         )
                         .alignment(Alignment::Center),
                     layout[0],
                 );
 
-                let rows: Vec<Row> = app
+                let rows: Vec<Row> = app.popups()
                     .component_variables
                     .iter()
                     .map(|v| {
@@ -518,7 +998,7 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
                     Paragraph::new("Press ENTER to Execute Job")
                         .style(
                             Style::default()
-                                .fg(Color::Green)
+                                .fg(theme.success)
                                 .add_modifier(Modifier::SLOW_BLINK),
                         )
                         .alignment(Alignment::Center),
@@ -527,19 +1007,19 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
             }
         }
         RunComponentStep::Result => {
-            if app.components_loading {
+            if app.popups().components_loading {
                 frame.render_widget(
                     Paragraph::new("Executing Job...").alignment(Alignment::Center),
                     inner_area,
                 );
-            } else if let Some(err) = &app.component_error {
+            } else if let Some(err) = &app.popups().component_error {
                 frame.render_widget(
                     Paragraph::new(format!("Error: {}", err))
-                        .style(Style::default().fg(Color::Red))
+                        .style(Style::default().fg(theme.error))
                         .wrap(Wrap { trim: true }),
                     inner_area,
                 );
-            } else if let Some(response) = &app.last_job_response {
+            } else if let Some(response) = &app.popups().last_job_response {
                 let job_info = response.job.as_ref();
                 let job_name = job_info
                     .and_then(|j| j.name.as_deref())
@@ -551,11 +1031,11 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
                     .and_then(|j| j.status.as_deref())
                     .unwrap_or("Unknown");
 
-                let text = vec![
+                let mut text = vec![
                     Line::from(Span::styled(
                         "Job Executed Successfully!",
                         Style::default()
-                            .fg(Color::Green)
+                            .fg(theme.success)
                             .add_modifier(Modifier::BOLD),
                     )),
                     Line::from(""),
@@ -567,13 +1047,57 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
                         Span::styled("Job ID: ", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw(job_id),
                     ]),
-                    Line::from(vec![
-                        Span::styled("Status: ", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(job_status),
-                    ]),
-                    Line::from(""),
-                    Line::from("Check Activity Log for status."),
                 ];
+                match &app.popups().job_poll {
+                    // Live status, refreshed until the job finishes
+                    Some(poll) => {
+                        let status = poll.status();
+                        let mut status_line = vec![
+                            Span::styled("Status: ", Style::default().add_modifier(Modifier::BOLD)),
+                            Span::styled(status, Style::default().fg(job_status_color(status))),
+                        ];
+                        if !poll.is_done() {
+                            status_line.push(Span::raw(format!(" {}", poll.spinner())));
+                        }
+                        text.push(Line::from(status_line));
+                        let components = poll
+                            .result
+                            .as_ref()
+                            .and_then(|r| r.component_results.as_ref());
+                        for comp in components.into_iter().flatten() {
+                            let status = comp.component_status.as_deref().unwrap_or("N/A");
+                            text.push(Line::from(vec![
+                                Span::raw(format!(
+                                    "{}: ",
+                                    comp.component_name.as_deref().unwrap_or("Unknown Component")
+                                )),
+                                Span::styled(status, Style::default().fg(job_status_color(status))),
+                            ]));
+                        }
+                        text.push(Line::from(""));
+                        if let Some(err) = &poll.last_error {
+                            text.push(Line::from(Span::styled(
+                                format!("Last status check failed: {}", err),
+                                Style::default().fg(theme.muted),
+                            )));
+                        }
+                        text.push(Line::from(if poll.is_done() && poll.last_error.is_some() {
+                            "Stopped checking the status. See the Activity Log."
+                        } else if poll.is_done() {
+                            "Job finished. Output is in the Activity Log."
+                        } else {
+                            "Checking status every few seconds..."
+                        }));
+                    }
+                    None => {
+                        text.push(Line::from(vec![
+                            Span::styled("Status: ", Style::default().add_modifier(Modifier::BOLD)),
+                            Span::raw(job_status),
+                        ]));
+                        text.push(Line::from(""));
+                        text.push(Line::from("Check Activity Log for status."));
+                    }
+                }
                 frame.render_widget(
                     Paragraph::new(text).alignment(Alignment::Center),
                     inner_area,
@@ -584,40 +1108,80 @@ pub fn render_run_component_popup(app: &mut App, frame: &mut Frame) {
 }
 
 pub fn render_popup(app: &App, frame: &mut Frame) {
-    if app.show_popup {
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .title(app.popup_title.as_str());
+    if app.popups().is_open(Popup::JobOutput) {
+        let follow = app.popups().job_follow.as_ref();
+        let title = match follow {
+            Some(f) if f.poll.is_done() => {
+                format!("{} (job {})", app.popups().popup_title, f.poll.status())
+            }
+            Some(f) => format!("{} (following {})", app.popups().popup_title, f.poll.spinner()),
+            None => app.popups().popup_title.clone(),
+        };
+        let mut block = Block::default().borders(Borders::ALL).title(title);
+        if app.current_view == CurrentView::ActivityDetail {
+            let hint = if follow.is_some() {
+                " F: stop following | Esc: close "
+            } else {
+                " F: follow while running | Esc: close "
+            };
+            block = block.title_bottom(Line::from(hint).right_aligned());
+        }
         let area = centered_rect(60, 60, frame.area());
 
         frame.render_widget(Clear, area); // Clear the area below the popup
 
-        if app.popup_loading {
+        if app.popups().popup_loading {
             frame.render_widget(
-                Paragraph::new("Loading...")
+                Paragraph::new(loading_text("Loading..."))
                     .block(block)
                     .alignment(Alignment::Center),
                 area,
             );
         } else {
-            let p = Paragraph::new(app.popup_content.as_str())
+            // Followed output stays scrolled to its end, like tail -f
+            let scroll = match follow {
+                Some(_) => {
+                    let inner = block.inner(area);
+                    let width = inner.width.max(1) as usize;
+                    let lines: usize = app
+                        .popups()
+                        .popup_content
+                        .lines()
+                        .map(|l| l.chars().count().div_ceil(width).max(1))
+                        .sum();
+                    lines.saturating_sub(inner.height as usize) as u16
+                }
+                None => 0,
+            };
+            let p = Paragraph::new(app.popups().popup_content.as_str())
                 .block(block)
                 .wrap(Wrap { trim: true })
-                .scroll((0, 0));
+                .scroll((scroll, 0));
             frame.render_widget(p, area);
         }
     }
 }
 
 pub fn render_device_search_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let area = centered_rect(80, 60, frame.area());
     frame.render_widget(Clear, area);
 
+    let title = match &app.popups().component_to_run {
+        Some(component) => format!(" Run {} on Device ", component.name),
+        None => " Search Devices ".to_string(),
+    };
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(" Search Devices ")
-        .title_bottom(Line::from(" Esc: close | Enter: select ").right_aligned())
-        .style(Style::default().bg(Color::DarkGray));
+        .title(title)
+        .title_bottom(
+            Line::from(
+                " Esc: close | Enter: select | PgDn: more | Ctrl+S: save search \
+                 | Ctrl+A: add all to collection ",
+            )
+            .right_aligned(),
+        )
+        .style(Style::default().bg(theme.popup_bg));
     frame.render_widget(block.clone(), area);
 
     let layout = Layout::default()
@@ -634,52 +1198,104 @@ pub fn render_device_search_popup(app: &mut App, frame: &mut Frame) {
     let input_block = Block::default()
         .borders(Borders::ALL)
         .title(" Hostname Search ")
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.accent));
 
-    let input = Paragraph::new(app.device_search_query.clone())
+    let input = Paragraph::new(app.popups().device_search_query.clone())
         .block(input_block)
         .style(
             Style::default()
-                .fg(Color::White)
+                .fg(theme.text)
                 .add_modifier(Modifier::BOLD),
         );
     frame.render_widget(input, layout[0]);
 
     // Status/Warning
-    let status_text = if app.device_search_loading {
-        Span::styled("Loading...", Style::default().fg(Color::Yellow))
-    } else if let Some(err) = &app.device_search_error {
-        Span::styled(format!("Error: {}", err), Style::default().fg(Color::Red))
-    } else if app.device_search_query.len() < 3 {
+    let searching = !app.popups().device_search_query.is_empty();
+    let status_text = if let Some(name) = &app.popups().saving_search {
         Span::styled(
-            "Type at least 3 characters...",
-            Style::default().fg(Color::Gray),
+            format!("Save search as: {}_  (Enter: save, Esc: cancel)", name),
+            Style::default().fg(theme.accent),
         )
-    } else if app.device_search_results.is_empty() && !app.device_search_query.is_empty() {
-        Span::styled("No results found.", Style::default().fg(Color::Yellow))
-    } else {
+    } else if !searching && !app.search_history.is_empty() {
+        Span::styled(
+            "Enter: run | Del: remove | Alt+1-9: run saved search | Ctrl+S: save a search",
+            Style::default().fg(theme.muted),
+        )
+    } else if app.popups().device_search_loading {
+        Span::styled(loading_text("Loading..."), Style::default().fg(theme.warning))
+    } else if let Some(err) = &app.popups().device_search_error {
+        Span::styled(format!("Error: {}", err), Style::default().fg(theme.error))
+    } else if split_search_query(&app.popups().device_search_query).0.len() < 3 {
         Span::styled(
-            format!("Found {} devices", app.device_search_results.len()),
-            Style::default().fg(Color::Green),
+            "Type at least 3 characters...",
+            Style::default().fg(theme.muted),
         )
+    } else if app.popups().device_search_results.is_empty() && !app.popups().device_search_query.is_empty() {
+        Span::styled("No results found.", Style::default().fg(theme.warning))
+    } else {
+        Span::styled(app.popups().device_search_summary(), Style::default().fg(theme.success))
     };
 
     frame.render_widget(Paragraph::new(status_text), layout[1]);
 
-    // Results
-    if !app.device_search_results.is_empty() {
-        let rows: Vec<Row> = app
-            .device_search_results
+    // Saved and recent searches, until something is typed
+    if !searching && !app.search_history.is_empty() {
+        let history = app.search_history.clone();
+        let saved = history.saved.iter().enumerate().map(|(i, search)| {
+            let key = if i < 9 { format!("★ Alt+{}", i + 1) } else { "★".to_string() };
+            Row::new(vec![key, search.name.clone(), search.query.clone()])
+        });
+        let recent = history
+            .recent
             .iter()
-            .enumerate()
-            .map(|(i, d)| {
-                let style = if Some(i) == app.device_search_table_state.selected() {
-                    Style::default().add_modifier(Modifier::REVERSED)
-                } else {
-                    Style::default()
-                };
+            .map(|query| Row::new(vec!["↻".to_string(), String::new(), query.clone()]));
+        let table = Table::new(
+            saved.chain(recent),
+            [Constraint::Length(9), Constraint::Percentage(30), Constraint::Min(0)],
+        )
+        .header(
+            Row::new(vec!["", "Name", "Query"])
+                .style(Style::default().add_modifier(Modifier::BOLD).fg(theme.accent)),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Saved & Recent Searches ")
+                .border_style(Style::default().fg(theme.text)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+        frame.render_stateful_widget(
+            table,
+            layout[2],
+            &mut app.popups_mut().device_search_history_state,
+        );
+        return;
+    }
+
+    // Results
+    if !app.popups().device_search_results.is_empty() {
+        let state = app.popups_mut();
+        let (query, _) = split_search_query(&state.last_searched_query);
+        let matched = Style::default()
+            .fg(theme.highlight)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Results ")
+            .border_style(Style::default().fg(theme.text));
+        let view = &mut state.device_search_results;
+        render_table_view(
+            frame,
+            layout[2],
+            block,
+            view,
+            &SEARCH_RESULT_TABLE,
+            &SEARCH_RESULT_COLUMNS,
+            |_, d, fit| {
                 let status = if d.online { "Online" } else { "Offline" };
-                let status_color = if d.online { Color::Green } else { Color::Gray };
+                let status_color = if d.online { theme.success } else { theme.muted };
+                let site = d.site_name.as_deref().unwrap_or("");
 
                 let os = d.operating_system.as_deref().unwrap_or("N/A");
                 let patch = d
@@ -688,103 +1304,66 @@ pub fn render_device_search_popup(app: &mut App, frame: &mut Frame) {
                     .and_then(|pm| pm.patch_status.clone())
                     .unwrap_or("Unknown".to_string());
 
-                Row::new(vec![
-                    Cell::from(d.hostname.clone()),
-                    Cell::from(d.site_name.as_deref().unwrap_or("").to_string()),
+                Row::new(fit.pick(vec![
+                    Cell::from(highlight_match(&d.hostname, &query, matched)),
+                    Cell::from(highlight_match(site, &query, matched)),
                     Cell::from(Span::styled(status, Style::default().fg(status_color))),
                     Cell::from(os),
                     Cell::from(patch),
-                ])
-                .style(style)
-            })
-            .collect();
-
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Percentage(25), // Hostname
-                Constraint::Percentage(25), // Site
-                Constraint::Percentage(10), // Status
-                Constraint::Percentage(25), // OS
-                Constraint::Percentage(15), // Patch
-            ],
-        )
-        .header(
-            Row::new(vec!["Hostname", "Site", "Status", "OS", "Patch"]).style(
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-        )
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Results ")
-                .border_style(Style::default().fg(Color::White)),
-        )
-        .highlight_symbol(">> ");
+                    last_seen_cell(d),
+                    av_cell(d),
+                ]))
+            },
+        );
+    }
+}
 
-        frame.render_stateful_widget(table, layout[2], &mut app.device_search_table_state);
+/// `text` with the first match of `query` in `style`.
+fn highlight_match(text: &str, query: &str, style: Style) -> Line<'static> {
+    match find_ignore_case(query, text) {
+        Some(range) => Line::from(vec![
+            Span::raw(text[..range.start].to_string()),
+            Span::styled(text[range.clone()].to_string(), style),
+            Span::raw(text[range.end..].to_string()),
+        ]),
+        None => Line::from(text.to_string()),
     }
 }
 
 pub fn render_device_variables_popup(
     device: &crate::api::datto::types::Device,
+    labels: &BTreeMap<usize, String>,
     frame: &mut Frame,
     state: &mut TableState,
 ) {
+    let theme = theme::current();
     let area = centered_rect(60, 60, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Variables (UDF) - Press 'Enter' to Edit | 'Esc'/'v' to close")
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.popup_bg));
 
     let mut rows = Vec::new();
 
     if let Some(udf) = &device.udf {
-        let udfs = vec![
-            ("UDF 1", &udf.udf1),
-            ("UDF 2", &udf.udf2),
-            ("UDF 3", &udf.udf3),
-            ("UDF 4", &udf.udf4),
-            ("UDF 5", &udf.udf5),
-            ("UDF 6", &udf.udf6),
-            ("UDF 7", &udf.udf7),
-            ("UDF 8", &udf.udf8),
-            ("UDF 9", &udf.udf9),
-            ("UDF 10", &udf.udf10),
-            ("UDF 11", &udf.udf11),
-            ("UDF 12", &udf.udf12),
-            ("UDF 13", &udf.udf13),
-            ("UDF 14", &udf.udf14),
-            ("UDF 15", &udf.udf15),
-            ("UDF 16", &udf.udf16),
-            ("UDF 17", &udf.udf17),
-            ("UDF 18", &udf.udf18),
-            ("UDF 19", &udf.udf19),
-            ("UDF 20", &udf.udf20),
-            ("UDF 21", &udf.udf21),
-            ("UDF 22", &udf.udf22),
-            ("UDF 23", &udf.udf23),
-            ("UDF 24", &udf.udf24),
-            ("UDF 25", &udf.udf25),
-            ("UDF 26", &udf.udf26),
-            ("UDF 27", &udf.udf27),
-            ("UDF 28", &udf.udf28),
-            ("UDF 29", &udf.udf29),
-            ("UDF 30", &udf.udf30),
+        let udfs = [
+            &udf.udf1, &udf.udf2, &udf.udf3, &udf.udf4, &udf.udf5, &udf.udf6, &udf.udf7, &udf.udf8,
+            &udf.udf9, &udf.udf10, &udf.udf11, &udf.udf12, &udf.udf13, &udf.udf14, &udf.udf15,
+            &udf.udf16, &udf.udf17, &udf.udf18, &udf.udf19, &udf.udf20, &udf.udf21, &udf.udf22,
+            &udf.udf23, &udf.udf24, &udf.udf25, &udf.udf26, &udf.udf27, &udf.udf28, &udf.udf29,
+            &udf.udf30,
         ];
 
-        for (label, val_opt) in udfs {
+        for (i, val_opt) in udfs.into_iter().enumerate() {
             let val = val_opt.as_deref().unwrap_or("");
-            rows.push(Row::new(vec![Cell::from(label), Cell::from(val)]));
+            rows.push(Row::new(vec![Cell::from(udf_label(labels, i + 1)), Cell::from(val)]));
         }
     } else {
         for i in 1..=30 {
             rows.push(Row::new(vec![
-                Cell::from(format!("UDF {}", i)),
+                Cell::from(udf_label(labels, i)),
                 Cell::from(""),
             ]));
         }
@@ -800,21 +1379,26 @@ pub fn render_device_variables_popup(
     .row_highlight_style(
         Style::default()
             .add_modifier(Modifier::BOLD)
-            .fg(Color::Yellow),
+            .fg(theme.highlight),
     );
 
     frame.render_stateful_widget(table, area, state);
 }
 
 pub fn render_site_move_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let area = centered_rect(60, 60, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(" Move Device to Site ")
-        .title_bottom(Line::from(" Esc: cancel | Enter: move ").right_aligned())
-        .style(Style::default().bg(Color::DarkGray));
+        .title(if app.popups().variable_copy.is_some() {
+            " Copy Variables to Site "
+        } else {
+            " Move Device to Site "
+        })
+        .title_bottom(Line::from(" Esc: cancel | Enter: choose ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
     frame.render_widget(block.clone(), area);
 
     let layout = Layout::default()
@@ -830,20 +1414,20 @@ pub fn render_site_move_popup(app: &mut App, frame: &mut Frame) {
     let input_block = Block::default()
         .borders(Borders::ALL)
         .title(" Filter Sites ")
-        .border_style(Style::default().fg(Color::Cyan));
-    let input = Paragraph::new(app.site_move_query.clone()).block(input_block);
+        .border_style(Style::default().fg(theme.accent));
+    let input = Paragraph::new(app.popups().site_move_query.clone()).block(input_block);
     frame.render_widget(input, layout[0]);
 
     // Results
-    let rows: Vec<Row> = app
+    let rows: Vec<Row> = app.popups()
         .filtered_sites
         .iter()
         .enumerate()
         .map(|(i, s)| {
-            let style = if Some(i) == app.site_move_table_state.selected() {
+            let style = if Some(i) == app.popups().site_move_table_state.selected() {
                 Style::default()
                     .add_modifier(Modifier::REVERSED)
-                    .fg(Color::Yellow)
+                    .fg(theme.highlight)
             } else {
                 Style::default()
             };
@@ -853,5 +1437,836 @@ pub fn render_site_move_popup(app: &mut App, frame: &mut Frame) {
 
     let table = Table::new(rows, [Constraint::Percentage(100)]).highlight_symbol(">> ");
 
-    frame.render_stateful_widget(table, layout[1], &mut app.site_move_table_state);
+    frame.render_stateful_widget(table, layout[1], &mut app.popups_mut().site_move_table_state);
+}
+
+pub fn render_profile_switcher_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(40, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = if app.popups().profile_switching {
+        " Switch Profile (connecting...) "
+    } else {
+        " Switch Profile "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(Line::from(" Esc: cancel | Enter: switch ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    if app.profiles.is_empty() {
+        let text = Paragraph::new("No profiles found in config.toml")
+            .style(Style::default().fg(theme.warning))
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .profiles
+        .iter()
+        .map(|name| {
+            let marker = if *name == app.active_profile { "* " } else { "  " };
+            Row::new(vec![Cell::from(format!("{}{}", marker, name))])
+        })
+        .collect();
+
+    let table = Table::new(rows, [Constraint::Percentage(100)])
+        .block(block)
+        .row_highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(theme.highlight),
+        )
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.popups_mut().profile_list_state);
+}
+
+pub fn render_recent_devices_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Recent Devices ")
+        .title_bottom(Line::from(" Esc: close | Enter: open ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    if app.recent_devices.is_empty() {
+        let text = Paragraph::new("No devices viewed yet")
+            .style(Style::default().fg(theme.muted))
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .recent_devices
+        .iter()
+        .map(|device| {
+            let site = app
+                .site_list()
+                .sites
+                .iter()
+                .find(|s| s.uid == device.site_uid)
+                .map(|s| s.name.clone())
+                .or_else(|| device.site_name.clone())
+                .unwrap_or_default();
+            let (status, color) = if device.online {
+                ("Online", theme.success)
+            } else {
+                ("Offline", theme.error)
+            };
+            Row::new(vec![
+                Cell::from(device.hostname.clone()),
+                Cell::from(site),
+                Cell::from(Span::styled(status, Style::default().fg(color))),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(40),
+            Constraint::Percentage(45),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(
+        Row::new(vec!["Hostname", "Site", "Status"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(theme.highlight),
+    )
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.popups_mut().recent_devices_state);
+}
+
+/// The devices being added, a name input and the existing collections to
+/// pick from.
+pub fn render_add_to_collection_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let popups = app.popups();
+    let count = popups.collection_devices.len();
+    let title = match popups.collection_devices.as_slice() {
+        [device] => format!(" Add {} to Collection ", device.hostname),
+        _ => format!(" Add {} Devices to Collection ", count),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(Line::from(" Enter: add | ↑/↓: pick | Esc: cancel ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+    frame.render_widget(block.clone(), area);
+
+    let [input_area, list_area] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(block.inner(area));
+    let input = Paragraph::new(format!("{}_", popups.collection_name)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Name (a new name creates the collection)")
+            .border_style(Style::default().fg(theme.accent)),
+    );
+    frame.render_widget(input, input_area);
+
+    let rows: Vec<Row> = app
+        .collections()
+        .collections
+        .iter()
+        .map(|collection| {
+            Row::new(vec![
+                Cell::from(collection.name.clone()),
+                Cell::from(format!("{} devices", collection.devices.len())),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Min(10), Constraint::Length(14)])
+        .block(Block::default().borders(Borders::TOP).title("Collections"))
+        .row_highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(theme.highlight),
+        )
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, list_area, &mut app.popups_mut().collection_list_state);
+}
+
+/// The antivirus scans started this session, newest first.
+pub fn render_scans_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(80, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Scans ")
+        .title_bottom(Line::from(" Esc: close ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    let jobs = &app.security().scans.jobs;
+    if jobs.is_empty() {
+        let text = Paragraph::new("No scans started this session")
+            .style(Style::default().fg(theme.muted))
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let rows: Vec<Row> = jobs
+        .iter()
+        .rev()
+        .map(|job| {
+            let color = match job.state {
+                ScanState::Completed => theme.success,
+                ScanState::Failed => theme.error,
+                ScanState::Starting | ScanState::Running => theme.warning,
+                ScanState::Requested => theme.accent,
+            };
+            Row::new(vec![
+                Cell::from(job.hostname.clone()),
+                Cell::from(job.provider),
+                Cell::from(format_time(Some(job.started_at))),
+                Cell::from(Span::styled(job.state.label(), Style::default().fg(color))),
+                Cell::from(job.result.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(20),
+            Constraint::Percentage(18),
+            Constraint::Percentage(20),
+            Constraint::Percentage(12),
+            Constraint::Percentage(30),
+        ],
+    )
+    .header(
+        Row::new(vec!["Hostname", "Console", "Started", "State", "Result"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(theme.highlight),
+    )
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.popups_mut().scans_state);
+}
+
+/// Alerts and incidents new since the last session, by site and severity.
+pub fn render_notifications_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(80, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let notifications = app.notifications();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Notification Center ({} new) ", notifications.items.len()))
+        .title_bottom(Line::from(" j/k: scroll | Esc: close ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    if notifications.items.is_empty() {
+        frame.render_widget(
+            Paragraph::new("Nothing new since the last session.")
+                .style(Style::default().fg(theme.muted))
+                .block(block),
+            area,
+        );
+        return;
+    }
+
+    let mut lines = Vec::new();
+    for (site, groups) in notifications.grouped() {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            site.to_string(),
+            Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+        )));
+        for (severity, items) in groups {
+            let color = match severity.to_lowercase().as_str() {
+                "critical" => theme.error,
+                "high" => theme.caution,
+                "moderate" | "medium" => theme.warning,
+                "low" => theme.accent,
+                _ if severity == INCIDENT_SEVERITY => theme.error,
+                _ => theme.text,
+            };
+            lines.push(Line::from(Span::styled(
+                format!("  {} ({})", severity, items.len()),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )));
+            for item in items {
+                let age = item.at.map(format_age).unwrap_or_default();
+                lines.push(Line::from(vec![
+                    Span::raw(format!("    {}", item.title)),
+                    Span::styled(format!("  {}", age), Style::default().fg(theme.muted)),
+                ]));
+            }
+        }
+    }
+
+    let scroll = notifications.scroll;
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(block),
+        area,
+    );
+}
+
+/// The selected site's notes, wrapped and scrollable, with light markdown.
+/// The alert's source, monitor and response details, then its diagnostics
+/// split into aligned key/value lines.
+pub fn render_alert_detail_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(75, 75, frame.area());
+    frame.render_widget(Clear, area);
+
+    let Some(detail) = app.popups().alert_detail.clone() else {
+        return;
+    };
+    let alert = &detail.alert;
+    let label =
+        |text: &str| Span::styled(format!("{:<16}", text), Style::default().fg(theme.accent));
+    let mut lines: Vec<Line> = Vec::new();
+    let mut field = |name: &str, value: Span<'static>| {
+        lines.push(Line::from(vec![label(name), value]));
+    };
+
+    let priority = alert.priority.clone().unwrap_or_else(|| "Unknown".to_string());
+    let priority_color = match priority.to_lowercase().as_str() {
+        "critical" => theme.error,
+        "high" => theme.caution,
+        "medium" => theme.warning,
+        "low" => theme.info,
+        _ => theme.text,
+    };
+    field("Priority", Span::styled(priority, Style::default().fg(priority_color)));
+    field("Raised", Span::raw(format_time(alert.raised_at())));
+    if let Some(source) = &alert.alert_source_info {
+        let text = |value: &Option<String>| value.clone().unwrap_or_else(|| "N/A".to_string());
+        field("Device", Span::raw(text(&source.device_name)));
+        field("Site", Span::raw(text(&source.site_name)));
+    }
+    if let Some(monitor) = detail.monitor() {
+        field("Monitor", Span::raw(monitor));
+    }
+    if let Some(context) = &alert.alert_context {
+        let text_fields = [
+            ("Package", &context.package_name),
+            ("Action", &context.action_type),
+            ("Version", &context.version),
+            ("Previous", &context.prev_version),
+        ];
+        for (name, value) in text_fields {
+            if let Some(value) = value {
+                field(name, Span::raw(value.clone()));
+            }
+        }
+        for (key, value) in &context.details {
+            let value = match value {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            field(key, Span::raw(value));
+        }
+    }
+    if let Some(info) = &alert.alert_monitor_info {
+        let flag = |value: Option<bool>| if value.unwrap_or(false) { "Yes" } else { "No" };
+        field("Sends Emails", Span::raw(flag(info.sends_emails)));
+        field("Creates Ticket", Span::raw(flag(info.creates_ticket)));
+    }
+    if let Some(ticket) = &alert.ticket_number {
+        field("Ticket", Span::raw(ticket.clone()));
+    }
+    if alert.muted == Some(true) {
+        field("Muted", Span::styled("Yes", Style::default().fg(theme.muted)));
+    }
+    if let Some(minutes) = alert.autoresolve_mins {
+        field("Auto-resolves", Span::raw(format!("after {} min", minutes)));
+    }
+    for action in alert.response_actions.iter().flatten() {
+        let text = format!(
+            "{} {} ({})",
+            action.action_type.as_deref().unwrap_or("Action"),
+            action.description.as_deref().unwrap_or_default(),
+            format_timestamp(action.action_time.clone()),
+        );
+        field("Response", Span::raw(text));
+    }
+    if let Some(ack) = app.alert_acknowledgement(alert) {
+        let text = format!("✓ {} ({})", ack.note, format_age(ack.acked_at));
+        field("Acknowledged", Span::styled(text, Style::default().fg(theme.success)));
+    }
+    let mut hint = String::from(" j/k: scroll | r: resolve | a: acknowledge");
+    if let Some(runbook) = &detail.runbook {
+        let how = match (&runbook.component_uid, &runbook.url) {
+            (Some(_), Some(_)) => "x: run component, o: open runbook",
+            (Some(_), None) => "x: run component",
+            (None, _) => "o: open runbook",
+        };
+        lines.push(Line::default());
+        lines.push(Line::from(vec![
+            Span::styled(
+                "Recommended fix: ",
+                Style::default().fg(theme.success).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("{} ({})", runbook.name, how)),
+        ]));
+        if runbook.component_uid.is_some() {
+            hint.push_str(" | x: run fix");
+        }
+        if runbook.url.is_some() {
+            hint.push_str(" | o: runbook");
+        }
+    }
+    hint.push_str(" | Esc: close ");
+
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled(
+        "Diagnostics",
+        Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+    )));
+    let diagnostics = diagnostic_lines(alert.diagnostics.as_deref().unwrap_or_default());
+    if diagnostics.is_empty() {
+        lines.push(Line::from(Span::styled("None", Style::default().fg(theme.muted))));
+    }
+    let width = diagnostics.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+    for (key, value) in diagnostics {
+        if key.is_empty() {
+            lines.push(Line::from(value));
+        } else {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<width$}  ", key), Style::default().fg(theme.highlight)),
+                Span::raw(value),
+            ]));
+        }
+    }
+
+    // Keep at least the last line on screen
+    let scroll = detail.scroll.min(lines.len().saturating_sub(1) as u16);
+    if let Some(detail) = app.popups_mut().alert_detail.as_mut() {
+        detail.scroll = scroll;
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Alert Detail ")
+        .title_bottom(Line::from(hint).right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(block),
+        area,
+    );
+}
+
+pub fn render_notes_viewer_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(70, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let Some(site) = app.site_list().selected_site() else {
+        return;
+    };
+    let title = format!(" Notes: {} ", site.name);
+    let lines = markdown_lines(site.notes.as_deref().unwrap_or_default());
+    // Keep at least the last line on screen
+    let scroll = app.popups().notes_scroll.min(lines.len().saturating_sub(1) as u16);
+    app.popups_mut().notes_scroll = scroll;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(Line::from(" j/k, PgUp/PgDn: scroll | g: top | Esc: close ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(block),
+        area,
+    );
+}
+
+/// Template list on top, and below it what the highlighted template would
+/// change on the site.
+pub fn render_variable_templates_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let hint = if app.popups().template_applying {
+        " Applying... "
+    } else {
+        " Esc: close | Enter: apply "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Apply Variable Template ")
+        .title_bottom(Line::from(hint).right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    if app.variable_templates.is_empty() {
+        let text = Paragraph::new("No templates configured; add [[variable_templates]] to config.toml")
+            .style(Style::default().fg(theme.muted))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let list_height = app.variable_templates.len().min(8) as u16 + 2;
+    let [list_area, preview_area] =
+        Layout::vertical([Constraint::Length(list_height), Constraint::Min(0)]).areas(inner);
+
+    let rows: Vec<Row> = app
+        .variable_templates
+        .iter()
+        .map(|t| Row::new(vec![t.name.clone()]))
+        .collect();
+    let list = Table::new(rows, [Constraint::Percentage(100)])
+        .block(Block::default().borders(Borders::ALL).title("Templates"))
+        .row_highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(theme.highlight),
+        )
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(list, list_area, &mut app.popups_mut().variable_template_state);
+
+    let changes = app.selected_template_changes();
+    let pending = changes.iter().filter(|c| c.is_change()).count();
+    let rows: Vec<Row> = changes
+        .iter()
+        .map(|change| {
+            let (label, value, color) = match change {
+                VariableChange::Create { value, .. } => ("create", value.clone(), theme.success),
+                VariableChange::Update { old, new, .. } => {
+                    ("update", format!("{} → {}", old, new), theme.caution)
+                }
+                VariableChange::Unchanged { value, .. } => ("same", value.clone(), theme.muted),
+            };
+            Row::new(vec![
+                Cell::from(Span::styled(label, Style::default().fg(color))),
+                Cell::from(change.name().to_string()),
+                Cell::from(value),
+            ])
+            .style(if change.is_change() {
+                Style::default()
+            } else {
+                Style::default().fg(theme.muted)
+            })
+        })
+        .collect();
+    let preview = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Percentage(35),
+            Constraint::Percentage(65),
+        ],
+    )
+    .header(
+        Row::new(vec!["Change", "Variable", "Value"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Preview ({} to write)", pending)),
+    );
+    frame.render_widget(preview, preview_area);
+}
+
+/// Asks about each conflicting variable in turn, then summarises the copy.
+pub fn render_variable_copy_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let Some(copy) = &app.popups().variable_copy else {
+        return;
+    };
+    let area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let target = copy.target.as_ref().map_or("", |(_, name)| name.as_str());
+    let conflict = copy.current_conflict();
+    let hint = if copy.applying {
+        " Copying... "
+    } else if conflict.is_some() {
+        " o: overwrite | s: skip | O/S: all remaining | Esc: cancel "
+    } else {
+        " Esc: cancel | Enter: copy "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Copy Variables: {} → {} ", copy.source_name, target))
+        .title_bottom(Line::from(hint).right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    let Some(changes) = &copy.changes else {
+        let text = Paragraph::new(loading_text(&format!("Loading variables of {}...", target)))
+            .style(Style::default().fg(theme.muted))
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    };
+
+    let mut lines = Vec::new();
+    if let Some(VariableChange::Update { name, old, new, .. }) = conflict {
+        lines.push(Line::from(vec![
+            Span::styled(name.clone(), Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!(" already exists on {}", target)),
+        ]));
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("  Current: ", Style::default().fg(theme.muted)),
+            Span::raw(old.clone()),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("  Copied:  ", Style::default().fg(theme.muted)),
+            Span::raw(new.clone()),
+        ]));
+    } else {
+        let to_write = copy.to_write();
+        let skipped = changes.iter().filter(|c| c.is_change()).count() - to_write.len();
+        let unchanged = changes.len() - changes.iter().filter(|c| c.is_change()).count();
+        lines.push(Line::from(format!("{} variables will be written to {}:", to_write.len(), target)));
+        for change in &to_write {
+            let (label, color) = match change {
+                VariableChange::Create { .. } => ("create", theme.success),
+                _ => ("overwrite", theme.caution),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<10}", label), Style::default().fg(color)),
+                Span::raw(change.name().to_string()),
+            ]));
+        }
+        lines.push(Line::from(""));
+        for (count, what) in [
+            (skipped, "skipped conflicts"),
+            (unchanged, "already identical"),
+            (copy.masked_skipped, "masked, not copied"),
+        ] {
+            if count > 0 {
+                lines.push(Line::styled(
+                    format!("{} {}", count, what),
+                    Style::default().fg(theme.muted),
+                ));
+            }
+        }
+    }
+
+    let text = Paragraph::new(lines).wrap(Wrap { trim: false }).block(block);
+    frame.render_widget(text, area);
+}
+
+pub fn render_session_restore_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let Some(session) = &app.popups().pending_session else {
+        return;
+    };
+    let area = centered_rect(50, 20, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Restore Session ")
+        .title_bottom(Line::from(" Enter/y: restore | Esc/n: start fresh ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    let mut lines = vec![
+        Line::from("Pick up where the last session left off?"),
+        Line::from(""),
+        Line::from(Span::styled(
+            session.summary.clone(),
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD),
+        )),
+    ];
+    if !session.site_filter.is_empty() || session.hide_empty {
+        let mut filters = Vec::new();
+        if !session.site_filter.is_empty() {
+            filters.push(format!("filter '{}'", session.site_filter));
+        }
+        if session.hide_empty {
+            filters.push("hiding empty sites".to_string());
+        }
+        lines.push(Line::from(Span::styled(
+            format!("Site list: {}", filters.join(", ")),
+            Style::default().fg(theme.muted),
+        )));
+    }
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }).block(block), area);
+}
+
+pub fn render_confirmation_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let Some(confirmation) = &app.popups().confirmation else {
+        return;
+    };
+    // A diff needs room for multi-line values such as notes
+    let area = if confirmation.changes.is_empty() {
+        centered_rect(50, 20, frame.area())
+    } else {
+        centered_rect(70, 60, frame.area())
+    };
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(
+            format!(" {} ", confirmation.title),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Line::from(" Enter/y: confirm | Esc/n: cancel ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    let mut lines = vec![Line::from(confirmation.message.clone())];
+    for change in &confirmation.changes {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            change.field,
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        let side = |sign: &str, value: &str, color| {
+            let style = Style::default().fg(color);
+            if value.is_empty() {
+                return vec![Line::from(Span::styled(format!("{} (empty)", sign), style))];
+            }
+            value
+                .lines()
+                .map(|line| Line::from(Span::styled(format!("{} {}", sign, line), style)))
+                .collect::<Vec<_>>()
+        };
+        lines.extend(side("-", &change.old, theme.error));
+        lines.extend(side("+", &change.new, theme.success));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).block(block),
+        area,
+    );
+}
+
+pub fn render_log_viewer_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(90, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Logs ")
+        .title_bottom(Line::from(" j/k: scroll | g/G: top/bottom | Esc: close ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let entries = crate::logging::recent_lines();
+    let height = inner.height as usize;
+    let max_scroll = entries.len().saturating_sub(height);
+    app.popups_mut().log_viewer_scroll = app.popups().log_viewer_scroll.min(max_scroll);
+    let end = entries.len() - app.popups().log_viewer_scroll;
+    let start = end.saturating_sub(height);
+
+    let lines: Vec<Line> = entries[start..end]
+        .iter()
+        .map(|entry| {
+            let color = if entry.contains(" ERROR ") {
+                theme.error
+            } else if entry.contains(" WARN ") {
+                theme.warning
+            } else if entry.contains(" DEBUG ") || entry.contains(" TRACE ") {
+                theme.muted
+            } else {
+                theme.text
+            };
+            Line::from(Span::styled(entry.clone(), Style::default().fg(color)))
+        })
+        .collect();
+
+    let text = if lines.is_empty() {
+        Paragraph::new("No log entries yet.").style(Style::default().fg(theme.muted))
+    } else {
+        Paragraph::new(lines)
+    };
+    frame.render_widget(text, inner);
+}
+
+pub fn render_error_history_popup(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(80, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Error History ({}) ", app.errors.len()))
+        .title(Line::from(format!(" {} tasks running ", app.tasks().running())).right_aligned())
+        .title_bottom(Line::from(" j/k: move | c: clear | Esc: close ").right_aligned())
+        .style(Style::default().bg(theme.popup_bg));
+
+    if app.errors.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No errors reported.")
+                .style(Style::default().fg(theme.success))
+                .block(block),
+            area,
+        );
+        return;
+    }
+
+    // Newest first
+    let rows: Vec<Row> = app
+        .errors
+        .iter()
+        .rev()
+        .map(|e| {
+            Row::new(vec![
+                Cell::from(e.timestamp.format("%H:%M:%S").to_string()),
+                Cell::from(e.source),
+                Cell::from(e.message.clone()).style(Style::default().fg(theme.error)),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Min(20),
+        ],
+    )
+    .header(
+        Row::new(vec!["Time", "Source", "Message"])
+            .style(Style::default().add_modifier(Modifier::BOLD).fg(theme.accent)),
+    )
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.popups_mut().error_history_state);
 }
@@ -0,0 +1,483 @@
+use crate::app::{
+    AV_GAP_COLUMNS, AV_PRODUCT_COLUMNS, App, OFFLINE_SERVER_COLUMNS, PATCH_COLUMNS,
+    PATCH_DEVICE_COLUMNS, ReportKind, WARRANTY_COLUMNS,
+};
+use crate::pages::site_detail::{av_status_color, patch_status_color, progress_label};
+use crate::ui::theme;
+use ratatui::{
+    prelude::*,
+    widgets::{Bar, BarChart, Block, Borders, Cell, Gauge, Paragraph, Row, Table, Tabs},
+};
+
+pub fn render_report(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let report = app.report();
+    let tabs = Tabs::new(ReportKind::ALL.iter().map(|k| k.title()).collect::<Vec<_>>())
+        .select(ReportKind::ALL.iter().position(|k| *k == report.kind).unwrap_or(0))
+        .block(Block::default().borders(Borders::ALL).title("Reports"))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(theme.accent),
+        );
+    frame.render_widget(tabs, chunks[0]);
+
+    match report.kind {
+        ReportKind::Warranty => render_warranty_report(app, frame, chunks[1]),
+        ReportKind::Patch => render_patch_report(app, frame, chunks[1]),
+        ReportKind::Antivirus => render_av_report(app, frame, chunks[1]),
+        ReportKind::OfflineServers => render_offline_servers(app, frame, chunks[1]),
+        ReportKind::Breakdown => render_breakdown(app, frame, chunks[1]),
+    }
+}
+
+/// Renders the device fetch progress inside `block`. Returns false once
+/// devices are loaded, so the caller draws its table instead.
+fn render_loading(app: &App, frame: &mut Frame, area: Rect, block: Block) -> bool {
+    let report = app.report();
+    if !(report.loading && report.devices.is_empty()) {
+        return false;
+    }
+    let theme = theme::current();
+    let (loaded, total) = report.progress.unwrap_or((0, None));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let ratio = total
+        .filter(|&t| t > 0)
+        .map(|t| (loaded as f64 / t as f64).min(1.0))
+        .unwrap_or(0.0);
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(theme.accent))
+            .ratio(ratio)
+            .label(progress_label(loaded, total)),
+        Rect { height: inner.height.min(1), ..inner },
+    );
+    true
+}
+
+/// Report block with the refresh marker and last export result.
+fn report_block(app: &App, mut title: String) -> Block<'static> {
+    let theme = theme::current();
+    let report = app.report();
+    if report.loading && !report.devices.is_empty() {
+        title.push_str(" (refreshing...)");
+    }
+    let mut block = Block::default().borders(Borders::ALL).title(title);
+    if let Some(message) = &report.export_message {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", message),
+            Style::default().fg(theme.success),
+        )));
+    }
+    block
+}
+
+fn render_warranty_report(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let report = app.report();
+    let block = report_block(
+        app,
+        format!(
+            "Warranty (expired or within {} days) {} devices",
+            report.warranty_days,
+            report.warranty_rows.len()
+        ),
+    );
+    if render_loading(app, frame, area, block.clone()) {
+        return;
+    }
+
+    if report.warranty_rows.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No devices with an expired or expiring warranty.")
+                .style(Style::default().fg(theme.muted))
+                .block(block),
+            area,
+        );
+        return;
+    }
+
+    // Rows are grouped by site; only the first row of each group names it
+    let rows: Vec<Row> = report
+        .warranty_rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let new_group = i == 0 || report.warranty_rows[i - 1].site != row.site;
+            let site = if new_group { row.site.clone() } else { String::new() };
+            Row::new(vec![
+                Cell::from(Span::styled(site, Style::default().add_modifier(Modifier::BOLD))),
+                Cell::from(row.hostname.clone()),
+                Cell::from(row.warranty_date.clone()),
+                Cell::from(row.days_left.to_string()),
+                Cell::from(Span::styled(
+                    row.status.label(),
+                    Style::default().fg(row.status.color()),
+                )),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(18),
+            Constraint::Percentage(12),
+            Constraint::Percentage(20),
+        ],
+    )
+    .header(Row::new(WARRANTY_COLUMNS.to_vec()).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.report_mut().table_state);
+}
+
+fn render_patch_report(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let report = app.report();
+
+    if report.patch_site.is_some() {
+        let site = report.patch_devices.first().map(|r| r.site.as_str()).unwrap_or("Site");
+        let block = report_block(
+            app,
+            format!("Patch Compliance > {} ({} not fully patched)", site, report.patch_devices.len()),
+        );
+        let rows: Vec<Row> = report
+            .patch_devices
+            .iter()
+            .map(|row| {
+                Row::new(vec![
+                    Cell::from(row.site.clone()),
+                    Cell::from(row.hostname.clone()),
+                    Cell::from(row.operating_system.clone()),
+                    Cell::from(Span::styled(
+                        row.status.clone(),
+                        Style::default().fg(patch_status_color(&row.status)),
+                    )),
+                ])
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(30),
+                Constraint::Percentage(20),
+            ],
+        )
+        .header(Row::new(PATCH_DEVICE_COLUMNS.to_vec()).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+        frame.render_stateful_widget(table, area, &mut app.report_mut().table_state);
+        return;
+    }
+
+    let block = report_block(app, format!("Patch Compliance ({} sites)", report.patch_rows.len()));
+    if render_loading(app, frame, area, block.clone()) {
+        return;
+    }
+
+    // Non-zero problem counts are colored like the device list's patch column
+    let count_cell = |count: usize, status: &str| {
+        let style = if count == 0 || status == "FullyPatched" {
+            Style::default()
+        } else {
+            Style::default().fg(patch_status_color(status))
+        };
+        Cell::from(Span::styled(count.to_string(), style))
+    };
+    let rows: Vec<Row> = report
+        .patch_rows
+        .iter()
+        .map(|row| {
+            let mut cells = vec![Cell::from(row.site.clone()), Cell::from(row.total.to_string())];
+            cells.extend(
+                row.counts
+                    .iter()
+                    .zip(&PATCH_COLUMNS[2..6])
+                    .map(|(&count, &status)| count_cell(count, status)),
+            );
+            cells.push(Cell::from(Span::styled(
+                row.other.to_string(),
+                Style::default().fg(theme.muted),
+            )));
+            Row::new(cells)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(28),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+        ],
+    )
+    .header(Row::new(PATCH_COLUMNS.to_vec()).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.report_mut().table_state);
+}
+
+fn render_av_report(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let report = app.report();
+    let block = report_block(app, format!("AV Coverage ({} devices)", report.devices.len()));
+    if render_loading(app, frame, area, block.clone()) {
+        return;
+    }
+
+    // Cross-tab on top, sized to its rows; devices without working AV below
+    let matrix_height = (report.av_products.len() as u16 + 3).min(area.height / 2);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(matrix_height), Constraint::Min(0)])
+        .split(area);
+
+    let matrix_rows: Vec<Row> = report
+        .av_products
+        .iter()
+        .map(|row| {
+            let mut cells = vec![Cell::from(row.product.clone()), Cell::from(row.total.to_string())];
+            cells.extend(row.counts.iter().zip(&AV_PRODUCT_COLUMNS[2..6]).map(|(&count, &status)| {
+                let style = if count == 0 {
+                    Style::default().fg(theme.muted)
+                } else {
+                    Style::default().fg(av_status_color(status))
+                };
+                Cell::from(Span::styled(count.to_string(), style))
+            }));
+            cells.push(Cell::from(row.other.to_string()));
+            Row::new(cells)
+        })
+        .collect();
+    let matrix = Table::new(
+        matrix_rows,
+        [
+            Constraint::Percentage(22),
+            Constraint::Percentage(10),
+            Constraint::Percentage(16),
+            Constraint::Percentage(16),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+        ],
+    )
+    .header(Row::new(AV_PRODUCT_COLUMNS.to_vec()).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(block);
+    frame.render_widget(matrix, chunks[0]);
+
+    let gaps_block = Block::default().borders(Borders::ALL).title(format!(
+        "Not Detected / Not Running ({} devices)",
+        report.av_gaps.len()
+    ));
+    if report.av_gaps.is_empty() {
+        frame.render_widget(
+            Paragraph::new("Every device has antivirus running.")
+                .style(Style::default().fg(theme.success))
+                .block(gaps_block),
+            chunks[1],
+        );
+        return;
+    }
+
+    let rows: Vec<Row> = report
+        .av_gaps
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let new_group = i == 0 || report.av_gaps[i - 1].site != row.site;
+            let site = if new_group { row.site.clone() } else { String::new() };
+            Row::new(vec![
+                Cell::from(Span::styled(site, Style::default().add_modifier(Modifier::BOLD))),
+                Cell::from(row.hostname.clone()),
+                Cell::from(row.product.clone()),
+                Cell::from(Span::styled(
+                    row.status.clone(),
+                    Style::default().fg(av_status_color(&row.status)),
+                )),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+        ],
+    )
+    .header(Row::new(AV_GAP_COLUMNS.to_vec()).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(gaps_block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, chunks[1], &mut app.report_mut().table_state);
+}
+
+fn render_offline_servers(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let report = app.report();
+    let mut title = format!("Offline Servers ({})", report.offline_servers.len());
+    if let Some(interval) = report.refresh_interval {
+        title.push_str(&format!(" [auto-refresh {}m]", interval.as_secs() / 60));
+    }
+    let block = report_block(app, title);
+    if render_loading(app, frame, area, block.clone()) {
+        return;
+    }
+
+    if report.offline_servers.is_empty() {
+        frame.render_widget(
+            Paragraph::new("All servers are online.")
+                .style(Style::default().fg(theme.success))
+                .block(block),
+            area,
+        );
+        return;
+    }
+
+    let rows: Vec<Row> = report
+        .offline_servers
+        .iter()
+        .map(|row| {
+            let cells = row.cells();
+            // A day or more offline is worth escalating
+            let color = match row.offline_secs {
+                Some(secs) if secs >= 86_400 => theme.error,
+                Some(_) => theme.warning,
+                None => theme.muted,
+            };
+            Row::new(vec![
+                Cell::from(cells[0].clone()),
+                Cell::from(cells[1].clone()),
+                Cell::from(cells[2].clone()),
+                Cell::from(cells[3].clone()),
+                Cell::from(Span::styled(cells[4].clone(), Style::default().fg(color))),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+            Constraint::Percentage(30),
+            Constraint::Percentage(18),
+            Constraint::Percentage(12),
+        ],
+    )
+    .header(Row::new(OFFLINE_SERVER_COLUMNS.to_vec()).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.report_mut().table_state);
+}
+
+/// Horizontal bars of `counts`, labelled with the value and its count.
+fn count_bars(counts: &[(String, usize)], color: Color) -> Vec<Bar<'static>> {
+    counts
+        .iter()
+        .map(|(label, count)| {
+            Bar::with_label(label.clone(), *count as u64)
+                .text_value(count.to_string())
+                .style(Style::default().fg(color))
+        })
+        .collect()
+}
+
+fn render_breakdown(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let report = app.report();
+    let sites = report.breakdown_scopes.len().saturating_sub(1);
+    let block = report_block(app, format!("Device Breakdown ({} sites)", sites));
+    if render_loading(app, frame, area, block.clone()) {
+        return;
+    }
+
+    // Scopes on the left; the selected one is charted on the right
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(area);
+    let rows: Vec<Row> = report
+        .breakdown_scopes
+        .iter()
+        .map(|scope| {
+            let style = if scope.site_uid.is_none() {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![Cell::from(scope.name.clone()), Cell::from(scope.devices.to_string())])
+                .style(style)
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Min(10), Constraint::Length(8)])
+        .header(Row::new(vec!["Scope", "Devices"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+
+    let breakdown = &report.breakdown;
+    let scope = report.selected_breakdown_scope().map(|s| s.name.clone()).unwrap_or_default();
+    let charts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(breakdown.categories.len().max(1) as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .split(columns[1]);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(24)])
+        .split(charts[0]);
+
+    frame.render_widget(
+        BarChart::horizontal(count_bars(&breakdown.categories, theme.accent)).bar_gap(0).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{}: Category ({} devices)", scope, breakdown.total)),
+        ),
+        top[0],
+    );
+    let status = vec![
+        Bar::with_label("Online", breakdown.online as u64).style(Style::default().fg(theme.success)),
+        Bar::with_label("Offline", breakdown.offline as u64).style(Style::default().fg(theme.error)),
+    ];
+    frame.render_widget(
+        BarChart::vertical(status)
+            .bar_width(9)
+            .bar_gap(2)
+            .block(Block::default().borders(Borders::ALL).title("Status")),
+        top[1],
+    );
+    frame.render_widget(
+        BarChart::horizontal(count_bars(&breakdown.operating_systems, theme.info))
+            .bar_gap(0)
+            .block(Block::default().borders(Borders::ALL).title("Operating System")),
+        charts[1],
+    );
+
+    frame.render_stateful_widget(table, columns[0], &mut app.report_mut().table_state);
+}
@@ -0,0 +1,139 @@
+use crate::common::utils::centered_rect;
+use crate::wizard::{SetupWizard, ValidationStatus};
+use crate::ui::theme;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+pub fn render_setup_wizard(wizard: &SetupWizard, frame: &mut Frame) {
+    let theme = theme::current();
+    let area = centered_rect(70, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let step_title = match wizard.forms.get(wizard.step) {
+        Some(form) => form.provider.title(),
+        None => "Save Profile",
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " Kyber TUI Setup ({}/{}): {} ",
+            wizard.step + 1,
+            wizard.forms.len() + 1,
+            step_title
+        ))
+        .title_bottom(
+            Line::from(" Tab: next field | Enter: test & continue | Esc: back | Ctrl+C: quit ")
+                .right_aligned(),
+        );
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let field_count = wizard
+        .forms
+        .get(wizard.step)
+        .map(|f| f.values.len())
+        .unwrap_or(1);
+
+    let mut constraints = vec![Constraint::Length(2)]; // Intro
+    constraints.extend(std::iter::repeat_n(Constraint::Length(3), field_count));
+    constraints.push(Constraint::Min(0)); // Status
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(constraints)
+        .split(inner);
+
+    let status_area = layout[field_count + 1];
+
+    match wizard.forms.get(wizard.step) {
+        Some(form) => {
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "Enter your {} credentials. They are checked against the live API before continuing.",
+                    form.provider.title()
+                ))
+                .wrap(Wrap { trim: true }),
+                layout[0],
+            );
+
+            for (i, ((label, secret), value)) in
+                form.provider.fields().iter().zip(&form.values).enumerate()
+            {
+                let shown = if *secret {
+                    "*".repeat(value.chars().count())
+                } else {
+                    value.clone()
+                };
+                render_field(frame, layout[i + 1], label, shown, wizard.focus == i);
+            }
+
+            let (text, color) = match &form.status {
+                ValidationStatus::Untested => (String::new(), theme.text),
+                ValidationStatus::Testing => ("Testing connection...".to_string(), theme.warning),
+                ValidationStatus::Passed => ("Connection OK".to_string(), theme.success),
+                ValidationStatus::Failed(e) => (
+                    format!(
+                        "Connection failed: {}\nEdit the values and press Enter to retry, or press Enter again to keep them anyway.",
+                        e
+                    ),
+                    theme.error,
+                ),
+            };
+            let text = match &wizard.error {
+                Some(err) => err.clone(),
+                None => text,
+            };
+            let color = if wizard.error.is_some() { theme.error } else { color };
+            frame.render_widget(
+                Paragraph::new(text)
+                    .style(Style::default().fg(color))
+                    .wrap(Wrap { trim: true }),
+                status_area,
+            );
+        }
+        None => {
+            let summary: Vec<String> = wizard
+                .forms
+                .iter()
+                .map(|f| {
+                    let state = match f.status {
+                        ValidationStatus::Passed => "verified",
+                        _ => "not verified",
+                    };
+                    format!("{}: {}", f.provider.title(), state)
+                })
+                .collect();
+            frame.render_widget(
+                Paragraph::new("Name this profile and press Enter to write config.toml."),
+                layout[0],
+            );
+            render_field(frame, layout[1], "Profile Name", wizard.profile_name.clone(), true);
+
+            let mut lines: Vec<Line> = summary.into_iter().map(Line::from).collect();
+            if let Some(err) = &wizard.error {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    err.clone(),
+                    Style::default().fg(theme.error),
+                )));
+            }
+            frame.render_widget(
+                Paragraph::new(lines).wrap(Wrap { trim: true }),
+                status_area,
+            );
+        }
+    }
+}
+
+fn render_field(frame: &mut Frame, area: Rect, label: &str, value: String, focused: bool) {
+    let theme = theme::current();
+    let style = if focused {
+        Style::default().fg(theme.highlight)
+    } else {
+        Style::default()
+    };
+    let block = Block::default().borders(Borders::ALL).title(label).style(style);
+    frame.render_widget(Paragraph::new(value).block(block), area);
+}
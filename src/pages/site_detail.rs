@@ -1,138 +1,262 @@
-use crate::app::{App, SiteDetailTab};
-use crate::common::utils::draw_pie_chart;
+use crate::app::{
+    App, DEVICE_COLUMNS, HISTORY_DAYS, SITE_ALERT_COLUMNS, SiteDetailState, SiteDetailTab,
+    SiteEditField, alert_device_name, device_av, device_type_label, last_seen_age, patch_status_label,
+    udf_label,
+};
+use crate::api::datto::types::Device;
+use crate::cache::Acknowledgement;
+use crate::common::utils::{draw_pie_chart, format_age, format_duration_short};
+use crate::ui::layout::{self, Column};
+use crate::ui::table::render_table_view;
+use crate::ui::{loading, theme};
+use chrono::Utc;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Tabs, Wrap},
+    widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Sparkline, Table, Tabs, Wrap},
 };
 
+/// Columns of the device table, by `DEVICE_COLUMNS`.
+const DEVICE_TABLE: [Column; 6] = [
+    Column::new(Constraint::Percentage(22), 12, 0), // Hostname
+    Column::new(Constraint::Percentage(12), 6, 3),  // Type
+    Column::new(Constraint::Percentage(8), 7, 1),   // Status
+    Column::new(Constraint::Percentage(15), 10, 2), // Patch Status
+    Column::new(Constraint::Percentage(18), 8, 2),  // Last Seen
+    Column::new(Constraint::Percentage(25), 8, 3),  // AV
+];
+
+/// Columns of the site alerts table, by `SITE_ALERT_COLUMNS`.
+const SITE_ALERT_TABLE: [Column; 3] = [
+    Column::new(Constraint::Length(12), 12, 1),     // Priority
+    Column::new(Constraint::Percentage(60), 16, 0), // Diagnostics
+    Column::new(Constraint::Percentage(25), 12, 2), // Computer Name
+];
+
 pub fn render_site_detail(app: &mut App, frame: &mut Frame, area: Rect) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
+    if app.site_detail().detail_tab == SiteDetailTab::Udfs {
+        let [tabs_area, matrix_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+        render_tabs(app, frame, tabs_area);
+        render_udf_matrix(app, frame, matrix_area);
+        return;
+    }
+    let chunks = layout::panes(area, 50);
 
     // --- Left Pane: Site Details ---
-    if let Some(idx) = app.table_state.selected() {
-        if let Some(site) = app.sites.get(idx) {
-            let chart_height = (chunks[0].width / 3) / 2;
-            let chart_height = chart_height.max(10).min(25); // Sanity bounds
-
-            let left_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(10),
-                    Constraint::Length(chart_height),
-                    Constraint::Min(0),
-                ])
-                .split(chunks[0]);
-
-            let text = vec![
-                Line::from(vec![
-                    Span::styled(
-                        "Description: ",
-                        Style::default().add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(site.description.as_deref().unwrap_or("N/A")),
-                ]),
-                Line::from(vec![
-                    Span::styled("Devices: ", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(
-                        site.devices_status
-                            .as_ref()
-                            .map_or("0".to_string(), |s| s.number_of_devices.to_string()),
-                    ),
-                ]),
-            ];
-
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .title(format!("Site: {}", site.name));
-            let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
-            frame.render_widget(paragraph, left_chunks[0]);
-
-            // Pie Charts Area
-            let charts_layout = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([
-                    Constraint::Percentage(33),
-                    Constraint::Percentage(33),
-                    Constraint::Percentage(34),
-                ])
-                .split(left_chunks[1]);
+    if let Some(site) = app.site_list().selected_site() {
+        let chart_height = (chunks[0].width / 3) / 2;
+        let chart_height = chart_height.max(10).min(25); // Sanity bounds
+
+        let left_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(10),
+                Constraint::Length(chart_height),
+                Constraint::Min(0),
+            ])
+            .split(chunks[0]);
+
+        let text = vec![
+            Line::from(vec![
+                Span::styled(
+                    "Description: ",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(site.description.as_deref().unwrap_or("N/A")),
+            ]),
+            Line::from(vec![
+                Span::styled("Devices: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(
+                    site.devices_status
+                        .as_ref()
+                        .map_or("0".to_string(), |s| s.number_of_devices.to_string()),
+                ),
+            ]),
+        ];
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Site: {}", site.name));
+        let inner = block.inner(left_chunks[0]);
+        frame.render_widget(block, left_chunks[0]);
+        let [text_area, trends_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(inner);
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), text_area);
+        render_site_trends(app, frame, trends_area, &site.uid);
+
+        // Stacked on a narrow terminal, the charts don't fit under the details
+        if chunks[0].height < 10 + chart_height {
+            render_site_detail_tabs(app, frame, chunks[1]);
+            return;
+        }
 
-            render_alerts_pie(app, frame, charts_layout[0]);
-            render_devices_pie(app, frame, charts_layout[1]);
-            render_patch_pie(app, frame, charts_layout[2]);
+        // Pie Charts Area
+        let charts_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(33),
+                Constraint::Percentage(33),
+                Constraint::Percentage(34),
+            ])
+            .split(left_chunks[1]);
 
-            render_av_status_bar_chart(app, frame, left_chunks[2]);
-        }
+        render_alerts_pie(app, frame, charts_layout[0]);
+        render_devices_pie(app, frame, charts_layout[1]);
+        render_patch_pie(app, frame, charts_layout[2]);
+
+        render_av_status_bar_chart(app, frame, left_chunks[2]);
     }
 
-    // --- Right Pane: Content (Tabs) ---
+    render_site_detail_tabs(app, frame, chunks[1]);
+}
+
+/// The tabs pane of site detail, beside (or below) the site's details.
+fn render_site_detail_tabs(app: &mut App, frame: &mut Frame, area: Rect) {
     let right_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(0)])
-        .split(chunks[1]);
+        .split(area);
+
+    render_tabs(app, frame, right_chunks[0]);
+
+    match app.site_detail().detail_tab {
+        SiteDetailTab::Devices => render_device_list(app, frame, right_chunks[1]),
+        SiteDetailTab::Alerts => render_site_alerts(app, frame, right_chunks[1]),
+        SiteDetailTab::Variables => render_variables(app, frame, right_chunks[1]),
+        // Rendered full width above
+        SiteDetailTab::Udfs => {}
+        SiteDetailTab::Settings => render_settings(app, frame, right_chunks[1]),
+    }
+}
 
-    let tabs = Tabs::new(vec!["Devices", "Alerts", "Variables", "Settings"])
-        .select(match app.detail_tab {
+fn render_tabs(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let tabs = Tabs::new(vec!["Devices", "Alerts", "Variables", "UDFs", "Settings"])
+        .select(match app.site_detail().detail_tab {
             SiteDetailTab::Devices => 0,
             SiteDetailTab::Alerts => 1,
             SiteDetailTab::Variables => 2,
-            SiteDetailTab::Settings => 3,
+            SiteDetailTab::Udfs => 3,
+            SiteDetailTab::Settings => 4,
         })
         .block(Block::default().borders(Borders::ALL).title("Tabs"))
         .highlight_style(
             Style::default()
                 .add_modifier(Modifier::BOLD)
-                .fg(Color::Cyan),
+                .fg(theme.accent),
         );
-    frame.render_widget(tabs, right_chunks[0]);
+    frame.render_widget(tabs, area);
+}
 
-    match app.detail_tab {
-        SiteDetailTab::Devices => render_device_list(app, frame, right_chunks[1]),
-        SiteDetailTab::Alerts => render_site_alerts(app, frame, right_chunks[1]),
-        SiteDetailTab::Variables => render_variables(app, frame, right_chunks[1]),
-        SiteDetailTab::Settings => render_settings(app, frame, right_chunks[1]),
+/// Appends the device filter (and how many devices pass it) to a panel title.
+fn push_device_filter(title: &mut String, detail: &SiteDetailState) {
+    if detail.is_device_filtering || !detail.device_filter_query.is_empty() {
+        title.push_str(&format!(" [Filter: {}", detail.device_filter_query));
+        if detail.is_device_filtering {
+            title.push('_');
+        }
+        title.push(']');
+    }
+    if detail.is_device_filter_active() {
+        title.push_str(&format!(" {}/{}", detail.devices.len(), detail.devices.items().len()));
+    }
+}
+
+/// Devices × chosen UDF columns. Takes the whole view so the columns fit.
+fn render_udf_matrix(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let detail = app.site_detail();
+    let columns = detail.matrix_columns(&app.udf_labels);
+    let selected_column = detail.udf_column.min(columns.len().saturating_sub(1));
+
+    let mut title = "UDFs".to_string();
+    push_device_filter(&mut title, detail);
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(
+            Line::from(" ←/→: column | e/Enter: edit | c: columns | f: filter ").right_aligned(),
+        );
+    if let Some(err) = &detail.device_filter_error {
+        block = block.title_bottom(
+            Line::from(Span::styled(format!(" {} ", err), Style::default().fg(theme.error))),
+        );
     }
+
+    if detail.devices_loading && detail.devices.items().is_empty() {
+        let columns = columns.len() + 1;
+        loading::render_loading_table(frame, area, block, "Loading devices...", columns);
+        return;
+    }
+
+    let header = std::iter::once(Cell::from("Hostname")).chain(
+        columns
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| {
+                let style = if i == selected_column {
+                    Style::default().fg(theme.accent)
+                } else {
+                    Style::default()
+                };
+                Cell::from(Span::styled(udf_label(&app.udf_labels, n), style))
+            }),
+    );
+    let selected_row = detail.devices.state.selected();
+    let rows: Vec<Row> = detail
+        .devices
+        .rows()
+        .enumerate()
+        .map(|(row, device)| {
+            let cells = columns.iter().enumerate().map(|(i, &n)| {
+                let value = device.udf.as_ref().and_then(|udf| udf.get(n)).unwrap_or("");
+                let cell = Cell::from(value.to_string());
+                if Some(row) == selected_row && i == selected_column {
+                    cell.style(Style::default().add_modifier(Modifier::REVERSED).fg(theme.highlight))
+                } else {
+                    cell
+                }
+            });
+            Row::new(std::iter::once(Cell::from(device.hostname.clone())).chain(cells))
+        })
+        .collect();
+
+    let widths = std::iter::once(Constraint::Length(20))
+        .chain(columns.iter().map(|_| Constraint::Fill(1)));
+    let table = Table::new(rows, widths)
+        .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(table, area, &mut app.site_detail_mut().devices.state);
 }
 
 fn render_settings(app: &mut App, frame: &mut Frame, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Settings ('Space/Enter': toggle/edit)");
+        .title("Settings ('Space/Enter': toggle/cycle/edit)");
+
+    // Long notes only fit the cell as their first line; 'o' shows them all
+    let notes = &app.site_detail().site_edit_state.notes;
+    let notes_preview = match notes.lines().next() {
+        Some(first) if notes.lines().nth(1).is_some() => {
+            format!("{} … ('o': view all)", first)
+        }
+        _ => notes.clone(),
+    };
 
     // Define the rows for the settings table
-    let rows = vec![
-        Row::new(vec![
-            Cell::from("Name"),
-            Cell::from(app.site_edit_state.name.clone()),
-        ]),
-        Row::new(vec![
-            Cell::from("Description"),
-            Cell::from(app.site_edit_state.description.clone()),
-        ]),
-        Row::new(vec![
-            Cell::from("Notes"),
-            Cell::from(app.site_edit_state.notes.clone()),
-        ]),
-        Row::new(vec![
-            Cell::from("On Demand"),
-            Cell::from(if app.site_edit_state.on_demand {
-                "[x] Enabled"
-            } else {
-                "[ ] Disabled"
-            }),
-        ]),
-        Row::new(vec![
-            Cell::from("Splashtop Auto-Install"),
-            Cell::from(if app.site_edit_state.splashtop_auto_install {
-                "[x] Enabled"
-            } else {
-                "[ ] Disabled"
-            }),
-        ]),
-    ];
+    let state = &app.site_detail().site_edit_state;
+    let rows: Vec<Row> = SiteEditField::ALL
+        .iter()
+        .map(|&field| {
+            let value = match field {
+                SiteEditField::Notes => notes_preview.clone(),
+                _ => state.display(field),
+            };
+            Row::new(vec![Cell::from(field.label()), Cell::from(value)])
+        })
+        .collect();
 
     let table = Table::new(
         rows,
@@ -143,146 +267,251 @@ fn render_settings(app: &mut App, frame: &mut Frame, area: Rect) {
     .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
     .highlight_symbol(">> ");
 
-    frame.render_stateful_widget(table, area, &mut app.settings_table_state);
+    frame.render_stateful_widget(table, area, &mut app.site_detail_mut().settings_table_state);
 }
 
-fn render_device_list(app: &mut App, frame: &mut Frame, area: Rect) {
-    let devices_block = Block::default().borders(Borders::ALL).title("Devices");
+/// Color of a device's patch status, shared with the patch report.
+pub(crate) fn patch_status_color(status: &str) -> Color {
+    let theme = theme::current();
+    match status {
+        "FullyPatched" => theme.success,
+        "ApprovedPending" => theme.accent, // Light Green/Cyan
+        "NoPolicy" => theme.error,
+        "NoData" => theme.special,
+        "RebootRequired" => theme.caution,
+        "InstallError" => theme.warning,
+        _ => theme.muted,
+    }
+}
 
-    if app.devices_loading {
-        frame.render_widget(
-            Paragraph::new("Loading devices...").block(devices_block),
-            area,
+/// Footer for an alert table showing the selected alert's acknowledgement.
+pub(crate) fn ack_footer(ack: &Acknowledgement) -> Line<'static> {
+    let theme = theme::current();
+    let text = if ack.note.is_empty() {
+        format!(" Acknowledged {} ('a': clear) ", format_age(ack.acked_at))
+    } else {
+        format!(" Acknowledged {}: {} ('a': clear) ", format_age(ack.acked_at), ack.note)
+    };
+    Line::from(Span::styled(text, Style::default().fg(theme.muted)))
+}
+
+/// Color of an antivirus status, shared with the AV coverage report.
+pub(crate) fn av_status_color(status: &str) -> Color {
+    let theme = theme::current();
+    match status {
+        "RunningAndUpToDate" => theme.success,
+        "RunningAndNotUpToDate" => theme.warning,
+        "NotDetected" => theme.caution,
+        "NotRunning" => theme.error,
+        _ => theme.text,
+    }
+}
+
+/// Glyph shown before the antivirus product in device tables.
+pub(crate) fn av_status_glyph(status: &str) -> &'static str {
+    match status {
+        "RunningAndUpToDate" => "✔",
+        "RunningAndNotUpToDate" => "▲",
+        "NotDetected" => "?",
+        "NotRunning" => "✘",
+        _ => "·",
+    }
+}
+
+/// Antivirus product with its status glyph, colored by status.
+pub(crate) fn av_cell(device: &Device) -> Cell<'static> {
+    let (product, status) = device_av(device);
+    Cell::from(Span::styled(
+        format!("{} {}", av_status_glyph(status), product),
+        Style::default().fg(av_status_color(status)),
+    ))
+}
+
+/// Agents not seen for this long are shown as stale in device tables.
+const LAST_SEEN_STALE_DAYS: i64 = 1;
+/// Agents not seen for this long are likely gone.
+const LAST_SEEN_LOST_DAYS: i64 = 7;
+
+/// How long ago the device checked in ("3d 4h"), colored by how stale it is.
+pub(crate) fn last_seen_cell(device: &Device) -> Cell<'static> {
+    let theme = theme::current();
+    let Some(age) = last_seen_age(device, Utc::now()) else {
+        return Cell::from(Span::styled("N/A", Style::default().fg(theme.muted)));
+    };
+    let color = match age.num_days() {
+        _ if device.online => theme.success,
+        days if days >= LAST_SEEN_LOST_DAYS => theme.error,
+        days if days >= LAST_SEEN_STALE_DAYS => theme.warning,
+        _ => theme.text,
+    };
+    Cell::from(Span::styled(
+        format_duration_short(age.num_seconds()),
+        Style::default().fg(color),
+    ))
+}
+
+/// "Loaded 150/420 devices", or just the loaded count if the total is unknown.
+pub(crate) fn progress_label(loaded: usize, total: Option<usize>) -> String {
+    match total {
+        Some(total) => format!("Loaded {}/{} devices", loaded, total),
+        None => format!("Loaded {} devices", loaded),
+    }
+}
+
+fn render_device_list(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let detail = app.site_detail();
+    let mut title = match detail.devices_cached_at {
+        Some(ts) if detail.devices_loading => match detail.devices_progress {
+            Some((loaded, total)) => format!(
+                "Devices (cached {}, refreshing... {})",
+                format_age(ts),
+                progress_label(loaded, total)
+            ),
+            None => format!("Devices (cached {}, refreshing...)", format_age(ts)),
+        },
+        Some(ts) => format!("Devices (cached {})", format_age(ts)),
+        None => "Devices".to_string(),
+    };
+    push_device_filter(&mut title, detail);
+    let mut devices_block = Block::default().borders(Borders::ALL).title(title);
+    if let Some(err) = &detail.device_filter_error {
+        devices_block = devices_block.title_bottom(
+            Line::from(Span::styled(format!(" {} ", err), Style::default().fg(theme.error))),
         );
-    } else if let Some(err) = &app.devices_error {
+    }
+
+    if app.site_detail().devices_loading && app.site_detail().devices_cached_at.is_none() {
+        match app.site_detail().devices_progress {
+            Some((loaded, total)) => {
+                let inner = devices_block.inner(area);
+                frame.render_widget(devices_block, area);
+                let ratio = total
+                    .filter(|&t| t > 0)
+                    .map(|t| (loaded as f64 / t as f64).min(1.0))
+                    .unwrap_or(0.0);
+                let gauge_area = Rect { height: inner.height.min(1), ..inner };
+                frame.render_widget(
+                    Gauge::default()
+                        .gauge_style(Style::default().fg(theme.accent))
+                        .ratio(ratio)
+                        .label(progress_label(loaded, total)),
+                    gauge_area,
+                );
+            }
+            None => loading::render_loading_table(
+                frame,
+                area,
+                devices_block,
+                "Loading devices...",
+                DEVICE_COLUMNS.len(),
+            ),
+        }
+    } else if let Some(err) = &app.site_detail().devices_error {
         frame.render_widget(
             Paragraph::new(format!("Error: {}", err))
-                .style(Style::default().fg(Color::Red))
+                .style(Style::default().fg(theme.error))
                 .block(devices_block),
             area,
         );
     } else {
-        let rows: Vec<Row> = app
-            .devices
-            .iter()
-            .enumerate()
-            .map(|(i, device)| {
-                let style = if Some(i) == app.devices_table_state.selected() {
-                    Style::default().add_modifier(Modifier::REVERSED)
-                } else {
-                    Style::default()
-                };
-
+        let detail = app.site_detail_mut();
+        let marked = &detail.selected_device_uids;
+        let view = &mut detail.devices;
+        render_table_view(
+            frame,
+            area,
+            devices_block,
+            view,
+            &DEVICE_TABLE,
+            &DEVICE_COLUMNS,
+            |_, device, fit| {
                 let status = if device.online { "Online" } else { "Offline" };
                 let status_color = if device.online {
-                    Color::Green
+                    theme.success
                 } else {
-                    Color::Gray
+                    theme.muted
                 };
 
-                let patch_status = device
-                    .patch_management
-                    .as_ref()
-                    .and_then(|pm| pm.patch_status.clone())
-                    .unwrap_or_else(|| "Unknown".to_string());
-
-                let patch_color = match patch_status.as_str() {
-                    "FullyPatched" => Color::Green,
-                    "ApprovedPending" => Color::Cyan, // Light Green/Cyan
-                    "NoPolicy" => Color::Red,
-                    "NoData" => Color::Magenta,
-                    "RebootRequired" => Color::LightRed, // Orange-ish often represented by LightRed or Yellow
-                    "InstallError" => Color::Yellow,
-                    _ => Color::Gray,
-                };
+                let patch_status = patch_status_label(device).to_string();
 
-                let mut device_type = device
-                    .device_type
-                    .as_ref()
-                    .and_then(|dt| dt.type_field.clone())
-                    .unwrap_or_else(|| "Unknown".to_string());
+                let patch_color = patch_status_color(&patch_status);
 
-                if device_type == "Main System Chassis" {
-                    device_type = "Server".to_string();
-                }
+                let device_type = device_type_label(device);
 
-                let hostname_prefix = if app.selected_device_uids.contains(&device.uid) {
+                let hostname_prefix = if marked.contains(&device.uid) {
                     "[*] "
                 } else {
                     ""
                 };
 
-                Row::new(vec![
+                Row::new(fit.pick(vec![
                     Cell::from(format!("{}{}", hostname_prefix, device.hostname)),
                     Cell::from(device_type),
                     Cell::from(Span::styled(status, Style::default().fg(status_color))),
                     Cell::from(Span::styled(patch_status, Style::default().fg(patch_color))),
-                ])
-                .style(style)
-            })
-            .collect();
-
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Percentage(35),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(35),
-            ],
-        )
-        .header(
-            Row::new(vec!["Hostname", "Type", "Status", "Patch Status"])
-                .style(Style::default().add_modifier(Modifier::BOLD)),
-        )
-        .block(devices_block)
-        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
-
-        frame.render_stateful_widget(table, area, &mut app.devices_table_state);
+                    last_seen_cell(device),
+                    av_cell(device),
+                ]))
+            },
+        );
     }
 }
 
 fn render_site_alerts(app: &mut App, frame: &mut Frame, area: Rect) {
-    let block = Block::default().borders(Borders::ALL).title("Site Alerts");
+    let theme = theme::current();
+    let mut block = Block::default().borders(Borders::ALL).title("Site Alerts ('a': acknowledge)");
 
-    if app.site_open_alerts_loading {
-        frame.render_widget(Paragraph::new("Loading alerts...").block(block), area);
+    if app.site_detail().site_open_alerts_loading {
+        let columns = SITE_ALERT_COLUMNS.len();
+        loading::render_loading_table(frame, area, block, "Loading alerts...", columns);
         return;
     }
 
-    if let Some(err) = &app.site_open_alerts_error {
+    if let Some(err) = &app.site_detail().site_open_alerts_error {
         frame.render_widget(
             Paragraph::new(format!("Error: {}", err))
-                .style(Style::default().fg(Color::Red))
+                .style(Style::default().fg(theme.error))
                 .block(block),
             area,
         );
         return;
     }
 
-    if app.site_open_alerts.is_empty() {
+    if app.site_detail().site_open_alerts.is_empty() {
         frame.render_widget(Paragraph::new("No open alerts.").block(block), area);
         return;
     }
 
-    let rows: Vec<Row> = app
+    // Acknowledged alerts render dimmed; the selected one's note goes in the footer
+    let detail = app.site_detail();
+    let acked: Vec<bool> = detail
         .site_open_alerts
-        .iter()
-        .enumerate()
-        .map(|(i, alert)| {
-            let style = if Some(i) == app.site_open_alerts_table_state.selected() {
-                Style::default().add_modifier(Modifier::REVERSED)
-            } else {
-                Style::default()
-            };
+        .rows()
+        .map(|alert| app.alert_acknowledgement(alert).is_some())
+        .collect();
+    if let Some(ack) =
+        detail.site_open_alerts.selected().and_then(|alert| app.alert_acknowledgement(alert))
+    {
+        block = block.title_bottom(ack_footer(ack));
+    }
 
+    let view = &mut app.site_detail_mut().site_open_alerts;
+    render_table_view(
+        frame,
+        area,
+        block,
+        view,
+        &SITE_ALERT_TABLE,
+        &SITE_ALERT_COLUMNS,
+        |i, alert, fit| {
             let priority = alert.priority.as_deref().unwrap_or("Unknown");
             let priority_style = match priority.to_lowercase().as_str() {
-                "critical" => Style::default().fg(Color::Red),
-                "high" => Style::default().fg(Color::Rgb(255, 165, 0)), // Orange
-                "moderate" | "medium" => Style::default().fg(Color::Yellow),
-                "low" => Style::default().fg(Color::Cyan),
-                "information" => Style::default().fg(Color::White),
+                "critical" => Style::default().fg(theme.error),
+                "high" => Style::default().fg(theme.caution),
+                "moderate" | "medium" => Style::default().fg(theme.warning),
+                "low" => Style::default().fg(theme.accent),
+                "information" => Style::default().fg(theme.text),
                 _ => Style::default(),
             };
 
@@ -295,117 +524,160 @@ fn render_site_alerts(app: &mut App, frame: &mut Frame, area: Rect) {
                 .trim()
                 .to_string();
 
-            let computer_name = alert
-                .alert_source_info
-                .as_ref()
-                .and_then(|s| s.device_name.as_deref())
-                .unwrap_or("N/A");
+            let computer_name = alert_device_name(alert);
+            let (priority_style, diagnostics, style) = if acked[i] {
+                let dimmed = Style::default().fg(theme.muted).add_modifier(Modifier::DIM);
+                (Style::default(), format!("✓ {}", diagnostics), dimmed)
+            } else {
+                (priority_style, diagnostics, Style::default())
+            };
 
-            Row::new(vec![
+            Row::new(fit.pick(vec![
                 Cell::from(Span::styled(priority, priority_style)),
                 Cell::from(diagnostics),
                 Cell::from(computer_name.to_string()),
-            ])
+            ]))
             .style(style)
-        })
-        .collect();
-
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(12),     // Priority
-            Constraint::Percentage(60), // Diagnostics
-            Constraint::Percentage(25), // Computer Name
-        ],
-    )
-    .header(
-        Row::new(vec!["Priority", "Diagnostics", "Computer Name"])
-            .style(Style::default().add_modifier(Modifier::BOLD)),
-    )
-    .block(block)
-    .highlight_symbol(">> ");
-
-    frame.render_stateful_widget(table, area, &mut app.site_open_alerts_table_state);
+        },
+    );
 }
 
 fn render_variables(app: &mut App, frame: &mut Frame, area: Rect) {
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title("Variables (Space/Enter: Select)");
-
-    if let Some(idx) = app.table_state.selected() {
-        if let Some(site) = app.sites.get(idx) {
-            if let Some(vars) = &site.variables {
-                let mut rows: Vec<Row> = vars
-                    .iter()
-                    .enumerate()
-                    .map(|(i, var)| {
-                        let style = if Some(i) == app.variables_table_state.selected() {
-                            Style::default().add_modifier(Modifier::REVERSED)
-                        } else {
-                            Style::default()
-                        };
-
-                        Row::new(vec![
-                            Cell::from(var.name.clone()),
-                            Cell::from(var.value.clone()),
-                            Cell::from(if var.masked { "*" } else { "" }),
-                        ])
-                        .style(style)
-                    })
-                    .collect();
-
-                // Add "Create new variable" row
-                rows.push(
+    let cached_at = app.site_list()
+        .selected_site()
+        .and_then(|site| app.site_detail().variables_cached_at.get(&site.uid));
+    let title = match cached_at {
+        Some(ts) => format!("Variables (cached {})", format_age(*ts)),
+        None => "Variables".to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title).title_bottom(
+        Line::from(" Enter: edit | x: delete | Space: mark | C: copy to site | t: template ")
+            .right_aligned(),
+    );
+
+    if let Some(site) = app.site_list().selected_site() {
+        if let Some(vars) = &site.variables {
+            let mut rows: Vec<Row> = vars
+                .iter()
+                .enumerate()
+                .map(|(i, var)| {
+                    let style = if Some(i) == app.site_detail().variables_table_state.selected() {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                    };
+
+                    let name_prefix = if app.site_detail().selected_variable_ids.contains(&var.id) {
+                        "[*] "
+                    } else {
+                        ""
+                    };
+
                     Row::new(vec![
-                        Cell::from(Span::styled(
-                            "+ Create new",
-                            Style::default().add_modifier(Modifier::BOLD | Modifier::ITALIC),
-                        )),
-                        Cell::from(""),
-                        Cell::from(""),
+                        Cell::from(format!("{}{}", name_prefix, var.name)),
+                        Cell::from(var.value.clone()),
+                        Cell::from(if var.masked { "*" } else { "" }),
                     ])
-                    .style(
-                        if app.variables_table_state.selected() == Some(rows.len()) {
-                            Style::default().add_modifier(Modifier::REVERSED)
-                        } else {
-                            Style::default()
-                        },
-                    ),
-                );
+                    .style(style)
+                })
+                .collect();
 
-                let table = Table::new(
-                    rows,
-                    [
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(60),
-                        Constraint::Percentage(10),
-                    ],
-                )
-                .header(
-                    Row::new(vec!["Name", "Value", "Masked"])
-                        .style(Style::default().add_modifier(Modifier::BOLD)),
-                )
-                .block(block) // Use the block here
-                .highlight_symbol(">> ");
-
-                frame.render_stateful_widget(table, area, &mut app.variables_table_state);
-                return;
-            }
+            // Add "Create new variable" row
+            rows.push(
+                Row::new(vec![
+                    Cell::from(Span::styled(
+                        "+ Create new",
+                        Style::default().add_modifier(Modifier::BOLD | Modifier::ITALIC),
+                    )),
+                    Cell::from(""),
+                    Cell::from(""),
+                ])
+                .style(
+                    if app.site_detail().variables_table_state.selected() == Some(rows.len()) {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                    },
+                ),
+            );
+
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Percentage(30),
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(10),
+                ],
+            )
+            .header(
+                Row::new(vec!["Name", "Value", "Masked"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(block) // Use the block here
+            .highlight_symbol(">> ");
+
+            frame.render_stateful_widget(table, area, &mut app.site_detail_mut().variables_table_state);
+            return;
         }
     }
     // Fallback if no site selected or no variables
     frame.render_widget(Paragraph::new("No variables").block(block), area);
 }
 
+/// Sparklines of the site's daily device, offline and open alert counts.
+fn render_site_trends(app: &App, frame: &mut Frame, area: Rect, site_uid: &str) {
+    let theme = theme::current();
+    let today = chrono::Local::now().date_naive();
+    let Some(series) = app.site_history().series(site_uid, today) else {
+        return;
+    };
+    let rows = Layout::vertical([Constraint::Length(2); 3]).split(area);
+    let trends = [
+        ("Devices", theme.accent),
+        ("Offline", theme.error),
+        ("Alerts", theme.warning),
+    ];
+    for (((label, color), data), row) in trends.into_iter().zip(&series).zip(rows.iter()) {
+        // Latest count, and its change since the oldest day recorded in the window
+        let known: Vec<u64> = data.iter().flatten().copied().collect();
+        let summary = match (known.first(), known.last()) {
+            (Some(first), Some(last)) if last != first => {
+                format!("{} ({:+})", last, *last as i64 - *first as i64)
+            }
+            (_, Some(last)) => last.to_string(),
+            _ => "-".to_string(),
+        };
+        let [label_area, chart_area] =
+            Layout::horizontal([Constraint::Length(18), Constraint::Min(0)]).areas(*row);
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(format!("{}: ", label), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(summary),
+            ])),
+            label_area,
+        );
+        frame.render_widget(
+            Sparkline::default()
+                .data(data)
+                .style(Style::default().fg(color))
+                .absent_value_style(Style::default().fg(theme.muted)),
+            Rect {
+                width: chart_area.width.min(HISTORY_DAYS as u16),
+                ..chart_area
+            },
+        );
+    }
+}
+
 fn render_alerts_pie(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
     let mut info = 0;
     let mut low = 0;
     let mut moderate = 0;
     let mut high = 0;
     let mut critical = 0;
 
-    for alert in &app.site_open_alerts {
+    for alert in app.site_detail().site_open_alerts.items() {
         match alert
             .priority
             .as_deref()
@@ -423,39 +695,39 @@ fn render_alerts_pie(app: &App, frame: &mut Frame, area: Rect) {
 
     let total = info + low + moderate + high + critical;
     let data = vec![
-        (info as f64, Color::White, "Info"),
-        (low as f64, Color::Cyan, "Low"),
-        (moderate as f64, Color::Yellow, "Mod"),
-        (high as f64, Color::Rgb(255, 165, 0), "High"),
-        (critical as f64, Color::Red, "Crit"),
+        (info as f64, theme.text, "Info"),
+        (low as f64, theme.accent, "Low"),
+        (moderate as f64, theme.warning, "Mod"),
+        (high as f64, theme.caution, "High"),
+        (critical as f64, theme.error, "Crit"),
     ];
 
     draw_pie_chart(frame, area, "Open Alerts", total, &data);
 }
 
 fn render_devices_pie(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
     let mut online = 0;
     let mut offline = 0;
 
-    if let Some(idx) = app.table_state.selected() {
-        if let Some(site) = app.sites.get(idx) {
-            if let Some(status) = &site.devices_status {
-                online = status.number_of_online_devices;
-                offline = status.number_of_offline_devices;
-            }
+    if let Some(site) = app.site_list().selected_site() {
+        if let Some(status) = &site.devices_status {
+            online = status.number_of_online_devices;
+            offline = status.number_of_offline_devices;
         }
     }
 
     let total = online + offline;
     let data = vec![
-        (online as f64, Color::Green, "Online"),
-        (offline as f64, Color::Red, "Offline"),
+        (online as f64, theme.success, "Online"),
+        (offline as f64, theme.error, "Offline"),
     ];
 
     draw_pie_chart(frame, area, "Device Status", total, &data);
 }
 
 fn render_patch_pie(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
     let mut fully_patched = 0;
     let mut approved_pending = 0;
     let mut install_error = 0;
@@ -464,7 +736,7 @@ fn render_patch_pie(app: &App, frame: &mut Frame, area: Rect) {
     let mut no_policy = 0;
     let mut other = 0;
 
-    for device in &app.devices {
+    for device in app.site_detail().devices.items() {
         if let Some(pm) = &device.patch_management {
             match pm.patch_status.as_deref() {
                 Some("FullyPatched") => fully_patched += 1,
@@ -489,13 +761,13 @@ fn render_patch_pie(app: &App, frame: &mut Frame, area: Rect) {
         + other;
 
     let data = vec![
-        (fully_patched as f64, Color::Green, "Patched"),
-        (approved_pending as f64, Color::Cyan, "Pending"),
-        (install_error as f64, Color::Yellow, "Error"),
-        (reboot_required as f64, Color::Rgb(255, 165, 0), "Reboot"),
-        (no_data as f64, Color::Red, "No Data"),
-        (no_policy as f64, Color::Gray, "No Pol"),
-        (other as f64, Color::White, "Other"),
+        (fully_patched as f64, theme.success, "Patched"),
+        (approved_pending as f64, theme.accent, "Pending"),
+        (install_error as f64, theme.warning, "Error"),
+        (reboot_required as f64, theme.caution, "Reboot"),
+        (no_data as f64, theme.error, "No Data"),
+        (no_policy as f64, theme.muted, "No Pol"),
+        (other as f64, theme.text, "Other"),
     ];
 
     draw_pie_chart(frame, area, "Patch Status", total, &data);
@@ -503,7 +775,7 @@ fn render_patch_pie(app: &App, frame: &mut Frame, area: Rect) {
 
 fn render_av_status_bar_chart(app: &App, frame: &mut Frame, area: Rect) {
     let mut stats: std::collections::BTreeMap<String, i32> = std::collections::BTreeMap::new();
-    for device in &app.devices {
+    for device in app.site_detail().devices.items() {
         let status = device
             .antivirus
             .as_ref()
@@ -533,13 +805,7 @@ fn render_av_status_bar_chart(app: &App, frame: &mut Frame, area: Rect) {
         let bar_width = ((count as f64 / max_value as f64) * bar_max_width as f64) as usize;
         let bar = "█".repeat(bar_width);
 
-        let color = match status_raw.as_str() {
-            "RunningAndUpToDate" => Color::Green,
-            "RunningAndNotUpToDate" => Color::Yellow,
-            "NotDetected" => Color::Rgb(255, 165, 0), // Orange
-            "NotRunning" => Color::Red,
-            _ => Color::White,
-        };
+        let color = av_status_color(&status_raw);
 
         lines.push(Line::from(vec![
             Span::styled(
@@ -1,91 +1,213 @@
-use crate::app::App;
+use crate::api::datto::types::Site;
+use crate::app::{App, FAIR_SCORE, GOOD_SCORE, SITE_COLUMNS, SiteRow, incident_key, is_server};
+use crate::common::site_rules::{SiteFacts, named_color};
+use crate::ui::layout::{Column, FittedColumns};
+use crate::ui::theme;
+use std::collections::HashMap;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Cell, Row, Table},
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
 };
 
-pub fn render_site_list(app: &mut App, frame: &mut Frame, area: Rect, block: Block) {
-    let rows: Vec<Row> = app
-        .sites
-        .iter()
-        .map(|site| {
-            let device_count = site
-                .devices_status
-                .as_ref()
-                .map(|s| s.number_of_devices)
-                .unwrap_or(0);
+/// Columns of the site table; the UID goes first when it doesn't fit.
+const COLUMNS: [Column; 7] = [
+    Column::new(Constraint::Percentage(28), 20, 0), // Site Name
+    Column::new(Constraint::Percentage(9), 7, 1),   // Devices
+    Column::new(Constraint::Percentage(9), 6, 1),   // Active
+    Column::new(Constraint::Percentage(9), 8, 3),   // Resolved
+    Column::new(Constraint::Percentage(9), 6, 2),   // Alerts
+    Column::new(Constraint::Percentage(10), 9, 2),  // Posture
+    Column::new(Constraint::Percentage(26), 36, 4), // UID
+];
+
+fn device_count(site: &Site) -> i32 {
+    site.devices_status
+        .as_ref()
+        .map(|s| s.number_of_devices)
+        .unwrap_or(0)
+}
+
+/// A posture score as a badge colored by how good it is; "-" while unknown.
+fn posture_cell(score: Option<u8>) -> Cell<'static> {
+    let theme = theme::current();
+    let Some(score) = score else {
+        return Cell::from("-");
+    };
+    let color = match score {
+        s if s >= GOOD_SCORE => theme.success,
+        s if s >= FAIR_SCORE => theme.warning,
+        _ => theme.error,
+    };
+    let style = Style::default().fg(theme.background).bg(color).add_modifier(Modifier::BOLD);
+    Cell::from(Span::styled(format!(" {:>3} ", score), style))
+}
 
-            let mut site_color = Style::default();
-            let mut lookup_key = site.name.to_lowercase();
+pub fn render_site_list(app: &mut App, frame: &mut Frame, area: Rect, block: Block) {
+    let theme = theme::current();
+    // Offline servers per site, from the account devices once they are loaded
+    let mut offline_servers: HashMap<&str, i64> = HashMap::new();
+    for device in app.report().devices.iter().filter(|d| is_server(d) && !d.online) {
+        *offline_servers.entry(device.site_uid.as_str()).or_default() += 1;
+    }
+    let site_list = app.site_list();
+    let incidents = |site: &Site| {
+        // Fetch stats by tuiMdrId or site name
+        app.security()
+            .incident_stats
+            .get(&incident_key(site, &app.security().mdr_defaults))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let alerts = |count: Option<usize>| count.map_or_else(|| "-".to_string(), |n| n.to_string());
+    let alert_style = |count: Option<usize>| match count {
+        Some(n) if n > 0 => Style::default().fg(theme.warning),
+        _ => Style::default(),
+    };
 
-            if let Some(vars) = &site.variables {
-                for var in vars {
-                    match var.name.as_str() {
-                        "tuiColor" => {
-                            let c = match var.value.to_lowercase().as_str() {
-                                "red" => Color::Red,
-                                "blue" => Color::Blue,
-                                "green" => Color::Green,
-                                "yellow" => Color::Yellow,
-                                "magenta" => Color::Magenta,
-                                "cyan" => Color::Cyan,
-                                "white" => Color::White,
-                                "gray" => Color::Gray,
-                                _ => Color::Reset,
-                            };
-                            if c != Color::Reset {
-                                site_color = Style::default().fg(c);
-                            }
-                        }
-                        "tuiMdrId" => {
-                            // Use the provided ID for lookup
-                            lookup_key = var.value.clone();
-                        }
-                        _ => {}
-                    }
+    let fit = FittedColumns::new(area, &COLUMNS);
+    let mut rows: Vec<Row> = site_list
+        .rows()
+        .map(|row| {
+            let site = match row {
+                SiteRow::Site(site) => site,
+                // Section header with the totals of its sites
+                SiteRow::Section(section) => {
+                    let sites: Vec<&Site> = site_list.section_sites(section).collect();
+                    let devices: i32 = sites.iter().map(|s| device_count(s)).sum();
+                    let active: i32 = sites.iter().map(|s| incidents(s).active).sum();
+                    let resolved: i32 = sites.iter().map(|s| incidents(s).resolved).sum();
+                    let open_alerts = sites
+                        .iter()
+                        .map(|s| site_list.open_alert_count(s))
+                        .sum::<Option<usize>>();
+                    // The section is as good as its worst site
+                    let posture =
+                        sites.iter().filter_map(|s| site_list.posture(s, &incidents(s))).min();
+                    let marker = if section.collapsed { "▸" } else { "▾" };
+                    let style = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+                    return Row::new(fit.pick(vec![
+                        Cell::from(format!("{} {} ({})", marker, section.title, sites.len())),
+                        Cell::from(devices.to_string()),
+                        Cell::from(active.to_string()),
+                        Cell::from(resolved.to_string()),
+                        Cell::from(alerts(open_alerts)),
+                        posture_cell(posture),
+                        Cell::from(""),
+                    ]))
+                    .style(style);
                 }
-            }
+            };
+            let device_count = device_count(site);
+            let stats = incidents(site);
 
-            // Fetch stats using the determined key
-            let stats = app
-                .incident_stats
-                .get(&lookup_key)
-                .cloned()
-                .unwrap_or_default();
+            let variables = site.variables.as_deref().unwrap_or_default();
+            let status = site.devices_status.as_ref();
+            let facts = SiteFacts {
+                devices: device_count.into(),
+                online_devices: status.map_or(0, |s| s.number_of_online_devices.into()),
+                offline_devices: status.map_or(0, |s| s.number_of_offline_devices.into()),
+                offline_servers: offline_servers.get(site.uid.as_str()).copied().unwrap_or(0),
+                active_incidents: stats.active.into(),
+                resolved_incidents: stats.resolved.into(),
+                variables,
+            };
+            let matched: Vec<_> = app.site_rules.iter().filter(|r| r.when.matches(&facts)).collect();
+
+            // The first matching rule with a color wins over tuiColor
+            let site_color = matched
+                .iter()
+                .find_map(|r| r.color.as_deref().and_then(named_color))
+                .or_else(|| {
+                    variables
+                        .iter()
+                        .find(|v| v.name == "tuiColor")
+                        .and_then(|v| named_color(&v.value))
+                })
+                .map_or(Style::default(), |c| Style::default().fg(c));
+            let badges: Vec<&str> = matched.iter().filter_map(|r| r.badge.as_deref()).collect();
+            let mut name = Vec::new();
+            // Account badge in the merged list
+            if site_list.show_merged {
+                let account = site.account.as_deref().unwrap_or(&app.active_profile);
+                name.push(Span::styled(
+                    format!("[{}] ", account),
+                    Style::default().fg(theme.info),
+                ));
+            }
+            name.push(Span::styled(site.name.clone(), site_color));
+            if !badges.is_empty() {
+                name.push(Span::styled(
+                    format!(" {}", badges.join(" ")),
+                    Style::default().fg(theme.caution).add_modifier(Modifier::BOLD),
+                ));
+            }
 
             let active_style = if stats.active > 0 {
-                Style::default().fg(Color::Red)
+                Style::default().fg(theme.error)
             } else {
                 Style::default()
             };
+            let open_alerts = site_list.open_alert_count(site);
 
-            Row::new(vec![
-                Cell::from(Span::styled(site.name.clone(), site_color)),
+            Row::new(fit.pick(vec![
+                Cell::from(Line::from(name)),
                 Cell::from(device_count.to_string()),
                 Cell::from(Span::styled(stats.active.to_string(), active_style)),
                 Cell::from(stats.resolved.to_string()),
+                Cell::from(Span::styled(alerts(open_alerts), alert_style(open_alerts))),
+                posture_cell(site_list.posture(site, &stats)),
                 Cell::from(site.uid.clone()),
-            ])
+            ]))
         })
         .collect();
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Percentage(30),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10), // Active
-            Constraint::Percentage(10), // Resolved
-            Constraint::Percentage(40),
-        ],
-    )
-    .header(
-        Row::new(vec!["Site Name", "Devices", "Active", "Resolved", "UID"])
-            .style(Style::default().add_modifier(Modifier::BOLD)),
-    )
-    .block(block)
-    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
-    .highlight_symbol(">> ");
+    let widths = fit.widths();
+    let header = Row::new(fit.pick(app.site_list().sort.header(&SITE_COLUMNS)))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+    // Pinned sites lead the rows; they get their own box above the rest,
+    // sharing the list's single selection index.
+    let pinned_count = app.site_list().pinned_visible_count();
+    if pinned_count == 0 {
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(table, area, &mut app.site_list_mut().table_state);
+        return;
+    }
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let pinned_height = (pinned_count as u16 + 3).min(inner.height / 2);
+    let [pinned_area, rest_area] =
+        Layout::vertical([Constraint::Length(pinned_height), Constraint::Min(0)]).areas(inner);
+
+    let rest = rows.split_off(pinned_count);
+    let selected = app.site_list().table_state.selected();
+    let mut pinned_state = TableState::default().with_selected(selected.filter(|&i| i < pinned_count));
+    let pinned_table = Table::new(rows, widths.clone())
+        .header(header.clone())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Pinned")
+                .border_style(Style::default().fg(theme.accent)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(pinned_table, pinned_area, &mut pinned_state);
 
-    frame.render_stateful_widget(table, area, &mut app.table_state);
+    let rest_table = Table::new(rest, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::TOP).title("All Sites"))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+    // Render the unpinned rows with the list's own state (so scrolling is kept),
+    // shifting the selection into their index space for the duration.
+    let state = &mut app.site_list_mut().table_state;
+    state.select(selected.and_then(|i| i.checked_sub(pinned_count)));
+    frame.render_stateful_widget(rest_table, rest_area, state);
+    state.select(selected);
 }
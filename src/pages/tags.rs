@@ -0,0 +1,111 @@
+use crate::app::App;
+use crate::ui::{layout, loading, theme};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+};
+
+/// Tags in use across the account on the left, and the devices carrying the
+/// selected one on the right.
+pub fn render_tags(app: &mut App, frame: &mut Frame, area: Rect) {
+    let chunks = layout::panes(area, 30);
+
+    render_tag_list(app, frame, chunks[0]);
+    render_tagged_devices(app, frame, chunks[1]);
+}
+
+fn render_tag_list(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let state = app.tags();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(layout::pane_border(!state.devices_focused))
+        .title(format!("Tags ({})", state.tags.len()));
+
+    let Some(udf) = app.tags_udf else {
+        let text = Paragraph::new(
+            "Set tags_udf in the profile to the UDF number devices keep their tags in, \
+             e.g. tags_udf = 10, then tag devices with 't' on the device detail view.",
+        )
+        .style(Style::default().fg(theme.dim))
+        .wrap(Wrap { trim: true })
+        .block(block);
+        frame.render_widget(text, area);
+        return;
+    };
+    let report = app.report();
+    if report.loading && report.devices.is_empty() {
+        loading::render_loading_table(frame, area, block, "Loading devices...", 2);
+        return;
+    }
+    if state.tags.is_empty() {
+        let text = Paragraph::new(format!("No device has tags in UDF {}", udf))
+            .style(Style::default().fg(theme.dim))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let rows: Vec<Row> = state
+        .tags
+        .iter()
+        .map(|(tag, count)| Row::new(vec![Cell::from(tag.clone()), Cell::from(count.to_string())]))
+        .collect();
+    let table = Table::new(rows, [Constraint::Min(10), Constraint::Length(7)])
+        .header(
+            Row::new(vec!["Tag", "Devices"]).style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.tags_mut().tag_state);
+}
+
+fn render_tagged_devices(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let state = app.tags();
+    let title = match state.selected_tag() {
+        Some(tag) => format!("Devices tagged {} ({})", tag, state.devices.len()),
+        None => "Devices".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(layout::pane_border(state.devices_focused))
+        .title(title)
+        .title_bottom(Line::from(" Enter: open device | r: reload ").right_aligned());
+
+    let rows: Vec<Row> = state
+        .devices
+        .iter()
+        .map(|device| {
+            let (status, color) =
+                if device.online { ("Online", theme.success) } else { ("Offline", theme.dim) };
+            Row::new(vec![
+                Cell::from(device.hostname.clone()),
+                Cell::from(device.site_name.clone().unwrap_or_default()),
+                Cell::from(Span::styled(status, Style::default().fg(color))),
+                Cell::from(device.operating_system.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Length(8),
+            Constraint::Percentage(40),
+        ],
+    )
+    .header(
+        Row::new(vec!["Hostname", "Site", "Status", "OS"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(table, area, &mut app.tags_mut().device_state);
+}
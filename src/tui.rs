@@ -22,9 +22,25 @@ pub fn restore() -> Result<()> {
     Ok(())
 }
 
+/// Takes the terminal back after `restore`, e.g. once a child process that
+/// ran in it exits, and redraws everything.
+pub fn resume(tui: &mut Tui) -> Result<()> {
+    execute!(stdout(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    tui.clear()?;
+    Ok(())
+}
+
+/// Restores the terminal before a panic is printed. A background task that
+/// panics doesn't take the app down (see `app::Supervisor`), so its panic is
+/// only logged and the screen is left alone.
 pub fn install_panic_hook() {
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
+        if let Some(task) = crate::app::current_task() {
+            tracing::error!(task, "{}", panic_info);
+            return;
+        }
         // intentionally ignore errors here since we're already panicking
         let _ = restore();
         original_hook(panic_info);
@@ -1,110 +1,391 @@
-use crate::app::{App, CurrentView, InputMode};
+pub mod layout;
+pub mod loading;
+pub mod table;
+pub mod theme;
+
+use crate::app::{App, CurrentView, Popup, SITE_COLUMNS};
+use crate::common::utils::{format_age, loading_text};
 use crate::pages::{
+    account_variables::render_account_variables,
     activity_detail::render_activity_detail,
+    collections::render_collections,
+    components::render_components,
     device_detail::render_device_detail,
+    integrations::render_integrations,
     popups::{
-        render_device_search_popup, render_input_modal, render_popup, render_quick_action_menu,
-        render_reboot_popup, render_run_component_popup, render_site_move_popup,
+        render_add_to_collection_popup, render_alert_detail_popup, render_confirmation_popup,
+        render_decommission_popup,
+        render_device_edit_popup,
+        render_device_search_popup,
+        render_error_history_popup,
+        render_input_modal, render_log_viewer_popup, render_net_tools_popup,
+        render_notes_viewer_popup,
+        render_notifications_popup, render_popup,
+        render_quick_action_menu,
+        render_profile_switcher_popup, render_reboot_popup, render_recent_devices_popup,
+        render_run_component_popup, render_scans_popup, render_session_restore_popup,
+        render_site_deletion_popup, render_site_move_popup, render_variable_copy_popup,
+        render_variable_templates_popup,
         render_warranty_popup,
     },
+    report::render_report,
     site_detail::render_site_detail,
     site_list::render_site_list,
+    tags::render_tags,
 };
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
 
 pub fn render(app: &mut App, frame: &mut Frame) {
+    let theme = theme::current();
     let layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(3), Constraint::Min(0)])
+        .constraints(vec![Constraint::Length(4), Constraint::Min(0)])
         .split(frame.area());
 
-    // Title / Status
-    let status_text = match app.current_view {
+    // Breadcrumb and key hints
+    let hints = match app.current_view {
         CurrentView::List => {
-            format!(
-                "Kyber TUI | Sites: {} | 'q': quit, 'r': reload, '/': search devices, 'j/k': move, 'Enter': details",
-                app.total_count
-            )
+            "'q': quit, 'r': reload, '/': filter, 'H': hide empty, 'h': recent devices, 'V': scans, 'b': notifications, 'd': search devices, 'R': reports, 'o': offline servers, 'c': components, 'v': account variables, 'T': tags, 'C': collections, 'I': integrations, 'p': pin, 'P': profile, 'M': merge accounts, 'W': open in Datto RMM, 'A': re-authenticate, 'L': logs, 'E': errors, 's/S': sort, 'j/k': move, 'Enter': details/expand group"
         }
         CurrentView::Detail => {
-            "Site Detail View | 'Esc'/'q': back, '/': search, 'Space': select, 'r': quick actions"
-                .to_string()
+            "'/': search, 'Space': select, '+': add to collection, 'f': filter, 'a': acknowledge alert, 's/S': sort, 'r': quick actions, 'C': copy variables, 'x': delete variable, 't': variable template, 'o': view notes, '←/→': UDF column, 'c': UDF columns, 'X': delete site, 'W': open in Datto RMM"
         }
         CurrentView::DeviceDetail => {
-            "Device Detail | 'Esc'/'q': back, 'r': quick actions, 'v': variables".to_string()
+            "'r': quick actions, 'e': edit description/name, 'w': watch online status, 'l': launch RDP/SSH, 't': edit tags, '+': add to collection, 'v': variables, 'Enter': alert detail, 'a': acknowledge alert, 'c': cancel scheduled job, 'n/N': next/prev device, 'PgUp/PgDn': scroll info, 'p': switch scrolled pane, 'h': recent devices, 'V': scans, 's/S': sort, 'W': open in Datto RMM"
+        }
+        CurrentView::ActivityDetail => {
+            "'Enter': open output, '1-9': jump to component, 'Space': collapse, 'F': follow output while running, 'R': re-run, 'c': cancel if scheduled"
+        }
+        CurrentView::Report => {
+            "'Tab': next report, '+/-': range, 'x'/'X': export CSV/JSON, 'r': reload, 'Enter': drill down, 'W': open in Datto RMM"
+        }
+        CurrentView::Components => "'/': filter, 'r': reload, 'j/k': move, 'Enter': run against device",
+        CurrentView::Integrations => "'j/k': move, 'a'/'Enter': re-authenticate, 'A': re-authenticate all",
+        CurrentView::AccountVariables => "'j/k': move, 'Enter': edit/create, 'x': delete, 'r': reload",
+        CurrentView::Tags => {
+            "'Tab': switch list, 'j/k': move, 'Enter': open device, '+': add to collection, 'r': reload"
         }
-        CurrentView::ActivityDetail => "Activity Detail | 'Esc'/'q': back".to_string(),
+        CurrentView::Collections => {
+            "'Tab': switch list, 'j/k': move, 'Enter': open, 'r': run on collection, 'x': remove device/delete collection"
+        }
+    };
+    let crumbs = breadcrumb(app);
+    // The site list is the root; 'q' quits there instead of going back
+    let mut hint_line = match crumbs.len() {
+        n if n > 2 => format!("'Esc'/'q': back to {}", crumbs[n - 2]),
+        _ => String::new(),
     };
+    if !hints.is_empty() {
+        if !hint_line.is_empty() {
+            hint_line.push_str(", ");
+        }
+        hint_line.push_str(hints);
+    }
+
+    let mut crumb_spans = Vec::new();
+    for (i, crumb) in crumbs.iter().enumerate() {
+        if i > 0 {
+            crumb_spans.push(Span::styled(" › ", Style::default().fg(theme.muted)));
+        }
+        let style = if i + 1 == crumbs.len() {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        crumb_spans.push(Span::styled(crumb.clone(), style));
+    }
 
+    // Remaining Datto RMM request quota, once the API has reported it
+    let mut header_block = Block::default().borders(Borders::ALL);
+    if let Some(quota) = app.client.as_ref().and_then(|c| c.quota()) {
+        let (text, color) = if quota.is_low() {
+            let reset = quota.reset_at.saturating_duration_since(std::time::Instant::now());
+            let text = format!(
+                " API {}/{}, holding requests for {}s ",
+                quota.remaining,
+                quota.limit,
+                reset.as_secs()
+            );
+            (text, theme.warning)
+        } else {
+            (format!(" API {}/{} ", quota.remaining, quota.limit), theme.muted)
+        };
+        header_block = header_block
+            .title_top(Line::from(Span::styled(text, Style::default().fg(color))).right_aligned());
+    }
+    // Alerts and incidents new since the last session, until the popup is opened
+    let unread = app.notifications().unread;
+    let bell = if unread > 0 {
+        Span::styled(
+            format!(" 🔔 {} ", unread),
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Span::styled(" 🔔 ", Style::default().fg(theme.muted))
+    };
+    header_block = header_block.title_top(Line::from(bell).right_aligned());
+    if app.read_only {
+        let style = Style::default()
+            .fg(theme.warning)
+            .add_modifier(Modifier::BOLD | Modifier::REVERSED);
+        header_block = header_block.title_top(Line::from(Span::styled(" READ-ONLY ", style)));
+    }
     frame.render_widget(
-        Paragraph::new(status_text).block(Block::default().borders(Borders::ALL).title("Status")),
+        Paragraph::new(vec![
+            Line::from(crumb_spans),
+            Line::from(Span::styled(hint_line, Style::default().fg(theme.muted))),
+        ])
+        .block(header_block),
         layout[0],
     );
 
     // Main Content
-    let main_block = Block::default().borders(Borders::ALL).title("Sites");
+    let site_list = app.site_list();
+    let mut title = match site_list.sites_cached_at {
+        Some(ts) if site_list.is_loading => {
+            format!("Sites (cached {}, {})", format_age(ts), loading_text("refreshing..."))
+        }
+        Some(ts) => format!("Sites (cached {})", format_age(ts)),
+        None => "Sites".to_string(),
+    };
+    if site_list.is_filtering || !site_list.filter_query.is_empty() {
+        title.push_str(&format!(" [Filter: {}", site_list.filter_query));
+        if site_list.is_filtering {
+            title.push('_');
+        }
+        title.push(']');
+    }
+    if site_list.hide_empty {
+        title.push_str(" [hiding empty]");
+    }
+    if site_list.show_merged {
+        title.push_str(" [all accounts]");
+    }
+    if site_list.is_filter_active() {
+        let total = if site_list.show_merged {
+            site_list.sites.len()
+        } else {
+            site_list.own_sites().count()
+        };
+        title.push_str(&format!(" {}/{}", site_list.visible_count(), total));
+    }
+    let main_block = Block::default().borders(Borders::ALL).title(title);
 
-    if let Some(err) = &app.error {
+    // Error banner for the current view; the content below stays usable
+    let mut content_area = layout[1];
+    if let Some(entry) = app.active_error() {
+        let banner_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(content_area);
         frame.render_widget(
-            Paragraph::new(format!("Error: {}", err))
-                .style(Style::default().fg(Color::Red))
-                .block(main_block),
-            layout[1],
+            Paragraph::new(format!(
+                " {}: {} ('E': error history)",
+                entry.source, entry.message
+            ))
+            .style(Style::default().fg(theme.error).add_modifier(Modifier::BOLD)),
+            banner_layout[0],
         );
-    } else if app.is_loading {
-        frame.render_widget(
-            Paragraph::new("Loading...")
-                .style(Style::default().fg(Color::Yellow))
-                .block(main_block),
-            layout[1],
+        content_area = banner_layout[1];
+    }
+
+    // Cached sites stay visible while the refresh runs; the other views
+    // show their own panels loading
+    let sites_loading = app.site_list().is_loading && app.site_list().sites_cached_at.is_none();
+    let view_name = crumbs.last().map_or("This view", String::as_str);
+    if layout::render_too_small(frame, content_area, app.current_view, view_name) {
+        // The note takes the place of the view
+    } else if app.current_view == CurrentView::List && sites_loading {
+        loading::render_loading_table(
+            frame,
+            content_area,
+            main_block,
+            "Loading sites...",
+            SITE_COLUMNS.len(),
         );
     } else {
         match app.current_view {
-            CurrentView::List => render_site_list(app, frame, layout[1], main_block),
-            CurrentView::Detail => render_site_detail(app, frame, layout[1]),
-            CurrentView::DeviceDetail => render_device_detail(app, frame, layout[1]),
-            CurrentView::ActivityDetail => render_activity_detail(app, frame, layout[1]),
+            CurrentView::List => render_site_list(app, frame, content_area, main_block),
+            CurrentView::Detail => render_site_detail(app, frame, content_area),
+            CurrentView::DeviceDetail => render_device_detail(app, frame, content_area),
+            CurrentView::ActivityDetail => render_activity_detail(app, frame, content_area),
+            CurrentView::Report => render_report(app, frame, content_area),
+            CurrentView::Components => render_components(app, frame, content_area),
+            CurrentView::Integrations => render_integrations(app, frame, content_area),
+            CurrentView::AccountVariables => render_account_variables(app, frame, content_area),
+            CurrentView::Tags => render_tags(app, frame, content_area),
+            CurrentView::Collections => render_collections(app, frame, content_area),
         }
     }
 
-    // Render Input Modal if Editing
-    if app.input_state.mode == InputMode::Editing {
-        render_input_modal(app, frame);
+    // Popups in the order they were opened, so the one taking keys is on top
+    for popup in app.popups().open_popups() {
+        match popup {
+            Popup::JobOutput => render_popup(app, frame),
+            // Drawn by the device detail view it belongs to
+            Popup::DeviceVariables => {}
+            Popup::DeviceSearch => render_device_search_popup(app, frame),
+            Popup::RunComponent => render_run_component_popup(app, frame),
+            Popup::QuickActions => render_quick_action_menu(app, frame),
+            Popup::Reboot => render_reboot_popup(app, frame),
+            Popup::SiteMove => render_site_move_popup(app, frame),
+            Popup::Warranty => render_warranty_popup(app, frame),
+            Popup::DeviceEdit => render_device_edit_popup(app, frame),
+            Popup::Decommission => render_decommission_popup(app, frame),
+            Popup::SiteDeletion => render_site_deletion_popup(app, frame),
+            Popup::NetTools => render_net_tools_popup(app, frame),
+            Popup::ProfileSwitcher => render_profile_switcher_popup(app, frame),
+            Popup::LogViewer => render_log_viewer_popup(app, frame),
+            Popup::ErrorHistory => render_error_history_popup(app, frame),
+            Popup::RecentDevices => render_recent_devices_popup(app, frame),
+            Popup::Scans => render_scans_popup(app, frame),
+            Popup::AddToCollection => render_add_to_collection_popup(app, frame),
+            Popup::Notifications => render_notifications_popup(app, frame),
+            Popup::NotesViewer => render_notes_viewer_popup(app, frame),
+            // Shown once the site picker above it has its target
+            Popup::VariableCopy if app.popups().is_open(Popup::SiteMove) => {}
+            Popup::VariableCopy => render_variable_copy_popup(app, frame),
+            Popup::VariableTemplates => render_variable_templates_popup(app, frame),
+            Popup::SessionRestore => render_session_restore_popup(app, frame),
+            Popup::Confirmation => render_confirmation_popup(app, frame),
+            Popup::Input => render_input_modal(app, frame),
+            Popup::AlertDetail => render_alert_detail_popup(app, frame),
+        }
     }
 
-    // Render Popup
-    render_popup(app, frame);
+    render_toast(app, frame);
+}
 
-    // Render Device Search Popup
-    if app.show_device_search {
-        render_device_search_popup(app, frame);
-    }
+/// Draws the current toast over the bottom right corner, above everything else.
+fn render_toast(app: &App, frame: &mut Frame) {
+    let Some((message, _)) = &app.toast else {
+        return;
+    };
+    let theme = theme::current();
+    let area = frame.area();
+    let width = (message.chars().count() as u16 + 4).min(area.width);
+    let height = 3.min(area.height);
+    let toast_area = Rect::new(
+        area.right().saturating_sub(width + 1),
+        area.bottom().saturating_sub(height + 1),
+        width,
+        height,
+    );
+    frame.render_widget(Clear, toast_area);
+    frame.render_widget(
+        Paragraph::new(message.as_str())
+            .style(Style::default().fg(theme.warning))
+            .block(Block::default().borders(Borders::ALL)),
+        toast_area,
+    );
+}
 
-    // Render Run Component Popup
-    if app.show_run_component {
-        render_run_component_popup(app, frame);
-    }
+/// Where the user is, outermost first: the account, the site list, then each
+/// level of the drill-down. `Back` returns to the second-to-last entry.
+pub fn breadcrumb(app: &App) -> Vec<String> {
+    let mut crumbs = vec![if app.active_profile.is_empty() {
+        "Kyber TUI".to_string()
+    } else {
+        format!("Kyber TUI [{}]", app.active_profile)
+    }];
 
-    // Render Quick Actions Menu
-    if app.show_quick_actions {
-        render_quick_action_menu(app, frame);
-    }
+    let report_crumbs = |crumbs: &mut Vec<String>| {
+        let report = app.report();
+        crumbs.push(format!("{} Report", report.kind.title()));
+        if let Some(uid) = &report.patch_site {
+            let name = report.patch_rows.iter().find(|r| r.site_uid == *uid).map(|r| r.site.clone());
+            crumbs.push(name.unwrap_or_else(|| uid.clone()));
+        }
+    };
+    let site_name = |uid: &str| {
+        app.site_list()
+            .sites
+            .iter()
+            .find(|s| s.uid == uid)
+            .map(|s| s.name.clone())
+    };
 
-    // Render Reboot Popup
-    if app.show_reboot_popup {
-        render_reboot_popup(app, frame);
+    if app.current_view == CurrentView::List {
+        crumbs.push(format!("Sites ({})", app.site_list().total_count));
+        return crumbs;
+    }
+    crumbs.push("Sites".to_string());
+    match app.current_view {
+        CurrentView::List => {}
+        CurrentView::Report => report_crumbs(&mut crumbs),
+        CurrentView::Components => crumbs.push("Components".to_string()),
+        CurrentView::Integrations => crumbs.push("Integrations".to_string()),
+        CurrentView::AccountVariables => crumbs.push("Account Variables".to_string()),
+        CurrentView::Tags => crumbs.push("Tags".to_string()),
+        CurrentView::Collections => crumbs.push("Collections".to_string()),
+        CurrentView::Detail => {
+            let name = app.site_list().selected_site().map(|s| s.name.clone());
+            crumbs.push(name.unwrap_or_else(|| "Site".to_string()));
+        }
+        CurrentView::DeviceDetail | CurrentView::ActivityDetail => {
+            let device = app.device_detail().selected_device.as_ref();
+            match app.device_detail().opened_from {
+                Some(CurrentView::Report) => report_crumbs(&mut crumbs),
+                Some(CurrentView::Tags) => crumbs.push("Tags".to_string()),
+                Some(CurrentView::Collections) => crumbs.push("Collections".to_string()),
+                _ => {
+                    let name = device
+                        .and_then(|d| site_name(&d.site_uid).or_else(|| d.site_name.clone()));
+                    crumbs.push(name.unwrap_or_else(|| "Site".to_string()));
+                }
+            }
+            crumbs.push(device.map_or_else(|| "Device".to_string(), |d| d.hostname.clone()));
+            if app.current_view == CurrentView::ActivityDetail {
+                crumbs.push("Activity".to_string());
+            }
+        }
     }
+    crumbs
+}
 
-    // Render Site Move Popup
-    if app.show_site_move {
-        render_site_move_popup(app, frame);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::datto::sites::SitesApi;
+    use crate::api::mock::MockRmm;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    #[tokio::test]
+    async fn test_site_list_renders_mock_sites() {
+        let mut app = App::default();
+        let sites = MockRmm::default().get_sites(0, 250, None).await.unwrap().sites;
+        app.site_list_mut().set_sites(sites);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|f| render(&mut app, f)).unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for name in ["Acme Corp", "Globex", "Initech"] {
+            assert!(screen.contains(name), "{} missing from site list", name);
+        }
     }
 
-    // Render Warranty Popup
-    if app.show_warranty_popup {
-        render_warranty_popup(app, frame);
+    #[tokio::test]
+    async fn test_breadcrumb_follows_drill_down() {
+        let mut app = App::default();
+        let sites = MockRmm::default().get_sites(0, 250, None).await.unwrap().sites;
+        app.site_list_mut().set_sites(sites);
+        assert_eq!(breadcrumb(&app), vec!["Kyber TUI", "Sites (0)"]);
+
+        let site = app.site_list().selected_site().unwrap().name.clone();
+        app.current_view = CurrentView::Detail;
+        assert_eq!(breadcrumb(&app), vec!["Kyber TUI".to_string(), "Sites".to_string(), site]);
+
+        app.current_view = CurrentView::Report;
+        assert_eq!(breadcrumb(&app), vec!["Kyber TUI", "Sites", "Warranty Report"]);
     }
 }
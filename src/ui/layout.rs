@@ -0,0 +1,206 @@
+use crate::app::CurrentView;
+use crate::ui::theme;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+/// Below this width two-pane views stack their panes instead of putting
+/// them side by side, e.g. on an 80 column terminal.
+pub const NARROW_WIDTH: u16 = 100;
+
+/// Splits a two-pane view: side by side with `first` percent for the first
+/// pane, or stacked (half each) when `area` is narrower than `NARROW_WIDTH`.
+pub fn panes(area: Rect, first: u16) -> [Rect; 2] {
+    if area.width < NARROW_WIDTH {
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(area)
+    } else {
+        Layout::horizontal([Constraint::Percentage(first), Constraint::Percentage(100 - first)])
+            .areas(area)
+    }
+}
+
+/// Border of the pane j/k move in; Tab moves the focus.
+pub fn pane_border(focused: bool) -> Style {
+    if focused {
+        Style::default().fg(theme::current().accent)
+    } else {
+        Style::default()
+    }
+}
+
+/// The smallest content area (width, height) a view is usable in.
+pub fn min_size(view: CurrentView) -> (u16, u16) {
+    match view {
+        CurrentView::List | CurrentView::Integrations | CurrentView::AccountVariables => (40, 8),
+        CurrentView::Detail | CurrentView::DeviceDetail => (60, 20),
+        CurrentView::ActivityDetail
+        | CurrentView::Components
+        | CurrentView::Tags
+        | CurrentView::Collections => (50, 12),
+        CurrentView::Report => (60, 12),
+    }
+}
+
+/// Renders a note in place of a view that doesn't fit `area`.
+///
+/// # Returns
+/// Whether `area` was too small for `view`, i.e. the note was shown.
+pub fn render_too_small(frame: &mut Frame, area: Rect, view: CurrentView, name: &str) -> bool {
+    let (width, height) = min_size(view);
+    if area.width >= width && area.height >= height {
+        return false;
+    }
+    let text = format!(
+        "{} needs at least {}x{}, the terminal has {}x{}. Enlarge the window to see it.",
+        name, width, height, area.width, area.height
+    );
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(theme::current().warning))
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(paragraph, area);
+    true
+}
+
+/// Renders `paragraph` inside `block`, scrolled down `scroll` lines. The
+/// offset is clamped to the content (and written back), and while content is
+/// cut off the block's bottom border says how much is above and below.
+pub fn render_scrolled(
+    frame: &mut Frame,
+    area: Rect,
+    block: Block,
+    paragraph: Paragraph,
+    scroll: &mut u16,
+) {
+    let inner = block.inner(area);
+    let lines = paragraph.line_count(inner.width) as u16;
+    let (offset, above, below) = scroll_window(lines, inner.height, *scroll);
+    *scroll = offset;
+    let mut block = block;
+    if above > 0 || below > 0 {
+        let style = Style::default().fg(theme::current().muted);
+        let text = format!(" ↑{} ↓{} PgUp/PgDn ", above, below);
+        block = block.title_bottom(Line::from(Span::styled(text, style)).right_aligned());
+    }
+    frame.render_widget(paragraph.scroll((offset, 0)).block(block), area);
+}
+
+/// The offset `scroll` clamped to `lines` of content in a `height` high
+/// window, with the lines hidden above and below it.
+fn scroll_window(lines: u16, height: u16, scroll: u16) -> (u16, u16, u16) {
+    let offset = scroll.min(lines.saturating_sub(height));
+    let below = lines.saturating_sub(offset + height);
+    (offset, offset, below)
+}
+
+/// A table column that can be left out when the table is too narrow.
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    width: Constraint,
+    /// Narrowest the column is still readable at
+    min: u16,
+    /// Columns with the highest priority number go first; 0 always stays
+    priority: u8,
+}
+
+impl Column {
+    pub const fn new(width: Constraint, min: u16, priority: u8) -> Self {
+        Self { width, min, priority }
+    }
+}
+
+/// The columns of a table that fit its area, dropping low-priority ones
+/// (rightmost first among equals) until the rest do.
+#[derive(Debug, Clone)]
+pub struct FittedColumns {
+    shown: Vec<usize>,
+    widths: Vec<Constraint>,
+}
+
+impl FittedColumns {
+    pub fn new(area: Rect, columns: &[Column]) -> Self {
+        // Borders and the highlight symbol
+        let available = area.width.saturating_sub(5);
+        let mut shown: Vec<usize> = (0..columns.len()).collect();
+        let needed = |shown: &[usize]| {
+            let spacing = shown.len().saturating_sub(1) as u16;
+            shown.iter().map(|&i| columns[i].min).sum::<u16>() + spacing
+        };
+        while needed(&shown) > available {
+            let Some(drop) = shown
+                .iter()
+                .enumerate()
+                .filter(|&(_, &i)| columns[i].priority > 0)
+                .max_by_key(|&(pos, &i)| (columns[i].priority, pos))
+                .map(|(pos, _)| pos)
+            else {
+                break;
+            };
+            shown.remove(drop);
+        }
+        // Percentages of the full table would leave the dropped columns'
+        // share empty, so they become shares of what is left
+        let dropped = shown.len() < columns.len();
+        let widths = shown
+            .iter()
+            .map(|&i| match columns[i].width {
+                Constraint::Percentage(p) if dropped => Constraint::Fill(p),
+                width => width,
+            })
+            .collect();
+        Self { shown, widths }
+    }
+
+    pub fn widths(&self) -> Vec<Constraint> {
+        self.widths.clone()
+    }
+
+    /// Keeps the items (cells, header labels) of the shown columns.
+    pub fn pick<T>(&self, items: Vec<T>) -> Vec<T> {
+        items
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| self.shown.contains(i))
+            .map(|(_, item)| item)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_narrow_tables_drop_low_priority_columns() {
+        let columns = [
+            Column::new(Constraint::Percentage(40), 20, 0),
+            Column::new(Constraint::Length(10), 10, 2),
+            Column::new(Constraint::Percentage(30), 12, 1),
+            Column::new(Constraint::Percentage(30), 12, 2),
+        ];
+        let wide = FittedColumns::new(Rect::new(0, 0, 80, 10), &columns);
+        assert_eq!(wide.pick(vec!["a", "b", "c", "d"]), ["a", "b", "c", "d"]);
+        assert_eq!(wide.widths()[0], Constraint::Percentage(40));
+
+        // 20 + 10 + 12 + spacing fits 45 columns less borders, the last one doesn't
+        let narrow = FittedColumns::new(Rect::new(0, 0, 50, 10), &columns);
+        assert_eq!(narrow.pick(vec!["a", "b", "c", "d"]), ["a", "b", "c"]);
+        assert_eq!(narrow.widths()[0], Constraint::Fill(40));
+
+        let tiny = FittedColumns::new(Rect::new(0, 0, 10, 10), &columns);
+        assert_eq!(tiny.pick(vec!["a", "b", "c", "d"]), ["a"]);
+
+        assert_eq!(panes(Rect::new(0, 0, 80, 20), 50)[1], Rect::new(0, 10, 80, 10));
+        assert_eq!(panes(Rect::new(0, 0, 120, 20), 50)[1], Rect::new(60, 0, 60, 20));
+    }
+
+    #[test]
+    fn test_scroll_window_stays_on_the_content() {
+        assert_eq!(scroll_window(5, 10, 3), (0, 0, 0));
+        assert_eq!(scroll_window(30, 10, 0), (0, 0, 20));
+        assert_eq!(scroll_window(30, 10, 5), (5, 5, 15));
+        // Past the end, the last page stays in view
+        assert_eq!(scroll_window(30, 10, 99), (20, 20, 0));
+    }
+}
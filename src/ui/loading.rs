@@ -0,0 +1,100 @@
+use crate::common::utils::loading_text;
+use crate::ui::theme;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Paragraph, Row, Table},
+};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Widths of the placeholder bars, cycled through so the rows look like data.
+const BAR_WIDTHS: [usize; 7] = [14, 9, 11, 6, 16, 8, 12];
+
+/// Ticks so far, advanced by `tick` on every `Event::Tick`; the spinners and
+/// skeleton rows are drawn from it.
+static TICKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Set when a frame shows a spinner or skeleton rows, i.e. needs redrawing
+/// on the next tick to move them on.
+static ANIMATING: AtomicBool = AtomicBool::new(false);
+
+/// Advances the animation.
+///
+/// # Returns
+/// Whether the last frame showed any of it, so another frame is needed.
+pub fn tick() -> bool {
+    TICKS.fetch_add(1, Ordering::Relaxed);
+    ANIMATING.swap(false, Ordering::Relaxed)
+}
+
+/// The spinner frame for the current tick.
+pub fn spinner() -> &'static str {
+    ANIMATING.store(true, Ordering::Relaxed);
+    spinner_at(TICKS.load(Ordering::Relaxed))
+}
+
+fn spinner_at(ticks: usize) -> &'static str {
+    SPINNER[ticks % SPINNER.len()]
+}
+
+/// `count` placeholder rows of `columns` bars. A brighter band moves down
+/// the rows with the ticks, so the panel visibly works while it waits.
+pub fn skeleton_rows(columns: usize, count: usize) -> Vec<Row<'static>> {
+    ANIMATING.store(true, Ordering::Relaxed);
+    skeleton_rows_at(columns, count, TICKS.load(Ordering::Relaxed))
+}
+
+fn skeleton_rows_at(columns: usize, count: usize, ticks: usize) -> Vec<Row<'static>> {
+    let theme = theme::current();
+    let band = ticks % count.max(1);
+    (0..count)
+        .map(|row| {
+            let (bar, color) = if row == band { ("▓", theme.muted) } else { ("░", theme.dim) };
+            let cells = (0..columns).map(move |column| {
+                let width = BAR_WIDTHS[(row * 3 + column * 5) % BAR_WIDTHS.len()];
+                bar.repeat(width)
+            });
+            Row::new(cells).style(Style::default().fg(color))
+        })
+        .collect()
+}
+
+/// A table panel that is still loading: `text` (see `loading_text`) on the
+/// first line and skeleton rows below, inside `block`.
+pub fn render_loading_table(
+    frame: &mut Frame,
+    area: Rect,
+    block: Block,
+    text: &str,
+    columns: usize,
+) {
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [label_area, rows_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+    let label =
+        Paragraph::new(loading_text(text)).style(Style::default().fg(theme::current().muted));
+    frame.render_widget(label, label_area);
+    let widths = vec![Constraint::Fill(1); columns];
+    let rows = skeleton_rows(columns, rows_area.height as usize);
+    frame.render_widget(Table::new(rows, widths), rows_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spinner_and_skeleton_follow_ticks() {
+        assert_ne!(spinner_at(0), spinner_at(1));
+        assert_eq!(spinner_at(0), spinner_at(SPINNER.len()));
+
+        let rows = skeleton_rows_at(2, 4, 1);
+        assert_eq!(rows.len(), 4);
+        // The bright band sits on row 1 and has moved on by the next tick
+        assert_ne!(rows[1], skeleton_rows_at(2, 4, 2)[1]);
+        assert_eq!(rows, skeleton_rows_at(2, 4, 5));
+        assert!(skeleton_rows_at(3, 0, 7).is_empty());
+    }
+}
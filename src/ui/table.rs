@@ -0,0 +1,32 @@
+use crate::app::TableView;
+use crate::ui::layout::{Column, FittedColumns};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Row, Table},
+};
+
+/// Renders `view` in `block`: a bold header of `labels` with the sort arrow,
+/// the rows `row(position, item, fit)` builds for the columns that fit, and
+/// the selected row highlighted.
+pub fn render_table_view<'a, T>(
+    frame: &mut Frame,
+    area: Rect,
+    block: Block<'a>,
+    view: &'a mut TableView<T>,
+    columns: &[Column],
+    labels: &[&str],
+    row: impl Fn(usize, &'a T, &FittedColumns) -> Row<'a>,
+) {
+    let fit = FittedColumns::new(area, columns);
+    let header = Row::new(fit.pick(view.sort.header(labels)))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let (items, state) = view.rows_and_state();
+    let rows: Vec<Row> =
+        items.into_iter().enumerate().map(|(i, item)| row(i, item, &fit)).collect();
+    let table = Table::new(rows, fit.widths())
+        .header(header)
+        .block(block)
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(table, area, state);
+}
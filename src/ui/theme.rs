@@ -0,0 +1,141 @@
+use ratatui::style::Color;
+use std::sync::RwLock;
+
+/// Semantic colour palette used by every page and popup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Primary foreground text
+    pub text: Color,
+    /// Secondary text, offline/unknown states
+    pub muted: Color,
+    /// Placeholder and disabled text
+    pub dim: Color,
+    /// Background behind labels drawn on top of charts
+    pub background: Color,
+    /// Popup and modal background
+    pub popup_bg: Color,
+    /// Headers, borders and "in progress" states
+    pub accent: Color,
+    /// Focused inputs and selected rows
+    pub highlight: Color,
+    pub success: Color,
+    pub warning: Color,
+    /// Between warning and error (high severity, reboot required, ...)
+    pub caution: Color,
+    pub error: Color,
+    pub info: Color,
+    pub special: Color,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        name: "dark",
+        text: Color::White,
+        muted: Color::Gray,
+        dim: Color::DarkGray,
+        background: Color::Black,
+        popup_bg: Color::DarkGray,
+        accent: Color::Cyan,
+        highlight: Color::Yellow,
+        success: Color::Green,
+        warning: Color::Yellow,
+        caution: Color::Rgb(255, 165, 0),
+        error: Color::Red,
+        info: Color::Blue,
+        special: Color::Magenta,
+    };
+
+    pub const LIGHT: Theme = Theme {
+        name: "light",
+        text: Color::Black,
+        muted: Color::Rgb(90, 90, 90),
+        dim: Color::Rgb(150, 150, 150),
+        background: Color::White,
+        popup_bg: Color::Rgb(225, 225, 225),
+        accent: Color::Rgb(0, 120, 140),
+        highlight: Color::Rgb(140, 0, 140),
+        success: Color::Rgb(0, 130, 0),
+        warning: Color::Rgb(160, 120, 0),
+        caution: Color::Rgb(200, 90, 0),
+        error: Color::Rgb(190, 0, 0),
+        info: Color::Rgb(0, 60, 190),
+        special: Color::Rgb(120, 60, 160),
+    };
+
+    pub const SOLARIZED: Theme = Theme {
+        name: "solarized",
+        text: Color::Rgb(147, 161, 161),
+        muted: Color::Rgb(131, 148, 150),
+        dim: Color::Rgb(88, 110, 117),
+        background: Color::Rgb(0, 43, 54),
+        popup_bg: Color::Rgb(7, 54, 66),
+        accent: Color::Rgb(42, 161, 152),
+        highlight: Color::Rgb(181, 137, 0),
+        success: Color::Rgb(133, 153, 0),
+        warning: Color::Rgb(181, 137, 0),
+        caution: Color::Rgb(203, 75, 22),
+        error: Color::Rgb(220, 50, 47),
+        info: Color::Rgb(38, 139, 210),
+        special: Color::Rgb(211, 54, 130),
+    };
+
+    pub const HIGH_CONTRAST: Theme = Theme {
+        name: "high-contrast",
+        text: Color::White,
+        muted: Color::White,
+        dim: Color::Gray,
+        background: Color::Black,
+        popup_bg: Color::Black,
+        accent: Color::LightCyan,
+        highlight: Color::LightYellow,
+        success: Color::LightGreen,
+        warning: Color::LightYellow,
+        caution: Color::Rgb(255, 175, 0),
+        error: Color::LightRed,
+        info: Color::LightBlue,
+        special: Color::LightMagenta,
+    };
+
+    pub const ALL: [Theme; 4] = [
+        Theme::DARK,
+        Theme::LIGHT,
+        Theme::SOLARIZED,
+        Theme::HIGH_CONTRAST,
+    ];
+
+    pub fn by_name(name: &str) -> Option<Theme> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::DARK);
+
+/// The active theme. Render functions read this once per call.
+pub fn current() -> Theme {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set(theme: Theme) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = theme;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(Theme::by_name("Solarized"), Some(Theme::SOLARIZED));
+        assert_eq!(Theme::by_name("high-contrast"), Some(Theme::HIGH_CONTRAST));
+        assert_eq!(Theme::by_name("neon"), None);
+    }
+}
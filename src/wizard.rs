@@ -0,0 +1,409 @@
+use crate::api::datto::DattoClient;
+use crate::api::datto_av::{AvApi, DattoAvClient};
+use crate::api::defender::{DefenderApi, DefenderClient};
+use crate::api::rocket_cyber::RocketCyberClient;
+use crate::api::rocket_cyber::agents::AgentsApi;
+use crate::api::sentinelone::{SentinelOneApi, SentinelOneClient};
+use crate::api::sophos::SophosClient;
+use crate::config::{
+    Config, ConfigFile, DattoAvConfig, DattoAvSection, DattoConfig, DattoPlatform, DattoSection,
+    DefenderConfig, DefenderSection, HttpSettings, ProfileFile, RocketCyberConfig,
+    RocketCyberSection, SentinelOneConfig, SentinelOneSection, SophosConfig, SophosSection,
+    DEFAULT_ACTIVITY_PAGE_SIZE, DEFAULT_PROFILE,
+};
+use crate::event::{Event, EventHandler};
+use crate::pages::setup_wizard::render_setup_wizard;
+use crate::tui::Tui;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Hostname used for the lookup-based auth checks; any auth failure surfaces before the empty result.
+pub const PROBE_HOSTNAME: &str = "kyber-tui-setup-probe";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Provider {
+    Datto,
+    RocketCyber,
+    Sophos,
+    DattoAv,
+    Defender,
+    SentinelOne,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 6] = [
+        Provider::Datto,
+        Provider::RocketCyber,
+        Provider::Sophos,
+        Provider::DattoAv,
+        Provider::Defender,
+        Provider::SentinelOne,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Provider::Datto => "Datto RMM",
+            Provider::RocketCyber => "RocketCyber",
+            Provider::Sophos => "Sophos Central",
+            Provider::DattoAv => "Datto AV",
+            Provider::Defender => "Microsoft Defender",
+            Provider::SentinelOne => "SentinelOne",
+        }
+    }
+
+    /// Field labels and whether the value should be masked when rendered.
+    pub fn fields(&self) -> &'static [(&'static str, bool)] {
+        match self {
+            Provider::Datto => &[
+                ("Platform or API URL", false),
+                ("API Key", false),
+                ("Secret Key", true),
+            ],
+            Provider::RocketCyber => &[("API URL", false), ("API Key", true)],
+            Provider::Sophos => &[("Partner ID", false), ("Client ID", false), ("Secret", true)],
+            Provider::DattoAv => &[("URL", false), ("Secret", true)],
+            Provider::Defender => &[
+                ("Tenant ID", false),
+                ("Client ID", false),
+                ("Client Secret", true),
+            ],
+            Provider::SentinelOne => &[("Console URL", false), ("API Token", true)],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValidationStatus {
+    Untested,
+    Testing,
+    Passed,
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct ProviderForm {
+    pub provider: Provider,
+    pub values: Vec<String>,
+    pub status: ValidationStatus,
+}
+
+#[derive(Debug)]
+pub struct SetupWizard {
+    pub forms: Vec<ProviderForm>,
+    /// Index into `forms`; `forms.len()` is the final save step.
+    pub step: usize,
+    pub focus: usize,
+    pub profile_name: String,
+    pub error: Option<String>,
+    pub should_quit: bool,
+    pub config: Option<Config>,
+}
+
+impl Default for SetupWizard {
+    fn default() -> Self {
+        Self {
+            forms: Provider::ALL
+                .iter()
+                .map(|p| ProviderForm {
+                    provider: *p,
+                    values: vec![String::new(); p.fields().len()],
+                    status: ValidationStatus::Untested,
+                })
+                .collect(),
+            step: 0,
+            focus: 0,
+            profile_name: DEFAULT_PROFILE.to_string(),
+            error: None,
+            should_quit: false,
+            config: None,
+        }
+    }
+}
+
+/// Runs the first-run wizard. Returns the saved config, or `None` if the user quit.
+pub async fn run(tui: &mut Tui, events: &mut EventHandler) -> Result<Option<Config>> {
+    let mut wizard = SetupWizard::default();
+
+    while !wizard.should_quit && wizard.config.is_none() {
+        tui.draw(|f| render_setup_wizard(&wizard, f))?;
+
+        match events.next().await? {
+            Event::Key(key) => wizard.handle_key_event(key, events.sender()),
+            Event::SetupValidated(step, result) => wizard.handle_validation(step, result),
+            _ => {}
+        }
+    }
+
+    Ok(wizard.config)
+}
+
+impl SetupWizard {
+    pub fn is_final_step(&self) -> bool {
+        self.step >= self.forms.len()
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.should_quit = true;
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                if self.step == 0 {
+                    self.should_quit = true;
+                } else {
+                    self.step -= 1;
+                    self.focus = 0;
+                    self.error = None;
+                }
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                let count = self.field_count();
+                self.focus = (self.focus + 1) % count;
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                let count = self.field_count();
+                self.focus = (self.focus + count - 1) % count;
+            }
+            KeyCode::Enter => {
+                if self.is_final_step() {
+                    self.save();
+                } else {
+                    self.submit_step(tx);
+                }
+            }
+            KeyCode::Backspace => {
+                self.current_value_mut().pop();
+                self.mark_dirty();
+            }
+            KeyCode::Char(c) => {
+                self.current_value_mut().push(c);
+                self.mark_dirty();
+            }
+            _ => {}
+        }
+    }
+
+    fn field_count(&self) -> usize {
+        self.forms
+            .get(self.step)
+            .map(|f| f.values.len())
+            .unwrap_or(1)
+    }
+
+    fn current_value_mut(&mut self) -> &mut String {
+        match self.forms.get_mut(self.step) {
+            Some(form) => &mut form.values[self.focus],
+            None => &mut self.profile_name,
+        }
+    }
+
+    fn mark_dirty(&mut self) {
+        if let Some(form) = self.forms.get_mut(self.step) {
+            form.status = ValidationStatus::Untested;
+        }
+        self.error = None;
+    }
+
+    /// Tests the current provider's credentials. A second Enter after a failed test
+    /// (without editing) keeps the values anyway, e.g. for integrations not in use yet.
+    fn submit_step(&mut self, tx: tokio::sync::mpsc::UnboundedSender<Event>) {
+        let step = self.step;
+        let form = &mut self.forms[step];
+
+        match form.status {
+            ValidationStatus::Testing => return,
+            ValidationStatus::Passed | ValidationStatus::Failed(_) => {
+                self.step += 1;
+                self.focus = 0;
+                return;
+            }
+            ValidationStatus::Untested => {}
+        }
+
+        if form.values.iter().any(|v| v.trim().is_empty()) {
+            self.error = Some("All fields are required".to_string());
+            return;
+        }
+
+        form.status = ValidationStatus::Testing;
+        self.error = None;
+        let provider = form.provider;
+        let values: Vec<String> = form.values.iter().map(|v| v.trim().to_string()).collect();
+        tokio::spawn(async move {
+            let result = validate(provider, values).await.map_err(|e| e.to_string());
+            let _ = tx.send(Event::SetupValidated(step, result));
+        });
+    }
+
+    fn handle_validation(&mut self, step: usize, result: Result<(), String>) {
+        if let Some(form) = self.forms.get_mut(step) {
+            if form.status != ValidationStatus::Testing {
+                // Values were edited while the test was running
+                return;
+            }
+            match result {
+                Ok(()) => {
+                    form.status = ValidationStatus::Passed;
+                    if self.step == step {
+                        self.step += 1;
+                        self.focus = 0;
+                    }
+                }
+                Err(e) => form.status = ValidationStatus::Failed(e),
+            }
+        }
+    }
+
+    fn profile_file(&self) -> ProfileFile {
+        let value = |p: Provider, i: usize| {
+            self.forms
+                .iter()
+                .find(|f| f.provider == p)
+                .map(|f| f.values[i].trim().to_string())
+        };
+        // A platform name is saved as such, anything else as a raw URL
+        let datto_url = value(Provider::Datto, 0);
+        let platform = datto_url.as_deref().and_then(DattoPlatform::parse);
+        ProfileFile {
+            datto: DattoSection {
+                platform,
+                api_url: datto_url.filter(|_| platform.is_none()),
+                api_key: value(Provider::Datto, 1),
+                secret_key: value(Provider::Datto, 2),
+                ..Default::default()
+            },
+            rocket: RocketCyberSection {
+                api_url: value(Provider::RocketCyber, 0),
+                api_key: value(Provider::RocketCyber, 1),
+                ..Default::default()
+            },
+            sophos: SophosSection {
+                partner_id: value(Provider::Sophos, 0),
+                client_id: value(Provider::Sophos, 1),
+                secret: value(Provider::Sophos, 2),
+                ..Default::default()
+            },
+            datto_av: DattoAvSection {
+                url: value(Provider::DattoAv, 0),
+                secret: value(Provider::DattoAv, 1),
+                ..Default::default()
+            },
+            defender: DefenderSection {
+                tenant_id: value(Provider::Defender, 0),
+                client_id: value(Provider::Defender, 1),
+                client_secret: value(Provider::Defender, 2),
+                ..Default::default()
+            },
+            sentinelone: SentinelOneSection {
+                url: value(Provider::SentinelOne, 0),
+                api_token: value(Provider::SentinelOne, 1),
+                ..Default::default()
+            },
+            udf_labels: Default::default(),
+            tags_udf: None,
+        }
+    }
+
+    fn save(&mut self) {
+        let name = self.profile_name.trim().to_string();
+        if name.is_empty() {
+            self.error = Some("Profile name is required".to_string());
+            return;
+        }
+
+        let result = (|| -> Result<Config> {
+            let mut file = ConfigFile::load()?.unwrap_or_default();
+            let mut profile = self.profile_file();
+            // The wizard only edits credentials; keep labels and HTTP settings set by hand
+            if let Some(existing) = file.profiles.remove(&name) {
+                profile.udf_labels = existing.udf_labels;
+                profile.tags_udf = existing.tags_udf;
+                profile.datto.activity_page_size = existing.datto.activity_page_size;
+                profile.datto.http = existing.datto.http;
+                profile.rocket.http = existing.rocket.http;
+                profile.sophos.http = existing.sophos.http;
+                profile.datto_av.http = existing.datto_av.http;
+                profile.defender.http = existing.defender.http;
+                profile.sentinelone.agent_uuid_udf = existing.sentinelone.agent_uuid_udf;
+                profile.sentinelone.http = existing.sentinelone.http;
+            }
+            file.profiles.insert(name.clone(), profile);
+            if file.default_profile.is_none() {
+                file.default_profile = Some(name.clone());
+            }
+            file.save()?;
+            Config::load(Some(&name))
+        })();
+
+        match result {
+            Ok(config) => self.config = Some(config),
+            Err(e) => self.error = Some(format!("{:#}", e)),
+        }
+    }
+}
+
+/// Live auth test for a single provider.
+async fn validate(provider: Provider, values: Vec<String>) -> Result<()> {
+    match provider {
+        Provider::Datto => {
+            let mut client = DattoClient::new(DattoConfig {
+                api_url: DattoPlatform::parse(&values[0])
+                    .map(DattoPlatform::api_url)
+                    .unwrap_or_else(|| values[0].clone()),
+                api_key: values[1].clone(),
+                secret_key: values[2].clone(),
+                http: HttpSettings::default(),
+                activity_page_size: DEFAULT_ACTIVITY_PAGE_SIZE,
+            })?;
+            client.ping().await?;
+            client.authenticate().await
+        }
+        Provider::RocketCyber => {
+            let client = RocketCyberClient::new(RocketCyberConfig {
+                api_url: values[0].clone(),
+                api_key: values[1].clone(),
+                http: HttpSettings::default(),
+            })?;
+            client.get_agents(PROBE_HOSTNAME).await.map(|_| ())
+        }
+        Provider::Sophos => {
+            let mut client = SophosClient::new(SophosConfig {
+                partner_id: values[0].clone(),
+                client_id: values[1].clone(),
+                secret: values[2].clone(),
+                http: HttpSettings::default(),
+            })?;
+            client.authenticate().await
+        }
+        Provider::DattoAv => {
+            let client = DattoAvClient::new(DattoAvConfig {
+                url: values[0].clone(),
+                secret: values[1].clone(),
+                http: HttpSettings::default(),
+            })?;
+            client.get_agent_details(PROBE_HOSTNAME).await.map(|_| ())
+        }
+        Provider::Defender => {
+            let mut client = DefenderClient::new(DefenderConfig {
+                tenant_id: values[0].clone(),
+                client_id: values[1].clone(),
+                client_secret: values[2].clone(),
+                http: HttpSettings::default(),
+            })?;
+            client.authenticate().await?;
+            // A token is issued without any API permissions granted
+            client.get_machines(PROBE_HOSTNAME).await.map(|_| ())
+        }
+        Provider::SentinelOne => {
+            let client = SentinelOneClient::new(SentinelOneConfig {
+                url: values[0].clone(),
+                api_token: values[1].clone(),
+                agent_uuid_udf: None,
+                http: HttpSettings::default(),
+            })?;
+            client.get_agents(PROBE_HOSTNAME).await.map(|_| ())
+        }
+    }
+}